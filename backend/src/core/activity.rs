@@ -0,0 +1,115 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::core::models::{MediaItem, MediaItemType, ReadStatus, WatchStatus};
+
+/// Only progress made within this window counts towards "currently into".
+pub const ACTIVITY_WINDOW_DAYS: i64 = 30;
+
+/// Progress loses half of its weight every this many days.
+const HALF_LIFE_DAYS: f32 = 7.0;
+
+/// A single recorded change of an item's progress counter.
+#[derive(Debug, Clone)]
+pub struct ProgressEvent {
+    pub item_id: Uuid,
+    pub progress: u32,
+    pub delta: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// An item ranked by recent engagement.
+pub struct Engagement<'a> {
+    pub item: &'a MediaItem,
+    pub velocity: f32,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// Ranks items by recent progress velocity: every forward step is weighted
+/// by the share of the item it covered and by how recently it happened.
+/// Dropped items and rewinds (negative deltas) are ignored.
+pub fn currently_into<'a>(
+    items: &'a [MediaItem],
+    events: &[ProgressEvent],
+    now: DateTime<Utc>,
+    limit: usize,
+) -> Vec<Engagement<'a>> {
+    let cutoff = now - Duration::days(ACTIVITY_WINDOW_DAYS);
+    let by_id: HashMap<Uuid, &MediaItem> = items.iter().map(|i| (i.id, i)).collect();
+    let mut scores: HashMap<Uuid, (f32, DateTime<Utc>)> = HashMap::new();
+
+    for ev in events {
+        if ev.delta <= 0 || ev.recorded_at < cutoff {
+            continue;
+        }
+        let Some(item) = by_id.get(&ev.item_id) else { continue };
+        let total = match &item.media_type {
+            MediaItemType::Series(_, WatchStatus::Dropped)
+            | MediaItemType::Readable(_, _, ReadStatus::Dropped)
+            | MediaItemType::Movie(_) => continue,
+            MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => p.total,
+        };
+
+        let base = total.filter(|t| *t > 0).unwrap_or(ev.progress.max(1));
+        let share = ev.delta as f32 / base as f32;
+        let age_days = (now - ev.recorded_at).num_seconds().max(0) as f32 / 86_400.0;
+        let weight = 0.5_f32.powf(age_days / HALF_LIFE_DAYS);
+
+        let entry = scores.entry(ev.item_id).or_insert((0.0, ev.recorded_at));
+        entry.0 += share * weight;
+        entry.1 = entry.1.max(ev.recorded_at);
+    }
+
+    let mut ranked: Vec<Engagement> = scores
+        .into_iter()
+        .filter_map(|(id, (velocity, last_activity))| {
+            by_id.get(&id).map(|item| Engagement { item, velocity, last_activity })
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.velocity.total_cmp(&a.velocity));
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Progress;
+
+    fn series(title: &str, total: u32, status: WatchStatus) -> MediaItem {
+        MediaItem::new(
+            title.into(),
+            MediaItemType::Series(Progress { current: 0, total: Some(total) }, status),
+        )
+    }
+
+    fn event(item: &MediaItem, delta: i64, days_ago: i64, now: DateTime<Utc>) -> ProgressEvent {
+        ProgressEvent {
+            item_id: item.id,
+            progress: delta.max(0) as u32,
+            delta,
+            recorded_at: now - Duration::days(days_ago),
+        }
+    }
+
+    #[test]
+    fn test_recent_progress_ranks_first() {
+        let now = Utc::now();
+        let items = vec![
+            series("Old binge", 12, WatchStatus::Watching),
+            series("This week", 12, WatchStatus::Watching),
+            series("Dropped", 12, WatchStatus::Dropped),
+        ];
+        let events = vec![
+            event(&items[0], 6, 20, now),
+            event(&items[1], 3, 1, now),
+            event(&items[2], 12, 0, now),
+            event(&items[1], -3, 0, now),
+        ];
+
+        let ranked = currently_into(&items, &events, now, 3);
+        let titles: Vec<&str> = ranked.iter().map(|e| e.item.title.as_str()).collect();
+        assert_eq!(titles, vec!["This week", "Old binge"]);
+    }
+}
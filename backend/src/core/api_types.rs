@@ -1,58 +1,164 @@
+use async_graphql::{InputObject, SimpleObject};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::core::activity::{Engagement, ProgressEvent};
+use crate::core::availability;
+use crate::core::custom_fields;
+use crate::core::focus::{self, FocusPin, FocusSummary};
+use crate::core::franchise::{self, FranchiseSummary};
+use crate::core::goals::{Goal, GoalProgress, Streak};
+use crate::core::history::HistoryEntry;
+use crate::core::jobs::{Job, RollbackConflict};
+use crate::core::ranking::Rating;
+use crate::core::scheduler::JobStatus;
+use crate::core::recommend::{Discovery, Recommendation, Signal};
+use crate::core::outbox::QueuedWrite;
+use crate::core::profiles::SharedScore;
+use crate::core::refresh::ItemChanges;
+use crate::core::schedule::{self, Notification};
 use crate::core::models::{
-    MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind, WatchStatus,
+    ContentRating, EpisodeRecord, MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind,
+    ScoreScale, TitleLanguage, WatchStatus,
 };
+use crate::core::search::{MediaDetails, NextAiring, ProviderDiagnostic, Relation, UnavailableProvider};
+use crate::core::stats::{ScoreSummary, TimeSpent};
+use crate::core::suggest;
+use crate::core::telemetry::TelemetryPayload;
+use crate::core::trakt::TraktAuth;
+use crate::core::write_through::{AniListAuth, QueuedUpdate};
+use crate::core::validation::FieldError;
+use crate::core::webhooks::Webhook;
 
 /// Flat JSON representation for the REST API.
 /// This is what the frontend sends and receives.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, SimpleObject, InputObject)]
+#[graphql(complex, name = "Item", input_name = "ItemInput")]
 pub struct ApiMediaItem {
     pub id: String,
     pub title: String,
     pub media_type: String,
+    /// Left out or empty when creating: the status of the media type in
+    /// `default_statuses`.
+    #[serde(default)]
+    #[graphql(default)]
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub global_score: Option<f32>,
+    /// Episodes or chapters; minutes listened for an audiobook.
     pub progress: u32,
+    /// For an audiobook, its running time in minutes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_episodes: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub poster_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::core::models::external_id_compat"
+    )]
     pub external_id: Option<String>,
     pub tags: Vec<String>,
     pub favorite: bool,
+    #[serde(default)]
+    #[graphql(default)]
+    pub repeat_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub notes_spoiler: bool,
+    #[serde(default)]
+    #[graphql(default)]
+    pub alt_titles: Vec<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub external_refs: Vec<String>,
+    /// RFC 3339 timestamp of a planned session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<String>,
+    /// Synopsis; accepted as `description` too.
+    #[serde(default, alias = "description", skip_serializing_if = "Option::is_none")]
+    pub overview: Option<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub genres: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub season_count: Option<u32>,
+    /// Name of the franchise the item belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub franchise: Option<String>,
+    /// User-defined fields, e.g. `{"owned on Blu-ray": "yes"}`.  An empty
+    /// value removes a field.
+    #[serde(default)]
+    #[graphql(default)]
+    pub custom_fields: BTreeMap<String, String>,
+    /// Volumes read, for readables that track volumes next to chapters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_progress: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_volumes: Option<u32>,
+    /// Whether progress is shown in volumes.  Follows whichever of
+    /// `progress` and `volume_progress` an update changes.
+    #[serde(default)]
+    #[graphql(default)]
+    pub prefer_volumes: bool,
+    /// Author of a readable or studio of an anime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// `safe`, `suggestive`, `erotica` or `pornographic`, as the provider
+    /// rates the title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_rating: Option<String>,
+    /// Left out of `GET /api/items` and `/api/search` unless
+    /// `include_hidden` is set.
+    #[serde(default)]
+    #[graphql(default)]
+    pub hidden: bool,
+    /// Newest chapter out, for readables being read.  Read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[graphql(skip_input)]
+    pub latest_available: Option<u32>,
+    /// Chapters out but not read yet, when there are any.  Read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[graphql(skip_input)]
+    pub chapters_behind: Option<u32>,
+    /// Planned, and Sonarr or Radarr has it downloaded.  Read-only.
+    #[serde(default)]
+    #[graphql(skip_input)]
+    pub ready_to_watch: bool,
+    /// RFC 3339 timestamp of when the item was added.  Read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[graphql(skip_input)]
+    pub created_at: Option<String>,
+    /// RFC 3339 timestamp of the item's last change.  Read-only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[graphql(skip_input)]
+    pub updated_at: Option<String>,
 }
 
 // ── MediaItem → ApiMediaItem ─────────────────────────────────
 
 impl From<&MediaItem> for ApiMediaItem {
     fn from(item: &MediaItem) -> Self {
-        let (media_type, status, progress, total) = match &item.media_type {
-            MediaItemType::Movie(ws) => ("movie", watch_status_str(ws), 0, None),
-            MediaItemType::Series(p, ws) => {
-                let mt = match item.source.as_deref() {
-                    Some("anilist") => "anime",
-                    _ => "series",
-                };
-                (mt, watch_status_str(ws), p.current, p.total)
-            }
-            MediaItemType::Readable(kind, p, rs) => {
-                let mt = readable_kind_str(kind);
-                (mt, read_status_str(rs), p.current, p.total)
-            }
+        let (status, progress, total) = match &item.media_type {
+            MediaItemType::Movie(ws) => (watch_status_str(ws), 0, None),
+            MediaItemType::Series(p, ws) => (watch_status_str(ws), p.current, p.total),
+            MediaItemType::Readable(_, p, rs) => (read_status_str(rs), p.current, p.total),
         };
 
         ApiMediaItem {
             id: item.id.to_string(),
             title: item.title.clone(),
-            media_type: media_type.to_string(),
+            media_type: media_type_of(item).to_string(),
             status: status.to_string(),
             score: item.get_score_display(),
             global_score: item.get_global_score_display(),
@@ -60,9 +166,42 @@ impl From<&MediaItem> for ApiMediaItem {
             total_episodes: total,
             poster_url: item.poster_url.clone(),
             source: item.source.clone(),
-            external_id: item.external_id.map(|e| e.to_string()),
+            external_id: item.external_id.clone(),
             tags: item.tags.iter().cloned().collect(),
-            favorite: item.tags.contains("favorite"),
+            favorite: item.favorite,
+            repeat_count: item.repeat_count,
+            notes: item.notes.clone(),
+            notes_spoiler: item.notes_spoiler,
+            alt_titles: item.alt_titles.clone(),
+            external_refs: {
+                let mut refs: Vec<String> = item.external_refs.iter().cloned().collect();
+                refs.sort();
+                refs
+            },
+            scheduled_for: item
+                .scheduled_for
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            overview: item.overview.clone(),
+            genres: item.genres.clone(),
+            runtime_minutes: item.runtime_minutes,
+            season_count: item.season_count,
+            franchise: item.franchise.clone(),
+            custom_fields: item.custom_fields.clone(),
+            volume_progress: item.volumes.as_ref().map(|v| v.current),
+            total_volumes: item.volumes.as_ref().and_then(|v| v.total),
+            prefer_volumes: item.prefer_volumes,
+            creator: item.creator.clone(),
+            content_rating: item.content_rating.map(|r| r.as_str().to_string()),
+            hidden: item.hidden,
+            latest_available: item.latest_available,
+            chapters_behind: schedule::chapters_behind(item),
+            ready_to_watch: availability::ready_to_watch(item),
+            created_at: item
+                .created_at
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            updated_at: item
+                .updated_at
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
         }
     }
 }
@@ -117,14 +256,40 @@ impl ApiMediaItem {
                 progress,
                 parse_read_status(&self.status),
             ),
+            "audiobook" => MediaItemType::Readable(
+                ReadableKind::Audiobook,
+                progress,
+                parse_read_status(&self.status),
+            ),
             other => return Err(format!("Unknown media_type: {other}")),
         };
 
+        let volumes = match (self.volume_progress, self.total_volumes) {
+            (None, None) => None,
+            (current, total) => Some(Progress { current: current.unwrap_or(0), total }),
+        };
+        if volumes.is_some() && !matches!(media_type, MediaItemType::Readable(..)) {
+            return Err("Only readables track volumes".into());
+        }
+
+        let scheduled_for = match self.scheduled_for.as_deref().filter(|s| !s.is_empty()) {
+            Some(s) => Some(
+                chrono::DateTime::parse_from_rfc3339(s)
+                    .map_err(|e| format!("Invalid scheduled_for: {e}"))?
+                    .with_timezone(&chrono::Utc),
+            ),
+            None => None,
+        };
+
+        let content_rating = match self.content_rating.as_deref().filter(|r| !r.is_empty()) {
+            Some(r) => Some(ContentRating::parse(r).ok_or_else(|| format!("Unknown content_rating: {r}"))?),
+            None => None,
+        };
+
         let mut tags: std::collections::HashSet<String> =
             self.tags.into_iter().collect();
-        if self.favorite {
-            tags.insert("favorite".to_string());
-        }
+        // Older clients mark favorites with a "favorite" tag.
+        let favorite = tags.remove("favorite") || self.favorite;
 
         let mut item = MediaItem {
             id,
@@ -132,10 +297,33 @@ impl ApiMediaItem {
             media_type,
             score: None,
             global_score: None,
-            external_id: self.external_id.and_then(|e| e.parse().ok()),
+            external_id: self.external_id,
             poster_url: self.poster_url,
             source: self.source,
             tags,
+            repeat_count: self.repeat_count,
+            notes: self.notes.filter(|n| !n.trim().is_empty()),
+            notes_spoiler: self.notes_spoiler,
+            alt_titles: self.alt_titles,
+            external_refs: self.external_refs.into_iter().collect(),
+            scheduled_for,
+            overview: self.overview.filter(|o| !o.trim().is_empty()),
+            genres: self.genres,
+            runtime_minutes: self.runtime_minutes,
+            season_count: self.season_count,
+            franchise: franchise::normalize_name(self.franchise.as_deref().unwrap_or(""))?,
+            custom_fields: custom_fields::normalize(self.custom_fields)?,
+            prefer_volumes: self.prefer_volumes && volumes.is_some(),
+            volumes,
+            creator: self.creator.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+            content_rating,
+            favorite,
+            hidden: self.hidden,
+            // Set by storage.
+            latest_available: None,
+            downloaded: false,
+            created_at: None,
+            updated_at: None,
         };
 
         if let Some(s) = self.score {
@@ -149,22 +337,541 @@ impl ApiMediaItem {
     }
 }
 
+// ── Score scales ─────────────────────────────────────────────
+
+impl ApiMediaItem {
+    /// Shows `score` and `global_score` in `scale` instead of 0-10.
+    pub fn in_scale(mut self, scale: ScoreScale) -> Self {
+        let convert = |s: f32| scale.scaled((s * 10.0).round() as u8);
+        self.score = self.score.map(convert);
+        self.global_score = self.global_score.map(convert);
+        self
+    }
+
+    /// Reads `score` and `global_score` as entered in `scale`, back in
+    /// 0-10.  A score still showing what `existing` has keeps its stored
+    /// value, so saving an item in a coarse scale does not round it.
+    pub fn out_of_scale(mut self, scale: ScoreScale, existing: Option<&MediaItem>) -> Result<Self, String> {
+        let convert = |entered: Option<f32>, stored: Option<u8>| -> Result<Option<f32>, String> {
+            let Some(entered) = entered else { return Ok(None) };
+            let stored = match stored {
+                Some(s) if scale.scaled(s) == entered => s,
+                _ => scale.to_stored(entered)?,
+            };
+            Ok(Some(stored as f32 / 10.0))
+        };
+        self.score = convert(self.score, existing.and_then(|i| i.score))?;
+        self.global_score = convert(self.global_score, existing.and_then(|i| i.global_score))?;
+        Ok(self)
+    }
+}
+
+// ── Sparse fieldsets ─────────────────────────────────────────
+
+/// The fields of an item as the API returns them, for `?fields=`.
+pub fn item_field_names() -> Vec<String> {
+    match <ApiMediaItem as utoipa::PartialSchema>::schema() {
+        utoipa::openapi::RefOr::T(utoipa::openapi::Schema::Object(object)) => object.properties.into_keys().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses a comma-separated `?fields=` list; `id` is always included.
+pub fn parse_item_fields(value: &str) -> Result<Vec<String>, String> {
+    let known = item_field_names();
+    let mut fields = vec!["id".to_string()];
+    for field in value.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        if !known.iter().any(|k| k == field) {
+            return Err(format!("Unknown field '{field}'; known fields: {}", known.join(", ")));
+        }
+        if !fields.iter().any(|f| f == field) {
+            fields.push(field.to_string());
+        }
+    }
+    Ok(fields)
+}
+
+impl ApiMediaItem {
+    /// The item with only `fields` (from `parse_item_fields`).
+    pub fn only(&self, fields: &[String]) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.retain(|key, _| fields.contains(key));
+        }
+        value
+    }
+}
+
+// ── Episodes ─────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, SimpleObject)]
+#[graphql(name = "Episode")]
+pub struct ApiEpisode {
+    pub episode: u32,
+    pub watched: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watched_at: Option<String>,
+}
+
+impl From<&EpisodeRecord> for ApiEpisode {
+    fn from(e: &EpisodeRecord) -> Self {
+        ApiEpisode {
+            episode: e.number,
+            watched: e.watched,
+            watched_at: e
+                .watched_at
+                .map(|ts| ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        }
+    }
+}
+
+/// Body of `POST /api/items/:id/episodes` (bulk mark).
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiEpisodeUpdate {
+    pub episodes: Vec<u32>,
+    #[serde(default = "default_true")]
+    pub watched: bool,
+}
+
+/// Body of `PUT /api/items/:id/episodes/:n`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiEpisodeMark {
+    #[serde(default = "default_true")]
+    pub watched: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// ── Merge ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiMergeRequest {
+    /// Item folded into the one named in the path, then deleted.
+    pub duplicate_id: String,
+}
+
+// ── History ──────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, SimpleObject)]
+#[graphql(name = "HistoryEntry")]
+pub struct ApiHistoryEntry {
+    pub id: i64,
+    pub item_id: String,
+    pub title: String,
+    pub event: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_value: Option<String>,
+    pub recorded_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<i64>,
+}
+
+impl From<&HistoryEntry> for ApiHistoryEntry {
+    fn from(h: &HistoryEntry) -> Self {
+        ApiHistoryEntry {
+            id: h.id,
+            item_id: h.item_id.to_string(),
+            title: h.title.clone(),
+            event: h.kind.as_str().to_string(),
+            old_value: h.old_value.clone(),
+            new_value: h.new_value.clone(),
+            recorded_at: h.recorded_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            job_id: h.job_id,
+        }
+    }
+}
+
+/// One recorded progress update of an item, for pace charts.
+#[derive(Debug, Serialize, Deserialize, ToSchema, SimpleObject)]
+#[graphql(name = "ProgressPoint")]
+pub struct ApiProgressPoint {
+    /// Episodes / chapters done after the update.
+    pub progress: u32,
+    /// Change from the previous value; negative when progress went back.
+    pub delta: i64,
+    pub recorded_at: String,
+}
+
+impl From<&ProgressEvent> for ApiProgressPoint {
+    fn from(e: &ProgressEvent) -> Self {
+        ApiProgressPoint {
+            progress: e.progress,
+            delta: e.delta,
+            recorded_at: e.recorded_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+}
+
+// ── Relations ────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRelation {
+    /// e.g. `sequel`, `prequel`, `adaptation`.
+    pub kind: String,
+    pub source: String,
+    pub external_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// The related entry as it is in the library; None when it is not.
+    pub item: Option<ApiMediaItem>,
+}
+
+impl ApiRelation {
+    pub fn new(relation: &Relation, item: Option<&MediaItem>) -> Self {
+        ApiRelation {
+            kind: relation.kind.clone(),
+            source: relation.source.clone(),
+            external_id: relation.external_id.clone(),
+            title: relation.title.clone(),
+            format: relation.format.clone(),
+            item: item.map(ApiMediaItem::from),
+        }
+    }
+}
+
+// ── Schedule ─────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiSession {
+    pub item: ApiMediaItem,
+    pub scheduled_for: String,
+    /// Countdown until the session starts.
+    pub starts_in_minutes: i64,
+}
+
+impl ApiSession {
+    /// The session of `item`, or None when it has none scheduled.
+    pub fn new(item: &MediaItem, now: chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        let at = item.scheduled_for?;
+        Some(ApiSession {
+            item: ApiMediaItem::from(item),
+            scheduled_for: at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            starts_in_minutes: (at - now).num_minutes(),
+        })
+    }
+}
+
+/// The next episode of a tracked series that is still airing.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiAiring {
+    pub item: ApiMediaItem,
+    pub episode: u32,
+    pub airing_at: String,
+    pub airs_in_minutes: i64,
+    /// Episodes already aired but not watched yet.
+    pub behind_by: u32,
+}
+
+impl ApiAiring {
+    pub fn new(item: &MediaItem, next: &NextAiring, now: chrono::DateTime<chrono::Utc>) -> Self {
+        ApiAiring {
+            item: ApiMediaItem::from(item),
+            episode: next.episode,
+            airing_at: next.airing_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            airs_in_minutes: (next.airing_at - now).num_minutes(),
+            behind_by: schedule::behind_by(item, next),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiSchedule {
+    pub sessions: Vec<ApiSession>,
+    /// Next episodes of watched and planned series, soonest first.
+    pub airing: Vec<ApiAiring>,
+}
+
+// ── Jobs ─────────────────────────────────────────────────────
+
+/// Body of `POST /api/jobs/refresh` and `POST /api/jobs/match`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ApiRefreshRequest {
+    /// Only report the changes; apply them later via `/api/jobs/:id/apply`.
+    pub dry_run: bool,
+    /// Items to look at; empty means every item with a provider id (for a
+    /// refresh) or every item without one (for a match).
+    pub item_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiJob {
+    pub id: i64,
+    pub kind: String,
+    pub status: String,
+    pub dry_run: bool,
+    pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub items: Vec<ItemChanges>,
+}
+
+impl From<&Job> for ApiJob {
+    fn from(job: &Job) -> Self {
+        ApiJob {
+            id: job.id,
+            kind: job.kind.as_str().to_string(),
+            status: job.status.as_str().to_string(),
+            dry_run: job.dry_run,
+            created_at: job.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            finished_at: job
+                .finished_at
+                .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            error: job.error.clone(),
+            items: job.items.clone(),
+        }
+    }
+}
+
+/// An item changed since the job a rollback would undo, listed in the
+/// details of its 409.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRollbackConflict {
+    pub item_id: String,
+    pub title: String,
+    /// When the item was last changed; absent when it was deleted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_at: Option<String>,
+}
+
+impl From<&RollbackConflict> for ApiRollbackConflict {
+    fn from(c: &RollbackConflict) -> Self {
+        ApiRollbackConflict {
+            item_id: c.item_id.to_string(),
+            title: c.title.clone(),
+            changed_at: c.changed_at.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        }
+    }
+}
+
+/// A background job of the scheduler, from `GET /api/admin/jobs`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiScheduledJob {
+    pub name: String,
+    pub description: String,
+    /// The interval in force, with its `job_interval_seconds` override.
+    pub interval_seconds: u64,
+    pub running: bool,
+    /// Runs and failed runs since startup.
+    pub runs: u32,
+    pub failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_finished_at: Option<String>,
+    /// `ok` when the last run did something, `idle` when there was nothing
+    /// to do, `failed`; absent before the first run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<String>,
+    /// What the last run did, or why it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message: Option<String>,
+    pub next_run_at: String,
+}
+
+impl From<&JobStatus> for ApiScheduledJob {
+    fn from(job: &JobStatus) -> Self {
+        let time = |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let (last_status, last_message) = match &job.last_result {
+            None => (None, None),
+            Some(Ok(None)) => (Some("idle"), None),
+            Some(Ok(Some(summary))) => (Some("ok"), Some(summary.clone())),
+            Some(Err(e)) => (Some("failed"), Some(e.clone())),
+        };
+        ApiScheduledJob {
+            name: job.spec.name.to_string(),
+            description: job.spec.description.to_string(),
+            interval_seconds: job.interval.as_secs(),
+            running: job.running,
+            runs: job.runs,
+            failures: job.failures,
+            last_started_at: job.last_started.map(time),
+            last_finished_at: job.last_finished.map(time),
+            last_status: last_status.map(str::to_string),
+            last_message,
+            next_run_at: time(job.next_run),
+        }
+    }
+}
+
+/// What an import would do, from `?dry_run=true`.  Nothing was written.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiImportPreview {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    /// Items only a `replace` import removes.
+    pub deleted: usize,
+    pub items: Vec<ApiImportPreviewItem>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiImportPreviewItem {
+    /// Id of the stored item, or of the new one.
+    pub item_id: String,
+    pub title: String,
+    /// `create`, `update`, `skip` or `delete`.
+    pub action: String,
+    /// Why, e.g. `Same title as Frieren`.
+    pub reason: String,
+    /// Fields an update changes, named as in `ApiMediaItem`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+// ── Backups ──────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiBackup {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: String,
+    /// `s3://<bucket>/<key>` of the uploaded copy, when the backup was
+    /// just written and `S3_BUCKET` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded_to: Option<String>,
+    /// Why that upload failed; the local file is kept either way.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_error: Option<String>,
+}
+
+/// Result of replacing the library with an archive.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRestore {
+    pub items: usize,
+    pub episodes: usize,
+    pub progress_events: usize,
+    pub history_entries: usize,
+    /// Snapshot taken just before the library was replaced.
+    pub backup: ApiBackup,
+}
+
+// ── Notifications ────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiNotification {
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+    pub kind: String,
+    pub message: String,
+    pub created_at: String,
+    pub read: bool,
+}
+
+impl From<&Notification> for ApiNotification {
+    fn from(n: &Notification) -> Self {
+        ApiNotification {
+            id: n.id,
+            item_id: n.item_id.map(|id| id.to_string()),
+            kind: n.kind.as_str().to_string(),
+            message: n.message.clone(),
+            created_at: n.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            read: n.read,
+        }
+    }
+}
+
+// ── Diagnostics ──────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiDiagnostic {
+    pub id: i64,
+    pub provider: String,
+    pub message: String,
+    pub snippet: String,
+    pub recorded_at: String,
+}
+
+impl From<&ProviderDiagnostic> for ApiDiagnostic {
+    fn from(d: &ProviderDiagnostic) -> Self {
+        ApiDiagnostic {
+            id: d.id,
+            provider: d.provider.clone(),
+            message: d.message.clone(),
+            snippet: d.snippet.clone(),
+            recorded_at: d
+                .recorded_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+}
+
 // ── Explore result (external search) ─────────────────────────
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// One entry of `POST /api/explore/details`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiDetailRequest {
+    pub source: String,
+    pub external_id: String,
+    /// Explore search type (`anime`, `movie`, ...).
+    #[serde(rename = "type")]
+    pub media_type: String,
+}
+
+/// Body of `POST /api/items/from-external`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiFromExternal {
+    pub source: String,
+    pub external_id: String,
+    /// Explore search type (`anime`, `movie`, ...); defaults to the
+    /// provider's first type.
+    #[serde(default, rename = "type")]
+    pub media_type: Option<String>,
+    /// Defaults to the type's status in `default_statuses`.
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiDetailsRequest {
+    pub items: Vec<ApiDetailRequest>,
+}
+
+/// Details for one requested entry; `result` is null when the provider
+/// does not know the id.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiExploreDetail {
+    pub source: String,
+    pub external_id: String,
+    pub result: Option<ApiExploreResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema, SimpleObject)]
+#[graphql(name = "ExploreResult")]
 pub struct ApiExploreResult {
     pub title: String,
     pub media_type: String,
     pub global_score: Option<f32>,
+    #[serde(default, deserialize_with = "crate::core::models::external_id_compat")]
     pub external_id: Option<String>,
     pub poster_url: Option<String>,
     pub source: String,
     pub total_episodes: Option<u32>,
     pub format_label: String,
+    pub alt_titles: Vec<String>,
+    pub external_refs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// See `ApiMediaItem::content_rating`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_rating: Option<String>,
 }
 
 impl ApiExploreResult {
-    pub fn from_search_result(r: &crate::core::search::SearchResult) -> Self {
+    /// The result with its name in `language` as the title, where the
+    /// provider has one.
+    pub fn from_search_result(r: &crate::core::search::SearchResult, language: TitleLanguage) -> Self {
+        let (title, alt_titles) = r.titles.localize(&r.title, &r.alt_titles, language);
         let (media_type, total) = match &r.media_type {
             MediaItemType::Movie(_) => ("movie", None),
             MediaItemType::Series(p, _) => {
@@ -180,21 +887,788 @@ impl ApiExploreResult {
         };
 
         ApiExploreResult {
-            title: r.title.clone(),
+            title,
             media_type: media_type.to_string(),
             global_score: r.global_score.map(|s| s as f32 / 10.0),
-            external_id: r.external_id.map(|e| e.to_string()),
+            external_id: r.external_id.clone(),
             poster_url: r.poster_url.clone(),
             source: r.source.to_string(),
             total_episodes: total,
             format_label: r.format_label.clone(),
+            alt_titles,
+            external_refs: r.external_refs.clone(),
+            creator: r.creator.clone(),
+            content_rating: r.content_rating.map(|c| c.as_str().to_string()),
+        }
+    }
+}
+
+impl ApiExploreResult {
+    /// Attaches this provider match to an item added by hand: source and
+    /// external id, poster and provider score are taken over, an unknown
+    /// total is filled in and titles / refs are added.  Tracking state
+    /// (status, progress, own score, notes) is left alone.
+    pub fn link_to(&self, item: &mut MediaItem) -> Result<(), String> {
+        let external_id = self
+            .external_id
+            .clone()
+            .filter(|id| !id.is_empty())
+            .ok_or("Match has no external_id")?;
+        let same_kind = match &item.media_type {
+            MediaItemType::Movie(_) => self.media_type == "movie",
+            MediaItemType::Series(..) => matches!(self.media_type.as_str(), "series" | "anime"),
+            MediaItemType::Readable(..) => {
+                !matches!(self.media_type.as_str(), "movie" | "series" | "anime")
+            }
+        };
+        if !same_kind {
+            return Err(format!("Cannot link a match of type {} to this item", self.media_type));
+        }
+
+        item.source = Some(self.source.clone());
+        item.external_id = Some(external_id);
+        if let Some(url) = &self.poster_url {
+            item.poster_url = Some(url.clone());
+        }
+        if let Some(g) = self.global_score {
+            item.set_global_score(g);
+        }
+        item.creator = item.creator.take().or_else(|| self.creator.clone());
+        item.content_rating =
+            item.content_rating.or(self.content_rating.as_deref().and_then(ContentRating::parse));
+        if let MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) = &mut item.media_type {
+            p.total = p.total.or(self.total_episodes);
+        }
+        for title in std::iter::once(&self.title).chain(&self.alt_titles) {
+            if *title != item.title && !item.alt_titles.contains(title) {
+                item.alt_titles.push(title.clone());
+            }
+        }
+        item.external_refs.extend(self.external_refs.iter().cloned());
+        Ok(())
+    }
+}
+
+/// One provider entry with everything the provider knows about it, for a
+/// preview before adding it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiExplorePreview {
+    pub result: ApiExploreResult,
+    pub overview: Option<String>,
+    pub genres: Vec<String>,
+    /// Length of a movie, or of an episode of a series.
+    pub runtime_minutes: Option<u32>,
+    pub season_count: Option<u32>,
+    pub episode_count: Option<u32>,
+    pub staff: Vec<ApiCredit>,
+    /// The entry as it is in the library; None when it is not.
+    pub item: Option<ApiMediaItem>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiCredit {
+    pub name: String,
+    /// e.g. `Director` or `Original Creator`.
+    pub role: String,
+}
+
+impl ApiExplorePreview {
+    pub fn new(
+        result: &crate::core::search::SearchResult,
+        details: MediaDetails,
+        language: TitleLanguage,
+        item: Option<ApiMediaItem>,
+    ) -> Self {
+        ApiExplorePreview {
+            result: ApiExploreResult::from_search_result(result, language),
+            overview: details.overview.filter(|o| !o.trim().is_empty()),
+            genres: details.genres,
+            runtime_minutes: details.runtime_minutes,
+            season_count: details.season_count,
+            episode_count: details.episode_count,
+            staff: details.staff.into_iter().map(|c| ApiCredit { name: c.name, role: c.role }).collect(),
+            item,
+        }
+    }
+}
+
+/// A typeahead entry of `GET /api/explore/suggest`: just enough to show
+/// the title and pick it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiSuggestion {
+    pub title: String,
+    pub media_type: String,
+    pub year: Option<i32>,
+    /// A small version of the poster.
+    pub poster_url: Option<String>,
+    pub source: String,
+    pub external_id: Option<String>,
+}
+
+impl ApiSuggestion {
+    pub fn from_search_result(r: &crate::core::search::SearchResult, language: TitleLanguage) -> Self {
+        let full = ApiExploreResult::from_search_result(r, language);
+        ApiSuggestion {
+            title: full.title,
+            media_type: full.media_type,
+            year: r.year,
+            poster_url: full.poster_url.as_deref().map(suggest::thumbnail_url),
+            source: full.source,
+            external_id: full.external_id,
+        }
+    }
+}
+
+/// Body of `PUT /api/items/:id/hidden`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiHiddenRequest {
+    pub hidden: bool,
+}
+
+/// Body of `POST /api/items/:id/match`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ApiMatchRequest {
+    /// Search category (`anime`, `manga`, ...); by default it follows the
+    /// item's type, with series searched as TV series.
+    pub media_type: Option<String>,
+    /// `external_id` of the candidate to link; without it the candidates
+    /// are only listed.
+    pub pick: Option<String>,
+}
+
+/// Provider entries that may be the same work as an item added by hand.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiMatchCandidates {
+    pub media_type: String,
+    pub candidates: Vec<ApiExploreResult>,
+    /// Index of the one candidate with exactly the item's title, if any.
+    pub suggested: Option<usize>,
+}
+
+// ── Providers ────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiProvider {
+    pub name: String,
+    pub media_types: Vec<&'static str>,
+    pub available: bool,
+    pub reason: Option<String>,
+}
+
+/// An RFC 7807 problem: the body of a 400 for a request that failed
+/// validation, sent as `application/problem+json`.  Also carries the
+/// `code`, `message` and `details` of every other API error.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiProblem {
+    /// Always `about:blank`; `title` says what kind of problem it is.
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    /// Always `invalid_request`.
+    pub code: &'static str,
+    pub message: String,
+    /// One entry per invalid field.
+    pub details: Vec<FieldError>,
+}
+
+impl ApiProblem {
+    pub fn invalid(errors: Vec<FieldError>) -> Self {
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        let detail = format!("Invalid fields: {}", fields.join(", "));
+        ApiProblem {
+            problem_type: "about:blank",
+            title: "Invalid request",
+            status: 400,
+            code: "invalid_request",
+            message: detail.clone(),
+            detail,
+            details: errors,
+        }
+    }
+}
+
+/// A provider that failed or timed out during an explore request.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiProviderError {
+    pub provider: String,
+    pub error: String,
+}
+
+/// `details` of the `provider_not_configured` 503 returned by
+/// `/api/explore` when every provider for the requested type is switched
+/// off.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiProviderUnavailable {
+    pub media_type: &'static str,
+    pub providers: Vec<ApiProvider>,
+}
+
+impl From<&UnavailableProvider> for ApiProvider {
+    fn from(p: &UnavailableProvider) -> Self {
+        ApiProvider {
+            name: p.name.to_string(),
+            media_types: p.supported_types.iter().map(|t| t.as_str()).collect(),
+            available: false,
+            reason: Some(p.reason.clone()),
+        }
+    }
+}
+
+// ── Recommendations ──────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRecommendation {
+    pub item: ApiMediaItem,
+    pub score: f32,
+    /// Why the item was picked, strongest signals first.
+    pub signals: Vec<Signal>,
+}
+
+impl From<&Recommendation<'_>> for ApiRecommendation {
+    fn from(r: &Recommendation<'_>) -> Self {
+        ApiRecommendation {
+            item: ApiMediaItem::from(r.item),
+            score: r.score,
+            signals: r.signals.clone(),
+        }
+    }
+}
+
+/// A provider entry outside the library, suggested for liked items.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiDiscovery {
+    pub result: ApiExploreResult,
+    pub score: f32,
+    /// The liked items it was suggested for, strongest first.
+    pub signals: Vec<Signal>,
+}
+
+impl ApiDiscovery {
+    pub fn new(d: &Discovery<'_>, language: TitleLanguage) -> Self {
+        ApiDiscovery {
+            result: ApiExploreResult::from_search_result(&d.result, language),
+            score: d.score,
+            signals: d
+                .because
+                .iter()
+                .map(|i| Signal::BecauseYouRated { item_id: i.id, title: i.title.clone(), score: i.score })
+                .collect(),
+        }
+    }
+}
+
+// ── Currently into ───────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiEngagement {
+    pub item: ApiMediaItem,
+    /// Recency-weighted share of the item progressed within the activity window.
+    pub velocity: f32,
+    pub last_activity: String,
+}
+
+impl From<&Engagement<'_>> for ApiEngagement {
+    fn from(e: &Engagement<'_>) -> Self {
+        ApiEngagement {
+            item: ApiMediaItem::from(e.item),
+            velocity: e.velocity,
+            last_activity: e.last_activity.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+}
+
+// ── Focus ────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiFocusEntry {
+    pub item: ApiMediaItem,
+    pub pinned_at: String,
+    /// Share of the item that is done, 0.0-1.0.
+    pub completion: f32,
+}
+
+impl ApiFocusEntry {
+    pub fn new(pin: &FocusPin, item: &MediaItem) -> Self {
+        ApiFocusEntry {
+            item: ApiMediaItem::from(item),
+            pinned_at: pin.pinned_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            completion: focus::completion(item),
+        }
+    }
+}
+
+/// The focus list widget: how many pins are used and how far along they are.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiFocusStats {
+    pub pinned: usize,
+    pub limit: u32,
+    /// Pinned items that are finished and can make room for the next one.
+    pub completed: usize,
+    /// Mean completion of the pinned items, 0.0-1.0.
+    pub progress: f32,
+}
+
+impl ApiFocusStats {
+    pub fn new(summary: &FocusSummary, limit: u32) -> Self {
+        ApiFocusStats {
+            pinned: summary.pinned,
+            limit,
+            completed: summary.completed,
+            progress: summary.progress,
+        }
+    }
+}
+
+// ── Ranking ──────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRankEntry {
+    pub item: ApiMediaItem,
+    /// Elo rating, 1500 before the first comparison.
+    pub rating: i32,
+    pub comparisons: u32,
+}
+
+impl ApiRankEntry {
+    pub fn new(rating: &Rating, item: &MediaItem, scale: ScoreScale) -> Self {
+        ApiRankEntry {
+            item: ApiMediaItem::from(item).in_scale(scale),
+            rating: rating.rating.round() as i32,
+            comparisons: rating.comparisons,
         }
     }
 }
 
+/// Two items to compare in ranking mode.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRankPair {
+    pub left: ApiRankEntry,
+    pub right: ApiRankEntry,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiRankChoice {
+    /// The item picked as the better one.
+    pub winner_id: String,
+    pub loser_id: String,
+}
+
+/// Both items of a comparison with their new ratings.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiRankResult {
+    pub winner: ApiRankEntry,
+    pub loser: ApiRankEntry,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiLeaderboardEntry {
+    /// 1 for the best rated item.
+    pub rank: usize,
+    #[serde(flatten)]
+    pub entry: ApiRankEntry,
+}
+
+// ── Goals ────────────────────────────────────────────────────
+
+/// A yearly target with how far it is.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiGoal {
+    pub id: i64,
+    pub year: i32,
+    /// What counts (e.g. `book`); missing for every completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    pub target: u32,
+    /// Items finished in the year.
+    pub completed: u32,
+    /// At least as far as an even pace through the year would be today.
+    pub on_track: bool,
+}
+
+impl ApiGoal {
+    pub fn new(goal: &Goal, progress: &GoalProgress) -> Self {
+        ApiGoal {
+            id: goal.id,
+            year: goal.year,
+            media_type: goal.media_type.clone(),
+            target: goal.target,
+            completed: progress.completed,
+            on_track: progress.on_track,
+        }
+    }
+}
+
+/// Body of `POST /api/goals`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiNewGoal {
+    pub year: i32,
+    /// One of the API's media types; leave out to count everything.
+    #[serde(default)]
+    pub media_type: Option<String>,
+    pub target: u32,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiStreak {
+    /// Days in a row with activity, up to today or yesterday.
+    pub current_days: u32,
+    pub longest_days: u32,
+    /// `YYYY-MM-DD` of the latest activity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_active: Option<String>,
+}
+
+impl From<&Streak> for ApiStreak {
+    fn from(s: &Streak) -> Self {
+        ApiStreak {
+            current_days: s.current,
+            longest_days: s.longest,
+            last_active: s.last_active.map(|d| d.to_string()),
+        }
+    }
+}
+
+/// `GET /api/goals`: every goal, newest year first, and the streak.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiGoals {
+    pub goals: Vec<ApiGoal>,
+    pub streak: ApiStreak,
+}
+
+// ── Franchises ───────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiFranchise {
+    pub name: String,
+    pub items: usize,
+    pub completed: usize,
+    /// Mean completion of the entries, 0.0-1.0.
+    pub progress: f32,
+    /// Mean score of the scored entries, 0.0-10.0.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_score: Option<f32>,
+}
+
+impl From<&FranchiseSummary> for ApiFranchise {
+    fn from(f: &FranchiseSummary) -> Self {
+        ApiFranchise {
+            name: f.name.clone(),
+            items: f.items,
+            completed: f.completed,
+            progress: f.progress,
+            average_score: f.average_score,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiFranchiseDetail {
+    #[serde(flatten)]
+    pub franchise: ApiFranchise,
+    pub entries: Vec<ApiMediaItem>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiFranchiseRename {
+    pub name: String,
+}
+
+// ── Dashboard ────────────────────────────────────────────────
+
+/// Everything the dashboard and `kars top` show, in one response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiDashboard {
+    pub generated_at: String,
+    pub stats: ApiStats,
+    /// Sessions scheduled within the next week, soonest first.
+    pub upcoming: Vec<ApiSession>,
+    /// Items with the most recent progress.
+    pub continue_watching: Vec<ApiEngagement>,
+    /// Unread notifications, newest first.
+    pub notifications: Vec<ApiNotification>,
+    /// Latest jobs, newest first.
+    pub jobs: Vec<ApiJob>,
+    /// Followed series with an episode out before midnight; null when the
+    /// providers were not asked (`kars top`).
+    pub airing_today: Option<usize>,
+    /// One-line overview, also printed when the interactive CLI starts.
+    pub summary: String,
+}
+
+// ── Telemetry ────────────────────────────────────────────────
+
+/// What the opt-in telemetry would send right now, and whether it will.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTelemetryPreview {
+    /// The `telemetry_enabled` setting.
+    pub enabled: bool,
+    /// `TELEMETRY_URL`; nothing is sent without it.
+    pub endpoint: Option<String>,
+    /// The exact body that would be POSTed.
+    pub payload: TelemetryPayload,
+}
+
+// ── Trakt ────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTraktStatus {
+    /// Whether `TRAKT_CLIENT_ID` and `TRAKT_CLIENT_SECRET` are set.
+    pub configured: bool,
+    pub connected: bool,
+    pub connected_at: Option<String>,
+    pub last_synced_at: Option<String>,
+    /// The `trakt_sync_hours` setting.
+    pub sync_hours: u32,
+}
+
+impl ApiTraktStatus {
+    pub fn new(configured: bool, auth: Option<&TraktAuth>, sync_hours: u32) -> Self {
+        let format = |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        ApiTraktStatus {
+            configured,
+            connected: auth.is_some(),
+            connected_at: auth.map(|a| format(a.connected_at)),
+            last_synced_at: auth.and_then(|a| a.last_synced_at).map(format),
+            sync_hours,
+        }
+    }
+}
+
+/// The code to enter at `verification_url`.  The account is connected
+/// once it is entered; `GET /api/trakt` tells when.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTraktConnect {
+    pub user_code: String,
+    pub verification_url: String,
+    pub expires_in: u64,
+}
+
+// ── AniList write-through ────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiAniListStatus {
+    pub linked: bool,
+    pub user_name: Option<String>,
+    pub connected_at: Option<String>,
+    /// The `anilist_write_through` setting.
+    pub write_through: bool,
+    /// Updates not sent yet, oldest first.
+    pub queue: Vec<ApiQueuedUpdate>,
+}
+
+impl ApiAniListStatus {
+    pub fn new(auth: Option<&AniListAuth>, write_through: bool, queue: &[QueuedUpdate]) -> Self {
+        ApiAniListStatus {
+            linked: auth.is_some(),
+            user_name: auth.map(|a| a.user_name.clone()),
+            connected_at: auth.map(|a| a.connected_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            write_through,
+            queue: queue.iter().map(ApiQueuedUpdate::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiQueuedUpdate {
+    pub item_id: Uuid,
+    pub queued_at: String,
+    /// Failed tries so far; the update is dropped after a few.
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl From<&QueuedUpdate> for ApiQueuedUpdate {
+    fn from(u: &QueuedUpdate) -> Self {
+        ApiQueuedUpdate {
+            item_id: u.item_id,
+            queued_at: u.queued_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            attempts: u.attempts,
+            last_error: u.last_error.clone(),
+        }
+    }
+}
+
+/// An AniList access token (see the README on getting one).
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiAniListLink {
+    pub token: String,
+}
+
+// ── Reading servers ──────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiReadingServers {
+    /// Kavita and Komga, when configured.
+    pub servers: Vec<String>,
+    /// The `reading_sync_minutes` setting.
+    pub sync_minutes: u32,
+}
+
+/// What a reading progress sync did.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiReadingSync {
+    /// The import job holding the changes; absent when nothing changed.
+    pub job: Option<ApiJob>,
+    /// Series with progress that match no readable, or several.
+    pub unmatched: Vec<String>,
+    /// Servers that could not be read.
+    pub errors: Vec<ApiProviderError>,
+}
+
+// ── Outbox ───────────────────────────────────────────────────
+
+/// An item write waiting for Turso to come back; the body of the 202
+/// returned when one is queued.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiQueuedWrite {
+    pub id: i64,
+    pub item_id: String,
+    /// `create`, `upsert`, `update` or `delete`.
+    pub op: &'static str,
+    /// Absent for deletes.
+    pub title: Option<String>,
+    pub queued_at: String,
+    /// Why the last replay held the write back.
+    pub conflict: Option<String>,
+}
+
+impl From<&QueuedWrite> for ApiQueuedWrite {
+    fn from(write: &QueuedWrite) -> Self {
+        ApiQueuedWrite {
+            id: write.id,
+            item_id: write.item_id.to_string(),
+            op: write.op.as_str(),
+            title: write.title().map(str::to_string),
+            queued_at: write.queued_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            conflict: write.conflict.clone(),
+        }
+    }
+}
+
+/// What replaying the outbox did.
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ApiOutboxReplay {
+    pub applied: usize,
+    /// Writes held back, with the reason; they stay queued.
+    pub conflicts: Vec<ApiQueuedWrite>,
+    /// Writes still queued, conflicts included.
+    pub pending: usize,
+}
+
+// ── Webhooks ─────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiWebhook {
+    pub id: i64,
+    pub url: String,
+    /// Key of the `X-Kars-Signature` HMAC.
+    pub secret: String,
+    pub created_at: String,
+}
+
+impl From<&Webhook> for ApiWebhook {
+    fn from(w: &Webhook) -> Self {
+        ApiWebhook {
+            id: w.id,
+            url: w.url.clone(),
+            secret: w.secret.clone(),
+            created_at: w.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiNewWebhook {
+    pub url: String,
+    /// Generated when left out.
+    pub secret: Option<String>,
+}
+
+// ── Notifiers ────────────────────────────────────────────────
+
+/// How the test notice went for one entry of the `notifiers` setting.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiNotifierTest {
+    /// `webhook`, `ntfy`, `email` or `telegram`.
+    pub kind: String,
+    pub sent: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ── Profiles ─────────────────────────────────────────────────
+
+/// A library served by this instance and its totals.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiProfile {
+    pub name: String,
+    pub stats: ApiStats,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApiNewProfile {
+    pub name: String,
+}
+
+/// How a profile's scores compare with the other profiles that share
+/// theirs.  Scores are in the profile's own scale.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiScoreComparison {
+    /// Profiles whose scores make up the averages: those with
+    /// `share_scores` on.
+    pub compared_with: Vec<String>,
+    /// Mean of this profile's scores.
+    pub mean_score: Option<f32>,
+    /// Mean of every score in the compared profiles.
+    pub instance_mean: Option<f32>,
+    /// Titles scored here and in a compared profile, biggest gap first.
+    pub titles: Vec<ApiSharedScore>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiSharedScore {
+    pub item_id: String,
+    pub title: String,
+    pub score: f32,
+    /// Mean score of the same title in the compared profiles.
+    pub average: f32,
+    /// How many compared profiles scored it.
+    pub raters: usize,
+    /// `score - average`.
+    pub difference: f32,
+    /// The difference once each profile's own mean is taken out: how much
+    /// more this profile likes the title than it likes things in general,
+    /// compared with the others.
+    pub normalized_difference: f32,
+}
+
+impl ApiSharedScore {
+    pub fn new(shared: &SharedScore, scale: ScoreScale) -> Self {
+        let average = shared.average();
+        ApiSharedScore {
+            item_id: shared.item.id.to_string(),
+            title: shared.item.title.clone(),
+            score: scale.scaled(shared.score),
+            average: on_scale(average, scale),
+            raters: shared.others.len(),
+            difference: on_scale(shared.score as f32 - average, scale),
+            normalized_difference: on_scale(shared.normalized_difference, scale),
+        }
+    }
+}
+
+/// A stored 0-100 mean or gap in `scale`, to one decimal.
+pub fn on_scale(stored: f32, scale: ScoreScale) -> f32 {
+    (stored * scale.max() as f32 / 10.0).round() / 10.0
+}
+
 // ── Stats ────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema, SimpleObject)]
+#[graphql(name = "Stats")]
 pub struct ApiStats {
     pub total: usize,
     pub watching: usize,
@@ -206,46 +1680,183 @@ pub struct ApiStats {
     pub series: usize,
     pub anime: usize,
     pub readable: usize,
+    /// Items currently being rewatched / reread.
+    pub rewatching: usize,
+    /// Sum of `repeat_count` across the library.
+    pub total_rewatches: u32,
+    /// Readables being read with new chapters out.
+    #[serde(default)]
+    pub behind_on_chapters: usize,
+    /// Unread chapters across those readables.
+    #[serde(default)]
+    pub chapters_behind: u32,
 }
 
 impl ApiStats {
     pub fn from_items(items: &[ApiMediaItem]) -> Self {
-        let mut stats = ApiStats {
-            total: items.len(),
-            watching: 0,
-            completed: 0,
-            plan_to_watch: 0,
-            on_hold: 0,
-            dropped: 0,
-            movies: 0,
-            series: 0,
-            anime: 0,
-            readable: 0,
-        };
-
+        let mut stats = ApiStats::default();
         for item in items {
-            match item.status.as_str() {
-                "watching" | "reading" => stats.watching += 1,
-                "completed" => stats.completed += 1,
-                "plan_to_watch" | "plan_to_read" => stats.plan_to_watch += 1,
-                "on_hold" => stats.on_hold += 1,
-                "dropped" => stats.dropped += 1,
-                _ => {}
-            }
-            match item.media_type.as_str() {
-                "movie" => stats.movies += 1,
-                "series" => stats.series += 1,
-                "anime" => stats.anime += 1,
-                _ => stats.readable += 1,
+            let rewatched = (item.repeat_count > 0) as usize;
+            stats.add(&item.media_type, &item.status, 1, rewatched, item.repeat_count);
+            if let Some(behind) = item.chapters_behind {
+                stats.behind_on_chapters += 1;
+                stats.chapters_behind += behind;
             }
         }
-
         stats
     }
+
+    /// Counts `count` items of one API media type and status, `rewatched`
+    /// of which have been through at least one rewatch, with `rewatches`
+    /// repeats between them.
+    pub fn add(&mut self, media_type: &str, status: &str, count: usize, rewatched: usize, rewatches: u32) {
+        self.total += count;
+        self.total_rewatches += rewatches;
+        if matches!(status, "watching" | "reading") {
+            self.rewatching += rewatched;
+        }
+        match status {
+            "watching" | "reading" => self.watching += count,
+            "completed" => self.completed += count,
+            "plan_to_watch" | "plan_to_read" => self.plan_to_watch += count,
+            "on_hold" => self.on_hold += count,
+            "dropped" => self.dropped += count,
+            _ => {}
+        }
+        match media_type {
+            "movie" => self.movies += count,
+            "series" => self.series += count,
+            "anime" => self.anime += count,
+            _ => self.readable += count,
+        }
+    }
+}
+
+/// Deeper statistics for the stats page, from `GET /api/stats/extended`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiExtendedStats {
+    /// Scored items per whole point: index 0 counts 0.0-0.9, index 10 only 10.0.
+    pub score_histogram: Vec<usize>,
+    pub scored: usize,
+    pub mean_score: Option<f32>,
+    pub median_score: Option<f32>,
+    /// Items per tag, most used first.
+    pub tags: Vec<ApiTagCount>,
+    /// Readable items per kind (`manga`, `book`, ...).
+    pub readable_kinds: BTreeMap<String, usize>,
+    /// Episodes of series and anime, counting finished rewatches in full.
+    pub episodes_watched: u64,
+    /// Chapters (or volumes / pages, as tracked) of readable items,
+    /// counting finished rereads in full.
+    pub chapters_read: u64,
+    pub movies_watched: u64,
+    /// Estimated time spent on the whole library, counting finished
+    /// rewatches in full.
+    pub time_spent: ApiTimeSpent,
+    /// The same per media type (`anime`, `book`, ...); types without a
+    /// runtime, page count or minutes to go by are left out.
+    pub time_by_media_type: BTreeMap<String, ApiTimeSpent>,
+    /// Time per year (`"2025"`), from progress logged and movies
+    /// completed that year.  Imports and progress set when an item was
+    /// added are not dated, so years add up to less than `time_spent`.
+    pub time_by_year: BTreeMap<String, ApiTimeSpent>,
+    /// Watched movies and series without a stored runtime, whose time is
+    /// missing from the estimates.
+    pub without_runtime: usize,
+}
+
+/// Hours watched and listened to, and pages read.
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ApiTimeSpent {
+    /// Episodes and movies times their runtime, to a tenth of an hour.
+    pub hours_watched: f64,
+    /// Progress on books, whose totals are page counts.
+    pub pages_read: u64,
+    /// Progress on audiobooks, to a tenth of an hour.
+    pub hours_listened: f64,
+}
+
+impl From<TimeSpent> for ApiTimeSpent {
+    fn from(time: TimeSpent) -> Self {
+        let hours = |minutes: u64| (minutes as f64 / 6.0).round() / 10.0;
+        ApiTimeSpent {
+            hours_watched: hours(time.watch_minutes),
+            pages_read: time.pages,
+            hours_listened: hours(time.listen_minutes),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiTagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+impl ApiExtendedStats {
+    pub fn new(scores: &ScoreSummary) -> Self {
+        ApiExtendedStats {
+            score_histogram: scores.histogram.to_vec(),
+            scored: scores.histogram.iter().sum(),
+            mean_score: scores.mean,
+            median_score: scores.median,
+            tags: Vec::new(),
+            readable_kinds: BTreeMap::new(),
+            episodes_watched: 0,
+            chapters_read: 0,
+            movies_watched: 0,
+            time_spent: ApiTimeSpent::default(),
+            time_by_media_type: BTreeMap::new(),
+            time_by_year: BTreeMap::new(),
+            without_runtime: 0,
+        }
+    }
 }
 
 // ── Helpers ──────────────────────────────────────────────────
 
+/// Status strings accepted by the API and CLI.
+pub const STATUSES: &[&str] = &[
+    "watching",
+    "reading",
+    "plan_to_watch",
+    "plan_to_read",
+    "completed",
+    "on_hold",
+    "dropped",
+];
+
+/// The API's `media_type` of an item: `anime` for series from AniList.
+pub fn media_type_of(item: &MediaItem) -> &'static str {
+    match &item.media_type {
+        MediaItemType::Movie(_) => "movie",
+        MediaItemType::Series(..) => match item.source.as_deref() {
+            Some("anilist") => "anime",
+            _ => "series",
+        },
+        MediaItemType::Readable(kind, ..) => readable_kind_str(kind),
+    }
+}
+
+/// API status string of an item (`watching`, `plan_to_read`, ...).
+pub fn status_of(media_type: &MediaItemType) -> &'static str {
+    match media_type {
+        MediaItemType::Movie(ws) | MediaItemType::Series(_, ws) => watch_status_str(ws),
+        MediaItemType::Readable(_, _, rs) => read_status_str(rs),
+    }
+}
+
+/// Overwrites the status carried by `media_type`; watch and read variants
+/// are interchangeable (`plan_to_watch` on a manga means `plan_to_read`).
+pub fn apply_status(media_type: &mut MediaItemType, status: &str) {
+    match media_type {
+        MediaItemType::Movie(ws) | MediaItemType::Series(_, ws) => {
+            *ws = parse_watch_status(status);
+        }
+        MediaItemType::Readable(_, _, rs) => *rs = parse_read_status(status),
+    }
+}
+
 fn watch_status_str(s: &WatchStatus) -> &'static str {
     match s {
         WatchStatus::Watching => "watching",
@@ -274,6 +1885,7 @@ fn readable_kind_str(k: &ReadableKind) -> &'static str {
         ReadableKind::Book => "book",
         ReadableKind::LightNovel => "light_novel",
         ReadableKind::WebNovel => "web_novel",
+        ReadableKind::Audiobook => "audiobook",
     }
 }
 
@@ -298,3 +1910,74 @@ fn parse_read_status(s: &str) -> ReadStatus {
         _ => ReadStatus::PlanToRead,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_to_keeps_tracking_state() {
+        let mut item = MediaItem::new(
+            "Dune".into(),
+            MediaItemType::Movie(WatchStatus::Completed),
+        );
+        item.set_score(9.0);
+        let mut found = ApiExploreResult {
+            title: "Dune: Part One".into(),
+            media_type: "series".into(),
+            global_score: Some(7.8),
+            external_id: Some("438631".into()),
+            poster_url: Some("https://image.tmdb.org/dune.jpg".into()),
+            source: "tmdb".into(),
+            total_episodes: None,
+            format_label: "Movie".into(),
+            alt_titles: vec!["Dune".into()],
+            external_refs: vec!["tmdb:movie:438631".into()],
+            creator: None,
+            content_rating: Some("safe".into()),
+        };
+        assert!(found.link_to(&mut item.clone()).is_err());
+
+        found.media_type = "movie".into();
+        found.link_to(&mut item).unwrap();
+        assert_eq!((item.source.as_deref(), item.external_id.as_deref()), (Some("tmdb"), Some("438631")));
+        assert_eq!(item.content_rating, Some(ContentRating::Safe));
+        assert_eq!(item.global_score, Some(78));
+        assert_eq!(item.score, Some(90));
+        assert_eq!(item.alt_titles, vec!["Dune: Part One".to_string()]);
+        assert!(item.external_refs.contains("tmdb:movie:438631"));
+        assert_eq!(item.media_type, MediaItemType::Movie(WatchStatus::Completed));
+    }
+
+    #[test]
+    fn test_scaled_scores_survive_a_round_trip() {
+        let mut item = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+        item.set_score(8.7);
+        let shown = ApiMediaItem::from(&item).in_scale(ScoreScale::FiveStars);
+        assert_eq!(shown.score, Some(4.5));
+
+        let saved = shown.clone().out_of_scale(ScoreScale::FiveStars, Some(&item)).unwrap();
+        assert_eq!(saved.into_media_item().unwrap().score, Some(87));
+
+        let mut changed = shown;
+        changed.score = Some(3.0);
+        let saved = changed.clone().out_of_scale(ScoreScale::FiveStars, Some(&item)).unwrap();
+        assert_eq!(saved.into_media_item().unwrap().score, Some(60));
+
+        changed.score = Some(7.0);
+        assert!(changed.out_of_scale(ScoreScale::FiveStars, Some(&item)).is_err());
+    }
+
+    #[test]
+    fn test_sparse_fields_keep_only_what_was_asked() {
+        let fields = parse_item_fields("title, poster_url,status,title").unwrap();
+        assert_eq!(fields, ["id", "title", "poster_url", "status"]);
+        assert!(parse_item_fields("title,password").unwrap_err().contains("Unknown field 'password'"));
+
+        let item = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+        let sparse = ApiMediaItem::from(&item).only(&fields);
+        let keys: Vec<&String> = sparse.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["id", "status", "title"]);
+        assert_eq!(sparse["title"], "Heat");
+    }
+}
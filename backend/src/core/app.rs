@@ -1,16 +1,46 @@
 use crate::core::models::{
-    MediaItem, MediaItemType, ReadableKind, Progress, WatchStatus, ReadStatus,
+    format_minutes, MediaItem, MediaItemType, ReadableKind, Progress, WatchStatus, ReadStatus, ScoreScale, TitleLanguage,
 };
 use crate::core::input::{InputHandler, InputProvider};
 use crate::core::storage::{StorageProvider, StorageError};
-use crate::core::search::{SearchProvider, MediaSearchType};
+use crate::core::search::{SearchProvider, SearchResult, MediaSearchType};
+use crate::core::matcher::{self, MatchOutcome};
+use crate::core::api_types::{apply_status, status_of, ApiMediaItem, ApiStats};
+use crate::core::custom_fields::{self, MAX_CUSTOM_FIELDS};
+use crate::core::query;
+use crate::core::schedule;
+use uuid::Uuid;
+
+/// How many menu actions can be undone.
+const UNDO_DEPTH: usize = 20;
+
+/// State needed to reverse one menu action.
+enum Change {
+    /// An item was modified; holds its previous version.
+    Modified(MediaItem),
+    /// An item was appended to the archive.
+    Added(Uuid),
+    /// An item was removed from the given position.
+    Removed(usize, MediaItem),
+    /// A duplicate (removed from the given position) was merged into an
+    /// item; holds that item's previous version and the duplicate.
+    Merged(MediaItem, usize, Box<MediaItem>),
+}
+
+struct UndoStep {
+    label: String,
+    change: Change,
+}
 
 pub struct App<S: StorageProvider, I: InputProvider> {
     archive: Vec<MediaItem>,
     storage: S,
     input: InputHandler<I>,
     searchers: Vec<Box<dyn SearchProvider>>,
+    score_scale: ScoreScale,
+    title_language: TitleLanguage,
     dirty: bool,
+    undo: Vec<UndoStep>,
 }
 
 impl<S: StorageProvider, I: InputProvider> App<S, I> {
@@ -20,12 +50,17 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
         searchers: Vec<Box<dyn SearchProvider>>,
     ) -> Result<Self, StorageError> {
         let archive = storage.load_all()?;
+        let score_scale = storage.score_scale();
+        let title_language = storage.title_language();
         Ok(Self {
             archive,
             storage,
             input: InputHandler::new(input_provider),
             searchers,
+            score_scale,
+            title_language,
             dirty: false,
+            undo: Vec::new(),
         })
     }
 
@@ -38,15 +73,96 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
         }
     }
 
-    fn has_duplicate(&self, title: &str) -> bool {
-        self.archive.iter().any(|item| item.title.eq_ignore_ascii_case(title))
+    /// Records how to reverse a menu action, dropping the oldest step once
+    /// `UNDO_DEPTH` is reached.
+    fn remember(&mut self, label: String, change: Change) {
+        if self.undo.len() == UNDO_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(UndoStep { label, change });
+    }
+
+    /// Snapshots `archive[idx]` before an in-place edit.
+    fn remember_edit(&mut self, idx: usize, action: &str) {
+        let before = self.archive[idx].clone();
+        self.remember(format!("{action} '{}'", before.title), Change::Modified(before));
+    }
+
+    fn undo_flow(&mut self) {
+        let Some(step) = self.undo.pop() else {
+            println!("Nothing to undo.");
+            return;
+        };
+        match step.change {
+            Change::Modified(before) => {
+                if let Some(item) = self.archive.iter_mut().find(|i| i.id == before.id) {
+                    *item = before;
+                }
+            }
+            Change::Added(id) => self.archive.retain(|i| i.id != id),
+            Change::Removed(idx, item) => {
+                let idx = idx.min(self.archive.len());
+                self.archive.insert(idx, item);
+            }
+            Change::Merged(before, idx, duplicate) => {
+                if let Some(item) = self.archive.iter_mut().find(|i| i.id == before.id) {
+                    *item = before;
+                }
+                let idx = idx.min(self.archive.len());
+                self.archive.insert(idx, *duplicate);
+            }
+        }
+        self.dirty = true;
+        self.auto_save();
+        println!("Undone: {}", step.label);
+    }
+
+    /// Asks before adding `item` when the archive already holds the same
+    /// work (possibly under another title or from another source).
+    fn confirm_if_duplicate(&mut self, item: &MediaItem) -> bool {
+        let Some(existing) = matcher::find_duplicate(&self.archive, item) else {
+            return true;
+        };
+        println!("Warning: '{}' already exists in archive as '{}'.", item.title, existing.title);
+        let confirm = self.input.get_string_trimmed("Add anyway? (y/N): ").unwrap_or_default();
+        if confirm != "y" && confirm != "Y" {
+            println!("Cancelled.");
+            return false;
+        }
+        true
+    }
+
+    /// Followed series with an episode out before midnight, as
+    /// `GET /api/dashboard` counts them.  Providers that fail are skipped.
+    fn airing_today(&self) -> usize {
+        let midnight = schedule::end_of_today(chrono::Utc::now());
+        let followed: Vec<&MediaItem> = self.archive.iter().filter(|i| schedule::follows_airing(i)).collect();
+        let mut count = 0;
+        for provider in &self.searchers {
+            let ids: Vec<String> = followed
+                .iter()
+                .filter(|i| i.source.as_deref() == Some(provider.source()))
+                .filter_map(|i| i.external_id.clone())
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+            match provider.next_airing(&ids) {
+                Ok(found) => count += found.iter().filter(|next| next.airing_at < midnight).count(),
+                Err(e) => eprintln!("Airing lookup failed ({}): {e}", provider.name()),
+            }
+        }
+        count
     }
 
     pub fn run(&mut self) {
         println!("== KARS ARCHIVE SYSTEM ==");
+        if let Some(summary) = self.storage.dashboard_summary(Some(self.airing_today())) {
+            println!("{summary}");
+        }
 
         loop {
-            println!("\n[1] Search & Add  [2] Add Manual  [3] List  [4] Detail  [5] Score  [6] Complete  [7] Progress  [8] Tags  [9] Save & Exit");
+            println!("\n[1] Search & Add  [2] Add Manual  [3] List  [4] Detail  [5] Score  [6] Complete  [7] Progress  [8] Tags  [n] Notes  [d] Delete  [m] Merge  [u] Undo  [9] Save & Exit");
             let choice = match self.input.get_string_trimmed("Selection: ") {
                 Ok(c) => c,
                 Err(_) => continue,
@@ -61,6 +177,10 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                 "6" => self.complete_item(),
                 "7" => self.update_progress_flow(),
                 "8" => self.manage_tags_flow(),
+                "d" | "D" => self.delete_item_flow(),
+                "m" | "M" => self.merge_items_flow(),
+                "n" | "N" => self.manage_notes_flow(),
+                "u" | "U" => self.undo_flow(),
                 "9" => {
                     match self.storage.save_all(&self.archive) {
                         Ok(()) => println!("Archive saved. Goodbye!"),
@@ -98,7 +218,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                 )
             }
             "3" => {
-                println!("[1] Book  [2] WebNovel  [3] LightNovel  [4] Manga  [5] Manhwa  [6] Webtoon");
+                println!("[1] Book  [2] WebNovel  [3] LightNovel  [4] Manga  [5] Manhwa  [6] Webtoon  [7] Audiobook (minutes)");
                 let readable_kind = match self.input.get_string_trimmed("Kind: ") {
                     Ok(ref k) => match k.as_str() {
                         "1" => ReadableKind::Book,
@@ -107,6 +227,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                         "4" => ReadableKind::Manga,
                         "5" => ReadableKind::Manhwa,
                         "6" => ReadableKind::Webtoon,
+                        "7" => ReadableKind::Audiobook,
                         _ => { println!("Invalid kind."); return; }
                     },
                     Err(_) => return,
@@ -124,16 +245,12 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             _ => { println!("Invalid type."); return; }
         };
 
-        if self.has_duplicate(&title) {
-            println!("Warning: '{}' already exists in archive.", title);
-            let confirm = self.input.get_string_trimmed("Add anyway? (y/N): ").unwrap_or_default();
-            if confirm != "y" && confirm != "Y" {
-                println!("Cancelled.");
-                return;
-            }
+        let item = MediaItem::new(title.clone(), media_type);
+        if !self.confirm_if_duplicate(&item) {
+            return;
         }
 
-        let item = MediaItem::new(title.clone(), media_type);
+        self.remember(format!("add '{title}'"), Change::Added(item.id));
         self.archive.push(item);
         self.dirty = true;
         self.auto_save();
@@ -142,7 +259,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
 
     fn search_and_add_flow(&mut self) {
         println!("\nSearch category:");
-        println!("[1] Anime  [2] Manga/Manhwa  [3] Light Novel  [4] Movie  [5] Series  [6] Book");
+        println!("[1] Anime  [2] Manga/Manhwa  [3] Light Novel  [4] Movie  [5] Series  [6] Book  [7] Audiobook");
 
         let search_type = match self.input.get_string_trimmed("Category: ") {
             Ok(ref c) => match c.as_str() {
@@ -152,6 +269,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                 "4" => MediaSearchType::Movie,
                 "5" => MediaSearchType::Series,
                 "6" => MediaSearchType::Book,
+                "7" => MediaSearchType::Audiobook,
                 _ => { println!("Invalid category."); return; }
             },
             Err(_) => return,
@@ -193,9 +311,9 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
 
         println!("Searching {}...", self.searchers[provider_idx].name());
 
-        let results = match self.searchers[provider_idx].search(&query, search_type) {
+        let results = match self.searchers[provider_idx].search(&query, search_type, 1) {
             Ok(r) if r.is_empty() => { println!("No results found."); return; }
-            Ok(r) => r,
+            Ok(r) => r.into_iter().map(|r| r.in_language(self.title_language)).collect::<Vec<_>>(),
             Err(e) => { eprintln!("Search failed: {e}"); return; }
         };
 
@@ -214,16 +332,13 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
         let result = results.into_iter().nth(choice).unwrap();
         let title = result.title.clone();
 
-        if self.has_duplicate(&title) {
-            println!("Warning: '{}' already exists in archive.", title);
-            let confirm = self.input.get_string_trimmed("Add anyway? (y/N): ").unwrap_or_default();
-            if confirm != "y" && confirm != "Y" {
-                println!("Cancelled.");
-                return;
-            }
+        let mut item = result.into_media_item();
+        apply_status(&mut item.media_type, &self.default_status(search_type));
+        if !self.confirm_if_duplicate(&item) {
+            return;
         }
 
-        let item = result.into_media_item();
+        self.remember(format!("add '{title}'"), Change::Added(item.id));
         self.archive.push(item);
         self.dirty = true;
         self.auto_save();
@@ -257,13 +372,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
         }
 
         for (i, item) in self.archive.iter().enumerate() {
-            let status = format_status(&item.media_type);
-            let score = item
-                .get_score_display()
-                .map(|s| format!(" [{s:.1}]"))
-                .unwrap_or_default();
-            let completed = if item.is_completed() { " ✓" } else { "" };
-            println!("  {}. {}{}{} — {}", i + 1, item.title, score, completed, status);
+            println!("  {}. {}", i + 1, list_line(item, self.score_scale));
         }
     }
 
@@ -285,46 +394,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             Some(i) => i,
             None => return,
         };
-        let item = &self.archive[idx];
-
-        println!("\n--- {} ---", item.title);
-        println!("  ID:     {}", item.id);
-        println!("  Type:   {}", format_status(&item.media_type));
-
-        if let Some(s) = item.get_score_display() {
-            println!("  Score:  {s:.1}");
-        }
-        if let Some(g) = item.get_global_score_display() {
-            println!("  Global: {g:.1}");
-        }
-
-        match &item.media_type {
-            MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => {
-                if let Some(pct) = p.percent() {
-                    println!("  Progress: {pct:.1}%");
-                }
-            }
-            _ => {}
-        }
-
-        if item.is_completed() {
-            println!("  Status: Completed ✓");
-        }
-
-        if let Some(url) = &item.poster_url {
-            println!("  Poster: {url}");
-        }
-        if let Some(eid) = item.external_id {
-            println!("  ExtID:  {eid}");
-        }
-        if let Some(src) = &item.source {
-            println!("  Source: {src}");
-        }
-
-        if !item.tags.is_empty() {
-            let tags: Vec<&str> = item.tags.iter().map(|s| s.as_str()).collect();
-            println!("  Tags:   {}", tags.join(", "));
-        }
+        print_detail(&self.archive[idx], self.score_scale);
     }
 
     fn set_score_flow(&mut self) {
@@ -332,15 +402,22 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             Some(i) => i,
             None => return,
         };
-        let score: f32 = match self.input.parse_trimmed("Score (0.0 - 10.0): ") {
+        let scale = self.score_scale;
+        let prompt = format!("Score ({}): ", scale.range_label());
+        let score: f32 = match self.input.parse_trimmed(&prompt) {
             Ok(v) => v,
             Err(_) => { println!("Invalid score."); return; }
         };
-        self.archive[idx].set_score(score);
+        let stored = match scale.to_stored(score) {
+            Ok(s) => s,
+            Err(e) => { println!("{e}."); return; }
+        };
+        self.remember_edit(idx, "score");
+        self.archive[idx].score = Some(stored);
         self.dirty = true;
         self.auto_save();
-        println!("Score set to {:.1} for '{}'",
-            self.archive[idx].get_score_display().unwrap_or(0.0),
+        println!("Score set to {} for '{}'",
+            scale.format(self.archive[idx].score_in(scale).unwrap_or(0.0)),
             self.archive[idx].title,
         );
     }
@@ -354,6 +431,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             println!("'{}' is already completed.", self.archive[idx].title);
             return;
         }
+        self.remember_edit(idx, "complete");
         self.archive[idx].force_complete();
         let title = self.archive[idx].title.clone();
         self.dirty = true;
@@ -361,6 +439,61 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
         println!("'{title}' marked as completed ✓");
     }
 
+    fn delete_item_flow(&mut self) {
+        let idx = match self.select_item("Delete item #: ") {
+            Some(i) => i,
+            None => return,
+        };
+        let confirm = self
+            .input
+            .get_string_trimmed(&format!("Delete '{}'? (y/N): ", self.archive[idx].title))
+            .unwrap_or_default();
+        if confirm != "y" && confirm != "Y" {
+            println!("Cancelled.");
+            return;
+        }
+        let item = self.archive.remove(idx);
+        println!("Deleted: {}", item.title);
+        self.remember(format!("delete '{}'", item.title), Change::Removed(idx, item));
+        self.dirty = true;
+        self.auto_save();
+    }
+
+    /// Folds a duplicate entry into the item to keep (see `MediaItem::merge`)
+    /// and removes the duplicate.
+    fn merge_items_flow(&mut self) {
+        let keep_idx = match self.select_item("Keep item #: ") {
+            Some(i) => i,
+            None => return,
+        };
+        let dup_idx = match self.select_item("Merge duplicate #: ") {
+            Some(i) if i != keep_idx => i,
+            Some(_) => { println!("Cannot merge an item into itself."); return; }
+            None => return,
+        };
+        let prompt = format!(
+            "Merge '{}' into '{}' and delete it? (y/N): ",
+            self.archive[dup_idx].title, self.archive[keep_idx].title,
+        );
+        let confirm = self.input.get_string_trimmed(&prompt).unwrap_or_default();
+        if confirm != "y" && confirm != "Y" {
+            println!("Cancelled.");
+            return;
+        }
+
+        let keep_id = self.archive[keep_idx].id;
+        let duplicate = self.archive.remove(dup_idx);
+        let Some(keep) = self.archive.iter_mut().find(|i| i.id == keep_id) else { return };
+        let before = keep.clone();
+        keep.merge(duplicate.clone());
+        println!("Merged '{}' into '{}'.", duplicate.title, keep.title);
+
+        let label = format!("merge '{}' into '{}'", duplicate.title, before.title);
+        self.remember(label, Change::Merged(before, dup_idx, Box::new(duplicate)));
+        self.dirty = true;
+        self.auto_save();
+    }
+
     fn update_progress_flow(&mut self) {
         let idx = match self.select_item("Update progress for item #: ") {
             Some(i) => i,
@@ -384,6 +517,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             Err(_) => { println!("Invalid number."); return; }
         };
 
+        self.remember_edit(idx, "progress of");
         match &mut self.archive[idx].media_type {
             MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => {
                 p.current = new_current;
@@ -396,6 +530,7 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             }
             _ => unreachable!(),
         }
+        self.archive[idx].prefer_volumes = false;
         self.dirty = true;
         self.auto_save();
     }
@@ -424,6 +559,9 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                     Ok(t) if !t.is_empty() => t,
                     _ => { println!("Tag cannot be empty."); return; }
                 };
+                if !self.archive[idx].tags.contains(&tag) {
+                    self.remember_edit(idx, "tag");
+                }
                 if self.archive[idx].tags.insert(tag.clone()) {
                     self.dirty = true;
                     self.auto_save();
@@ -437,6 +575,9 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
                     Ok(t) if !t.is_empty() => t,
                     _ => return,
                 };
+                if self.archive[idx].tags.contains(&tag) {
+                    self.remember_edit(idx, "untag");
+                }
                 if self.archive[idx].tags.remove(&tag) {
                     self.dirty = true;
                     self.auto_save();
@@ -448,26 +589,632 @@ impl<S: StorageProvider, I: InputProvider> App<S, I> {
             _ => {}
         }
     }
+
+    fn manage_notes_flow(&mut self) {
+        let idx = match self.select_item("Notes for item #: ") {
+            Some(i) => i,
+            None => return,
+        };
+        let item = &self.archive[idx];
+        println!("\n--- {} ---", item.title);
+        match &item.notes {
+            Some(notes) => {
+                for line in notes.lines() {
+                    println!("  {line}");
+                }
+            }
+            None => println!("  No notes."),
+        }
+        if item.notes_spoiler {
+            println!("  (marked as spoiler)");
+        }
+        println!("[1] Write notes  [2] Clear notes  [3] Toggle spoiler  [0] Cancel");
+        let choice = match self.input.get_string_trimmed("Action: ") {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        match choice.as_str() {
+            "1" => {
+                println!("Enter notes (markdown). Finish with a single '.' on its own line.");
+                let mut lines = Vec::new();
+                loop {
+                    match self.input.get_string("> ") {
+                        Ok(line) if line.trim() == "." => break,
+                        Ok(line) => lines.push(line),
+                        Err(_) => return,
+                    }
+                }
+                let text = lines.join("\n");
+                self.remember_edit(idx, "notes of");
+                self.archive[idx].notes = if text.trim().is_empty() { None } else { Some(text) };
+                self.dirty = true;
+                self.auto_save();
+                println!("Notes saved.");
+            }
+            "2" => {
+                self.remember_edit(idx, "notes of");
+                self.archive[idx].notes = None;
+                self.archive[idx].notes_spoiler = false;
+                self.dirty = true;
+                self.auto_save();
+                println!("Notes cleared.");
+            }
+            "3" => {
+                self.remember_edit(idx, "spoiler flag of");
+                let flag = !self.archive[idx].notes_spoiler;
+                self.archive[idx].notes_spoiler = flag;
+                self.dirty = true;
+                self.auto_save();
+                println!("Spoiler flag {}.", if flag { "set" } else { "cleared" });
+            }
+            _ => {}
+        }
+    }
+
+    /// Bulk-adds a list of titles (e.g. a plain-text watchlist).  Each title
+    /// is looked up with the providers for `search_type`: a single exact
+    /// match is added automatically, ambiguous results are offered as a
+    /// choice, and titles without any result become manual entries.
+    pub fn import_titles(&mut self, titles: &[String], search_type: MediaSearchType, status: &str) {
+        let (mut matched, mut manual, mut skipped) = (0, 0, 0);
+
+        for title in titles {
+            let blank = MediaItem::new(title.clone(), search_type.blank_media_type());
+            if matcher::find_duplicate(&self.archive, &blank).is_some() {
+                println!("~ {title}: already in archive, skipped.");
+                skipped += 1;
+                continue;
+            }
+
+            let results = self.search_all(title, search_type);
+            let mut item = match matcher::match_title(title, &results) {
+                MatchOutcome::Exact(idx) => {
+                    let result = results.into_iter().nth(idx).unwrap();
+                    println!("+ {title}: matched on {}", result.source);
+                    matched += 1;
+                    result.into_media_item()
+                }
+                MatchOutcome::NoResults => {
+                    println!("+ {title}: no match, added manually");
+                    manual += 1;
+                    MediaItem::new(title.clone(), search_type.blank_media_type())
+                }
+                MatchOutcome::Ambiguous => {
+                    println!("\n? {title}: several candidates");
+                    for (i, r) in results.iter().enumerate() {
+                        println!("{}", r.display_line(i + 1));
+                    }
+                    println!("  [m] Add as manual entry  [0] Skip");
+                    let choice = self.input.get_string_trimmed("Pick #: ").unwrap_or_default();
+                    match choice.as_str() {
+                        "m" | "M" => {
+                            manual += 1;
+                            MediaItem::new(title.clone(), search_type.blank_media_type())
+                        }
+                        c => match c.parse::<usize>() {
+                            Ok(v) if v >= 1 && v <= results.len() => {
+                                matched += 1;
+                                results.into_iter().nth(v - 1).unwrap().into_media_item()
+                            }
+                            _ => {
+                                println!("  Skipped.");
+                                skipped += 1;
+                                continue;
+                            }
+                        },
+                    }
+                }
+            };
+
+            // The provider entry may reveal a match under another title.
+            if let Some(existing) = matcher::find_duplicate(&self.archive, &item) {
+                println!("~ {title}: already in archive as '{}', skipped.", existing.title);
+                skipped += 1;
+                continue;
+            }
+
+            apply_status(&mut item.media_type, status);
+            self.archive.push(item);
+            self.dirty = true;
+        }
+
+        self.auto_save();
+        println!("\nImport finished: {matched} matched, {manual} manual, {skipped} skipped.");
+    }
+
+    /// Status titles of `search_type` are added with unless one is given.
+    pub fn default_status(&self, search_type: MediaSearchType) -> String {
+        self.storage.default_status(search_type.as_str())
+    }
+
+    /// Adds a title without asking: the exact provider match if there is
+    /// one, otherwise the provider's best-ranked result; titles no provider
+    /// knows become manual entries.
+    pub fn add_title(&mut self, title: &str, search_type: MediaSearchType, status: &str) -> Result<(), String> {
+        let results = self.search_all(title, search_type);
+        let mut item = match matcher::match_title(title, &results) {
+            MatchOutcome::Exact(idx) => results.into_iter().nth(idx).unwrap().into_media_item(),
+            MatchOutcome::Ambiguous => results.into_iter().next().unwrap().into_media_item(),
+            MatchOutcome::NoResults => MediaItem::new(title.to_string(), search_type.blank_media_type()),
+        };
+        if let Some(existing) = matcher::find_duplicate(&self.archive, &item) {
+            return Err(format!("'{}' is already in the archive as '{}'.", item.title, existing.title));
+        }
+
+        apply_status(&mut item.media_type, status);
+        match &item.source {
+            Some(source) => println!("Added '{}' from {source}.", item.title),
+            None => println!("Added '{}' as a manual entry.", item.title),
+        }
+        self.archive.push(item);
+        self.dirty = true;
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Sets or moves the progress of the item matching `title`.  Moving
+    /// back stops at zero; moving forward stops at the known total, starts
+    /// a planned item and completes one that reaches its total (see
+    /// `MediaItem::advance`).
+    pub fn change_progress(&mut self, title: &str, change: ProgressChange, volumes: bool) -> Result<(), String> {
+        let idx = self.find_item(title)?;
+        let item = &mut self.archive[idx];
+        if volumes {
+            if !matches!(item.media_type, MediaItemType::Readable(..)) {
+                return Err(format!("'{}' is not a readable; only readables have volumes.", item.title));
+            }
+            match change {
+                ProgressChange::By(delta @ 1..) => {
+                    if !item.advance_volumes(u32::try_from(delta).unwrap_or(u32::MAX)) {
+                        return Err(format!("'{}' is already completed.", item.title));
+                    }
+                }
+                change => {
+                    let v = item.volumes.get_or_insert(Progress { current: 0, total: None });
+                    v.current = match change {
+                        ProgressChange::By(delta) => (v.current as i64 + delta).max(0) as u32,
+                        ProgressChange::To(n) => n,
+                    };
+                    item.prefer_volumes = true;
+                }
+            }
+        } else {
+            let (MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _)) = &mut item.media_type else {
+                return Err(format!("'{}' is a movie; movies don't have progress tracking.", item.title));
+            };
+            match change {
+                ProgressChange::By(delta @ 1..) => {
+                    if !item.advance(u32::try_from(delta).unwrap_or(u32::MAX)) {
+                        return Err(format!("'{}' is already completed.", item.title));
+                    }
+                }
+                ProgressChange::By(delta) => p.current = (p.current as i64 + delta).max(0) as u32,
+                ProgressChange::To(n) => p.current = n,
+            }
+            item.prefer_volumes = false;
+        }
+        println!("{} — {}", item.title, format_status(item));
+        self.dirty = true;
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Marks the item matching `title` as completed.
+    pub fn complete_title(&mut self, title: &str) -> Result<(), String> {
+        let idx = self.find_item(title)?;
+        let item = &mut self.archive[idx];
+        if item.is_completed() {
+            return Err(format!("'{}' is already completed.", item.title));
+        }
+        item.force_complete();
+        println!("'{}' marked as completed ✓", item.title);
+        self.dirty = true;
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Hides the item matching `title` from lists and searches, or shows
+    /// it again.
+    pub fn set_hidden(&mut self, title: &str, hidden: bool) -> Result<(), String> {
+        let idx = self.find_item(title)?;
+        let item = &mut self.archive[idx];
+        if item.hidden == hidden {
+            let state = if hidden { "hidden" } else { "not hidden" };
+            return Err(format!("'{}' is already {state}.", item.title));
+        }
+        item.hidden = hidden;
+        let done = if hidden { "hidden" } else { "shown again" };
+        println!("'{}' {done}", item.title);
+        self.dirty = true;
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Sets a custom field of the item matching `title`, or removes it when
+    /// `value` is None or blank.
+    pub fn set_custom_field(&mut self, title: &str, name: &str, value: Option<&str>) -> Result<(), String> {
+        let idx = self.find_item(title)?;
+        let item = &mut self.archive[idx];
+        let name = name.trim();
+        match value.map(str::trim).filter(|v| !v.is_empty()) {
+            Some(value) => {
+                custom_fields::validate(name, value)?;
+                if !item.custom_fields.contains_key(name) && item.custom_fields.len() >= MAX_CUSTOM_FIELDS {
+                    return Err(format!("Items have at most {MAX_CUSTOM_FIELDS} custom fields"));
+                }
+                item.custom_fields.insert(name.to_string(), value.to_string());
+                println!("'{}': {name} = {value}", item.title);
+            }
+            None => {
+                if item.custom_fields.remove(name).is_none() {
+                    return Err(format!("'{}' has no field '{name}'.", item.title));
+                }
+                println!("'{}': removed {name}", item.title);
+            }
+        }
+        self.dirty = true;
+        self.auto_save();
+        Ok(())
+    }
+
+    /// Prints one custom field of the item matching `title`, or all of
+    /// them as `name: value` lines.
+    pub fn print_custom_fields(&self, title: &str, name: Option<&str>) -> Result<(), String> {
+        let item = &self.archive[self.find_item(title)?];
+        let out = match name.map(str::trim) {
+            Some(name) => match item.custom_fields.get(name) {
+                Some(value) => format!("{value}\n"),
+                None => return Err(format!("'{}' has no field '{name}'.", item.title)),
+            },
+            None => item
+                .custom_fields
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}\n"))
+                .collect(),
+        };
+        emit(&out)
+    }
+
+    /// Prints the archive, optionally only the items with an API status
+    /// (`watching`, `plan_to_read`, ...).
+    pub fn print_list(&self, status: Option<&str>, include_hidden: bool, format: OutputFormat) -> Result<(), String> {
+        let items: Vec<&MediaItem> = self
+            .archive
+            .iter()
+            .filter(|i| include_hidden || !i.hidden)
+            .filter(|i| status.is_none_or(|s| status_of(&i.media_type) == s))
+            .collect();
+        self.print_items(&items, format)
+    }
+
+    /// Prints the items matching a search such as `status:watching
+    /// score>=8 frieren` (see `core::query`).
+    pub fn print_query(&self, search: &str, include_hidden: bool, format: OutputFormat) -> Result<(), String> {
+        let sql = query::parse(search)?.to_sql(self.score_scale)?;
+        let items = self.storage.query_items(&sql).map_err(|e| e.to_string())?;
+        let items: Vec<&MediaItem> = items.iter().filter(|i| include_hidden || !i.hidden).collect();
+        self.print_items(&items, format)
+    }
+
+    fn print_items(&self, items: &[&MediaItem], format: OutputFormat) -> Result<(), String> {
+        let out = match format {
+            OutputFormat::Text => items.iter().map(|i| list_line(i, self.score_scale) + "\n").collect(),
+            OutputFormat::Json => {
+                let api: Vec<ApiMediaItem> = items
+                    .iter()
+                    .map(|i| ApiMediaItem::from(*i).in_scale(self.score_scale))
+                    .collect();
+                to_json(&api)?
+            }
+            OutputFormat::Tsv => tsv_items(items, self.score_scale),
+        };
+        emit(&out)
+    }
+
+    /// Prints the item matching `title`.
+    pub fn print_item(&self, title: &str, format: OutputFormat) -> Result<(), String> {
+        let item = &self.archive[self.find_item(title)?];
+        match format {
+            OutputFormat::Text => {
+                print_detail(item, self.score_scale);
+                Ok(())
+            }
+            OutputFormat::Json => emit(&to_json(&ApiMediaItem::from(item).in_scale(self.score_scale))?),
+            OutputFormat::Tsv => emit(&tsv_items(&[item], self.score_scale)),
+        }
+    }
+
+    /// Prints the library totals, the numbers of `GET /api/stats`.
+    pub fn print_stats(&self, format: OutputFormat) -> Result<(), String> {
+        let api_items: Vec<ApiMediaItem> = self.archive.iter().map(ApiMediaItem::from).collect();
+        let stats = ApiStats::from_items(&api_items);
+        if format == OutputFormat::Json {
+            return emit(&to_json(&stats)?);
+        }
+
+        let fields = match serde_json::to_value(&stats).map_err(|e| e.to_string())? {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!("ApiStats serializes to an object"),
+        };
+        let out = if format == OutputFormat::Tsv {
+            let names: Vec<&str> = fields.keys().map(String::as_str).collect();
+            let values: Vec<String> = fields.values().map(|v| v.to_string()).collect();
+            format!("{}\n{}\n", names.join("\t"), values.join("\t"))
+        } else {
+            fields.iter().map(|(name, value)| format!("{name:<16}{value}\n")).collect()
+        };
+        emit(&out)
+    }
+
+    /// Index of the one item whose title (or an alternative title) is
+    /// `title`, ignoring case and punctuation, or failing that contains it.
+    fn find_item(&self, title: &str) -> Result<usize, String> {
+        let wanted = matcher::normalize_title(title);
+        if wanted.is_empty() {
+            return Err("Title cannot be empty.".into());
+        }
+        let titles = |item: &MediaItem| -> Vec<String> {
+            std::iter::once(&item.title)
+                .chain(&item.alt_titles)
+                .map(|t| matcher::normalize_title(t))
+                .collect()
+        };
+
+        let exact: Vec<usize> = (0..self.archive.len())
+            .filter(|&i| titles(&self.archive[i]).contains(&wanted))
+            .collect();
+        let found = if exact.is_empty() {
+            (0..self.archive.len())
+                .filter(|&i| titles(&self.archive[i]).iter().any(|t| t.contains(&wanted)))
+                .collect()
+        } else {
+            exact
+        };
+
+        match found.as_slice() {
+            [] => Err(format!("No item matches '{title}'.")),
+            [idx] => Ok(*idx),
+            several => {
+                let names: Vec<&str> = several.iter().map(|&i| self.archive[i].title.as_str()).collect();
+                Err(format!("'{title}' matches several items: {}.", names.join(", ")))
+            }
+        }
+    }
+
+    /// Queries every provider supporting `search_type` in order and returns
+    /// the first non-empty result list, titled in the configured language.
+    fn search_all(&self, query: &str, search_type: MediaSearchType) -> Vec<SearchResult> {
+        for searcher in self
+            .searchers
+            .iter()
+            .filter(|s| s.supported_types().contains(&search_type))
+        {
+            match searcher.search(query, search_type, 1) {
+                Ok(results) if !results.is_empty() => {
+                    return results.into_iter().map(|r| r.in_language(self.title_language)).collect();
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("  {} search failed: {e}", searcher.name()),
+            }
+        }
+        Vec::new()
+    }
+}
+
+/// A progress update given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProgressChange {
+    /// `12`: set the progress.
+    To(u32),
+    /// `+1` or `-2`: move it.
+    By(i64),
+}
+
+impl std::str::FromStr for ProgressChange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid progress '{s}'; expected e.g. 12, +1 or -1.");
+        if s.starts_with(['+', '-']) {
+            s.parse().map(ProgressChange::By).map_err(|_| invalid())
+        } else {
+            s.parse().map(ProgressChange::To).map_err(|_| invalid())
+        }
+    }
+}
+
+/// The detail view of one item.
+fn print_detail(item: &MediaItem, scale: ScoreScale) {
+    println!("\n--- {} ---", item.title);
+    println!("  ID:     {}", item.id);
+    println!("  Type:   {}", format_status(item));
+
+    if let Some(s) = item.score_in(scale) {
+        println!("  Score:  {}", scale.format(s));
+    }
+    if let Some(g) = item.global_score.map(|g| scale.scaled(g)) {
+        println!("  Global: {}", scale.format(g));
+    }
+
+    match &item.media_type {
+        MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => {
+            if let Some(pct) = p.percent() {
+                println!("  Progress: {pct:.1}%");
+            }
+        }
+        _ => {}
+    }
+    if let Some(v) = &item.volumes {
+        let total = v.total.map_or("?".into(), |t| t.to_string());
+        println!("  Volumes: {}/{total}", v.current);
+    }
+
+    if item.is_completed() {
+        println!("  Status: Completed ✓");
+    }
+    if item.favorite {
+        println!("  Favorite: ★");
+    }
+    if item.hidden {
+        println!("  Hidden: yes");
+    }
+    if item.repeat_count > 0 {
+        println!("  Repeats: {}", item.repeat_count);
+    }
+
+    if let Some(url) = &item.poster_url {
+        println!("  Poster: {url}");
+    }
+    if let Some(eid) = &item.external_id {
+        println!("  ExtID:  {eid}");
+    }
+    if let Some(src) = &item.source {
+        println!("  Source: {src}");
+    }
+    if let Some(creator) = &item.creator {
+        println!("  Creator: {creator}");
+    }
+
+    if let Some(overview) = &item.overview {
+        println!("  Overview:");
+        for line in overview.lines() {
+            println!("    {line}");
+        }
+    }
+
+    if !item.tags.is_empty() {
+        let tags: Vec<&str> = item.tags.iter().map(|s| s.as_str()).collect();
+        println!("  Tags:   {}", tags.join(", "));
+    }
+
+    for (name, value) in &item.custom_fields {
+        println!("  {name}: {value}");
+    }
+
+    if let Some(notes) = &item.notes {
+        let label = if item.notes_spoiler { "Notes (spoilers):" } else { "Notes:" };
+        println!("  {label}");
+        for line in notes.lines() {
+            println!("    {line}");
+        }
+    }
 }
 
-fn format_status(media_type: &MediaItemType) -> String {
-    match media_type {
+/// How the non-interactive commands print their results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// For people.
+    Text,
+    /// The API's JSON.
+    Json,
+    /// Tab-separated values with a header row.
+    Tsv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(format!("Unknown format '{s}'; expected text, json or tsv.")),
+        }
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value)
+        .map(|json| json + "\n")
+        .map_err(|e| e.to_string())
+}
+
+/// Writes command output to stdout.  A reader that went away (`| head`)
+/// is not an error.
+fn emit(out: &str) -> Result<(), String> {
+    use std::io::Write;
+    match std::io::stdout().lock().write_all(out.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Columns of the TSV item lists.
+const TSV_COLUMNS: &[&str] = &[
+    "id", "title", "media_type", "status", "progress", "total", "score", "global_score",
+    "source", "external_id", "tags", "favorite", "repeat_count",
+];
+
+fn tsv_items(items: &[&MediaItem], scale: ScoreScale) -> String {
+    let mut out = TSV_COLUMNS.join("\t") + "\n";
+    for item in items {
+        out += &tsv_row(&ApiMediaItem::from(*item).in_scale(scale)).join("\t");
+        out.push('\n');
+    }
+    out
+}
+
+/// The `TSV_COLUMNS` of one item; missing values are empty.
+fn tsv_row(item: &ApiMediaItem) -> Vec<String> {
+    let opt = |v: Option<String>| v.unwrap_or_default();
+    let mut tags = item.tags.clone();
+    tags.sort();
+    [
+        item.id.clone(),
+        item.title.clone(),
+        item.media_type.clone(),
+        item.status.clone(),
+        item.progress.to_string(),
+        opt(item.total_episodes.map(|t| t.to_string())),
+        opt(item.score.map(|s| s.to_string())),
+        opt(item.global_score.map(|s| s.to_string())),
+        opt(item.source.clone()),
+        opt(item.external_id.clone()),
+        tags.join(","),
+        item.favorite.to_string(),
+        item.repeat_count.to_string(),
+    ]
+    .into_iter()
+    .map(|field| field.replace(['\t', '\n', '\r'], " "))
+    .collect()
+}
+
+/// `Title [score] ✓ — status` line of the item lists.
+fn list_line(item: &MediaItem, scale: ScoreScale) -> String {
+    let status = format_status(item);
+    let score = item
+        .score_in(scale)
+        .map(|s| format!(" [{}]", scale.format(s)))
+        .unwrap_or_default();
+    let completed = if item.is_completed() { " ✓" } else { "" };
+    format!("{}{}{} — {}", item.title, score, completed, status)
+}
+
+fn format_status(item: &MediaItem) -> String {
+    match &item.media_type {
         MediaItemType::Movie(s) => format!("Movie ({})", watch_label(s)),
         MediaItemType::Series(p, s) => {
-            let progress = format_progress(p);
+            let progress = format_progress(p, "");
             format!("Series {progress} ({})", watch_label(s))
         }
-        MediaItemType::Readable(kind, p, s) => {
-            let progress = format_progress(p);
+        MediaItemType::Readable(kind, _, s) => {
+            let progress = match item.shown_progress() {
+                Some((p, "volumes")) => format_progress(p, "vol. "),
+                Some((p, "minutes")) => format_listened(p),
+                Some((p, _)) => format_progress(p, ""),
+                None => String::new(),
+            };
             format!("{kind:?} {progress} ({})", read_label(s))
         }
     }
 }
 
-fn format_progress(p: &Progress) -> String {
+/// `[3/12] 25%`, with `prefix` before the numbers (`[vol. 3/12]`).
+fn format_progress(p: &Progress, prefix: &str) -> String {
     let base = match p.total {
-        Some(t) => format!("[{}/{}]", p.current, t),
-        None => format!("[{}/?]", p.current),
+        Some(t) => format!("[{prefix}{}/{}]", p.current, t),
+        None => format!("[{prefix}{}/?]", p.current),
     };
     match p.percent() {
         Some(pct) => format!("{base} {pct:.0}%"),
@@ -475,6 +1222,16 @@ fn format_progress(p: &Progress) -> String {
     }
 }
 
+/// `[2h 05m/11h 30m] 18%` for an audiobook.
+fn format_listened(p: &Progress) -> String {
+    let total = p.total.map_or("?".into(), format_minutes);
+    let base = format!("[{}/{total}]", format_minutes(p.current));
+    match p.percent() {
+        Some(pct) => format!("{base} {pct:.0}%"),
+        None => base,
+    }
+}
+
 fn watch_label(s: &WatchStatus) -> &'static str {
     match s {
         WatchStatus::Watching => "Watching",
@@ -493,4 +1250,36 @@ fn read_label(s: &ReadStatus) -> &'static str {
         ReadStatus::OnHold => "On Hold",
         ReadStatus::Dropped => "Dropped",
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_change_parses_moves_and_values() {
+        assert_eq!("12".parse(), Ok(ProgressChange::To(12)));
+        assert_eq!("+1".parse(), Ok(ProgressChange::By(1)));
+        assert_eq!("-3".parse(), Ok(ProgressChange::By(-3)));
+        assert!("abc".parse::<ProgressChange>().is_err());
+        assert!("+".parse::<ProgressChange>().is_err());
+    }
+
+    #[test]
+    fn test_tsv_row_flattens_tabs_and_newlines() {
+        let mut item = MediaItem::new(
+            "Tab\tand\nnewline".into(),
+            MediaItemType::Series(Progress { current: 3, total: Some(12) }, WatchStatus::Watching),
+        );
+        item.set_score(8.5);
+        item.tags.insert("cozy".into());
+        item.tags.insert("anime".into());
+
+        let row = tsv_row(&ApiMediaItem::from(&item));
+        assert_eq!(row.len(), TSV_COLUMNS.len());
+        assert_eq!(row[1], "Tab and newline");
+        assert_eq!(&row[3..7], ["watching", "3", "12", "8.5"]);
+        assert_eq!(row[7], "");
+        assert_eq!(row[10], "anime,cozy");
+    }
+}
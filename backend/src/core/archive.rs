@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::core::history::HistoryKind;
+use crate::core::models::MediaItem;
+use crate::core::settings::Settings;
+
+/// Version of the archive format written by `GET /api/export`.  Bump it
+/// when a change would make older servers misread an archive.
+pub const ARCHIVE_VERSION: u32 = 3;
+
+/// The whole library in kars' own JSON format: every item with all of its
+/// fields, the per-item activity and the settings.  Jobs, notifications
+/// and provider diagnostics are server state and are not part of it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Archive {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub items: Vec<MediaItem>,
+    #[serde(flatten)]
+    pub activity: Activity,
+    /// Missing when the archive should leave the settings alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Settings>,
+}
+
+/// Everything recorded about items besides the items themselves.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct Activity {
+    pub episodes: Vec<ArchivedEpisode>,
+    pub progress: Vec<ArchivedProgress>,
+    pub history: Vec<ArchivedHistory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ArchivedEpisode {
+    pub item_id: Uuid,
+    pub episode: u32,
+    pub watched: bool,
+    pub watched_at: Option<DateTime<Utc>>,
+}
+
+/// A row of the progress log that feeds activity and "currently into".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ArchivedProgress {
+    pub item_id: Uuid,
+    pub progress: u32,
+    pub delta: i64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A row of the audit log.  `item_id` may name an item that no longer
+/// exists, since deletions are logged too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ArchivedHistory {
+    pub item_id: Uuid,
+    pub title: String,
+    pub event: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// How an import treats the items already stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep existing items and merge duplicates into them.
+    #[default]
+    Merge,
+    /// Wipe the library and restore the archive exactly.
+    Replace,
+}
+
+impl Archive {
+    /// Brings items of an older archive up to date.  Version 1 stored
+    /// external ids as numbers, Open Library's without the `OL` and `W`
+    /// of the work key; up to version 2 favorites were a `favorite` tag.
+    pub fn upgrade(&mut self) {
+        for item in &mut self.items {
+            upgrade_item(item, self.version);
+        }
+    }
+
+    /// Checks that this server understands the archive, that no item id
+    /// appears twice and that every audit row has a known event.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version == 0 || self.version > ARCHIVE_VERSION {
+            return Err(format!(
+                "Unsupported archive version {} (expected at most {ARCHIVE_VERSION})",
+                self.version
+            ));
+        }
+        let mut ids = HashSet::new();
+        if let Some(dup) = self.items.iter().find(|i| !ids.insert(i.id)) {
+            return Err(format!("Item {} appears more than once", dup.id));
+        }
+        let history = &self.activity.history;
+        if let Some(h) = history.iter().find(|h| HistoryKind::parse(&h.event).is_none()) {
+            return Err(format!("Unknown history event '{}'", h.event));
+        }
+        Ok(())
+    }
+}
+
+/// Brings one item written by archive `version` up to date (see
+/// `Archive::upgrade`).
+fn upgrade_item(item: &mut MediaItem, version: u32) {
+    if version < 2
+        && item.source.as_deref() == Some("openlibrary")
+        && let Some(id) = &mut item.external_id
+        && id.chars().all(|c| c.is_ascii_digit())
+    {
+        *id = format!("OL{id}W");
+    }
+    if version < 3 && item.tags.remove("favorite") {
+        item.favorite = true;
+    }
+}
+
+/// Reads an item snapshot kept outside an archive, such as the `before`
+/// of a job's items.  Snapshots carry no version; ones taken before
+/// favorites had their own field lack it and are upgraded like a version 1
+/// archive, which leaves ids already in today's form alone.
+pub fn read_snapshot(json: &str) -> Result<MediaItem, serde_json::Error> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let version = if value.get("favorite").is_some() { ARCHIVE_VERSION } else { 1 };
+    let mut item = serde_json::from_value(value)?;
+    upgrade_item(&mut item, version);
+    Ok(item)
+}
+
+impl Activity {
+    /// Prepares activity for a merge import.  `targets` maps each imported
+    /// item id to the stored item it ended up in.  Episode marks follow
+    /// their item; progress and audit rows are only kept for `created`
+    /// items, so importing the same archive twice adds them once.
+    pub fn remap(self, targets: &HashMap<Uuid, Uuid>, created: &HashSet<Uuid>) -> Activity {
+        let episodes = self
+            .episodes
+            .into_iter()
+            .filter_map(|mut e| {
+                e.item_id = *targets.get(&e.item_id)?;
+                Some(e)
+            })
+            .collect();
+        Activity {
+            episodes,
+            progress: self
+                .progress
+                .into_iter()
+                .filter(|p| created.contains(&p.item_id))
+                .collect(),
+            history: self
+                .history
+                .into_iter()
+                .filter(|h| created.contains(&h.item_id))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(item_id: Uuid, episode: u32) -> ArchivedEpisode {
+        ArchivedEpisode { item_id, episode, watched: true, watched_at: None }
+    }
+
+    #[test]
+    fn test_upgrade_reads_version_1_ids() {
+        use crate::core::models::{MediaItemType, Progress, ReadStatus, ReadableKind};
+
+        let progress = Progress { current: 0, total: None };
+        let mut item = MediaItem::new(
+            "Dune".into(),
+            MediaItemType::Readable(ReadableKind::Book, progress, ReadStatus::PlanToRead),
+        );
+        item.source = Some("openlibrary".into());
+        item.tags.insert("favorite".into());
+        let mut json = serde_json::to_value(&item).unwrap();
+        json["externalId"] = serde_json::json!(27448);
+        let json = serde_json::json!({ "version": 1, "exported_at": Utc::now(), "items": [json] });
+
+        let mut archive: Archive = serde_json::from_value(json).unwrap();
+        archive.upgrade();
+        assert_eq!(archive.items[0].external_id.as_deref(), Some("OL27448W"));
+        assert!(archive.items[0].favorite && archive.items[0].tags.is_empty());
+    }
+
+    #[test]
+    fn test_remap_follows_merges_and_keeps_logs_of_new_items() {
+        let (merged, kept, new, unknown) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let progress = |item_id| ArchivedProgress {
+            item_id,
+            progress: 1,
+            delta: 1,
+            recorded_at: Utc::now(),
+        };
+        let activity = Activity {
+            episodes: vec![episode(merged, 1), episode(new, 2), episode(unknown, 3)],
+            progress: vec![progress(merged), progress(new)],
+            history: Vec::new(),
+        };
+
+        let targets = HashMap::from([(merged, kept), (new, new)]);
+        let remapped = activity.remap(&targets, &HashSet::from([new]));
+        assert_eq!(remapped.episodes, vec![episode(kept, 1), episode(new, 2)]);
+        assert_eq!(remapped.progress.len(), 1);
+        assert_eq!(remapped.progress[0].item_id, new);
+    }
+}
@@ -0,0 +1,117 @@
+//! What Sonarr and Radarr have downloaded, matched to the library so
+//! planned movies and series already on disk can be flagged as ready.
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::core::matcher::normalize_title;
+use crate::core::models::{MediaItem, MediaItemType, WatchStatus};
+use crate::core::search::SearchError;
+
+/// A movie with its file, or a series with at least one episode file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Download {
+    pub title: String,
+    pub alt_titles: Vec<String>,
+    /// Ids in `MediaItem::external_refs` form, e.g. `tvdb:81189`.
+    pub refs: Vec<String>,
+    pub series: bool,
+}
+
+pub trait MediaManager: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Everything with a file on disk.  Blocking.
+    fn downloads(&self) -> Result<Vec<Download>, SearchError>;
+}
+
+/// Planned movies and series that are downloaded.
+pub fn ready_to_watch(item: &MediaItem) -> bool {
+    item.downloaded
+        && matches!(
+            item.media_type,
+            MediaItemType::Movie(WatchStatus::PlanToWatch) | MediaItemType::Series(_, WatchStatus::PlanToWatch)
+        )
+}
+
+/// The items the downloads belong to: the movie (or series) sharing an
+/// id, otherwise the only one with any of the download's titles.
+pub fn downloaded_items(items: &[MediaItem], downloads: &[Download]) -> HashSet<Uuid> {
+    let mut found = HashSet::new();
+    for download in downloads {
+        let candidates: Vec<&MediaItem> = items
+            .iter()
+            .filter(|item| match item.media_type {
+                MediaItemType::Movie(_) => !download.series,
+                MediaItemType::Series(..) => download.series,
+                MediaItemType::Readable(..) => false,
+            })
+            .collect();
+        if let Some(item) = candidates
+            .iter()
+            .find(|item| download.refs.iter().any(|r| item.external_refs.contains(r)))
+        {
+            found.insert(item.id);
+            continue;
+        }
+
+        let titles: Vec<String> = std::iter::once(&download.title)
+            .chain(&download.alt_titles)
+            .map(|t| normalize_title(t))
+            .filter(|t| !t.is_empty())
+            .collect();
+        let mut named = candidates.iter().filter(|item| {
+            std::iter::once(&item.title)
+                .chain(&item.alt_titles)
+                .any(|t| titles.contains(&normalize_title(t)))
+        });
+        if let (Some(item), None) = (named.next(), named.next()) {
+            found.insert(item.id);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Progress;
+
+    #[test]
+    fn test_downloaded_items_match_by_ref_then_title() {
+        let mut dune = MediaItem::new("Dune: Part One".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        dune.external_refs.insert("tmdb:movie:438631".into());
+        let severance = MediaItem::new(
+            "Severance".into(),
+            MediaItemType::Series(Progress { current: 0, total: None }, WatchStatus::PlanToWatch),
+        );
+        let shogun = || {
+            MediaItem::new("Shogun".into(), MediaItemType::Series(Progress { current: 0, total: None }, WatchStatus::PlanToWatch))
+        };
+        let items = vec![
+            dune,
+            severance,
+            shogun(),
+            shogun(),
+            MediaItem::new("Severance".into(), MediaItemType::Movie(WatchStatus::PlanToWatch)),
+        ];
+        let download = |title: &str, refs: &[&str], series| Download {
+            title: title.into(),
+            alt_titles: Vec::new(),
+            refs: refs.iter().map(|r| r.to_string()).collect(),
+            series,
+        };
+
+        let found = downloaded_items(
+            &items,
+            &[download("Dune", &["tmdb:movie:438631"], false), download("Severance", &[], true), download("Shogun", &[], true)],
+        );
+        assert_eq!(found, HashSet::from([items[0].id, items[1].id]));
+
+        let mut item = items[1].clone();
+        item.downloaded = true;
+        assert!(ready_to_watch(&item));
+        item.media_type = MediaItemType::Series(Progress { current: 1, total: None }, WatchStatus::Watching);
+        assert!(!ready_to_watch(&item));
+    }
+}
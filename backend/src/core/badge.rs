@@ -0,0 +1,114 @@
+//! Small SVG badges in the style of shields.io ("kars | 412 completed"),
+//! for embedding live library numbers in a README or blog.
+
+use crate::core::api_types::ApiStats;
+
+/// Metrics a badge can show, as named in `?metric=`.
+pub const METRICS: &[&str] = &[
+    "total", "completed", "watching", "planned", "on_hold", "dropped", "movies", "series", "anime",
+    "readable", "rewatches",
+];
+
+/// Named colors, as shields.io spells them.
+const COLORS: &[(&str, &str)] = &[
+    ("brightgreen", "#4c1"),
+    ("green", "#97ca00"),
+    ("yellow", "#dfb317"),
+    ("orange", "#fe7d37"),
+    ("red", "#e05d44"),
+    ("blue", "#007ec6"),
+    ("lightgrey", "#9f9f9f"),
+    ("grey", "#555"),
+];
+
+pub const DEFAULT_COLOR: &str = "blue";
+
+/// The count of `metric` and the words after it, e.g. `(412,
+/// "completed")`.  None for an unknown metric.
+pub fn metric(stats: &ApiStats, metric: &str) -> Option<(u64, &'static str)> {
+    let (count, words) = match metric {
+        "total" => (stats.total, "items"),
+        "completed" => (stats.completed, "completed"),
+        "watching" => (stats.watching, "in progress"),
+        "planned" => (stats.plan_to_watch, "planned"),
+        "on_hold" => (stats.on_hold, "on hold"),
+        "dropped" => (stats.dropped, "dropped"),
+        "movies" => (stats.movies, "movies"),
+        "series" => (stats.series, "series"),
+        "anime" => (stats.anime, "anime"),
+        "readable" => (stats.readable, "books & manga"),
+        "rewatches" => (stats.total_rewatches as usize, "rewatches"),
+        _ => return None,
+    };
+    Some((count as u64, words))
+}
+
+/// The fill for `color`: a name from the shields.io palette or a 3 or 6
+/// digit hex color with or without `#`.  None for anything else, which
+/// keeps arbitrary text out of the SVG.
+pub fn color(color: &str) -> Option<String> {
+    if let Some((_, hex)) = COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(color)) {
+        return Some(hex.to_string());
+    }
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    (matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("#{hex}"))
+}
+
+/// Rough width of `text` in 11 px Verdana, in tenths of a pixel.  The
+/// SVG stretches the text to it, so small errors only change spacing.
+fn text_width(text: &str) -> u32 {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '|' | '!' | '\'' => 30,
+            'f' | 't' | 'r' | 'I' | ' ' | '(' | ')' | '-' => 45,
+            'm' | 'w' | 'M' | 'W' => 105,
+            c if c.is_ascii_uppercase() => 80,
+            _ => 68,
+        })
+        .sum()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A flat badge with `label` on grey and `message` on `fill`, which must
+/// come from [`color`].
+pub fn render(label: &str, message: &str, fill: &str) -> String {
+    let (label_text, message_text) = (text_width(label), text_width(message));
+    // Each side is padded by 5 px; text is positioned in tenths.
+    let label_width = label_text.div_ceil(10) + 10;
+    let message_width = message_text.div_ceil(10) + 10;
+    let width = label_width + message_width;
+    let (label_x, message_x) = (label_width * 5, label_width * 10 + message_width * 5);
+    let (label, message) = (escape(label), escape(message));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{fill}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="110" transform="scale(.1)"><text x="{label_x}" y="150" fill="#010101" fill-opacity=".3" textLength="{label_text}">{label}</text><text x="{label_x}" y="140" textLength="{label_text}">{label}</text><text x="{message_x}" y="150" fill="#010101" fill-opacity=".3" textLength="{message_text}">{message}</text><text x="{message_x}" y="140" textLength="{message_text}">{message}</text></g></svg>"##
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_sizes_and_escapes_text() {
+        let stats = ApiStats { completed: 412, ..ApiStats::default() };
+        assert_eq!(metric(&stats, "completed"), Some((412, "completed")));
+        assert_eq!(metric(&stats, "score"), None);
+        assert_eq!(color("Orange").as_deref(), Some("#fe7d37"));
+        assert_eq!(color("#a1B2c3").as_deref(), Some("#a1B2c3"));
+        assert_eq!(color("f00").as_deref(), Some("#f00"));
+        assert_eq!(color("red\"/><script>"), None);
+
+        let svg = render("kars", "412 completed", "#4c1");
+        // "kars" is 68 + 68 + 45 + 68 tenths wide: 25 px, padded to 35.
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="#));
+        assert!(svg.contains(r##"<rect width="35" height="20" fill="#555"/>"##));
+        assert!(svg.contains("<title>kars: 412 completed</title>"));
+        assert!(render("a&b", "<1>", "#4c1").contains("<title>a&amp;b: &lt;1&gt;</title>"));
+    }
+}
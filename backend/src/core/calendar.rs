@@ -0,0 +1,110 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// When a calendar event happens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTime {
+    /// Starts at a moment and lasts `minutes`.
+    At { start: DateTime<Utc>, minutes: u32 },
+    /// Takes the whole day, e.g. a release date.
+    Day(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    /// Stable across feed refreshes, so calendar apps update the event
+    /// instead of adding a copy.
+    pub uid: String,
+    pub summary: String,
+    pub time: EventTime,
+}
+
+/// Renders events as an iCalendar (RFC 5545) document.
+pub fn to_ics(name: &str, events: &[CalendarEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//kars//Media Archive//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+    let stamp = now.format("%Y%m%dT%H%M%SZ");
+    for event in events {
+        lines.push("BEGIN:VEVENT".into());
+        lines.push(format!("UID:{}", escape(&event.uid)));
+        lines.push(format!("DTSTAMP:{stamp}"));
+        match &event.time {
+            EventTime::At { start, minutes } => {
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")));
+                lines.push(format!("DURATION:PT{minutes}M"));
+            }
+            EventTime::Day(day) => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", day.format("%Y%m%d")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.push("END:VEVENT".into());
+    }
+    lines.push("END:VCALENDAR".into());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold(&line));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Escapes text values: backslashes, separators and newlines.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits a content line into pieces of at most 75 octets, each
+/// continuation starting with a space.
+fn fold(line: &str) -> String {
+    const LIMIT: usize = 75;
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ics_escapes_and_folds() {
+        let now = DateTime::parse_from_rfc3339("2026-10-16T10:00:00Z").unwrap().to_utc();
+        let events = vec![
+            CalendarEvent {
+                uid: "airing-1-6@kars".into(),
+                summary: "Frieren, episode 6".into(),
+                time: EventTime::At { start: now, minutes: 30 },
+            },
+            CalendarEvent {
+                uid: "release-2@kars".into(),
+                summary: "x".repeat(80),
+                time: EventTime::Day(NaiveDate::from_ymd_opt(2026, 12, 18).unwrap()),
+            },
+        ];
+
+        let ics = to_ics("kars", &events, now);
+        assert!(ics.contains("DTSTART:20261016T100000Z\r\nDURATION:PT30M\r\n"));
+        assert!(ics.contains("SUMMARY:Frieren\\, episode 6\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261218\r\n"));
+        assert!(ics.lines().all(|l| l.len() <= 75));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+/// Most fields an item can carry.
+pub const MAX_CUSTOM_FIELDS: usize = 50;
+/// Longest accepted field name.
+pub const MAX_FIELD_NAME: usize = 64;
+/// Longest accepted field value.
+pub const MAX_FIELD_VALUE: usize = 1000;
+
+/// User-defined fields of an item, for what kars does not model ("owned
+/// on Blu-ray", "translation group").  Names and values are trimmed; an
+/// empty value removes the field.
+pub fn normalize(fields: BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+    let mut out = BTreeMap::new();
+    for (name, value) in fields {
+        let name = name.trim();
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        validate(name, value)?;
+        out.insert(name.to_string(), value.to_string());
+    }
+    if out.len() > MAX_CUSTOM_FIELDS {
+        return Err(format!("Items have at most {MAX_CUSTOM_FIELDS} custom fields"));
+    }
+    Ok(out)
+}
+
+/// Checks one trimmed field before it is stored.
+pub fn validate(name: &str, value: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Custom field names cannot be empty".into());
+    }
+    if name.chars().count() > MAX_FIELD_NAME {
+        return Err(format!("Custom field names are at most {MAX_FIELD_NAME} characters"));
+    }
+    if value.chars().count() > MAX_FIELD_VALUE {
+        return Err(format!("Custom field values are at most {MAX_FIELD_VALUE} characters"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_trims_and_drops_empty() {
+        let fields = BTreeMap::from([
+            (" owned on Blu-ray ".to_string(), " yes ".to_string()),
+            ("translation group".to_string(), "  ".to_string()),
+        ]);
+        assert_eq!(
+            normalize(fields),
+            Ok(BTreeMap::from([("owned on Blu-ray".to_string(), "yes".to_string())]))
+        );
+        assert!(normalize(BTreeMap::from([(" ".to_string(), "x".to_string())])).is_err());
+        assert!(normalize(BTreeMap::from([("k".to_string(), "x".repeat(1001))])).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Item lists in formats other tools read and write: CSV with the columns
+//! of `CSV_COLUMNS`, and the XML of MyAnimeList's list export.  The JSON
+//! archive is `core::archive`.
+
+use serde_json::{json, Map, Value};
+
+use crate::core::api_types::ApiMediaItem;
+use crate::core::models::{MediaItem, ScoreScale};
+use crate::core::validation;
+
+/// Columns of CSV exports.  Imports need `title`, `media_type` and
+/// `status`; other columns may be missing, and unknown ones are ignored.
+pub const CSV_COLUMNS: &[&str] = &[
+    "id", "title", "media_type", "status", "progress", "total_episodes", "volume_progress", "total_volumes",
+    "score", "global_score", "source", "external_id", "external_refs", "tags", "favorite", "repeat_count",
+    "notes",
+];
+
+/// Columns whose values are lists, written comma-separated in one cell.
+const LIST_COLUMNS: &[&str] = &["external_refs", "tags"];
+const NUMBER_COLUMNS: &[&str] = &["progress", "total_episodes", "volume_progress", "total_volumes", "repeat_count"];
+const SCORE_COLUMNS: &[&str] = &["score", "global_score"];
+
+// ── CSV ──────────────────────────────────────────────────────
+
+/// The items as CSV with a header row, scores in `scale`.
+pub fn write_csv(items: &[MediaItem], scale: ScoreScale) -> String {
+    let mut out = csv_line(CSV_COLUMNS.iter().map(|c| c.to_string()));
+    for item in items {
+        let api = serde_json::to_value(ApiMediaItem::from(item).in_scale(scale)).unwrap_or_default();
+        out += &csv_line(CSV_COLUMNS.iter().map(|column| match &api[*column] {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            Value::Array(values) => values
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        }));
+    }
+    out
+}
+
+/// Reads items from CSV written by `write_csv` or by hand, scores in
+/// `scale`.  Every row is checked like an item sent to the API; the first
+/// invalid one fails the whole file.
+pub fn read_csv(text: &str, scale: ScoreScale) -> Result<Vec<MediaItem>, String> {
+    let mut rows = parse_csv(text.trim_start_matches('\u{feff}'))?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or("The file is empty")?
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    for required in ["title", "media_type", "status"] {
+        if !header.iter().any(|h| h == required) {
+            return Err(format!("The header has no '{required}' column"));
+        }
+    }
+
+    rows.enumerate()
+        .map(|(i, row)| {
+            let line = i + 2;
+            let mut fields = Map::new();
+            for (column, value) in header.iter().zip(row) {
+                let value = value.trim();
+                if value.is_empty() || !CSV_COLUMNS.contains(&column.as_str()) {
+                    continue;
+                }
+                let parsed = if LIST_COLUMNS.contains(&column.as_str()) {
+                    value.split(',').map(str::trim).filter(|v| !v.is_empty()).collect::<Vec<_>>().into()
+                } else if NUMBER_COLUMNS.contains(&column.as_str()) {
+                    value.parse::<u32>().map_err(|_| format!("Row {line}: {column} is not a whole number"))?.into()
+                } else if SCORE_COLUMNS.contains(&column.as_str()) {
+                    value.parse::<f32>().map_err(|_| format!("Row {line}: {column} is not a number"))?.into()
+                } else if column == "favorite" {
+                    value.parse::<bool>().map_err(|_| format!("Row {line}: favorite is not true or false"))?.into()
+                } else {
+                    value.into()
+                };
+                fields.insert(column.clone(), parsed);
+            }
+            to_item(fields, scale).map_err(|e| format!("Row {line}: {e}"))
+        })
+        .collect()
+}
+
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    let fields: Vec<String> = fields
+        .map(|f| {
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f
+            }
+        })
+        .collect();
+    fields.join(",") + "\n"
+}
+
+/// Splits RFC 4180 CSV into rows of fields.  Blank lines are skipped.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let (mut rows, mut row, mut field) = (Vec::new(), Vec::new(), String::new());
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("A quoted field is never closed".into());
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|r: &Vec<String>| !(r.len() == 1 && r[0].trim().is_empty()));
+    Ok(rows)
+}
+
+// ── MyAnimeList XML ──────────────────────────────────────────
+
+/// The items that have a MyAnimeList id (an `mal:anime:` or `mal:manga:`
+/// external ref) in the XML of MyAnimeList's list export, and how many
+/// were left out for having none.
+pub fn write_mal(items: &[MediaItem]) -> (String, usize) {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n<myanimelist>\n");
+    let mut skipped = 0;
+    for item in items {
+        let api = ApiMediaItem::from(item);
+        let mal_id = |kind: &str| {
+            api.external_refs
+                .iter()
+                .find_map(|r| r.strip_prefix(&format!("mal:{kind}:")).map(str::to_string))
+        };
+        let score = api.score.map_or(0, |s| s.round() as u32);
+        let total = |t: Option<u32>| t.unwrap_or(0);
+        if let Some(id) = mal_id("anime") {
+            out += &format!(
+                "\t<anime>\n\
+                 \t\t<series_animedb_id>{id}</series_animedb_id>\n\
+                 \t\t<series_title><![CDATA[{}]]></series_title>\n\
+                 \t\t<series_episodes>{}</series_episodes>\n\
+                 \t\t<my_watched_episodes>{}</my_watched_episodes>\n\
+                 \t\t<my_score>{score}</my_score>\n\
+                 \t\t<my_status>{}</my_status>\n\
+                 \t\t<my_times_watched>{}</my_times_watched>\n\
+                 \t\t<update_on_import>1</update_on_import>\n\
+                 \t</anime>\n",
+                cdata(&api.title),
+                total(api.total_episodes),
+                api.progress,
+                mal_status(&api.status, "Watch"),
+                api.repeat_count,
+            );
+        } else if let Some(id) = mal_id("manga") {
+            out += &format!(
+                "\t<manga>\n\
+                 \t\t<manga_mangadb_id>{id}</manga_mangadb_id>\n\
+                 \t\t<manga_title><![CDATA[{}]]></manga_title>\n\
+                 \t\t<manga_volumes>{}</manga_volumes>\n\
+                 \t\t<manga_chapters>{}</manga_chapters>\n\
+                 \t\t<my_read_volumes>{}</my_read_volumes>\n\
+                 \t\t<my_read_chapters>{}</my_read_chapters>\n\
+                 \t\t<my_score>{score}</my_score>\n\
+                 \t\t<my_status>{}</my_status>\n\
+                 \t\t<my_times_read>{}</my_times_read>\n\
+                 \t\t<update_on_import>1</update_on_import>\n\
+                 \t</manga>\n",
+                cdata(&api.title),
+                total(api.total_volumes),
+                total(api.total_episodes),
+                total(api.volume_progress),
+                api.progress,
+                mal_status(&api.status, "Read"),
+                api.repeat_count,
+            );
+        } else {
+            skipped += 1;
+        }
+    }
+    out += "</myanimelist>\n";
+    (out, skipped)
+}
+
+/// Reads the anime and manga of a MyAnimeList list export.  Items keep
+/// their MyAnimeList id as an external ref, which `GET /api/resolve` and
+/// imports use to find the matching provider entries.
+pub fn read_mal(text: &str) -> Result<Vec<MediaItem>, String> {
+    if !text.contains("<myanimelist") {
+        return Err("Not a MyAnimeList export: there is no <myanimelist> element".into());
+    }
+    let mut items = Vec::new();
+    for (kind, block) in mal_entries(text) {
+        let field = |name: &str| tag_text(block, name).unwrap_or_default();
+        let number = |name: &str| field(name).trim().parse::<u32>().unwrap_or(0);
+        let nonzero = |name: &str| Some(number(name)).filter(|n| *n > 0);
+        let (id, title, progress, total, times) = match kind {
+            "anime" => ("series_animedb_id", "series_title", "my_watched_episodes", "series_episodes", "my_times_watched"),
+            _ => ("manga_mangadb_id", "manga_title", "my_read_chapters", "manga_chapters", "my_times_read"),
+        };
+        let title = field(title);
+        let status = match field("my_status").trim() {
+            "Watching" | "Reading" | "1" => "watching",
+            "Completed" | "2" => "completed",
+            "On-Hold" | "3" => "on_hold",
+            "Dropped" | "4" => "dropped",
+            _ => "plan_to_watch",
+        };
+        let mut fields = Map::new();
+        fields.insert("title".into(), title.trim().into());
+        fields.insert("media_type".into(), if kind == "anime" { "anime" } else { "manga" }.into());
+        fields.insert("status".into(), status.into());
+        fields.insert("progress".into(), number(progress).into());
+        fields.insert("total_episodes".into(), nonzero(total).into());
+        fields.insert("score".into(), nonzero("my_score").into());
+        fields.insert("repeat_count".into(), number(times).into());
+        fields.insert("external_refs".into(), json!([format!("mal:{kind}:{}", field(id).trim())]));
+        if kind == "manga" {
+            fields.insert("volume_progress".into(), nonzero("my_read_volumes").into());
+            fields.insert("total_volumes".into(), nonzero("manga_volumes").into());
+        }
+        let item = to_item(fields, ScoreScale::Ten).map_err(|e| format!("{}: {e}", title.trim()))?;
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn mal_status(status: &str, verb: &str) -> String {
+    match status {
+        "watching" | "reading" => format!("{verb}ing"),
+        "completed" => "Completed".into(),
+        "on_hold" => "On-Hold".into(),
+        "dropped" => "Dropped".into(),
+        _ => format!("Plan to {verb}"),
+    }
+}
+
+/// A title inside `<![CDATA[...]]>`, which cannot hold its own end.
+fn cdata(text: &str) -> String {
+    text.replace("]]>", "]]]]><![CDATA[>")
+}
+
+/// The `<anime>` and `<manga>` elements of an export, in order.
+fn mal_entries(text: &str) -> Vec<(&'static str, &str)> {
+    let mut entries = Vec::new();
+    let mut rest = text;
+    loop {
+        let next = ["anime", "manga"]
+            .into_iter()
+            .filter_map(|kind| rest.find(&format!("<{kind}>")).map(|at| (at, kind)))
+            .min();
+        let Some((at, kind)) = next else { break };
+        let body = &rest[at + kind.len() + 2..];
+        let Some(end) = body.find(&format!("</{kind}>")) else { break };
+        entries.push((kind, &body[..end]));
+        rest = &body[end..];
+    }
+    entries
+}
+
+/// Text of the first `<name>` element in `block`, unescaped.
+fn tag_text(block: &str, name: &str) -> Option<String> {
+    let start = block.find(&format!("<{name}>"))? + name.len() + 2;
+    let end = start + block[start..].find(&format!("</{name}>"))?;
+    let raw = &block[start..end];
+    Some(match raw.trim().strip_prefix("<![CDATA[").and_then(|r| r.strip_suffix("]]>")) {
+        Some(cdata) => cdata.replace("]]]]><![CDATA[>", "]]>"),
+        None => raw
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    })
+}
+
+/// An item from API fields, checked like one sent to `POST /api/items`.
+fn to_item(mut fields: Map<String, Value>, scale: ScoreScale) -> Result<MediaItem, String> {
+    for (key, default) in [("id", json!("")), ("progress", json!(0)), ("tags", json!([])), ("favorite", json!(false))] {
+        fields.entry(key).or_insert(default);
+    }
+    let api: ApiMediaItem = serde_json::from_value(Value::Object(fields)).map_err(|e| e.to_string())?;
+    if let Err(errors) = validation::validate_item(&api, scale) {
+        let errors: Vec<String> = errors.into_iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+        return Err(errors.join("; "));
+    }
+    api.out_of_scale(scale, None)?.into_media_item()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_round_trip() {
+        let csv = "title,media_type,status,progress,score,tags,notes\n\
+                   Frieren,anime,watching,12,9.5,\"fantasy, slow\",\"Says \"\"hi\"\"\nthen leaves\"\n\
+                   \n\
+                   Dune,book,plan_to_read,,,,\n";
+        let items = read_csv(csv, ScoreScale::Ten).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].score, Some(95));
+        assert_eq!(items[0].notes.as_deref(), Some("Says \"hi\"\nthen leaves"));
+
+        let again = read_csv(&write_csv(&items, ScoreScale::Hundred), ScoreScale::Hundred).unwrap();
+        assert_eq!(again[0].title, "Frieren");
+        assert_eq!(again[0].score, Some(95));
+        assert_eq!(again[0].tags, items[0].tags);
+        assert_eq!(again[0].notes, items[0].notes);
+
+        let err = read_csv("title,media_type,status,progress\nX,anime,watching,many\n", ScoreScale::Ten).unwrap_err();
+        assert_eq!(err, "Row 2: progress is not a whole number");
+    }
+
+    #[test]
+    fn test_mal_round_trip() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<myanimelist>
+	<myinfo><user_export_type>1</user_export_type></myinfo>
+	<anime>
+		<series_animedb_id>21</series_animedb_id>
+		<series_title><![CDATA[One Piece]]></series_title>
+		<series_episodes>0</series_episodes>
+		<my_watched_episodes>100</my_watched_episodes>
+		<my_score>9</my_score>
+		<my_status>Watching</my_status>
+		<my_times_watched>0</my_times_watched>
+	</anime>
+	<manga>
+		<manga_mangadb_id>2</manga_mangadb_id>
+		<manga_title>Berserk &amp; Co</manga_title>
+		<manga_volumes>0</manga_volumes>
+		<manga_chapters>0</manga_chapters>
+		<my_read_volumes>3</my_read_volumes>
+		<my_read_chapters>30</my_read_chapters>
+		<my_score>0</my_score>
+		<my_status>Plan to Read</my_status>
+	</manga>
+</myanimelist>"#;
+        let items = read_mal(xml).unwrap();
+        let api: Vec<ApiMediaItem> = items.iter().map(ApiMediaItem::from).collect();
+        assert_eq!((api[0].title.as_str(), api[0].status.as_str(), api[0].progress), ("One Piece", "watching", 100));
+        assert_eq!(api[0].score, Some(9.0));
+        assert_eq!(api[0].external_refs, vec!["mal:anime:21"]);
+        assert_eq!((api[1].title.as_str(), api[1].status.as_str()), ("Berserk & Co", "plan_to_read"));
+        assert_eq!(api[1].volume_progress, Some(3));
+
+        let mut unlinked = items[0].clone();
+        unlinked.external_refs.clear();
+        let (written, skipped) = write_mal(&[items[0].clone(), items[1].clone(), unlinked]);
+        assert_eq!(skipped, 1);
+        let again: Vec<ApiMediaItem> = read_mal(&written).unwrap().iter().map(ApiMediaItem::from).collect();
+        assert_eq!(again[1].title, "Berserk & Co");
+        assert_eq!(again[1].external_refs, vec!["mal:manga:2"]);
+        assert_eq!(again[0].score, Some(9.0));
+    }
+}
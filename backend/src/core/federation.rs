@@ -0,0 +1,151 @@
+use serde_json::{json, Value};
+
+use crate::core::history::HistoryEntry;
+
+const ACTIVITY_STREAMS: &str = "https://www.w3.org/ns/activitystreams";
+const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// Media type of ActivityPub documents.
+pub const ACTIVITY_JSON: &str = "application/activity+json";
+
+/// A read-only ActivityPub actor that publishes the user's completions.
+/// Objects are addressed under `base_url`, the public URL of the server.
+#[derive(Debug, Clone)]
+pub struct Federation {
+    base_url: String,
+    user: String,
+}
+
+impl Federation {
+    pub fn new(base_url: &str, user: &str) -> Self {
+        Federation {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user: user.to_string(),
+        }
+    }
+
+    fn actor_id(&self) -> String {
+        format!("{}/ap/actor", self.base_url)
+    }
+
+    /// `user@host`, as used in WebFinger and Mastodon searches.
+    pub fn handle(&self) -> String {
+        let host = self
+            .base_url
+            .split_once("://")
+            .map_or(self.base_url.as_str(), |(_, rest)| rest);
+        let host = host.split('/').next().unwrap_or(host);
+        format!("{}@{host}", self.user)
+    }
+
+    pub fn actor(&self) -> Value {
+        json!({
+            "@context": ACTIVITY_STREAMS,
+            "id": self.actor_id(),
+            "type": "Person",
+            "preferredUsername": self.user,
+            "name": self.user,
+            "summary": "Media finished, as tracked by kars.",
+            "url": self.base_url,
+            "inbox": format!("{}/ap/inbox", self.base_url),
+            "outbox": format!("{}/ap/outbox", self.base_url),
+        })
+    }
+
+    /// The WebFinger document for `resource`, or None when it does not
+    /// name this actor.
+    pub fn webfinger(&self, resource: &str) -> Option<Value> {
+        let handle = resource.strip_prefix("acct:").unwrap_or(resource);
+        if !handle.eq_ignore_ascii_case(&self.handle()) && resource != self.actor_id() {
+            return None;
+        }
+        Some(json!({
+            "subject": format!("acct:{}", self.handle()),
+            "links": [{
+                "rel": "self",
+                "type": ACTIVITY_JSON,
+                "href": self.actor_id(),
+            }],
+        }))
+    }
+
+    /// An ordered collection of `Create` activities, one per completion,
+    /// in the order given (newest first).
+    pub fn outbox(&self, completions: &[HistoryEntry]) -> Value {
+        let activities: Vec<Value> = completions.iter().map(|e| self.activity(e)).collect();
+        json!({
+            "@context": ACTIVITY_STREAMS,
+            "id": format!("{}/ap/outbox", self.base_url),
+            "type": "OrderedCollection",
+            "totalItems": activities.len(),
+            "orderedItems": activities,
+        })
+    }
+
+    fn activity(&self, entry: &HistoryEntry) -> Value {
+        let published = entry.recorded_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let note_id = format!("{}/ap/notes/{}", self.base_url, entry.id);
+        json!({
+            "id": format!("{note_id}/activity"),
+            "type": "Create",
+            "actor": self.actor_id(),
+            "published": published,
+            "to": [PUBLIC],
+            "object": {
+                "id": note_id,
+                "type": "Note",
+                "attributedTo": self.actor_id(),
+                "content": format!("<p>Finished {}</p>", escape_html(&entry.title)),
+                "published": published,
+                "to": [PUBLIC],
+            },
+        })
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::HistoryKind;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_outbox_publishes_escaped_completion_notes() {
+        let federation = Federation::new("https://media.example.org/", "ana");
+        assert_eq!(federation.handle(), "ana@media.example.org");
+        assert!(federation.webfinger("acct:ana@media.example.org").is_some());
+        assert!(federation.webfinger("acct:bob@media.example.org").is_none());
+
+        let entry = HistoryEntry {
+            id: 7,
+            item_id: Uuid::new_v4(),
+            title: "Tom & Jerry".into(),
+            kind: HistoryKind::StatusChanged,
+            old_value: Some("watching".into()),
+            new_value: Some("completed".into()),
+            recorded_at: Utc::now(),
+            job_id: None,
+        };
+
+        let outbox = federation.outbox(&[entry]);
+        let note = &outbox["orderedItems"][0]["object"];
+        assert_eq!(note["id"], "https://media.example.org/ap/notes/7");
+        assert_eq!(note["content"], "<p>Finished Tom &amp; Jerry</p>");
+    }
+}
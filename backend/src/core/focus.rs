@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::core::models::{MediaItem, MediaItemType};
+
+/// An item on the focus list: the few things the user has committed to
+/// finishing next.  Pins live in their own table, apart from tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusPin {
+    pub item_id: Uuid,
+    pub pinned_at: DateTime<Utc>,
+}
+
+/// How far along the focus list is, for the dashboard widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FocusSummary {
+    pub pinned: usize,
+    pub completed: usize,
+    /// Mean completion of the pinned items, 0.0-1.0.
+    pub progress: f32,
+}
+
+/// Share of an item that is done, 0.0-1.0.  Completed items count as
+/// done; anything else without a known total counts as not started.
+pub fn completion(item: &MediaItem) -> f32 {
+    if item.is_completed() {
+        return 1.0;
+    }
+    match &item.media_type {
+        MediaItemType::Movie(_) => 0.0,
+        MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => {
+            p.percent().map_or(0.0, |pct| (pct / 100.0).clamp(0.0, 1.0))
+        }
+    }
+}
+
+pub fn summarize(items: &[&MediaItem]) -> FocusSummary {
+    let progress = match items.len() {
+        0 => 0.0,
+        n => items.iter().map(|i| completion(i)).sum::<f32>() / n as f32,
+    };
+    FocusSummary {
+        pinned: items.len(),
+        completed: items.iter().filter(|i| i.is_completed()).count(),
+        progress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, WatchStatus};
+
+    #[test]
+    fn test_summarize_averages_completion() {
+        let done = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+        let started = MediaItem::new("Alien".into(), MediaItemType::Movie(WatchStatus::Watching));
+        let halfway = MediaItem::new(
+            "Frieren".into(),
+            MediaItemType::Series(Progress { current: 14, total: Some(28) }, WatchStatus::Watching),
+        );
+
+        let summary = summarize(&[&done, &started, &halfway]);
+        assert_eq!(summary.pinned, 3);
+        assert_eq!(summary.completed, 1);
+        assert!((summary.progress - 0.5).abs() < f32::EPSILON);
+        assert_eq!(summarize(&[]).progress, 0.0);
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use crate::core::focus;
+use crate::core::models::MediaItem;
+
+/// Longest accepted franchise name.
+pub const MAX_FRANCHISE_NAME: usize = 100;
+
+/// A franchise ("Monogatari", "MCU") and how far along its entries are.
+/// Franchises exist through the items that name them; an item belongs to
+/// at most one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FranchiseSummary {
+    pub name: String,
+    pub items: usize,
+    pub completed: usize,
+    /// Mean completion of the entries, 0.0-1.0.
+    pub progress: f32,
+    /// Mean user score of the scored entries, 0.0-10.0.
+    pub average_score: Option<f32>,
+}
+
+/// Trims a franchise name; empty names mean no franchise.
+pub fn normalize_name(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(None);
+    }
+    if name.chars().count() > MAX_FRANCHISE_NAME {
+        return Err(format!("Franchise names are at most {MAX_FRANCHISE_NAME} characters"));
+    }
+    Ok(Some(name.to_string()))
+}
+
+/// One summary per franchise named by `items`, by name.
+pub fn summarize(items: &[MediaItem]) -> Vec<FranchiseSummary> {
+    let mut groups: BTreeMap<&str, Vec<&MediaItem>> = BTreeMap::new();
+    for item in items {
+        if let Some(name) = item.franchise.as_deref() {
+            groups.entry(name).or_default().push(item);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(name, entries)| summary(name, &entries))
+        .collect()
+}
+
+pub fn summary(name: &str, entries: &[&MediaItem]) -> FranchiseSummary {
+    let focus = focus::summarize(entries);
+    let scores: Vec<f32> = entries.iter().filter_map(|i| i.get_score_display()).collect();
+    FranchiseSummary {
+        name: name.to_string(),
+        items: entries.len(),
+        completed: focus.completed,
+        progress: focus.progress,
+        average_score: match scores.len() {
+            0 => None,
+            n => Some(scores.iter().sum::<f32>() / n as f32),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, Progress, WatchStatus};
+
+    #[test]
+    fn test_summarize_groups_by_franchise() {
+        let mut bake = MediaItem::new(
+            "Bakemonogatari".into(),
+            MediaItemType::Series(Progress { current: 15, total: Some(15) }, WatchStatus::Completed),
+        );
+        bake.franchise = Some("Monogatari".into());
+        bake.set_score(9.0);
+        let mut nise = MediaItem::new(
+            "Nisemonogatari".into(),
+            MediaItemType::Series(Progress { current: 0, total: Some(11) }, WatchStatus::PlanToWatch),
+        );
+        nise.franchise = Some("Monogatari".into());
+        let loose = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+
+        let summaries = summarize(&[bake, nise, loose]);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].items, 2);
+        assert_eq!(summaries[0].completed, 1);
+        assert!((summaries[0].progress - 0.5).abs() < f32::EPSILON);
+        assert_eq!(summaries[0].average_score, Some(9.0));
+
+        assert_eq!(normalize_name("  MCU "), Ok(Some("MCU".into())));
+        assert_eq!(normalize_name(" "), Ok(None));
+    }
+}
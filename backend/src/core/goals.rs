@@ -0,0 +1,136 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Media types a goal can count, as the API names them.  A goal without
+/// a type counts everything.
+pub const GOAL_MEDIA_TYPES: &[&str] = &[
+    "movie", "series", "anime", "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel", "audiobook",
+];
+
+/// A yearly target, e.g. 24 books in 2025.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    pub id: i64,
+    pub year: i32,
+    /// One of `GOAL_MEDIA_TYPES`; None counts every completion.
+    pub media_type: Option<String>,
+    pub target: u32,
+}
+
+/// How far a goal is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    pub completed: u32,
+    /// At least as far as an even pace through the year would be today.
+    pub on_track: bool,
+}
+
+/// Checks a goal before it is stored.
+pub fn validate(year: i32, media_type: Option<&str>, target: u32) -> Result<(), String> {
+    if !(1900..=9999).contains(&year) {
+        return Err(format!("Invalid year {year}"));
+    }
+    if let Some(t) = media_type
+        && !GOAL_MEDIA_TYPES.contains(&t)
+    {
+        return Err(format!("Unknown media_type '{t}'; expected one of {}", GOAL_MEDIA_TYPES.join(", ")));
+    }
+    if target == 0 {
+        return Err("Goals need a target of at least 1".into());
+    }
+    Ok(())
+}
+
+/// Counts the completions of `goal`'s year and type.  `completions` holds
+/// the day and media type of each item finished, one entry per item and
+/// year.
+pub fn progress(goal: &Goal, completions: &[(NaiveDate, &str)], today: NaiveDate) -> GoalProgress {
+    let completed = completions
+        .iter()
+        .filter(|(day, media_type)| {
+            day.year() == goal.year && goal.media_type.as_deref().is_none_or(|t| t == *media_type)
+        })
+        .count() as u32;
+
+    let expected = match goal.year.cmp(&today.year()) {
+        std::cmp::Ordering::Less => goal.target as f32,
+        std::cmp::Ordering::Greater => 0.0,
+        std::cmp::Ordering::Equal => {
+            let days_in_year = if today.leap_year() { 366.0 } else { 365.0 };
+            goal.target as f32 * today.ordinal() as f32 / days_in_year
+        }
+    };
+    GoalProgress { completed, on_track: completed as f32 >= expected.floor() }
+}
+
+/// Days in a row with activity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Streak {
+    /// Run that ends today, or yesterday while today has no activity yet.
+    pub current: u32,
+    pub longest: u32,
+    pub last_active: Option<NaiveDate>,
+}
+
+/// Streaks from the days something was watched, read or rated.  `days`
+/// may be in any order and repeat.
+pub fn streak(days: &[NaiveDate], today: NaiveDate) -> Streak {
+    let mut days = days.to_vec();
+    days.sort();
+    days.dedup();
+
+    let (mut longest, mut run) = (0, 0);
+    let mut previous: Option<NaiveDate> = None;
+    for &day in &days {
+        run = match previous {
+            Some(p) if p.succ_opt() == Some(day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let last_active = days.last().copied();
+    let alive = last_active.is_some_and(|last| last == today || last.succ_opt() == Some(today));
+    Streak {
+        current: if alive { run } else { 0 },
+        longest,
+        last_active,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_progress_counts_the_goal_year_and_type() {
+        let goal = Goal { id: 1, year: 2025, media_type: Some("book".into()), target: 24 };
+        let completions = [
+            (day(1, 10), "book"),
+            (day(2, 3), "book"),
+            (day(2, 4), "movie"),
+            (NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(), "book"),
+        ];
+        // Two of 24 by early July is behind an even pace of 12.
+        assert_eq!(progress(&goal, &completions, day(7, 2)), GoalProgress { completed: 2, on_track: false });
+        assert!(progress(&goal, &completions, day(1, 20)).on_track);
+
+        let any = Goal { media_type: None, ..goal };
+        assert_eq!(progress(&any, &completions, day(7, 2)).completed, 3);
+    }
+
+    #[test]
+    fn test_streak_survives_until_a_day_is_missed() {
+        let days = [day(3, 1), day(3, 2), day(3, 2), day(3, 3), day(3, 7), day(3, 8)];
+        assert_eq!(
+            streak(&days, day(3, 9)),
+            Streak { current: 2, longest: 3, last_active: Some(day(3, 8)) }
+        );
+        assert_eq!(streak(&days, day(3, 10)).current, 0);
+        assert_eq!(streak(&[], day(3, 10)), Streak { current: 0, longest: 0, last_active: None });
+    }
+}
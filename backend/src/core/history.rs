@@ -0,0 +1,162 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::core::api_types::status_of;
+use crate::core::models::{MediaItem, MediaItemType};
+
+/// Kind of mutation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Added,
+    StatusChanged,
+    ProgressUpdated,
+    ScoreSet,
+    Deleted,
+    /// Folded into another item; `new_value` holds the surviving title.
+    Merged,
+    /// A finished pass, logged when a rewatch / reread starts over (see
+    /// `MediaItem::start_rewatch`): `old_value` holds the score it ended
+    /// with and `new_value` when it was completed, if the log knows.
+    PassCompleted,
+}
+
+impl HistoryKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HistoryKind::Added => "added",
+            HistoryKind::StatusChanged => "status_changed",
+            HistoryKind::ProgressUpdated => "progress_updated",
+            HistoryKind::ScoreSet => "score_set",
+            HistoryKind::Deleted => "deleted",
+            HistoryKind::Merged => "merged",
+            HistoryKind::PassCompleted => "pass_completed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "added" => Some(HistoryKind::Added),
+            "status_changed" => Some(HistoryKind::StatusChanged),
+            "progress_updated" => Some(HistoryKind::ProgressUpdated),
+            "score_set" => Some(HistoryKind::ScoreSet),
+            "deleted" => Some(HistoryKind::Deleted),
+            "merged" => Some(HistoryKind::Merged),
+            "pass_completed" => Some(HistoryKind::PassCompleted),
+            _ => None,
+        }
+    }
+}
+
+/// A change detected between two versions of an item, before it is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryChange {
+    pub kind: HistoryKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// A stored audit log row.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub item_id: Uuid,
+    pub title: String,
+    pub kind: HistoryKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+    /// The job (e.g. an import) that made the change.
+    pub job_id: Option<i64>,
+}
+
+/// Lists the audited changes between the stored version of an item
+/// (`None` for a new item) and the version about to be written.
+pub fn diff(old: Option<&MediaItem>, new: &MediaItem) -> Vec<HistoryChange> {
+    let Some(old) = old else {
+        return vec![HistoryChange {
+            kind: HistoryKind::Added,
+            old_value: None,
+            new_value: Some(status_of(&new.media_type).to_string()),
+        }];
+    };
+
+    let mut changes = Vec::new();
+
+    // Logged first, before the reset that follows it.
+    if new.repeat_count > old.repeat_count {
+        changes.push(HistoryChange {
+            kind: HistoryKind::PassCompleted,
+            old_value: old.get_score_display().map(|s| format!("{s:.1}")),
+            new_value: None,
+        });
+    }
+
+    let (old_status, new_status) = (status_of(&old.media_type), status_of(&new.media_type));
+    if old_status != new_status {
+        changes.push(HistoryChange {
+            kind: HistoryKind::StatusChanged,
+            old_value: Some(old_status.to_string()),
+            new_value: Some(new_status.to_string()),
+        });
+    }
+
+    if let (Some(before), Some(after)) = (progress_of(old), progress_of(new))
+        && before != after
+    {
+        changes.push(HistoryChange {
+            kind: HistoryKind::ProgressUpdated,
+            old_value: Some(before.to_string()),
+            new_value: Some(after.to_string()),
+        });
+    }
+
+    if old.score != new.score {
+        changes.push(HistoryChange {
+            kind: HistoryKind::ScoreSet,
+            old_value: old.get_score_display().map(|s| format!("{s:.1}")),
+            new_value: new.get_score_display().map(|s| format!("{s:.1}")),
+        });
+    }
+
+    changes
+}
+
+fn progress_of(item: &MediaItem) -> Option<u32> {
+    match &item.media_type {
+        MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => Some(p.current),
+        MediaItemType::Movie(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, WatchStatus};
+
+    #[test]
+    fn test_diff_records_each_changed_field() {
+        let old = MediaItem::new(
+            "Monster".into(),
+            MediaItemType::Series(Progress { current: 3, total: Some(74) }, WatchStatus::PlanToWatch),
+        );
+        let mut new = old.clone();
+        new.media_type =
+            MediaItemType::Series(Progress { current: 4, total: Some(74) }, WatchStatus::Watching);
+        new.set_score(9.0);
+
+        let kinds: Vec<HistoryKind> = diff(Some(&old), &new).iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![HistoryKind::StatusChanged, HistoryKind::ProgressUpdated, HistoryKind::ScoreSet]
+        );
+        assert!(diff(Some(&new), &new).is_empty());
+        assert_eq!(diff(None, &new)[0].kind, HistoryKind::Added);
+
+        new.media_type = MediaItemType::Series(Progress { current: 74, total: Some(74) }, WatchStatus::Completed);
+        let mut rewatch = new.clone();
+        assert!(rewatch.start_rewatch());
+        let changes = diff(Some(&new), &rewatch);
+        assert_eq!(changes[0].kind, HistoryKind::PassCompleted);
+        assert_eq!(changes[0].old_value.as_deref(), Some("9.0"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Imports into a library that already has items: how an archive's
+//! items merge into the stored ones, and the preview of a dry run.  The
+//! API and the CLI write the resulting plan with `Database::apply_merge`.
+
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::core::api_types::{ApiImportPreview, ApiImportPreviewItem, ApiMediaItem};
+use crate::core::archive::{Activity, Archive, ARCHIVE_VERSION};
+use crate::core::matcher;
+use crate::core::models::MediaItem;
+
+/// A plain list of items is read as an archive without activity or
+/// settings.
+pub fn parse_import(body: serde_json::Value) -> Result<Archive, String> {
+    let mut archive = if body.is_array() {
+        let payload: Vec<ApiMediaItem> = serde_json::from_value(body).map_err(|e| e.to_string())?;
+        Archive {
+            version: ARCHIVE_VERSION,
+            exported_at: chrono::Utc::now(),
+            items: payload
+                .into_iter()
+                .map(ApiMediaItem::into_media_item)
+                .collect::<Result<_, _>>()?,
+            activity: Default::default(),
+            settings: None,
+        }
+    } else {
+        serde_json::from_value(body).map_err(|e| e.to_string())?
+    };
+    archive.validate()?;
+    archive.upgrade();
+    Ok(archive)
+}
+
+/// The writes of a merge import and the activity that goes with them.
+pub struct MergePlan {
+    /// (stored version, version to write), one entry per touched item.
+    pub writes: Vec<(Option<MediaItem>, MediaItem)>,
+    pub activity: Activity,
+    /// Why an imported item was merged into a stored one, by stored id,
+    /// e.g. `Same title as Frieren`.
+    pub reasons: HashMap<Uuid, String>,
+}
+
+impl MergePlan {
+    /// Drops the items the merge leaves as they are.
+    pub fn without_unchanged(mut self) -> Self {
+        self.writes.retain(|(before, item)| before.as_ref() != Some(item));
+        self
+    }
+}
+
+/// Works out how the archive's items merge into the `stored` ones.
+pub fn plan_merge(mut stored: Vec<MediaItem>, archive: Archive) -> MergePlan {
+    let mut writes: Vec<(Option<MediaItem>, MediaItem)> = Vec::new();
+    // Imported id → id of the item it was written to.
+    let mut targets = HashMap::new();
+    let mut reasons = HashMap::new();
+    for item in archive.items {
+        let existing = stored
+            .iter()
+            .position(|a| a.id == item.id)
+            .or_else(|| {
+                let dup = matcher::find_duplicate(&stored, &item)?;
+                stored.iter().position(|a| a.id == dup.id)
+            });
+        let Some(idx) = existing else {
+            targets.insert(item.id, item.id);
+            writes.push((None, item.clone()));
+            stored.push(item);
+            continue;
+        };
+
+        let before = stored[idx].clone();
+        targets.insert(item.id, before.id);
+        reasons.entry(before.id).or_insert_with(|| {
+            let rule = if before.id == item.id {
+                "Same id"
+            } else {
+                matcher::duplicate_reason(&before, &item).unwrap_or("Same work")
+            };
+            format!("{rule} as {}", before.title)
+        });
+        stored[idx].merge(item);
+        match writes.iter_mut().find(|(_, w)| w.id == before.id) {
+            Some(write) => write.1 = stored[idx].clone(),
+            None => writes.push((Some(before), stored[idx].clone())),
+        }
+    }
+
+    let created: HashSet<Uuid> = writes
+        .iter()
+        .filter(|(before, _)| before.is_none())
+        .map(|(_, item)| item.id)
+        .collect();
+    let activity = archive.activity.remap(&targets, &created);
+    MergePlan { writes, activity, reasons }
+}
+
+pub fn preview_merge(plan: &MergePlan) -> ApiImportPreview {
+    let items = plan
+        .writes
+        .iter()
+        .map(|(before, item)| {
+            let Some(before) = before else {
+                return preview_item(item, "create", "Not in the library yet".into(), Vec::new());
+            };
+            let reason = plan.reasons.get(&item.id).cloned().unwrap_or_default();
+            match changed_fields(before, item) {
+                fields if fields.is_empty() => preview_item(item, "skip", format!("{reason}, nothing to change"), fields),
+                fields => preview_item(item, "update", reason, fields),
+            }
+        })
+        .collect();
+    import_preview(items)
+}
+
+pub fn preview_replace(stored: &[MediaItem], archive: &Archive) -> ApiImportPreview {
+    let mut items: Vec<ApiImportPreviewItem> = archive
+        .items
+        .iter()
+        .map(|item| match stored.iter().find(|s| s.id == item.id) {
+            None => preview_item(item, "create", "Not in the library yet".into(), Vec::new()),
+            Some(before) => match changed_fields(before, item) {
+                fields if fields.is_empty() => preview_item(item, "skip", "Same id, nothing to change".into(), fields),
+                fields => preview_item(item, "update", "Same id, replaced by the archived version".into(), fields),
+            },
+        })
+        .collect();
+    let kept: HashSet<Uuid> = archive.items.iter().map(|i| i.id).collect();
+    for item in stored.iter().filter(|s| !kept.contains(&s.id)) {
+        items.push(preview_item(item, "delete", "Not in the archive".into(), Vec::new()));
+    }
+    import_preview(items)
+}
+
+fn preview_item(item: &MediaItem, action: &str, reason: String, fields: Vec<String>) -> ApiImportPreviewItem {
+    ApiImportPreviewItem {
+        item_id: item.id.to_string(),
+        title: item.title.clone(),
+        action: action.into(),
+        reason,
+        fields,
+    }
+}
+
+fn import_preview(items: Vec<ApiImportPreviewItem>) -> ApiImportPreview {
+    let count = |action: &str| items.iter().filter(|i| i.action == action).count();
+    ApiImportPreview {
+        created: count("create"),
+        updated: count("update"),
+        skipped: count("skip"),
+        deleted: count("delete"),
+        items,
+    }
+}
+
+/// Fields of `after` that differ from `before`, named as in the API.
+/// The modification time is left out.
+fn changed_fields(before: &MediaItem, after: &MediaItem) -> Vec<String> {
+    let as_map = |item: &MediaItem| match serde_json::to_value(ApiMediaItem::from(item)) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (before, after) = (as_map(before), as_map(after));
+    after
+        .iter()
+        .filter(|(key, value)| key.as_str() != "updated_at" && before.get(key.as_str()) != Some(value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
@@ -0,0 +1,92 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::core::refresh::ItemChanges;
+
+/// What a background job does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// Re-fetches provider metadata (totals, score, poster) of items.
+    MetadataRefresh,
+    /// Adds a batch of items, merging duplicates into existing ones.
+    Import,
+    /// Looks for provider entries of items added by hand.
+    Match,
+}
+
+impl JobKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobKind::MetadataRefresh => "metadata_refresh",
+            JobKind::Import => "import",
+            JobKind::Match => "match",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "metadata_refresh" => Some(JobKind::MetadataRefresh),
+            "import" => Some(JobKind::Import),
+            "match" => Some(JobKind::Match),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    /// A dry run finished; its changes can still be applied.
+    Preview,
+    Completed,
+    Failed,
+    /// The job's changes were reversed.
+    RolledBack,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Preview => "preview",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::RolledBack => "rolled_back",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(JobStatus::Running),
+            "preview" => Some(JobStatus::Preview),
+            "completed" => Some(JobStatus::Completed),
+            "failed" => Some(JobStatus::Failed),
+            "rolled_back" => Some(JobStatus::RolledBack),
+            _ => None,
+        }
+    }
+}
+
+/// A stored background job and what it found or changed.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: i64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub dry_run: bool,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    /// Only items with changes or errors are listed.
+    pub items: Vec<ItemChanges>,
+}
+
+/// An item a job touched that was edited or deleted after the job
+/// finished, so rolling the job back would throw that away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollbackConflict {
+    pub item_id: Uuid,
+    pub title: String,
+    /// When it was last changed; None when it was deleted.
+    pub changed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,230 @@
+use crate::core::models::{MediaItem, MediaItemType, ReadableKind};
+use crate::core::search::{MediaSearchType, SearchResult};
+
+/// Outcome of matching a free-text title against provider results.
+#[derive(Debug, PartialEq)]
+pub enum MatchOutcome {
+    /// Exactly one result carries the same normalized title.
+    Exact(usize),
+    /// Results exist but none (or several) match exactly — ask the user.
+    Ambiguous,
+    NoResults,
+}
+
+/// Lowercases and strips everything but letters and digits, so that
+/// "Frieren: Beyond Journey's End" and "frieren beyond journeys end" compare
+/// equal.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn match_title(query: &str, results: &[SearchResult]) -> MatchOutcome {
+    if results.is_empty() {
+        return MatchOutcome::NoResults;
+    }
+    let wanted = normalize_title(query);
+    let mut exact = results
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| normalize_title(&r.title) == wanted)
+        .map(|(i, _)| i);
+
+    match (exact.next(), exact.next()) {
+        (Some(idx), None) => MatchOutcome::Exact(idx),
+        _ => MatchOutcome::Ambiguous,
+    }
+}
+
+/// Search category in which to look for a provider entry of an item added
+/// by hand.  Manga, manhwa and webtoons are searched as manga, novels as
+/// light novels, books and audiobooks as themselves; series are searched as TV series, since nothing
+/// tells an anime entered by hand apart from one.
+pub fn search_type_for_unmatched(item: &MediaItem) -> MediaSearchType {
+    match &item.media_type {
+        MediaItemType::Movie(_) => MediaSearchType::Movie,
+        MediaItemType::Series(..) => MediaSearchType::Series,
+        MediaItemType::Readable(ReadableKind::Manga | ReadableKind::Manhwa | ReadableKind::Webtoon, ..) => {
+            MediaSearchType::Manga
+        }
+        MediaItemType::Readable(ReadableKind::LightNovel | ReadableKind::WebNovel, ..) => {
+            MediaSearchType::LightNovel
+        }
+        MediaItemType::Readable(ReadableKind::Book, ..) => MediaSearchType::Book,
+        MediaItemType::Readable(ReadableKind::Audiobook, ..) => MediaSearchType::Audiobook,
+    }
+}
+
+/// Whether both items were imported from the same provider entry.
+pub fn same_provider_entry(a: &MediaItem, b: &MediaItem) -> bool {
+    a.source.is_some()
+        && a.external_id.is_some()
+        && a.source == b.source
+        && a.external_id == b.external_id
+}
+
+/// Finds an archived item that is the same work as `candidate`, even when
+/// the two came from different sources: they come from the same provider
+/// entry, share an external reference (e.g. the AniList id a MangaDex entry
+/// links to), or any of their titles (primary or alternative) normalize to
+/// the same string.
+pub fn find_duplicate<'a>(archive: &'a [MediaItem], candidate: &MediaItem) -> Option<&'a MediaItem> {
+    let titles: Vec<String> = std::iter::once(&candidate.title)
+        .chain(&candidate.alt_titles)
+        .map(|t| normalize_title(t))
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    archive.iter().find(|item| {
+        item.id != candidate.id
+            && (same_provider_entry(item, candidate)
+                || !item.external_refs.is_disjoint(&candidate.external_refs)
+                || std::iter::once(&item.title)
+                    .chain(&item.alt_titles)
+                    .any(|t| titles.contains(&normalize_title(t))))
+    })
+}
+
+/// Which of `find_duplicate`'s rules makes `item` the same work as
+/// `candidate`, e.g. for an import preview.  None when no rule does.
+pub fn duplicate_reason(item: &MediaItem, candidate: &MediaItem) -> Option<&'static str> {
+    let titles = |i: &MediaItem| -> Vec<String> {
+        std::iter::once(&i.title)
+            .chain(&i.alt_titles)
+            .map(|t| normalize_title(t))
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    if same_provider_entry(item, candidate) {
+        Some("Same provider entry")
+    } else if !item.external_refs.is_disjoint(&candidate.external_refs) {
+        Some("Same external reference")
+    } else if titles(item).iter().any(|t| titles(candidate).contains(t)) {
+        Some("Same title")
+    } else {
+        None
+    }
+}
+
+/// Finds an item of the same kind (movie, series, or the same kind of
+/// readable) as `candidate` under the same title, ignoring case and
+/// punctuation.  Stricter than `find_duplicate`: alternative titles are
+/// not compared, so a movie and its source novel stay apart.
+pub fn find_same_title<'a>(items: &'a [MediaItem], candidate: &MediaItem) -> Option<&'a MediaItem> {
+    let title = normalize_title(&candidate.title);
+    if title.is_empty() {
+        return None;
+    }
+    items.iter().find(|item| {
+        item.id != candidate.id
+            && same_kind(&item.media_type, &candidate.media_type)
+            && normalize_title(&item.title) == title
+    })
+}
+
+fn same_kind(a: &MediaItemType, b: &MediaItemType) -> bool {
+    match (a, b) {
+        (MediaItemType::Movie(_), MediaItemType::Movie(_)) | (MediaItemType::Series(..), MediaItemType::Series(..)) => true,
+        (MediaItemType::Readable(a, ..), MediaItemType::Readable(b, ..)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, ReadStatus, WatchStatus};
+    use crate::core::search::LocalizedTitles;
+
+    fn result(title: &str) -> SearchResult {
+        SearchResult {
+            title: title.into(),
+            media_type: MediaItemType::Movie(WatchStatus::PlanToWatch),
+            global_score: None,
+            external_id: None,
+            poster_url: None,
+            source: "test",
+            format_label: String::new(),
+            alt_titles: Vec::new(),
+            external_refs: Vec::new(),
+            creator: None,
+            content_rating: None,
+            titles: LocalizedTitles::default(),
+            year: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_ignores_case_and_punctuation() {
+        assert_eq!(normalize_title("Frieren: Beyond Journey's End"), "frierenbeyondjourneysend");
+    }
+
+    #[test]
+    fn test_match_title_outcomes() {
+        let results = vec![result("Dune"), result("Dune: Part Two")];
+        assert_eq!(match_title("dune", &results), MatchOutcome::Exact(0));
+        assert_eq!(match_title("Dune 2", &results), MatchOutcome::Ambiguous);
+        assert_eq!(match_title("Dune", &[]), MatchOutcome::NoResults);
+
+        let twins = vec![result("Dune"), result("DUNE")];
+        assert_eq!(match_title("Dune", &twins), MatchOutcome::Ambiguous);
+    }
+
+    #[test]
+    fn test_search_type_for_unmatched_follows_readable_kind() {
+        let readable = |kind| {
+            let progress = Progress { current: 0, total: None };
+            MediaItem::new("x".into(), MediaItemType::Readable(kind, progress, ReadStatus::Reading))
+        };
+        assert_eq!(search_type_for_unmatched(&readable(ReadableKind::Manhwa)), MediaSearchType::Manga);
+        assert_eq!(search_type_for_unmatched(&readable(ReadableKind::WebNovel)), MediaSearchType::LightNovel);
+        assert_eq!(search_type_for_unmatched(&readable(ReadableKind::Book)), MediaSearchType::Book);
+    }
+
+    #[test]
+    fn test_find_duplicate_across_sources() {
+        let movie = || MediaItemType::Movie(WatchStatus::PlanToWatch);
+        let mut romaji = MediaItem::new("Shingeki no Kyojin".into(), movie());
+        romaji.alt_titles = vec!["Attack on Titan".into(), "進撃の巨人".into()];
+        romaji.external_refs.insert("anilist:16498".into());
+        let archive = vec![romaji];
+
+        let english = MediaItem::new("Attack on Titan".into(), movie());
+        assert!(find_duplicate(&archive, &english).is_some());
+        assert_eq!(duplicate_reason(&archive[0], &english), Some("Same title"));
+
+        let mut linked = MediaItem::new("AoT".into(), movie());
+        linked.external_refs.insert("anilist:16498".into());
+        assert!(find_duplicate(&archive, &linked).is_some());
+        assert_eq!(duplicate_reason(&archive[0], &linked), Some("Same external reference"));
+
+        let other = MediaItem::new("Vinland Saga".into(), movie());
+        assert!(find_duplicate(&archive, &other).is_none());
+        assert_eq!(duplicate_reason(&archive[0], &other), None);
+
+        let mut archive = archive;
+        archive[0].source = Some("anilist".into());
+        archive[0].external_id = Some("16498".into());
+        let mut renamed = MediaItem::new("Totally different".into(), movie());
+        renamed.source = Some("anilist".into());
+        renamed.external_id = Some("16498".into());
+        assert!(find_duplicate(&archive, &renamed).is_some());
+    }
+
+    #[test]
+    fn test_find_same_title_only_within_kind() {
+        let progress = Progress { current: 0, total: None };
+        let items = vec![
+            MediaItem::new("Dune".into(), MediaItemType::Readable(ReadableKind::Book, progress, ReadStatus::Completed)),
+            MediaItem::new("Dune: Part Two".into(), MediaItemType::Movie(WatchStatus::PlanToWatch)),
+        ];
+        let movie = MediaItem::new("dune".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        assert!(find_same_title(&items, &movie).is_none());
+
+        let sequel = MediaItem::new("DUNE - part two".into(), MediaItemType::Movie(WatchStatus::Completed));
+        assert_eq!(find_same_title(&items, &sequel).map(|i| i.id), Some(items[1].id));
+    }
+}
@@ -4,3 +4,38 @@ pub mod storage;
 pub mod search;
 pub mod app;
 pub mod api_types;
+pub mod activity;
+pub mod matcher;
+pub mod history;
+pub mod settings;
+pub mod schedule;
+pub mod refresh;
+pub mod jobs;
+pub mod scheduler;
+pub mod archive;
+pub mod import;
+pub mod exchange;
+pub mod recommend;
+pub mod focus;
+pub mod ranking;
+pub mod federation;
+pub mod stats;
+pub mod badge;
+pub mod telemetry;
+pub mod calendar;
+pub mod webhooks;
+pub mod notify;
+pub mod franchise;
+pub mod profiles;
+pub mod rate_limit;
+pub mod custom_fields;
+pub mod goals;
+pub mod query;
+pub mod trakt;
+pub mod write_through;
+pub mod scrobble;
+pub mod reading_sync;
+pub mod availability;
+pub mod validation;
+pub mod outbox;
+pub mod suggest;
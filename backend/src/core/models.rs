@@ -1,8 +1,10 @@
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use std::collections::HashSet;
+use utoipa::ToSchema;
+use std::collections::{BTreeMap, HashSet};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum WatchStatus {
     Watching,
@@ -12,7 +14,7 @@ pub enum WatchStatus {
     Dropped,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum ReadStatus {
     Reading,
@@ -22,7 +24,7 @@ pub enum ReadStatus {
     Dropped,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, ToSchema)]
 pub struct Progress {
     pub current: u32,
     pub total: Option<u32>,
@@ -43,10 +45,24 @@ impl Progress {
             _ => false,
         }
     }
+
+    /// Adds `by`, stopping at a known total.
+    pub fn advance(&mut self, by: u32) {
+        let current = self.current.saturating_add(by);
+        self.current = self.total.map_or(current, |t| current.min(t));
+    }
+}
+
+/// Watch state of a single episode of a series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeRecord {
+    pub number: u32,
+    pub watched: bool,
+    pub watched_at: Option<DateTime<Utc>>,
 }
 
 /// Categorizes different types of readable media to reduce code duplication.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum ReadableKind {
     Book,
@@ -55,9 +71,29 @@ pub enum ReadableKind {
     Manga,
     Manhwa,
     Webtoon,
+    /// Progress and total are minutes listened and the running time.
+    Audiobook,
+}
+
+impl ReadableKind {
+    /// What `Progress` counts for this kind.
+    pub fn progress_unit(&self) -> &'static str {
+        match self {
+            ReadableKind::Audiobook => "minutes",
+            _ => "chapters",
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
+/// Minutes as `12h 05m`, or `42m` under an hour.
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h {m:02}m"),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum MediaItemType {
     Movie(WatchStatus),
@@ -65,7 +101,147 @@ pub enum MediaItemType {
     Readable(ReadableKind, Progress, ReadStatus),
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+/// How explicit a title is, on MangaDex's scale.  TMDB and AniList only
+/// flag adult titles: those are `Pornographic`, the rest `Safe`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentRating {
+    Safe,
+    Suggestive,
+    Erotica,
+    Pornographic,
+}
+
+impl ContentRating {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentRating::Safe => "safe",
+            ContentRating::Suggestive => "suggestive",
+            ContentRating::Erotica => "erotica",
+            ContentRating::Pornographic => "pornographic",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "safe" => Some(ContentRating::Safe),
+            "suggestive" => Some(ContentRating::Suggestive),
+            "erotica" => Some(ContentRating::Erotica),
+            "pornographic" => Some(ContentRating::Pornographic),
+            _ => None,
+        }
+    }
+
+    /// The rating of a title a provider flags as adult or not.
+    pub fn from_adult_flag(adult: bool) -> Self {
+        if adult { ContentRating::Pornographic } else { ContentRating::Safe }
+    }
+
+    /// Whether `Settings::hide_adult` hides titles with this rating.
+    pub fn is_adult(self) -> bool {
+        matches!(self, ContentRating::Erotica | ContentRating::Pornographic)
+    }
+}
+
+/// How scores are shown and entered.  Stored scores are always 0-100,
+/// whatever the scale.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreScale {
+    /// 0-10 in steps of 0.1 (`8.5`).
+    #[default]
+    Ten,
+    /// Whole numbers 0-100.
+    Hundred,
+    /// 0-5 stars in half stars.
+    FiveStars,
+}
+
+impl ScoreScale {
+    pub fn max(self) -> u32 {
+        match self {
+            ScoreScale::Ten => 10,
+            ScoreScale::Hundred => 100,
+            ScoreScale::FiveStars => 5,
+        }
+    }
+
+    /// Steps per point: scores are multiples of `1 / steps_per_point`.
+    fn steps_per_point(self) -> u32 {
+        match self {
+            ScoreScale::Ten => 10,
+            ScoreScale::Hundred => 1,
+            ScoreScale::FiveStars => 2,
+        }
+    }
+
+    /// A stored 0-100 score in this scale, rounded to its nearest step.
+    pub fn scaled(self, stored: u8) -> f32 {
+        let steps = self.max() * self.steps_per_point();
+        let units = (stored as f32 * steps as f32 / 100.0).round();
+        units / self.steps_per_point() as f32
+    }
+
+    /// A score entered in this scale as stored 0-100.  Scores between two
+    /// steps are rounded to the nearest one.
+    pub fn to_stored(self, score: f32) -> Result<u8, String> {
+        if !(0.0..=self.max() as f32).contains(&score) {
+            return Err(format!("Scores are between 0 and {}", self.max()));
+        }
+        let steps = self.max() * self.steps_per_point();
+        let units = (score * self.steps_per_point() as f32).round();
+        Ok((units * 100.0 / steps as f32).round() as u8)
+    }
+
+    /// Prompt hint, e.g. `0 - 100`.
+    pub fn range_label(self) -> &'static str {
+        match self {
+            ScoreScale::Ten => "0.0 - 10.0",
+            ScoreScale::Hundred => "0 - 100",
+            ScoreScale::FiveStars => "0 - 5, in steps of 0.5",
+        }
+    }
+
+    /// `score` (already in this scale) the way people write it.
+    pub fn format(self, score: f32) -> String {
+        match self {
+            ScoreScale::Ten => format!("{score:.1}"),
+            ScoreScale::Hundred => format!("{score:.0}"),
+            ScoreScale::FiveStars => format!("{score}★"),
+        }
+    }
+}
+
+/// Which of a title's names is shown for anime and manga: the English
+/// one, the romanized original (romaji) or the original script.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleLanguage {
+    #[default]
+    English,
+    Romaji,
+    Native,
+}
+
+/// Reads an external id written as a string or, as before ids could hold
+/// MangaDex UUIDs and Open Library keys, as a number.
+pub fn external_id_compat<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        Text(String),
+    }
+    Ok(Option::<Id>::deserialize(deserializer)?.map(|id| match id {
+        Id::Number(n) => n.to_string(),
+        Id::Text(s) => s,
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MediaItem {
     pub id: Uuid,
@@ -75,14 +251,85 @@ pub struct MediaItem {
     pub score: Option<u8>,        // Stored 0-100 (represents 0.0-10.0)
     #[serde(default)]
     pub global_score: Option<u8>, // Stored 0-100 (represents 0.0-10.0)
-    #[serde(default)]
-    pub external_id: Option<u32>,
+    /// The provider's id, e.g. `154587` (AniList) or a MangaDex UUID.
+    #[serde(default, deserialize_with = "external_id_compat")]
+    pub external_id: Option<String>,
     #[serde(default)]
     pub poster_url: Option<String>,
     #[serde(default)]
     pub source: Option<String>,
     #[serde(default)]
     pub tags: HashSet<String>,
+    /// Number of times a completed item has been started again.
+    #[serde(default)]
+    pub repeat_count: u32,
+    /// Free-form markdown notes / review.
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Whether `notes` contain spoilers and should be hidden by default.
+    #[serde(default)]
+    pub notes_spoiler: bool,
+    /// Other known titles (romaji, native, English, synonyms).
+    #[serde(default)]
+    pub alt_titles: Vec<String>,
+    /// Cross-source identifiers as `source:id`, e.g. `anilist:21`,
+    /// `mal:anime:21`, `tmdb:tv:37854` (the id part may itself contain `:`).
+    #[serde(default)]
+    pub external_refs: HashSet<String>,
+    /// Planned viewing / reading session ("watching with friends on Saturday").
+    #[serde(default)]
+    pub scheduled_for: Option<DateTime<Utc>>,
+    /// Plot summary from the provider.
+    #[serde(default)]
+    pub overview: Option<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// Length of a movie, or of an episode of a series.
+    #[serde(default)]
+    pub runtime_minutes: Option<u32>,
+    #[serde(default)]
+    pub season_count: Option<u32>,
+    /// Name of the franchise the item belongs to (see `core::franchise`).
+    #[serde(default)]
+    pub franchise: Option<String>,
+    /// User-defined fields, by name (see `core::custom_fields`).
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+    /// Volume progress of a readable, tracked next to its chapters once set.
+    #[serde(default)]
+    pub volumes: Option<Progress>,
+    /// Whether the user updates volumes rather than chapters, so progress
+    /// is shown in volumes.
+    #[serde(default)]
+    pub prefer_volumes: bool,
+    /// Author of a readable, or studio of an anime, as the provider names
+    /// them.
+    #[serde(default)]
+    pub creator: Option<String>,
+    /// As the provider rates the title; None for items added by hand.
+    #[serde(default)]
+    pub content_rating: Option<ContentRating>,
+    #[serde(default)]
+    pub favorite: bool,
+    /// Kept for stats and history but left out of lists and searches
+    /// unless asked for.
+    #[serde(default)]
+    pub hidden: bool,
+    /// Newest chapter out at the provider, for readables being read (see
+    /// `schedule::chapters_behind`).  Maintained by the server.
+    #[serde(default)]
+    pub latest_available: Option<u32>,
+    /// Whether Sonarr or Radarr has the movie, or an episode of the series,
+    /// on disk (see `availability::ready_to_watch`).  Maintained by the
+    /// server.
+    #[serde(default)]
+    pub downloaded: bool,
+    /// When the item was added.  Maintained by storage, not the user.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the item last changed.  Maintained by storage, not the user.
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl MediaItem {
@@ -97,9 +344,37 @@ impl MediaItem {
             poster_url: None,
             source: None,
             tags: HashSet::new(),
+            repeat_count: 0,
+            notes: None,
+            notes_spoiler: false,
+            alt_titles: Vec::new(),
+            external_refs: HashSet::new(),
+            scheduled_for: None,
+            overview: None,
+            genres: Vec::new(),
+            runtime_minutes: None,
+            season_count: None,
+            franchise: None,
+            custom_fields: BTreeMap::new(),
+            volumes: None,
+            prefer_volumes: false,
+            creator: None,
+            content_rating: None,
+            favorite: false,
+            hidden: false,
+            latest_available: None,
+            downloaded: false,
+            created_at: None,
+            updated_at: None,
         }
     }
 
+    /// Whether the provider rates the item as adult (see
+    /// `ContentRating::is_adult`).
+    pub fn is_adult(&self) -> bool {
+        self.content_rating.is_some_and(ContentRating::is_adult)
+    }
+
     fn clamp_score(input_score: f32) -> u8 {
         (input_score.clamp(0.0, 10.0) * 10.0).round() as u8
     }
@@ -125,6 +400,11 @@ impl MediaItem {
         Self::score_display(self.global_score)
     }
 
+    /// The score in `scale`.
+    pub fn score_in(&self, scale: ScoreScale) -> Option<f32> {
+        self.score.map(|s| scale.scaled(s))
+    }
+
     pub fn is_completed(&self) -> bool {
         match &self.media_type {
             MediaItemType::Movie(WatchStatus::Completed)
@@ -152,7 +432,303 @@ impl MediaItem {
                 *s = ReadStatus::Completed;
                 p.total = p.total.or(Some(p.current));
                 if let Some(t) = p.total { p.current = t; }
+                if let Some(v) = &mut self.volumes {
+                    v.total = v.total.or(Some(v.current));
+                    if let Some(t) = v.total { v.current = t; }
+                }
+            }
+        }
+    }
+
+    /// Moves progress forward by `by` episodes / chapters, stopping at a
+    /// known total.  A planned item becomes Watching/Reading and reaching
+    /// the total completes it.  Returns false (and leaves the item
+    /// untouched) for movies and completed items.
+    pub fn advance(&mut self, by: u32) -> bool {
+        if self.is_completed() {
+            return false;
+        }
+        match &mut self.media_type {
+            MediaItemType::Movie(_) => return false,
+            MediaItemType::Series(p, s) => {
+                p.advance(by);
+                if p.is_finished() {
+                    *s = WatchStatus::Completed;
+                } else if *s == WatchStatus::PlanToWatch {
+                    *s = WatchStatus::Watching;
+                }
+            }
+            MediaItemType::Readable(_, p, s) => {
+                p.advance(by);
+                if p.is_finished() {
+                    *s = ReadStatus::Completed;
+                } else if *s == ReadStatus::PlanToRead {
+                    *s = ReadStatus::Reading;
+                }
+            }
+        }
+        true
+    }
+
+    /// Moves a readable's volume progress forward like `advance` does its
+    /// chapters, and shows progress in volumes from now on.  Finishing the
+    /// last volume completes the item.  Returns false (and leaves the item
+    /// untouched) for anything but unfinished readables.
+    pub fn advance_volumes(&mut self, by: u32) -> bool {
+        if self.is_completed() {
+            return false;
+        }
+        let MediaItemType::Readable(_, _, s) = &mut self.media_type else {
+            return false;
+        };
+        let volumes = self.volumes.get_or_insert(Progress { current: 0, total: None });
+        volumes.advance(by);
+        if volumes.is_finished() {
+            *s = ReadStatus::Completed;
+        } else if *s == ReadStatus::PlanToRead {
+            *s = ReadStatus::Reading;
+        }
+        self.prefer_volumes = true;
+        true
+    }
+
+    /// After an edit, shows progress in whichever of chapters and volumes
+    /// the edit moved, compared to `before`.  Moving both or neither keeps
+    /// the current choice.
+    pub fn follow_updated_unit(&mut self, before: &MediaItem) {
+        let chapters = |i: &MediaItem| match &i.media_type {
+            MediaItemType::Readable(_, p, _) => Some(p.current),
+            _ => None,
+        };
+        let volumes = |i: &MediaItem| i.volumes.as_ref().map(|v| v.current);
+        match (chapters(self) != chapters(before), volumes(self) != volumes(before)) {
+            (true, false) => self.prefer_volumes = false,
+            (false, true) => self.prefer_volumes = self.volumes.is_some(),
+            _ => {}
+        }
+    }
+
+    /// The progress to show, with its unit: volumes when the user tracks
+    /// those, otherwise episodes / chapters.  None for movies.
+    pub fn shown_progress(&self) -> Option<(&Progress, &'static str)> {
+        match &self.media_type {
+            MediaItemType::Movie(_) => None,
+            MediaItemType::Series(p, _) => Some((p, "episodes")),
+            MediaItemType::Readable(..) if self.prefer_volumes && self.volumes.is_some() => {
+                self.volumes.as_ref().map(|v| (v, "volumes"))
+            }
+            MediaItemType::Readable(kind, p, _) => Some((p, kind.progress_unit())),
+        }
+    }
+
+    /// Starts a rewatch / reread of a completed item: progress goes back to
+    /// zero, the status returns to Watching/Reading and `repeat_count` is
+    /// bumped.  Returns false (and leaves the item untouched) when the item
+    /// has not been completed yet.
+    pub fn start_rewatch(&mut self) -> bool {
+        if !self.is_completed() {
+            return false;
+        }
+        match &mut self.media_type {
+            MediaItemType::Movie(s) => {
+                *s = WatchStatus::Watching;
+            }
+            MediaItemType::Series(p, s) => {
+                *s = WatchStatus::Watching;
+                p.current = 0;
+            }
+            MediaItemType::Readable(_, p, s) => {
+                *s = ReadStatus::Reading;
+                p.current = 0;
+                if let Some(v) = &mut self.volumes {
+                    v.current = 0;
+                }
+            }
+        }
+        self.repeat_count += 1;
+        true
+    }
+
+    /// Folds a duplicate entry of the same work into this one.  Tags, alt
+    /// titles and external refs are unioned; the further progress (with its
+    /// status) wins, and so does the further volume count; this item's score, notes and custom fields are kept,
+    /// falling back to the duplicate's; provider fields fill gaps.
+    pub fn merge(&mut self, other: MediaItem) {
+        match (&mut self.media_type, other.media_type) {
+            (MediaItemType::Series(p, s), MediaItemType::Series(op, os)) => {
+                take_further(p, s, op, os);
             }
+            (MediaItemType::Readable(_, p, s), MediaItemType::Readable(_, op, os)) => {
+                take_further(p, s, op, os);
+            }
+            (MediaItemType::Movie(s), MediaItemType::Movie(WatchStatus::Completed)) => {
+                *s = WatchStatus::Completed;
+            }
+            _ => {}
+        }
+
+        self.tags.extend(other.tags);
+        self.score = self.score.or(other.score);
+        self.notes = match (self.notes.take(), other.notes) {
+            (Some(mine), Some(theirs)) if mine != theirs => Some(format!("{mine}\n\n---\n\n{theirs}")),
+            (mine, theirs) => mine.or(theirs),
+        };
+        self.notes_spoiler |= other.notes_spoiler;
+        self.repeat_count = self.repeat_count.max(other.repeat_count);
+
+        if self.source.is_none() {
+            self.source = other.source;
+            self.external_id = other.external_id;
+        }
+        self.global_score = self.global_score.or(other.global_score);
+        self.poster_url = self.poster_url.take().or(other.poster_url);
+
+        for title in std::iter::once(other.title).chain(other.alt_titles) {
+            if title != self.title && !self.alt_titles.contains(&title) {
+                self.alt_titles.push(title);
+            }
+        }
+        self.external_refs.extend(other.external_refs);
+        self.scheduled_for = self.scheduled_for.or(other.scheduled_for);
+        for (name, value) in other.custom_fields {
+            self.custom_fields.entry(name).or_insert(value);
         }
+        self.volumes = match (self.volumes.take(), other.volumes) {
+            (Some(mine), Some(theirs)) => Some(Progress {
+                current: mine.current.max(theirs.current),
+                total: mine.total.or(theirs.total),
+            }),
+            (mine, theirs) => mine.or(theirs),
+        };
+        self.prefer_volumes |= other.prefer_volumes;
+        self.creator = self.creator.take().or(other.creator);
+        self.content_rating = self.content_rating.or(other.content_rating);
+        self.favorite |= other.favorite;
+        self.latest_available = self.latest_available.max(other.latest_available);
+        self.downloaded |= other.downloaded;
+    }
+}
+
+/// Keeps whichever progress (and its status) is further along, with the
+/// larger known total.
+fn take_further<S>(progress: &mut Progress, status: &mut S, other: Progress, other_status: S) {
+    let total = progress.total.max(other.total);
+    if other.current > progress.current {
+        *progress = other;
+        *status = other_status;
+    }
+    progress.total = total;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(current: u32, total: Option<u32>, status: WatchStatus) -> MediaItem {
+        MediaItem::new(
+            "Test".into(),
+            MediaItemType::Series(Progress { current, total }, status),
+        )
+    }
+
+    #[test]
+    fn test_start_rewatch_resets_progress() {
+        let mut item = series(12, Some(12), WatchStatus::Completed);
+        assert!(item.start_rewatch());
+        assert_eq!(item.repeat_count, 1);
+        assert_eq!(
+            item.media_type,
+            MediaItemType::Series(Progress { current: 0, total: Some(12) }, WatchStatus::Watching)
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_merge_keeps_furthest_progress_and_unions_metadata() {
+        let mut manual = series(3, None, WatchStatus::Watching);
+        manual.set_score(8.0);
+        manual.tags.insert("cozy".into());
+
+        let mut imported = series(5, Some(28), WatchStatus::OnHold);
+        imported.title = "Sousou no Frieren".into();
+        imported.source = Some("anilist".into());
+        imported.external_id = Some("154587".into());
+        imported.set_score(6.0);
+        imported.tags.insert("fantasy".into());
+        imported.notes = Some("Great OST".into());
+
+        manual.merge(imported);
+        assert_eq!(
+            manual.media_type,
+            MediaItemType::Series(Progress { current: 5, total: Some(28) }, WatchStatus::OnHold)
+        );
+        assert_eq!(manual.get_score_display(), Some(8.0));
+        assert_eq!(manual.tags.len(), 2);
+        assert_eq!(manual.notes.as_deref(), Some("Great OST"));
+        assert_eq!(manual.external_id.as_deref(), Some("154587"));
+        assert_eq!(manual.alt_titles, vec!["Sousou no Frieren".to_string()]);
+    }
+
+    #[test]
+    fn test_start_rewatch_requires_completion() {
+        let mut item = series(3, Some(12), WatchStatus::Watching);
+        assert!(!item.start_rewatch());
+        assert_eq!(item.repeat_count, 0);
+    }
+
+    #[test]
+    fn test_advance_starts_and_completes() {
+        let mut item = series(0, Some(12), WatchStatus::PlanToWatch);
+        assert!(item.advance(1));
+        assert_eq!(
+            item.media_type,
+            MediaItemType::Series(Progress { current: 1, total: Some(12) }, WatchStatus::Watching)
+        );
+
+        assert!(item.advance(20));
+        assert_eq!(
+            item.media_type,
+            MediaItemType::Series(Progress { current: 12, total: Some(12) }, WatchStatus::Completed)
+        );
+        assert!(!item.advance(1));
+
+        let mut movie = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        assert!(!movie.advance(1));
+    }
+
+    #[test]
+    fn test_score_scales_round_trip() {
+        assert_eq!(ScoreScale::Ten.scaled(87), 8.7);
+        assert_eq!(ScoreScale::Hundred.scaled(87), 87.0);
+        assert_eq!(ScoreScale::FiveStars.scaled(87), 4.5);
+
+        assert_eq!(ScoreScale::Ten.to_stored(8.5), Ok(85));
+        assert_eq!(ScoreScale::Hundred.to_stored(73.0), Ok(73));
+        assert_eq!(ScoreScale::FiveStars.to_stored(3.5), Ok(70));
+        assert_eq!(ScoreScale::FiveStars.to_stored(3.3), Ok(70));
+        assert!(ScoreScale::FiveStars.to_stored(6.0).is_err());
+        assert!(ScoreScale::Hundred.to_stored(-1.0).is_err());
+        assert!(ScoreScale::Ten.to_stored(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_volumes_are_shown_once_updated() {
+        let mut manga = MediaItem::new(
+            "Berserk".into(),
+            MediaItemType::Readable(ReadableKind::Manga, Progress { current: 40, total: None }, ReadStatus::PlanToRead),
+        );
+        assert_eq!(manga.shown_progress().map(|(p, unit)| (p.current, unit)), Some((40, "chapters")));
+
+        assert!(manga.advance_volumes(2));
+        assert_eq!(manga.shown_progress().map(|(p, unit)| (p.current, unit)), Some((2, "volumes")));
+        assert!(matches!(manga.media_type, MediaItemType::Readable(_, _, ReadStatus::Reading)));
+
+        let before = manga.clone();
+        manga.advance(5);
+        manga.follow_updated_unit(&before);
+        assert_eq!(manga.shown_progress().map(|(p, unit)| (p.current, unit)), Some((45, "chapters")));
+
+        let mut movie = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Watching));
+        assert!(!movie.advance_volumes(1));
+        assert_eq!(movie.volumes, None);
+    }
+}
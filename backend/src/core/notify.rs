@@ -0,0 +1,393 @@
+//! Push notifications about library events: an item completed, a new
+//! episode aired, a backup failed.  Where they go is configured in the
+//! `notifiers` setting; each channel (webhook, ntfy, email, Telegram)
+//! implements `Notifier` in `infra::notifiers`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::models::MediaItem;
+use crate::core::search::NextAiring;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NoticeEvent {
+    /// An item was marked completed by hand (not by an import or refresh).
+    ItemCompleted,
+    /// An episode of a series being watched or planned aired.
+    EpisodeAired,
+    /// An automatic backup could not be written or uploaded.
+    BackupFailed,
+    /// Sent by `POST /api/notifiers/test`, whatever the notifier's events.
+    Test,
+}
+
+impl NoticeEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NoticeEvent::ItemCompleted => "item_completed",
+            NoticeEvent::EpisodeAired => "episode_aired",
+            NoticeEvent::BackupFailed => "backup_failed",
+            NoticeEvent::Test => "test",
+        }
+    }
+}
+
+/// One notification, as every channel sends it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notice {
+    pub event: NoticeEvent,
+    pub title: String,
+    pub message: String,
+}
+
+impl Notice {
+    pub fn completed(title: &str) -> Self {
+        Notice {
+            event: NoticeEvent::ItemCompleted,
+            title: "Completed".into(),
+            message: format!("Finished '{title}'"),
+        }
+    }
+
+    pub fn episode_aired(title: &str, episode: u32) -> Self {
+        Notice {
+            event: NoticeEvent::EpisodeAired,
+            title: "New episode".into(),
+            message: format!("Episode {episode} of '{title}' is out"),
+        }
+    }
+
+    pub fn backup_failed(error: &str) -> Self {
+        Notice {
+            event: NoticeEvent::BackupFailed,
+            title: "Backup failed".into(),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn test() -> Self {
+        Notice {
+            event: NoticeEvent::Test,
+            title: "Test".into(),
+            message: "Notifications from kars arrive here".into(),
+        }
+    }
+}
+
+/// A channel notices are sent through.
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Sends one notice.  Blocking.
+    fn send(&self, notice: &Notice) -> Result<(), String>;
+}
+
+/// One entry of the `notifiers` setting: where to send notices and which.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NotifierConfig {
+    /// Events sent; empty sends all of them.
+    #[serde(default)]
+    pub events: Vec<NoticeEvent>,
+    #[serde(flatten)]
+    pub channel: Channel,
+}
+
+impl NotifierConfig {
+    pub fn wants(&self, event: NoticeEvent) -> bool {
+        event == NoticeEvent::Test || self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+pub const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+fn default_ntfy_server() -> String {
+    DEFAULT_NTFY_SERVER.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Channel {
+    /// A JSON `POST` of `{event, title, message}`.  The path and query
+    /// often carry a token, so they read as `REDACTED` like other secrets.
+    Webhook { url: String },
+    /// A message to an ntfy topic, on ntfy.sh unless `server` is set.
+    Ntfy {
+        #[serde(default = "default_ntfy_server")]
+        server: String,
+        topic: String,
+        /// Access token for a protected topic.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// An email sent through an SMTP server.
+    Email {
+        host: String,
+        /// Defaults to 587 for `starttls`, 465 for `tls` and 25 for `none`.
+        #[serde(default)]
+        port: Option<u16>,
+        #[serde(default)]
+        security: SmtpSecurity,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: String,
+    },
+    /// A message from a Telegram bot to a chat.
+    Telegram {
+        /// Left out (or sent as `REDACTED`) to keep the stored token.
+        #[serde(default)]
+        bot_token: String,
+        chat_id: String,
+    },
+}
+
+/// What secrets (SMTP passwords, bot and ntfy tokens, webhook paths) read
+/// as in settings and exports.  Sent back in place of a secret, it keeps the stored one.
+pub const REDACTED: &str = "********";
+
+impl Channel {
+    /// The channel's secret, if it has one.
+    fn secret_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Channel::Webhook { .. } => None,
+            Channel::Ntfy { token, .. } => token.as_mut(),
+            Channel::Email { password, .. } => password.as_mut(),
+            Channel::Telegram { bot_token, .. } => Some(bot_token),
+        }
+    }
+
+    /// Whether `other` sends to the same place, so their secrets are the
+    /// same account's.
+    fn same_target(&self, other: &Channel) -> bool {
+        match (self, other) {
+            (Channel::Ntfy { server, topic, .. }, Channel::Ntfy { server: s, topic: t, .. }) => server == s && topic == t,
+            (Channel::Email { host, username, .. }, Channel::Email { host: h, username: u, .. }) => {
+                host == h && username == u
+            }
+            (Channel::Telegram { chat_id, .. }, Channel::Telegram { chat_id: c, .. }) => chat_id == c,
+            (Channel::Webhook { url }, Channel::Webhook { url: u }) => url_origin(url) == url_origin(u),
+            _ => false,
+        }
+    }
+}
+
+/// `scheme://host:port` of a URL, without any credentials before the host.
+fn url_origin(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    format!("{scheme}://{host}")
+}
+
+/// A webhook URL as shown: the origin, with everything after it redacted.
+fn redact_url(url: &str) -> String {
+    let origin = url_origin(url);
+    if url == origin || url.strip_prefix(&origin) == Some("/") {
+        url.to_string()
+    } else {
+        format!("{origin}/{REDACTED}")
+    }
+}
+
+/// Replaces every secret with `REDACTED`.
+pub fn redact_notifiers(notifiers: &mut [NotifierConfig]) {
+    for notifier in notifiers {
+        if let Channel::Webhook { url } = &mut notifier.channel {
+            *url = redact_url(url);
+        } else if let Some(secret) = notifier.channel.secret_mut().filter(|s| !s.is_empty()) {
+            *secret = REDACTED.into();
+        }
+    }
+}
+
+/// Fills in secrets left out or sent as `REDACTED` from the `stored`
+/// notifier with the same target, preferring the one at the same position
+/// (two webhooks often share a host).  Without one, a redacted secret is
+/// dropped rather than saved as is.
+pub fn keep_secrets(notifiers: &mut [NotifierConfig], stored: &[NotifierConfig]) {
+    for (i, notifier) in notifiers.iter_mut().enumerate() {
+        let same = stored
+            .get(i)
+            .filter(|s| s.channel.same_target(&notifier.channel))
+            .or_else(|| stored.iter().find(|s| s.channel.same_target(&notifier.channel)));
+        if let (Channel::Webhook { url }, Some(Channel::Webhook { url: stored_url })) =
+            (&mut notifier.channel, same.map(|s| &s.channel))
+        {
+            if url.ends_with(REDACTED) && redact_url(stored_url) == *url {
+                *url = stored_url.clone();
+            }
+            continue;
+        }
+        let kept = same.and_then(|s| s.channel.clone().secret_mut().cloned());
+        match &mut notifier.channel {
+            Channel::Ntfy { token: secret, .. } | Channel::Email { password: secret, .. } => {
+                if secret.as_deref().is_none_or(|s| s == REDACTED) {
+                    *secret = kept;
+                } else if secret.as_deref() == Some("") {
+                    *secret = None;
+                }
+            }
+            Channel::Telegram { bot_token, .. } if bot_token.is_empty() || bot_token == REDACTED => {
+                *bot_token = kept.unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    #[default]
+    Starttls,
+    Tls,
+    /// Plain text; only for a relay on the same host or network.
+    None,
+}
+
+/// Checks the `notifiers` setting: required fields filled in, URLs with
+/// a scheme and addresses that look like addresses.
+pub fn validate_notifiers(notifiers: &[NotifierConfig]) -> Result<(), String> {
+    fn url(value: &str, field: &str) -> Result<(), String> {
+        if !(value.starts_with("http://") || value.starts_with("https://")) {
+            return Err(format!("{field} must start with http:// or https://"));
+        }
+        Ok(())
+    }
+    fn filled(value: &str, field: &str) -> Result<(), String> {
+        if value.trim().is_empty() {
+            return Err(format!("{field} must not be empty"));
+        }
+        Ok(())
+    }
+    fn address(value: &str, field: &str) -> Result<(), String> {
+        match value.split_once('@') {
+            Some((user, domain)) if !user.is_empty() && !domain.is_empty() => Ok(()),
+            _ => Err(format!("{field} must be an email address")),
+        }
+    }
+
+    for notifier in notifiers {
+        match &notifier.channel {
+            Channel::Webhook { url: u } => {
+                url(u, "Webhook url")?;
+                if u.ends_with(REDACTED) {
+                    return Err("Webhook url is redacted; enter the full URL again".into());
+                }
+            }
+            Channel::Ntfy { server, topic, .. } => {
+                url(server, "ntfy server")?;
+                filled(topic, "ntfy topic")?;
+            }
+            Channel::Email { host, from, to, .. } => {
+                filled(host, "SMTP host")?;
+                address(from, "Email from")?;
+                address(to, "Email to")?;
+            }
+            Channel::Telegram { bot_token, chat_id } => {
+                filled(bot_token, "Telegram bot_token")?;
+                filled(chat_id, "Telegram chat_id")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Episodes that aired since the last lookup.  `upcoming` holds the next
+/// episode of every followed series as last looked up; the announced ones
+/// are the remembered episodes whose time has come, and the rest is
+/// replaced by `lookup`.  Episodes already out when first seen are not
+/// announced.
+pub fn aired_episodes(
+    upcoming: &mut HashMap<Uuid, (String, NextAiring)>,
+    lookup: &[(&MediaItem, NextAiring)],
+    now: DateTime<Utc>,
+) -> Vec<Notice> {
+    let mut aired: Vec<&(String, NextAiring)> = upcoming.values().filter(|(_, next)| next.airing_at <= now).collect();
+    aired.sort_by_key(|(_, next)| next.airing_at);
+    let notices = aired.into_iter().map(|(title, next)| Notice::episode_aired(title, next.episode)).collect();
+    *upcoming = lookup
+        .iter()
+        .filter(|(_, next)| next.airing_at > now)
+        .map(|(item, next)| (item.id, (item.title.clone(), next.clone())))
+        .collect();
+    notices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, Progress, WatchStatus};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_config_parses_and_aired_episodes_are_announced_once() {
+        let notifiers: Vec<NotifierConfig> = serde_json::from_str(
+            r#"[{"kind": "ntfy", "topic": "kars", "events": ["episode_aired"]},
+                {"kind": "telegram", "bot_token": "123:abc", "chat_id": "42"}]"#,
+        )
+        .unwrap();
+        assert!(matches!(&notifiers[0].channel, Channel::Ntfy { server, .. } if server == DEFAULT_NTFY_SERVER));
+        assert!(!notifiers[0].wants(NoticeEvent::BackupFailed));
+        assert!(notifiers[0].wants(NoticeEvent::Test));
+        assert!(notifiers[1].wants(NoticeEvent::BackupFailed));
+        assert!(validate_notifiers(&notifiers).is_ok());
+        let email = r#"[{"kind": "email", "host": "smtp.example.org", "from": "kars", "to": "me@example.org"}]"#;
+        assert!(validate_notifiers(&serde_json::from_str::<Vec<_>>(email).unwrap()).is_err());
+
+        let at = |h| Utc.with_ymd_and_hms(2026, 10, 1, h, 0, 0).unwrap();
+        let progress = Progress { current: 4, total: Some(28) };
+        let show = MediaItem::new("Frieren".into(), MediaItemType::Series(progress, WatchStatus::Watching));
+        let next = |episode, h| NextAiring { external_id: "1".into(), episode, airing_at: at(h) };
+        let mut upcoming = HashMap::new();
+
+        // Episode 4 was out before the first lookup; 5 airs at 14:00.
+        assert!(aired_episodes(&mut upcoming, &[(&show, next(4, 9))], at(10)).is_empty());
+        assert!(aired_episodes(&mut upcoming, &[(&show, next(5, 14))], at(11)).is_empty());
+        let notices = aired_episodes(&mut upcoming, &[(&show, next(5, 14))], at(15));
+        assert_eq!(notices, [Notice::episode_aired("Frieren", 5)]);
+        assert_eq!(notices[0].message, "Episode 5 of 'Frieren' is out");
+        assert!(aired_episodes(&mut upcoming, &[(&show, next(5, 14))], at(16)).is_empty());
+    }
+
+    #[test]
+    fn test_secrets_are_redacted_and_kept() {
+        let stored: Vec<NotifierConfig> = serde_json::from_str(
+            r#"[{"kind": "ntfy", "topic": "kars", "token": "tk_secret"},
+                {"kind": "email", "host": "smtp.example.org", "username": "me", "password": "hunter2",
+                 "from": "kars@example.org", "to": "me@example.org"},
+                {"kind": "telegram", "bot_token": "123:abc", "chat_id": "42"},
+                {"kind": "webhook", "url": "https://hooks.example.org/services/T0/B0/xoxb?token=abc"},
+                {"kind": "webhook", "url": "https://hooks.example.org/services/T1/B1/other"}]"#,
+        )
+        .unwrap();
+        let mut shown = stored.clone();
+        redact_notifiers(&mut shown);
+        let json = serde_json::to_string(&shown).unwrap();
+        assert!(!json.contains("tk_secret") && !json.contains("hunter2") && !json.contains("123:abc"));
+        assert!(!json.contains("xoxb") && !json.contains("token=abc") && !json.contains("other"));
+        assert!(matches!(&shown[3].channel, Channel::Webhook { url } if url == "https://hooks.example.org/********"));
+        assert!(validate_notifiers(&shown).is_err());
+
+        // Sent back as shown, or with the secrets left out, nothing changes.
+        keep_secrets(&mut shown, &stored);
+        assert_eq!(shown, stored);
+        let mut omitted: Vec<NotifierConfig> =
+            serde_json::from_str(r#"[{"kind": "telegram", "chat_id": "42"}, {"kind": "ntfy", "topic": "other", "token": "********"}]"#)
+                .unwrap();
+        keep_secrets(&mut omitted, &stored);
+        assert!(matches!(&omitted[0].channel, Channel::Telegram { bot_token, .. } if bot_token == "123:abc"));
+        assert!(matches!(&omitted[1].channel, Channel::Ntfy { token: None, .. }));
+
+        // A webhook on another host gets nothing from the stored ones.
+        let mut moved: Vec<NotifierConfig> =
+            serde_json::from_str(r#"[{"kind": "webhook", "url": "https://elsewhere.example/********"}]"#).unwrap();
+        keep_secrets(&mut moved, &stored);
+        assert!(matches!(&moved[0].channel, Channel::Webhook { url } if url == "https://elsewhere.example/********"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Item writes buffered while Turso is unreachable, and the checks that
+//! decide whether replaying one would overwrite a change made meanwhile.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::core::api_types::ApiMediaItem;
+use crate::core::models::MediaItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuedOp {
+    /// `POST /api/items`, with its `upsert` flag.
+    Create { upsert: bool },
+    /// `PUT /api/items/:id`.
+    Update,
+    /// `DELETE /api/items/:id`.
+    Delete,
+}
+
+impl QueuedOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueuedOp::Create { upsert: false } => "create",
+            QueuedOp::Create { upsert: true } => "upsert",
+            QueuedOp::Update => "update",
+            QueuedOp::Delete => "delete",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "create" => Some(QueuedOp::Create { upsert: false }),
+            "upsert" => Some(QueuedOp::Create { upsert: true }),
+            "update" => Some(QueuedOp::Update),
+            "delete" => Some(QueuedOp::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// A write waiting for Turso.  Creates and updates keep the request body
+/// as sent, so scores are converted with the score scale in force when
+/// they are replayed.
+#[derive(Debug, Clone)]
+pub struct QueuedWrite {
+    /// Order of arrival; 0 until stored.
+    pub id: i64,
+    pub item_id: Uuid,
+    pub op: QueuedOp,
+    pub payload: Option<ApiMediaItem>,
+    /// The `updated_at` the client last saw; for deletes, when the delete
+    /// was queued.  None when the client did not say.
+    pub base: Option<DateTime<Utc>>,
+    pub queued_at: DateTime<Utc>,
+    /// Why the last replay held the write back.
+    pub conflict: Option<String>,
+}
+
+impl QueuedWrite {
+    pub fn new(item_id: Uuid, op: QueuedOp, payload: Option<ApiMediaItem>, now: DateTime<Utc>) -> Self {
+        let base = match op {
+            QueuedOp::Delete => Some(now),
+            _ => payload
+                .as_ref()
+                .and_then(|p| p.updated_at.as_deref())
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc)),
+        };
+        QueuedWrite { id: 0, item_id, op, payload, base, queued_at: now, conflict: None }
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.payload.as_ref().map(|p| p.title.as_str())
+    }
+}
+
+/// Why `write` cannot be replayed over `current`, the stored item it
+/// targets (for creates, the one it would duplicate).  Writes to items
+/// an earlier write of the same replay already changed skip the
+/// timestamp check: they were queued on top of that change.
+pub fn conflict(write: &QueuedWrite, current: Option<&MediaItem>, replayed_item: bool) -> Option<String> {
+    let changed_at = |current: &MediaItem| {
+        current
+            .updated_at
+            .filter(|updated| !replayed_item && write.base.is_some_and(|base| *updated > base))
+    };
+    match (write.op, current) {
+        (QueuedOp::Create { upsert: false }, Some(current)) => {
+            Some(format!("{} was added to the library meanwhile", current.title))
+        }
+        (QueuedOp::Create { .. }, _) => None,
+        (QueuedOp::Update, None) => Some("The item was deleted meanwhile".into()),
+        (QueuedOp::Update | QueuedOp::Delete, Some(current)) => changed_at(current).map(|at| {
+            let at = at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+            format!("{} was changed at {at}, after this write was based on it", current.title)
+        }),
+        (QueuedOp::Delete, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, WatchStatus};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_conflict_when_changed_after_the_base() {
+        let at = |h| Utc.with_ymd_and_hms(2026, 10, 1, h, 0, 0).unwrap();
+        let mut stored = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        stored.updated_at = Some(at(12));
+        let write = |op, base| QueuedWrite { base: Some(base), ..QueuedWrite::new(stored.id, op, None, at(13)) };
+
+        assert_eq!(conflict(&write(QueuedOp::Update, at(12)), Some(&stored), false), None);
+        assert!(conflict(&write(QueuedOp::Update, at(11)), Some(&stored), false).is_some());
+        // Based on an earlier write of the same replay.
+        assert_eq!(conflict(&write(QueuedOp::Update, at(11)), Some(&stored), true), None);
+        assert!(conflict(&write(QueuedOp::Update, at(12)), None, false).is_some());
+
+        assert_eq!(conflict(&write(QueuedOp::Delete, at(13)), Some(&stored), false), None);
+        assert_eq!(conflict(&write(QueuedOp::Delete, at(13)), None, false), None);
+        assert!(conflict(&write(QueuedOp::Create { upsert: false }, at(13)), Some(&stored), false).is_some());
+        assert_eq!(conflict(&write(QueuedOp::Create { upsert: true }, at(13)), Some(&stored), false), None);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::core::matcher;
+use crate::core::models::MediaItem;
+
+/// The instance's own library, served at the usual paths.
+pub const DEFAULT_PROFILE: &str = "default";
+/// Longest accepted profile name.
+pub const MAX_PROFILE_NAME: usize = 32;
+
+/// Profile names end up in URLs and file names, so they are limited to
+/// lowercase letters, digits, `-` and `_`.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_PROFILE_NAME {
+        return Err(format!("Profile names are 1 to {MAX_PROFILE_NAME} characters"));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err("Profile names may only contain a-z, 0-9, '-' and '_'".into());
+    }
+    Ok(())
+}
+
+/// A title scored in one profile and in at least one other.
+pub struct SharedScore<'a> {
+    pub item: &'a MediaItem,
+    /// Stored 0-100.
+    pub score: u8,
+    /// Scores of the same work in the other profiles, stored 0-100.
+    pub others: Vec<u8>,
+    /// How far the score sits above the profile's own mean, less how far
+    /// the others' scores sit above theirs, in stored points.  Tells a
+    /// real disagreement from someone who scores everything higher.
+    pub normalized_difference: f32,
+}
+
+impl SharedScore<'_> {
+    /// Mean of the other profiles' scores, stored 0-100.
+    pub fn average(&self) -> f32 {
+        self.others.iter().map(|&s| s as f32).sum::<f32>() / self.others.len() as f32
+    }
+}
+
+/// Mean of the scored items, stored 0-100.
+pub fn mean_score(items: &[MediaItem]) -> Option<f32> {
+    let scores: Vec<f32> = items.iter().filter_map(|i| i.score).map(f32::from).collect();
+    (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32)
+}
+
+/// The scored items of `mine` that other libraries scored too, matched by
+/// id or as the same work (see `matcher::find_duplicate`), biggest gap
+/// first.
+pub fn shared_scores<'a>(mine: &'a [MediaItem], others: &[Vec<MediaItem>]) -> Vec<SharedScore<'a>> {
+    let my_mean = mean_score(mine).unwrap_or(0.0);
+    let others: Vec<(f32, Vec<MediaItem>)> = others
+        .iter()
+        .map(|items| (mean_score(items).unwrap_or(0.0), items.iter().filter(|i| i.score.is_some()).cloned().collect()))
+        .collect();
+
+    let mut shared: Vec<SharedScore> = mine
+        .iter()
+        .filter_map(|item| {
+            let score = item.score?;
+            let matches: Vec<(f32, u8)> = others
+                .iter()
+                .filter_map(|(mean, items)| {
+                    // A library restored from another's export keeps its ids.
+                    let same = items.iter().find(|i| i.id == item.id).or_else(|| matcher::find_duplicate(items, item));
+                    Some((*mean, same?.score?))
+                })
+                .collect();
+            if matches.is_empty() {
+                return None;
+            }
+            let their_offset = matches.iter().map(|&(mean, s)| s as f32 - mean).sum::<f32>() / matches.len() as f32;
+            Some(SharedScore {
+                item,
+                score,
+                others: matches.iter().map(|&(_, s)| s).collect(),
+                normalized_difference: (score as f32 - my_mean) - their_offset,
+            })
+        })
+        .collect();
+    shared.sort_by(|a, b| {
+        let gap = |s: &SharedScore| (s.score as f32 - s.average()).abs();
+        gap(b).total_cmp(&gap(a)).then_with(|| a.item.title.cmp(&b.item.title))
+    });
+    shared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("partner").is_ok());
+        assert!(validate_name("kid-2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("Partner").is_err());
+        assert!(validate_name("../x").is_err());
+        assert!(validate_name(&"a".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn test_shared_scores_are_normalized_by_each_mean() {
+        use crate::core::models::{MediaItemType, WatchStatus};
+        let scored = |title: &str, score| {
+            let mut item = MediaItem::new(title.into(), MediaItemType::Movie(WatchStatus::Completed));
+            item.score = Some(score);
+            item
+        };
+        let mine = vec![scored("Heat", 90), scored("Alien", 50), MediaItem::new("Brazil".into(), MediaItemType::Movie(WatchStatus::Completed))];
+        let partner = vec![scored("heat", 80), scored("Alien", 80), scored("Brazil", 20)];
+
+        let shared = shared_scores(&mine, &[partner]);
+        let rows: Vec<(&str, f32, f32)> = shared
+            .iter()
+            .map(|s| (s.item.title.as_str(), s.average(), s.normalized_difference))
+            .collect();
+        // Means: 70 here, 60 there.
+        assert_eq!(rows, [("Alien", 80.0, -40.0), ("Heat", 80.0, 0.0)]);
+        assert!(shared_scores(&mine, &[]).is_empty());
+    }
+}
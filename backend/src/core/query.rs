@@ -0,0 +1,284 @@
+//! Library search syntax, e.g. `status:watching tag:fantasy score>=8
+//! type:manga creator:"kentaro miura" is:favorite frieren`.  Terms are ANDed; a
+//! leading `-` negates one, and double quotes keep spaces in a value
+//! (`tag:"slice of life"`).  Words that are not a filter match titles, so
+//! `re:zero` is just text.
+
+use crate::core::api_types::STATUSES;
+use crate::core::goals::GOAL_MEDIA_TYPES;
+use crate::core::models::ScoreScale;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn sql(self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// One of `STATUSES`; watch and read variants match each other.
+    Status(String),
+    Tag(String),
+    /// Author or studio, the whole name.
+    Creator(String),
+    /// One of `GOAL_MEDIA_TYPES`.
+    Type(String),
+    /// A score in the instance's scale.
+    Score(Comparison, f32),
+    /// `is:favorite`.
+    Favorite,
+    /// Part of the title.
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub negated: bool,
+    pub filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    pub terms: Vec<Term>,
+}
+
+/// A parameter of a compiled query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Text(String),
+    Integer(i64),
+}
+
+/// A compiled query: a condition on `media_items` with its `?` parameters
+/// in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sql {
+    pub condition: String,
+    pub params: Vec<SqlValue>,
+}
+
+/// Splits on whitespace outside double quotes.  The flag is set for words
+/// that start with a quote, which are always text.
+fn words(input: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut words = Vec::new();
+    let (mut word, mut quoted, mut in_quotes) = (String::new(), false, false);
+    for c in input.chars() {
+        match c {
+            '"' => {
+                if !in_quotes && word.is_empty() {
+                    quoted = true;
+                }
+                in_quotes = !in_quotes;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !word.is_empty() {
+                    words.push((std::mem::take(&mut word), quoted));
+                }
+                quoted = false;
+            }
+            c => word.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("Unclosed quote".into());
+    }
+    if !word.is_empty() {
+        words.push((word, quoted));
+    }
+    Ok(words)
+}
+
+/// Reads `score>=8`, `score<5`, `score=7.5` or `score:7.5`.
+fn parse_score(rest: &str) -> Result<Filter, String> {
+    let (comparison, value) = [
+        (">=", Comparison::Ge),
+        ("<=", Comparison::Le),
+        (">", Comparison::Gt),
+        ("<", Comparison::Lt),
+        ("=", Comparison::Eq),
+        (":", Comparison::Eq),
+    ]
+    .into_iter()
+    .find_map(|(op, comparison)| rest.strip_prefix(op).map(|value| (comparison, value)))
+    .ok_or_else(|| format!("Expected a comparison after 'score', e.g. score>=8, not 'score{rest}'"))?;
+    let value = value
+        .parse::<f32>()
+        .map_err(|_| format!("Invalid score '{value}'"))?;
+    Ok(Filter::Score(comparison, value))
+}
+
+fn parse_term(word: &str) -> Result<Filter, String> {
+    if let Some(rest) = word.strip_prefix("score")
+        && rest.starts_with(['>', '<', '=', ':'])
+    {
+        return parse_score(rest);
+    }
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(Filter::Text(word.to_string()));
+    };
+    match key {
+        "status" => {
+            let value = value.to_lowercase();
+            if !STATUSES.contains(&value.as_str()) {
+                return Err(format!("Unknown status '{value}'; expected one of {}", STATUSES.join(", ")));
+            }
+            Ok(Filter::Status(value))
+        }
+        "type" => {
+            let value = value.to_lowercase();
+            if !GOAL_MEDIA_TYPES.contains(&value.as_str()) {
+                return Err(format!("Unknown type '{value}'; expected one of {}", GOAL_MEDIA_TYPES.join(", ")));
+            }
+            Ok(Filter::Type(value))
+        }
+        "tag" if value.is_empty() => Err("Expected a tag after 'tag:'".into()),
+        "tag" => Ok(Filter::Tag(value.to_string())),
+        "creator" if value.is_empty() => Err("Expected a name after 'creator:'".into()),
+        "creator" => Ok(Filter::Creator(value.to_string())),
+        "is" if value.eq_ignore_ascii_case("favorite") => Ok(Filter::Favorite),
+        "is" => Err(format!("Unknown 'is:{value}'; expected is:favorite")),
+        _ => Ok(Filter::Text(word.to_string())),
+    }
+}
+
+/// Parses a search.  Status and type names are checked here; scores are
+/// checked against the scale when compiling.
+pub fn parse(input: &str) -> Result<Query, String> {
+    let mut terms = Vec::new();
+    for (word, quoted) in words(input)? {
+        let (negated, word) = match word.strip_prefix('-') {
+            Some(rest) if !quoted && !rest.is_empty() => (true, rest),
+            _ => (false, word.as_str()),
+        };
+        let filter = if quoted { Filter::Text(word.to_string()) } else { parse_term(word)? };
+        terms.push(Term { negated, filter });
+    }
+    Ok(Query { terms })
+}
+
+/// The status with the same meaning for the other kind of item:
+/// `watching` for `reading`, `plan_to_read` for `plan_to_watch`.
+fn status_pair(status: &str) -> (&str, &str) {
+    match status {
+        "watching" | "reading" => ("watching", "reading"),
+        "plan_to_watch" | "plan_to_read" => ("plan_to_watch", "plan_to_read"),
+        other => (other, other),
+    }
+}
+
+impl Query {
+    /// Compiles the query into a condition on `media_items`.  Scores are
+    /// read in `scale`; an empty query matches everything.
+    pub fn to_sql(&self, scale: ScoreScale) -> Result<Sql, String> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        for term in &self.terms {
+            let condition = match &term.filter {
+                Filter::Status(status) => {
+                    let (watch, read) = status_pair(status);
+                    params.push(SqlValue::Text(watch.to_string()));
+                    params.push(SqlValue::Text(read.to_string()));
+                    "(watch_status = ? OR read_status = ?)".to_string()
+                }
+                Filter::Tag(tag) => {
+                    params.push(SqlValue::Text(tag.clone()));
+                    "EXISTS (SELECT 1 FROM json_each(media_items.tags) WHERE json_each.value = ? COLLATE NOCASE)"
+                        .to_string()
+                }
+                Filter::Creator(creator) => {
+                    params.push(SqlValue::Text(creator.clone()));
+                    "creator = ? COLLATE NOCASE".to_string()
+                }
+                Filter::Type(media_type) => match media_type.as_str() {
+                    "movie" => "media_type = 'movie'".to_string(),
+                    "series" => "(media_type = 'series' AND COALESCE(source, '') <> 'anilist')".to_string(),
+                    "anime" => "(media_type = 'series' AND source = 'anilist')".to_string(),
+                    kind => {
+                        params.push(SqlValue::Text(kind.to_string()));
+                        "readable_kind = ?".to_string()
+                    }
+                },
+                Filter::Score(comparison, value) => {
+                    params.push(SqlValue::Integer(scale.to_stored(*value)? as i64));
+                    format!("score {} ?", comparison.sql())
+                }
+                Filter::Favorite => "favorite = 1".to_string(),
+                Filter::Text(text) => {
+                    params.push(SqlValue::Text(format!("%{text}%")));
+                    "title LIKE ?".to_string()
+                }
+            };
+            // Missing columns (no score, no read status) count as a miss
+            // either way.
+            conditions.push(if term.negated {
+                format!("NOT COALESCE({condition}, 0)")
+            } else {
+                format!("COALESCE({condition}, 0)")
+            });
+        }
+        let condition = if conditions.is_empty() { "1".to_string() } else { conditions.join(" AND ") };
+        Ok(Sql { condition, params })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filters_and_text() {
+        let query = parse(r#"status:watching -tag:"Slice of Life" score>=8 type:manga creator:"Kentaro Miura" -is:favorite frieren re:zero"#).unwrap();
+        let filters: Vec<(bool, Filter)> = query.terms.into_iter().map(|t| (t.negated, t.filter)).collect();
+        assert_eq!(
+            filters,
+            vec![
+                (false, Filter::Status("watching".into())),
+                (true, Filter::Tag("Slice of Life".into())),
+                (false, Filter::Score(Comparison::Ge, 8.0)),
+                (false, Filter::Type("manga".into())),
+                (false, Filter::Creator("Kentaro Miura".into())),
+                (true, Filter::Favorite),
+                (false, Filter::Text("frieren".into())),
+                (false, Filter::Text("re:zero".into())),
+            ]
+        );
+        assert!(parse("status:binging").is_err());
+        assert!(parse("score>=high").is_err());
+        assert!(parse("is:dropped").is_err());
+        assert!(parse("\"unclosed").is_err());
+    }
+
+    #[test]
+    fn test_to_sql_reads_scores_in_the_scale() {
+        let sql = parse("score>4 status:reading").unwrap().to_sql(ScoreScale::FiveStars).unwrap();
+        assert_eq!(
+            sql.condition,
+            "COALESCE(score > ?, 0) AND COALESCE((watch_status = ? OR read_status = ?), 0)"
+        );
+        assert_eq!(
+            sql.params,
+            vec![
+                SqlValue::Integer(80),
+                SqlValue::Text("watching".into()),
+                SqlValue::Text("reading".into()),
+            ]
+        );
+        assert!(parse("score>=11").unwrap().to_sql(ScoreScale::Ten).is_err());
+        assert_eq!(Query::default().to_sql(ScoreScale::Ten).unwrap().condition, "1");
+    }
+}
@@ -0,0 +1,121 @@
+//! Pairwise ranking: the user picks the better of two items, and Elo
+//! ratings turn the picks into an order.  Only finished items and
+//! favorites take part.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::core::models::MediaItem;
+
+/// Rating of an item that was never compared.
+pub const INITIAL_RATING: f64 = 1500.0;
+/// Most a single pick moves a rating.
+const K_FACTOR: f64 = 32.0;
+/// Opponents are drawn from this many items rated closest to the first.
+const CLOSEST: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub item_id: Uuid,
+    pub rating: f64,
+    pub comparisons: u32,
+}
+
+impl Rating {
+    pub fn new(item_id: Uuid) -> Self {
+        Rating { item_id, rating: INITIAL_RATING, comparisons: 0 }
+    }
+}
+
+/// Whether an item is offered for comparison.
+pub fn is_rankable(item: &MediaItem) -> bool {
+    !item.hidden && (item.favorite || item.is_completed())
+}
+
+/// Chance that an item rated `a` beats one rated `b`.
+fn expected(a: f64, b: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+}
+
+/// Moves both ratings after `winner` was picked over `loser`.  An upset
+/// moves them further than an expected win.
+pub fn record(winner: &mut Rating, loser: &mut Rating) {
+    let gain = K_FACTOR * (1.0 - expected(winner.rating, loser.rating));
+    winner.rating += gain;
+    loser.rating -= gain;
+    winner.comparisons += 1;
+    loser.comparisons += 1;
+}
+
+/// The next two items to compare: one of the least compared items, and
+/// one of the items rated closest to it, so every pick says something
+/// new.  `random` returns values in 0.0-1.0 to vary the pair between
+/// calls.  None when fewer than two items are rankable.
+pub fn next_pair<'a>(
+    items: &'a [MediaItem],
+    ratings: &HashMap<Uuid, Rating>,
+    mut random: impl FnMut() -> f64,
+) -> Option<[(&'a MediaItem, Rating); 2]> {
+    let pool: Vec<(&MediaItem, Rating)> = items
+        .iter()
+        .filter(|i| is_rankable(i))
+        .map(|i| (i, ratings.get(&i.id).copied().unwrap_or_else(|| Rating::new(i.id))))
+        .collect();
+    if pool.len() < 2 {
+        return None;
+    }
+    let pick = |candidates: &[&(&'a MediaItem, Rating)], r: f64| {
+        *candidates[((r * candidates.len() as f64) as usize).min(candidates.len() - 1)]
+    };
+
+    let fewest = pool.iter().map(|(_, r)| r.comparisons).min()?;
+    let least_compared: Vec<_> = pool.iter().filter(|(_, r)| r.comparisons == fewest).collect();
+    let first = pick(&least_compared, random());
+
+    let distance = |r: &Rating| (r.rating - first.1.rating).abs();
+    let mut others: Vec<_> = pool.iter().filter(|(i, _)| i.id != first.0.id).collect();
+    others.sort_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)).then(a.comparisons.cmp(&b.comparisons)));
+    others.truncate(CLOSEST);
+    Some([first, pick(&others, random())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, WatchStatus};
+
+    #[test]
+    fn test_pairs_close_ratings_and_records_upsets() {
+        let movie = |title: &str, status| MediaItem::new(title.into(), MediaItemType::Movie(status));
+        let mut planned = movie("Dune", WatchStatus::PlanToWatch);
+        let items = vec![
+            movie("Heat", WatchStatus::Completed),
+            movie("Alien", WatchStatus::Completed),
+            movie("Ronin", WatchStatus::Completed),
+            movie("Brazil", WatchStatus::Completed),
+            movie("Tenet", WatchStatus::Completed),
+            planned.clone(),
+        ];
+        let id = |i: usize| items[i].id;
+        let ids = |pair: Option<[(&MediaItem, Rating); 2]>| pair.map(|[a, b]| (a.0.id, b.0.id));
+        let mut ratings = HashMap::new();
+        for (i, rating, comparisons) in [(0, 1600.0, 4), (1, 1500.0, 2), (2, 1410.0, 3), (3, 1590.0, 5), (4, 1100.0, 3)] {
+            ratings.insert(id(i), Rating { item_id: id(i), rating, comparisons });
+        }
+
+        // Alien is the least compared; the opponents closest to it are
+        // Ronin, Brazil and Heat, never the unfinished Dune.
+        assert_eq!(ids(next_pair(&items, &ratings, || 0.0)), Some((id(1), id(2))));
+        assert_eq!(ids(next_pair(&items, &ratings, || 0.99)), Some((id(1), id(0))));
+        planned.favorite = true;
+        let pair = [items[0].clone(), planned.clone()];
+        assert_eq!(ids(next_pair(&pair, &HashMap::new(), || 0.0)), Some((id(0), planned.id)));
+        assert!(next_pair(&items[..1], &ratings, || 0.0).is_none());
+
+        let (mut favored, mut underdog) = (ratings[&id(0)], ratings[&id(4)]);
+        record(&mut underdog, &mut favored);
+        assert!((underdog.rating - 1100.0 - 30.3).abs() < 0.1);
+        assert!((favored.rating - 1600.0 + 30.3).abs() < 0.1);
+        assert_eq!((underdog.comparisons, favored.comparisons), (4, 5));
+    }
+}
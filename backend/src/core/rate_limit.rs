@@ -0,0 +1,162 @@
+//! Per-client request limits for shared deployments.  Every client gets a
+//! token bucket holding `burst` requests that refills at `per_minute`, so
+//! short bursts pass while a steady flood is slowed to the configured rate.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Buckets kept at most.  Past it full buckets are dropped (a full bucket
+/// is the same as none), then the least recently used ones.
+const MAX_CLIENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests a client gets back every minute; at least 1.
+    pub per_minute: u32,
+    /// Requests a client can make at once; at least 1.
+    pub burst: u32,
+}
+
+/// The verdict on one request, with what the `X-RateLimit-*` headers
+/// report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    /// Size of the bucket.
+    pub limit: u32,
+    /// Requests left right now.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_secs: u64,
+    /// Seconds until the next request is let through; 0 when allowed.
+    pub retry_after_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    buckets: HashMap<String, Bucket>,
+    max_clients: usize,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimiter { limit, buckets: HashMap::new(), max_clients: MAX_CLIENTS }
+    }
+
+    fn per_second(&self) -> f64 {
+        f64::from(self.limit.per_minute) / 60.0
+    }
+
+    fn refilled(&self, bucket: Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.per_second()).min(f64::from(self.limit.burst))
+    }
+
+    fn seconds_until(&self, missing: f64) -> u64 {
+        (missing.max(0.0) / self.per_second()).ceil() as u64
+    }
+
+    /// Makes room for one more bucket.  When dropping the full ones is not
+    /// enough, the least recently used tenth goes, so a flood of new
+    /// clients doesn't sort the map on every request.
+    fn evict(&mut self, now: Instant) {
+        let burst = f64::from(self.limit.burst);
+        let full: Vec<String> = self
+            .buckets
+            .iter()
+            .filter(|(_, b)| self.refilled(**b, now) >= burst)
+            .map(|(client, _)| client.clone())
+            .collect();
+        for client in full {
+            self.buckets.remove(&client);
+        }
+        if self.buckets.len() < self.max_clients {
+            return;
+        }
+
+        let keep = self.max_clients - self.max_clients.div_ceil(10);
+        let mut by_age: Vec<(Instant, String)> =
+            self.buckets.iter().map(|(client, b)| (b.updated, client.clone())).collect();
+        let oldest = by_age.len() - keep;
+        by_age.select_nth_unstable(oldest - 1);
+        for (_, client) in &by_age[..oldest] {
+            self.buckets.remove(client);
+        }
+    }
+
+    /// Takes one request from `client`'s bucket if there is one left.
+    pub fn check(&mut self, client: &str, now: Instant) -> Decision {
+        if self.buckets.len() >= self.max_clients && !self.buckets.contains_key(client) {
+            self.evict(now);
+        }
+
+        let burst = f64::from(self.limit.burst);
+        let bucket = self.buckets.get(client).copied().unwrap_or(Bucket { tokens: burst, updated: now });
+        let mut tokens = self.refilled(bucket, now);
+        let allowed = tokens >= 1.0;
+        if allowed {
+            tokens -= 1.0;
+        }
+        self.buckets.insert(client.to_string(), Bucket { tokens, updated: now });
+
+        Decision {
+            allowed,
+            limit: self.limit.burst,
+            remaining: tokens.floor() as u32,
+            reset_secs: self.seconds_until(burst - tokens),
+            retry_after_secs: if allowed { 0 } else { self.seconds_until(1.0 - tokens).max(1) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_allows_bursts_and_refills_over_time() {
+        let mut limiter = RateLimiter::new(RateLimit { per_minute: 60, burst: 3 });
+        let start = Instant::now();
+        let at = |secs: f64| start + Duration::from_secs_f64(secs);
+
+        let remaining: Vec<u32> = (0..3).map(|_| limiter.check("10.0.0.1", at(0.0)).remaining).collect();
+        assert_eq!(remaining, [2, 1, 0]);
+        let denied = limiter.check("10.0.0.1", at(0.2));
+        assert!(!denied.allowed);
+        assert_eq!((denied.retry_after_secs, denied.reset_secs), (1, 3));
+
+        // Other clients have their own bucket.
+        assert!(limiter.check("10.0.0.2", at(0.2)).allowed);
+
+        // One request comes back per second at 60 a minute.
+        let later = limiter.check("10.0.0.1", at(1.5));
+        assert!(later.allowed);
+        assert_eq!(later.remaining, 0);
+        assert!(!limiter.check("10.0.0.1", at(1.6)).allowed);
+        assert_eq!(limiter.check("10.0.0.1", at(60.0)).remaining, 2);
+    }
+
+    #[test]
+    fn test_busy_clients_stay_under_the_cap() {
+        let mut limiter = RateLimiter::new(RateLimit { per_minute: 1, burst: 5 });
+        limiter.max_clients = 20;
+        let start = Instant::now();
+        for i in 0..100u64 {
+            let client = format!("10.0.0.{i}");
+            limiter.check(&client, start + Duration::from_secs(i));
+            assert!(limiter.buckets.len() <= 20);
+        }
+        // The most recent clients keep their buckets, the oldest are gone.
+        assert!(limiter.buckets.contains_key("10.0.0.99"));
+        assert!(!limiter.buckets.contains_key("10.0.0.0"));
+        assert_eq!(limiter.check("10.0.0.99", start + Duration::from_secs(100)).remaining, 3);
+    }
+}
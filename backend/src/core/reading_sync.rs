@@ -0,0 +1,110 @@
+//! Reading progress from self-hosted comic servers (Kavita, Komga),
+//! carried over to the matching readables.  Progress only moves forward,
+//! so reading further in kars is never undone by a server that lags.
+
+use crate::core::matcher::normalize_title;
+use crate::core::models::{MediaItem, MediaItemType};
+use crate::core::search::SearchError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadUnit {
+    Chapters,
+    Volumes,
+}
+
+/// A series the user started on a reading server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerSeries {
+    pub title: String,
+    pub alt_titles: Vec<String>,
+    /// Highest chapter or volume read to the end.
+    pub read: u32,
+    pub unit: ReadUnit,
+}
+
+pub trait ReadingServer: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Every series with something read.  Blocking.
+    fn progress(&self) -> Result<Vec<ServerSeries>, SearchError>;
+}
+
+/// The one readable with any of the series' titles (alternative titles
+/// count); None when no or several readables have them.
+pub fn find_readable<'a>(items: &'a [MediaItem], series: &ServerSeries) -> Option<&'a MediaItem> {
+    let titles: Vec<String> = std::iter::once(&series.title)
+        .chain(&series.alt_titles)
+        .map(|t| normalize_title(t))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let mut found = items.iter().filter(|item| {
+        matches!(item.media_type, MediaItemType::Readable(..))
+            && std::iter::once(&item.title)
+                .chain(&item.alt_titles)
+                .any(|t| titles.contains(&normalize_title(t)))
+    });
+    match (found.next(), found.next()) {
+        (Some(item), None) => Some(item),
+        _ => None,
+    }
+}
+
+/// Moves the item up to the series' progress.  Returns false when the
+/// item is already as far (or finished).
+pub fn apply(item: &mut MediaItem, series: &ServerSeries) -> bool {
+    let current = match (&item.media_type, series.unit) {
+        (MediaItemType::Readable(_, p, _), ReadUnit::Chapters) => p.current,
+        (MediaItemType::Readable(..), ReadUnit::Volumes) => item.volumes.as_ref().map_or(0, |v| v.current),
+        _ => return false,
+    };
+    if series.read <= current {
+        return false;
+    }
+    match series.unit {
+        ReadUnit::Chapters => item.advance(series.read - current),
+        ReadUnit::Volumes => item.advance_volumes(series.read - current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, ReadStatus, ReadableKind, WatchStatus};
+
+    #[test]
+    fn test_find_and_apply_moves_progress_forward() {
+        let manga = |title: &str| {
+            MediaItem::new(
+                title.into(),
+                MediaItemType::Readable(ReadableKind::Manga, Progress { current: 10, total: None }, ReadStatus::Reading),
+            )
+        };
+        let mut berserk = manga("Berserk");
+        berserk.alt_titles.push("ベルセルク".into());
+        let items = vec![
+            berserk,
+            manga("Vagabond"),
+            MediaItem::new("Berserk".into(), MediaItemType::Series(Progress { current: 0, total: None }, WatchStatus::PlanToWatch)),
+        ];
+        let mut series = ServerSeries {
+            title: "ベルセルク".into(),
+            alt_titles: Vec::new(),
+            read: 12,
+            unit: ReadUnit::Chapters,
+        };
+
+        let mut item = find_readable(&items, &series).unwrap().clone();
+        assert_eq!(item.id, items[0].id);
+        assert!(apply(&mut item, &series));
+        assert!(!apply(&mut item, &series));
+        series.read = 5;
+        assert!(!apply(&mut item, &series));
+
+        series.unit = ReadUnit::Volumes;
+        assert!(apply(&mut item, &series));
+        assert_eq!(item.volumes, Some(Progress { current: 5, total: None }));
+        assert_eq!(
+            item.media_type,
+            MediaItemType::Readable(ReadableKind::Manga, Progress { current: 12, total: None }, ReadStatus::Reading)
+        );
+    }
+}
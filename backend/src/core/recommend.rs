@@ -0,0 +1,247 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::matcher;
+use crate::core::models::{MediaItem, MediaItemType, ReadStatus, WatchStatus};
+use crate::core::search::SearchResult;
+
+/// Items scored at least this (0-100) count as liked.
+const LIKED_SCORE: u8 = 70;
+
+/// Provider scores from this (0-100) up are worth mentioning.
+const ACCLAIMED_SCORE: u8 = 80;
+
+/// One reason an item was recommended.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "signal", rename_all = "snake_case")]
+pub enum Signal {
+    /// Tags the item shares with items the user liked.
+    SharedTags { tags: Vec<String> },
+    /// The liked item that contributed most.
+    BecauseYouRated { item_id: Uuid, title: String, score: Option<u8> },
+    /// Provider score, 0-100.
+    Acclaimed { global_score: u8 },
+}
+
+/// A planned item ranked for "what to start next", with the signals that
+/// put it there.
+pub struct Recommendation<'a> {
+    pub item: &'a MediaItem,
+    pub score: f32,
+    pub signals: Vec<Signal>,
+}
+
+/// Ranks planned items by how much they share with liked ones (scored
+/// 7.0+ or favorited), weighting each liked item by its score, with a
+/// small bonus for acclaimed items.  Items without any signal are left
+/// out.
+pub fn recommend(items: &[MediaItem], limit: usize) -> Vec<Recommendation<'_>> {
+    let liked: Vec<(&MediaItem, f32)> = items
+        .iter()
+        .filter_map(|i| like_weight(i).map(|w| (i, w)))
+        .collect();
+
+    let mut ranked: Vec<Recommendation> = items
+        .iter()
+        .filter(|i| is_planned(i))
+        .filter_map(|item| {
+            let mut score = 0.0;
+            let mut shared = BTreeSet::new();
+            let mut best: Option<(&MediaItem, f32)> = None;
+            for &(l, weight) in &liked {
+                let common: Vec<&String> = item.tags.intersection(&l.tags).collect();
+                if common.is_empty() {
+                    continue;
+                }
+                let contribution = common.len() as f32 * weight;
+                score += contribution;
+                shared.extend(common.into_iter().cloned());
+                if best.is_none_or(|(_, c)| contribution > c) {
+                    best = Some((l, contribution));
+                }
+            }
+
+            let mut signals = Vec::new();
+            if !shared.is_empty() {
+                signals.push(Signal::SharedTags { tags: shared.into_iter().collect() });
+            }
+            if let Some((l, _)) = best {
+                signals.push(Signal::BecauseYouRated {
+                    item_id: l.id,
+                    title: l.title.clone(),
+                    score: l.score,
+                });
+            }
+            if let Some(g) = item.global_score.filter(|&g| g >= ACCLAIMED_SCORE) {
+                score += (g - ACCLAIMED_SCORE + 1) as f32 / 20.0;
+                signals.push(Signal::Acclaimed { global_score: g });
+            }
+
+            (!signals.is_empty()).then_some(Recommendation { item, score, signals })
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Liked items with a provider entry, best liked first: the items whose
+/// provider recommendations are looked up.
+pub fn discovery_seeds(items: &[MediaItem], limit: usize) -> Vec<&MediaItem> {
+    let mut seeds: Vec<(&MediaItem, f32)> = items
+        .iter()
+        .filter(|i| i.source.is_some() && i.external_id.is_some())
+        .filter_map(|i| like_weight(i).map(|w| (i, w)))
+        .collect();
+    seeds.sort_by(|a, b| b.1.total_cmp(&a.1));
+    seeds.into_iter().take(limit).map(|(i, _)| i).collect()
+}
+
+/// A provider entry that is not in the library, suggested for liked items.
+pub struct Discovery<'a> {
+    pub result: SearchResult,
+    pub score: f32,
+    /// The liked items it was suggested for, strongest first.
+    pub because: Vec<&'a MediaItem>,
+}
+
+/// Merges what providers suggested for each seed (in the provider's
+/// order) into one ranking.  A suggestion earns the seed's like weight,
+/// divided by its position in the provider's list; entries suggested for
+/// several seeds add up.  Entries already in the library are left out.
+pub fn rank_discoveries<'a>(
+    library: &[MediaItem],
+    suggestions: Vec<(&'a MediaItem, Vec<SearchResult>)>,
+    limit: usize,
+) -> Vec<Discovery<'a>> {
+    // Each entry with what every seed in `because` contributed.
+    let mut found: Vec<(Discovery<'a>, Vec<f32>)> = Vec::new();
+    for (seed, results) in suggestions {
+        let Some(weight) = like_weight(seed) else { continue };
+        for (pos, result) in results.into_iter().enumerate() {
+            let contribution = weight / (pos + 1) as f32;
+            let existing = found.iter_mut().find(|(d, _)| {
+                d.result.source == result.source && d.result.external_id == result.external_id
+            });
+            if let Some((discovery, contributions)) = existing {
+                discovery.score += contribution;
+                discovery.because.push(seed);
+                contributions.push(contribution);
+            } else if matcher::find_duplicate(library, &result.clone().into_media_item()).is_none() {
+                let discovery = Discovery { result, score: contribution, because: vec![seed] };
+                found.push((discovery, vec![contribution]));
+            }
+        }
+    }
+
+    let mut ranked: Vec<Discovery<'a>> = found
+        .into_iter()
+        .map(|(mut discovery, contributions)| {
+            let mut seeds: Vec<_> = discovery.because.into_iter().zip(contributions).collect();
+            seeds.sort_by(|a, b| b.1.total_cmp(&a.1));
+            discovery.because = seeds.into_iter().map(|(seed, _)| seed).collect();
+            discovery
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// How much a liked item counts, or None if it is not liked.
+fn like_weight(item: &MediaItem) -> Option<f32> {
+    match item.score {
+        Some(s) if s >= LIKED_SCORE => Some(s as f32 / 100.0),
+        _ if item.favorite => Some(LIKED_SCORE as f32 / 100.0),
+        _ => None,
+    }
+}
+
+fn is_planned(item: &MediaItem) -> bool {
+    matches!(
+        item.media_type,
+        MediaItemType::Movie(WatchStatus::PlanToWatch)
+            | MediaItemType::Series(_, WatchStatus::PlanToWatch)
+            | MediaItemType::Readable(_, _, ReadStatus::PlanToRead)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::search::LocalizedTitles;
+
+    fn movie(title: &str, status: WatchStatus, tags: &[&str], score: Option<u8>) -> MediaItem {
+        let mut item = MediaItem::new(title.into(), MediaItemType::Movie(status));
+        item.tags = tags.iter().map(|t| t.to_string()).collect();
+        item.score = score;
+        item
+    }
+
+    #[test]
+    fn test_recommend_explains_shared_tags_and_source_item() {
+        let mut items = vec![
+            movie("Blade Runner", WatchStatus::Completed, &["sci-fi", "noir"], Some(95)),
+            movie("Alien", WatchStatus::Completed, &["sci-fi", "horror"], Some(60)),
+            movie("Dune", WatchStatus::PlanToWatch, &["sci-fi"], None),
+            movie("Ghost in the Shell", WatchStatus::PlanToWatch, &["sci-fi", "noir"], None),
+            movie("Notting Hill", WatchStatus::PlanToWatch, &["romance"], None),
+        ];
+        // Liked for being a favorite despite its score.
+        items[1].favorite = true;
+
+        let ranked = recommend(&items, 10);
+        let titles: Vec<&str> = ranked.iter().map(|r| r.item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Ghost in the Shell", "Dune"]);
+        assert_eq!(
+            ranked[0].signals,
+            vec![
+                Signal::SharedTags { tags: vec!["noir".into(), "sci-fi".into()] },
+                Signal::BecauseYouRated {
+                    item_id: items[0].id,
+                    title: "Blade Runner".into(),
+                    score: Some(95),
+                },
+            ]
+        );
+    }
+
+    fn suggestion(title: &str, id: u32) -> SearchResult {
+        SearchResult {
+            title: title.into(),
+            media_type: MediaItemType::Movie(WatchStatus::PlanToWatch),
+            global_score: None,
+            external_id: Some(id.to_string()),
+            poster_url: None,
+            source: "tmdb",
+            format_label: "Movie".into(),
+            alt_titles: Vec::new(),
+            external_refs: Vec::new(),
+            creator: None,
+            content_rating: None,
+            titles: LocalizedTitles::default(),
+            year: None,
+        }
+    }
+
+    #[test]
+    fn test_discoveries_add_up_and_skip_library() {
+        let items = vec![
+            movie("Blade Runner", WatchStatus::Completed, &[], Some(95)),
+            movie("Alien", WatchStatus::Completed, &[], Some(80)),
+            movie("Dune", WatchStatus::PlanToWatch, &[], None),
+        ];
+        let suggestions = vec![
+            (&items[0], vec![suggestion("Dune", 1), suggestion("Gattaca", 2), suggestion("Brazil", 3)]),
+            (&items[1], vec![suggestion("Brazil", 3), suggestion("Aliens", 4)]),
+        ];
+
+        let ranked = rank_discoveries(&items, suggestions, 10);
+        let titles: Vec<&str> = ranked.iter().map(|d| d.result.title.as_str()).collect();
+        assert_eq!(titles, vec!["Brazil", "Gattaca", "Aliens"]);
+        let because: Vec<&str> = ranked[0].because.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(because, vec!["Alien", "Blade Runner"]);
+    }
+}
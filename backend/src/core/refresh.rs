@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::core::models::{ContentRating, MediaItem, MediaItemType};
+use crate::core::search::{MediaDetails, MediaSearchType, SearchResult};
+
+/// One field a metadata refresh would change.  Provider data only ever
+/// fills in or updates a field, it never clears one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum FieldChange {
+    /// Total episodes / chapters / pages.
+    Total { old: Option<u32>, new: u32 },
+    /// Provider score, 0-100.
+    GlobalScore { old: Option<u8>, new: u8 },
+    PosterUrl { old: Option<String>, new: String },
+    /// Author or studio; only filled in when the item has none.
+    Creator { old: Option<String>, new: String },
+    /// How explicit the provider rates the title.
+    ContentRating { old: Option<ContentRating>, new: ContentRating },
+    /// Synopsis; only filled in when the item has none.
+    Overview { old: Option<String>, new: String },
+    /// Provider entry found for an item added by hand.
+    Link { source: String, external_id: String },
+}
+
+impl FieldChange {
+    pub fn apply(&self, item: &mut MediaItem) {
+        match self {
+            FieldChange::Total { new, .. } => {
+                if let MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) =
+                    &mut item.media_type
+                {
+                    p.total = Some(*new);
+                }
+            }
+            FieldChange::GlobalScore { new, .. } => item.global_score = Some(*new),
+            FieldChange::PosterUrl { new, .. } => item.poster_url = Some(new.clone()),
+            FieldChange::Creator { new, .. } => item.creator = Some(new.clone()),
+            FieldChange::ContentRating { new, .. } => item.content_rating = Some(*new),
+            FieldChange::Overview { new, .. } => item.overview = Some(new.clone()),
+            FieldChange::Link { source, external_id } => {
+                item.source = Some(source.clone());
+                item.external_id = Some(external_id.clone());
+            }
+        }
+    }
+}
+
+/// What a job did (or would do) to one item: the fields a refresh
+/// changes, whether an import created the item, or why it failed.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ItemChanges {
+    pub item_id: Uuid,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub created: bool,
+    pub changes: Vec<FieldChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Search category used to look an item up again at its source.
+pub fn search_type_for(item: &MediaItem) -> MediaSearchType {
+    let anime = item.source.as_deref() == Some("anilist");
+    let manga = anime || item.source.as_deref() == Some("mangadex");
+    match &item.media_type {
+        MediaItemType::Movie(_) if anime => MediaSearchType::Anime,
+        MediaItemType::Movie(_) => MediaSearchType::Movie,
+        MediaItemType::Series(..) if anime => MediaSearchType::Anime,
+        MediaItemType::Series(..) => MediaSearchType::Series,
+        MediaItemType::Readable(..) if manga => MediaSearchType::Manga,
+        MediaItemType::Readable(..) => MediaSearchType::Book,
+    }
+}
+
+/// Field-level differences between a stored item and fresh provider data.
+pub fn diff(item: &MediaItem, fresh: &SearchResult) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    let old_total = match &item.media_type {
+        MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => Some(p.total),
+        MediaItemType::Movie(_) => None,
+    };
+    let new_total = match &fresh.media_type {
+        MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => p.total,
+        MediaItemType::Movie(_) => None,
+    };
+    if let (Some(old), Some(new)) = (old_total, new_total)
+        && old != Some(new)
+    {
+        changes.push(FieldChange::Total { old, new });
+    }
+
+    if let Some(new) = fresh.global_score.filter(|&s| item.global_score != Some(s)) {
+        changes.push(FieldChange::GlobalScore { old: item.global_score, new });
+    }
+
+    if let Some(new) = fresh.poster_url.as_ref().filter(|&u| item.poster_url.as_ref() != Some(u)) {
+        changes.push(FieldChange::PosterUrl {
+            old: item.poster_url.clone(),
+            new: new.clone(),
+        });
+    }
+
+    if let Some(new) = fresh.creator.as_ref().filter(|_| item.creator.is_none()) {
+        changes.push(FieldChange::Creator { old: None, new: new.clone() });
+    }
+
+    if let Some(new) = fresh.content_rating.filter(|&r| item.content_rating != Some(r)) {
+        changes.push(FieldChange::ContentRating { old: item.content_rating, new });
+    }
+
+    changes
+}
+
+/// The provider's synopsis for an item that has none yet.
+pub fn overview_change(item: &MediaItem, details: MediaDetails) -> Option<FieldChange> {
+    if item.overview.is_some() {
+        return None;
+    }
+    let new = details.overview.filter(|o| !o.trim().is_empty())?;
+    Some(FieldChange::Overview { old: None, new })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, WatchStatus};
+    use crate::core::search::LocalizedTitles;
+
+    #[test]
+    fn test_diff_reports_and_applies_changed_fields() {
+        let mut item = MediaItem::new(
+            "Frieren".into(),
+            MediaItemType::Series(Progress { current: 4, total: Some(12) }, WatchStatus::Watching),
+        );
+        item.global_score = Some(82);
+        item.poster_url = Some("https://img/a.jpg".into());
+
+        let fresh = SearchResult {
+            title: "Frieren".into(),
+            media_type: MediaItemType::Series(
+                Progress { current: 0, total: Some(13) },
+                WatchStatus::PlanToWatch,
+            ),
+            global_score: Some(84),
+            external_id: Some("154587".into()),
+            poster_url: None,
+            source: "anilist",
+            format_label: "TV".into(),
+            alt_titles: Vec::new(),
+            external_refs: Vec::new(),
+            creator: Some("Madhouse".into()),
+            content_rating: Some(ContentRating::Safe),
+            titles: LocalizedTitles::default(),
+            year: None,
+        };
+
+        let changes = diff(&item, &fresh);
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange::Total { old: Some(12), new: 13 },
+                FieldChange::GlobalScore { old: Some(82), new: 84 },
+                FieldChange::Creator { old: None, new: "Madhouse".into() },
+                FieldChange::ContentRating { old: None, new: ContentRating::Safe },
+            ]
+        );
+
+        for change in &changes {
+            change.apply(&mut item);
+        }
+        assert!(diff(&item, &fresh).is_empty());
+        assert_eq!(item.poster_url.as_deref(), Some("https://img/a.jpg"));
+        assert!(matches!(item.media_type, MediaItemType::Series(Progress { current: 4, .. }, _)));
+
+        let details = |overview: &str| MediaDetails { overview: Some(overview.into()), ..MediaDetails::default() };
+        let change = overview_change(&item, details("An elf mage outlives her party.")).unwrap();
+        change.apply(&mut item);
+        assert_eq!(item.overview.as_deref(), Some("An elf mage outlives her party."));
+        assert_eq!(overview_change(&item, details("Another synopsis.")), None);
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::core::models::{MediaItem, MediaItemType, ReadStatus, WatchStatus};
+use crate::core::search::NextAiring;
+
+/// Kind of in-app notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// A scheduled session is about to start.
+    SessionReminder,
+}
+
+impl NotificationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationKind::SessionReminder => "session_reminder",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "session_reminder" => Some(NotificationKind::SessionReminder),
+            _ => None,
+        }
+    }
+}
+
+/// A notification about to be stored.  `key` identifies the event so the
+/// same reminder is never stored twice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewNotification {
+    pub key: String,
+    pub item_id: Option<Uuid>,
+    pub kind: NotificationKind,
+    pub message: String,
+}
+
+/// A stored notification.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: i64,
+    pub item_id: Option<Uuid>,
+    pub kind: NotificationKind,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read: bool,
+}
+
+/// The next local midnight after `now`; "today" ends there.
+pub fn end_of_today(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.with_timezone(&chrono::Local)
+        .date_naive()
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+        .map_or(now + Duration::days(1), |t| t.with_timezone(&Utc))
+}
+
+/// Items with a session scheduled between `now` and `now + window`,
+/// soonest first.
+pub fn upcoming_sessions(items: &[MediaItem], now: DateTime<Utc>, window: Duration) -> Vec<&MediaItem> {
+    let mut sessions: Vec<&MediaItem> = items
+        .iter()
+        .filter(|i| i.scheduled_for.is_some_and(|at| at >= now && at <= now + window))
+        .collect();
+    sessions.sort_by_key(|i| i.scheduled_for);
+    sessions
+}
+
+/// Reminders for the sessions starting within `lead` of `now`.
+pub fn session_reminders(items: &[MediaItem], now: DateTime<Utc>, lead: Duration) -> Vec<NewNotification> {
+    upcoming_sessions(items, now, lead)
+        .into_iter()
+        .filter_map(|item| {
+            let at = item.scheduled_for?;
+            let minutes = (at - now).num_minutes();
+            Some(NewNotification {
+                key: format!("session:{}:{}", item.id, at.timestamp()),
+                item_id: Some(item.id),
+                kind: NotificationKind::SessionReminder,
+                message: format!("'{}' starts in {minutes} min", item.title),
+            })
+        })
+        .collect()
+}
+
+/// Series whose air dates are worth looking up: the ones being watched
+/// or planned.
+pub fn follows_airing(item: &MediaItem) -> bool {
+    matches!(
+        item.media_type,
+        MediaItemType::Series(_, WatchStatus::Watching | WatchStatus::PlanToWatch)
+    ) && item.external_id.is_some()
+}
+
+/// Movies whose release date is worth looking up: the planned ones.
+pub fn awaits_release(item: &MediaItem) -> bool {
+    matches!(item.media_type, MediaItemType::Movie(WatchStatus::PlanToWatch))
+        && item.external_id.is_some()
+}
+
+/// Readables whose new chapters are worth looking up: the ones being read.
+pub fn follows_chapters(item: &MediaItem) -> bool {
+    matches!(item.media_type, MediaItemType::Readable(_, _, ReadStatus::Reading))
+}
+
+/// Chapters out but not read yet, for a readable being read whose newest
+/// chapter is known.  None when there is nothing to catch up on.
+pub fn chapters_behind(item: &MediaItem) -> Option<u32> {
+    let MediaItemType::Readable(_, p, ReadStatus::Reading) = &item.media_type else {
+        return None;
+    };
+    item.latest_available
+        .map(|latest| latest.saturating_sub(p.current))
+        .filter(|&n| n > 0)
+}
+
+/// Episodes that have aired but not been watched yet: everything before
+/// the next one, minus the progress.
+pub fn behind_by(item: &MediaItem, next: &NextAiring) -> u32 {
+    let watched = match &item.media_type {
+        MediaItemType::Series(p, _) => p.current,
+        _ => 0,
+    };
+    next.episode.saturating_sub(1).saturating_sub(watched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, ReadableKind};
+
+    fn scheduled(title: &str, at: Option<DateTime<Utc>>) -> MediaItem {
+        let mut item = MediaItem::new(title.into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        item.scheduled_for = at;
+        item
+    }
+
+    #[test]
+    fn test_upcoming_sessions_and_reminders() {
+        let now = Utc::now();
+        let items = vec![
+            scheduled("Saturday", Some(now + Duration::days(2))),
+            scheduled("Tonight", Some(now + Duration::minutes(30))),
+            scheduled("Last week", Some(now - Duration::days(7))),
+            scheduled("Unplanned", None),
+        ];
+
+        let titles: Vec<&str> = upcoming_sessions(&items, now, Duration::days(7))
+            .iter()
+            .map(|i| i.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Tonight", "Saturday"]);
+
+        let reminders = session_reminders(&items, now, Duration::hours(1));
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].item_id, Some(items[1].id));
+    }
+
+    #[test]
+    fn test_behind_by_counts_aired_unwatched_episodes() {
+        let item = MediaItem::new(
+            "Frieren".into(),
+            MediaItemType::Series(Progress { current: 3, total: Some(28) }, WatchStatus::Watching),
+        );
+        let next = |episode| NextAiring { external_id: "1".into(), episode, airing_at: Utc::now() };
+        assert_eq!(behind_by(&item, &next(6)), 2);
+        assert_eq!(behind_by(&item, &next(4)), 0);
+        assert_eq!(behind_by(&item, &next(1)), 0);
+    }
+
+    #[test]
+    fn test_chapters_behind_only_counts_readables_being_read() {
+        let mut item = MediaItem::new(
+            "Berserk".into(),
+            MediaItemType::Readable(ReadableKind::Manga, Progress { current: 370, total: None }, ReadStatus::Reading),
+        );
+        assert_eq!(chapters_behind(&item), None);
+        item.latest_available = Some(374);
+        assert_eq!(chapters_behind(&item), Some(4));
+        item.latest_available = Some(370);
+        assert_eq!(chapters_behind(&item), None);
+
+        item.latest_available = Some(374);
+        item.media_type = MediaItemType::Readable(
+            ReadableKind::Manga,
+            Progress { current: 370, total: None },
+            ReadStatus::OnHold,
+        );
+        assert_eq!(chapters_behind(&item), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Periodic background jobs: chapter checks, backups, syncs and the like.
+//! Every job the server can run is listed in `JOBS` with its default
+//! interval; `job_interval_seconds` in the settings overrides it.  A run
+//! reports whether it did something, so the status page can tell an idle
+//! check from work done.
+
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// What one run did: a summary of the work, None when there was nothing
+/// to do, or why it failed.
+pub type JobResult = Result<Option<String>, String>;
+
+/// Shortest interval a job can be given.
+pub const MIN_INTERVAL_SECS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub interval: Duration,
+    /// Run right after startup rather than one interval later.
+    pub run_at_start: bool,
+}
+
+const fn job(name: &'static str, description: &'static str, secs: u64) -> JobSpec {
+    JobSpec { name, description, interval: Duration::from_secs(secs), run_at_start: true }
+}
+
+pub const REMINDERS: JobSpec = job("reminders", "Turns sessions about to start into notifications", 60);
+pub const NEW_CHAPTERS: JobSpec = job("new_chapters", "Looks up the newest chapter of the manga being read", 6 * 60 * 60);
+pub const DOWNLOADS: JobSpec = job("downloads", "Asks Sonarr and Radarr what is on disk", 15 * 60);
+pub const TRAKT_SYNC: JobSpec = job("trakt_sync", "Pulls Trakt history once trakt_sync_hours have passed", 10 * 60);
+pub const READING_SYNC: JobSpec =
+    job("reading_sync", "Pulls Kavita and Komga progress once reading_sync_minutes have passed", 60);
+pub const OUTBOX_REPLAY: JobSpec = job("outbox_replay", "Replays writes queued while Turso was down", 60);
+pub const WRITE_THROUGH: JobSpec = job("write_through", "Sends changes of AniList items to the linked account", 30);
+pub const BACKUPS: JobSpec = job("backups", "Takes a backup once backup_interval_hours have passed", 10 * 60);
+pub const WEBHOOKS: JobSpec = job("webhooks", "Delivers new audit log entries to webhook receivers", 5);
+pub const NOTIFICATIONS: JobSpec = job("notifications", "Tells the notifiers about items completed since the last run", 30);
+pub const AIRED_EPISODES: JobSpec =
+    job("aired_episodes", "Tells the notifiers about episodes that aired since the last run", 60 * 60);
+pub const TELEMETRY: JobSpec = JobSpec {
+    run_at_start: false,
+    ..job("telemetry", "Sends the anonymous usage report while telemetry_enabled is on", 24 * 60 * 60)
+};
+
+/// Every job, in the order the status page lists them.
+pub const JOBS: &[JobSpec] = &[
+    REMINDERS,
+    NEW_CHAPTERS,
+    DOWNLOADS,
+    TRAKT_SYNC,
+    READING_SYNC,
+    OUTBOX_REPLAY,
+    WRITE_THROUGH,
+    BACKUPS,
+    WEBHOOKS,
+    NOTIFICATIONS,
+    AIRED_EPISODES,
+    TELEMETRY,
+];
+
+/// The interval of `spec` with the override from `job_interval_seconds`.
+pub fn interval(spec: &JobSpec, overrides: &BTreeMap<String, u32>) -> Duration {
+    overrides
+        .get(spec.name)
+        .map_or(spec.interval, |&secs| Duration::from_secs(u64::from(secs.max(MIN_INTERVAL_SECS))))
+}
+
+/// Checks `job_interval_seconds`: known job names and no interval below
+/// the minimum.
+pub fn validate_intervals(overrides: &BTreeMap<String, u32>) -> Result<(), String> {
+    for (name, &secs) in overrides {
+        if !JOBS.iter().any(|j| j.name == name) {
+            return Err(format!("Unknown job '{name}'"));
+        }
+        if secs < MIN_INTERVAL_SECS {
+            return Err(format!("The interval of {name} must be at least {MIN_INTERVAL_SECS} seconds"));
+        }
+    }
+    Ok(())
+}
+
+/// A registered job and how its runs went since startup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobStatus {
+    pub spec: JobSpec,
+    /// The interval in force, with its override.
+    pub interval: Duration,
+    pub running: bool,
+    pub runs: u32,
+    pub failures: u32,
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    pub last_result: Option<JobResult>,
+    pub next_run: DateTime<Utc>,
+}
+
+impl JobStatus {
+    pub fn new(spec: JobSpec, now: DateTime<Utc>) -> Self {
+        let next_run = if spec.run_at_start { now } else { after(now, spec.interval) };
+        JobStatus {
+            spec,
+            interval: spec.interval,
+            running: false,
+            runs: 0,
+            failures: 0,
+            last_started: None,
+            last_finished: None,
+            last_result: None,
+            next_run,
+        }
+    }
+
+    /// Whether a run is due at `now`.
+    pub fn due(&self, now: DateTime<Utc>) -> bool {
+        !self.running && now >= self.next_run
+    }
+
+    /// Applies a changed interval; the next run moves with it.
+    pub fn set_interval(&mut self, interval: Duration) {
+        if interval == self.interval {
+            return;
+        }
+        self.next_run = match self.last_started {
+            Some(started) => after(started, interval),
+            None if self.spec.run_at_start => self.next_run,
+            // Counted from registration.
+            None => after(self.next_run - chrono::Duration::from_std(self.interval).unwrap_or_default(), interval),
+        };
+        self.interval = interval;
+    }
+
+    pub fn start(&mut self, now: DateTime<Utc>) {
+        self.running = true;
+        self.last_started = Some(now);
+        self.next_run = after(now, self.interval);
+    }
+
+    pub fn finish(&mut self, result: JobResult, now: DateTime<Utc>) {
+        self.running = false;
+        self.runs += 1;
+        if result.is_err() {
+            self.failures += 1;
+        }
+        self.last_finished = Some(now);
+        self.last_result = Some(result);
+    }
+}
+
+fn after(at: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+    at + chrono::Duration::from_std(interval).unwrap_or(chrono::Duration::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_status_follows_runs_and_interval_changes() {
+        let at = |m| Utc.with_ymd_and_hms(2026, 10, 1, 12, m, 0).unwrap();
+        let mut status = JobStatus::new(BACKUPS, at(0));
+        assert!(status.due(at(0)));
+
+        status.start(at(0));
+        assert!(!status.due(at(30)));
+        status.finish(Err("disk full".into()), at(1));
+        assert_eq!((status.runs, status.failures), (1, 1));
+        assert_eq!(status.next_run, at(10));
+
+        // A shorter interval counts from the last start.
+        let overrides = BTreeMap::from([("backups".to_string(), 120)]);
+        status.set_interval(interval(&BACKUPS, &overrides));
+        assert_eq!(status.next_run, at(2));
+        assert!(JobStatus::new(TELEMETRY, at(0)).next_run > at(59));
+
+        assert!(validate_intervals(&overrides).is_ok());
+        assert!(validate_intervals(&BTreeMap::from([("backups".to_string(), 1)])).is_err());
+        assert!(validate_intervals(&BTreeMap::from([("cleanup".to_string(), 60)])).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Plays reported by a media server, turned into progress.  Plex and
+//! Jellyfin webhooks and Trakt-style scrobble bodies are read into a
+//! `Scrobble`, matched to a library item and applied to it.
+
+use serde_json::Value;
+
+use crate::core::matcher::normalize_title;
+use crate::core::models::{MediaItem, MediaItemType, WatchStatus};
+
+/// Share of a Trakt-style play (in percent) from which it counts as
+/// watched, as at Trakt.
+const WATCHED_PERCENT: f64 = 80.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Played {
+    Movie,
+    Episode { season: Option<u32>, number: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scrobble {
+    /// The movie's title, or the show's for an episode.
+    pub title: String,
+    pub played: Played,
+    /// Ids in `MediaItem::external_refs` form, e.g. `tmdb:movie:438631`.
+    /// Episode ids are left out, only the show's are kept.
+    pub refs: Vec<String>,
+}
+
+/// A number sent as a number or a numeric string (Jellyfin templates do
+/// both).
+fn as_number(value: Option<&Value>) -> Option<u32> {
+    match value? {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Reads a webhook body.  Ok(None) for events that are not a finished
+/// play, e.g. Plex's `media.pause` or a Jellyfin stop halfway through.
+pub fn parse(body: &Value) -> Result<Option<Scrobble>, String> {
+    if body.get("event").is_some() {
+        from_plex(body)
+    } else if body.get("NotificationType").is_some() {
+        from_jellyfin(body)
+    } else if body.get("movie").is_some() || body.get("episode").is_some() {
+        from_trakt(body)
+    } else {
+        Err("Expected a Plex or Jellyfin webhook or a Trakt-style scrobble".into())
+    }
+}
+
+/// `media.scrobble` is sent once 90% of an item was played.
+fn from_plex(body: &Value) -> Result<Option<Scrobble>, String> {
+    if body["event"] != "media.scrobble" {
+        return Ok(None);
+    }
+    let meta = &body["Metadata"];
+    match meta["type"].as_str() {
+        Some("movie") => {
+            let title = text(meta.get("title")).ok_or("Plex movie without a title")?;
+            // Guids look like `tmdb://438631` or `imdb://tt1160419`.
+            let refs = meta["Guid"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|g| g["id"].as_str()?.split_once("://"))
+                .filter_map(|(kind, id)| match kind {
+                    "tmdb" => Some(format!("tmdb:movie:{id}")),
+                    "imdb" => Some(format!("imdb:{id}")),
+                    _ => None,
+                })
+                .collect();
+            Ok(Some(Scrobble { title, played: Played::Movie, refs }))
+        }
+        Some("episode") => {
+            let title = text(meta.get("grandparentTitle")).ok_or("Plex episode without a show title")?;
+            let number = as_number(meta.get("index")).ok_or("Plex episode without a number")?;
+            let season = as_number(meta.get("parentIndex"));
+            Ok(Some(Scrobble { title, played: Played::Episode { season, number }, refs: Vec::new() }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The webhook plugin's default template, sent on `PlaybackStop`.
+fn from_jellyfin(body: &Value) -> Result<Option<Scrobble>, String> {
+    if body["NotificationType"] != "PlaybackStop" || body["PlayedToCompletion"] != true {
+        return Ok(None);
+    }
+    match body["ItemType"].as_str() {
+        Some("Movie") => {
+            let title = text(body.get("Name")).ok_or("Jellyfin movie without a name")?;
+            let refs = [("tmdb:movie", "Provider_tmdb"), ("imdb", "Provider_imdb")]
+                .into_iter()
+                .filter_map(|(prefix, key)| text(body.get(key)).map(|id| format!("{prefix}:{id}")))
+                .collect();
+            Ok(Some(Scrobble { title, played: Played::Movie, refs }))
+        }
+        Some("Episode") => {
+            let title = text(body.get("SeriesName")).ok_or("Jellyfin episode without a series name")?;
+            let number = as_number(body.get("EpisodeNumber")).ok_or("Jellyfin episode without a number")?;
+            let season = as_number(body.get("SeasonNumber"));
+            Ok(Some(Scrobble { title, played: Played::Episode { season, number }, refs: Vec::new() }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// A body like Trakt's `/scrobble/stop`: a `movie`, or a `show` with an
+/// `episode`, and how far it was played.
+fn from_trakt(body: &Value) -> Result<Option<Scrobble>, String> {
+    if body["progress"].as_f64().is_some_and(|p| p < WATCHED_PERCENT) {
+        return Ok(None);
+    }
+    let (media, tmdb_kind, played) = match (body.get("movie"), body.get("episode")) {
+        (Some(movie), _) => (movie, "movie", Played::Movie),
+        (None, Some(episode)) => {
+            let number = as_number(episode.get("number")).ok_or("Episode without a number")?;
+            let season = as_number(episode.get("season"));
+            (body.get("show").ok_or("Episode without its show")?, "tv", Played::Episode { season, number })
+        }
+        (None, None) => unreachable!("checked by parse"),
+    };
+    let title = text(media.get("title")).ok_or("Scrobble without a title")?;
+    let ids = &media["ids"];
+    let refs = [(format!("tmdb:{tmdb_kind}"), "tmdb"), ("imdb".into(), "imdb"), ("tvdb".into(), "tvdb")]
+        .into_iter()
+        .filter_map(|(prefix, key)| text(ids.get(key)).map(|id| format!("{prefix}:{id}")))
+        .collect();
+    Ok(Some(Scrobble { title, played, refs }))
+}
+
+/// The library item a play belongs to: the one sharing an id, otherwise
+/// the only movie (or series) with the title.  When several have it, the
+/// only unfinished one; None when that is still ambiguous.
+pub fn find_item<'a>(items: &'a [MediaItem], scrobble: &Scrobble) -> Option<&'a MediaItem> {
+    let is_kind = |item: &&MediaItem| match scrobble.played {
+        Played::Movie => matches!(item.media_type, MediaItemType::Movie(_)),
+        Played::Episode { .. } => matches!(item.media_type, MediaItemType::Series(..)),
+    };
+    if let Some(item) = items
+        .iter()
+        .filter(is_kind)
+        .find(|item| scrobble.refs.iter().any(|r| item.external_refs.contains(r)))
+    {
+        return Some(item);
+    }
+
+    let title = normalize_title(&scrobble.title);
+    let named: Vec<&MediaItem> = items
+        .iter()
+        .filter(is_kind)
+        .filter(|item| std::iter::once(&item.title).chain(&item.alt_titles).any(|t| normalize_title(t) == title))
+        .collect();
+    match named.as_slice() {
+        [item] => Some(item),
+        _ => {
+            let mut unfinished = named.into_iter().filter(|item| !item.is_completed());
+            match (unfinished.next(), unfinished.next()) {
+                (Some(item), None) => Some(item),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Applies a play.  A movie is completed.  An episode of the first season
+/// (or one without a season) brings progress up to its number; an episode
+/// of a later season adds one, as the length of earlier seasons is not
+/// known.  Returns false when nothing changed.
+pub fn apply(item: &mut MediaItem, scrobble: &Scrobble) -> bool {
+    match (&mut item.media_type, &scrobble.played) {
+        (MediaItemType::Movie(status), Played::Movie) => {
+            if *status == WatchStatus::Completed {
+                return false;
+            }
+            *status = WatchStatus::Completed;
+            true
+        }
+        (MediaItemType::Series(progress, _), Played::Episode { season, number }) => {
+            let by = match season {
+                Some(season) if *season > 1 => 1,
+                _ => number.saturating_sub(progress.current),
+            };
+            by > 0 && item.advance(by)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::Progress;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_reads_each_format() {
+        let plex = json!({
+            "event": "media.scrobble",
+            "Metadata": {"type": "episode", "grandparentTitle": "Severance", "parentIndex": 2, "index": 3}
+        });
+        assert_eq!(
+            parse(&plex).unwrap(),
+            Some(Scrobble {
+                title: "Severance".into(),
+                played: Played::Episode { season: Some(2), number: 3 },
+                refs: Vec::new(),
+            })
+        );
+        assert_eq!(parse(&json!({"event": "media.pause", "Metadata": {}})).unwrap(), None);
+
+        let jellyfin = json!({
+            "NotificationType": "PlaybackStop", "PlayedToCompletion": true, "ItemType": "Movie",
+            "Name": "Dune", "Provider_tmdb": "438631", "Provider_imdb": ""
+        });
+        let movie = parse(&jellyfin).unwrap().unwrap();
+        assert_eq!((movie.played, movie.refs), (Played::Movie, vec!["tmdb:movie:438631".to_string()]));
+
+        let trakt = json!({"show": {"title": "Severance", "ids": {"tmdb": 95396}},
+                           "episode": {"season": 1, "number": 4}, "progress": 50});
+        assert_eq!(parse(&trakt).unwrap(), None);
+        assert!(parse(&json!({"hello": 1})).is_err());
+    }
+
+    #[test]
+    fn test_find_and_apply_episode() {
+        let series = |title: &str, current, status| {
+            MediaItem::new(title.into(), MediaItemType::Series(Progress { current, total: Some(9) }, status))
+        };
+        let items = vec![
+            series("Severance", 9, WatchStatus::Completed),
+            series("Severance", 2, WatchStatus::Watching),
+            MediaItem::new("Severance".into(), MediaItemType::Movie(WatchStatus::PlanToWatch)),
+        ];
+        let mut scrobble = Scrobble {
+            title: "severance".into(),
+            played: Played::Episode { season: Some(1), number: 5 },
+            refs: Vec::new(),
+        };
+        let mut item = find_item(&items, &scrobble).unwrap().clone();
+        assert_eq!(item.id, items[1].id);
+
+        assert!(apply(&mut item, &scrobble));
+        assert_eq!(item.media_type, MediaItemType::Series(Progress { current: 5, total: Some(9) }, WatchStatus::Watching));
+        assert!(!apply(&mut item, &scrobble));
+
+        scrobble.played = Played::Episode { season: Some(2), number: 1 };
+        assert!(apply(&mut item, &scrobble));
+        assert_eq!(item.media_type, MediaItemType::Series(Progress { current: 6, total: Some(9) }, WatchStatus::Watching));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::core::models::{MediaItem, MediaItemType};
+use crate::core::models::{
+    ContentRating, MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind, TitleLanguage, WatchStatus,
+};
+use chrono::{DateTime, NaiveDate, Utc};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,8 +12,23 @@ pub enum SearchError {
     #[error("API error: {0}")]
     Api(String),
 
-    #[error("Parse error: {0}")]
-    Parse(String),
+    /// The provider answered with JSON that does not match our schema.
+    #[error("Unexpected {provider} payload: {message}")]
+    Payload {
+        provider: String,
+        message: String,
+        snippet: String,
+    },
+}
+
+/// A stored provider payload mismatch (see `SearchError::Payload`).
+#[derive(Debug, Clone)]
+pub struct ProviderDiagnostic {
+    pub id: i64,
+    pub provider: String,
+    pub message: String,
+    pub snippet: String,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -21,28 +39,159 @@ pub enum MediaSearchType {
     Movie,
     Series,
     Book,
+    Audiobook,
+}
+
+impl MediaSearchType {
+    /// Parses the lowercase names used by the API and CLI (`anime`, `movie`, ...).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "anime" => Some(MediaSearchType::Anime),
+            "manga" => Some(MediaSearchType::Manga),
+            "light_novel" => Some(MediaSearchType::LightNovel),
+            "movie" => Some(MediaSearchType::Movie),
+            "series" => Some(MediaSearchType::Series),
+            "book" => Some(MediaSearchType::Book),
+            "audiobook" => Some(MediaSearchType::Audiobook),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaSearchType::Anime => "anime",
+            MediaSearchType::Manga => "manga",
+            MediaSearchType::LightNovel => "light_novel",
+            MediaSearchType::Movie => "movie",
+            MediaSearchType::Series => "series",
+            MediaSearchType::Book => "book",
+            MediaSearchType::Audiobook => "audiobook",
+        }
+    }
+
+    /// Media type for an entry of this category created without provider data.
+    pub fn blank_media_type(self) -> MediaItemType {
+        let progress = Progress { current: 0, total: None };
+        match self {
+            MediaSearchType::Movie => MediaItemType::Movie(WatchStatus::PlanToWatch),
+            MediaSearchType::Anime | MediaSearchType::Series => {
+                MediaItemType::Series(progress, WatchStatus::PlanToWatch)
+            }
+            MediaSearchType::Manga => {
+                MediaItemType::Readable(ReadableKind::Manga, progress, ReadStatus::PlanToRead)
+            }
+            MediaSearchType::LightNovel => {
+                MediaItemType::Readable(ReadableKind::LightNovel, progress, ReadStatus::PlanToRead)
+            }
+            MediaSearchType::Book => {
+                MediaItemType::Readable(ReadableKind::Book, progress, ReadStatus::PlanToRead)
+            }
+            MediaSearchType::Audiobook => {
+                MediaItemType::Readable(ReadableKind::Audiobook, progress, ReadStatus::PlanToRead)
+            }
+        }
+    }
+}
+
+/// A known provider that is switched off, e.g. because its API key is missing.
+#[derive(Debug, Clone)]
+pub struct UnavailableProvider {
+    pub name: &'static str,
+    pub supported_types: &'static [MediaSearchType],
+    pub reason: String,
 }
 
+/// A title's names by language, from providers that list them (AniList
+/// and MangaDex).  Empty for the others.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocalizedTitles {
+    pub english: Option<String>,
+    pub romaji: Option<String>,
+    pub native: Option<String>,
+}
+
+impl LocalizedTitles {
+    /// The name in `language`, if the provider has one.
+    pub fn get(&self, language: TitleLanguage) -> Option<&str> {
+        let name = match language {
+            TitleLanguage::English => &self.english,
+            TitleLanguage::Romaji => &self.romaji,
+            TitleLanguage::Native => &self.native,
+        };
+        name.as_deref().filter(|n| !n.trim().is_empty())
+    }
+
+    /// The default title: English, then romaji, then native.
+    pub fn preferred(&self) -> Option<&str> {
+        [TitleLanguage::English, TitleLanguage::Romaji, TitleLanguage::Native]
+            .into_iter()
+            .find_map(|language| self.get(language))
+    }
+
+    /// `title` and `alt_titles` with the name in `language` as the title
+    /// and the old title among the alternatives.  Unchanged when there is
+    /// no name in that language.
+    pub fn localize(&self, title: &str, alt_titles: &[String], language: TitleLanguage) -> (String, Vec<String>) {
+        match self.get(language) {
+            Some(name) if name != title => {
+                let alt_titles = std::iter::once(title.to_string())
+                    .chain(alt_titles.iter().filter(|t| *t != name).cloned())
+                    .collect();
+                (name.to_string(), alt_titles)
+            }
+            _ => (title.to_string(), alt_titles.to_vec()),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct SearchResult {
     pub title: String,
     pub media_type: MediaItemType,
     pub global_score: Option<u8>,
-    pub external_id: Option<u32>,
+    /// The provider's id, e.g. `154587` (AniList), `OL27448W` (Open
+    /// Library) or a MangaDex UUID.
+    pub external_id: Option<String>,
     pub poster_url: Option<String>,
     pub source: &'static str,
     pub format_label: String,
+    pub alt_titles: Vec<String>,
+    /// See `MediaItem::external_refs`.
+    pub external_refs: Vec<String>,
+    /// See `MediaItem::creator`.
+    pub creator: Option<String>,
+    /// None when the provider does not rate its entries.
+    pub content_rating: Option<ContentRating>,
+    pub titles: LocalizedTitles,
+    /// Year of release, first airing or first publication.
+    pub year: Option<i32>,
 }
 
 impl SearchResult {
+    /// Shows the name in `language` as the title (see `LocalizedTitles`).
+    pub fn in_language(mut self, language: TitleLanguage) -> Self {
+        (self.title, self.alt_titles) = self.titles.localize(&self.title, &self.alt_titles, language);
+        self
+    }
+
     pub fn into_media_item(self) -> MediaItem {
         let mut item = MediaItem::new(self.title, self.media_type);
         item.global_score = self.global_score;
         item.external_id = self.external_id;
         item.poster_url = self.poster_url;
         item.source = Some(self.source.to_string());
+        item.alt_titles = self.alt_titles;
+        item.external_refs = self.external_refs.into_iter().collect();
+        item.creator = self.creator;
+        item.content_rating = self.content_rating;
         item
     }
 
+    /// Whether the provider rates the entry as adult.
+    pub fn is_adult(&self) -> bool {
+        self.content_rating.is_some_and(ContentRating::is_adult)
+    }
+
     pub fn display_line(&self, idx: usize) -> String {
         let count = match &self.media_type {
             MediaItemType::Series(p, _) => p.total.map(|t| format!(" [{t} ep]")),
@@ -63,12 +212,305 @@ impl SearchResult {
     }
 }
 
+/// An ISBN as 13 digits: hyphens and spaces are dropped, ISBN-10s are
+/// converted, and the check digit has to match.  None for anything else.
+pub fn normalize_isbn(isbn: &str) -> Option<String> {
+    let chars: Vec<char> = isbn.chars().filter(|c| !matches!(c, '-' | ' ')).map(|c| c.to_ascii_uppercase()).collect();
+    let digit = |c: char| c.to_digit(10);
+    let isbn13_check = |digits: &[u32]| {
+        let sum: u32 = digits.iter().enumerate().map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 }).sum();
+        (10 - sum % 10) % 10
+    };
+    match chars.len() {
+        10 => {
+            let mut digits: Vec<u32> = chars[..9].iter().map(|&c| digit(c)).collect::<Option<_>>()?;
+            let check = match chars[9] {
+                'X' => 10,
+                c => digit(c)?,
+            };
+            let sum: u32 = digits.iter().enumerate().map(|(i, d)| (10 - i as u32) * d).sum();
+            if !(sum + check).is_multiple_of(11) {
+                return None;
+            }
+            digits.splice(0..0, [9, 7, 8]);
+            digits.push(isbn13_check(&digits));
+            Some(digits.iter().map(u32::to_string).collect())
+        }
+        13 => {
+            let digits: Vec<u32> = chars.iter().map(|&c| digit(c)).collect::<Option<_>>()?;
+            (isbn13_check(&digits[..12]) == digits[12]).then(|| chars.iter().collect())
+        }
+        _ => None,
+    }
+}
+
+/// Keeps the non-empty, distinct candidates that differ from `primary`.
+pub fn collect_alt_titles(
+    primary: &str,
+    candidates: impl IntoIterator<Item = String>,
+) -> Vec<String> {
+    let mut titles: Vec<String> = Vec::new();
+    for t in candidates {
+        let t = t.trim();
+        if !t.is_empty() && t != primary && !titles.iter().any(|x| x == t) {
+            titles.push(t.to_string());
+        }
+    }
+    titles
+}
+
+/// Parses the id of a provider whose ids are numbers (AniList, TMDB).
+/// Such a provider knows no other ids, so lookups of them find nothing.
+pub fn numeric_id(external_id: &str) -> Option<u32> {
+    external_id.parse().ok()
+}
+
+/// The next episode of a title that is still airing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NextAiring {
+    pub external_id: String,
+    pub episode: u32,
+    pub airing_at: DateTime<Utc>,
+}
+
+/// The newest chapter of a title, by the id in its `source:id` ref.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatestChapter {
+    pub ref_id: String,
+    pub chapter: u32,
+}
+
+/// The release date of a movie.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub external_id: String,
+    pub date: NaiveDate,
+}
+
+/// A link from a provider entry to another one, such as its sequel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    /// Lowercase relation type, e.g. `sequel`, `prequel`, `adaptation`.
+    pub kind: String,
+    /// Provider of the related entry.
+    pub source: String,
+    pub external_id: String,
+    pub title: String,
+    /// Provider format label, e.g. `TV` or `Manga`.
+    pub format: Option<String>,
+}
+
+/// Metadata only a provider's detail endpoint has, fetched when an item
+/// is added and when a refresh finds it without an overview.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaDetails {
+    pub overview: Option<String>,
+    pub genres: Vec<String>,
+    /// Length of a movie, or of an episode of a series.
+    pub runtime_minutes: Option<u32>,
+    pub season_count: Option<u32>,
+    pub episode_count: Option<u32>,
+    /// The main people behind the entry, most important first.
+    pub staff: Vec<Credit>,
+}
+
+/// A person credited on a provider entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credit {
+    pub name: String,
+    /// As the provider words it, e.g. `Director` or `Original Creator`.
+    pub role: String,
+}
+
+impl MediaDetails {
+    /// Fills in what the item does not have yet; values the user entered
+    /// are kept.
+    pub fn apply(self, item: &mut MediaItem) {
+        if item.overview.is_none() {
+            item.overview = self.overview.filter(|o| !o.trim().is_empty());
+        }
+        if item.genres.is_empty() {
+            item.genres = self.genres;
+        }
+        item.runtime_minutes = item.runtime_minutes.or(self.runtime_minutes);
+        item.season_count = item.season_count.or(self.season_count);
+        if let MediaItemType::Series(p, _) = &mut item.media_type {
+            p.total = p.total.or(self.episode_count);
+        }
+    }
+}
+
+/// A quarter of the year, as AniList groups airing anime: winter is
+/// January to March, and so on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Season {
+    Winter,
+    Spring,
+    Summer,
+    Fall,
+}
+
+impl Season {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "winter" => Some(Season::Winter),
+            "spring" => Some(Season::Spring),
+            "summer" => Some(Season::Summer),
+            "fall" => Some(Season::Fall),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Season::Winter => "winter",
+            Season::Spring => "spring",
+            Season::Summer => "summer",
+            Season::Fall => "fall",
+        }
+    }
+
+    /// First and last day of the season in `year`; None for years chrono
+    /// cannot represent.
+    pub fn dates(self, year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        let first_month = match self {
+            Season::Winter => 1,
+            Season::Spring => 4,
+            Season::Summer => 7,
+            Season::Fall => 10,
+        };
+        let start = NaiveDate::from_ymd_opt(year, first_month, 1)?;
+        let end = start.checked_add_months(chrono::Months::new(3))?.pred_opt()?;
+        Some((start, end))
+    }
+}
+
+/// A chart to browse without a search query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Browse {
+    /// What is popular right now.
+    Trending,
+    /// The most popular titles that started in one season.
+    Season { year: i32, season: Season },
+}
+
+/// Entries per page of search results.
+pub const SEARCH_PAGE_SIZE: u32 = 10;
+/// Entries per page of a chart.
+pub const BROWSE_PAGE_SIZE: u32 = 20;
+
 pub trait SearchProvider: Send + Sync {
     fn name(&self) -> &str;
+    /// Value of `MediaItem::source` for entries from this provider.
+    fn source(&self) -> &'static str;
     fn supported_types(&self) -> &[MediaSearchType];
+    /// One page (1-based) of up to `SEARCH_PAGE_SIZE` matches; pages past
+    /// the last are empty.
     fn search(
         &self,
         query: &str,
         media_type: MediaSearchType,
+        page: u32,
     ) -> Result<Vec<SearchResult>, SearchError>;
+
+    /// Looks up a single entry by its `external_id`.  Providers without
+    /// such a lookup return `Ok(None)`, as do lookups of unknown ids.
+    fn fetch(
+        &self,
+        _external_id: &str,
+        _media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        Ok(None)
+    }
+
+    /// Looks up several entries at once; unknown ids are left out.  The
+    /// default calls `fetch` per id, providers with a batch query override it.
+    fn fetch_many(
+        &self,
+        external_ids: &[String],
+        media_type: MediaSearchType,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let mut results = Vec::new();
+        for id in external_ids {
+            results.extend(self.fetch(id, media_type)?);
+        }
+        Ok(results)
+    }
+
+    /// Next episode air dates of the given series; titles that finished
+    /// airing are left out.  Providers without airing data return nothing.
+    fn next_airing(&self, _external_ids: &[String]) -> Result<Vec<NextAiring>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// Newest chapter of each title, by the id part of the item's
+    /// `source:id` ref.  Titles without chapters are left out; providers
+    /// without chapter data return nothing.
+    fn latest_chapters(&self, _ref_ids: &[String]) -> Result<Vec<LatestChapter>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// Detail metadata of one entry, or None when the provider has none
+    /// beyond its search results.
+    fn details(
+        &self,
+        _external_id: &str,
+        _media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        Ok(None)
+    }
+
+    /// Entries related to one entry (sequels, prequels, adaptations, ...).
+    /// Providers without relation data return nothing.
+    fn relations(&self, _external_id: &str) -> Result<Vec<Relation>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// One page (1-based) of up to `BROWSE_PAGE_SIZE` entries of a chart,
+    /// most popular first.  Providers without charts return nothing.
+    fn browse(
+        &self,
+        _browse: Browse,
+        _media_type: MediaSearchType,
+        _page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// Entries the provider suggests to people who liked one entry, best
+    /// suggestion first.  Providers without suggestions return nothing.
+    fn recommendations(
+        &self,
+        _external_id: &str,
+        _media_type: MediaSearchType,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// Release dates of the given movies; unknown ids and movies without
+    /// a date are left out.  Providers without them return nothing.
+    fn release_dates(&self, _external_ids: &[String]) -> Result<Vec<Release>, SearchError> {
+        Ok(Vec::new())
+    }
+
+    /// The book with an ISBN-13 (see `normalize_isbn`).  Providers without
+    /// ISBN lookups return `Ok(None)`, as do lookups of unknown ISBNs.
+    fn lookup_isbn(&self, _isbn: &str) -> Result<Option<SearchResult>, SearchError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isbns_are_normalized_to_13_digits() {
+        assert_eq!(normalize_isbn("978-0-441-17271-9").as_deref(), Some("9780441172719"));
+        assert_eq!(normalize_isbn("0441172717").as_deref(), Some("9780441172719"));
+        assert_eq!(normalize_isbn("0-8044-2957-x").as_deref(), Some("9780804429573"));
+        assert!(normalize_isbn("9780441172710").is_none());
+        assert!(normalize_isbn("0441172718").is_none());
+        assert!(normalize_isbn("dune").is_none());
+    }
 }
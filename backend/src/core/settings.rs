@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::BTreeMap;
+
+use crate::core::models::{ScoreScale, TitleLanguage};
+use crate::core::notify::{self, NotifierConfig};
+use crate::core::scheduler;
+use crate::core::search::MediaSearchType;
+use crate::core::validation;
+
+/// User-editable configuration, stored one top-level field per row in the
+/// `settings` table.  Fields missing from the table take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct Settings {
+    /// Provider names per search type (`anime`, `movie`, ...), tried in
+    /// order until one returns results.
+    pub provider_chains: BTreeMap<String, Vec<String>>,
+    /// How long before a scheduled session its reminder is sent.
+    pub reminder_lead_minutes: u32,
+    /// Hours between automatic backups; 0 turns them off.
+    pub backup_interval_hours: u32,
+    /// How many backups are kept before the oldest is deleted.
+    pub backup_keep: u32,
+    /// Saved list views by name, shared by every device.
+    pub view_presets: BTreeMap<String, ViewPreset>,
+    /// How many items can be pinned to the focus list at once.
+    pub focus_limit: u32,
+    /// Opt-in: send the anonymous report previewed at
+    /// `/api/telemetry/preview` once a day.
+    pub telemetry_enabled: bool,
+    /// Scale of the scores the API and CLI show and accept.
+    pub score_scale: ScoreScale,
+    /// Which name of an anime or manga search results show as its title:
+    /// English, romaji or native.  Providers without it keep their title.
+    pub title_language: TitleLanguage,
+    /// Hours between pulls of the connected Trakt account's watched
+    /// history; 0 only imports when asked.
+    pub trakt_sync_hours: u32,
+    /// Opt-in: send progress, score and status changes of items from
+    /// AniList to the linked AniList account.
+    pub anilist_write_through: bool,
+    /// Minutes between pulls of reading progress from Kavita and Komga;
+    /// 0 only syncs when asked.
+    pub reading_sync_minutes: u32,
+    /// Download the poster of every item added into `POSTER_DIR` and
+    /// serve it, with thumbnails, from `/api/posters`.
+    pub local_posters: bool,
+    /// Leave titles rated erotica or pornographic out of explore results,
+    /// discoveries and library lists.
+    pub hide_adult: bool,
+    /// Seconds between runs of background jobs, by job name (see
+    /// `/api/admin/jobs`), for jobs that should not keep their default.
+    pub job_interval_seconds: BTreeMap<String, u32>,
+    /// Where notices of completed items, new episodes and failed backups
+    /// are pushed: webhooks, ntfy topics, email addresses and Telegram
+    /// chats.
+    pub notifiers: Vec<NotifierConfig>,
+    /// Status of items added without one, by media type (`anime`,
+    /// `manga`, ...).  Types not listed are planned.
+    pub default_statuses: BTreeMap<String, String>,
+    /// Color scheme the frontends should use.  Only a hint; the server
+    /// does not read it.
+    pub theme: Theme,
+    /// Opt-in: let this profile's scores take part in the score
+    /// comparison of `/api/profiles/{name}/scores`, and ask for its own.
+    pub share_scores: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Follow the device.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// A named configuration of the library list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ViewPreset {
+    /// Column ids to show; empty shows the default columns.
+    pub visible_fields: Vec<String>,
+    pub sort: Option<ViewSort>,
+    /// Filter values by field, e.g. `status` → `watching`.
+    pub filters: BTreeMap<String, String>,
+    /// Field the rows are grouped by.
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ViewSort {
+    pub field: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Longest accepted view preset name.
+pub const MAX_PRESET_NAME: usize = 64;
+
+/// Checks a view preset name: non-empty, trimmed, and not too long.
+pub fn validate_preset_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() || name.trim() != name {
+        return Err("Preset name must be non-empty and not start or end with spaces".into());
+    }
+    if name.chars().count() > MAX_PRESET_NAME {
+        return Err(format!("Preset name is longer than {MAX_PRESET_NAME} characters"));
+    }
+    Ok(())
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            provider_chains: BTreeMap::new(),
+            reminder_lead_minutes: 60,
+            backup_interval_hours: 24,
+            backup_keep: 7,
+            view_presets: BTreeMap::new(),
+            focus_limit: 5,
+            telemetry_enabled: false,
+            score_scale: ScoreScale::Ten,
+            title_language: TitleLanguage::English,
+            trakt_sync_hours: 0,
+            anilist_write_through: false,
+            reading_sync_minutes: 60,
+            local_posters: false,
+            hide_adult: false,
+            job_interval_seconds: BTreeMap::new(),
+            notifiers: Vec::new(),
+            default_statuses: BTreeMap::new(),
+            theme: Theme::System,
+            share_scores: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn chain_for(&self, search_type: &str) -> &[String] {
+        self.provider_chains
+            .get(search_type)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Status an item of `media_type` is added with when none is given:
+    /// the configured one, or planned.
+    pub fn default_status(&self, media_type: &str) -> &str {
+        match self.default_statuses.get(media_type) {
+            Some(status) => status,
+            None if matches!(media_type, "movie" | "series" | "anime") => "plan_to_watch",
+            None => "plan_to_read",
+        }
+    }
+
+    /// The settings as shown to clients and written to exports: notifier
+    /// secrets read as `notify::REDACTED`.
+    pub fn redacted(mut self) -> Self {
+        notify::redact_notifiers(&mut self.notifiers);
+        self
+    }
+
+    /// Takes notifier secrets that were left out or redacted from the
+    /// `stored` settings.
+    pub fn keep_secrets(&mut self, stored: &Settings) {
+        notify::keep_secrets(&mut self.notifiers, &stored.notifiers);
+    }
+
+    /// Checks that at least one backup is kept and one item can be
+    /// focused, that view preset names, job intervals, notifiers and
+    /// default statuses are valid, and that every chain is keyed by a known search type and
+    /// only names providers (from `providers`: name and supported types)
+    /// that serve that type.
+    pub fn validate(&self, providers: &[(&str, &[MediaSearchType])]) -> Result<(), String> {
+        if self.backup_keep == 0 {
+            return Err("backup_keep must be at least 1".into());
+        }
+        if self.focus_limit == 0 {
+            return Err("focus_limit must be at least 1".into());
+        }
+        for name in self.view_presets.keys() {
+            validate_preset_name(name)?;
+        }
+        scheduler::validate_intervals(&self.job_interval_seconds)?;
+        notify::validate_notifiers(&self.notifiers)?;
+        for (media_type, status) in &self.default_statuses {
+            if !validation::MEDIA_TYPES.contains(&media_type.as_str()) {
+                return Err(format!("Unknown media type '{media_type}' in default_statuses"));
+            }
+            if !validation::STATUSES.contains(&status.as_str()) {
+                return Err(format!("Unknown status '{status}' for {media_type}"));
+            }
+        }
+        for (key, chain) in &self.provider_chains {
+            let search_type = MediaSearchType::parse(key)
+                .ok_or_else(|| format!("Unknown search type '{key}'"))?;
+            for name in chain {
+                let supported = providers
+                    .iter()
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .map(|(_, types)| *types)
+                    .ok_or_else(|| format!("Unknown provider '{name}'"))?;
+                if !supported.contains(&search_type) {
+                    return Err(format!("Provider '{name}' does not support '{key}'"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Orders providers (given by name, in their default order) according to
+/// `chain`.  Providers named in the chain come first, in chain order; the
+/// rest keep their default order after them.  Names are matched
+/// case-insensitively and unknown names are ignored.
+pub fn chain_order(names: &[&str], chain: &[String]) -> Vec<usize> {
+    let configured = chain
+        .iter()
+        .filter_map(|wanted| names.iter().position(|n| n.eq_ignore_ascii_case(wanted)));
+    let mut order = Vec::with_capacity(names.len());
+    for idx in configured.chain(0..names.len()) {
+        if !order.contains(&idx) {
+            order.push(idx);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_order_puts_configured_providers_first() {
+        let names = ["AniList", "MangaDex", "Open Library"];
+        let chain = vec!["mangadex".to_string(), "Kitsu".to_string()];
+        assert_eq!(chain_order(&names, &chain), vec![1, 0, 2]);
+        assert_eq!(chain_order(&names, &[]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_or_unsupported_providers() {
+        let providers: &[(&str, &[MediaSearchType])] = &[
+            ("AniList", &[MediaSearchType::Anime, MediaSearchType::Manga]),
+            ("MangaDex", &[MediaSearchType::Manga]),
+        ];
+        let mut settings = Settings::default();
+        settings
+            .provider_chains
+            .insert("manga".into(), vec!["mangadex".into(), "AniList".into()]);
+        assert!(settings.validate(providers).is_ok());
+
+        settings.provider_chains.insert("anime".into(), vec!["MangaDex".into()]);
+        assert!(settings.validate(providers).is_err());
+
+        settings.provider_chains.insert("anime".into(), vec!["Kitsu".into()]);
+        assert!(settings.validate(providers).is_err());
+    }
+
+    #[test]
+    fn test_default_statuses_fall_back_to_planned() {
+        let mut settings = Settings::default();
+        settings.default_statuses.insert("manga".into(), "reading".into());
+        assert_eq!(settings.default_status("manga"), "reading");
+        assert_eq!(settings.default_status("anime"), "plan_to_watch");
+        assert_eq!(settings.default_status("webtoon"), "plan_to_read");
+        assert!(settings.validate(&[]).is_ok());
+
+        settings.default_statuses.insert("anime".into(), "binging".into());
+        assert!(settings.validate(&[]).is_err());
+        settings.default_statuses.clear();
+        settings.default_statuses.insert("podcast".into(), "watching".into());
+        assert!(settings.validate(&[]).is_err());
+
+        let parsed: Settings = serde_json::from_str(r#"{"theme": "dark"}"#).unwrap();
+        assert_eq!(parsed.theme, Theme::Dark);
+    }
+}
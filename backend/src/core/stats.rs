@@ -0,0 +1,107 @@
+/// Distribution of user scores, on the 0.0-10.0 display scale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreSummary {
+    /// Scored items per whole point: index 0 counts 0.0-0.9, index 10 only 10.0.
+    pub histogram: [usize; 11],
+    pub mean: Option<f32>,
+    pub median: Option<f32>,
+}
+
+/// Summarizes stored scores (0-100) given as `(score, count)` pairs in
+/// ascending score order.
+pub fn score_summary(counts: &[(u8, usize)]) -> ScoreSummary {
+    let mut histogram = [0; 11];
+    for &(score, count) in counts {
+        histogram[(score / 10).min(10) as usize] += count;
+    }
+
+    let scored: usize = counts.iter().map(|&(_, c)| c).sum();
+    if scored == 0 {
+        return ScoreSummary { histogram, mean: None, median: None };
+    }
+    let sum: usize = counts.iter().map(|&(s, c)| s as usize * c).sum();
+
+    // The scores at positions (n - 1) / 2 and n / 2; equal for odd n.
+    let nth = |n: usize| {
+        let mut seen = 0;
+        counts
+            .iter()
+            .find(|&&(_, c)| {
+                seen += c;
+                seen > n
+            })
+            .map_or(0, |&(s, _)| s)
+    };
+    let median = (nth((scored - 1) / 2) as f32 + nth(scored / 2) as f32) / 2.0;
+
+    ScoreSummary {
+        histogram,
+        mean: Some(sum as f32 / scored as f32 / 10.0),
+        median: Some(median / 10.0),
+    }
+}
+
+/// Estimated time spent on a part of the library.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeSpent {
+    /// Episodes and movies times their stored runtime.
+    pub watch_minutes: u64,
+    /// Progress on books, whose totals are page counts.
+    pub pages: u64,
+    /// Progress on audiobooks.
+    pub listen_minutes: u64,
+}
+
+impl TimeSpent {
+    /// Time behind `units` of progress on items of one API media type:
+    /// episodes (or movie viewings) that together ran `runtime_minutes`,
+    /// pages of books or minutes of audiobooks.  Chapters of other
+    /// readables have no known length and count for nothing.
+    pub fn of(media_type: &str, units: u64, runtime_minutes: u64) -> Self {
+        match media_type {
+            "movie" | "series" | "anime" => TimeSpent { watch_minutes: runtime_minutes, ..Self::default() },
+            "book" => TimeSpent { pages: units, ..Self::default() },
+            "audiobook" => TimeSpent { listen_minutes: units, ..Self::default() },
+            _ => Self::default(),
+        }
+    }
+
+    pub fn add(&mut self, other: TimeSpent) {
+        self.watch_minutes += other.watch_minutes;
+        self.pages += other.pages;
+        self.listen_minutes += other.listen_minutes;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_spent_counts_runtime_pages_and_minutes() {
+        let mut total = TimeSpent::of("anime", 24, 24 * 23);
+        total.add(TimeSpent::of("movie", 2, 250));
+        total.add(TimeSpent::of("book", 412, 0));
+        total.add(TimeSpent::of("audiobook", 95, 0));
+        assert_eq!(total, TimeSpent { watch_minutes: 802, pages: 412, listen_minutes: 95 });
+        assert!(TimeSpent::of("manga", 120, 0).is_empty());
+    }
+
+    #[test]
+    fn test_score_summary_buckets_and_median() {
+        let summary = score_summary(&[(65, 1), (80, 2), (100, 1)]);
+        assert_eq!(summary.histogram[6], 1);
+        assert_eq!(summary.histogram[8], 2);
+        assert_eq!(summary.histogram[10], 1);
+        assert_eq!(summary.median, Some(8.0));
+        assert!((summary.mean.unwrap() - 8.125).abs() < 1e-4);
+
+        let even = score_summary(&[(60, 1), (90, 1)]);
+        assert_eq!(even.median, Some(7.5));
+        assert_eq!(score_summary(&[]).mean, None);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::core::models::MediaItem;
+use crate::core::models::{MediaItem, ScoreScale, TitleLanguage};
+use crate::core::query::Sql;
+use crate::core::settings::Settings;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,4 +21,29 @@ pub enum StorageError {
 pub trait StorageProvider {
     fn load_all(&self) -> Result<Vec<MediaItem>, StorageError>;
     fn save_all(&self, items: &[MediaItem]) -> Result<(), StorageError>;
+    /// Items matching a compiled search (see `core::query`).
+    fn query_items(&self, sql: &Sql) -> Result<Vec<MediaItem>, StorageError>;
+
+    /// The instance's score scale; storage without settings uses 0-10.
+    fn score_scale(&self) -> ScoreScale {
+        ScoreScale::default()
+    }
+
+    /// The language search results show titles in; storage without
+    /// settings uses English.
+    fn title_language(&self) -> TitleLanguage {
+        TitleLanguage::default()
+    }
+
+    /// Status an item of `media_type` is added with when none is given
+    /// (see `Settings::default_status`).
+    fn default_status(&self, media_type: &str) -> String {
+        Settings::default().default_status(media_type).to_string()
+    }
+
+    /// The dashboard's one-line summary (see `ApiDashboard::summary`), or
+    /// None for storage without a dashboard.
+    fn dashboard_summary(&self, _airing_today: Option<usize>) -> Option<String> {
+        None
+    }
 }
\ No newline at end of file
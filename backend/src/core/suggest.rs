@@ -0,0 +1,130 @@
+//! Title suggestions for the add dialog's typeahead.  Each keystroke the
+//! dialog sends asks one provider for a handful of matches, and answers
+//! are kept for a while, so typing, deleting and retyping a title does not
+//! reach the provider again.  Answers are kept per provider chain, so
+//! profiles with different chains never see each other's answers; what
+//! a profile hides or how it titles results is applied after the cache.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::core::search::{MediaSearchType, SearchResult};
+
+/// Suggestions returned for one query.
+pub const SUGGEST_LIMIT: usize = 8;
+
+/// How long an answer is reused.
+const TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Answers kept before the oldest are dropped.
+const CAPACITY: usize = 1_000;
+
+/// Media type, provider chain and query of a cached answer.
+type Key = (&'static str, Vec<String>, String);
+
+/// The cache key for a query asked along `chain`: case and spacing do not
+/// matter, and chain names are matched like `settings::chain_order` does.
+fn key(media_type: MediaSearchType, chain: &[String], query: &str) -> Key {
+    let query = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let chain = chain.iter().map(|name| name.to_lowercase()).collect();
+    (media_type.as_str(), chain, query)
+}
+
+#[derive(Default)]
+pub struct SuggestCache {
+    answers: HashMap<Key, (Instant, Vec<SearchResult>)>,
+}
+
+impl SuggestCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The answer to the same query along the same chain, if it is recent
+    /// enough.
+    pub fn get(&self, media_type: MediaSearchType, chain: &[String], query: &str, now: Instant) -> Option<Vec<SearchResult>> {
+        let (stored, results) = self.answers.get(&key(media_type, chain, query))?;
+        (now.saturating_duration_since(*stored) < TTL).then(|| results.clone())
+    }
+
+    pub fn insert(
+        &mut self,
+        media_type: MediaSearchType,
+        chain: &[String],
+        query: &str,
+        results: Vec<SearchResult>,
+        now: Instant,
+    ) {
+        if self.answers.len() >= CAPACITY {
+            self.answers.retain(|_, (stored, _)| now.saturating_duration_since(*stored) < TTL);
+        }
+        if self.answers.len() >= CAPACITY {
+            let oldest = self.answers.iter().min_by_key(|(_, (stored, _))| *stored).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.answers.remove(&oldest);
+            }
+        }
+        self.answers.insert(key(media_type, chain, query), (now, results));
+    }
+}
+
+/// A smaller version of a provider's poster, where the provider serves
+/// one at a predictable URL; other URLs are returned as they are.
+pub fn thumbnail_url(poster_url: &str) -> String {
+    if poster_url.starts_with("https://image.tmdb.org/t/p/w500/") {
+        poster_url.replacen("/w500/", "/w154/", 1)
+    } else if poster_url.contains("anilistcdn") {
+        poster_url.replacen("/cover/large/", "/cover/medium/", 1)
+    } else if poster_url.starts_with("https://covers.openlibrary.org/") && poster_url.ends_with("-M.jpg") {
+        format!("{}-S.jpg", poster_url.trim_end_matches("-M.jpg"))
+    } else {
+        poster_url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, WatchStatus};
+    use crate::core::search::LocalizedTitles;
+
+    #[test]
+    fn test_answers_are_reused_until_they_expire() {
+        let result = SearchResult {
+            title: "Frieren".into(),
+            media_type: MediaItemType::Movie(WatchStatus::PlanToWatch),
+            global_score: None,
+            external_id: Some("154587".into()),
+            poster_url: None,
+            source: "anilist",
+            format_label: "TV".into(),
+            alt_titles: Vec::new(),
+            external_refs: Vec::new(),
+            creator: None,
+            content_rating: None,
+            titles: LocalizedTitles::default(),
+            year: Some(2023),
+        };
+        let mut cache = SuggestCache::new();
+        let start = Instant::now();
+        let chain = vec!["AniList".to_string()];
+        cache.insert(MediaSearchType::Anime, &chain, "Frie", vec![result], start);
+
+        let hit = cache.get(MediaSearchType::Anime, &["anilist".into()], "  frie ", start + Duration::from_secs(60));
+        assert_eq!(hit.map(|r| r.len()), Some(1));
+        assert!(cache.get(MediaSearchType::Manga, &chain, "frie", start).is_none());
+        assert!(cache.get(MediaSearchType::Anime, &chain, "frie", start + TTL).is_none());
+        // Another chain may be answered by another provider.
+        assert!(cache.get(MediaSearchType::Anime, &[], "frie", start).is_none());
+        assert!(cache.get(MediaSearchType::Anime, &["Kitsu".into()], "frie", start).is_none());
+
+        assert_eq!(
+            thumbnail_url("https://image.tmdb.org/t/p/w500/abc.jpg"),
+            "https://image.tmdb.org/t/p/w154/abc.jpg"
+        );
+        assert_eq!(
+            thumbnail_url("https://covers.openlibrary.org/b/id/42-M.jpg"),
+            "https://covers.openlibrary.org/b/id/42-S.jpg"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use utoipa::ToSchema;
+
+use crate::core::api_types::ApiStats;
+use crate::core::jobs::JobKind;
+use crate::core::settings::Settings;
+
+/// Version of the payload layout.  Bump it whenever a field is added,
+/// removed or changes meaning.
+pub const TELEMETRY_SCHEMA: u32 = 1;
+
+/// What the payload is built from.  Gathered locally; only the coarse
+/// summary produced by `build_payload` ever leaves the server.
+#[derive(Debug)]
+pub struct Usage {
+    pub stats: ApiStats,
+    pub settings: Settings,
+    pub focus_pins: usize,
+    pub scheduled_items: usize,
+    /// Kinds of the jobs that were ever run.
+    pub job_kinds: BTreeSet<String>,
+}
+
+/// The complete anonymous report.  It holds no titles, ids, tags,
+/// timestamps or URLs: item counts are reported as ranges and features
+/// only as used or not.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct TelemetryPayload {
+    pub schema: u32,
+    /// Version of kars sending the report.
+    pub version: String,
+    /// Item count ranges by type, e.g. `"10-49"`.
+    pub items: BTreeMap<String, String>,
+    /// Whether each feature is in use.
+    pub features: BTreeMap<String, bool>,
+}
+
+/// Builds the report.  Every field sent is listed here and nowhere else.
+pub fn build_payload(usage: &Usage) -> TelemetryPayload {
+    let s = &usage.stats;
+    let items = [
+        ("total", s.total),
+        ("movies", s.movies),
+        ("series", s.series),
+        ("anime", s.anime),
+        ("readable", s.readable),
+    ];
+
+    let settings = &usage.settings;
+    let features = [
+        ("view_presets", !settings.view_presets.is_empty()),
+        ("provider_chains", !settings.provider_chains.is_empty()),
+        ("automatic_backups", settings.backup_interval_hours > 0),
+        ("notifiers", !settings.notifiers.is_empty()),
+        ("focus_list", usage.focus_pins > 0),
+        ("scheduled_sessions", usage.scheduled_items > 0),
+        ("rewatches", s.total_rewatches > 0),
+        ("metadata_refresh", usage.job_kinds.contains(JobKind::MetadataRefresh.as_str())),
+        ("imports", usage.job_kinds.contains(JobKind::Import.as_str())),
+    ];
+
+    TelemetryPayload {
+        schema: TELEMETRY_SCHEMA,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        items: items
+            .into_iter()
+            .map(|(k, n)| (k.to_string(), bucket(n).to_string()))
+            .collect(),
+        features: features.into_iter().map(|(k, used)| (k.to_string(), used)).collect(),
+    }
+}
+
+/// Coarse range for a count, so that exact library sizes are not sent.
+fn bucket(n: usize) -> &'static str {
+    match n {
+        0 => "0",
+        1..=9 => "1-9",
+        10..=49 => "10-49",
+        50..=99 => "50-99",
+        100..=499 => "100-499",
+        500..=999 => "500-999",
+        1000..=4999 => "1000-4999",
+        _ => "5000+",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_only_reports_ranges_and_flags() {
+        let usage = Usage {
+            stats: ApiStats { total: 120, movies: 7, anime: 113, ..ApiStats::default() },
+            settings: Settings::default(),
+            focus_pins: 2,
+            scheduled_items: 0,
+            job_kinds: BTreeSet::from(["import".to_string()]),
+        };
+
+        let payload = build_payload(&usage);
+        assert_eq!(payload.items["total"], "100-499");
+        assert_eq!(payload.items["movies"], "1-9");
+        assert_eq!(payload.items["series"], "0");
+        assert!(payload.features["focus_list"]);
+        assert!(payload.features["imports"]);
+        assert!(!payload.features["metadata_refresh"]);
+    }
+}
@@ -0,0 +1,31 @@
+//! State of the Trakt connection.  Tokens live in their own table rather
+//! than in `Settings`, so they never end up in exports.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Tokens are refreshed when they have less than this left.
+const REFRESH_MARGIN: Duration = Duration::hours(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraktAuth {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+    pub connected_at: DateTime<Utc>,
+    /// When watched history was last pulled, by hand or on schedule.
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+impl TraktAuth {
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at - now < REFRESH_MARGIN
+    }
+
+    /// Whether a scheduled sync is due, syncing every `hours` (0 = never).
+    pub fn sync_due(&self, hours: u32, now: DateTime<Utc>) -> bool {
+        hours > 0
+            && self
+                .last_synced_at
+                .is_none_or(|at| now - at >= Duration::hours(hours as i64))
+    }
+}
@@ -0,0 +1,110 @@
+//! Checks of items sent by clients, reported field by field.  Anything
+//! that used to be quietly fixed up (an unknown status read as planned, a
+//! score clamped into range) is rejected here instead.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::core::api_types::ApiMediaItem;
+use crate::core::custom_fields;
+use crate::core::franchise;
+use crate::core::models::ScoreScale;
+
+/// Values accepted as `media_type`.
+pub const MEDIA_TYPES: &[&str] = &["movie", "series", "anime", "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel", "audiobook"];
+
+/// Values accepted as `status`.  Watch and read statuses are
+/// interchangeable (`plan_to_watch` on a manga means `plan_to_read`).
+pub const STATUSES: &[&str] = &["watching", "reading", "plan_to_watch", "plan_to_read", "completed", "on_hold", "dropped"];
+
+/// What is wrong with one field of a request body.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Name of the field, as in the request.
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        FieldError { field: field.into(), message: message.into() }
+    }
+}
+
+/// Checks an item before it is stored, with scores entered in `scale`.
+/// Every problem is reported, not just the first.
+pub fn validate_item(item: &ApiMediaItem, scale: ScoreScale) -> Result<(), Vec<FieldError>> {
+    let mut errors = Vec::new();
+    if item.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "Title cannot be empty"));
+    }
+    if !MEDIA_TYPES.contains(&item.media_type.as_str()) {
+        errors.push(FieldError::new("media_type", format!("Expected one of: {}", MEDIA_TYPES.join(", "))));
+    }
+    if !STATUSES.contains(&item.status.as_str()) {
+        errors.push(FieldError::new("status", format!("Expected one of: {}", STATUSES.join(", "))));
+    }
+    if let Some(total) = item.total_episodes
+        && item.progress > total
+    {
+        errors.push(FieldError::new("progress", format!("Progress is past the total of {total}")));
+    }
+    if let (Some(current), Some(total)) = (item.volume_progress, item.total_volumes)
+        && current > total
+    {
+        errors.push(FieldError::new("volume_progress", format!("Volume progress is past the total of {total}")));
+    }
+    let readable = !matches!(item.media_type.as_str(), "movie" | "series" | "anime");
+    if !readable && (item.volume_progress.is_some() || item.total_volumes.is_some()) {
+        errors.push(FieldError::new("volume_progress", "Only readables track volumes"));
+    }
+    for (field, score) in [("score", item.score), ("global_score", item.global_score)] {
+        if let Some(score) = score
+            && !(0.0..=scale.max() as f32).contains(&score)
+        {
+            errors.push(FieldError::new(field, format!("Scores are between 0 and {}", scale.max())));
+        }
+    }
+    if !item.id.is_empty() && uuid::Uuid::parse_str(&item.id).is_err() {
+        errors.push(FieldError::new("id", "Expected a UUID"));
+    }
+    if let Some(scheduled_for) = item.scheduled_for.as_deref().filter(|s| !s.is_empty())
+        && chrono::DateTime::parse_from_rfc3339(scheduled_for).is_err()
+    {
+        errors.push(FieldError::new("scheduled_for", "Expected an RFC 3339 timestamp"));
+    }
+    if let Err(e) = franchise::normalize_name(item.franchise.as_deref().unwrap_or("")) {
+        errors.push(FieldError::new("franchise", e));
+    }
+    if let Err(e) = custom_fields::normalize(item.custom_fields.clone()) {
+        errors.push(FieldError::new("custom_fields", e));
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_item_reports_every_field() {
+        let mut item: ApiMediaItem = serde_json::from_value(serde_json::json!({
+            "id": "", "title": "Frieren", "media_type": "anime", "status": "watching",
+            "progress": 12, "total_episodes": 28, "score": 9.5, "tags": [], "favorite": false,
+        }))
+        .unwrap();
+        assert_eq!(validate_item(&item, ScoreScale::Ten), Ok(()));
+
+        item.title = "  ".into();
+        item.status = "finished".into();
+        item.progress = 30;
+        item.volume_progress = Some(1);
+        let fields: Vec<String> = validate_item(&item, ScoreScale::FiveStars)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["title", "status", "progress", "volume_progress", "score"]);
+    }
+}
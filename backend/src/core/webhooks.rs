@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use utoipa::ToSchema;
+
+use crate::core::api_types::ApiMediaItem;
+use crate::core::history::{HistoryEntry, HistoryKind};
+
+/// Header carrying the event name, e.g. `item.completed`.
+pub const EVENT_HEADER: &str = "X-Kars-Event";
+/// Header carrying `sha256=<hex HMAC of the body>`, keyed with the
+/// webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-Kars-Signature";
+/// Header carrying the delivery id; retries reuse it.
+pub const DELIVERY_HEADER: &str = "X-Kars-Delivery";
+
+/// A registered receiver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    /// Id of the last audit log entry sent to it.
+    pub delivered_through: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "item.created")]
+    Created,
+    #[serde(rename = "item.completed")]
+    Completed,
+    #[serde(rename = "item.scored")]
+    Scored,
+}
+
+impl WebhookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WebhookEvent::Created => "item.created",
+            WebhookEvent::Completed => "item.completed",
+            WebhookEvent::Scored => "item.scored",
+        }
+    }
+}
+
+/// The events an audit log entry triggers.  Changes made by jobs (imports,
+/// refreshes) trigger none, so a bulk import does not flood receivers.
+pub fn events_for(entry: &HistoryEntry) -> Vec<WebhookEvent> {
+    if entry.job_id.is_some() {
+        return Vec::new();
+    }
+    let completed = entry.new_value.as_deref() == Some("completed");
+    match entry.kind {
+        HistoryKind::Added if completed => vec![WebhookEvent::Created, WebhookEvent::Completed],
+        HistoryKind::Added => vec![WebhookEvent::Created],
+        HistoryKind::StatusChanged if completed => vec![WebhookEvent::Completed],
+        HistoryKind::ScoreSet if entry.new_value.is_some() => vec![WebhookEvent::Scored],
+        _ => Vec::new(),
+    }
+}
+
+/// The JSON body POSTed to every receiver.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub occurred_at: String,
+    /// The item as it is when the event is sent; None when it has been
+    /// deleted since.
+    pub item: Option<ApiMediaItem>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+impl WebhookPayload {
+    pub fn new(event: WebhookEvent, entry: &HistoryEntry, item: Option<ApiMediaItem>) -> Self {
+        WebhookPayload {
+            event,
+            occurred_at: entry.recorded_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            item,
+            old_value: entry.old_value.clone(),
+            new_value: entry.new_value.clone(),
+        }
+    }
+}
+
+/// Value of the signature header for `body`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+/// Checks a receiver URL before it is stored.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err("Webhook URL must start with http:// or https://".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn entry(kind: HistoryKind, new_value: Option<&str>, job_id: Option<i64>) -> HistoryEntry {
+        HistoryEntry {
+            id: 1,
+            item_id: Uuid::new_v4(),
+            title: "Frieren".into(),
+            kind,
+            old_value: None,
+            new_value: new_value.map(str::to_string),
+            recorded_at: Utc::now(),
+            job_id,
+        }
+    }
+
+    #[test]
+    fn test_events_and_signature() {
+        use WebhookEvent::*;
+        assert_eq!(events_for(&entry(HistoryKind::Added, Some("watching"), None)), [Created]);
+        assert_eq!(
+            events_for(&entry(HistoryKind::Added, Some("completed"), None)),
+            [Created, Completed]
+        );
+        assert_eq!(events_for(&entry(HistoryKind::StatusChanged, Some("completed"), None)), [Completed]);
+        assert_eq!(events_for(&entry(HistoryKind::ScoreSet, Some("8.5"), None)), [Scored]);
+        assert!(events_for(&entry(HistoryKind::ScoreSet, None, None)).is_empty());
+        assert!(events_for(&entry(HistoryKind::Added, Some("watching"), Some(3))).is_empty());
+
+        // RFC 4231, test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Write-through to AniList: progress, score and status changes of items
+//! from AniList are queued and sent to the linked account's list.  The
+//! queue is filled from the audit log, so changes made through the CLI or
+//! while AniList was unreachable are sent too.
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::core::history::HistoryKind;
+use crate::core::models::{MediaItem, MediaItemType, ReadStatus, WatchStatus};
+use crate::core::search::numeric_id;
+
+/// Updates are dropped after failing this many times.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// The linked AniList account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AniListAuth {
+    pub token: String,
+    pub user_id: u32,
+    pub user_name: String,
+    pub connected_at: DateTime<Utc>,
+    /// Id of the last audit log entry looked at.
+    pub history_cursor: i64,
+}
+
+/// An item waiting to be sent.  The item's state at sending time is what
+/// gets sent, so an item is queued once however often it changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedUpdate {
+    pub item_id: Uuid,
+    pub queued_at: DateTime<Utc>,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+/// An entry of an AniList list, as `SaveMediaListEntry` takes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListEntry {
+    pub media_id: u32,
+    /// `CURRENT`, `PLANNING`, `COMPLETED`, `PAUSED`, `DROPPED` or
+    /// `REPEATING`.
+    pub status: &'static str,
+    pub progress: Option<u32>,
+    pub progress_volumes: Option<u32>,
+    /// 0-100, like `MediaItem::score`.
+    pub score: Option<u8>,
+    pub repeat: u32,
+}
+
+/// Whether an audit log entry of this kind is sent.
+pub fn is_synced(kind: HistoryKind) -> bool {
+    matches!(kind, HistoryKind::ProgressUpdated | HistoryKind::ScoreSet | HistoryKind::StatusChanged)
+}
+
+fn watch_status(status: &WatchStatus, repeating: bool) -> &'static str {
+    match status {
+        WatchStatus::Watching if repeating => "REPEATING",
+        WatchStatus::Watching => "CURRENT",
+        WatchStatus::PlanToWatch => "PLANNING",
+        WatchStatus::Completed => "COMPLETED",
+        WatchStatus::OnHold => "PAUSED",
+        WatchStatus::Dropped => "DROPPED",
+    }
+}
+
+fn read_status(status: &ReadStatus, repeating: bool) -> &'static str {
+    match status {
+        ReadStatus::Reading if repeating => "REPEATING",
+        ReadStatus::Reading => "CURRENT",
+        ReadStatus::PlanToRead => "PLANNING",
+        ReadStatus::Completed => "COMPLETED",
+        ReadStatus::OnHold => "PAUSED",
+        ReadStatus::Dropped => "DROPPED",
+    }
+}
+
+/// The list entry for an item from AniList; None for other items.  An
+/// item in progress that was finished before is `REPEATING`.
+pub fn list_entry(item: &MediaItem) -> Option<ListEntry> {
+    if item.source.as_deref() != Some("anilist") {
+        return None;
+    }
+    let media_id = numeric_id(item.external_id.as_deref()?)?;
+    let repeating = item.repeat_count > 0;
+    let (status, progress) = match &item.media_type {
+        MediaItemType::Movie(s) => (watch_status(s, repeating), None),
+        MediaItemType::Series(p, s) => (watch_status(s, repeating), Some(p.current)),
+        MediaItemType::Readable(_, p, s) => (read_status(s, repeating), Some(p.current)),
+    };
+    Some(ListEntry {
+        media_id,
+        status,
+        progress,
+        progress_volumes: item.volumes.as_ref().map(|v| v.current),
+        score: item.score,
+        repeat: item.repeat_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, ReadableKind};
+
+    #[test]
+    fn test_list_entry_only_for_anilist_items() {
+        let mut item = MediaItem::new(
+            "Berserk".into(),
+            MediaItemType::Readable(
+                ReadableKind::Manga,
+                Progress { current: 370, total: None },
+                ReadStatus::Reading,
+            ),
+        );
+        item.score = Some(95);
+        item.volumes = Some(Progress { current: 41, total: None });
+        assert_eq!(list_entry(&item), None);
+
+        item.source = Some("anilist".into());
+        item.external_id = Some("30002".into());
+        assert_eq!(
+            list_entry(&item),
+            Some(ListEntry {
+                media_id: 30002,
+                status: "CURRENT",
+                progress: Some(370),
+                progress_volumes: Some(41),
+                score: Some(95),
+                repeat: 0,
+            })
+        );
+
+        item.repeat_count = 1;
+        assert_eq!(list_entry(&item).unwrap().status, "REPEATING");
+    }
+}
@@ -1,56 +1,257 @@
 use crate::core::models::{
-    MediaItemType, Progress, ReadStatus, ReadableKind, WatchStatus,
+    ContentRating, MediaItemType, Progress, ReadStatus, ReadableKind, WatchStatus,
 };
-use crate::core::search::{MediaSearchType, SearchError, SearchProvider, SearchResult};
+use crate::core::write_through::ListEntry;
+use crate::core::search::{
+    collect_alt_titles, numeric_id, Browse, Credit, LocalizedTitles, MediaDetails, MediaSearchType, NextAiring,
+    Relation, SearchError, SearchProvider, SearchResult, BROWSE_PAGE_SIZE, SEARCH_PAGE_SIZE,
+};
+use chrono::{DateTime, NaiveDate};
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
 use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 const ANILIST_URL: &str = "https://graphql.anilist.co";
 
+const MEDIA_FIELDS: &str = r#"
+fragment mediaFields on Media {
+  id
+  idMal
+  title {
+    romaji
+    english
+    native
+  }
+  synonyms
+  episodes
+  chapters
+  meanScore
+  coverImage {
+    large
+  }
+  startDate {
+    year
+  }
+  format
+  countryOfOrigin
+  isAdult
+  studios(isMain: true) {
+    nodes {
+      name
+    }
+  }
+}
+"#;
+
 const SEARCH_QUERY: &str = r#"
-query ($search: String, $type: MediaType, $format: MediaFormat) {
-  Page(perPage: 10) {
+query ($search: String, $type: MediaType, $format: MediaFormat, $page: Int, $perPage: Int) {
+  Page(page: $page, perPage: $perPage) {
     media(search: $search, type: $type, format: $format, sort: SEARCH_MATCH) {
+      ...mediaFields
+    }
+  }
+}
+"#;
+
+/// Charts.  Unset variables are ignored by AniList, so one query serves
+/// trending and seasonal lists.
+const BROWSE_QUERY: &str = r#"
+query ($type: MediaType, $format: MediaFormat, $sort: [MediaSort], $season: MediaSeason, $seasonYear: Int, $startFrom: FuzzyDateInt, $startTo: FuzzyDateInt, $page: Int, $perPage: Int) {
+  Page(page: $page, perPage: $perPage) {
+    media(type: $type, format: $format, sort: $sort, season: $season, seasonYear: $seasonYear, startDate_greater: $startFrom, startDate_lesser: $startTo, isAdult: false) {
+      ...mediaFields
+    }
+  }
+}
+"#;
+
+/// AniList caps pages at 50 entries.
+const BATCH_SIZE: usize = 50;
+
+const BATCH_QUERY: &str = r#"
+query ($ids: [Int], $perPage: Int) {
+  Page(perPage: $perPage) {
+    media(id_in: $ids) {
+      ...mediaFields
+    }
+  }
+}
+"#;
+
+/// Next episode of still-airing anime; uses no media fragment.
+const AIRING_QUERY: &str = r#"
+query ($ids: [Int], $perPage: Int) {
+  Page(perPage: $perPage) {
+    media(id_in: $ids, type: ANIME) {
       id
-      title {
-        romaji
-        english
+      nextAiringEpisode {
+        episode
+        airingAt
+      }
+    }
+  }
+}
+"#;
+
+/// Entries linked to one entry; uses no media fragment.
+const RELATIONS_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id) {
+    relations {
+      edges {
+        relationType(version: 2)
+        node {
+          id
+          format
+          title {
+            romaji
+            english
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// What users who liked one entry recommend, most upvoted first.
+const RECOMMENDATIONS_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id) {
+    recommendations(sort: RATING_DESC, perPage: 10) {
+      nodes {
+        mediaRecommendation {
+          ...mediaFields
+        }
       }
-      episodes
-      chapters
-      meanScore
-      coverImage {
-        large
+    }
+  }
+}
+"#;
+
+const FETCH_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id) {
+    ...mediaFields
+  }
+}
+"#;
+
+const DETAILS_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id) {
+    description(asHtml: false)
+    genres
+    duration
+    episodes
+    staff(sort: RELEVANCE, perPage: 10) {
+      edges {
+        role
+        node {
+          name {
+            full
+          }
+        }
       }
-      format
-      countryOfOrigin
     }
   }
 }
 "#;
 
+const VIEWER_QUERY: &str = r#"
+query {
+  Viewer {
+    id
+    name
+  }
+}
+"#;
+
+const SAVE_ENTRY_MUTATION: &str = r#"
+mutation ($mediaId: Int, $status: MediaListStatus, $progress: Int, $progressVolumes: Int,
+          $scoreRaw: Int, $repeat: Int) {
+  SaveMediaListEntry(mediaId: $mediaId, status: $status, progress: $progress,
+                     progressVolumes: $progressVolumes, scoreRaw: $scoreRaw, repeat: $repeat) {
+    id
+  }
+}
+"#;
+
 // ── GraphQL request ──────────────────────────────────────────────
 
 #[derive(Serialize)]
-struct GqlRequest {
-    query: &'static str,
-    variables: GqlVariables,
+struct GqlRequest<V> {
+    query: String,
+    variables: V,
+}
+
+#[derive(Serialize)]
+struct FetchVariables {
+    id: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchVariables {
+    ids: Vec<u32>,
+    per_page: usize,
 }
 
 #[derive(Serialize)]
-struct GqlVariables {
+struct SearchVariables {
     search: String,
     #[serde(rename = "type")]
     media_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     format: Option<String>,
+    page: u32,
+    #[serde(rename = "perPage")]
+    per_page: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowseVariables {
+    #[serde(rename = "type")]
+    media_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    sort: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season_year: Option<i32>,
+    /// `YYYYMMDD`, exclusive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_from: Option<u32>,
+    /// `YYYYMMDD`, exclusive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_to: Option<u32>,
+    page: u32,
+    per_page: u32,
+}
+
+/// Fields left out are not changed on the list.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveEntryVariables {
+    media_id: u32,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress_volumes: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score_raw: Option<u8>,
+    repeat: u32,
 }
 
 // ── GraphQL response ─────────────────────────────────────────────
 
 #[derive(Deserialize)]
-struct GqlResponse {
-    data: Option<GqlData>,
+struct GqlResponse<T> {
+    data: Option<T>,
     errors: Option<Vec<GqlError>>,
 }
 
@@ -60,33 +261,186 @@ struct GqlError {
 }
 
 #[derive(Deserialize)]
-struct GqlData {
+struct ViewerData {
+    #[serde(rename = "Viewer")]
+    viewer: AniListViewer,
+}
+
+/// The account a token belongs to.
+#[derive(Deserialize)]
+pub struct AniListViewer {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct SaveEntryData {
+    #[serde(rename = "SaveMediaListEntry")]
+    entry: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct SearchData {
     #[serde(rename = "Page")]
     page: GqlPage,
 }
 
+#[derive(Deserialize)]
+struct FetchData {
+    #[serde(rename = "Media")]
+    media: Option<GqlMedia>,
+}
+
+#[derive(Deserialize)]
+struct DetailsData {
+    #[serde(rename = "Media")]
+    media: Option<GqlDetailsMedia>,
+}
+
+#[derive(Deserialize)]
+struct GqlDetailsMedia {
+    /// Markdown-ish text that still carries `<br>` and `<i>` tags.
+    description: Option<String>,
+    #[serde(default)]
+    genres: Vec<String>,
+    /// Minutes per episode.
+    duration: Option<u32>,
+    episodes: Option<u32>,
+    staff: Option<GqlStaffConnection>,
+}
+
+#[derive(Deserialize)]
+struct GqlStaffConnection {
+    #[serde(default)]
+    edges: Vec<GqlStaffEdge>,
+}
+
+#[derive(Deserialize)]
+struct GqlStaffEdge {
+    role: Option<String>,
+    node: Option<GqlStaff>,
+}
+
+#[derive(Deserialize)]
+struct GqlStaff {
+    name: GqlStaffName,
+}
+
+#[derive(Deserialize)]
+struct GqlStaffName {
+    full: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct GqlPage {
     media: Vec<GqlMedia>,
 }
 
+#[derive(Deserialize)]
+struct AiringData {
+    #[serde(rename = "Page")]
+    page: AiringPage,
+}
+
+#[derive(Deserialize)]
+struct AiringPage {
+    media: Vec<GqlAiringMedia>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlAiringMedia {
+    id: u32,
+    next_airing_episode: Option<GqlAiringEpisode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlAiringEpisode {
+    episode: u32,
+    /// Unix seconds.
+    airing_at: i64,
+}
+
+#[derive(Deserialize)]
+struct RelationsData {
+    #[serde(rename = "Media")]
+    media: Option<GqlRelationsMedia>,
+}
+
+#[derive(Deserialize)]
+struct GqlRelationsMedia {
+    relations: GqlRelationConnection,
+}
+
+#[derive(Deserialize)]
+struct GqlRelationConnection {
+    #[serde(default)]
+    edges: Vec<GqlRelationEdge>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRelationEdge {
+    relation_type: Option<String>,
+    node: Option<GqlRelationNode>,
+}
+
+#[derive(Deserialize)]
+struct GqlRelationNode {
+    id: u32,
+    format: Option<String>,
+    title: GqlTitle,
+}
+
+#[derive(Deserialize)]
+struct RecommendationsData {
+    #[serde(rename = "Media")]
+    media: Option<GqlRecommendationsMedia>,
+}
+
+#[derive(Deserialize)]
+struct GqlRecommendationsMedia {
+    recommendations: GqlRecommendationConnection,
+}
+
+#[derive(Deserialize)]
+struct GqlRecommendationConnection {
+    #[serde(default)]
+    nodes: Vec<GqlRecommendation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRecommendation {
+    media_recommendation: Option<GqlMedia>,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GqlMedia {
     id: u32,
+    id_mal: Option<u32>,
     title: GqlTitle,
+    #[serde(default)]
+    synonyms: Vec<String>,
     episodes: Option<u32>,
     chapters: Option<u32>,
     mean_score: Option<u32>,
     cover_image: Option<GqlCoverImage>,
+    start_date: Option<GqlFuzzyDate>,
     format: Option<String>,
     country_of_origin: Option<String>,
+    is_adult: Option<bool>,
+    /// Main animation studios; empty for manga.
+    studios: Option<GqlStudios>,
 }
 
 #[derive(Deserialize)]
 struct GqlTitle {
     romaji: Option<String>,
     english: Option<String>,
+    native: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -94,17 +448,112 @@ struct GqlCoverImage {
     large: Option<String>,
 }
 
+/// Only the year is asked for.
+#[derive(Deserialize)]
+struct GqlFuzzyDate {
+    year: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct GqlStudios {
+    nodes: Vec<GqlStudio>,
+}
+
+#[derive(Deserialize)]
+struct GqlStudio {
+    name: String,
+}
+
 // ── Client ───────────────────────────────────────────────────────
 
 pub struct AniListClient {
     client: Client,
+    retry: RetryPolicy,
+    /// Access token of a linked account, for list updates.
+    token: Option<String>,
 }
 
 impl AniListClient {
     pub fn new() -> Self {
         Self {
             client: Client::new(),
+            retry: RetryPolicy::from_env(),
+            token: None,
+        }
+    }
+
+    /// A client acting as the account the token belongs to.
+    pub fn with_token(token: &str) -> Self {
+        Self {
+            token: Some(token.to_string()),
+            ..Self::new()
+        }
+    }
+
+    /// The account the client's token belongs to.
+    pub fn viewer(&self) -> Result<AniListViewer, SearchError> {
+        let data: Option<ViewerData> = self.post_query(VIEWER_QUERY.into(), serde_json::json!({}))?;
+        data.map(|d| d.viewer)
+            .ok_or_else(|| SearchError::Api("AniList did not accept the token".into()))
+    }
+
+    /// Creates or updates the entry on the account's list.
+    pub fn save_entry(&self, entry: &ListEntry) -> Result<(), SearchError> {
+        let variables = SaveEntryVariables {
+            media_id: entry.media_id,
+            status: entry.status,
+            progress: entry.progress,
+            progress_volumes: entry.progress_volumes,
+            score_raw: entry.score,
+            repeat: entry.repeat,
+        };
+        let data: Option<SaveEntryData> = self.post_query(SAVE_ENTRY_MUTATION.into(), variables)?;
+        match data.and_then(|d| d.entry) {
+            Some(_) => Ok(()),
+            None => Err(SearchError::Api(format!("AniList does not know media {}", entry.media_id))),
+        }
+    }
+
+    /// Runs a query (with the shared media fragment) and unwraps `data`.
+    fn post<V: Serialize, T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: V,
+    ) -> Result<Option<T>, SearchError> {
+        self.post_query(format!("{query}{MEDIA_FIELDS}"), variables)
+    }
+
+    /// Runs a complete query and unwraps `data`.
+    fn post_query<V: Serialize, T: DeserializeOwned>(
+        &self,
+        query: String,
+        variables: V,
+    ) -> Result<Option<T>, SearchError> {
+        let body = GqlRequest { query, variables };
+
+        let mut request = self.client.post(ANILIST_URL).json(&body);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = send_with_retry(&self.retry, request)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
         }
+
+        let gql: GqlResponse<T> = decode_json("anilist", response)?;
+
+        if let Some(errors) = gql.errors {
+            let msg = errors
+                .iter()
+                .map(|e| e.message.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(SearchError::Api(msg));
+        }
+
+        gql.data
+            .map(Some)
+            .ok_or_else(|| SearchError::Api("No data in response".into()))
     }
 
     fn map_media(
@@ -112,12 +561,20 @@ impl AniListClient {
         media: GqlMedia,
         search_type: MediaSearchType,
     ) -> Option<SearchResult> {
-        let title = media
-            .title
-            .english
-            .filter(|s| !s.is_empty())
-            .or(media.title.romaji)
-            .unwrap_or_else(|| "Unknown".into());
+        let titles = LocalizedTitles {
+            english: media.title.english,
+            romaji: media.title.romaji,
+            native: media.title.native,
+        };
+        let title = titles.preferred().unwrap_or("Unknown").to_string();
+        let alt_titles = collect_alt_titles(
+            &title,
+            [&titles.romaji, &titles.english, &titles.native]
+                .into_iter()
+                .flatten()
+                .cloned()
+                .chain(media.synonyms),
+        );
 
         let format_str = media.format.as_deref().unwrap_or("UNKNOWN");
         let country = media.country_of_origin.as_deref().unwrap_or("JP");
@@ -169,6 +626,16 @@ impl AniListClient {
             _ => return None,
         };
 
+        // AniList ids are shared by anime and manga; MAL ids are not.
+        let mal_kind = match search_type {
+            MediaSearchType::Anime => "anime",
+            _ => "manga",
+        };
+        let mut external_refs = vec![format!("anilist:{}", media.id)];
+        if let Some(mal) = media.id_mal {
+            external_refs.push(format!("mal:{mal_kind}:{mal}"));
+        }
+
         // AniList meanScore: 0-100 → our global_score: 0-100 (u8)
         let global_score = media.mean_score.map(|s| s.min(100) as u8);
 
@@ -176,19 +643,130 @@ impl AniListClient {
             title,
             media_type,
             global_score,
-            external_id: Some(media.id),
+            external_id: Some(media.id.to_string()),
             poster_url: media.cover_image.and_then(|c| c.large),
             source: "anilist",
             format_label,
+            alt_titles,
+            external_refs,
+            creator: media.studios.and_then(|s| s.nodes.into_iter().next()).map(|s| s.name),
+            content_rating: media.is_adult.map(ContentRating::from_adult_flag),
+            titles,
+            year: media.start_date.and_then(|d| d.year),
         })
     }
 }
 
+fn map_relations(media: GqlRelationsMedia) -> Vec<Relation> {
+    media
+        .relations
+        .edges
+        .into_iter()
+        .filter_map(|edge| {
+            let node = edge.node?;
+            let title = node
+                .title
+                .english
+                .filter(|s| !s.is_empty())
+                .or(node.title.romaji)
+                .unwrap_or_else(|| "Unknown".into());
+            Some(Relation {
+                kind: edge.relation_type?.to_lowercase(),
+                source: "anilist".into(),
+                external_id: node.id.to_string(),
+                title,
+                format: node.format,
+            })
+        })
+        .collect()
+}
+
+/// Variables of the chart for the type, or None for types AniList does
+/// not list.
+fn browse_variables(browse: Browse, media_type: MediaSearchType, page: u32) -> Option<BrowseVariables> {
+    let (api_type, format) = match media_type {
+        MediaSearchType::Anime => ("ANIME", None),
+        MediaSearchType::Manga => ("MANGA", None),
+        MediaSearchType::LightNovel => ("MANGA", Some("NOVEL")),
+        _ => return None,
+    };
+    let mut variables = BrowseVariables {
+        media_type: api_type.into(),
+        format: format.map(str::to_string),
+        sort: vec!["TRENDING_DESC".into()],
+        season: None,
+        season_year: None,
+        start_from: None,
+        start_to: None,
+        page,
+        per_page: BROWSE_PAGE_SIZE,
+    };
+    if let Browse::Season { year, season } = browse {
+        variables.sort = vec!["POPULARITY_DESC".into()];
+        // Anime have an airing season; manga and novels only a start date.
+        if media_type == MediaSearchType::Anime {
+            variables.season = Some(season.as_str().to_uppercase());
+            variables.season_year = Some(year);
+        } else {
+            let (start, end) = season.dates(year)?;
+            variables.start_from = Some(fuzzy_date(start.pred_opt()?));
+            variables.start_to = Some(fuzzy_date(end.succ_opt()?));
+        }
+    }
+    Some(variables)
+}
+
+/// An AniList (or Google Books) description as plain text: line breaks
+/// and paragraphs kept, other tags dropped and the common entities decoded.
+pub(crate) fn plain_text(description: &str) -> String {
+    let mut text = String::with_capacity(description.len());
+    let mut rest = description;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        if matches!(tag.trim_end_matches('/').trim(), "br" | "p" | "/p") {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        // At most one blank line between paragraphs.
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// AniList's `FuzzyDateInt`: the date as `YYYYMMDD`.
+fn fuzzy_date(date: NaiveDate) -> u32 {
+    use chrono::Datelike;
+    date.year() as u32 * 10_000 + date.month() * 100 + date.day()
+}
+
 impl SearchProvider for AniListClient {
     fn name(&self) -> &str {
         "AniList"
     }
 
+    fn source(&self) -> &'static str {
+        "anilist"
+    }
+
     fn supported_types(&self) -> &[MediaSearchType] {
         &[
             MediaSearchType::Anime,
@@ -201,6 +779,7 @@ impl SearchProvider for AniListClient {
         &self,
         query: &str,
         media_type: MediaSearchType,
+        page: u32,
     ) -> Result<Vec<SearchResult>, SearchError> {
         let (api_type, format_filter) = match media_type {
             MediaSearchType::Anime => ("ANIME", None),
@@ -209,38 +788,16 @@ impl SearchProvider for AniListClient {
             _ => return Ok(Vec::new()),
         };
 
-        let body = GqlRequest {
-            query: SEARCH_QUERY,
-            variables: GqlVariables {
-                search: query.to_string(),
-                media_type: api_type.to_string(),
-                format: format_filter.map(|f| f.to_string()),
-            },
+        let variables = SearchVariables {
+            search: query.to_string(),
+            media_type: api_type.to_string(),
+            format: format_filter.map(|f| f.to_string()),
+            page,
+            per_page: SEARCH_PAGE_SIZE,
+        };
+        let Some(data) = self.post::<_, SearchData>(SEARCH_QUERY, variables)? else {
+            return Ok(Vec::new());
         };
-
-        let response = self
-            .client
-            .post(ANILIST_URL)
-            .json(&body)
-            .send()
-            .map_err(|e| SearchError::Network(e.to_string()))?;
-
-        let gql: GqlResponse = response
-            .json()
-            .map_err(|e| SearchError::Parse(e.to_string()))?;
-
-        if let Some(errors) = gql.errors {
-            let msg = errors
-                .iter()
-                .map(|e| e.message.as_str())
-                .collect::<Vec<_>>()
-                .join(", ");
-            return Err(SearchError::Api(msg));
-        }
-
-        let data = gql
-            .data
-            .ok_or_else(|| SearchError::Api("No data in response".into()))?;
 
         let results = data
             .page
@@ -251,4 +808,227 @@ impl SearchProvider for AniListClient {
 
         Ok(results)
     }
+
+    fn browse(
+        &self,
+        browse: Browse,
+        media_type: MediaSearchType,
+        page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let Some(variables) = browse_variables(browse, media_type, page) else {
+            return Ok(Vec::new());
+        };
+        let Some(data) = self.post::<_, SearchData>(BROWSE_QUERY, variables)? else {
+            return Ok(Vec::new());
+        };
+        Ok(data
+            .page
+            .media
+            .into_iter()
+            .filter_map(|m| self.map_media(m, media_type))
+            .collect())
+    }
+
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        let Some(id) = numeric_id(external_id) else { return Ok(None) };
+        let data = self.post::<_, FetchData>(FETCH_QUERY, FetchVariables { id })?;
+        Ok(data
+            .and_then(|d| d.media)
+            .and_then(|m| self.map_media(m, media_type)))
+    }
+    fn fetch_many(
+        &self,
+        external_ids: &[String],
+        media_type: MediaSearchType,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let ids: Vec<u32> = external_ids.iter().filter_map(|id| numeric_id(id)).collect();
+        let mut results = Vec::new();
+        for ids in ids.chunks(BATCH_SIZE) {
+            let variables = BatchVariables {
+                ids: ids.to_vec(),
+                per_page: BATCH_SIZE,
+            };
+            if let Some(data) = self.post::<_, SearchData>(BATCH_QUERY, variables)? {
+                results.extend(
+                    data.page
+                        .media
+                        .into_iter()
+                        .filter_map(|m| self.map_media(m, media_type)),
+                );
+            }
+        }
+        Ok(results)
+    }
+
+    fn details(
+        &self,
+        external_id: &str,
+        _media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        let Some(id) = numeric_id(external_id) else { return Ok(None) };
+        let data = self.post_query::<_, DetailsData>(DETAILS_QUERY.into(), FetchVariables { id })?;
+        Ok(data.and_then(|d| d.media).map(|m| MediaDetails {
+            overview: m.description.as_deref().map(plain_text),
+            genres: m.genres,
+            runtime_minutes: m.duration.filter(|&d| d > 0),
+            season_count: None,
+            episode_count: m.episodes.filter(|&n| n > 0),
+            staff: m
+                .staff
+                .map(|s| s.edges)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|edge| {
+                    let name = edge.node?.name.full?;
+                    Some(Credit { name, role: edge.role.unwrap_or_default() })
+                })
+                .collect(),
+        }))
+    }
+
+    fn relations(&self, external_id: &str) -> Result<Vec<Relation>, SearchError> {
+        let Some(id) = numeric_id(external_id) else { return Ok(Vec::new()) };
+        let variables = FetchVariables { id };
+        let data = self.post_query::<_, RelationsData>(RELATIONS_QUERY.to_string(), variables)?;
+        Ok(data.and_then(|d| d.media).map(map_relations).unwrap_or_default())
+    }
+
+    fn recommendations(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let Some(id) = numeric_id(external_id) else { return Ok(Vec::new()) };
+        let variables = FetchVariables { id };
+        let Some(media) = self
+            .post::<_, RecommendationsData>(RECOMMENDATIONS_QUERY, variables)?
+            .and_then(|d| d.media)
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(media
+            .recommendations
+            .nodes
+            .into_iter()
+            .filter_map(|n| n.media_recommendation)
+            .filter_map(|m| self.map_media(m, media_type))
+            .collect())
+    }
+
+    fn next_airing(&self, external_ids: &[String]) -> Result<Vec<NextAiring>, SearchError> {
+        let ids: Vec<u32> = external_ids.iter().filter_map(|id| numeric_id(id)).collect();
+        let mut airing = Vec::new();
+        for ids in ids.chunks(BATCH_SIZE) {
+            let variables = BatchVariables {
+                ids: ids.to_vec(),
+                per_page: BATCH_SIZE,
+            };
+            let Some(data) = self.post_query::<_, AiringData>(AIRING_QUERY.into(), variables)? else {
+                continue;
+            };
+            airing.extend(data.page.media.into_iter().filter_map(|m| {
+                let next = m.next_airing_episode?;
+                Some(NextAiring {
+                    external_id: m.id.to_string(),
+                    episode: next.episode,
+                    airing_at: DateTime::from_timestamp(next.airing_at, 0)?,
+                })
+            }));
+        }
+        Ok(airing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relations_prefer_english_titles() {
+        let body = r#"{
+            "relations": {
+                "edges": [
+                    {
+                        "relationType": "SEQUEL",
+                        "node": {"id": 21, "format": "TV", "title": {"romaji": "Shingeki no Kyojin 2", "english": "Attack on Titan Season 2"}}
+                    },
+                    {
+                        "relationType": "ADAPTATION",
+                        "node": {"id": 53390, "format": "MANGA", "title": {"romaji": "Shingeki no Kyojin", "english": ""}}
+                    },
+                    {"relationType": "OTHER", "node": null}
+                ]
+            }
+        }"#;
+        let relations = map_relations(serde_json::from_str(body).unwrap());
+
+        assert_eq!(relations.len(), 2);
+        assert_eq!(relations[0].kind, "sequel");
+        assert_eq!(relations[0].title, "Attack on Titan Season 2");
+        assert_eq!(relations[1].kind, "adaptation");
+        assert_eq!(relations[1].title, "Shingeki no Kyojin");
+        assert_eq!(relations[1].format.as_deref(), Some("MANGA"));
+    }
+
+    #[test]
+    fn test_browse_variables_per_type() {
+        use crate::core::search::Season;
+
+        let winter = Browse::Season { year: 2025, season: Season::Winter };
+        let anime = browse_variables(winter, MediaSearchType::Anime, 1).unwrap();
+        assert_eq!(anime.season.as_deref(), Some("WINTER"));
+        assert_eq!(anime.season_year, Some(2025));
+        assert_eq!(anime.sort, ["POPULARITY_DESC"]);
+
+        let fall = Browse::Season { year: 2025, season: Season::Fall };
+        let novels = browse_variables(fall, MediaSearchType::LightNovel, 2).unwrap();
+        assert_eq!(novels.format.as_deref(), Some("NOVEL"));
+        assert_eq!((novels.start_from, novels.start_to), (Some(20250930), Some(20260101)));
+        assert_eq!(novels.season, None);
+        assert_eq!(novels.page, 2);
+
+        let trending = browse_variables(Browse::Trending, MediaSearchType::Manga, 1).unwrap();
+        assert_eq!(trending.sort, ["TRENDING_DESC"]);
+        assert!(browse_variables(Browse::Trending, MediaSearchType::Movie, 1).is_none());
+    }
+
+    #[test]
+    fn test_plain_text_description() {
+        let description = "Elf mage <i>Frieren</i> &amp; her party defeated the Demon King.<br>\n<br>\n<br>\nDecades later&#039;s \"journey\" begins.<br><br>\n(Source: Crunchyroll)";
+        assert_eq!(
+            plain_text(description),
+            "Elf mage Frieren & her party defeated the Demon King.\n\nDecades later's \"journey\" begins.\n\n(Source: Crunchyroll)"
+        );
+    }
+    #[test]
+    fn test_titles_follow_the_language_preference() {
+        use crate::core::models::TitleLanguage;
+
+        let body = r#"{
+            "id": 154587, "format": "TV", "synonyms": ["Frieren at the Funeral"],
+            "title": {"romaji": "Sousou no Frieren", "english": "Frieren: Beyond Journey's End", "native": "葬送のフリーレン"}
+        }"#;
+        let result = AniListClient::new()
+            .map_media(serde_json::from_str(body).unwrap(), MediaSearchType::Anime)
+            .unwrap();
+        assert_eq!(result.title, "Frieren: Beyond Journey's End");
+
+        let romaji = result.clone().in_language(TitleLanguage::Romaji);
+        assert_eq!(romaji.title, "Sousou no Frieren");
+        assert_eq!(romaji.alt_titles[0], "Frieren: Beyond Journey's End");
+        assert!(!romaji.alt_titles.contains(&"Sousou no Frieren".to_string()));
+        assert_eq!(result.clone().in_language(TitleLanguage::Native).title, "葬送のフリーレン");
+
+        // Without an English name the romaji one is the title.
+        let body = r#"{"id": 1, "format": "TV", "title": {"romaji": "Mushishi", "english": " "}}"#;
+        let result = AniListClient::new()
+            .map_media(serde_json::from_str(body).unwrap(), MediaSearchType::Anime)
+            .unwrap();
+        assert_eq!(result.title, "Mushishi");
+        assert_eq!(result.in_language(TitleLanguage::Native).title, "Mushishi");
+    }
 }
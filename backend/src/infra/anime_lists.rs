@@ -0,0 +1,134 @@
+use crate::core::models::MediaItem;
+use crate::core::search::SearchError;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Community-maintained id mapping between anime databases.
+const MAPPING_URL: &str =
+    "https://raw.githubusercontent.com/Fribb/anime-lists/master/anime-list-full.json";
+
+// ── Response types ───────────────────────────────────────────────
+
+/// One anime with its id on each service.  Ids are mostly numbers but a
+/// few entries carry strings, so they are kept as raw JSON values.
+#[derive(Deserialize)]
+pub struct AnimeMapping {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    anidb_id: Option<serde_json::Value>,
+    anilist_id: Option<serde_json::Value>,
+    mal_id: Option<serde_json::Value>,
+    kitsu_id: Option<serde_json::Value>,
+    thetvdb_id: Option<serde_json::Value>,
+    themoviedb_id: Option<serde_json::Value>,
+}
+
+impl AnimeMapping {
+    /// The entry's ids in `MediaItem::external_refs` form.
+    pub fn refs(&self) -> Vec<String> {
+        let tmdb = match self.kind.as_deref() {
+            Some("MOVIE") => "tmdb:movie",
+            _ => "tmdb:tv",
+        };
+        [
+            ("anilist", &self.anilist_id),
+            ("mal:anime", &self.mal_id),
+            ("anidb", &self.anidb_id),
+            ("kitsu", &self.kitsu_id),
+            ("tvdb", &self.thetvdb_id),
+            (tmdb, &self.themoviedb_id),
+        ]
+        .into_iter()
+        .filter_map(|(prefix, id)| id_str(id.as_ref()?).map(|id| format!("{prefix}:{id}")))
+        .collect()
+    }
+}
+
+fn id_str(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::String(s) if !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) => {
+            Some(s.clone())
+        }
+        _ => None,
+    }
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+pub struct AnimeListsClient {
+    client: Client,
+    retry: RetryPolicy,
+}
+
+impl AnimeListsClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            retry: RetryPolicy::from_env(),
+        }
+    }
+
+    pub fn fetch(&self) -> Result<Vec<AnimeMapping>, SearchError> {
+        let resp = send_with_retry(&self.retry, self.client.get(MAPPING_URL))?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("anime-lists returned {}", resp.status())));
+        }
+        decode_json("anime-lists", resp)
+    }
+}
+
+/// Adds every id known for the same anime to the items' `external_refs`.
+/// Returns the indices of the items that gained refs.
+pub fn expand_refs(items: &mut [MediaItem], mappings: &[AnimeMapping]) -> Vec<usize> {
+    let all_refs: Vec<Vec<String>> = mappings.iter().map(AnimeMapping::refs).collect();
+    let mut by_ref: HashMap<&str, usize> = HashMap::new();
+    for (idx, refs) in all_refs.iter().enumerate() {
+        for r in refs {
+            by_ref.entry(r.as_str()).or_insert(idx);
+        }
+    }
+
+    let mut changed = Vec::new();
+    for (i, item) in items.iter_mut().enumerate() {
+        let Some(&idx) = item.external_refs.iter().find_map(|r| by_ref.get(r.as_str())) else {
+            continue;
+        };
+        let before = item.external_refs.len();
+        item.external_refs.extend(all_refs[idx].iter().cloned());
+        if item.external_refs.len() != before {
+            changed.push(i);
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItemType, Progress, WatchStatus};
+
+    #[test]
+    fn test_expand_refs_links_known_ids() {
+        let mappings: Vec<AnimeMapping> = serde_json::from_str(
+            r#"[{"type": "TV", "anilist_id": 21, "mal_id": 21, "thetvdb_id": 81797,
+                 "themoviedb_id": 37854, "kitsu_id": "12", "anidb_id": null}]"#,
+        )
+        .unwrap();
+
+        let mut item = MediaItem::new(
+            "One Piece".into(),
+            MediaItemType::Series(Progress { current: 0, total: None }, WatchStatus::Watching),
+        );
+        item.external_refs.insert("mal:anime:21".into());
+        let mut items = vec![item];
+
+        assert_eq!(expand_refs(&mut items, &mappings), vec![0]);
+        for r in ["anilist:21", "tvdb:81797", "tmdb:tv:37854", "kitsu:12"] {
+            assert!(items[0].external_refs.contains(r), "missing {r}");
+        }
+        assert!(expand_refs(&mut items, &mappings).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::core::availability::{Download, MediaManager};
+use crate::core::search::SearchError;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+// ── Response types ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct AlternateTitle {
+    title: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Movie {
+    title: String,
+    original_title: Option<String>,
+    #[serde(default)]
+    alternate_titles: Vec<AlternateTitle>,
+    #[serde(default)]
+    tmdb_id: u32,
+    imdb_id: Option<String>,
+    #[serde(default)]
+    has_file: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Series {
+    title: String,
+    #[serde(default)]
+    alternate_titles: Vec<AlternateTitle>,
+    #[serde(default)]
+    tvdb_id: u32,
+    /// Only sent by Sonarr v4.
+    #[serde(default)]
+    tmdb_id: u32,
+    imdb_id: Option<String>,
+    statistics: Option<Statistics>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Statistics {
+    #[serde(default)]
+    episode_file_count: u32,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Sonarr,
+    Radarr,
+}
+
+/// A Sonarr or Radarr instance; both share the v3 API.
+pub struct ArrClient {
+    client: Client,
+    kind: Kind,
+    url: String,
+    api_key: String,
+    retry: RetryPolicy,
+}
+
+impl ArrClient {
+    /// Reads `SONARR_URL` and `SONARR_API_KEY`; None unless both are set.
+    pub fn sonarr_from_env() -> Option<Self> {
+        Self::from_env(Kind::Sonarr, "SONARR_URL", "SONARR_API_KEY")
+    }
+
+    /// Reads `RADARR_URL` and `RADARR_API_KEY`; None unless both are set.
+    pub fn radarr_from_env() -> Option<Self> {
+        Self::from_env(Kind::Radarr, "RADARR_URL", "RADARR_API_KEY")
+    }
+
+    fn from_env(kind: Kind, url: &str, api_key: &str) -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        Some(Self {
+            client: Client::new(),
+            kind,
+            url: var(url)?.trim_end_matches('/').to_string(),
+            api_key: var(api_key)?,
+            retry: RetryPolicy::from_env(),
+        })
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, SearchError> {
+        let request = self
+            .client
+            .get(format!("{}/api/v3/{path}", self.url))
+            .header("X-Api-Key", &self.api_key);
+        let resp = send_with_retry(&self.retry, request)?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("{} returned {}", self.name(), resp.status())));
+        }
+        decode_json(&self.name().to_lowercase(), resp)
+    }
+}
+
+impl MediaManager for ArrClient {
+    fn name(&self) -> &'static str {
+        match self.kind {
+            Kind::Sonarr => "Sonarr",
+            Kind::Radarr => "Radarr",
+        }
+    }
+
+    fn downloads(&self) -> Result<Vec<Download>, SearchError> {
+        Ok(match self.kind {
+            Kind::Radarr => self.get::<Vec<Movie>>("movie")?.into_iter().filter_map(movie_download).collect(),
+            Kind::Sonarr => self.get::<Vec<Series>>("series")?.into_iter().filter_map(series_download).collect(),
+        })
+    }
+}
+
+/// Ids of 0 or empty strings stand for unknown ones.
+fn refs<const N: usize>(ids: [(&str, String); N]) -> Vec<String> {
+    ids.into_iter()
+        .filter(|(_, id)| !id.is_empty() && id != "0")
+        .map(|(prefix, id)| format!("{prefix}:{id}"))
+        .collect()
+}
+
+fn movie_download(movie: Movie) -> Option<Download> {
+    if !movie.has_file {
+        return None;
+    }
+    let alt_titles = movie
+        .original_title
+        .into_iter()
+        .chain(movie.alternate_titles.into_iter().map(|a| a.title))
+        .collect();
+    Some(Download {
+        refs: refs([
+            ("tmdb:movie", movie.tmdb_id.to_string()),
+            ("imdb", movie.imdb_id.unwrap_or_default()),
+        ]),
+        title: movie.title,
+        alt_titles,
+        series: false,
+    })
+}
+
+fn series_download(series: Series) -> Option<Download> {
+    if series.statistics.is_none_or(|s| s.episode_file_count == 0) {
+        return None;
+    }
+    Some(Download {
+        refs: refs([
+            ("tvdb", series.tvdb_id.to_string()),
+            ("tmdb:tv", series.tmdb_id.to_string()),
+            ("imdb", series.imdb_id.unwrap_or_default()),
+        ]),
+        title: series.title,
+        alt_titles: series.alternate_titles.into_iter().map(|a| a.title).collect(),
+        series: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_downloaded_entries_count() {
+        let series: Vec<Series> = serde_json::from_str(
+            r#"[{"title": "Severance", "tvdbId": 371980, "tmdbId": 0, "imdbId": "tt11280740",
+                 "alternateTitles": [{"title": "Separación", "seasonNumber": -1}],
+                 "statistics": {"episodeFileCount": 3}},
+                {"title": "Shogun", "tvdbId": 1, "statistics": {"episodeFileCount": 0}}]"#,
+        )
+        .unwrap();
+        let downloads: Vec<Download> = series.into_iter().filter_map(series_download).collect();
+        assert_eq!(
+            downloads,
+            vec![Download {
+                title: "Severance".into(),
+                alt_titles: vec!["Separación".into()],
+                refs: vec!["tvdb:371980".into(), "imdb:tt11280740".into()],
+                series: true,
+            }]
+        );
+    }
+}
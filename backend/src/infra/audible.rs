@@ -0,0 +1,266 @@
+use crate::core::models::{format_minutes, ContentRating, MediaItemType, Progress, ReadStatus, ReadableKind};
+use crate::core::search::{
+    Credit, LocalizedTitles, MediaDetails, MediaSearchType, SearchError, SearchProvider, SearchResult,
+    SEARCH_PAGE_SIZE,
+};
+use crate::infra::anilist::plain_text;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+/// Product fields asked for on every request.
+const RESPONSE_GROUPS: &str = "contributors,media,product_attrs,product_desc,rating";
+
+// ── Response types ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct ProductsResponse {
+    #[serde(default)]
+    products: Vec<Product>,
+}
+
+#[derive(Deserialize)]
+struct ProductResponse {
+    product: Product,
+}
+
+#[derive(Deserialize)]
+struct Product {
+    asin: String,
+    title: Option<String>,
+    #[serde(default)]
+    authors: Vec<Contributor>,
+    #[serde(default)]
+    narrators: Vec<Contributor>,
+    runtime_length_min: Option<u32>,
+    /// `YYYY-MM-DD`.
+    release_date: Option<String>,
+    /// HTML.
+    publisher_summary: Option<String>,
+    /// Cover URLs by size; `500` is asked for.
+    #[serde(default)]
+    product_images: std::collections::HashMap<String, String>,
+    rating: Option<Rating>,
+    is_adult_product: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct Contributor {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Rating {
+    overall_distribution: Option<Distribution>,
+}
+
+#[derive(Deserialize)]
+struct Distribution {
+    /// 1.0-5.0
+    average_rating: Option<f64>,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+/// The Audible catalog: audiobooks with their running time and narrators.
+/// Needs no account; `AUDIBLE_REGION` picks the store (`us` by default).
+pub struct AudibleClient {
+    client: Client,
+    base_url: String,
+    retry: RetryPolicy,
+}
+
+/// The catalog API host of an Audible store.
+fn region_host(region: &str) -> Option<&'static str> {
+    Some(match region {
+        "us" => "api.audible.com",
+        "uk" => "api.audible.co.uk",
+        "de" => "api.audible.de",
+        "fr" => "api.audible.fr",
+        "it" => "api.audible.it",
+        "es" => "api.audible.es",
+        "ca" => "api.audible.ca",
+        "au" => "api.audible.com.au",
+        "in" => "api.audible.in",
+        "jp" => "api.audible.co.jp",
+        _ => return None,
+    })
+}
+
+impl AudibleClient {
+    pub fn new() -> Self {
+        let region = std::env::var("AUDIBLE_REGION").unwrap_or_default().trim().to_ascii_lowercase();
+        let host = region_host(&region).unwrap_or_else(|| {
+            if !region.is_empty() {
+                eprintln!("Note: unknown AUDIBLE_REGION '{region}', using the US store.");
+            }
+            "api.audible.com"
+        });
+        Self {
+            client: Client::new(),
+            base_url: format!("https://{host}/1.0/catalog/products"),
+            retry: RetryPolicy::from_env(),
+        }
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, url: &str, params: &[(&str, String)]) -> Result<Option<T>, SearchError> {
+        let request = self.client.get(url).query(params).query(&[("response_groups", RESPONSE_GROUPS), ("image_sizes", "500")]);
+        let resp = send_with_retry(&self.retry, request)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Audible returned {}", resp.status())));
+        }
+        decode_json("audible", resp).map(Some)
+    }
+
+    fn product(&self, asin: &str) -> Result<Option<Product>, SearchError> {
+        if asin.is_empty() || !asin.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Ok(None);
+        }
+        let data: Option<ProductResponse> = self.get(&format!("{}/{asin}", self.base_url), &[])?;
+        // Unknown ASINs come back as a product without a title.
+        Ok(data.map(|d| d.product).filter(|p| p.title.is_some()))
+    }
+}
+
+fn map_product(product: Product) -> Option<SearchResult> {
+    let title = product.title?;
+    let year = product.release_date.as_deref().and_then(|d| d.get(..4)).and_then(|y| y.parse::<i32>().ok());
+    let creator = product.authors.first().map(|a| a.name.clone());
+    let mut label = creator.clone().unwrap_or_else(|| "Unknown".into());
+    if let Some(narrator) = product.narrators.first() {
+        label.push_str(&format!(" · read by {}", narrator.name));
+    }
+    if let Some(minutes) = product.runtime_length_min {
+        label.push_str(&format!(" ({})", format_minutes(minutes)));
+    }
+
+    Some(SearchResult {
+        title,
+        media_type: MediaItemType::Readable(
+            ReadableKind::Audiobook,
+            Progress { current: 0, total: product.runtime_length_min.filter(|&m| m > 0) },
+            ReadStatus::PlanToRead,
+        ),
+        // average_rating: 1.0-5.0 → our global_score: 0-100
+        global_score: product
+            .rating
+            .and_then(|r| r.overall_distribution)
+            .and_then(|d| d.average_rating)
+            .filter(|&r| r > 0.0)
+            .map(|r| ((r.clamp(0.0, 5.0) / 5.0) * 100.0).round() as u8),
+        poster_url: product.product_images.get("500").cloned(),
+        external_refs: vec![format!("audible:{}", product.asin)],
+        external_id: Some(product.asin),
+        source: "audible",
+        format_label: label,
+        alt_titles: Vec::new(),
+        creator,
+        content_rating: product.is_adult_product.map(ContentRating::from_adult_flag),
+        titles: LocalizedTitles::default(),
+        year,
+    })
+}
+
+fn map_details(product: Product) -> MediaDetails {
+    let authors = product.authors.into_iter().map(|a| Credit { name: a.name, role: "Author".into() });
+    let narrators = product.narrators.into_iter().map(|n| Credit { name: n.name, role: "Narrator".into() });
+    MediaDetails {
+        overview: product.publisher_summary.as_deref().map(plain_text),
+        runtime_minutes: product.runtime_length_min.filter(|&m| m > 0),
+        staff: authors.chain(narrators).collect(),
+        ..MediaDetails::default()
+    }
+}
+
+impl SearchProvider for AudibleClient {
+    fn name(&self) -> &str {
+        "Audible"
+    }
+
+    fn source(&self) -> &'static str {
+        "audible"
+    }
+
+    fn supported_types(&self) -> &[MediaSearchType] {
+        &[MediaSearchType::Audiobook]
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        media_type: MediaSearchType,
+        page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Audiobook {
+            return Ok(Vec::new());
+        }
+        let params = [
+            ("keywords", query.to_string()),
+            ("num_results", SEARCH_PAGE_SIZE.to_string()),
+            ("page", page.max(1).to_string()),
+            ("products_sort_by", "Relevance".to_string()),
+        ];
+        let data: Option<ProductsResponse> = self.get(&self.base_url, &params)?;
+        Ok(data.map(|d| d.products).unwrap_or_default().into_iter().filter_map(map_product).collect())
+    }
+
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Audiobook {
+            return Ok(None);
+        }
+        Ok(self.product(external_id)?.and_then(map_product))
+    }
+
+    fn details(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        if media_type != MediaSearchType::Audiobook {
+            return Ok(None);
+        }
+        Ok(self.product(external_id)?.map(map_details))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_products_map_to_audiobooks() {
+        let body = r#"{"products": [{
+            "asin": "B002V1OF70",
+            "title": "Dune",
+            "authors": [{"asin": "B000APZGGS", "name": "Frank Herbert"}],
+            "narrators": [{"name": "Scott Brick"}, {"name": "Orlagh Cassidy"}],
+            "runtime_length_min": 1263,
+            "release_date": "2007-01-16",
+            "publisher_summary": "<p>Set on the desert planet Arrakis.</p>",
+            "product_images": {"500": "https://m.media-amazon.com/images/I/dune._SL500_.jpg"},
+            "rating": {"overall_distribution": {"average_rating": 4.6}},
+            "is_adult_product": false
+        }]}"#;
+        let product = || serde_json::from_str::<ProductsResponse>(body).unwrap().products.remove(0);
+        let dune = map_product(product()).unwrap();
+        let details = map_details(product());
+
+        assert_eq!(dune.format_label, "Frank Herbert · read by Scott Brick (21h 03m)");
+        assert_eq!((dune.year, dune.global_score), (Some(2007), Some(92)));
+        assert!(matches!(
+            dune.media_type,
+            MediaItemType::Readable(ReadableKind::Audiobook, Progress { current: 0, total: Some(1263) }, _)
+        ));
+        assert_eq!(dune.external_refs, ["audible:B002V1OF70"]);
+        assert_eq!(details.overview.as_deref(), Some("Set on the desert planet Arrakis."));
+        assert_eq!(details.staff.len(), 3);
+        assert_eq!(details.staff[1], Credit { name: "Scott Brick".into(), role: "Narrator".into() });
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use crate::core::storage::StorageError;
+use crate::infra::database::Database;
+
+const PREFIX: &str = "kars-";
+const SUFFIX: &str = ".db";
+const NAME_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// A snapshot file in the backup directory.
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Snapshots the database into `dir` as `kars-<timestamp>.db`.  The file
+/// only appears under its final name once it is complete.
+pub async fn create_backup(db: &Database, dir: &Path) -> Result<BackupInfo, StorageError> {
+    std::fs::create_dir_all(dir)?;
+    let created_at = Utc::now();
+    let name = format!("{PREFIX}{}{SUFFIX}", created_at.format(NAME_FORMAT));
+    let partial = dir.join(format!(".{name}.partial"));
+    let _ = std::fs::remove_file(&partial);
+
+    db.snapshot_to(&partial).await?;
+    let path = dir.join(&name);
+    std::fs::rename(&partial, &path)?;
+
+    Ok(BackupInfo {
+        size: std::fs::metadata(&path)?.len(),
+        name,
+        created_at,
+    })
+}
+
+/// Backups in `dir`, newest first.  A missing directory has none.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, StorageError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(created_at) = parse_name(&name) else { continue };
+        backups.push(BackupInfo {
+            size: entry.metadata()?.len(),
+            name,
+            created_at,
+        });
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// Deletes all but the `keep` newest backups and returns the removed ones.
+pub fn prune_backups(dir: &Path, keep: usize) -> Result<Vec<PathBuf>, StorageError> {
+    let mut removed = Vec::new();
+    for old in list_backups(dir)?.into_iter().skip(keep) {
+        let path = dir.join(&old.name);
+        std::fs::remove_file(&path)?;
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+fn parse_name(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name.strip_prefix(PREFIX)?.strip_suffix(SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, NAME_FORMAT)
+        .ok()
+        .map(|t| t.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_keeps_newest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "kars-20240101-000000.db",
+            "kars-20240103-000000.db",
+            "kars-20240102-000000.db",
+            ".kars-20240104-000000.db.partial",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        let removed = prune_backups(dir.path(), 2).unwrap();
+        assert_eq!(removed, vec![dir.path().join("kars-20240101-000000.db")]);
+
+        let names: Vec<String> = list_backups(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names, vec!["kars-20240103-000000.db", "kars-20240102-000000.db"]);
+        assert!(dir.path().join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::core::activity::{self, ACTIVITY_WINDOW_DAYS};
+use crate::core::api_types::{ApiDashboard, ApiEngagement, ApiJob, ApiNotification, ApiSession, ApiStats};
+use crate::core::schedule;
+use crate::core::storage::StorageError;
+use crate::infra::database::Database;
+
+/// How far ahead scheduled sessions are shown.
+const UPCOMING_DAYS: i64 = 7;
+const CONTINUE_LIMIT: usize = 5;
+const NOTIFICATION_LIMIT: u32 = 5;
+const JOB_LIMIT: u32 = 5;
+
+/// Gathers the dashboard as of `now`.  Serves `GET /api/dashboard`,
+/// `kars top` and the interactive CLI's startup line.  `airing_today` is
+/// looked up from the providers by the caller, which may skip it.
+pub async fn load(db: &Database, now: DateTime<Utc>, airing_today: Option<usize>) -> Result<ApiDashboard, StorageError> {
+    let items = db.load_all().await?;
+    let events = db
+        .progress_events_since(now - Duration::days(ACTIVITY_WINDOW_DAYS))
+        .await?;
+    let stats = db.stats().await?;
+    let scheduled_today = schedule::upcoming_sessions(&items, now, schedule::end_of_today(now) - now).len();
+
+    Ok(ApiDashboard {
+        generated_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        summary: summary(&stats, scheduled_today, airing_today),
+        stats,
+        upcoming: schedule::upcoming_sessions(&items, now, Duration::days(UPCOMING_DAYS))
+            .into_iter()
+            .filter_map(|item| ApiSession::new(item, now))
+            .collect(),
+        continue_watching: activity::currently_into(&items, &events, now, CONTINUE_LIMIT)
+            .iter()
+            .map(ApiEngagement::from)
+            .collect(),
+        notifications: db
+            .list_notifications(NOTIFICATION_LIMIT, true)
+            .await?
+            .iter()
+            .map(ApiNotification::from)
+            .collect(),
+        jobs: db.list_jobs(JOB_LIMIT).await?.iter().map(ApiJob::from).collect(),
+        airing_today,
+    })
+}
+
+/// "12 items · 3 in progress · 1 airing today · 4 new chapters · 1
+/// scheduled today · 5 planned"; the airing count is left out when it
+/// was not looked up.
+fn summary(stats: &ApiStats, scheduled_today: usize, airing_today: Option<usize>) -> String {
+    if stats.total == 0 {
+        return "Archive is empty.".into();
+    }
+    let mut parts = vec![
+        format!("{} items", stats.total),
+        format!("{} in progress", stats.watching),
+    ];
+    if let Some(airing) = airing_today {
+        parts.push(format!("{airing} airing today"));
+    }
+    parts.push(format!("{} new chapters", stats.chapters_behind));
+    parts.push(format!("{scheduled_today} scheduled today"));
+    parts.push(format!("{} planned", stats.plan_to_watch));
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind, WatchStatus};
+
+    #[tokio::test]
+    async fn test_summary_counts_today_and_new_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let now = Utc::now();
+        let midnight = schedule::end_of_today(now);
+        assert_eq!(load(&db, now, Some(0)).await.unwrap().summary, "Archive is empty.");
+
+        let mut manga = MediaItem::new(
+            "Berserk".into(),
+            MediaItemType::Readable(ReadableKind::Manga, Progress { current: 370, total: None }, ReadStatus::Reading),
+        );
+        manga.latest_available = Some(374);
+        let mut tonight = MediaItem::new(
+            "Frieren".into(),
+            MediaItemType::Series(Progress { current: 3, total: Some(28) }, WatchStatus::Watching),
+        );
+        tonight.scheduled_for = Some(now + (midnight - now) / 2);
+        let mut tomorrow = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        tomorrow.scheduled_for = Some(midnight + Duration::hours(1));
+        for mut item in [manga, tonight, tomorrow] {
+            db.upsert_item(&mut item).await.unwrap();
+        }
+
+        let d = load(&db, now, Some(1)).await.unwrap();
+        assert_eq!(
+            d.summary,
+            "3 items · 2 in progress · 1 airing today · 4 new chapters · 1 scheduled today · 1 planned"
+        );
+        assert_eq!(d.airing_today, Some(1));
+        assert!(!load(&db, now, None).await.unwrap().summary.contains("airing"));
+    }
+}
@@ -1,18 +1,296 @@
+use crate::core::activity::ProgressEvent;
+use crate::core::api_types::{ApiExtendedStats, ApiStats, ApiTagCount};
+use crate::core::archive::{
+    self, Activity, Archive, ArchivedEpisode, ArchivedHistory, ArchivedProgress, ARCHIVE_VERSION,
+};
+use crate::core::focus::FocusPin;
+use crate::core::ranking::{self, Rating};
+use crate::core::goals::Goal;
+use crate::core::history::{self, HistoryChange, HistoryEntry, HistoryKind};
+use crate::core::import::MergePlan;
+use crate::core::outbox::{QueuedOp, QueuedWrite};
 use crate::core::models::{
-    MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind, WatchStatus,
+    ContentRating, EpisodeRecord, MediaItem, MediaItemType, Progress, ReadStatus, ReadableKind,
+    ScoreScale, TitleLanguage, WatchStatus,
 };
+use crate::core::jobs::{Job, JobKind, JobStatus, RollbackConflict};
+use crate::core::query::{Sql, SqlValue};
+use crate::core::refresh::ItemChanges;
+use crate::core::schedule::{NewNotification, Notification, NotificationKind};
+use crate::core::search::{ProviderDiagnostic, Relation};
+use crate::core::settings::Settings;
+use crate::core::stats::{self, TimeSpent};
+use crate::core::telemetry::Usage;
+use crate::core::trakt::TraktAuth;
+use crate::core::write_through::{AniListAuth, QueuedUpdate};
+use crate::core::webhooks::Webhook;
 use crate::core::storage::{StorageError, StorageProvider};
+use chrono::{DateTime, SecondsFormat, Utc};
 use libsql::{Builder, Connection};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::runtime::Runtime;
 use uuid::Uuid;
 
+/// Schema migrations, applied in order.  Entry N brings the schema to
+/// version N + 1; the applied version is tracked in `schema_version`.
+/// Never edit an entry that has shipped — append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS media_items (
+        id            TEXT PRIMARY KEY,
+        title         TEXT NOT NULL,
+        media_type    TEXT NOT NULL,
+        readable_kind TEXT,
+        watch_status  TEXT,
+        read_status   TEXT,
+        progress_cur  INTEGER NOT NULL DEFAULT 0,
+        progress_tot  INTEGER,
+        score         INTEGER,
+        global_score  INTEGER,
+        external_id   INTEGER,
+        poster_url    TEXT,
+        source        TEXT,
+        tags          TEXT NOT NULL DEFAULT '[]'
+    )",
+    "ALTER TABLE media_items ADD COLUMN repeat_count INTEGER NOT NULL DEFAULT 0",
+    "CREATE TABLE IF NOT EXISTS progress_history (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id     TEXT NOT NULL,
+        progress    INTEGER NOT NULL,
+        delta       INTEGER NOT NULL,
+        recorded_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_progress_history_item ON progress_history(item_id);
+    CREATE INDEX IF NOT EXISTS idx_progress_history_time ON progress_history(recorded_at)",
+    "ALTER TABLE media_items ADD COLUMN notes TEXT;
+    ALTER TABLE media_items ADD COLUMN notes_spoiler INTEGER NOT NULL DEFAULT 0",
+    "CREATE TABLE IF NOT EXISTS episodes (
+        item_id    TEXT NOT NULL,
+        episode    INTEGER NOT NULL,
+        watched    INTEGER NOT NULL DEFAULT 0,
+        watched_at TEXT,
+        PRIMARY KEY (item_id, episode)
+    )",
+    "CREATE TABLE IF NOT EXISTS history (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        item_id     TEXT NOT NULL,
+        title       TEXT NOT NULL,
+        event       TEXT NOT NULL,
+        old_value   TEXT,
+        new_value   TEXT,
+        recorded_at TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_history_item ON history(item_id);
+    CREATE INDEX IF NOT EXISTS idx_history_time ON history(recorded_at)",
+    "CREATE TABLE IF NOT EXISTS diagnostics (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        provider    TEXT NOT NULL,
+        message     TEXT NOT NULL,
+        snippet     TEXT NOT NULL,
+        recorded_at TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS settings (
+        key   TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )",
+    "ALTER TABLE media_items ADD COLUMN alt_titles TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE media_items ADD COLUMN external_refs TEXT NOT NULL DEFAULT '[]'",
+    "CREATE INDEX IF NOT EXISTS idx_media_source_ext ON media_items(source, external_id)",
+    "CREATE TABLE IF NOT EXISTS external_refs (
+        source  TEXT NOT NULL,
+        ref_id  TEXT NOT NULL,
+        item_id TEXT NOT NULL,
+        PRIMARY KEY (source, ref_id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_external_refs_item ON external_refs(item_id);
+    INSERT OR IGNORE INTO external_refs (source, ref_id, item_id)
+        SELECT substr(j.value, 1, instr(j.value, ':') - 1),
+               substr(j.value, instr(j.value, ':') + 1),
+               m.id
+        FROM media_items m, json_each(m.external_refs) j
+        WHERE instr(j.value, ':') > 0",
+    "ALTER TABLE media_items ADD COLUMN scheduled_for TEXT;
+    CREATE TABLE IF NOT EXISTS notifications (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        key        TEXT NOT NULL UNIQUE,
+        item_id    TEXT,
+        kind       TEXT NOT NULL,
+        message    TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        read       INTEGER NOT NULL DEFAULT 0
+    )",
+    "CREATE TABLE IF NOT EXISTS jobs (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        kind        TEXT NOT NULL,
+        status      TEXT NOT NULL,
+        dry_run     INTEGER NOT NULL DEFAULT 0,
+        created_at  TEXT NOT NULL,
+        finished_at TEXT,
+        error       TEXT,
+        items       TEXT NOT NULL DEFAULT '[]'
+    )",
+    "ALTER TABLE history ADD COLUMN job_id INTEGER REFERENCES jobs(id);
+    CREATE INDEX IF NOT EXISTS idx_history_job ON history(job_id);
+    CREATE TABLE IF NOT EXISTS job_items (
+        job_id  INTEGER NOT NULL REFERENCES jobs(id),
+        item_id TEXT NOT NULL,
+        before  TEXT,
+        PRIMARY KEY (job_id, item_id)
+    )",
+    "CREATE TABLE IF NOT EXISTS focus_pins (
+        item_id   TEXT PRIMARY KEY,
+        pinned_at TEXT NOT NULL
+    )",
+    "ALTER TABLE media_items ADD COLUMN overview TEXT;
+    ALTER TABLE media_items ADD COLUMN genres TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE media_items ADD COLUMN runtime_minutes INTEGER;
+    ALTER TABLE media_items ADD COLUMN season_count INTEGER",
+    "CREATE TABLE IF NOT EXISTS webhooks (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        url        TEXT NOT NULL,
+        secret     TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS relations (
+        item_id     TEXT NOT NULL,
+        kind        TEXT NOT NULL,
+        source      TEXT NOT NULL,
+        external_id INTEGER NOT NULL,
+        title       TEXT NOT NULL,
+        format      TEXT,
+        PRIMARY KEY (item_id, source, external_id)
+    )",
+    "ALTER TABLE media_items ADD COLUMN franchise TEXT;
+    CREATE INDEX IF NOT EXISTS idx_media_items_franchise ON media_items(franchise)",
+    "ALTER TABLE media_items ADD COLUMN custom_fields TEXT NOT NULL DEFAULT '{}'",
+    "ALTER TABLE media_items ADD COLUMN volume_cur INTEGER;
+    ALTER TABLE media_items ADD COLUMN volume_tot INTEGER;
+    ALTER TABLE media_items ADD COLUMN prefer_volumes INTEGER NOT NULL DEFAULT 0",
+    "CREATE TABLE IF NOT EXISTS goals (
+        id         INTEGER PRIMARY KEY AUTOINCREMENT,
+        year       INTEGER NOT NULL,
+        media_type TEXT,
+        target     INTEGER NOT NULL
+    )",
+    "ALTER TABLE media_items ADD COLUMN created_at TEXT;
+    ALTER TABLE media_items ADD COLUMN updated_at TEXT;
+    UPDATE media_items SET
+        created_at = (SELECT MIN(recorded_at) FROM history WHERE item_id = media_items.id),
+        updated_at = (SELECT MAX(recorded_at) FROM history WHERE item_id = media_items.id);
+    CREATE INDEX IF NOT EXISTS idx_media_items_updated ON media_items(updated_at)",
+    "ALTER TABLE media_items ADD COLUMN creator TEXT;
+    CREATE INDEX IF NOT EXISTS idx_media_items_creator ON media_items(creator COLLATE NOCASE)",
+    "ALTER TABLE media_items ADD COLUMN latest_available INTEGER",
+    // External ids become text to hold MangaDex UUIDs and Open Library work
+    // keys, whose numbers were stored without the `OL`/`W` around them.
+    "DROP INDEX IF EXISTS idx_media_source_ext;
+    ALTER TABLE media_items RENAME COLUMN external_id TO external_id_num;
+    ALTER TABLE media_items ADD COLUMN external_id TEXT;
+    UPDATE media_items SET external_id = CASE source
+        WHEN 'openlibrary' THEN 'OL' || external_id_num || 'W'
+        ELSE CAST(external_id_num AS TEXT) END
+    WHERE external_id_num IS NOT NULL;
+    ALTER TABLE media_items DROP COLUMN external_id_num;
+    CREATE INDEX IF NOT EXISTS idx_media_source_ext ON media_items(source, external_id);
+    CREATE TABLE relations_text (
+        item_id     TEXT NOT NULL,
+        kind        TEXT NOT NULL,
+        source      TEXT NOT NULL,
+        external_id TEXT NOT NULL,
+        title       TEXT NOT NULL,
+        format      TEXT,
+        PRIMARY KEY (item_id, source, external_id)
+    );
+    INSERT INTO relations_text
+        SELECT item_id, kind, source, CAST(external_id AS TEXT), title, format FROM relations ORDER BY rowid;
+    DROP TABLE relations;
+    ALTER TABLE relations_text RENAME TO relations",
+    "CREATE TABLE IF NOT EXISTS trakt_auth (
+        id             INTEGER PRIMARY KEY CHECK (id = 1),
+        access_token   TEXT NOT NULL,
+        refresh_token  TEXT NOT NULL,
+        expires_at     TEXT NOT NULL,
+        connected_at   TEXT NOT NULL,
+        last_synced_at TEXT
+    )",
+    "CREATE TABLE IF NOT EXISTS anilist_auth (
+        id             INTEGER PRIMARY KEY CHECK (id = 1),
+        token          TEXT NOT NULL,
+        user_id        INTEGER NOT NULL,
+        user_name      TEXT NOT NULL,
+        connected_at   TEXT NOT NULL,
+        history_cursor INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS anilist_queue (
+        item_id    TEXT PRIMARY KEY,
+        queued_at  TEXT NOT NULL,
+        attempts   INTEGER NOT NULL DEFAULT 0,
+        last_error TEXT
+    )",
+    "ALTER TABLE media_items ADD COLUMN downloaded INTEGER NOT NULL DEFAULT 0",
+    // For filtered lists: `type:` and `status:` searches, and results in
+    // title order.  `status:` matches either status column, which SQLite
+    // serves from one index each.
+    "CREATE INDEX IF NOT EXISTS idx_media_items_title ON media_items(title COLLATE NOCASE);
+    CREATE INDEX IF NOT EXISTS idx_media_items_type ON media_items(media_type);
+    CREATE INDEX IF NOT EXISTS idx_media_items_watch_status ON media_items(watch_status);
+    CREATE INDEX IF NOT EXISTS idx_media_items_read_status ON media_items(read_status);
+    CREATE INDEX IF NOT EXISTS idx_media_source_ext ON media_items(source, external_id)",
+    "ALTER TABLE media_items ADD COLUMN content_rating TEXT",
+    "ALTER TABLE media_items ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0",
+    // Favorites used to be a "favorite" tag.
+    "ALTER TABLE media_items ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
+    UPDATE media_items SET
+        favorite = 1,
+        tags = (SELECT json_group_array(value) FROM json_each(media_items.tags) WHERE value <> 'favorite')
+    WHERE EXISTS (SELECT 1 FROM json_each(media_items.tags) WHERE value = 'favorite')",
+    "CREATE TABLE IF NOT EXISTS rankings (
+        item_id     TEXT PRIMARY KEY,
+        rating      REAL NOT NULL,
+        comparisons INTEGER NOT NULL
+    )",
+    // The last audit log entry sent to each webhook; existing ones start
+    // from the newest entry, as they did after every restart before.
+    "ALTER TABLE webhooks ADD COLUMN delivered_through INTEGER NOT NULL DEFAULT 0;
+    UPDATE webhooks SET delivered_through = (SELECT COALESCE(MAX(id), 0) FROM history)",
+];
+
+/// Only the most recent diagnostics rows are kept.
+const MAX_DIAGNOSTICS: i64 = 200;
+
+/// Most connections kept open for reads.
+const MAX_READERS: usize = 8;
+
+/// How long a local connection waits for another one's lock (the CLI's,
+/// say) before giving up with `SQLITE_BUSY`.
+const BUSY_TIMEOUT_MS: u32 = 5_000;
+
+/// How long `Database::reachable` waits for an answer.
+const PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Column list shared by every `SELECT` that feeds `row_to_media_item`.
+const ITEM_COLUMNS: &str = "id, title, media_type, readable_kind, watch_status, read_status,
+     progress_cur, progress_tot, score, global_score,
+     external_id, poster_url, source, tags, repeat_count, notes, notes_spoiler,
+     alt_titles, external_refs, scheduled_for, overview, genres, runtime_minutes, season_count,
+     franchise, custom_fields, volume_cur, volume_tot, prefer_volumes, created_at, updated_at,
+     creator, latest_available, downloaded, content_rating, hidden, favorite";
+
 // ═══════════════════════════════════════════════════════════════
 // Database — async-only, no runtime.  Used by the web server.
 // ═══════════════════════════════════════════════════════════════
 
+/// Writes go through `conn`; reads take one of `readers` in turn, so
+/// requests holding a read lock on the web state run their queries side
+/// by side instead of queueing on one connection.  Callers must not read
+/// and write at the same time: the server keeps the database behind an
+/// `RwLock` and only writes under its write guard.
 pub struct Database {
     conn: Connection,
+    readers: Vec<Connection>,
+    next_reader: AtomicUsize,
+    /// Connected to Turso rather than a local file.
+    remote: bool,
 }
 
 impl Database {
@@ -26,13 +304,7 @@ impl Database {
             .build()
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        let conn = db
-            .connect()
-            .map_err(|e| StorageError::Database(e.to_string()))?;
-
-        let storage = Self { conn };
-        storage.run_migrations().await?;
-        Ok(storage)
+        Self::open(db, false).await
     }
 
     /// Connect to a remote Turso database (async).
@@ -41,38 +313,100 @@ impl Database {
             .build()
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        let conn = db
-            .connect()
-            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Self::open(db, true).await
+    }
 
-        let storage = Self { conn };
+    /// Migrates, then opens one reader per core (at most `MAX_READERS`).
+    /// Local files switch to WAL, so reads and a write (from the CLI, say)
+    /// do not block each other.
+    async fn open(db: libsql::Database, remote: bool) -> Result<Self, StorageError> {
+        let connect = || async {
+            let conn = db.connect().map_err(|e| StorageError::Database(e.to_string()))?;
+            if !remote {
+                pragma(&conn, &format!("busy_timeout = {BUSY_TIMEOUT_MS}")).await?;
+            }
+            Ok::<_, StorageError>(conn)
+        };
+        let conn = connect().await?;
+        if !remote {
+            pragma(&conn, "journal_mode = WAL").await?;
+        }
+        let mut storage = Self { conn, readers: Vec::new(), next_reader: AtomicUsize::new(0), remote };
         storage.run_migrations().await?;
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        for _ in 0..cores.min(MAX_READERS) {
+            storage.readers.push(connect().await?);
+        }
         Ok(storage)
     }
 
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+
+    /// Whether the database answers within a few seconds; only ever false
+    /// for Turso.
+    pub async fn reachable(&self) -> bool {
+        let ping = self.conn.query("SELECT 1", ());
+        matches!(tokio::time::timeout(PING_TIMEOUT, ping).await, Ok(Ok(_)))
+    }
+
+    /// A connection for queries that only read.
+    fn reader(&self) -> &Connection {
+        let next = self.next_reader.fetch_add(1, Ordering::Relaxed);
+        self.readers.get(next % self.readers.len().max(1)).unwrap_or(&self.conn)
+    }
+
     async fn run_migrations(&self) -> Result<(), StorageError> {
         self.conn
             .execute(
-                "CREATE TABLE IF NOT EXISTS media_items (
-                    id            TEXT PRIMARY KEY,
-                    title         TEXT NOT NULL,
-                    media_type    TEXT NOT NULL,
-                    readable_kind TEXT,
-                    watch_status  TEXT,
-                    read_status   TEXT,
-                    progress_cur  INTEGER NOT NULL DEFAULT 0,
-                    progress_tot  INTEGER,
-                    score         INTEGER,
-                    global_score  INTEGER,
-                    external_id   INTEGER,
-                    poster_url    TEXT,
-                    source        TEXT,
-                    tags          TEXT NOT NULL DEFAULT '[]'
-                )",
+                "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)",
                 (),
             )
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut rows = self
+            .conn
+            .query("SELECT version FROM schema_version", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let current = match rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            Some(row) => row.get::<i64>(0).unwrap_or(0) as usize,
+            None => {
+                self.conn
+                    .execute("INSERT INTO schema_version (version) VALUES (0)", ())
+                    .await
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+                0
+            }
+        };
+        // An open statement would make SQLite refuse to drop tables.
+        drop(rows);
+
+        for (idx, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+            let tx = self
+                .conn
+                .transaction()
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            tx.execute_batch(sql)
+                .await
+                .map_err(|e| StorageError::Database(format!("migration {}: {e}", idx + 1)))?;
+            tx.execute(
+                "UPDATE schema_version SET version = ?1",
+                libsql::params![(idx + 1) as i64],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+            tx.commit()
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
         Ok(())
     }
 
@@ -80,8 +414,8 @@ impl Database {
 
     pub async fn load_all(&self) -> Result<Vec<MediaItem>, StorageError> {
         let mut rows = self
-            .conn
-            .query("SELECT * FROM media_items ORDER BY title", ())
+            .reader()
+            .query(&format!("SELECT {ITEM_COLUMNS} FROM media_items ORDER BY title"), ())
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
@@ -103,95 +437,2166 @@ impl Database {
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        tx.execute("DELETE FROM media_items", ())
+        let mut previous: HashMap<Uuid, MediaItem> = HashMap::new();
+        let mut rows = tx
+            .query(&format!("SELECT {ITEM_COLUMNS} FROM media_items"), ())
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let item = row_to_media_item(&row)?;
+            previous.insert(item.id, item);
+        }
+
+        for table in ["media_items", "external_refs"] {
+            tx.execute(&format!("DELETE FROM {table}"), ())
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
 
         for item in items {
-            insert_item_in_tx(&tx, item).await?;
+            let old = previous.remove(&item.id);
+            record_changes(&tx, old.as_ref(), item, None).await?;
+            write_item(&tx, old.as_ref(), item).await?;
+        }
+        for gone in previous.values() {
+            record_deletion(&tx, gone).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Writes a consistent copy of the whole database to a new SQLite file
+    /// at `dest`.  Local databases are copied with `VACUUM INTO`; a Turso
+    /// database is materialized table by table inside one read transaction.
+    pub async fn snapshot_to(&self, dest: &std::path::Path) -> Result<(), StorageError> {
+        let dest_str = dest
+            .to_str()
+            .ok_or_else(|| StorageError::Database("Snapshot path is not valid UTF-8".into()))?;
+        if !self.remote {
+            self.conn
+                .execute("VACUUM INTO ?1", libsql::params![dest_str])
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            return Ok(());
+        }
+
+        let target = Builder::new_local(dest_str)
+            .build()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .connect()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        // Tables before the indexes that reference them.
+        let mut rows = tx
+            .query(
+                "SELECT type, name, sql FROM sqlite_master
+                 WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+                 ORDER BY type = 'table' DESC",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut tables = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let kind: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let name: String = row.get(1).map_err(|e| StorageError::Database(e.to_string()))?;
+            let sql: String = row.get(2).map_err(|e| StorageError::Database(e.to_string()))?;
+            target
+                .execute(&sql, ())
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            if kind == "table" {
+                tables.push(name);
+            }
+        }
+
+        for table in &tables {
+            let mut rows = tx
+                .query(&format!("SELECT * FROM \"{table}\""), ())
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let columns = rows.column_count();
+            let placeholders = vec!["?"; columns as usize].join(", ");
+            let insert = format!("INSERT INTO \"{table}\" VALUES ({placeholders})");
+            while let Some(row) = rows
+                .next()
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?
+            {
+                let values = (0..columns)
+                    .map(|i| row.get_value(i))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+                target
+                    .execute(&insert, libsql::params::Params::Positional(values))
+                    .await
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ── Granular operations (used by web API) ────────────────
+
+    pub async fn get_item(&self, id: Uuid) -> Result<Option<MediaItem>, StorageError> {
+        fetch_item(self.reader(), id).await
+    }
+
+    /// The items with these ids, in one query and in the order asked for.
+    /// Unknown ids are left out.
+    pub async fn get_items(&self, ids: &[Uuid]) -> Result<Vec<MediaItem>, StorageError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = (1..=ids.len()).map(|i| format!("?{i}")).collect::<Vec<_>>().join(", ");
+        let mut rows = self
+            .reader()
+            .query(
+                &format!("SELECT {ITEM_COLUMNS} FROM media_items WHERE id IN ({placeholders})"),
+                libsql::params_from_iter(ids.iter().map(Uuid::to_string).collect::<Vec<_>>()),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut found = HashMap::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let item = row_to_media_item(&row)?;
+            found.insert(item.id, item);
+        }
+        Ok(ids.iter().filter_map(|id| found.remove(id)).collect())
+    }
+
+    /// The item imported from the same provider entry, if any.
+    pub async fn find_by_external_id(
+        &self,
+        source: &str,
+        external_id: &str,
+    ) -> Result<Option<MediaItem>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                &format!(
+                    "SELECT {ITEM_COLUMNS} FROM media_items
+                     WHERE source = ?1 AND external_id = ?2 LIMIT 1"
+                ),
+                libsql::params![source, external_id],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        match rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            Some(row) => Ok(Some(row_to_media_item(&row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The item carrying the external reference `source:ref_id`.
+    pub async fn resolve_external_ref(
+        &self,
+        source: &str,
+        ref_id: &str,
+    ) -> Result<Option<MediaItem>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id FROM external_refs WHERE source = ?1 AND ref_id = ?2",
+                libsql::params![source, ref_id],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        let item_id: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+        let id = Uuid::parse_str(&item_id)
+            .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?;
+        fetch_item(self.reader(), id).await
+    }
+
+    /// Stores `item` and sets the fields the server maintains (see
+    /// `ServerFields`) to the stored values.
+    pub async fn upsert_item(&self, item: &mut MediaItem) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let old = fetch_item(&tx, item.id).await?;
+        record_changes(&tx, old.as_ref(), item, None).await?;
+        write_item(&tx, old.as_ref(), item).await?.apply(item);
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Stores `keep` (already merged in memory) and removes `duplicate` in
+    /// one transaction.  The duplicate's watched episodes, progress log and
+    /// focus pin move over to `keep`, whose server-maintained fields are
+    /// set to the stored values.
+    pub async fn merge_items(
+        &self,
+        keep: &mut MediaItem,
+        duplicate: &MediaItem,
+    ) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let old = fetch_item(&tx, keep.id).await?;
+        record_changes(&tx, old.as_ref(), keep, None).await?;
+        write_item(&tx, old.as_ref(), keep).await?.apply(keep);
+
+        let (keep_id, dup_id) = (keep.id.to_string(), duplicate.id.to_string());
+        tx.execute(
+            "INSERT INTO episodes (item_id, episode, watched, watched_at)
+             SELECT ?1, episode, watched, watched_at FROM episodes WHERE item_id = ?2
+             ON CONFLICT (item_id, episode) DO UPDATE SET
+                 watched = MAX(watched, excluded.watched),
+                 watched_at = COALESCE(watched_at, excluded.watched_at)",
+            libsql::params![keep_id.clone(), dup_id.clone()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute(
+            "UPDATE progress_history SET item_id = ?1 WHERE item_id = ?2",
+            libsql::params![keep_id.clone(), dup_id.clone()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute(
+            "INSERT OR IGNORE INTO focus_pins (item_id, pinned_at)
+             SELECT ?1, pinned_at FROM focus_pins WHERE item_id = ?2",
+            libsql::params![keep_id, dup_id.clone()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let change = HistoryChange {
+            kind: HistoryKind::Merged,
+            old_value: None,
+            new_value: Some(keep.title.clone()),
+        };
+        insert_history(&tx, duplicate, &change, &format_timestamp(Utc::now()), None).await?;
+
+        for table in ["media_items", "episodes", "external_refs", "focus_pins", "relations", "rankings"] {
+            let column = if table == "media_items" { "id" } else { "item_id" };
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE {column} = ?1"),
+                libsql::params![dup_id.clone()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
         }
 
         tx.commit()
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        Ok(())
-    }
-
-    // ── Granular operations (used by web API) ────────────────
+        Ok(())
+    }
+
+    pub async fn delete_item(&self, id: Uuid) -> Result<bool, StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let Some(item) = fetch_item(&tx, id).await? else {
+            return Ok(false);
+        };
+        record_deletion(&tx, &item).await?;
+        delete_item_rows(&tx, id).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// Library statistics, counted per type and status in SQL so that no
+    /// item has to be loaded.
+    pub async fn stats(&self) -> Result<ApiStats, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT media_type, media_type = 'series' AND source IS 'anilist',
+                        watch_status, read_status,
+                        COUNT(*), SUM(repeat_count > 0), SUM(repeat_count)
+                 FROM media_items
+                 GROUP BY 1, 2, 3, 4",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut stats = ApiStats::default();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let media_type: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let (api_type, status) = match media_type.as_str() {
+                "movie" => ("movie", watch_str(&parse_watch_status(opt_text(&row, 2).as_deref()))),
+                "series" => {
+                    let api_type = if row.get::<i64>(1).unwrap_or(0) != 0 { "anime" } else { "series" };
+                    (api_type, watch_str(&parse_watch_status(opt_text(&row, 2).as_deref())))
+                }
+                "readable" => ("readable", read_str(&parse_read_status(opt_text(&row, 3).as_deref()))),
+                other => {
+                    return Err(StorageError::Corruption(format!("Unknown media_type: {other}")));
+                }
+            };
+            stats.add(
+                api_type,
+                status,
+                row.get::<i64>(4).unwrap_or(0) as usize,
+                row.get::<i64>(5).unwrap_or(0) as usize,
+                row.get::<i64>(6).unwrap_or(0) as u32,
+            );
+        }
+
+        // Same rule as `schedule::chapters_behind`.
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT COUNT(*), SUM(latest_available - progress_cur) FROM media_items
+                 WHERE media_type = 'readable' AND read_status = 'reading'
+                   AND latest_available > progress_cur",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            stats.behind_on_chapters = row.get::<i64>(0).unwrap_or(0) as usize;
+            stats.chapters_behind = opt_int(&row, 1).unwrap_or(0) as u32;
+        }
+        Ok(stats)
+    }
+
+    /// Stores the newest chapters found by the chapter check.  Only that
+    /// column changes, so neither the audit log nor `updated_at` moves.
+    pub async fn set_latest_chapters(&self, latest: &[(Uuid, u32)]) -> Result<(), StorageError> {
+        for (id, chapter) in latest {
+            self.conn
+                .execute(
+                    "UPDATE media_items SET latest_available = ?2 WHERE id = ?1",
+                    libsql::params![id.to_string(), *chapter as i64],
+                )
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Stores which items the download check found on disk (`true`) or
+    /// no longer found.  Like `set_latest_chapters`, it leaves the audit
+    /// log and `updated_at` alone.
+    pub async fn set_downloaded(&self, changes: &[(Uuid, bool)]) -> Result<(), StorageError> {
+        for (id, downloaded) in changes {
+            self.conn
+                .execute(
+                    "UPDATE media_items SET downloaded = ?2 WHERE id = ?1",
+                    libsql::params![id.to_string(), *downloaded as i64],
+                )
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Score distribution, tag and kind counts and how much has been
+    /// watched or read, aggregated in SQL.
+    pub async fn extended_stats(&self) -> Result<ApiExtendedStats, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT score, COUNT(*) FROM media_items WHERE score IS NOT NULL
+                 GROUP BY score ORDER BY score",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut scores = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            scores.push((row.get::<i64>(0).unwrap_or(0) as u8, row.get::<i64>(1).unwrap_or(0) as usize));
+        }
+        let mut stats = ApiExtendedStats::new(&stats::score_summary(&scores));
+
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT j.value, COUNT(*) FROM media_items m, json_each(m.tags) j
+                 GROUP BY j.value ORDER BY 2 DESC, 1",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            stats.tags.push(ApiTagCount {
+                tag: row.get::<String>(0).unwrap_or_default(),
+                count: row.get::<i64>(1).unwrap_or(0) as usize,
+            });
+        }
+
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT readable_kind, COUNT(*) FROM media_items WHERE media_type = 'readable'
+                 GROUP BY readable_kind",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let kind = readable_str(&parse_readable_kind(opt_text(&row, 0).as_deref()));
+            *stats.readable_kinds.entry(kind.to_string()).or_default() +=
+                row.get::<i64>(1).unwrap_or(0) as usize;
+        }
+
+        // Earlier runs of a rewatched item count in full; only their total
+        // is known.
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT media_type,
+                        SUM(progress_cur + repeat_count * COALESCE(progress_tot, 0)),
+                        SUM((watch_status IS 'completed') + repeat_count)
+                 FROM media_items GROUP BY media_type",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let media_type: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let units = row.get::<i64>(1).unwrap_or(0) as u64;
+            match media_type.as_str() {
+                "movie" => stats.movies_watched = row.get::<i64>(2).unwrap_or(0) as u64,
+                "series" => stats.episodes_watched = units,
+                _ => stats.chapters_read += units,
+            }
+        }
+
+        // Time spent: units are movie viewings or progress, counted like
+        // above, and each unit of a movie or series runs its runtime.
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT media_type, anilist, readable_kind, SUM(units),
+                        SUM(units * COALESCE(runtime_minutes, 0)),
+                        SUM(units > 0 AND runtime_minutes IS NULL)
+                 FROM (SELECT media_type, source IS 'anilist' AS anilist, readable_kind, runtime_minutes,
+                              CASE media_type
+                                  WHEN 'movie' THEN (watch_status IS 'completed') + repeat_count
+                                  ELSE progress_cur + repeat_count * COALESCE(progress_tot, 0)
+                              END AS units
+                       FROM media_items)
+                 GROUP BY 1, 2, 3",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut total = TimeSpent::default();
+        let mut by_type: BTreeMap<String, TimeSpent> = BTreeMap::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let media_type = api_media_type(&row, 0)?;
+            let time = TimeSpent::of(
+                &media_type,
+                row.get::<i64>(3).unwrap_or(0) as u64,
+                row.get::<i64>(4).unwrap_or(0) as u64,
+            );
+            if matches!(media_type.as_str(), "movie" | "series" | "anime") {
+                stats.without_runtime += row.get::<i64>(5).unwrap_or(0) as usize;
+            }
+            total.add(time);
+            if !time.is_empty() {
+                by_type.entry(media_type).or_default().add(time);
+            }
+        }
+        stats.time_spent = total.into();
+        stats.time_by_media_type = by_type.into_iter().map(|(t, time)| (t, time.into())).collect();
+
+        // Per year: progress logged on series and readables, and movies
+        // finished by hand.
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT substr(p.recorded_at, 1, 4), m.media_type, m.source IS 'anilist', m.readable_kind,
+                        SUM(p.delta), SUM(p.delta * COALESCE(m.runtime_minutes, 0))
+                 FROM progress_history p JOIN media_items m ON m.id = p.item_id
+                 WHERE p.delta > 0 AND m.media_type != 'movie'
+                 GROUP BY 1, 2, 3, 4
+                 UNION ALL
+                 SELECT substr(h.recorded_at, 1, 4), m.media_type, 0, NULL,
+                        COUNT(*), SUM(COALESCE(m.runtime_minutes, 0))
+                 FROM history h JOIN media_items m ON m.id = h.item_id
+                 WHERE m.media_type = 'movie' AND h.job_id IS NULL
+                   AND h.event IN ('added', 'status_changed') AND h.new_value = 'completed'
+                 GROUP BY 1",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut by_year: BTreeMap<String, TimeSpent> = BTreeMap::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let year: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let media_type = api_media_type(&row, 1)?;
+            let time = TimeSpent::of(
+                &media_type,
+                row.get::<i64>(4).unwrap_or(0) as u64,
+                row.get::<i64>(5).unwrap_or(0) as u64,
+            );
+            if !time.is_empty() {
+                by_year.entry(year).or_default().add(time);
+            }
+        }
+        stats.time_by_year = by_year.into_iter().map(|(y, time)| (y, time.into())).collect();
+        Ok(stats)
+    }
+
+    /// The raw usage counts the telemetry report is built from.
+    pub async fn usage(&self) -> Result<Usage, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT COUNT(*) FROM media_items WHERE scheduled_for IS NOT NULL",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let scheduled_items = match rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            Some(row) => row.get::<i64>(0).unwrap_or(0) as usize,
+            None => 0,
+        };
+
+        let mut rows = self
+            .reader()
+            .query("SELECT DISTINCT kind FROM jobs", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut job_kinds = BTreeSet::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            job_kinds.insert(row.get::<String>(0).unwrap_or_default());
+        }
+
+        Ok(Usage {
+            stats: self.stats().await?,
+            settings: self.load_settings().await?,
+            focus_pins: self.list_focus().await?.len(),
+            scheduled_items,
+            job_kinds,
+        })
+    }
+
+    // ── History ──────────────────────────────────────────────
+
+    /// Audit log of a single item, newest first.
+    pub async fn item_history(&self, item_id: Uuid) -> Result<Vec<HistoryEntry>, StorageError> {
+        self.query_history(
+            "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+             FROM history WHERE item_id = ?1 ORDER BY id DESC",
+            libsql::params![item_id.to_string()],
+        )
+        .await
+    }
+
+    /// Library-wide activity feed, newest first.
+    pub async fn recent_activity(&self, limit: u32) -> Result<Vec<HistoryEntry>, StorageError> {
+        self.query_history(
+            "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+             FROM history ORDER BY id DESC LIMIT ?1",
+            libsql::params![limit as i64],
+        )
+        .await
+    }
+
+    /// Items finished by hand (added as or moved to completed), newest
+    /// first.  Changes made by jobs such as imports are left out.
+    pub async fn recent_completions(&self, limit: u32) -> Result<Vec<HistoryEntry>, StorageError> {
+        self.query_history(
+            "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+             FROM history
+             WHERE event IN ('added', 'status_changed') AND new_value = 'completed'
+               AND job_id IS NULL
+             ORDER BY id DESC LIMIT ?1",
+            libsql::params![limit as i64],
+        )
+        .await
+    }
+
+    /// Audit log entries after `after_id`, oldest first.
+    pub async fn history_since(&self, after_id: i64, limit: u32) -> Result<Vec<HistoryEntry>, StorageError> {
+        self.query_history(
+            "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+             FROM history WHERE id > ?1 ORDER BY id LIMIT ?2",
+            libsql::params![after_id, limit as i64],
+        )
+        .await
+    }
+
+    /// Id of the newest audit log entry, 0 when there is none.
+    pub async fn latest_history_id(&self) -> Result<i64, StorageError> {
+        let mut rows = self
+            .reader()
+            .query("SELECT COALESCE(MAX(id), 0) FROM history", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        match rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            Some(row) => row.get::<i64>(0).map_err(|e| StorageError::Database(e.to_string())),
+            None => Ok(0),
+        }
+    }
+
+    /// Items ordered by their most recent audit log entry, newest first.
+    /// Deleted items are skipped.
+    pub async fn recently_updated(&self, limit: u32) -> Result<Vec<MediaItem>, StorageError> {
+        let columns = ITEM_COLUMNS
+            .split(',')
+            .map(|c| format!("m.{}", c.trim()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut rows = self
+            .reader()
+            .query(
+                &format!(
+                    "SELECT {columns} FROM media_items m
+                     JOIN (SELECT item_id, MAX(id) AS last FROM history GROUP BY item_id) h
+                       ON h.item_id = m.id
+                     ORDER BY h.last DESC LIMIT ?1"
+                ),
+                libsql::params![limit as i64],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            items.push(row_to_media_item(&row)?);
+        }
+        Ok(items)
+    }
+
+    async fn query_history(
+        &self,
+        sql: &str,
+        params: impl libsql::params::IntoParams,
+    ) -> Result<Vec<HistoryEntry>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(sql, params)
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut entries = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let item_id: String = row
+                .get::<String>(1)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let event: String = row
+                .get::<String>(3)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let recorded_at: String = row
+                .get::<String>(6)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            entries.push(HistoryEntry {
+                id: row.get::<i64>(0).unwrap_or(0),
+                item_id: Uuid::parse_str(&item_id)
+                    .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?,
+                title: row.get::<String>(2).unwrap_or_default(),
+                kind: HistoryKind::parse(&event).ok_or_else(|| {
+                    StorageError::Corruption(format!("Unknown history event: {event}"))
+                })?,
+                old_value: opt_text(&row, 4),
+                new_value: opt_text(&row, 5),
+                recorded_at: parse_timestamp(&recorded_at)?,
+                job_id: row.get::<Option<i64>>(7).unwrap_or(None),
+            });
+        }
+        Ok(entries)
+    }
+
+    // ── Settings ─────────────────────────────────────────────
+
+    pub async fn load_settings(&self) -> Result<Settings, StorageError> {
+        let mut rows = self
+            .reader()
+            .query("SELECT key, value FROM settings", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut fields = serde_json::Map::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let key: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let value: String = row.get(1).map_err(|e| StorageError::Database(e.to_string()))?;
+            fields.insert(key, serde_json::from_str(&value)?);
+        }
+        Ok(serde_json::from_value(serde_json::Value::Object(fields))?)
+    }
+
+    pub async fn save_settings(&self, settings: &Settings) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        write_settings(&tx, settings).await?;
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ── Jobs ─────────────────────────────────────────────────
+
+    /// Registers a new running job and returns it.
+    pub async fn create_job(&self, kind: JobKind, dry_run: bool) -> Result<Job, StorageError> {
+        let created_at = Utc::now();
+        self.conn
+            .execute(
+                "INSERT INTO jobs (kind, status, dry_run, created_at) VALUES (?1, ?2, ?3, ?4)",
+                libsql::params![
+                    kind.as_str(),
+                    JobStatus::Running.as_str(),
+                    dry_run as i64,
+                    format_timestamp(created_at),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(Job {
+            id: self.conn.last_insert_rowid(),
+            kind,
+            status: JobStatus::Running,
+            dry_run,
+            created_at,
+            finished_at: None,
+            error: None,
+            items: Vec::new(),
+        })
+    }
+
+    pub async fn finish_job(
+        &self,
+        id: i64,
+        status: JobStatus,
+        items: &[ItemChanges],
+        error: Option<&str>,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "UPDATE jobs SET status = ?1, items = ?2, error = ?3, finished_at = ?4 WHERE id = ?5",
+                libsql::params![
+                    status.as_str(),
+                    serde_json::to_string(items)?,
+                    error,
+                    format_timestamp(Utc::now()),
+                    id,
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Marks jobs left running by a previous process as failed.
+    pub async fn fail_interrupted_jobs(&self) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "UPDATE jobs SET status = ?1, error = 'Interrupted by restart', finished_at = ?2
+                 WHERE status = ?3",
+                libsql::params![
+                    JobStatus::Failed.as_str(),
+                    format_timestamp(Utc::now()),
+                    JobStatus::Running.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn get_job(&self, id: i64) -> Result<Option<Job>, StorageError> {
+        Ok(self.query_jobs("WHERE id = ?1", id).await?.pop())
+    }
+
+    /// Newest first.
+    pub async fn list_jobs(&self, limit: u32) -> Result<Vec<Job>, StorageError> {
+        self.query_jobs("ORDER BY id DESC LIMIT ?1", limit as i64).await
+    }
+
+    async fn query_jobs(&self, clause: &str, param: i64) -> Result<Vec<Job>, StorageError> {
+        let sql = format!(
+            "SELECT id, kind, status, dry_run, created_at, finished_at, error, items FROM jobs {clause}"
+        );
+        let mut rows = self
+            .reader()
+            .query(&sql, libsql::params![param])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut jobs = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let kind: String = row.get(1).map_err(|e| StorageError::Database(e.to_string()))?;
+            let status: String = row.get(2).map_err(|e| StorageError::Database(e.to_string()))?;
+            let created_at: String = row.get(4).map_err(|e| StorageError::Database(e.to_string()))?;
+            let items: String = row.get(7).map_err(|e| StorageError::Database(e.to_string()))?;
+            jobs.push(Job {
+                id: row.get::<i64>(0).unwrap_or(0),
+                kind: JobKind::parse(&kind)
+                    .ok_or_else(|| StorageError::Corruption(format!("Unknown job kind: {kind}")))?,
+                status: JobStatus::parse(&status)
+                    .ok_or_else(|| StorageError::Corruption(format!("Unknown job status: {status}")))?,
+                dry_run: row.get::<i64>(3).unwrap_or(0) != 0,
+                created_at: parse_timestamp(&created_at)?,
+                finished_at: opt_text(&row, 5).map(|t| parse_timestamp(&t)).transpose()?,
+                error: opt_text(&row, 6),
+                items: serde_json::from_str(&items)?,
+            });
+        }
+        Ok(jobs)
+    }
+
+    /// Writes a merge plan as a completed (or failed) import job.
+    pub async fn apply_merge(&self, plan: &MergePlan) -> Result<Job, StorageError> {
+        let job = self.create_job(JobKind::Import, false).await?;
+        let report: Vec<ItemChanges> = plan
+            .writes
+            .iter()
+            .map(|(before, item)| ItemChanges {
+                item_id: item.id,
+                title: item.title.clone(),
+                created: before.is_none(),
+                changes: Vec::new(),
+                error: None,
+            })
+            .collect();
+        let (status, error) = match self.import_items(job.id, &plan.writes, &plan.activity).await {
+            Ok(()) => (JobStatus::Completed, None),
+            Err(e) => (JobStatus::Failed, Some(e.to_string())),
+        };
+        self.finish_job(job.id, status, &report, error.as_deref()).await?;
+        self.get_job(job.id)
+            .await?
+            .ok_or_else(|| StorageError::Database(format!("Job {} disappeared", job.id)))
+    }
+
+    /// Writes the items of an import job in one transaction.  Each entry is
+    /// the stored version (None for a new item) and the version to write;
+    /// the stored version is kept so the job can be rolled back.  The
+    /// `activity` must already refer to stored item ids.
+    pub async fn import_items(
+        &self,
+        job_id: i64,
+        items: &[(Option<MediaItem>, MediaItem)],
+        activity: &Activity,
+    ) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        for (before, item) in items {
+            record_changes(&tx, before.as_ref(), item, Some(job_id)).await?;
+            write_item(&tx, before.as_ref(), item).await?;
+            let before = before.as_ref().map(serde_json::to_string).transpose()?;
+            tx.execute(
+                "INSERT OR IGNORE INTO job_items (job_id, item_id, before) VALUES (?1, ?2, ?3)",
+                libsql::params![job_id, item.id.to_string(), before],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        write_activity(&tx, activity).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Items of a job changed or deleted after `finished_at`, which a
+    /// rollback would overwrite.  Items it created and that are gone now
+    /// are not listed, since the rollback would delete them anyway.
+    pub async fn rollback_conflicts(
+        &self,
+        job_id: i64,
+        finished_at: DateTime<Utc>,
+    ) -> Result<Vec<RollbackConflict>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id, before FROM job_items WHERE job_id = ?1 ORDER BY item_id",
+                libsql::params![job_id],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            entries.push((row_uuid(&row, 0)?, opt_text(&row, 1)));
+        }
+
+        let mut conflicts = Vec::new();
+        for (id, before) in entries {
+            match (fetch_item(self.reader(), id).await?, before) {
+                (Some(item), _) if item.updated_at.is_some_and(|t| t > finished_at) => {
+                    conflicts.push(RollbackConflict { item_id: id, title: item.title, changed_at: item.updated_at });
+                }
+                (None, Some(json)) => {
+                    let before = archive::read_snapshot(&json)?;
+                    conflicts.push(RollbackConflict { item_id: id, title: before.title, changed_at: None });
+                }
+                _ => {}
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Reverses a job in one transaction: items it created are deleted and
+    /// items it changed get their previous version back (discarding any
+    /// edits made since; see `rollback_conflicts`).  Returns how many items
+    /// were touched.
+    pub async fn rollback_job(&self, job_id: i64) -> Result<usize, StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut rows = tx
+            .query(
+                "SELECT item_id, before FROM job_items WHERE job_id = ?1",
+                libsql::params![job_id],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut entries = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let item_id: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let id = Uuid::parse_str(&item_id)
+                .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?;
+            entries.push((id, opt_text(&row, 1)));
+        }
+
+        for (id, before) in &entries {
+            let current = fetch_item(&tx, *id).await?;
+            match before {
+                Some(json) => {
+                    let before = archive::read_snapshot(json)?;
+                    record_changes(&tx, current.as_ref(), &before, None).await?;
+                    write_item(&tx, current.as_ref(), &before).await?;
+                }
+                None => {
+                    if let Some(item) = current {
+                        record_deletion(&tx, &item).await?;
+                        delete_item_rows(&tx, *id).await?;
+                    }
+                }
+            }
+        }
+
+        tx.execute(
+            "UPDATE jobs SET status = ?1 WHERE id = ?2",
+            libsql::params![JobStatus::RolledBack.as_str(), job_id],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(entries.len())
+    }
+
+    // ── Archive ──────────────────────────────────────────────
+
+    /// Reads the whole library into an archive.
+    pub async fn export_archive(&self) -> Result<Archive, StorageError> {
+        let items = self.load_all().await?;
+
+        let mut activity = Activity::default();
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id, episode, watched, watched_at FROM episodes
+                 ORDER BY item_id, episode",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            activity.episodes.push(ArchivedEpisode {
+                item_id: row_uuid(&row, 0)?,
+                episode: row.get::<i64>(1).unwrap_or(0) as u32,
+                watched: row.get::<i64>(2).unwrap_or(0) != 0,
+                watched_at: opt_text(&row, 3).map(|t| parse_timestamp(&t)).transpose()?,
+            });
+        }
+
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id, progress, delta, recorded_at FROM progress_history ORDER BY id",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let recorded_at: String = row.get(3).map_err(|e| StorageError::Database(e.to_string()))?;
+            activity.progress.push(ArchivedProgress {
+                item_id: row_uuid(&row, 0)?,
+                progress: row.get::<i64>(1).unwrap_or(0) as u32,
+                delta: row.get::<i64>(2).unwrap_or(0),
+                recorded_at: parse_timestamp(&recorded_at)?,
+            });
+        }
+
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id, title, event, old_value, new_value, recorded_at
+                 FROM history ORDER BY id",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let recorded_at: String = row.get(5).map_err(|e| StorageError::Database(e.to_string()))?;
+            activity.history.push(ArchivedHistory {
+                item_id: row_uuid(&row, 0)?,
+                title: row.get::<String>(1).unwrap_or_default(),
+                event: row.get::<String>(2).map_err(|e| StorageError::Database(e.to_string()))?,
+                old_value: opt_text(&row, 3),
+                new_value: opt_text(&row, 4),
+                recorded_at: parse_timestamp(&recorded_at)?,
+            });
+        }
+
+        Ok(Archive {
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            items,
+            activity,
+            settings: Some(self.load_settings().await?.redacted()),
+        })
+    }
+
+    /// Replaces the library with `archive` in one transaction.  Items,
+    /// episodes, the progress and audit logs are wiped first; settings are
+    /// only replaced when the archive carries them.  Focus pins and
+    /// rankings survive for items the archive restores.  Jobs are left
+    /// alone.
+    pub async fn replace_all(&self, archive: &Archive) -> Result<(), StorageError> {
+        // Exports carry redacted notifier secrets; the stored ones stay.
+        let settings = match &archive.settings {
+            Some(settings) => {
+                let mut settings = settings.clone();
+                settings.keep_secrets(&self.load_settings().await?);
+                Some(settings)
+            }
+            None => None,
+        };
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut tables = vec!["media_items", "external_refs", "episodes", "progress_history", "history"];
+        if archive.settings.is_some() {
+            tables.push("settings");
+        }
+        for table in tables {
+            tx.execute(&format!("DELETE FROM {table}"), ())
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+
+        for item in &archive.items {
+            write_item(&tx, None, item).await?;
+        }
+        write_activity(&tx, &archive.activity).await?;
+        if let Some(settings) = &settings {
+            write_settings(&tx, settings).await?;
+        }
+        for table in ["focus_pins", "relations", "rankings"] {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE item_id NOT IN (SELECT id FROM media_items)"),
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ── Focus ────────────────────────────────────────────────
+
+    /// The focus list, oldest pin first.  Pins of items that no longer
+    /// exist are skipped.
+    pub async fn list_focus(&self) -> Result<Vec<FocusPin>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT f.item_id, f.pinned_at FROM focus_pins f
+                 JOIN media_items m ON m.id = f.item_id
+                 ORDER BY f.pinned_at, f.item_id",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut pins = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let pinned_at: String = row.get(1).map_err(|e| StorageError::Database(e.to_string()))?;
+            pins.push(FocusPin {
+                item_id: row_uuid(&row, 0)?,
+                pinned_at: parse_timestamp(&pinned_at)?,
+            });
+        }
+        Ok(pins)
+    }
+
+    /// Stores a pin; an item already pinned keeps its original time.  The
+    /// caller enforces the size limit.
+    pub async fn pin_item(&self, pin: &FocusPin) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO focus_pins (item_id, pinned_at) VALUES (?1, ?2)",
+                libsql::params![pin.item_id.to_string(), format_timestamp(pin.pinned_at)],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns false when the item was not pinned.
+    pub async fn unpin_item(&self, item_id: Uuid) -> Result<bool, StorageError> {
+        let changed = self
+            .conn
+            .execute(
+                "DELETE FROM focus_pins WHERE item_id = ?1",
+                libsql::params![item_id.to_string()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    // ── Ranking ──────────────────────────────────────────────
+
+    /// Ratings of compared items, best first.  Ratings of items that no
+    /// longer exist are skipped.
+    pub async fn list_ratings(&self) -> Result<Vec<Rating>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT r.item_id, r.rating, r.comparisons FROM rankings r
+                 JOIN media_items m ON m.id = r.item_id
+                 ORDER BY r.rating DESC, r.comparisons DESC, m.title COLLATE NOCASE",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut ratings = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            ratings.push(Rating {
+                item_id: row_uuid(&row, 0)?,
+                rating: row.get::<f64>(1).unwrap_or(ranking::INITIAL_RATING),
+                comparisons: opt_int(&row, 2).unwrap_or(0) as u32,
+            });
+        }
+        Ok(ratings)
+    }
+
+    /// Records that `winner` was picked over `loser` and returns both
+    /// updated ratings.  The caller checks that both items exist.
+    pub async fn record_pick(&self, winner: Uuid, loser: Uuid) -> Result<(Rating, Rating), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut pair = [Rating::new(winner), Rating::new(loser)];
+        for rating in &mut pair {
+            let mut rows = tx
+                .query(
+                    "SELECT rating, comparisons FROM rankings WHERE item_id = ?1",
+                    libsql::params![rating.item_id.to_string()],
+                )
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            if let Some(row) = rows
+                .next()
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?
+            {
+                rating.rating = row.get::<f64>(0).unwrap_or(ranking::INITIAL_RATING);
+                rating.comparisons = opt_int(&row, 1).unwrap_or(0) as u32;
+            }
+        }
+        let [mut winner, mut loser] = pair;
+        ranking::record(&mut winner, &mut loser);
+        for rating in [&winner, &loser] {
+            tx.execute(
+                "INSERT INTO rankings (item_id, rating, comparisons) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (item_id) DO UPDATE SET
+                     rating = excluded.rating,
+                     comparisons = excluded.comparisons",
+                libsql::params![rating.item_id.to_string(), rating.rating, rating.comparisons as i64],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok((winner, loser))
+    }
+
+    // ── Goals ────────────────────────────────────────────────
+
+    /// Every goal, newest year first.
+    pub async fn list_goals(&self) -> Result<Vec<Goal>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT id, year, media_type, target FROM goals ORDER BY year DESC, media_type",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut goals = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            goals.push(Goal {
+                id: row.get::<i64>(0).map_err(|e| StorageError::Database(e.to_string()))?,
+                year: row.get::<i64>(1).unwrap_or(0) as i32,
+                media_type: opt_text(&row, 2),
+                target: row.get::<i64>(3).unwrap_or(0) as u32,
+            });
+        }
+        Ok(goals)
+    }
+
+    /// Stores the target for a year and media type, replacing the one set
+    /// before.  Returns the goal with its id.
+    pub async fn set_goal(&self, year: i32, media_type: Option<&str>, target: u32) -> Result<Goal, StorageError> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT id FROM goals WHERE year = ?1 AND media_type IS ?2",
+                libsql::params![year as i64, media_type],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let existing = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .and_then(|row| row.get::<i64>(0).ok());
+        drop(rows);
+
+        let id = match existing {
+            Some(id) => {
+                self.conn
+                    .execute("UPDATE goals SET target = ?1 WHERE id = ?2", libsql::params![target as i64, id])
+                    .await
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+                id
+            }
+            None => {
+                self.conn
+                    .execute(
+                        "INSERT INTO goals (year, media_type, target) VALUES (?1, ?2, ?3)",
+                        libsql::params![year as i64, media_type, target as i64],
+                    )
+                    .await
+                    .map_err(|e| StorageError::Database(e.to_string()))?;
+                self.conn.last_insert_rowid()
+            }
+        };
+        Ok(Goal { id, year, media_type: media_type.map(str::to_string), target })
+    }
+
+    /// Returns false when there was no such goal.
+    pub async fn delete_goal(&self, id: i64) -> Result<bool, StorageError> {
+        let changed = self
+            .conn
+            .execute("DELETE FROM goals WHERE id = ?1", libsql::params![id])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    /// When items were finished by hand (added as or moved to completed),
+    /// oldest first.  Completions made by jobs such as imports are left out.
+    pub async fn completion_times(&self) -> Result<Vec<(Uuid, DateTime<Utc>)>, StorageError> {
+        let entries = self
+            .query_history(
+                "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+                 FROM history
+                 WHERE event IN ('added', 'status_changed') AND new_value = 'completed'
+                   AND job_id IS NULL
+                 ORDER BY id",
+                (),
+            )
+            .await?;
+        Ok(entries.into_iter().map(|e| (e.item_id, e.recorded_at)).collect())
+    }
+
+    /// When anything was added, watched, read or rated by hand.
+    pub async fn activity_times(&self) -> Result<Vec<DateTime<Utc>>, StorageError> {
+        let entries = self
+            .query_history(
+                "SELECT id, item_id, title, event, old_value, new_value, recorded_at, job_id
+                 FROM history
+                 WHERE event IN ('added', 'status_changed', 'progress_updated', 'score_set')
+                   AND job_id IS NULL
+                 ORDER BY id",
+                (),
+            )
+            .await?;
+        Ok(entries.into_iter().map(|e| e.recorded_at).collect())
+    }
+
+    // ── Franchises ───────────────────────────────────────────
+
+    /// Entries of a franchise, by title.
+    pub async fn franchise_items(&self, name: &str) -> Result<Vec<MediaItem>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                &format!(
+                    "SELECT {ITEM_COLUMNS} FROM media_items WHERE franchise = ?1
+                     ORDER BY title COLLATE NOCASE"
+                ),
+                libsql::params![name],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut items = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            items.push(row_to_media_item(&row)?);
+        }
+        Ok(items)
+    }
+
+    /// Moves every entry of franchise `from` to `to`, or out of any
+    /// franchise when `to` is None.  Renaming onto an existing franchise
+    /// merges the two.  Returns how many items changed.
+    pub async fn rename_franchise(&self, from: &str, to: Option<&str>) -> Result<u64, StorageError> {
+        self.conn
+            .execute(
+                "UPDATE media_items SET franchise = ?2 WHERE franchise = ?1",
+                libsql::params![from, to],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    // ── Relations ────────────────────────────────────────────
+
+    /// Related entries of an item, in the order the provider listed them.
+    pub async fn list_relations(&self, item_id: Uuid) -> Result<Vec<Relation>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT kind, source, external_id, title, format FROM relations
+                 WHERE item_id = ?1 ORDER BY rowid",
+                libsql::params![item_id.to_string()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut relations = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            relations.push(Relation {
+                kind: row.get::<String>(0).map_err(|e| StorageError::Database(e.to_string()))?,
+                source: row.get::<String>(1).map_err(|e| StorageError::Database(e.to_string()))?,
+                external_id: row.get::<String>(2).map_err(|e| StorageError::Database(e.to_string()))?,
+                title: row.get::<String>(3).map_err(|e| StorageError::Database(e.to_string()))?,
+                format: opt_text(&row, 4),
+            });
+        }
+        Ok(relations)
+    }
+
+    /// Replaces the stored relations of an item with fresh provider data.
+    pub async fn replace_relations(&self, item_id: Uuid, relations: &[Relation]) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute(
+            "DELETE FROM relations WHERE item_id = ?1",
+            libsql::params![item_id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        for r in relations {
+            tx.execute(
+                "INSERT OR IGNORE INTO relations (item_id, kind, source, external_id, title, format)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                libsql::params![
+                    item_id.to_string(),
+                    r.kind.clone(),
+                    r.source.clone(),
+                    r.external_id.clone(),
+                    r.title.clone(),
+                    r.format.clone(),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The library item for a provider entry: imported from it, or
+    /// carrying it as an external reference.
+    pub async fn find_by_provider_entry(
+        &self,
+        source: &str,
+        external_id: &str,
+    ) -> Result<Option<MediaItem>, StorageError> {
+        if let Some(item) = self.find_by_external_id(source, external_id).await? {
+            return Ok(Some(item));
+        }
+        self.resolve_external_ref(source, external_id).await
+    }
+
+    // ── Webhooks ─────────────────────────────────────────────
+
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query("SELECT id, url, secret, created_at, delivered_through FROM webhooks ORDER BY id", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut hooks = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let created_at: String = row.get(3).map_err(|e| StorageError::Database(e.to_string()))?;
+            hooks.push(Webhook {
+                id: row.get::<i64>(0).map_err(|e| StorageError::Database(e.to_string()))?,
+                url: row.get::<String>(1).map_err(|e| StorageError::Database(e.to_string()))?,
+                secret: row.get::<String>(2).map_err(|e| StorageError::Database(e.to_string()))?,
+                created_at: parse_timestamp(&created_at)?,
+                delivered_through: row.get::<i64>(4).map_err(|e| StorageError::Database(e.to_string()))?,
+            });
+        }
+        Ok(hooks)
+    }
+
+    /// Registers a receiver and returns it with its id.  It gets the
+    /// changes made from now on.
+    pub async fn add_webhook(&self, url: &str, secret: &str) -> Result<Webhook, StorageError> {
+        let created_at = Utc::now();
+        let delivered_through = self.latest_history_id().await?;
+        self.conn
+            .execute(
+                "INSERT INTO webhooks (url, secret, created_at, delivered_through) VALUES (?1, ?2, ?3, ?4)",
+                libsql::params![url, secret, format_timestamp(created_at), delivered_through],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(Webhook {
+            id: self.conn.last_insert_rowid(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            created_at,
+            delivered_through,
+        })
+    }
+
+    /// Notes that audit log entries up to `history_id` were sent to a
+    /// webhook.  Nothing happens when it was deleted in the meantime.
+    pub async fn set_webhook_delivered(&self, id: i64, history_id: i64) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "UPDATE webhooks SET delivered_through = ?2 WHERE id = ?1",
+                libsql::params![id, history_id],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns false when there was no such webhook.
+    pub async fn delete_webhook(&self, id: i64) -> Result<bool, StorageError> {
+        let changed = self
+            .conn
+            .execute("DELETE FROM webhooks WHERE id = ?1", libsql::params![id])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    // ── Trakt ────────────────────────────────────────────────
+
+    pub async fn load_trakt_auth(&self) -> Result<Option<TraktAuth>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT access_token, refresh_token, expires_at, connected_at, last_synced_at
+                 FROM trakt_auth WHERE id = 1",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        let expires_at: String = row.get(2).map_err(|e| StorageError::Database(e.to_string()))?;
+        let connected_at: String = row.get(3).map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(Some(TraktAuth {
+            access_token: row.get(0).map_err(|e| StorageError::Database(e.to_string()))?,
+            refresh_token: row.get(1).map_err(|e| StorageError::Database(e.to_string()))?,
+            expires_at: parse_timestamp(&expires_at)?,
+            connected_at: parse_timestamp(&connected_at)?,
+            last_synced_at: opt_text(&row, 4).map(|t| parse_timestamp(&t)).transpose()?,
+        }))
+    }
+
+    /// Stores the connection, replacing any previous one.
+    pub async fn save_trakt_auth(&self, auth: &TraktAuth) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO trakt_auth
+                 (id, access_token, refresh_token, expires_at, connected_at, last_synced_at)
+                 VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+                libsql::params![
+                    auth.access_token.as_str(),
+                    auth.refresh_token.as_str(),
+                    format_timestamp(auth.expires_at),
+                    format_timestamp(auth.connected_at),
+                    auth.last_synced_at.map(format_timestamp),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn set_trakt_synced(&self, at: DateTime<Utc>) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "UPDATE trakt_auth SET last_synced_at = ?1",
+                libsql::params![format_timestamp(at)],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns false when no account was connected.
+    pub async fn delete_trakt_auth(&self) -> Result<bool, StorageError> {
+        let changed = self
+            .conn
+            .execute("DELETE FROM trakt_auth", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    // ── AniList write-through ────────────────────────────────
+
+    pub async fn load_anilist_auth(&self) -> Result<Option<AniListAuth>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT token, user_id, user_name, connected_at, history_cursor
+                 FROM anilist_auth WHERE id = 1",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        let connected_at: String = row.get(3).map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(Some(AniListAuth {
+            token: row.get(0).map_err(|e| StorageError::Database(e.to_string()))?,
+            user_id: row.get::<i64>(1).map_err(|e| StorageError::Database(e.to_string()))? as u32,
+            user_name: row.get(2).map_err(|e| StorageError::Database(e.to_string()))?,
+            connected_at: parse_timestamp(&connected_at)?,
+            history_cursor: row.get(4).map_err(|e| StorageError::Database(e.to_string()))?,
+        }))
+    }
+
+    /// Links an account, replacing any previous one and its queue.
+    pub async fn save_anilist_auth(&self, auth: &AniListAuth) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute("DELETE FROM anilist_queue", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute(
+            "INSERT OR REPLACE INTO anilist_auth
+             (id, token, user_id, user_name, connected_at, history_cursor)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+            libsql::params![
+                auth.token.as_str(),
+                auth.user_id as i64,
+                auth.user_name.as_str(),
+                format_timestamp(auth.connected_at),
+                auth.history_cursor,
+            ],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Unlinks the account and drops its queue.  Returns false when no
+    /// account was linked.
+    pub async fn delete_anilist_auth(&self) -> Result<bool, StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.execute("DELETE FROM anilist_queue", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let changed = tx
+            .execute("DELETE FROM anilist_auth", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    /// Queues the items (once each, keeping earlier failures) and moves the
+    /// audit log cursor to `cursor`, in one transaction.
+    pub async fn queue_anilist_updates(&self, item_ids: &[Uuid], cursor: i64) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let queued_at = format_timestamp(Utc::now());
+        for id in item_ids {
+            tx.execute(
+                "INSERT OR IGNORE INTO anilist_queue (item_id, queued_at) VALUES (?1, ?2)",
+                libsql::params![id.to_string(), queued_at.as_str()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        tx.execute(
+            "UPDATE anilist_auth SET history_cursor = ?1",
+            libsql::params![cursor],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Oldest first.
+    pub async fn anilist_queue(&self) -> Result<Vec<QueuedUpdate>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT item_id, queued_at, attempts, last_error FROM anilist_queue ORDER BY queued_at, item_id",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut queue = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let item_id: String = row.get(0).map_err(|e| StorageError::Database(e.to_string()))?;
+            let queued_at: String = row.get(1).map_err(|e| StorageError::Database(e.to_string()))?;
+            queue.push(QueuedUpdate {
+                item_id: Uuid::parse_str(&item_id)
+                    .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?,
+                queued_at: parse_timestamp(&queued_at)?,
+                attempts: row.get::<i64>(2).unwrap_or(0) as u32,
+                last_error: opt_text(&row, 3),
+            });
+        }
+        Ok(queue)
+    }
+
+    /// Takes a sent (or abandoned) update off the queue.
+    pub async fn dequeue_anilist_update(&self, item_id: Uuid) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "DELETE FROM anilist_queue WHERE item_id = ?1",
+                libsql::params![item_id.to_string()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn fail_anilist_update(&self, item_id: Uuid, error: &str) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "UPDATE anilist_queue SET attempts = attempts + 1, last_error = ?1 WHERE item_id = ?2",
+                libsql::params![error, item_id.to_string()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ── Notifications ────────────────────────────────────────
+
+    /// Stores new notifications, skipping those whose key is already
+    /// present.  Returns how many were added.
+    pub async fn add_notifications(&self, batch: &[NewNotification]) -> Result<u64, StorageError> {
+        let now = format_timestamp(Utc::now());
+        let mut added = 0;
+        for n in batch {
+            added += self
+                .conn
+                .execute(
+                    "INSERT OR IGNORE INTO notifications (key, item_id, kind, message, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    libsql::params![
+                        n.key.clone(),
+                        n.item_id.map(|id| id.to_string()),
+                        n.kind.as_str(),
+                        n.message.clone(),
+                        now.clone(),
+                    ],
+                )
+                .await
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+        Ok(added)
+    }
+
+    /// Newest first.
+    pub async fn list_notifications(
+        &self,
+        limit: u32,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT id, item_id, kind, message, created_at, read FROM notifications
+                 WHERE read = 0 OR ?1 = 0 ORDER BY id DESC LIMIT ?2",
+                libsql::params![unread_only as i64, limit as i64],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut notifications = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let kind: String = row.get(2).map_err(|e| StorageError::Database(e.to_string()))?;
+            let created_at: String = row.get(4).map_err(|e| StorageError::Database(e.to_string()))?;
+            notifications.push(Notification {
+                id: row.get::<i64>(0).unwrap_or(0),
+                item_id: opt_text(&row, 1).and_then(|id| Uuid::parse_str(&id).ok()),
+                kind: NotificationKind::parse(&kind).ok_or_else(|| {
+                    StorageError::Corruption(format!("Unknown notification kind: {kind}"))
+                })?,
+                message: row.get::<String>(3).unwrap_or_default(),
+                created_at: parse_timestamp(&created_at)?,
+                read: row.get::<i64>(5).unwrap_or(0) != 0,
+            });
+        }
+        Ok(notifications)
+    }
+
+    pub async fn mark_notification_read(&self, id: i64) -> Result<bool, StorageError> {
+        let changed = self
+            .conn
+            .execute("UPDATE notifications SET read = 1 WHERE id = ?1", libsql::params![id])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(changed > 0)
+    }
+
+    // ── Diagnostics ──────────────────────────────────────────
+
+    pub async fn record_diagnostic(
+        &self,
+        provider: &str,
+        message: &str,
+        snippet: &str,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "INSERT INTO diagnostics (provider, message, snippet, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                libsql::params![provider, message, snippet, format_timestamp(Utc::now())],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.conn
+            .execute(
+                "DELETE FROM diagnostics WHERE id <= (SELECT MAX(id) FROM diagnostics) - ?1",
+                libsql::params![MAX_DIAGNOSTICS],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Stored payload mismatches, newest first.
+    pub async fn list_diagnostics(&self) -> Result<Vec<ProviderDiagnostic>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT id, provider, message, snippet, recorded_at
+                 FROM diagnostics ORDER BY id DESC",
+                (),
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut diagnostics = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let recorded_at: String = row
+                .get::<String>(4)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            diagnostics.push(ProviderDiagnostic {
+                id: row.get::<i64>(0).unwrap_or(0),
+                provider: row.get::<String>(1).unwrap_or_default(),
+                message: row.get::<String>(2).unwrap_or_default(),
+                snippet: row.get::<String>(3).unwrap_or_default(),
+                recorded_at: parse_timestamp(&recorded_at)?,
+            });
+        }
+        Ok(diagnostics)
+    }
+
+    pub async fn clear_diagnostics(&self) -> Result<(), StorageError> {
+        self.conn
+            .execute("DELETE FROM diagnostics", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    // ── Episodes ─────────────────────────────────────────────
+
+    pub async fn list_episodes(&self, item_id: Uuid) -> Result<Vec<EpisodeRecord>, StorageError> {
+        let mut rows = self
+            .reader()
+            .query(
+                "SELECT episode, watched, watched_at FROM episodes
+                 WHERE item_id = ?1 ORDER BY episode",
+                libsql::params![item_id.to_string()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut episodes = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            episodes.push(EpisodeRecord {
+                number: row.get::<i64>(0).unwrap_or(0) as u32,
+                watched: row.get::<i64>(1).unwrap_or(0) != 0,
+                watched_at: opt_text(&row, 2)
+                    .map(|ts| parse_timestamp(&ts))
+                    .transpose()?,
+            });
+        }
+        Ok(episodes)
+    }
+
+    /// Marks the given episodes as watched / unwatched and returns how many
+    /// episodes of the item are now watched.  The rows are first brought
+    /// in line with the item's `progress`, so the episodes counted before
+    /// any were marked one by one keep counting.
+    pub async fn set_episodes_watched(
+        &self,
+        item_id: Uuid,
+        progress: u32,
+        numbers: &[u32],
+        watched: bool,
+    ) -> Result<u32, StorageError> {
+        let tx = self
+            .conn
+            .transaction()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        sync_episodes(&tx, item_id, progress, true).await?;
+
+        let watched_at = watched.then(|| format_timestamp(Utc::now()));
+        for n in numbers {
+            tx.execute(
+                "INSERT INTO episodes (item_id, episode, watched, watched_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(item_id, episode) DO UPDATE SET
+                    watched = excluded.watched,
+                    watched_at = CASE
+                        WHEN excluded.watched = episodes.watched THEN episodes.watched_at
+                        ELSE excluded.watched_at
+                    END",
+                libsql::params![
+                    item_id.to_string(),
+                    *n as i64,
+                    watched as i64,
+                    watched_at.clone(),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
 
-    pub async fn get_item(&self, id: Uuid) -> Result<Option<MediaItem>, StorageError> {
-        let mut rows = self
-            .conn
+        let mut rows = tx
             .query(
-                "SELECT * FROM media_items WHERE id = ?1",
-                libsql::params![id.to_string()],
+                "SELECT COUNT(*) FROM episodes WHERE item_id = ?1 AND watched = 1",
+                libsql::params![item_id.to_string()],
             )
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-
-        match rows
+        let count = rows
             .next()
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?
-        {
-            Some(row) => Ok(Some(row_to_media_item(&row)?)),
-            None => Ok(None),
-        }
-    }
+            .map(|row| row.get::<i64>(0).unwrap_or(0))
+            .unwrap_or(0);
+        drop(rows);
 
-    pub async fn upsert_item(&self, item: &MediaItem) -> Result<(), StorageError> {
-        let (media_type, readable_kind, watch_status, read_status, cur, tot) =
-            decompose_media_type(&item.media_type);
-        let tags_json = serde_json::to_string(&item.tags)?;
+        tx.commit()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(count as u32)
+    }
 
-        self.conn
-            .execute(
-                "INSERT OR REPLACE INTO media_items
-                    (id, title, media_type, readable_kind, watch_status, read_status,
-                     progress_cur, progress_tot, score, global_score,
-                     external_id, poster_url, source, tags)
-                 VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14)",
-                libsql::params![
-                    item.id.to_string(),
-                    item.title.clone(),
-                    media_type,
-                    readable_kind,
-                    watch_status,
-                    read_status,
-                    cur as i64,
-                    tot.map(|t| t as i64),
-                    item.score.map(|s| s as i64),
-                    item.global_score.map(|s| s as i64),
-                    item.external_id.map(|e| e as i64),
-                    item.poster_url.clone(),
-                    item.source.clone(),
-                    tags_json,
-                ],
+    /// Progress changes recorded at or after `since`, oldest first.
+    pub async fn progress_events_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<ProgressEvent>, StorageError> {
+        let rows = self
+            .reader()
+            .query(
+                "SELECT item_id, progress, delta, recorded_at FROM progress_history
+                 WHERE recorded_at >= ?1 ORDER BY recorded_at",
+                libsql::params![format_timestamp(since)],
             )
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        Ok(())
+        collect_progress_events(rows).await
     }
 
-    pub async fn delete_item(&self, id: Uuid) -> Result<bool, StorageError> {
-        let affected = self
-            .conn
-            .execute(
-                "DELETE FROM media_items WHERE id = ?1",
-                libsql::params![id.to_string()],
+    /// Every recorded progress change of one item, oldest first.
+    pub async fn item_progress_history(&self, item_id: Uuid) -> Result<Vec<ProgressEvent>, StorageError> {
+        let rows = self
+            .reader()
+            .query(
+                "SELECT item_id, progress, delta, recorded_at FROM progress_history
+                 WHERE item_id = ?1 ORDER BY recorded_at, id",
+                libsql::params![item_id.to_string()],
             )
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
-        Ok(affected > 0)
+        collect_progress_events(rows).await
     }
 
-    pub async fn search_items(&self, query: &str) -> Result<Vec<MediaItem>, StorageError> {
-        let pattern = format!("%{query}%");
+    /// Items matching a compiled search (see `core::query`), by title.
+    pub async fn query_items(&self, sql: &Sql) -> Result<Vec<MediaItem>, StorageError> {
+        let params: Vec<libsql::Value> = sql
+            .params
+            .iter()
+            .map(|p| match p {
+                SqlValue::Text(s) => libsql::Value::Text(s.clone()),
+                SqlValue::Integer(i) => libsql::Value::Integer(*i),
+            })
+            .collect();
         let mut rows = self
-            .conn
+            .reader()
             .query(
-                "SELECT * FROM media_items WHERE title LIKE ?1 ORDER BY title",
-                libsql::params![pattern],
+                &format!(
+                    "SELECT {ITEM_COLUMNS} FROM media_items WHERE {} ORDER BY title COLLATE NOCASE",
+                    sql.condition
+                ),
+                libsql::params_from_iter(params),
             )
             .await
             .map_err(|e| StorageError::Database(e.to_string()))?;
@@ -239,26 +2644,210 @@ impl StorageProvider for SqlStorage {
     fn save_all(&self, items: &[MediaItem]) -> Result<(), StorageError> {
         self.rt.block_on(self.db.save_all(items))
     }
+
+    fn query_items(&self, sql: &Sql) -> Result<Vec<MediaItem>, StorageError> {
+        self.rt.block_on(self.db.query_items(sql))
+    }
+
+    fn score_scale(&self) -> ScoreScale {
+        match self.rt.block_on(self.db.load_settings()) {
+            Ok(settings) => settings.score_scale,
+            Err(e) => {
+                eprintln!("Failed to load settings: {e}");
+                ScoreScale::default()
+            }
+        }
+    }
+
+    fn title_language(&self) -> TitleLanguage {
+        match self.rt.block_on(self.db.load_settings()) {
+            Ok(settings) => settings.title_language,
+            Err(e) => {
+                eprintln!("Failed to load settings: {e}");
+                TitleLanguage::default()
+            }
+        }
+    }
+
+    fn default_status(&self, media_type: &str) -> String {
+        let settings = self.rt.block_on(self.db.load_settings()).unwrap_or_else(|e| {
+            eprintln!("Failed to load settings: {e}");
+            Settings::default()
+        });
+        settings.default_status(media_type).to_string()
+    }
+
+    fn dashboard_summary(&self, airing_today: Option<usize>) -> Option<String> {
+        match self.rt.block_on(crate::infra::dashboard::load(&self.db, Utc::now(), airing_today)) {
+            Ok(d) => Some(d.summary),
+            Err(e) => {
+                eprintln!("Failed to load the dashboard: {e}");
+                None
+            }
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════
+// Outbox — item writes kept in a local file while Turso is down.
+// ═══════════════════════════════════════════════════════════════
+
+pub struct Outbox {
+    conn: Connection,
+}
+
+impl Outbox {
+    pub async fn open(path: &str) -> Result<Self, StorageError> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent).map_err(StorageError::Io)?;
+        }
+        let conn = Builder::new_local(path)
+            .build()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .connect()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id TEXT NOT NULL,
+                op TEXT NOT NULL,
+                payload TEXT,
+                base TEXT,
+                queued_at TEXT NOT NULL,
+                conflict TEXT
+            )",
+            (),
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(Self { conn })
+    }
+
+    /// Queues a write; returns it with its id.
+    pub async fn push(&self, mut write: QueuedWrite) -> Result<QueuedWrite, StorageError> {
+        let payload = write.payload.as_ref().map(serde_json::to_string).transpose()?;
+        self.conn
+            .execute(
+                "INSERT INTO outbox (item_id, op, payload, base, queued_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                libsql::params![
+                    write.item_id.to_string(),
+                    write.op.as_str(),
+                    payload,
+                    write.base.map(format_timestamp),
+                    format_timestamp(write.queued_at),
+                ],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        write.id = self.conn.last_insert_rowid();
+        Ok(write)
+    }
+
+    /// Every queued write, oldest first.
+    pub async fn list(&self) -> Result<Vec<QueuedWrite>, StorageError> {
+        let mut rows = self
+            .conn
+            .query("SELECT id, item_id, op, payload, base, queued_at, conflict FROM outbox ORDER BY id", ())
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let mut writes = Vec::new();
+        while let Some(row) = rows
+            .next()
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?
+        {
+            let op: String = row.get(2).map_err(|e| StorageError::Database(e.to_string()))?;
+            let queued_at: String = row.get(5).map_err(|e| StorageError::Database(e.to_string()))?;
+            writes.push(QueuedWrite {
+                id: row.get(0).map_err(|e| StorageError::Database(e.to_string()))?,
+                item_id: row_uuid(&row, 1)?,
+                op: QueuedOp::parse(&op).ok_or_else(|| StorageError::Corruption(format!("Unknown outbox op: {op}")))?,
+                payload: opt_text(&row, 3).map(|p| serde_json::from_str(&p)).transpose()?,
+                base: opt_text(&row, 4).map(|t| parse_timestamp(&t)).transpose()?,
+                queued_at: parse_timestamp(&queued_at)?,
+                conflict: opt_text(&row, 6),
+            });
+        }
+        Ok(writes)
+    }
+
+    /// Records why a write was held back, or clears it.
+    pub async fn set_conflict(&self, id: i64, conflict: Option<&str>) -> Result<(), StorageError> {
+        self.conn
+            .execute("UPDATE outbox SET conflict = ?2 WHERE id = ?1", libsql::params![id, conflict])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Takes a replayed (or discarded) write off the queue; false when
+    /// there was none with the id.
+    pub async fn remove(&self, id: i64) -> Result<bool, StorageError> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM outbox WHERE id = ?1", libsql::params![id])
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(removed > 0)
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════
 // Helpers
 // ═══════════════════════════════════════════════════════════════
 
-async fn insert_item_in_tx(
-    tx: &libsql::Transaction,
+/// Fields of an item kept by the server rather than by clients, as
+/// `write_item` stored them.
+struct ServerFields {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    latest_available: Option<u32>,
+    downloaded: bool,
+}
+
+impl ServerFields {
+    fn apply(self, item: &mut MediaItem) {
+        item.created_at = Some(self.created_at);
+        item.updated_at = Some(self.updated_at);
+        item.latest_available = self.latest_available;
+        item.downloaded = self.downloaded;
+    }
+}
+
+/// Inserts or replaces a single row.  Works on both a plain connection and
+/// a transaction (which derefs to `Connection`).  `old` is the stored
+/// version, if any, from which the server's fields carry over.
+async fn write_item(
+    conn: &Connection,
+    old: Option<&MediaItem>,
     item: &MediaItem,
-) -> Result<(), StorageError> {
+) -> Result<ServerFields, StorageError> {
+    let (created_at, updated_at) = timestamps(old, item, Utc::now());
+    // Kept by the chapter check.
+    let latest_available = item.latest_available.or(old.and_then(|o| o.latest_available));
+    // Kept by the download check.
+    let downloaded = item.downloaded || old.is_some_and(|o| o.downloaded);
     let (media_type, readable_kind, watch_status, read_status, cur, tot) =
         decompose_media_type(&item.media_type);
     let tags_json = serde_json::to_string(&item.tags)?;
+    let alt_titles_json = serde_json::to_string(&item.alt_titles)?;
+    let external_refs_json = serde_json::to_string(&item.external_refs)?;
+    let genres_json = serde_json::to_string(&item.genres)?;
+    let custom_fields_json = serde_json::to_string(&item.custom_fields)?;
 
-    tx.execute(
-        "INSERT INTO media_items
+    conn.execute(
+        "INSERT OR REPLACE INTO media_items
             (id, title, media_type, readable_kind, watch_status, read_status,
              progress_cur, progress_tot, score, global_score,
-             external_id, poster_url, source, tags)
-         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14)",
+             external_id, poster_url, source, tags, repeat_count, notes, notes_spoiler,
+             alt_titles, external_refs, scheduled_for, overview, genres, runtime_minutes,
+             season_count, franchise, custom_fields, volume_cur, volume_tot, prefer_volumes,
+             created_at, updated_at, creator, latest_available, downloaded, content_rating, hidden,
+             favorite)
+         VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,
+                 ?21,?22,?23,?24,?25,?26,?27,?28,?29,?30,?31,?32,?33,?34,?35,?36,?37)",
         libsql::params![
             item.id.to_string(),
             item.title.clone(),
@@ -270,10 +2859,380 @@ async fn insert_item_in_tx(
             tot.map(|t| t as i64),
             item.score.map(|s| s as i64),
             item.global_score.map(|s| s as i64),
-            item.external_id.map(|e| e as i64),
+            item.external_id.clone(),
             item.poster_url.clone(),
             item.source.clone(),
             tags_json,
+            item.repeat_count as i64,
+            item.notes.clone(),
+            item.notes_spoiler as i64,
+            alt_titles_json,
+            external_refs_json,
+            item.scheduled_for.map(format_timestamp),
+            item.overview.clone(),
+            genres_json,
+            item.runtime_minutes.map(|m| m as i64),
+            item.season_count.map(|s| s as i64),
+            item.franchise.clone(),
+            custom_fields_json,
+            item.volumes.as_ref().map(|v| v.current as i64),
+            item.volumes.as_ref().and_then(|v| v.total).map(|t| t as i64),
+            item.prefer_volumes as i64,
+            format_timestamp(created_at),
+            format_timestamp(updated_at),
+            item.creator.clone(),
+            latest_available.map(|c| c as i64),
+            downloaded as i64,
+            item.content_rating.map(|r| r.as_str()),
+            item.hidden as i64,
+            item.favorite as i64,
+        ],
+    )
+    .await
+    .map_err(|e| StorageError::Database(e.to_string()))?;
+
+    // Keep the lookup table in step with the item's refs.  A ref already
+    // claimed by another item moves to this one.
+    conn.execute(
+        "DELETE FROM external_refs WHERE item_id = ?1",
+        libsql::params![item.id.to_string()],
+    )
+    .await
+    .map_err(|e| StorageError::Database(e.to_string()))?;
+    for r in &item.external_refs {
+        let Some((source, ref_id)) = r.split_once(':') else { continue };
+        conn.execute(
+            "INSERT OR REPLACE INTO external_refs (source, ref_id, item_id) VALUES (?1, ?2, ?3)",
+            libsql::params![source, ref_id, item.id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+
+    // A series tracked per episode follows progress set any other way:
+    // increments, edits and the reset of a rewatch.
+    if let MediaItemType::Series(p, _) = &item.media_type {
+        let moved = match old.map(|o| &o.media_type) {
+            Some(MediaItemType::Series(before, _)) => before.current != p.current,
+            _ => true,
+        };
+        if moved {
+            sync_episodes(conn, item.id, p.current, false).await?;
+        }
+    }
+    Ok(ServerFields { created_at, updated_at, latest_available, downloaded })
+}
+
+/// Brings the watched episode rows of an item to `progress` of them:
+/// the latest watched ones are unmarked when there are too many, and the
+/// first unwatched ones marked (with no watch time) when there are too
+/// few.  Items without rows are left alone unless `seed` is set.
+async fn sync_episodes(conn: &Connection, item_id: Uuid, progress: u32, seed: bool) -> Result<(), StorageError> {
+    let mut rows = conn
+        .query(
+            "SELECT episode, watched FROM episodes WHERE item_id = ?1 ORDER BY episode",
+            libsql::params![item_id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    let (mut known, mut watched) = (0usize, Vec::new());
+    while let Some(row) = rows
+        .next()
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?
+    {
+        known += 1;
+        if row.get::<i64>(1).unwrap_or(0) != 0 {
+            watched.push(row.get::<i64>(0).unwrap_or(0) as u32);
+        }
+    }
+    drop(rows);
+    if known == 0 && !seed {
+        return Ok(());
+    }
+
+    let progress = progress as usize;
+    let changes: Vec<(u32, bool)> = if watched.len() > progress {
+        watched[progress..].iter().map(|&n| (n, false)).collect()
+    } else {
+        (1..)
+            .filter(|n| !watched.contains(n))
+            .take(progress - watched.len())
+            .map(|n| (n, true))
+            .collect()
+    };
+    for (episode, watched) in changes {
+        conn.execute(
+            "INSERT INTO episodes (item_id, episode, watched, watched_at) VALUES (?1, ?2, ?3, NULL)
+             ON CONFLICT (item_id, episode) DO UPDATE SET watched = excluded.watched, watched_at = NULL",
+            libsql::params![item_id.to_string(), episode as i64, watched as i64],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// `created_at` and `updated_at` of an item about to be written over
+/// `old`.  The creation time never moves once stored; the update time
+/// moves to `now` only when something the user tracks changed (not the
+/// timestamps, `latest_available` or `downloaded`).
+/// Items without a stored version (new ones, restored archives) keep the
+/// times they carry.
+fn timestamps(old: Option<&MediaItem>, item: &MediaItem, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let Some(old) = old else {
+        let created = item.created_at.unwrap_or(now);
+        return (created, item.updated_at.unwrap_or(created));
+    };
+    let created = old.created_at.or(item.created_at).unwrap_or(now);
+    let tracked = |i: &MediaItem| MediaItem {
+        created_at: None,
+        updated_at: None,
+        latest_available: None,
+        downloaded: false,
+        ..i.clone()
+    };
+    let unchanged = tracked(old) == tracked(item);
+    let updated = match old.updated_at {
+        Some(updated) if unchanged => updated,
+        _ => now,
+    };
+    (created, updated)
+}
+
+/// Runs `PRAGMA {setting}`, which answers with a row `execute` refuses;
+/// nothing happens until that row is read.
+async fn pragma(conn: &Connection, setting: &str) -> Result<(), StorageError> {
+    let mut rows = conn
+        .query(&format!("PRAGMA {setting}"), ())
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    rows.next().await.map_err(|e| StorageError::Database(e.to_string()))?;
+    Ok(())
+}
+
+async fn fetch_item(conn: &Connection, id: Uuid) -> Result<Option<MediaItem>, StorageError> {
+    let mut rows = conn
+        .query(
+            &format!("SELECT {ITEM_COLUMNS} FROM media_items WHERE id = ?1"),
+            libsql::params![id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+    match rows
+        .next()
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?
+    {
+        Some(row) => Ok(Some(row_to_media_item(&row)?)),
+        None => Ok(None),
+    }
+}
+
+/// The API media type from the `media_type`, `source IS 'anilist'` and
+/// `readable_kind` columns starting at `index`.
+fn api_media_type(row: &libsql::Row, index: i32) -> Result<String, StorageError> {
+    let media_type: String = row.get(index).map_err(|e| StorageError::Database(e.to_string()))?;
+    Ok(match media_type.as_str() {
+        "series" if row.get::<i64>(index + 1).unwrap_or(0) != 0 => "anime".into(),
+        "readable" => readable_str(&parse_readable_kind(opt_text(row, index + 2).as_deref())).into(),
+        _ => media_type,
+    })
+}
+
+/// Reads `item_id, progress, delta, recorded_at` rows of `progress_history`.
+async fn collect_progress_events(mut rows: libsql::Rows) -> Result<Vec<ProgressEvent>, StorageError> {
+    let mut events = Vec::new();
+    while let Some(row) = rows
+        .next()
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?
+    {
+        let item_id: String = row
+            .get::<String>(0)
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let recorded_at: String = row
+            .get::<String>(3)
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        events.push(ProgressEvent {
+            item_id: Uuid::parse_str(&item_id)
+                .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?,
+            progress: row.get::<i64>(1).unwrap_or(0) as u32,
+            delta: row.get::<i64>(2).unwrap_or(0),
+            recorded_at: parse_timestamp(&recorded_at)?,
+        });
+    }
+    Ok(events)
+}
+
+/// Writes the audit log (`history`) and `progress_history` rows describing
+/// how `new` differs from the stored version `old`.
+async fn record_changes(
+    conn: &Connection,
+    old: Option<&MediaItem>,
+    new: &MediaItem,
+    job_id: Option<i64>,
+) -> Result<(), StorageError> {
+    let now = format_timestamp(Utc::now());
+
+    for mut change in history::diff(old, new) {
+        if change.kind == HistoryKind::PassCompleted {
+            change.new_value = completed_at(conn, new.id).await?;
+        }
+        insert_history(conn, new, &change, &now, job_id).await?;
+
+        // Progress of brand-new items is not activity, so only updates of
+        // existing items feed the velocity log.
+        if change.kind == HistoryKind::ProgressUpdated {
+            let before: i64 = change.old_value.as_deref().and_then(|v| v.parse().ok()).unwrap_or(0);
+            let after: i64 = change.new_value.as_deref().and_then(|v| v.parse().ok()).unwrap_or(0);
+            conn.execute(
+                "INSERT INTO progress_history (item_id, progress, delta, recorded_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                libsql::params![new.id.to_string(), after, after - before, now.clone()],
+            )
+            .await
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// When the audit log last saw the item completed, if ever.
+async fn completed_at(conn: &Connection, item_id: Uuid) -> Result<Option<String>, StorageError> {
+    let mut rows = conn
+        .query(
+            "SELECT recorded_at FROM history
+             WHERE item_id = ?1 AND event IN ('added', 'status_changed') AND new_value = 'completed'
+             ORDER BY id DESC LIMIT 1",
+            libsql::params![item_id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    let row = rows.next().await.map_err(|e| StorageError::Database(e.to_string()))?;
+    Ok(row.and_then(|row| opt_text(&row, 0)))
+}
+
+/// Removes an item together with everything stored about it except its
+/// audit history.
+async fn delete_item_rows(conn: &Connection, id: Uuid) -> Result<(), StorageError> {
+    for table in [
+        "media_items",
+        "progress_history",
+        "episodes",
+        "external_refs",
+        "focus_pins",
+        "relations",
+        "rankings",
+    ] {
+        let column = if table == "media_items" { "id" } else { "item_id" };
+        conn.execute(
+            &format!("DELETE FROM {table} WHERE {column} = ?1"),
+            libsql::params![id.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Adds archived activity rows.  Episode marks are merged with existing
+/// ones: watched wins and the first watch time is kept.
+async fn write_activity(conn: &Connection, activity: &Activity) -> Result<(), StorageError> {
+    for e in &activity.episodes {
+        conn.execute(
+            "INSERT INTO episodes (item_id, episode, watched, watched_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (item_id, episode) DO UPDATE SET
+                 watched = MAX(watched, excluded.watched),
+                 watched_at = COALESCE(watched_at, excluded.watched_at)",
+            libsql::params![
+                e.item_id.to_string(),
+                e.episode as i64,
+                e.watched as i64,
+                e.watched_at.map(format_timestamp),
+            ],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    for p in &activity.progress {
+        conn.execute(
+            "INSERT INTO progress_history (item_id, progress, delta, recorded_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            libsql::params![
+                p.item_id.to_string(),
+                p.progress as i64,
+                p.delta,
+                format_timestamp(p.recorded_at),
+            ],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    for h in &activity.history {
+        conn.execute(
+            "INSERT INTO history (item_id, title, event, old_value, new_value, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            libsql::params![
+                h.item_id.to_string(),
+                h.title.clone(),
+                h.event.clone(),
+                h.old_value.clone(),
+                h.new_value.clone(),
+                format_timestamp(h.recorded_at),
+            ],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Stores each top-level settings field as its own row.
+async fn write_settings(conn: &Connection, settings: &Settings) -> Result<(), StorageError> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(settings)? else {
+        return Err(StorageError::Corruption("settings must serialize to an object".into()));
+    };
+    for (key, value) in fields {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            libsql::params![key, value.to_string()],
+        )
+        .await
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+    }
+    Ok(())
+}
+
+async fn record_deletion(conn: &Connection, item: &MediaItem) -> Result<(), StorageError> {
+    let change = HistoryChange {
+        kind: HistoryKind::Deleted,
+        old_value: None,
+        new_value: None,
+    };
+    insert_history(conn, item, &change, &format_timestamp(Utc::now()), None).await
+}
+
+async fn insert_history(
+    conn: &Connection,
+    item: &MediaItem,
+    change: &HistoryChange,
+    recorded_at: &str,
+    job_id: Option<i64>,
+) -> Result<(), StorageError> {
+    conn.execute(
+        "INSERT INTO history (item_id, title, event, old_value, new_value, recorded_at, job_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        libsql::params![
+            item.id.to_string(),
+            item.title.clone(),
+            change.kind.as_str(),
+            change.old_value.clone(),
+            change.new_value.clone(),
+            recorded_at.to_string(),
+            job_id,
         ],
     )
     .await
@@ -281,16 +3240,28 @@ async fn insert_item_in_tx(
     Ok(())
 }
 
-fn decompose_media_type(
-    mt: &MediaItemType,
-) -> (
+/// Timestamps are stored as RFC 3339 UTC strings, which sort lexically.
+fn format_timestamp(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, StorageError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|ts| ts.with_timezone(&Utc))
+        .map_err(|e| StorageError::Corruption(format!("Invalid timestamp '{s}': {e}")))
+}
+
+/// (media_type, readable_kind, watch_status, read_status, progress_cur, progress_tot)
+type MediaTypeColumns = (
     &'static str,
     Option<&'static str>,
     Option<&'static str>,
     Option<&'static str>,
     u32,
     Option<u32>,
-) {
+);
+
+fn decompose_media_type(mt: &MediaItemType) -> MediaTypeColumns {
     match mt {
         MediaItemType::Movie(ws) => ("movie", None, Some(watch_str(ws)), None, 0, None),
         MediaItemType::Series(p, ws) => {
@@ -307,6 +3278,26 @@ fn decompose_media_type(
     }
 }
 
+fn opt_text(row: &libsql::Row, idx: i32) -> Option<String> {
+    match row.get::<libsql::Value>(idx).ok()? {
+        libsql::Value::Text(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn row_uuid(row: &libsql::Row, idx: i32) -> Result<Uuid, StorageError> {
+    let s: String = row.get(idx).map_err(|e| StorageError::Database(e.to_string()))?;
+    Uuid::parse_str(&s).map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))
+}
+
+fn opt_int(row: &libsql::Row, idx: i32) -> Option<i64> {
+    match row.get::<libsql::Value>(idx).ok()? {
+        libsql::Value::Integer(i) => Some(i),
+        _ => None,
+    }
+}
+
+/// Maps a row selected with `ITEM_COLUMNS` back into a `MediaItem`.
 fn row_to_media_item(row: &libsql::Row) -> Result<MediaItem, StorageError> {
     let id_str: String = row
         .get::<String>(0)
@@ -317,71 +3308,40 @@ fn row_to_media_item(row: &libsql::Row) -> Result<MediaItem, StorageError> {
     let media_type_str: String = row
         .get::<String>(2)
         .map_err(|e| StorageError::Database(e.to_string()))?;
-    let readable_kind: Option<String> = row
-        .get::<libsql::Value>(3)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Text(s) => Some(s),
-            _ => None,
-        });
-    let watch_status: Option<String> = row
-        .get::<libsql::Value>(4)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Text(s) => Some(s),
-            _ => None,
-        });
-    let read_status: Option<String> = row
-        .get::<libsql::Value>(5)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Text(s) => Some(s),
-            _ => None,
-        });
+    let readable_kind = opt_text(row, 3);
+    let watch_status = opt_text(row, 4);
+    let read_status = opt_text(row, 5);
     let progress_cur: i64 = row.get::<i64>(6).unwrap_or(0);
-    let progress_tot: Option<i64> = row
-        .get::<libsql::Value>(7)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Integer(i) => Some(i),
-            _ => None,
-        });
-    let score: Option<i64> = row
-        .get::<libsql::Value>(8)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Integer(i) => Some(i),
-            _ => None,
-        });
-    let global_score: Option<i64> = row
-        .get::<libsql::Value>(9)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Integer(i) => Some(i),
-            _ => None,
-        });
-    let external_id: Option<i64> = row
-        .get::<libsql::Value>(10)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Integer(i) => Some(i),
-            _ => None,
-        });
-    let poster_url: Option<String> = row
-        .get::<libsql::Value>(11)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Text(s) => Some(s),
-            _ => None,
-        });
-    let source: Option<String> = row
-        .get::<libsql::Value>(12)
-        .ok()
-        .and_then(|v| match v {
-            libsql::Value::Text(s) => Some(s),
-            _ => None,
-        });
+    let progress_tot = opt_int(row, 7);
+    let score = opt_int(row, 8);
+    let global_score = opt_int(row, 9);
+    let external_id = opt_text(row, 10);
+    let poster_url = opt_text(row, 11);
+    let source = opt_text(row, 12);
     let tags_json: String = row.get::<String>(13).unwrap_or_else(|_| "[]".into());
+    let repeat_count: i64 = row.get::<i64>(14).unwrap_or(0);
+    let notes = opt_text(row, 15);
+    let notes_spoiler = opt_int(row, 16).unwrap_or(0) != 0;
+    let alt_titles_json = opt_text(row, 17).unwrap_or_else(|| "[]".into());
+    let external_refs_json = opt_text(row, 18).unwrap_or_else(|| "[]".into());
+    let scheduled_for = opt_text(row, 19).map(|s| parse_timestamp(&s)).transpose()?;
+    let overview = opt_text(row, 20);
+    let genres_json = opt_text(row, 21).unwrap_or_else(|| "[]".into());
+    let runtime_minutes = opt_int(row, 22);
+    let season_count = opt_int(row, 23);
+    let franchise = opt_text(row, 24);
+    let custom_fields_json = opt_text(row, 25).unwrap_or_else(|| "{}".into());
+    let volume_cur = opt_int(row, 26);
+    let volume_tot = opt_int(row, 27);
+    let prefer_volumes = opt_int(row, 28).unwrap_or(0) != 0;
+    let created_at = opt_text(row, 29).map(|s| parse_timestamp(&s)).transpose()?;
+    let updated_at = opt_text(row, 30).map(|s| parse_timestamp(&s)).transpose()?;
+    let creator = opt_text(row, 31);
+    let latest_available = opt_int(row, 32);
+    let downloaded = opt_int(row, 33).unwrap_or(0) != 0;
+    let content_rating = opt_text(row, 34).and_then(|r| ContentRating::parse(&r));
+    let hidden = opt_int(row, 35).unwrap_or(0) != 0;
+    let favorite = opt_int(row, 36).unwrap_or(0) != 0;
 
     let id = Uuid::parse_str(&id_str)
         .map_err(|e| StorageError::Corruption(format!("Invalid UUID: {e}")))?;
@@ -413,6 +3373,12 @@ fn row_to_media_item(row: &libsql::Row) -> Result<MediaItem, StorageError> {
     };
 
     let tags: HashSet<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+    let alt_titles: Vec<String> = serde_json::from_str(&alt_titles_json).unwrap_or_default();
+    let external_refs: HashSet<String> =
+        serde_json::from_str(&external_refs_json).unwrap_or_default();
+    let genres: Vec<String> = serde_json::from_str(&genres_json).unwrap_or_default();
+    let custom_fields: BTreeMap<String, String> =
+        serde_json::from_str(&custom_fields_json).unwrap_or_default();
 
     Ok(MediaItem {
         id,
@@ -420,10 +3386,35 @@ fn row_to_media_item(row: &libsql::Row) -> Result<MediaItem, StorageError> {
         media_type,
         score: score.map(|s| s as u8),
         global_score: global_score.map(|s| s as u8),
-        external_id: external_id.map(|e| e as u32),
+        external_id,
         poster_url,
         source,
         tags,
+        repeat_count: repeat_count as u32,
+        notes,
+        notes_spoiler,
+        alt_titles,
+        external_refs,
+        scheduled_for,
+        overview,
+        genres,
+        runtime_minutes: runtime_minutes.map(|m| m as u32),
+        season_count: season_count.map(|s| s as u32),
+        franchise,
+        custom_fields,
+        volumes: volume_cur.map(|current| Progress {
+            current: current as u32,
+            total: volume_tot.map(|t| t as u32),
+        }),
+        prefer_volumes,
+        creator,
+        content_rating,
+        favorite,
+        hidden,
+        latest_available: latest_available.map(|c| c as u32),
+        downloaded,
+        created_at,
+        updated_at,
     })
 }
 
@@ -457,6 +3448,7 @@ fn readable_str(k: &ReadableKind) -> &'static str {
         ReadableKind::Manga => "manga",
         ReadableKind::Manhwa => "manhwa",
         ReadableKind::Webtoon => "webtoon",
+        ReadableKind::Audiobook => "audiobook",
     }
 }
 
@@ -490,6 +3482,263 @@ fn parse_readable_kind(s: Option<&str>) -> ReadableKind {
         Some("manga") => ReadableKind::Manga,
         Some("manhwa") => ReadableKind::Manhwa,
         Some("webtoon") => ReadableKind::Webtoon,
+        Some("audiobook") => ReadableKind::Audiobook,
         _ => ReadableKind::Book,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Instant;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_get_items_keeps_the_requested_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let mut ids = Vec::new();
+        for title in ["Alien", "Brazil", "Heat"] {
+            let mut item = MediaItem::new(title.into(), MediaItemType::Movie(WatchStatus::Completed));
+            db.upsert_item(&mut item).await.unwrap();
+            ids.push(item.id);
+        }
+
+        let wanted = [ids[2], Uuid::new_v4(), ids[0]];
+        let titles: Vec<String> = db.get_items(&wanted).await.unwrap().into_iter().map(|i| i.title).collect();
+        assert_eq!(titles, ["Heat", "Alien"]);
+        assert!(db.get_items(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_time_spent_counts_runtime_pages_and_listening() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let progress = |total| Progress { current: 0, total: Some(total) };
+        let mut series = MediaItem::new("Mushishi".into(), MediaItemType::Series(progress(12), WatchStatus::Watching));
+        series.runtime_minutes = Some(30);
+        let mut book = MediaItem::new(
+            "Dune".into(),
+            MediaItemType::Readable(ReadableKind::Book, progress(600), ReadStatus::Reading),
+        );
+        let mut audiobook = MediaItem::new(
+            "Dune".into(),
+            MediaItemType::Readable(ReadableKind::Audiobook, progress(1263), ReadStatus::Reading),
+        );
+        let mut movie = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        movie.runtime_minutes = Some(170);
+        let mut untimed = MediaItem::new("Alien".into(), MediaItemType::Movie(WatchStatus::Completed));
+        for item in [&mut series, &mut book, &mut audiobook, &mut movie, &mut untimed] {
+            db.upsert_item(item).await.unwrap();
+        }
+
+        // Dated progress: 10 episodes, 120 pages, 90 minutes, one movie.
+        let set = |item: &mut MediaItem, current| match &mut item.media_type {
+            MediaItemType::Series(p, _) | MediaItemType::Readable(_, p, _) => p.current = current,
+            _ => {}
+        };
+        set(&mut series, 10);
+        set(&mut book, 120);
+        set(&mut audiobook, 90);
+        movie.media_type = MediaItemType::Movie(WatchStatus::Completed);
+        // A finished earlier run counts in full, but is not dated.
+        series.repeat_count = 1;
+        for item in [&mut series, &mut book, &mut audiobook, &mut movie] {
+            db.upsert_item(item).await.unwrap();
+        }
+
+        let stats = db.extended_stats().await.unwrap();
+        // 22 episodes of 30 minutes and 170 minutes of movie.
+        assert_eq!(stats.time_spent.hours_watched, 13.8);
+        assert_eq!(stats.time_spent.pages_read, 120);
+        assert_eq!(stats.time_spent.hours_listened, 1.5);
+        assert_eq!(stats.time_by_media_type["series"].hours_watched, 11.0);
+        assert_eq!(stats.time_by_media_type["book"].pages_read, 120);
+        assert_eq!(stats.without_runtime, 1);
+
+        let year = Utc::now().format("%Y").to_string();
+        let dated = &stats.time_by_year[&year];
+        assert_eq!((dated.hours_watched, dated.pages_read, dated.hours_listened), (7.8, 120, 1.5));
+    }
+
+    #[tokio::test]
+    async fn test_rewatches_keep_each_finished_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let mut item = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+        item.set_score(8.0);
+        db.upsert_item(&mut item).await.unwrap();
+
+        assert!(item.start_rewatch());
+        db.upsert_item(&mut item).await.unwrap();
+        item.media_type = MediaItemType::Movie(WatchStatus::Completed);
+        item.set_score(9.0);
+        db.upsert_item(&mut item).await.unwrap();
+        assert!(item.start_rewatch());
+        db.upsert_item(&mut item).await.unwrap();
+
+        let history = db.item_history(item.id).await.unwrap();
+        let completions: Vec<String> = history
+            .iter()
+            .filter(|h| h.new_value.as_deref() == Some("completed"))
+            .map(|h| format_timestamp(h.recorded_at))
+            .collect();
+        let passes: Vec<(Option<&str>, Option<String>)> = history
+            .iter()
+            .filter(|h| h.kind == HistoryKind::PassCompleted)
+            .map(|h| (h.old_value.as_deref(), h.new_value.clone()))
+            .collect();
+        // Newest first.
+        assert_eq!(completions.len(), 2);
+        assert_eq!(
+            passes,
+            [(Some("9.0"), Some(completions[0].clone())), (Some("8.0"), Some(completions[1].clone()))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rollback_upgrades_old_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let mut item = MediaItem::new(
+            "Dune".into(),
+            MediaItemType::Readable(ReadableKind::Book, Progress { current: 0, total: None }, ReadStatus::PlanToRead),
+        );
+        item.source = Some("openlibrary".into());
+        item.external_id = Some("OL27448W".into());
+        item.favorite = true;
+        db.upsert_item(&mut item).await.unwrap();
+
+        // A snapshot from before text ids and the favorite field.
+        let mut before = serde_json::to_value(&item).unwrap();
+        before["title"] = "Dune (1965)".into();
+        before["externalId"] = 27448.into();
+        before["tags"] = serde_json::json!(["favorite"]);
+        before.as_object_mut().unwrap().remove("favorite");
+        db.conn
+            .execute(
+                "INSERT INTO jobs (id, kind, status, created_at) VALUES (1, 'import', 'succeeded', ?1)",
+                libsql::params![format_timestamp(Utc::now())],
+            )
+            .await
+            .unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO job_items (job_id, item_id, before) VALUES (1, ?1, ?2)",
+                libsql::params![item.id.to_string(), before.to_string()],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(db.rollback_job(1).await.unwrap(), 1);
+        let restored = db.get_item(item.id).await.unwrap().unwrap();
+        assert_eq!(restored.title, "Dune (1965)");
+        assert_eq!(restored.external_id.as_deref(), Some("OL27448W"));
+        assert!(restored.favorite && restored.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rollback_restores_and_reports_later_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let mut alien = MediaItem::new("Alien".into(), MediaItemType::Movie(WatchStatus::PlanToWatch));
+        db.upsert_item(&mut alien).await.unwrap();
+        let stored = db.get_item(alien.id).await.unwrap().unwrap();
+        let merged = MediaItem { title: "Alien (1979)".into(), ..stored.clone() };
+        let heat = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+        let plan = MergePlan {
+            writes: vec![(Some(stored), merged), (None, heat.clone())],
+            activity: Activity::default(),
+            reasons: HashMap::new(),
+        };
+        let job = db.apply_merge(&plan).await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        let finished_at = job.finished_at.unwrap();
+        assert!(db.rollback_conflicts(job.id, finished_at).await.unwrap().is_empty());
+
+        // Timestamps have whole seconds.
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        let mut edited = db.get_item(heat.id).await.unwrap().unwrap();
+        edited.title = "Heat (1995)".into();
+        db.upsert_item(&mut edited).await.unwrap();
+        let conflicts = db.rollback_conflicts(job.id, finished_at).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].item_id, conflicts[0].title.as_str()), (heat.id, "Heat (1995)"));
+        assert!(conflicts[0].changed_at.is_some_and(|t| t > finished_at));
+
+        assert_eq!(db.rollback_job(job.id).await.unwrap(), 2);
+        assert_eq!(db.get_item(alien.id).await.unwrap().unwrap().title, "Alien");
+        assert!(db.get_item(heat.id).await.unwrap().is_none());
+        assert_eq!(db.get_job(job.id).await.unwrap().unwrap().status, JobStatus::RolledBack);
+    }
+
+    #[tokio::test]
+    async fn test_episode_rows_follow_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("kars.db").to_string_lossy()).await.unwrap();
+        let series = |current| MediaItemType::Series(Progress { current, total: Some(12) }, WatchStatus::Completed);
+        let mut item = MediaItem::new("Mushishi".into(), series(12));
+        db.upsert_item(&mut item).await.unwrap();
+        let watched = |episodes: Vec<EpisodeRecord>| {
+            episodes.iter().filter(|e| e.watched).map(|e| e.number).collect::<Vec<_>>()
+        };
+
+        // Twelve episodes counted before any was marked stay counted.
+        assert_eq!(db.set_episodes_watched(item.id, 12, &[4], true).await.unwrap(), 12);
+        assert_eq!(watched(db.list_episodes(item.id).await.unwrap()).len(), 12);
+
+        // A rewatch starts over, so marking one episode counts one.
+        assert!(item.start_rewatch());
+        db.upsert_item(&mut item).await.unwrap();
+        assert!(watched(db.list_episodes(item.id).await.unwrap()).is_empty());
+        assert_eq!(db.set_episodes_watched(item.id, 0, &[2], true).await.unwrap(), 1);
+
+        // Progress moved another way fills in from the first episode.
+        item.media_type = series(3);
+        db.upsert_item(&mut item).await.unwrap();
+        assert_eq!(watched(db.list_episodes(item.id).await.unwrap()), [1, 2, 3]);
+    }
+
+    /// Reads per second when every request takes the exclusive guard (as
+    /// with the old `Mutex`) and when reads share the lock.  Run with
+    /// `cargo test --release -p kars bench_concurrent_reads -- --ignored --nocapture`;
+    /// the shared figure grows with the number of cores.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn bench_concurrent_reads() {
+        const ITEMS: usize = 2_000;
+        const REQUESTS: usize = 256;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::local(&dir.path().join("bench.db").to_string_lossy()).await.unwrap();
+        for i in 0..ITEMS {
+            let mut item = MediaItem::new(format!("Item {i}"), MediaItemType::Movie(WatchStatus::Completed));
+            db.upsert_item(&mut item).await.unwrap();
+        }
+        let db = Arc::new(RwLock::new(db));
+
+        for shared in [false, true] {
+            let start = Instant::now();
+            let tasks: Vec<_> = (0..REQUESTS)
+                .map(|_| {
+                    let db = db.clone();
+                    tokio::spawn(async move {
+                        let items = if shared {
+                            db.read().await.load_all().await
+                        } else {
+                            db.write().await.load_all().await
+                        };
+                        assert_eq!(items.unwrap().len(), ITEMS);
+                    })
+                })
+                .collect();
+            for task in tasks {
+                task.await.unwrap();
+            }
+            let per_second = REQUESTS as f64 / start.elapsed().as_secs_f64();
+            let lock = if shared { "shared" } else { "exclusive" };
+            println!("{lock:>9}: {per_second:.0} reads/s of {ITEMS} items");
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::core::models::{ContentRating, MediaItemType, Progress, ReadStatus, ReadableKind};
+use crate::core::search::{
+    LocalizedTitles, MediaDetails, MediaSearchType, SearchError, SearchProvider, SearchResult, SEARCH_PAGE_SIZE,
+};
+use crate::infra::anilist::plain_text;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+const VOLUMES_URL: &str = "https://www.googleapis.com/books/v1/volumes";
+
+// ── Response types ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct VolumesResponse {
+    /// Absent when nothing matched.
+    #[serde(default)]
+    items: Vec<Volume>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Volume {
+    id: String,
+    volume_info: VolumeInfo,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeInfo {
+    title: Option<String>,
+    #[serde(default)]
+    authors: Vec<String>,
+    /// `2005`, `2005-08` or `2005-08-01`.
+    published_date: Option<String>,
+    /// HTML.
+    description: Option<String>,
+    page_count: Option<u32>,
+    #[serde(default)]
+    categories: Vec<String>,
+    /// 1.0-5.0
+    average_rating: Option<f64>,
+    image_links: Option<ImageLinks>,
+    /// `MATURE` or `NOT_MATURE`.
+    maturity_rating: Option<String>,
+    #[serde(default)]
+    industry_identifiers: Vec<IndustryIdentifier>,
+}
+
+#[derive(Deserialize)]
+struct ImageLinks {
+    thumbnail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IndustryIdentifier {
+    /// `ISBN_10`, `ISBN_13` or `OTHER`.
+    #[serde(rename = "type")]
+    kind: String,
+    identifier: String,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+/// Google Books: books Open Library lacks, and ISBNs of recent editions.
+/// Works without a key at a low daily quota; `GOOGLE_BOOKS_API_KEY`
+/// raises it.
+pub struct GoogleBooksClient {
+    client: Client,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+}
+
+impl GoogleBooksClient {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            api_key: std::env::var("GOOGLE_BOOKS_API_KEY").ok().filter(|k| !k.trim().is_empty()),
+            retry: RetryPolicy::from_env(),
+        }
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, url: &str, params: &[(&str, String)]) -> Result<Option<T>, SearchError> {
+        let mut request = self.client.get(url).query(params);
+        if let Some(key) = &self.api_key {
+            request = request.query(&[("key", key)]);
+        }
+        let resp = send_with_retry(&self.retry, request)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Google Books returned {}", resp.status())));
+        }
+        decode_json("googlebooks", resp).map(Some)
+    }
+
+    fn volumes(&self, query: &str, page: u32) -> Result<Vec<SearchResult>, SearchError> {
+        let params = [
+            ("q", query.to_string()),
+            ("printType", "books".to_string()),
+            ("maxResults", SEARCH_PAGE_SIZE.to_string()),
+            ("startIndex", (page.saturating_sub(1) * SEARCH_PAGE_SIZE).to_string()),
+        ];
+        let data: Option<VolumesResponse> = self.get(VOLUMES_URL, &params)?;
+        Ok(data.map(|d| d.items).unwrap_or_default().into_iter().filter_map(map_volume).collect())
+    }
+
+    fn volume(&self, id: &str) -> Result<Option<Volume>, SearchError> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+            return Ok(None);
+        }
+        self.get(&format!("{VOLUMES_URL}/{id}"), &[])
+    }
+}
+
+fn map_volume(volume: Volume) -> Option<SearchResult> {
+    let info = volume.volume_info;
+    let title = info.title?;
+    let year = info.published_date.as_deref().and_then(|d| d.get(..4)).and_then(|y| y.parse::<i32>().ok());
+    let creator = info.authors.into_iter().next();
+    let author = creator.clone().unwrap_or_else(|| "Unknown".into());
+    let label_year = year.map_or_else(|| "?".to_string(), |y| y.to_string());
+
+    let mut external_refs = vec![format!("googlebooks:{}", volume.id)];
+    external_refs.extend(
+        info.industry_identifiers
+            .iter()
+            .filter(|i| i.kind == "ISBN_13")
+            .map(|i| format!("isbn:{}", i.identifier)),
+    );
+
+    Some(SearchResult {
+        title,
+        media_type: MediaItemType::Readable(
+            ReadableKind::Book,
+            Progress { current: 0, total: info.page_count.filter(|&p| p > 0) },
+            ReadStatus::PlanToRead,
+        ),
+        // averageRating: 1.0-5.0 → our global_score: 0-100
+        global_score: info.average_rating.map(|r| ((r.clamp(0.0, 5.0) / 5.0) * 100.0).round() as u8),
+        external_id: Some(volume.id),
+        // Thumbnails are linked over plain http.
+        poster_url: info
+            .image_links
+            .and_then(|l| l.thumbnail)
+            .map(|url| url.replacen("http://", "https://", 1)),
+        source: "googlebooks",
+        format_label: format!("{author} ({label_year})"),
+        alt_titles: Vec::new(),
+        external_refs,
+        creator,
+        content_rating: info.maturity_rating.as_deref().map(|r| match r {
+            "MATURE" => ContentRating::Erotica,
+            _ => ContentRating::Safe,
+        }),
+        titles: LocalizedTitles::default(),
+        year,
+    })
+}
+
+impl SearchProvider for GoogleBooksClient {
+    fn name(&self) -> &str {
+        "Google Books"
+    }
+
+    fn source(&self) -> &'static str {
+        "googlebooks"
+    }
+
+    fn supported_types(&self) -> &[MediaSearchType] {
+        &[MediaSearchType::Book]
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        media_type: MediaSearchType,
+        page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Book {
+            return Ok(Vec::new());
+        }
+        self.volumes(query, page)
+    }
+
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Book {
+            return Ok(None);
+        }
+        Ok(self.volume(external_id)?.and_then(map_volume))
+    }
+
+    fn details(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        if media_type != MediaSearchType::Book {
+            return Ok(None);
+        }
+        Ok(self.volume(external_id)?.map(|v| MediaDetails {
+            overview: v.volume_info.description.as_deref().map(plain_text),
+            // Categories read like `Fiction / Science Fiction / General`.
+            genres: v
+                .volume_info
+                .categories
+                .iter()
+                .flat_map(|c| c.split(" / "))
+                .filter(|g| *g != "General")
+                .fold(Vec::new(), |mut genres, g| {
+                    if !genres.iter().any(|known: &String| known == g) {
+                        genres.push(g.to_string());
+                    }
+                    genres
+                }),
+            ..MediaDetails::default()
+        }))
+    }
+
+    fn lookup_isbn(&self, isbn: &str) -> Result<Option<SearchResult>, SearchError> {
+        Ok(self.volumes(&format!("isbn:{isbn}"), 1)?.into_iter().next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volumes_map_to_books() {
+        let body = r#"{"items": [{
+            "id": "B1hSG45JCX4C",
+            "volumeInfo": {
+                "title": "Dune",
+                "authors": ["Frank Herbert"],
+                "publishedDate": "1990-09-01",
+                "pageCount": 535,
+                "averageRating": 4.5,
+                "maturityRating": "NOT_MATURE",
+                "imageLinks": {"thumbnail": "http://books.google.com/books/content?id=B1hSG45JCX4C&zoom=1"},
+                "industryIdentifiers": [
+                    {"type": "ISBN_10", "identifier": "0441172717"},
+                    {"type": "ISBN_13", "identifier": "9780441172719"}
+                ]
+            }
+        }, {"id": "untitled", "volumeInfo": {}}]}"#;
+        let data: VolumesResponse = serde_json::from_str(body).unwrap();
+        let books: Vec<SearchResult> = data.items.into_iter().filter_map(map_volume).collect();
+
+        assert_eq!(books.len(), 1);
+        let dune = &books[0];
+        assert_eq!((dune.title.as_str(), dune.year, dune.global_score), ("Dune", Some(1990), Some(90)));
+        assert_eq!(dune.format_label, "Frank Herbert (1990)");
+        assert_eq!(dune.external_refs, ["googlebooks:B1hSG45JCX4C", "isbn:9780441172719"]);
+        assert!(dune.poster_url.as_deref().is_some_and(|url| url.starts_with("https://")));
+        assert!(matches!(dune.media_type, MediaItemType::Readable(ReadableKind::Book, Progress { total: Some(535), .. }, _)));
+        assert!(serde_json::from_str::<VolumesResponse>("{\"totalItems\": 0}").unwrap().items.is_empty());
+    }
+}
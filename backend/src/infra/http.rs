@@ -0,0 +1,229 @@
+use crate::core::search::SearchError;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Maximum length of a payload excerpt kept for diagnostics.
+const SNIPPET_LEN: usize = 1500;
+
+/// JSON keys whose values are never kept in diagnostics.
+const SENSITIVE_KEYS: &[&str] = &[
+    "token",
+    "access_token",
+    "refresh_token",
+    "api_key",
+    "apikey",
+    "secret",
+    "password",
+    "authorization",
+    "email",
+];
+
+/// How provider requests are retried after transient failures: timeouts,
+/// refused connections, 429 and 5xx answers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Tries in total, including the first; 1 turns retries off.
+    pub attempts: u32,
+    /// Wait before the first retry; doubled for every further one.
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    /// Share of each wait (0.0-1.0) that is randomized, so that clients
+    /// failing together do not retry in lockstep.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The defaults, overridden by `PROVIDER_RETRY_ATTEMPTS`,
+    /// `PROVIDER_RETRY_BACKOFF_MS` and `PROVIDER_RETRY_JITTER` when set.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            attempts: var("PROVIDER_RETRY_ATTEMPTS").unwrap_or(default.attempts).max(1),
+            backoff: var("PROVIDER_RETRY_BACKOFF_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.backoff),
+            jitter: var("PROVIDER_RETRY_JITTER")
+                .unwrap_or(default.jitter)
+                .clamp(0.0, 1.0),
+            ..default
+        }
+    }
+
+    /// Wait before retry number `retry` (0 for the first), with `random`
+    /// in 0.0-1.0 taking off up to `jitter` of it.
+    fn delay(&self, retry: u32, random: f64) -> Duration {
+        let full = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        full.mul_f64(1.0 - self.jitter * random)
+    }
+}
+
+/// Sends a provider request, retrying transient failures as `policy`
+/// says.  A 429 answer's `Retry-After` (in seconds, capped at
+/// `max_backoff`) is honored.  The last answer is returned as is, so
+/// callers still see the final status.
+pub fn send_with_retry(policy: &RetryPolicy, request: RequestBuilder) -> Result<Response, SearchError> {
+    let mut retry = 0;
+    loop {
+        // Bodies that cannot be cloned (streams) are sent once.
+        let Some(attempt) = request.try_clone() else {
+            return request.send().map_err(|e| SearchError::Network(e.to_string()));
+        };
+        let last = retry + 1 >= policy.attempts;
+        let wait = match attempt.send() {
+            Ok(resp) if last || !is_transient_status(resp.status()) => return Ok(resp),
+            Ok(resp) => retry_after(&resp).map(|d| d.min(policy.max_backoff)),
+            Err(e) if last || !(e.is_timeout() || e.is_connect()) => {
+                return Err(SearchError::Network(e.to_string()));
+            }
+            Err(_) => None,
+        };
+        std::thread::sleep(wait.unwrap_or_else(|| policy.delay(retry, random_unit())));
+        retry += 1;
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn retry_after(resp: &Response) -> Option<Duration> {
+    if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let secs: u64 = resp.headers().get("retry-after")?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+/// A random number in 0.0-1.0, for jitter and ranking pairs.
+pub fn random_unit() -> f64 {
+    (uuid::Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0
+}
+
+/// Decodes a provider response body.  When the body does not match the
+/// expected schema, the error carries a redacted excerpt of the payload
+/// around the failure so schema drift can be diagnosed later.
+pub fn decode_json<T: DeserializeOwned>(provider: &str, resp: Response) -> Result<T, SearchError> {
+    let body = resp
+        .text()
+        .map_err(|e| SearchError::Network(e.to_string()))?;
+    serde_json::from_str(&body).map_err(|e| SearchError::Payload {
+        provider: provider.to_string(),
+        message: e.to_string(),
+        snippet: snippet_around(&redact(&body), e.line(), e.column()),
+    })
+}
+
+/// Masks the string values of sensitive keys with `*`, keeping the body
+/// length (and so the decoder's line/column) unchanged.
+fn redact(body: &str) -> String {
+    let mut chars: Vec<char> = body.chars().collect();
+    let lower: Vec<char> = body.to_lowercase().chars().collect();
+    if lower.len() != chars.len() {
+        // Case folding changed the length; fall back to dropping the body.
+        return "[payload withheld]".into();
+    }
+
+    for key in SENSITIVE_KEYS {
+        let needle: Vec<char> = format!("\"{key}\"").chars().collect();
+        let mut i = 0;
+        while i + needle.len() <= lower.len() {
+            if lower[i..i + needle.len()] != needle[..] {
+                i += 1;
+                continue;
+            }
+            let mut j = i + needle.len();
+            while j < chars.len() && (chars[j].is_whitespace() || chars[j] == ':') {
+                j += 1;
+            }
+            if j < chars.len() && chars[j] == '"' {
+                j += 1;
+                while j < chars.len() && chars[j] != '"' {
+                    let escaped = chars[j] == '\\';
+                    chars[j] = '*';
+                    j += 1;
+                    if escaped && j < chars.len() {
+                        chars[j] = '*';
+                        j += 1;
+                    }
+                }
+            }
+            i = j.max(i + 1);
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Cuts a window of at most `SNIPPET_LEN` characters centred on the
+/// reported error position.
+fn snippet_around(body: &str, line: usize, column: usize) -> String {
+    let chars: Vec<char> = body.chars().collect();
+    if chars.len() <= SNIPPET_LEN {
+        return body.to_string();
+    }
+    let offset: usize = body
+        .lines()
+        .take(line.saturating_sub(1))
+        .map(|l| l.chars().count() + 1)
+        .sum::<usize>()
+        + column;
+    let start = offset
+        .saturating_sub(SNIPPET_LEN / 2)
+        .min(chars.len() - SNIPPET_LEN);
+    let mut snippet: String = chars[start..start + SNIPPET_LEN].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if start + SNIPPET_LEN < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            attempts: 5,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+            jitter: 0.5,
+        };
+        assert_eq!(policy.delay(0, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(1, 0.0), Duration::from_secs(1));
+        assert_eq!(policy.delay(3, 0.0), Duration::from_secs(3));
+        assert_eq!(policy.delay(1, 1.0), Duration::from_millis(500));
+        assert_eq!(policy.delay(40, 0.0), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_redact_masks_sensitive_values_in_place() {
+        let body = r#"{"title": "Dune", "Access_Token": "ab\"c", "score": 8}"#;
+        let redacted = redact(body);
+        assert_eq!(redacted.len(), body.len());
+        assert!(redacted.contains(r#""title": "Dune""#));
+        assert!(redacted.contains(r#""Access_Token": "*****""#));
+        assert!(redacted.contains(r#""score": 8"#));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::core::reading_sync::{ReadUnit, ReadingServer, ServerSeries};
+use crate::core::search::SearchError;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+// ── Response types ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct AuthUser {
+    token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SeriesDto {
+    id: u64,
+    name: String,
+    original_name: Option<String>,
+    localized_name: Option<String>,
+    #[serde(default)]
+    pages_read: u32,
+}
+
+/// Numbers: `minNumber`/`maxNumber` since Kavita 0.8, `number` before.
+/// Loose chapters sit in a volume numbered below 1 (`-100000` or `0`).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeDto {
+    max_number: Option<f64>,
+    number: Option<f64>,
+    #[serde(default)]
+    pages: u32,
+    #[serde(default)]
+    pages_read: u32,
+    #[serde(default)]
+    chapters: Vec<ChapterDto>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChapterDto {
+    max_number: Option<f64>,
+    /// A string such as `"12.5"` before 0.8.
+    number: Option<String>,
+    #[serde(default)]
+    is_special: bool,
+    #[serde(default)]
+    pages: u32,
+    #[serde(default)]
+    pages_read: u32,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+pub struct KavitaClient {
+    client: Client,
+    url: String,
+    api_key: String,
+    retry: RetryPolicy,
+}
+
+impl KavitaClient {
+    pub const NAME: &'static str = "Kavita";
+
+    /// Reads `KAVITA_URL` and `KAVITA_API_KEY` (from the user's Kavita
+    /// settings); None unless both are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        Some(Self {
+            client: Client::new(),
+            url: var("KAVITA_URL")?.trim_end_matches('/').to_string(),
+            api_key: var("KAVITA_API_KEY")?,
+            retry: RetryPolicy::from_env(),
+        })
+    }
+
+    /// Trades the API key for a session token.
+    fn token(&self) -> Result<String, SearchError> {
+        let request = self
+            .client
+            .post(format!("{}/api/Plugin/authenticate", self.url))
+            .query(&[("apiKey", self.api_key.as_str()), ("pluginName", "kars")]);
+        let resp = send_with_retry(&self.retry, request)?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Kavita returned {}", resp.status())));
+        }
+        decode_json::<AuthUser>("kavita", resp).map(|user| user.token)
+    }
+
+    fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+        token: &str,
+    ) -> Result<T, SearchError> {
+        let resp = send_with_retry(&self.retry, request.bearer_auth(token))?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Kavita returned {}", resp.status())));
+        }
+        decode_json("kavita", resp)
+    }
+}
+
+impl ReadingServer for KavitaClient {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn progress(&self) -> Result<Vec<ServerSeries>, SearchError> {
+        let token = self.token()?;
+        // Page size 0 returns every series.
+        let request = self
+            .client
+            .post(format!("{}/api/Series/all-v2", self.url))
+            .query(&[("PageNumber", "1"), ("PageSize", "0")])
+            .json(&json!({ "statements": [], "combination": 1, "limitTo": 0 }));
+        let all: Vec<SeriesDto> = self.send(request, &token)?;
+
+        let mut progress = Vec::new();
+        for series in all.into_iter().filter(|s| s.pages_read > 0) {
+            let request = self
+                .client
+                .get(format!("{}/api/Series/volumes", self.url))
+                .query(&[("seriesId", series.id)]);
+            let volumes: Vec<VolumeDto> = self.send(request, &token)?;
+            progress.extend(series_progress(series, &volumes));
+        }
+        Ok(progress)
+    }
+}
+
+/// The highest chapter and the highest volume read to the end, as one
+/// entry each.
+fn series_progress(series: SeriesDto, volumes: &[VolumeDto]) -> Vec<ServerSeries> {
+    let finished = |pages: u32, read: u32| pages > 0 && read >= pages;
+    let highest = |numbers: &mut dyn Iterator<Item = f64>| {
+        numbers.filter(|n| *n >= 1.0).fold(None, |max: Option<f64>, n| Some(max.map_or(n, |m| m.max(n))))
+    };
+
+    let chapter = highest(
+        &mut volumes
+            .iter()
+            .flat_map(|v| &v.chapters)
+            .filter(|c| !c.is_special && finished(c.pages, c.pages_read))
+            .filter_map(|c| c.max_number.or_else(|| c.number.as_deref()?.parse().ok())),
+    );
+    let volume = highest(
+        &mut volumes
+            .iter()
+            .filter(|v| finished(v.pages, v.pages_read))
+            .filter_map(|v| v.max_number.or(v.number)),
+    );
+
+    let alt_titles: Vec<String> = [series.original_name, series.localized_name]
+        .into_iter()
+        .flatten()
+        .filter(|t| !t.is_empty() && *t != series.name)
+        .collect();
+    [(chapter, ReadUnit::Chapters), (volume, ReadUnit::Volumes)]
+        .into_iter()
+        .filter_map(|(read, unit)| {
+            Some(ServerSeries {
+                title: series.name.clone(),
+                alt_titles: alt_titles.clone(),
+                read: read? as u32,
+                unit,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_progress_takes_highest_finished_numbers() {
+        let series: SeriesDto = serde_json::from_str(
+            r#"{"id": 1, "name": "Berserk", "originalName": "ベルセルク", "localizedName": "", "pagesRead": 500}"#,
+        )
+        .unwrap();
+        let volumes: Vec<VolumeDto> = serde_json::from_str(
+            r#"[{"maxNumber": 1, "pages": 200, "pagesRead": 200,
+                 "chapters": [{"maxNumber": -100000, "pages": 200, "pagesRead": 200}]},
+                {"maxNumber": 2, "pages": 200, "pagesRead": 150, "chapters": []},
+                {"maxNumber": -100000, "pages": 60, "pagesRead": 40,
+                 "chapters": [{"maxNumber": 365, "pages": 20, "pagesRead": 20},
+                              {"number": "366.5", "pages": 20, "pagesRead": 20},
+                              {"maxNumber": 367, "pages": 20, "pagesRead": 0},
+                              {"maxNumber": 900, "isSpecial": true, "pages": 5, "pagesRead": 5}]}]"#,
+        )
+        .unwrap();
+
+        let progress = series_progress(series, &volumes);
+        let read: Vec<(u32, ReadUnit)> = progress.iter().map(|s| (s.read, s.unit)).collect();
+        assert_eq!(read, vec![(366, ReadUnit::Chapters), (1, ReadUnit::Volumes)]);
+        assert_eq!(progress[0].alt_titles, vec!["ベルセルク".to_string()]);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::core::reading_sync::{ReadUnit, ReadingServer, ServerSeries};
+use crate::core::search::SearchError;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+/// Series fetched per request.
+const PAGE_SIZE: &str = "200";
+
+// ── Response types ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct Page {
+    content: Vec<Series>,
+    last: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Series {
+    name: String,
+    books_read_count: u32,
+    metadata: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+    title: Option<String>,
+    #[serde(default)]
+    alternate_titles: Vec<AlternateTitle>,
+}
+
+#[derive(Deserialize)]
+struct AlternateTitle {
+    title: String,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+pub struct KomgaClient {
+    client: Client,
+    url: String,
+    api_key: String,
+    /// What a Komga book is in this library.
+    unit: ReadUnit,
+    retry: RetryPolicy,
+}
+
+impl KomgaClient {
+    pub const NAME: &'static str = "Komga";
+
+    /// Reads `KOMGA_URL` and `KOMGA_API_KEY`; None unless both are set.
+    /// Books count as volumes unless `KOMGA_BOOK_UNIT` is `chapters`.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let unit = match var("KOMGA_BOOK_UNIT").as_deref().map(str::trim) {
+            Some("chapters") => ReadUnit::Chapters,
+            _ => ReadUnit::Volumes,
+        };
+        Some(Self {
+            client: Client::new(),
+            url: var("KOMGA_URL")?.trim_end_matches('/').to_string(),
+            api_key: var("KOMGA_API_KEY")?,
+            unit,
+            retry: RetryPolicy::from_env(),
+        })
+    }
+}
+
+impl ReadingServer for KomgaClient {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// A series' progress is the number of its books read to the end.
+    fn progress(&self) -> Result<Vec<ServerSeries>, SearchError> {
+        let mut progress = Vec::new();
+        for page in 0.. {
+            let request = self
+                .client
+                .get(format!("{}/api/v1/series", self.url))
+                .header("X-API-Key", &self.api_key)
+                .query(&[
+                    ("read_status", "READ"),
+                    ("read_status", "IN_PROGRESS"),
+                    ("size", PAGE_SIZE),
+                    ("page", &page.to_string()),
+                ]);
+            let resp = send_with_retry(&self.retry, request)?;
+            if !resp.status().is_success() {
+                return Err(SearchError::Api(format!("Komga returned {}", resp.status())));
+            }
+            let page: Page = decode_json("komga", resp)?;
+            progress.extend(
+                page.content
+                    .into_iter()
+                    .filter(|s| s.books_read_count > 0)
+                    .map(|s| to_series(s, self.unit)),
+            );
+            if page.last {
+                break;
+            }
+        }
+        Ok(progress)
+    }
+}
+
+/// The metadata title wins over the folder name, which becomes an
+/// alternative title.
+fn to_series(series: Series, unit: ReadUnit) -> ServerSeries {
+    let metadata = series.metadata.unwrap_or(Metadata { title: None, alternate_titles: Vec::new() });
+    let title = metadata.title.filter(|t| !t.is_empty()).unwrap_or_else(|| series.name.clone());
+    let alt_titles = std::iter::once(series.name)
+        .chain(metadata.alternate_titles.into_iter().map(|a| a.title))
+        .filter(|t| !t.is_empty() && *t != title)
+        .collect();
+    ServerSeries { title, alt_titles, read: series.books_read_count, unit }
+}
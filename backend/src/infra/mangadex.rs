@@ -1,5 +1,9 @@
-use crate::core::models::{MediaItemType, Progress, ReadStatus, ReadableKind};
-use crate::core::search::{MediaSearchType, SearchError, SearchProvider, SearchResult};
+use crate::core::models::{ContentRating, MediaItemType, Progress, ReadStatus, ReadableKind};
+use crate::core::search::{
+    collect_alt_titles, LatestChapter, LocalizedTitles, MediaSearchType, SearchError, SearchProvider,
+    SearchResult, SEARCH_PAGE_SIZE,
+};
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
 use reqwest::blocking::Client;
 use serde::Deserialize;
 
@@ -14,6 +18,11 @@ struct MangaListResponse {
     data: Vec<MangaData>,
 }
 
+#[derive(Deserialize)]
+struct MangaResponse {
+    data: MangaData,
+}
+
 #[derive(Deserialize)]
 struct MangaData {
     id: String,
@@ -25,10 +34,14 @@ struct MangaData {
 #[serde(rename_all = "camelCase")]
 struct MangaAttributes {
     title: serde_json::Value, // {"en": "...", "ja": "..."}
+    #[serde(default)]
+    alt_titles: Vec<serde_json::Value>, // [{"ja-ro": "..."}, ...]
+    links: Option<serde_json::Value>,   // {"al": "30013", "mal": "13", ...}
     original_language: Option<String>,
     last_chapter: Option<String>,
     year: Option<u32>,
     status: Option<String>,
+    content_rating: Option<String>,
     tags: Vec<TagData>,
 }
 
@@ -49,6 +62,24 @@ struct Relationship {
     attributes: Option<serde_json::Value>,
 }
 
+// ── Chapter types ────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct ChapterListResponse {
+    data: Vec<ChapterData>,
+}
+
+#[derive(Deserialize)]
+struct ChapterData {
+    attributes: ChapterAttributes,
+}
+
+#[derive(Deserialize)]
+struct ChapterAttributes {
+    /// e.g. `"112"` or `"112.5"`; None for oneshots.
+    chapter: Option<String>,
+}
+
 // ── Statistics types ─────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -60,6 +91,7 @@ struct StatsResponse {
 
 pub struct MangaDexClient {
     client: Client,
+    retry: RetryPolicy,
 }
 
 impl MangaDexClient {
@@ -69,6 +101,7 @@ impl MangaDexClient {
                 .user_agent(USER_AGENT)
                 .build()
                 .unwrap_or_else(|_| Client::new()),
+            retry: RetryPolicy::from_env(),
         }
     }
 
@@ -89,6 +122,41 @@ impl MangaDexClient {
             .to_string()
     }
 
+    fn extract_alt_titles(attrs: &MangaAttributes, primary: &str) -> Vec<String> {
+        let candidates = std::iter::once(&attrs.title)
+            .chain(attrs.alt_titles.iter())
+            .filter_map(|t| t.as_object())
+            .flat_map(|m| m.values())
+            .filter_map(|v| v.as_str().map(str::to_string));
+        collect_alt_titles(primary, candidates)
+    }
+
+    /// The English, romanized and original names among the title and
+    /// alternative titles, by their language keys (`en`, `ja-ro`, `ja`
+    /// for a Japanese original).
+    fn extract_titles(attrs: &MangaAttributes) -> LocalizedTitles {
+        let original = attrs.original_language.as_deref().unwrap_or("ja");
+        let find = |key: &str| {
+            std::iter::once(&attrs.title)
+                .chain(attrs.alt_titles.iter())
+                .find_map(|t| t.get(key).and_then(|v| v.as_str()).map(str::to_string))
+        };
+        LocalizedTitles { english: find("en"), romaji: find(&format!("{original}-ro")), native: find(original) }
+    }
+
+    /// MangaDex id plus the AniList / MAL ids listed in `links`.
+    fn extract_refs(id: &str, links: Option<&serde_json::Value>) -> Vec<String> {
+        let mut refs = vec![format!("mangadex:{id}")];
+        let link = |key: &str| links.and_then(|l| l.get(key)).and_then(|v| v.as_str());
+        if let Some(al) = link("al") {
+            refs.push(format!("anilist:{al}"));
+        }
+        if let Some(mal) = link("mal") {
+            refs.push(format!("mal:manga:{mal}"));
+        }
+        refs
+    }
+
     fn extract_cover_filename(relationships: &[Relationship]) -> Option<String> {
         relationships
             .iter()
@@ -99,15 +167,14 @@ impl MangaDexClient {
             .map(|s| s.to_string())
     }
 
-    fn extract_author(relationships: &[Relationship]) -> String {
+    fn extract_author(relationships: &[Relationship]) -> Option<String> {
         relationships
             .iter()
             .find(|r| r.rel_type == "author")
             .and_then(|r| r.attributes.as_ref())
             .and_then(|a| a.get("name"))
             .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string()
+            .map(|s| s.to_string())
     }
 
     fn has_tag(tags: &[TagData], name: &str) -> bool {
@@ -145,15 +212,101 @@ impl MangaDexClient {
 
         let params: Vec<(&str, &str)> = ids.iter().map(|id| ("manga[]", *id)).collect();
 
-        self.client
-            .get(&format!("{BASE_URL}/statistics/manga"))
-            .query(&params)
-            .send()
+        let request = self
+            .client
+            .get(format!("{BASE_URL}/statistics/manga"))
+            .query(&params);
+        send_with_retry(&self.retry, request)
             .ok()
-            .and_then(|r| r.json::<StatsResponse>().ok())
+            .and_then(|r| decode_json::<StatsResponse>("mangadex", r).ok())
             .map(|s| s.statistics)
             .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()))
     }
+
+    /// Highest English chapter number of one manga; a `.5` extra counts
+    /// as the chapter before it.
+    fn latest_chapter(&self, manga_id: &str) -> Result<Option<u32>, SearchError> {
+        let request = self
+            .client
+            .get(format!("{BASE_URL}/chapter"))
+            .query(&[
+                ("manga", manga_id),
+                ("translatedLanguage[]", "en"),
+                ("order[chapter]", "desc"),
+                ("limit", "1"),
+            ]);
+        let resp = send_with_retry(&self.retry, request)?;
+        let data: ChapterListResponse = decode_json("mangadex", resp)?;
+        Ok(data
+            .data
+            .into_iter()
+            .filter_map(|c| c.attributes.chapter?.parse::<f32>().ok())
+            .map(|c| c as u32)
+            .next())
+    }
+
+    /// Search result of one manga; `stats` is a `/statistics/manga` answer.
+    fn map_manga(manga: MangaData, stats: &serde_json::Value) -> SearchResult {
+        let title = Self::extract_title(&manga.attributes.title);
+        let creator = Self::extract_author(&manga.relationships);
+        let author = creator.as_deref().unwrap_or("Unknown");
+        let (kind, kind_label) = Self::determine_kind(&manga.attributes);
+
+        let total_chapters = manga
+            .attributes
+            .last_chapter
+            .as_deref()
+            .and_then(|s| s.parse::<f32>().ok())
+            .map(|c| c as u32);
+
+        let year = manga
+            .attributes
+            .year
+            .map(|y| y.to_string())
+            .unwrap_or_else(|| "?".into());
+
+        let status = manga
+            .attributes
+            .status
+            .as_deref()
+            .unwrap_or("unknown");
+
+        let poster_url = Self::extract_cover_filename(&manga.relationships)
+            .map(|f| format!("{COVER_BASE}/{}/{f}.256.jpg", manga.id));
+
+        // Stats: rating.bayesian is 1-10
+        let global_score = stats
+            .get(&manga.id)
+            .and_then(|s| s.get("rating"))
+            .and_then(|r| r.get("bayesian"))
+            .and_then(|v| v.as_f64())
+            .map(|r| (r.clamp(0.0, 10.0) * 10.0).round() as u8);
+
+        let alt_titles = Self::extract_alt_titles(&manga.attributes, &title);
+        let titles = Self::extract_titles(&manga.attributes);
+        let external_refs =
+            Self::extract_refs(&manga.id, manga.attributes.links.as_ref());
+
+        SearchResult {
+            title,
+            alt_titles,
+            external_refs,
+            media_type: MediaItemType::Readable(
+                kind,
+                Progress { current: 0, total: total_chapters },
+                ReadStatus::PlanToRead,
+            ),
+            global_score,
+            external_id: Some(manga.id),
+            poster_url,
+            source: "mangadex",
+            format_label: format!("{kind_label} · {author} ({year}, {status})"),
+            creator,
+            content_rating: manga.attributes.content_rating.as_deref().and_then(ContentRating::parse),
+            titles,
+            year: manga.attributes.year.map(|y| y as i32),
+        }
+    }
 }
 
 impl SearchProvider for MangaDexClient {
@@ -161,96 +314,85 @@ impl SearchProvider for MangaDexClient {
         "MangaDex"
     }
 
+    fn source(&self) -> &'static str {
+        "mangadex"
+    }
+
     fn supported_types(&self) -> &[MediaSearchType] {
         &[MediaSearchType::Manga]
     }
 
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        // The id goes into the URL path, so only UUIDs are looked up.
+        if media_type != MediaSearchType::Manga
+            || external_id.is_empty()
+            || !external_id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+        {
+            return Ok(None);
+        }
+
+        let request = self
+            .client
+            .get(format!("{BASE_URL}/manga/{external_id}"))
+            .query(&[("includes[]", "cover_art"), ("includes[]", "author")]);
+        let resp = send_with_retry(&self.retry, request)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let data: MangaResponse = decode_json("mangadex", resp)?;
+        let stats = self.fetch_stats(&[data.data.id.as_str()]);
+        Ok(Some(Self::map_manga(data.data, &stats)))
+    }
+
+    fn latest_chapters(&self, ref_ids: &[String]) -> Result<Vec<LatestChapter>, SearchError> {
+        let mut found = Vec::new();
+        for id in ref_ids {
+            if let Some(chapter) = self.latest_chapter(id)? {
+                found.push(LatestChapter { ref_id: id.clone(), chapter });
+            }
+        }
+        Ok(found)
+    }
+
     fn search(
         &self,
         query: &str,
         media_type: MediaSearchType,
+        page: u32,
     ) -> Result<Vec<SearchResult>, SearchError> {
         if media_type != MediaSearchType::Manga {
             return Ok(Vec::new());
         }
 
-        let resp = self
+        let request = self
             .client
-            .get(&format!("{BASE_URL}/manga"))
+            .get(format!("{BASE_URL}/manga"))
             .query(&[
                 ("title", query),
-                ("limit", "10"),
+                ("limit", &SEARCH_PAGE_SIZE.to_string()),
+                ("offset", &(page.saturating_sub(1) * SEARCH_PAGE_SIZE).to_string()),
                 ("includes[]", "cover_art"),
                 ("includes[]", "author"),
                 ("order[relevance]", "desc"),
                 ("contentRating[]", "safe"),
                 ("contentRating[]", "suggestive"),
-            ])
-            .send()
-            .map_err(|e| SearchError::Network(e.to_string()))?;
+            ]);
+        let resp = send_with_retry(&self.retry, request)?;
 
-        let data: MangaListResponse = resp
-            .json()
-            .map_err(|e| SearchError::Parse(e.to_string()))?;
+        let data: MangaListResponse = decode_json("mangadex", resp)?;
 
         // Batch fetch statistics for all results
         let ids: Vec<&str> = data.data.iter().map(|m| m.id.as_str()).collect();
         let stats = self.fetch_stats(&ids);
 
-        let results = data
+        Ok(data
             .data
             .into_iter()
-            .map(|manga| {
-                let title = Self::extract_title(&manga.attributes.title);
-                let author = Self::extract_author(&manga.relationships);
-                let (kind, kind_label) = Self::determine_kind(&manga.attributes);
-
-                let total_chapters = manga
-                    .attributes
-                    .last_chapter
-                    .as_deref()
-                    .and_then(|s| s.parse::<f32>().ok())
-                    .map(|c| c as u32);
-
-                let year = manga
-                    .attributes
-                    .year
-                    .map(|y| y.to_string())
-                    .unwrap_or_else(|| "?".into());
-
-                let status = manga
-                    .attributes
-                    .status
-                    .as_deref()
-                    .unwrap_or("unknown");
-
-                let poster_url = Self::extract_cover_filename(&manga.relationships)
-                    .map(|f| format!("{COVER_BASE}/{}/{f}.256.jpg", manga.id));
-
-                // Stats: rating.bayesian is 1-10
-                let global_score = stats
-                    .get(&manga.id)
-                    .and_then(|s| s.get("rating"))
-                    .and_then(|r| r.get("bayesian"))
-                    .and_then(|v| v.as_f64())
-                    .map(|r| (r.clamp(0.0, 10.0) * 10.0).round() as u8);
-
-                SearchResult {
-                    title,
-                    media_type: MediaItemType::Readable(
-                        kind,
-                        Progress { current: 0, total: total_chapters },
-                        ReadStatus::PlanToRead,
-                    ),
-                    global_score,
-                    external_id: None, // MangaDex uses UUIDs, not u32
-                    poster_url,
-                    source: "mangadex",
-                    format_label: format!("{kind_label} · {author} ({year}, {status})"),
-                }
-            })
-            .collect();
-
-        Ok(results)
+            .map(|manga| Self::map_manga(manga, &stats))
+            .collect())
     }
 }
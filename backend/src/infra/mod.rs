@@ -3,5 +3,22 @@ pub mod database;
 pub mod anilist;
 pub mod tmdb;
 pub mod openlibrary;
+pub mod google_books;
+pub mod audible;
 pub mod mangadex;
+pub mod anime_lists;
+pub mod trakt;
+pub mod kavita;
+pub mod komga;
+pub mod arr;
+pub mod http;
+pub mod backup;
+pub mod posters;
+pub mod s3;
+pub mod dashboard;
+pub mod telemetry;
+pub mod tls;
+pub mod top;
+pub mod webhooks;
+pub mod notifiers;
 pub mod web;
@@ -0,0 +1,133 @@
+//! The channels of `core::notify`: a plain webhook, ntfy, SMTP email and
+//! Telegram.
+
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::core::notify::{Channel, Notice, Notifier, SmtpSecurity};
+
+/// The notifier for a configured channel.
+pub fn build(channel: &Channel) -> Box<dyn Notifier> {
+    match channel.clone() {
+        Channel::Webhook { url } => Box::new(WebhookNotifier { url }),
+        Channel::Ntfy { server, topic, token } => Box::new(NtfyNotifier { server, topic, token }),
+        Channel::Email { host, port, security, username, password, from, to } => {
+            Box::new(EmailNotifier { host, port, security, username, password, from, to })
+        }
+        Channel::Telegram { bot_token, chat_id } => Box::new(TelegramNotifier { bot_token, chat_id }),
+    }
+}
+
+/// Sends a request and turns anything but a 2xx answer into an error
+/// naming `service`.  Errors leave out the URL, which may hold a token.
+fn send(service: &str, request: impl FnOnce(&Client) -> RequestBuilder) -> Result<(), String> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = request(&client).send().map_err(|e| format!("{service}: {}", e.without_url()))?;
+    if !resp.status().is_success() {
+        return Err(format!("{service} answered {}", resp.status()));
+    }
+    Ok(())
+}
+
+struct WebhookNotifier {
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, notice: &Notice) -> Result<(), String> {
+        send(&self.url, |client| client.post(&self.url).json(notice))
+    }
+}
+
+struct NtfyNotifier {
+    server: String,
+    topic: String,
+    token: Option<String>,
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    fn send(&self, notice: &Notice) -> Result<(), String> {
+        let url = format!("{}/{}", self.server.trim_end_matches('/'), self.topic);
+        send("ntfy", |client| {
+            let request = client
+                .post(&url)
+                .header("Title", &notice.title)
+                .header("Tags", notice.event.as_str())
+                .body(notice.message.clone());
+            match &self.token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+        })
+    }
+}
+
+struct TelegramNotifier {
+    bot_token: String,
+    chat_id: String,
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn send(&self, notice: &Notice) -> Result<(), String> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        let text = format!("{}\n{}", notice.title, notice.message);
+        send("Telegram", |client| client.post(&url).json(&json!({ "chat_id": self.chat_id, "text": text })))
+    }
+}
+
+struct EmailNotifier {
+    host: String,
+    port: Option<u16>,
+    security: SmtpSecurity,
+    username: Option<String>,
+    password: Option<String>,
+    from: String,
+    to: String,
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn send(&self, notice: &Notice) -> Result<(), String> {
+        let mailbox = |address: &str| address.parse::<Mailbox>().map_err(|e| format!("{address}: {e}"));
+        let email = Message::builder()
+            .from(mailbox(&self.from)?)
+            .to(mailbox(&self.to)?)
+            .subject(format!("kars: {}", notice.title))
+            .body(notice.message.clone())
+            .map_err(|e| e.to_string())?;
+
+        let (builder, default_port) = match self.security {
+            SmtpSecurity::Starttls => (SmtpTransport::starttls_relay(&self.host).map_err(|e| e.to_string())?, 587),
+            SmtpSecurity::Tls => (SmtpTransport::relay(&self.host).map_err(|e| e.to_string())?, 465),
+            SmtpSecurity::None => (SmtpTransport::builder_dangerous(&self.host), 25),
+        };
+        let mut builder = builder.port(self.port.unwrap_or(default_port)).timeout(Some(Duration::from_secs(20)));
+        if let Some(username) = &self.username {
+            builder = builder.credentials(Credentials::new(username.clone(), self.password.clone().unwrap_or_default()));
+        }
+        builder.build().send(&email).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
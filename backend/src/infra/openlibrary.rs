@@ -1,5 +1,9 @@
 use crate::core::models::{MediaItemType, Progress, ReadStatus, ReadableKind};
-use crate::core::search::{MediaSearchType, SearchError, SearchProvider, SearchResult};
+use crate::core::search::{
+    LocalizedTitles, MediaDetails, MediaSearchType, SearchError, SearchProvider, SearchResult,
+    SEARCH_PAGE_SIZE,
+};
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
 use reqwest::blocking::Client;
 use serde::Deserialize;
 
@@ -24,52 +28,48 @@ struct BookDoc {
     ratings_average: Option<f64>,
 }
 
+#[derive(Deserialize)]
+struct SentenceResponse {
+    docs: Vec<SentenceDoc>,
+}
+
+#[derive(Deserialize)]
+struct SentenceDoc {
+    key: Option<String>,
+    /// One entry per edition language; the first is the work's own.
+    first_sentence: Option<Vec<String>>,
+}
+
 // ── Client ───────────────────────────────────────────────────────
 
 pub struct OpenLibraryClient {
     client: Client,
+    retry: RetryPolicy,
 }
 
 impl OpenLibraryClient {
     pub fn new() -> Self {
         Self {
             client: Client::new(),
+            retry: RetryPolicy::from_env(),
         }
     }
-}
-
-impl SearchProvider for OpenLibraryClient {
-    fn name(&self) -> &str {
-        "Open Library"
-    }
-
-    fn supported_types(&self) -> &[MediaSearchType] {
-        &[MediaSearchType::Book]
-    }
-
-    fn search(
-        &self,
-        query: &str,
-        media_type: MediaSearchType,
-    ) -> Result<Vec<SearchResult>, SearchError> {
-        if media_type != MediaSearchType::Book {
-            return Ok(Vec::new());
-        }
 
-        let resp = self
+    /// One page of `search.json` for a query, which may also be a field
+    /// query such as `key:/works/OL27448W`.
+    fn search_docs(&self, query: &str, page: u32) -> Result<Vec<SearchResult>, SearchError> {
+        let request = self
             .client
             .get(SEARCH_URL)
             .query(&[
                 ("q", query),
                 ("fields", "key,title,author_name,first_publish_year,cover_i,number_of_pages_median,ratings_average"),
-                ("limit", "10"),
-            ])
-            .send()
-            .map_err(|e| SearchError::Network(e.to_string()))?;
+                ("limit", &SEARCH_PAGE_SIZE.to_string()),
+                ("page", &page.to_string()),
+            ]);
+        let resp = send_with_retry(&self.retry, request)?;
 
-        let data: SearchResponse = resp
-            .json()
-            .map_err(|e| SearchError::Parse(e.to_string()))?;
+        let data: SearchResponse = decode_json("openlibrary", resp)?;
 
         let results = data
             .docs
@@ -77,13 +77,10 @@ impl SearchProvider for OpenLibraryClient {
             .filter_map(|doc| {
                 let title = doc.title?;
 
-                let author = doc
-                    .author_name
-                    .as_ref()
-                    .and_then(|a| a.first())
-                    .cloned()
-                    .unwrap_or_else(|| "Unknown".into());
+                let creator = doc.author_name.and_then(|a| a.into_iter().next());
+                let author = creator.clone().unwrap_or_else(|| "Unknown".into());
 
+                let first_published = doc.first_publish_year.map(|y| y as i32);
                 let year = doc
                     .first_publish_year
                     .map(|y| y.to_string())
@@ -98,11 +95,11 @@ impl SearchProvider for OpenLibraryClient {
                     ((r.clamp(0.0, 5.0) / 5.0) * 100.0).round() as u8
                 });
 
-                // Extract numeric work ID from key like "/works/OL27448W"
+                // The work key without its path, e.g. "OL27448W" from "/works/OL27448W"
                 let external_id = doc
                     .key
                     .as_deref()
-                    .and_then(|k| k.trim_start_matches("/works/OL").trim_end_matches('W').parse::<u32>().ok());
+                    .map(|k| k.trim_start_matches("/works/").to_string());
 
                 Some(SearchResult {
                     title,
@@ -115,10 +112,19 @@ impl SearchProvider for OpenLibraryClient {
                         ReadStatus::PlanToRead,
                     ),
                     global_score,
+                    external_refs: external_id
+                        .iter()
+                        .map(|id| format!("openlibrary:{id}"))
+                        .collect(),
                     external_id,
                     poster_url,
                     source: "openlibrary",
                     format_label: format!("{author} ({year})"),
+                    alt_titles: Vec::new(),
+                    creator,
+                    content_rating: None,
+                    titles: LocalizedTitles::default(),
+                    year: first_published,
                 })
             })
             .collect();
@@ -126,3 +132,82 @@ impl SearchProvider for OpenLibraryClient {
         Ok(results)
     }
 }
+
+/// Whether an id is a work key such as `OL27448W`.
+fn is_work_key(external_id: &str) -> bool {
+    external_id
+        .strip_prefix("OL")
+        .and_then(|rest| rest.strip_suffix('W'))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl SearchProvider for OpenLibraryClient {
+    fn name(&self) -> &str {
+        "Open Library"
+    }
+
+    fn source(&self) -> &'static str {
+        "openlibrary"
+    }
+
+    fn supported_types(&self) -> &[MediaSearchType] {
+        &[MediaSearchType::Book]
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        media_type: MediaSearchType,
+        page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Book {
+            return Ok(Vec::new());
+        }
+        self.search_docs(query, page)
+    }
+
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        if media_type != MediaSearchType::Book || !is_work_key(external_id) {
+            return Ok(None);
+        }
+        let found = self.search_docs(&format!("key:/works/{external_id}"), 1)?;
+        Ok(found.into_iter().find(|r| r.external_id.as_deref() == Some(external_id)))
+    }
+
+    /// The work an edition with the ISBN belongs to.
+    fn lookup_isbn(&self, isbn: &str) -> Result<Option<SearchResult>, SearchError> {
+        Ok(self.search_docs(&format!("isbn:{isbn}"), 1)?.into_iter().next())
+    }
+
+    /// Open Library has no synopsis for most works, so the overview is the
+    /// work's first sentence.
+    fn details(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        if media_type != MediaSearchType::Book || !is_work_key(external_id) {
+            return Ok(None);
+        }
+        let request = self.client.get(SEARCH_URL).query(&[
+            ("q", format!("key:/works/{external_id}").as_str()),
+            ("fields", "key,first_sentence"),
+        ]);
+        let resp = send_with_retry(&self.retry, request)?;
+        let data: SentenceResponse = decode_json("openlibrary", resp)?;
+        let key = format!("/works/{external_id}");
+        let Some(sentence) = data
+            .docs
+            .into_iter()
+            .find(|d| d.key.as_deref() == Some(key.as_str()))
+            .and_then(|d| d.first_sentence?.into_iter().next())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(MediaDetails { overview: Some(sentence), ..MediaDetails::default() }))
+    }
+}
@@ -0,0 +1,169 @@
+//! Local copies of poster images, so the library keeps its covers when
+//! the provider CDNs they link to move or go away.  Each item gets a
+//! directory holding the original and JPEG thumbnails.
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Largest poster download accepted.
+const MAX_POSTER_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosterSize {
+    /// 160 px wide, for list rows.
+    Small,
+    /// 342 px wide, for detail panels.
+    Medium,
+    /// As downloaded.
+    Original,
+}
+
+impl PosterSize {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "small" => Some(PosterSize::Small),
+            "medium" => Some(PosterSize::Medium),
+            "original" => Some(PosterSize::Original),
+            _ => None,
+        }
+    }
+
+    fn width(self) -> Option<u32> {
+        match self {
+            PosterSize::Small => Some(160),
+            PosterSize::Medium => Some(342),
+            PosterSize::Original => None,
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            PosterSize::Small => "small.jpg",
+            PosterSize::Medium => "medium.jpg",
+            PosterSize::Original => "original",
+        }
+    }
+}
+
+/// Downloads the poster at `url` and stores it with its thumbnails below
+/// `dir`, replacing an earlier copy.  Blocking; run it off the async
+/// runtime.
+pub fn store(dir: &Path, id: Uuid, url: &str) -> Result<(), String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = client.get(url).send().map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("{url} answered {}", resp.status()));
+    }
+    let length = resp.content_length();
+    let bytes = read_capped(resp, length).map_err(|e| format!("{url}: {e}"))?;
+    let thumbnails = thumbnails(&bytes)?;
+
+    let item_dir = dir.join(id.to_string());
+    std::fs::create_dir_all(&item_dir).map_err(|e| e.to_string())?;
+    let files = std::iter::once((PosterSize::Original, bytes)).chain(thumbnails);
+    for (size, data) in files {
+        let partial = item_dir.join(format!(".{}.partial", size.file_name()));
+        std::fs::write(&partial, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&partial, item_dir.join(size.file_name())).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Reads a download of `length` bytes, if announced, giving up once it
+/// passes `MAX_POSTER_BYTES` rather than holding all of an oversized body.
+fn read_capped(body: impl Read, length: Option<u64>) -> Result<Vec<u8>, String> {
+    let too_large = || format!("larger than {} MB", MAX_POSTER_BYTES / (1024 * 1024));
+    if length.is_some_and(|len| len > MAX_POSTER_BYTES) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    body.take(MAX_POSTER_BYTES + 1).read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    if bytes.len() as u64 > MAX_POSTER_BYTES {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+/// The small and medium JPEGs of a poster.  Posters narrower than a size
+/// are not scaled up.
+pub fn thumbnails(bytes: &[u8]) -> Result<Vec<(PosterSize, Vec<u8>)>, String> {
+    let poster = image::load_from_memory(bytes).map_err(|e| format!("Not a poster image: {e}"))?;
+    [PosterSize::Small, PosterSize::Medium]
+        .into_iter()
+        .map(|size| {
+            let width = size.width().unwrap_or(u32::MAX);
+            let scaled = if poster.width() > width {
+                poster.resize(width, u32::MAX, FilterType::Lanczos3)
+            } else {
+                poster.clone()
+            };
+            let mut jpeg = Cursor::new(Vec::new());
+            DynamicImage::ImageRgb8(scaled.to_rgb8())
+                .write_to(&mut jpeg, ImageFormat::Jpeg)
+                .map_err(|e| e.to_string())?;
+            Ok((size, jpeg.into_inner()))
+        })
+        .collect()
+}
+
+/// The stored poster of an item in `size` and its content type.  None
+/// when it was never stored.
+pub fn read(dir: &Path, id: Uuid, size: PosterSize) -> Option<(Vec<u8>, &'static str)> {
+    let data = std::fs::read(path(dir, id, size)).ok()?;
+    let content_type = match size {
+        PosterSize::Original => image::guess_format(&data).map(|f| f.to_mime_type()).unwrap_or("application/octet-stream"),
+        _ => "image/jpeg",
+    };
+    Some((data, content_type))
+}
+
+/// Deletes an item's stored poster, if any.
+pub fn remove(dir: &Path, id: Uuid) -> std::io::Result<()> {
+    match std::fs::remove_dir_all(dir.join(id.to_string())) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn path(dir: &Path, id: Uuid, size: PosterSize) -> PathBuf {
+    dir.join(id.to_string()).join(size.file_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnails_keep_aspect_ratio() {
+        let mut png = Cursor::new(Vec::new());
+        DynamicImage::new_rgba8(600, 900).write_to(&mut png, ImageFormat::Png).unwrap();
+
+        let sizes: Vec<(PosterSize, u32, u32)> = thumbnails(png.get_ref())
+            .unwrap()
+            .into_iter()
+            .map(|(size, jpeg)| {
+                let thumb = image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg).unwrap();
+                (size, thumb.width(), thumb.height())
+            })
+            .collect();
+        assert_eq!(sizes, vec![(PosterSize::Small, 160, 240), (PosterSize::Medium, 342, 513)]);
+        assert!(thumbnails(b"<html>").is_err());
+    }
+
+    #[test]
+    fn test_oversized_downloads_are_cut_off() {
+        let body = || std::io::repeat(0).take(MAX_POSTER_BYTES + 100);
+        assert!(read_capped(body(), Some(MAX_POSTER_BYTES + 100)).is_err());
+        // A missing or understated length still stops at the limit.
+        assert!(read_capped(body(), None).is_err());
+        assert!(read_capped(body(), Some(1000)).is_err());
+        assert_eq!(read_capped(&b"poster"[..], Some(6)).unwrap(), b"poster");
+    }
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use crate::core::telemetry::TelemetryPayload;
+
+/// Where reports go, from `TELEMETRY_URL`.  Without it nothing is sent,
+/// whatever the settings say.
+pub fn endpoint_from_env() -> Option<String> {
+    std::env::var("TELEMETRY_URL").ok().filter(|u| !u.trim().is_empty())
+}
+
+/// POSTs the report as JSON.  Blocking; run it off the async runtime.
+pub fn send(endpoint: &str, payload: &TelemetryPayload) -> Result<(), String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let resp = client
+        .post(endpoint)
+        .json(payload)
+        .send()
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("{endpoint} answered {}", resp.status()));
+    }
+    Ok(())
+}
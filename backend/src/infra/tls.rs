@@ -0,0 +1,139 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use axum::http::{header, HeaderMap, Uri};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Router;
+use tokio::task::JoinSet;
+
+/// HTTPS served by kars itself, from `TLS_CERT_PATH` and `TLS_KEY_PATH`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first.
+    pub cert_path: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_path: PathBuf,
+    /// Port of a plain HTTP listener that redirects to HTTPS, from
+    /// `HTTP_REDIRECT_PORT`.
+    pub redirect_port: Option<u16>,
+}
+
+impl TlsConfig {
+    /// None when neither path is set.  Setting only one of them, or a
+    /// redirect port without them, is an error rather than a silent
+    /// fallback to plain HTTP.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let redirect_port = match var("HTTP_REDIRECT_PORT") {
+            Some(port) => Some(
+                port.parse::<u16>()
+                    .map_err(|_| format!("HTTP_REDIRECT_PORT is not a port: {port}"))?,
+            ),
+            None => None,
+        };
+        match (var("TLS_CERT_PATH"), var("TLS_KEY_PATH")) {
+            (Some(cert), Some(key)) => Ok(Some(TlsConfig {
+                cert_path: cert.into(),
+                key_path: key.into(),
+                redirect_port,
+            })),
+            (None, None) if redirect_port.is_none() => Ok(None),
+            (None, None) => Err("HTTP_REDIRECT_PORT needs TLS_CERT_PATH and TLS_KEY_PATH".into()),
+            _ => Err("TLS_CERT_PATH and TLS_KEY_PATH must be set together".into()),
+        }
+    }
+}
+
+/// Serves `app` over HTTPS on every address until the process ends, plus
+/// the redirect listeners when a port is configured.  Redirects go to the
+/// port of the first address.
+pub async fn serve(app: Router, addrs: Vec<SocketAddr>, tls: TlsConfig) {
+    // Fails when a provider is installed already, which is fine.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to load TLS certificate {}: {e}", tls.cert_path.display()));
+
+    let port = addrs.first().map_or(443, SocketAddr::port);
+    let mut servers = JoinSet::new();
+    if let Some(redirect_port) = tls.redirect_port {
+        let redirects = Router::new().fallback(move |headers: HeaderMap, uri: Uri| async move {
+            redirect_to_https(&headers, &uri, port)
+        });
+        for addr in redirect_addrs(&addrs, redirect_port) {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .unwrap_or_else(|e| panic!("Failed to bind {addr}: {e}"));
+            let redirects = redirects.clone();
+            servers.spawn(async move { axum::serve(listener, redirects).await });
+        }
+    }
+    for addr in addrs {
+        let server = axum_server::bind_rustls(addr, config.clone())
+            .serve(app.clone().into_make_service_with_connect_info::<SocketAddr>());
+        servers.spawn(server);
+    }
+    while let Some(result) = servers.join_next().await {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Server stopped: {e}"),
+            Err(e) => eprintln!("Server task failed: {e}"),
+        }
+    }
+}
+
+/// Where the redirect listeners bind: `redirect_port` on each IP the
+/// HTTPS server listens on, so `BIND_ADDRESS` covers both.
+pub fn redirect_addrs(addrs: &[SocketAddr], redirect_port: u16) -> Vec<SocketAddr> {
+    let mut redirects: Vec<SocketAddr> = Vec::new();
+    for addr in addrs {
+        let redirect = SocketAddr::new(addr.ip(), redirect_port);
+        if !redirects.contains(&redirect) {
+            redirects.push(redirect);
+        }
+    }
+    redirects
+}
+
+fn redirect_to_https(headers: &HeaderMap, uri: &Uri, https_port: u16) -> Response {
+    let Some(host) = headers.get(header::HOST).and_then(|h| h.to_str().ok()) else {
+        return (axum::http::StatusCode::BAD_REQUEST, "Missing Host header").into_response();
+    };
+    let path = uri.path_and_query().map_or("/", |p| p.as_str());
+    Redirect::permanent(&https_location(host, https_port, path)).into_response()
+}
+
+/// The HTTPS URL for a plain HTTP request to `host` (which may carry the
+/// HTTP port).  The port is left out when it is 443.
+pub fn https_location(host: &str, https_port: u16, path: &str) -> String {
+    let hostname = match host.rfind(':') {
+        // An IPv6 literal without a port ends with `]`.
+        Some(i) if !host[i..].contains(']') => &host[..i],
+        _ => host,
+    };
+    match https_port {
+        443 => format!("https://{hostname}{path}"),
+        port => format!("https://{hostname}:{port}{path}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_https_location_swaps_port() {
+        assert_eq!(https_location("media.example:80", 443, "/items?x=1"), "https://media.example/items?x=1");
+        assert_eq!(https_location("media.example", 8443, "/"), "https://media.example:8443/");
+        assert_eq!(https_location("[::1]:8080", 3001, "/api"), "https://[::1]:3001/api");
+        assert_eq!(https_location("[::1]", 443, "/"), "https://[::1]/");
+    }
+
+    #[test]
+    fn test_redirect_addrs_follow_the_https_ips() {
+        let addrs: Vec<SocketAddr> =
+            ["127.0.0.1:3001", "100.64.0.7:8443", "127.0.0.1:3002"].iter().map(|a| a.parse().unwrap()).collect();
+        let expected: Vec<SocketAddr> = ["127.0.0.1:8080", "100.64.0.7:8080"].iter().map(|a| a.parse().unwrap()).collect();
+        assert_eq!(redirect_addrs(&addrs, 8080), expected);
+    }
+}
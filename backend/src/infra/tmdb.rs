@@ -1,5 +1,10 @@
-use crate::core::models::{MediaItemType, Progress, WatchStatus};
-use crate::core::search::{MediaSearchType, SearchError, SearchProvider, SearchResult};
+use crate::core::models::{ContentRating, MediaItemType, Progress, WatchStatus};
+use crate::core::search::{
+    collect_alt_titles, numeric_id, Browse, Credit, LocalizedTitles, MediaDetails, MediaSearchType, Release,
+    SearchError, SearchProvider, SearchResult, UnavailableProvider, SEARCH_PAGE_SIZE,
+};
+use chrono::NaiveDate;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
 use reqwest::blocking::Client;
 use serde::Deserialize;
 
@@ -17,28 +22,87 @@ struct PagedResponse<T> {
 struct MovieResult {
     id: u32,
     title: String,
+    original_title: Option<String>,
     vote_average: Option<f64>,
     poster_path: Option<String>,
     release_date: Option<String>,
+    adult: Option<bool>,
 }
 
 #[derive(Deserialize)]
 struct TvResult {
     id: u32,
     name: String,
+    original_name: Option<String>,
     vote_average: Option<f64>,
     poster_path: Option<String>,
     first_air_date: Option<String>,
+    adult: Option<bool>,
+    /// Only present in `/tv/{id}` details.
+    number_of_episodes: Option<u32>,
 }
 
+#[derive(Deserialize)]
+struct Genre {
+    name: String,
+}
+
+/// The parts of `/movie/{id}` and `/tv/{id}` that search results lack.
+#[derive(Deserialize)]
+struct DetailsResult {
+    overview: Option<String>,
+    #[serde(default)]
+    genres: Vec<Genre>,
+    /// Movies only.
+    runtime: Option<u32>,
+    /// Series only: typical episode lengths.
+    #[serde(default)]
+    episode_run_time: Vec<u32>,
+    number_of_seasons: Option<u32>,
+    number_of_episodes: Option<u32>,
+    /// Series only.
+    #[serde(default)]
+    created_by: Vec<Person>,
+    /// Asked for with `append_to_response=credits`.
+    credits: Option<Credits>,
+}
+
+#[derive(Deserialize)]
+struct Person {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Credits {
+    #[serde(default)]
+    crew: Vec<CrewMember>,
+}
+
+#[derive(Deserialize)]
+struct CrewMember {
+    name: String,
+    job: String,
+}
+
+/// Crew jobs listed as staff, in this order.
+const STAFF_JOBS: [&str; 5] = ["Director", "Screenplay", "Writer", "Novel", "Original Music Composer"];
+
+/// Most staff credits kept per entry.
+const MAX_STAFF: usize = 10;
+
 // ── Client ───────────────────────────────────────────────────────
 
 pub struct TmdbClient {
     client: Client,
     api_key: String,
+    retry: RetryPolicy,
 }
 
 impl TmdbClient {
+    pub const NAME: &'static str = "TMDB";
+    pub const SUPPORTED_TYPES: &'static [MediaSearchType] =
+        &[MediaSearchType::Movie, MediaSearchType::Series];
+
     /// Reads the TMDB Bearer token from TMDB_API_KEY env var.
     /// Returns None if the env var is not set, so the app can still run without it.
     pub fn from_env() -> Option<Self> {
@@ -49,92 +113,197 @@ impl TmdbClient {
         Some(Self {
             client: Client::new(),
             api_key: key,
+            retry: RetryPolicy::from_env(),
         })
     }
 
-    fn get(&self, path: &str, query: &str) -> Result<reqwest::blocking::Response, SearchError> {
+    /// Describes the disabled provider when `from_env` returned None.
+    pub fn missing_key() -> UnavailableProvider {
+        UnavailableProvider {
+            name: Self::NAME,
+            supported_types: Self::SUPPORTED_TYPES,
+            reason: "TMDB_API_KEY is not set".into(),
+        }
+    }
+
+    fn get(&self, path: &str, query: &str, page: u32) -> Result<reqwest::blocking::Response, SearchError> {
         let url = format!("{BASE_URL}{path}");
-        self.client
+        let request = self
+            .client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .query(&[
                 ("query", query),
                 ("include_adult", "false"),
                 ("language", "en-US"),
-                ("page", "1"),
-            ])
-            .send()
-            .map_err(|e| SearchError::Network(e.to_string()))
+                ("page", &page.to_string()),
+            ]);
+        send_with_retry(&self.retry, request)
     }
 
-    fn search_movies(&self, query: &str) -> Result<Vec<SearchResult>, SearchError> {
-        let resp = self.get("/search/movie", query)?;
-        let page: PagedResponse<MovieResult> = resp
-            .json()
-            .map_err(|e| SearchError::Parse(e.to_string()))?;
+    /// Fetches `/movie/{id}`, `/tv/{id}` or a page below them; None when
+    /// TMDB does not know the id.
+    fn get_details<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>, SearchError> {
+        let request = self
+            .client
+            .get(format!("{BASE_URL}{path}"))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .query(&[("language", "en-US")]);
+        let resp = send_with_retry(&self.retry, request)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("TMDB returned {}", resp.status())));
+        }
+        decode_json("tmdb", resp).map(Some)
+    }
+
+    /// One page of a list endpoint (trending, discover), at most 20
+    /// entries.
+    fn get_list<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        page: u32,
+        params: &[(&str, String)],
+    ) -> Result<Vec<T>, SearchError> {
+        let request = self
+            .client
+            .get(format!("{BASE_URL}{path}"))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .query(&[("language", "en-US"), ("include_adult", "false")])
+            .query(&[("page", page)])
+            .query(params);
+        let resp = send_with_retry(&self.retry, request)?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("TMDB returned {}", resp.status())));
+        }
+        let page: PagedResponse<T> = decode_json("tmdb", resp)?;
+        Ok(page.results)
+    }
+
+    fn search_movies(&self, query: &str, page: u32) -> Result<Vec<SearchResult>, SearchError> {
+        let (tmdb_page, skip) = tmdb_page(page);
+        let resp = self.get("/search/movie", query, tmdb_page)?;
+        let page: PagedResponse<MovieResult> = decode_json("tmdb", resp)?;
 
         let results = page
             .results
             .into_iter()
-            .take(10)
-            .map(|m| {
-                let year = m
-                    .release_date
-                    .as_deref()
-                    .and_then(|d| d.get(..4))
-                    .unwrap_or("?");
-
-                SearchResult {
-                    title: m.title,
-                    media_type: MediaItemType::Movie(WatchStatus::PlanToWatch),
-                    global_score: vote_to_score(m.vote_average),
-                    external_id: Some(m.id),
-                    poster_url: m.poster_path.map(|p| format!("{POSTER_BASE}{p}")),
-                    source: "tmdb",
-                    format_label: format!("Movie ({year})"),
-                }
-            })
+            .skip(skip)
+            .take(SEARCH_PAGE_SIZE as usize)
+            .map(map_movie)
             .collect();
 
         Ok(results)
     }
 
-    fn search_tv(&self, query: &str) -> Result<Vec<SearchResult>, SearchError> {
-        let resp = self.get("/search/tv", query)?;
-        let page: PagedResponse<TvResult> = resp
-            .json()
-            .map_err(|e| SearchError::Parse(e.to_string()))?;
+    fn search_tv(&self, query: &str, page: u32) -> Result<Vec<SearchResult>, SearchError> {
+        let (tmdb_page, skip) = tmdb_page(page);
+        let resp = self.get("/search/tv", query, tmdb_page)?;
+        let page: PagedResponse<TvResult> = decode_json("tmdb", resp)?;
 
         let results = page
             .results
             .into_iter()
-            .take(10)
-            .map(|t| {
-                let year = t
-                    .first_air_date
-                    .as_deref()
-                    .and_then(|d| d.get(..4))
-                    .unwrap_or("?");
-
-                SearchResult {
-                    title: t.name,
-                    media_type: MediaItemType::Series(
-                        Progress { current: 0, total: None },
-                        WatchStatus::PlanToWatch,
-                    ),
-                    global_score: vote_to_score(t.vote_average),
-                    external_id: Some(t.id),
-                    poster_url: t.poster_path.map(|p| format!("{POSTER_BASE}{p}")),
-                    source: "tmdb",
-                    format_label: format!("TV Series ({year})"),
-                }
-            })
+            .skip(skip)
+            .take(SEARCH_PAGE_SIZE as usize)
+            .map(map_tv)
             .collect();
 
         Ok(results)
     }
 }
 
+/// TMDB page holding a page of search results, and how many of its
+/// entries come before it: TMDB pages hold two search pages.
+fn tmdb_page(page: u32) -> (u32, usize) {
+    let per_tmdb_page = 20 / SEARCH_PAGE_SIZE;
+    let skip = (page.saturating_sub(1) % per_tmdb_page) * SEARCH_PAGE_SIZE;
+    (page.div_ceil(per_tmdb_page).max(1), skip as usize)
+}
+
+fn map_movie(m: MovieResult) -> SearchResult {
+    let year = m
+        .release_date
+        .as_deref()
+        .and_then(|d| d.get(..4))
+        .unwrap_or("?");
+
+    SearchResult {
+        alt_titles: collect_alt_titles(&m.title, m.original_title),
+        external_refs: vec![format!("tmdb:movie:{}", m.id)],
+        title: m.title,
+        media_type: MediaItemType::Movie(WatchStatus::PlanToWatch),
+        global_score: vote_to_score(m.vote_average),
+        external_id: Some(m.id.to_string()),
+        poster_url: m.poster_path.map(|p| format!("{POSTER_BASE}{p}")),
+        source: "tmdb",
+        format_label: format!("Movie ({year})"),
+        creator: None,
+        content_rating: m.adult.map(ContentRating::from_adult_flag),
+        titles: LocalizedTitles::default(),
+        year: year.parse().ok(),
+    }
+}
+
+fn map_tv(t: TvResult) -> SearchResult {
+    let year = t
+        .first_air_date
+        .as_deref()
+        .and_then(|d| d.get(..4))
+        .unwrap_or("?");
+
+    SearchResult {
+        alt_titles: collect_alt_titles(&t.name, t.original_name),
+        external_refs: vec![format!("tmdb:tv:{}", t.id)],
+        title: t.name,
+        media_type: MediaItemType::Series(
+            Progress { current: 0, total: t.number_of_episodes },
+            WatchStatus::PlanToWatch,
+        ),
+        global_score: vote_to_score(t.vote_average),
+        external_id: Some(t.id.to_string()),
+        poster_url: t.poster_path.map(|p| format!("{POSTER_BASE}{p}")),
+        source: "tmdb",
+        format_label: format!("TV Series ({year})"),
+        creator: None,
+        content_rating: t.adult.map(ContentRating::from_adult_flag),
+        titles: LocalizedTitles::default(),
+        year: year.parse().ok(),
+    }
+}
+
+fn map_details(d: DetailsResult) -> MediaDetails {
+    MediaDetails {
+        overview: d.overview,
+        genres: d.genres.into_iter().map(|g| g.name).collect(),
+        runtime_minutes: d
+            .runtime
+            .or_else(|| d.episode_run_time.first().copied())
+            .filter(|&m| m > 0),
+        season_count: d.number_of_seasons,
+        episode_count: d.number_of_episodes.filter(|&n| n > 0),
+        staff: map_staff(d.created_by, d.credits.map(|c| c.crew).unwrap_or_default()),
+    }
+}
+
+/// Series creators, then the crew by `STAFF_JOBS`.
+fn map_staff(created_by: Vec<Person>, mut crew: Vec<CrewMember>) -> Vec<Credit> {
+    crew.retain(|c| STAFF_JOBS.contains(&c.job.as_str()));
+    crew.sort_by_key(|c| STAFF_JOBS.iter().position(|job| *job == c.job));
+    let creators = created_by.into_iter().map(|p| Credit { name: p.name, role: "Creator".into() });
+    let crew = crew.into_iter().map(|c| Credit { name: c.name, role: c.job });
+    let mut staff: Vec<Credit> = Vec::new();
+    for credit in creators.chain(crew) {
+        if !staff.contains(&credit) {
+            staff.push(credit);
+        }
+    }
+    staff.truncate(MAX_STAFF);
+    staff
+}
+
 /// TMDB vote_average: 0.0-10.0 → our global_score: 0-100 (u8)
 fn vote_to_score(vote: Option<f64>) -> Option<u8> {
     vote.filter(|&v| v > 0.0)
@@ -143,22 +312,175 @@ fn vote_to_score(vote: Option<f64>) -> Option<u8> {
 
 impl SearchProvider for TmdbClient {
     fn name(&self) -> &str {
-        "TMDB"
+        Self::NAME
+    }
+
+    fn source(&self) -> &'static str {
+        "tmdb"
     }
 
     fn supported_types(&self) -> &[MediaSearchType] {
-        &[MediaSearchType::Movie, MediaSearchType::Series]
+        Self::SUPPORTED_TYPES
     }
 
     fn search(
         &self,
         query: &str,
         media_type: MediaSearchType,
+        page: u32,
     ) -> Result<Vec<SearchResult>, SearchError> {
         match media_type {
-            MediaSearchType::Movie => self.search_movies(query),
-            MediaSearchType::Series => self.search_tv(query),
+            MediaSearchType::Movie => self.search_movies(query, page),
+            MediaSearchType::Series => self.search_tv(query, page),
             _ => Ok(Vec::new()),
         }
     }
+    fn fetch(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<SearchResult>, SearchError> {
+        let Some(external_id) = numeric_id(external_id) else { return Ok(None) };
+        match media_type {
+            MediaSearchType::Movie => {
+                Ok(self.get_details(&format!("/movie/{external_id}"))?.map(map_movie))
+            }
+            MediaSearchType::Series => Ok(self.get_details(&format!("/tv/{external_id}"))?.map(map_tv)),
+            _ => Ok(None),
+        }
+    }
+
+    fn details(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Option<MediaDetails>, SearchError> {
+        let Some(external_id) = numeric_id(external_id) else { return Ok(None) };
+        let path = match media_type {
+            MediaSearchType::Movie => format!("/movie/{external_id}?append_to_response=credits"),
+            MediaSearchType::Series => format!("/tv/{external_id}?append_to_response=credits"),
+            _ => return Ok(None),
+        };
+        Ok(self.get_details::<DetailsResult>(&path)?.map(map_details))
+    }
+
+    fn browse(
+        &self,
+        browse: Browse,
+        media_type: MediaSearchType,
+        page: u32,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let (kind, after, before) = match media_type {
+            MediaSearchType::Movie => ("movie", "primary_release_date.gte", "primary_release_date.lte"),
+            MediaSearchType::Series => ("tv", "first_air_date.gte", "first_air_date.lte"),
+            _ => return Ok(Vec::new()),
+        };
+        let (path, params) = match browse {
+            Browse::Trending => (format!("/trending/{kind}/week"), Vec::new()),
+            Browse::Season { year, season } => {
+                let Some((start, end)) = season.dates(year) else {
+                    return Ok(Vec::new());
+                };
+                let params = vec![
+                    ("sort_by", "popularity.desc".to_string()),
+                    (after, start.to_string()),
+                    (before, end.to_string()),
+                ];
+                (format!("/discover/{kind}"), params)
+            }
+        };
+        Ok(match media_type {
+            MediaSearchType::Movie => self
+                .get_list::<MovieResult>(&path, page, &params)?
+                .into_iter()
+                .map(map_movie)
+                .collect(),
+            _ => self.get_list::<TvResult>(&path, page, &params)?.into_iter().map(map_tv).collect(),
+        })
+    }
+
+    fn recommendations(
+        &self,
+        external_id: &str,
+        media_type: MediaSearchType,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let Some(external_id) = numeric_id(external_id) else { return Ok(Vec::new()) };
+        let results = match media_type {
+            MediaSearchType::Movie => self
+                .get_details::<PagedResponse<MovieResult>>(&format!("/movie/{external_id}/similar"))?
+                .map(|page| page.results.into_iter().take(10).map(map_movie).collect()),
+            MediaSearchType::Series => self
+                .get_details::<PagedResponse<TvResult>>(&format!("/tv/{external_id}/similar"))?
+                .map(|page| page.results.into_iter().take(10).map(map_tv).collect()),
+            _ => None,
+        };
+        Ok(results.unwrap_or_default())
+    }
+
+    fn release_dates(&self, external_ids: &[String]) -> Result<Vec<Release>, SearchError> {
+        let mut releases = Vec::new();
+        for external_id in external_ids {
+            let Some(id) = numeric_id(external_id) else { continue };
+            let Some(movie) = self.get_details::<MovieResult>(&format!("/movie/{id}"))? else {
+                continue;
+            };
+            let date = movie
+                .release_date
+                .as_deref()
+                .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+            if let Some(date) = date {
+                releases.push(Release { external_id: external_id.clone(), date });
+            }
+        }
+        Ok(releases)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::MediaItem;
+
+    #[test]
+    fn test_tv_details_fill_missing_fields() {
+        let body = r#"{
+            "overview": "A chemistry teacher turns to crime.",
+            "genres": [{"id": 18, "name": "Drama"}, {"id": 80, "name": "Crime"}],
+            "episode_run_time": [47],
+            "number_of_seasons": 5,
+            "number_of_episodes": 62,
+            "created_by": [{"name": "Vince Gilligan"}],
+            "credits": {"crew": [
+                {"name": "Dave Porter", "job": "Original Music Composer"},
+                {"name": "Michelle MacLaren", "job": "Producer"},
+                {"name": "Vince Gilligan", "job": "Writer"}
+            ]}
+        }"#;
+        let details = map_details(serde_json::from_str(body).unwrap());
+        let staff: Vec<(&str, &str)> = details.staff.iter().map(|c| (c.name.as_str(), c.role.as_str())).collect();
+        assert_eq!(
+            staff,
+            [("Vince Gilligan", "Creator"), ("Vince Gilligan", "Writer"), ("Dave Porter", "Original Music Composer")]
+        );
+
+        let mut item = MediaItem::new(
+            "Breaking Bad".into(),
+            MediaItemType::Series(Progress { current: 3, total: None }, WatchStatus::Watching),
+        );
+        item.runtime_minutes = Some(50);
+        details.apply(&mut item);
+
+        assert_eq!(item.genres, ["Drama", "Crime"]);
+        assert_eq!(item.season_count, Some(5));
+        assert_eq!(item.runtime_minutes, Some(50));
+        assert!(matches!(item.media_type, MediaItemType::Series(Progress { total: Some(62), .. }, _)));
+    }
+
+    #[test]
+    fn test_search_pages_split_tmdb_pages() {
+        assert_eq!(tmdb_page(1), (1, 0));
+        assert_eq!(tmdb_page(2), (1, 10));
+        assert_eq!(tmdb_page(3), (2, 0));
+        assert_eq!(tmdb_page(4), (2, 10));
+    }
 }
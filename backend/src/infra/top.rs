@@ -0,0 +1,104 @@
+use chrono::Utc;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::core::api_types::{ApiDashboard, ApiMediaItem};
+use crate::infra::dashboard;
+use crate::infra::database::Database;
+
+/// Width of the title column.
+const TITLE_WIDTH: usize = 40;
+
+/// `kars top`: redraws the dashboard every `interval` until interrupted.
+pub async fn run(db: Database, interval: Duration) {
+    loop {
+        let screen = match dashboard::load(&db, Utc::now(), None).await {
+            Ok(d) => render(&d),
+            Err(e) => format!("Failed to load the dashboard: {e}\n"),
+        };
+        // Clear the screen and move the cursor home before drawing.
+        print!("\x1b[2J\x1b[H{screen}");
+        let _ = io::stdout().flush();
+        tokio::time::sleep(interval).await;
+    }
+}
+
+pub fn render(d: &ApiDashboard) -> String {
+    let mut out = String::new();
+    let s = &d.stats;
+    let _ = writeln!(out, "KARS top — {}  (Ctrl-C to quit)\n", d.generated_at);
+    let _ = writeln!(
+        out,
+        "Library  {} items · {} in progress · {} completed · {} planned\n",
+        s.total, s.watching, s.completed, s.plan_to_watch
+    );
+
+    section(&mut out, "Up next", d.upcoming.iter().map(|session| {
+        format!("{:<10} {}", countdown(session.starts_in_minutes), title(&session.item))
+    }));
+    section(&mut out, "Continue watching", d.continue_watching.iter().map(|e| {
+        format!("{:<TITLE_WIDTH$} {}", title(&e.item), progress(&e.item))
+    }));
+    section(&mut out, "Notifications", d.notifications.iter().map(|n| {
+        format!("{}  {}", &n.created_at[..n.created_at.len().min(16)], n.message)
+    }));
+    section(&mut out, "Jobs", d.jobs.iter().map(|j| {
+        let error = j.error.as_deref().map(|e| format!("  {e}")).unwrap_or_default();
+        format!("#{:<5} {:<10} {:<12} {} items{error}", j.id, j.kind, j.status, j.items.len())
+    }));
+    out
+}
+
+fn section(out: &mut String, heading: &str, lines: impl Iterator<Item = String>) {
+    let _ = writeln!(out, "{heading}");
+    let mut empty = true;
+    for line in lines {
+        let _ = writeln!(out, "  {line}");
+        empty = false;
+    }
+    if empty {
+        let _ = writeln!(out, "  —");
+    }
+    out.push('\n');
+}
+
+fn title(item: &ApiMediaItem) -> String {
+    if item.title.chars().count() <= TITLE_WIDTH {
+        return item.title.clone();
+    }
+    let mut short: String = item.title.chars().take(TITLE_WIDTH - 1).collect();
+    short.push('…');
+    short
+}
+
+fn progress(item: &ApiMediaItem) -> String {
+    let (current, total, prefix) = match item.volume_progress {
+        Some(volumes) if item.prefer_volumes => (volumes, item.total_volumes, "vol. "),
+        _ => (item.progress, item.total_episodes, ""),
+    };
+    match total {
+        Some(total) => format!("{prefix}{current}/{total}"),
+        None => format!("{prefix}{current}"),
+    }
+}
+
+fn countdown(minutes: i64) -> String {
+    match minutes {
+        m if m < 60 => format!("in {m}m"),
+        m if m < 24 * 60 => format!("in {}h {:02}m", m / 60, m % 60),
+        m => format!("in {}d {}h", m / (24 * 60), m % (24 * 60) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown_picks_the_largest_unit() {
+        assert_eq!(countdown(5), "in 5m");
+        assert_eq!(countdown(125), "in 2h 05m");
+        assert_eq!(countdown(3 * 24 * 60 + 90), "in 3d 1h");
+    }
+}
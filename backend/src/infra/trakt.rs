@@ -0,0 +1,388 @@
+use crate::core::models::{MediaItem, MediaItemType, Progress, WatchStatus};
+use crate::core::search::SearchError;
+use crate::infra::http::{decode_json, send_with_retry, RetryPolicy};
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+const BASE_URL: &str = "https://api.trakt.tv";
+
+/// Show statuses after which no more episodes air.
+const FINISHED_STATUSES: &[&str] = &["ended", "canceled"];
+
+// ── Response types ───────────────────────────────────────────────
+
+/// The code the user enters at `verification_url` to connect kars.
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_url: String,
+    /// Seconds until the code stops working.
+    pub expires_in: u64,
+    /// Seconds to wait between polls.
+    pub interval: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Seconds from `created_at`.
+    pub expires_in: i64,
+    /// Unix time.
+    pub created_at: i64,
+}
+
+impl Token {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.created_at + self.expires_in, 0).unwrap_or_else(Utc::now)
+    }
+}
+
+/// What polling for a device token answered.
+#[derive(Debug)]
+pub enum Poll {
+    Pending,
+    /// Polling too often; wait longer.
+    SlowDown,
+    Approved(Token),
+    /// The code expired, was denied or was already used.
+    Failed(String),
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Ids {
+    imdb: Option<String>,
+    tmdb: Option<u64>,
+    tvdb: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Movie {
+    title: String,
+    #[serde(default)]
+    ids: Ids,
+}
+
+/// `status` and `aired_episodes` are only sent with `extended=full`.
+#[derive(Deserialize)]
+struct Show {
+    title: String,
+    #[serde(default)]
+    ids: Ids,
+    status: Option<String>,
+    aired_episodes: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct WatchedMovie {
+    plays: u32,
+    movie: Movie,
+}
+
+#[derive(Deserialize)]
+pub struct WatchedShow {
+    show: Show,
+    #[serde(default)]
+    seasons: Vec<Season>,
+}
+
+#[derive(Deserialize)]
+struct Season {
+    number: u32,
+    #[serde(default)]
+    episodes: Vec<Episode>,
+}
+
+#[derive(Deserialize)]
+struct Episode {
+    number: u32,
+}
+
+/// A rating from 1 to 10 of a movie or show.
+#[derive(Deserialize)]
+pub struct Rating {
+    rating: u8,
+    movie: Option<Movie>,
+    show: Option<Show>,
+}
+
+/// Everything an import reads from the user's Trakt account.
+pub struct History {
+    pub movies: Vec<WatchedMovie>,
+    pub shows: Vec<WatchedShow>,
+    pub ratings: Vec<Rating>,
+}
+
+// ── Client ───────────────────────────────────────────────────────
+
+/// The app registered at trakt.tv, from `TRAKT_CLIENT_ID` and
+/// `TRAKT_CLIENT_SECRET`.
+#[derive(Debug, Clone)]
+pub struct TraktConfig {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl TraktConfig {
+    /// None unless both variables are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        Some(TraktConfig {
+            client_id: var("TRAKT_CLIENT_ID")?,
+            client_secret: var("TRAKT_CLIENT_SECRET")?,
+        })
+    }
+}
+
+/// Blocking; run it off the async runtime.
+pub struct TraktClient {
+    client: Client,
+    config: TraktConfig,
+    retry: RetryPolicy,
+}
+
+impl TraktClient {
+    pub fn new(config: &TraktConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+            retry: RetryPolicy::from_env(),
+        }
+    }
+
+    fn request(&self, builder: RequestBuilder) -> RequestBuilder {
+        builder
+            .header("content-type", "application/json")
+            .header("trakt-api-version", "2")
+            .header("trakt-api-key", &self.config.client_id)
+    }
+
+    /// Starts connecting an account: the user enters the returned code at
+    /// Trakt while `poll_token` waits for it.
+    pub fn device_code(&self) -> Result<DeviceCode, SearchError> {
+        let request = self
+            .request(self.client.post(format!("{BASE_URL}/oauth/device/code")))
+            .json(&json!({ "client_id": self.config.client_id }));
+        let resp = send_with_retry(&self.retry, request)?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Trakt returned {}", resp.status())));
+        }
+        decode_json("trakt", resp)
+    }
+
+    /// Asks once whether the user entered the code yet.
+    pub fn poll_token(&self, device_code: &str) -> Result<Poll, SearchError> {
+        let request = self
+            .request(self.client.post(format!("{BASE_URL}/oauth/device/token")))
+            .json(&json!({
+                "code": device_code,
+                "client_id": self.config.client_id,
+                "client_secret": self.config.client_secret,
+            }));
+        // A 429 means "slow down" here, so it is not retried.
+        let once = RetryPolicy { attempts: 1, ..self.retry };
+        let resp = send_with_retry(&once, request)?;
+        Ok(match resp.status() {
+            s if s.is_success() => Poll::Approved(decode_json("trakt", resp)?),
+            StatusCode::BAD_REQUEST => Poll::Pending,
+            StatusCode::TOO_MANY_REQUESTS => Poll::SlowDown,
+            StatusCode::NOT_FOUND => Poll::Failed("Invalid device code".into()),
+            StatusCode::CONFLICT => Poll::Failed("Code was already used".into()),
+            StatusCode::GONE => Poll::Failed("Code expired".into()),
+            s if s.as_u16() == 418 => Poll::Failed("Connection was denied".into()),
+            s => return Err(SearchError::Api(format!("Trakt returned {s}"))),
+        })
+    }
+
+    pub fn refresh(&self, refresh_token: &str) -> Result<Token, SearchError> {
+        let request = self
+            .request(self.client.post(format!("{BASE_URL}/oauth/token")))
+            .json(&json!({
+                "refresh_token": refresh_token,
+                "client_id": self.config.client_id,
+                "client_secret": self.config.client_secret,
+                "redirect_uri": "urn:ietf:wg:oauth:2.0:oob",
+                "grant_type": "refresh_token",
+            }));
+        let resp = send_with_retry(&self.retry, request)?;
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Trakt returned {}", resp.status())));
+        }
+        decode_json("trakt", resp)
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, access_token: &str, path: &str) -> Result<T, SearchError> {
+        let request = self
+            .request(self.client.get(format!("{BASE_URL}{path}")))
+            .bearer_auth(access_token);
+        let resp = send_with_retry(&self.retry, request)?;
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            return Err(SearchError::Api("Trakt rejected the token; connect again".into()));
+        }
+        if !resp.status().is_success() {
+            return Err(SearchError::Api(format!("Trakt returned {}", resp.status())));
+        }
+        decode_json("trakt", resp)
+    }
+
+    /// Watched movies and shows with their ratings.
+    pub fn history(&self, access_token: &str) -> Result<History, SearchError> {
+        let mut ratings: Vec<Rating> = self.get(access_token, "/sync/ratings/movies")?;
+        ratings.extend(self.get::<Vec<Rating>>(access_token, "/sync/ratings/shows")?);
+        Ok(History {
+            movies: self.get(access_token, "/sync/watched/movies")?,
+            shows: self.get(access_token, "/sync/watched/shows?extended=full")?,
+            ratings,
+        })
+    }
+}
+
+// ── Mapping ──────────────────────────────────────────────────────
+
+/// The ids in `MediaItem::external_refs` form; `tmdb_kind` is `movie` or
+/// `tv`.
+fn refs(ids: &Ids, tmdb_kind: &str) -> HashSet<String> {
+    let mut refs = HashSet::new();
+    if let Some(id) = ids.tmdb {
+        refs.insert(format!("tmdb:{tmdb_kind}:{id}"));
+    }
+    if let Some(id) = &ids.imdb {
+        refs.insert(format!("imdb:{id}"));
+    }
+    if let Some(id) = ids.tvdb {
+        refs.insert(format!("tvdb:{id}"));
+    }
+    refs
+}
+
+/// An item linked to TMDB when Trakt knows the TMDB id, so it merges with
+/// items added from TMDB search.
+fn item(title: String, media_type: MediaItemType, ids: &Ids, tmdb_kind: &str) -> MediaItem {
+    let mut item = MediaItem::new(title, media_type);
+    if let Some(id) = ids.tmdb {
+        item.source = Some("tmdb".into());
+        item.external_id = Some(id.to_string());
+    }
+    item.external_refs = refs(ids, tmdb_kind);
+    item
+}
+
+/// Turns watched history into items to merge into the library.  Movies
+/// are completed, rewatches counted in `repeat_count`; a show's progress
+/// is its distinct watched episodes outside specials, and it is completed
+/// once it has ended and every aired episode was seen.  Ratings of watched
+/// titles become scores.
+pub fn to_items(history: History) -> Vec<MediaItem> {
+    let mut scores: HashMap<String, u8> = HashMap::new();
+    for rating in &history.ratings {
+        let score = rating.rating.min(10) * 10;
+        let (ids, kind) = match (&rating.movie, &rating.show) {
+            (Some(movie), _) => (&movie.ids, "movie"),
+            (_, Some(show)) => (&show.ids, "tv"),
+            _ => continue,
+        };
+        for r in refs(ids, kind) {
+            scores.insert(r, score);
+        }
+    }
+    let score_of = |item: &MediaItem| item.external_refs.iter().find_map(|r| scores.get(r).copied());
+
+    let mut items = Vec::new();
+    for watched in history.movies {
+        let mut movie = item(
+            watched.movie.title,
+            MediaItemType::Movie(WatchStatus::Completed),
+            &watched.movie.ids,
+            "movie",
+        );
+        movie.repeat_count = watched.plays.saturating_sub(1);
+        movie.score = score_of(&movie);
+        items.push(movie);
+    }
+    for watched in history.shows {
+        let seen: HashSet<(u32, u32)> = watched
+            .seasons
+            .iter()
+            .filter(|s| s.number > 0)
+            .flat_map(|s| s.episodes.iter().map(move |e| (s.number, e.number)))
+            .collect();
+        let show = &watched.show;
+        let finished = show.status.as_deref().is_some_and(|s| FINISHED_STATUSES.contains(&s));
+        let total = show.aired_episodes.filter(|_| finished);
+        let current = seen.len() as u32;
+        let status = match total {
+            Some(total) if current >= total => WatchStatus::Completed,
+            _ => WatchStatus::Watching,
+        };
+        let mut series = item(
+            show.title.clone(),
+            MediaItemType::Series(Progress { current, total }, status),
+            &show.ids,
+            "tv",
+        );
+        series.season_count = watched.seasons.iter().filter(|s| s.number > 0).map(|s| s.number).max();
+        series.score = score_of(&series);
+        items.push(series);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_items_maps_history_and_ratings() {
+        let history = History {
+            movies: serde_json::from_str(
+                r#"[{"plays": 3, "movie": {"title": "Dune", "year": 2021,
+                     "ids": {"trakt": 1, "tmdb": 438631, "imdb": "tt1160419"}}}]"#,
+            )
+            .unwrap(),
+            shows: serde_json::from_str(
+                r#"[{"show": {"title": "Severance", "ids": {"tmdb": 95396, "tvdb": 371980},
+                              "status": "returning series", "aired_episodes": 19},
+                     "seasons": [{"number": 0, "episodes": [{"number": 1}]},
+                                 {"number": 1, "episodes": [{"number": 1}, {"number": 2}]}]},
+                    {"show": {"title": "Chernobyl", "ids": {"tmdb": 87108},
+                              "status": "ended", "aired_episodes": 2},
+                     "seasons": [{"number": 1, "episodes": [{"number": 1}, {"number": 2}]}]}]"#,
+            )
+            .unwrap(),
+            ratings: serde_json::from_str(
+                r#"[{"rating": 9, "type": "movie", "movie": {"title": "Dune", "ids": {"tmdb": 438631}}},
+                    {"rating": 7, "type": "show", "show": {"title": "Chernobyl", "ids": {"tmdb": 87108}}}]"#,
+            )
+            .unwrap(),
+        };
+
+        let items = to_items(history);
+        assert_eq!(items.len(), 3);
+
+        let dune = &items[0];
+        assert_eq!(dune.media_type, MediaItemType::Movie(WatchStatus::Completed));
+        assert_eq!((dune.source.as_deref(), dune.external_id.as_deref()), (Some("tmdb"), Some("438631")));
+        assert!(dune.external_refs.contains("imdb:tt1160419"));
+        assert_eq!((dune.repeat_count, dune.score), (2, Some(90)));
+
+        let severance = &items[1];
+        assert_eq!(
+            severance.media_type,
+            MediaItemType::Series(Progress { current: 2, total: None }, WatchStatus::Watching)
+        );
+        assert_eq!(severance.score, None);
+
+        let chernobyl = &items[2];
+        assert_eq!(
+            chernobyl.media_type,
+            MediaItemType::Series(Progress { current: 2, total: Some(2) }, WatchStatus::Completed)
+        );
+        assert_eq!(chernobyl.score, Some(70));
+    }
+}
@@ -1,292 +1,5764 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::get,
+    http::{HeaderValue, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    routing::{delete, get, post, put},
     Json, Router,
 };
-use serde::Deserialize;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 
-use crate::core::api_types::{ApiMediaItem, ApiStats, ApiExploreResult};
-use crate::core::search::{MediaSearchType, SearchProvider};
-use crate::infra::database::Database;
+use crate::core::activity::{self, ACTIVITY_WINDOW_DAYS};
+use crate::core::api_types::{
+    ApiAiring, ApiBackup, ApiDashboard, ApiDetailsRequest, ApiDiagnostic, ApiExploreDetail, ApiExplorePreview, ApiFromExternal, ApiEngagement, ApiEpisode, ApiEpisodeMark, ApiEpisodeUpdate,
+    ApiExploreResult, ApiExtendedStats, ApiFocusEntry, ApiFocusStats, ApiHiddenRequest, ApiHistoryEntry, ApiJob, ApiRollbackConflict, ApiMatchCandidates, ApiMatchRequest, ApiMediaItem, ApiMergeRequest, ApiNotification, ApiProgressPoint, ApiProvider,
+    ApiOutboxReplay, ApiProblem, ApiProviderError, ApiQueuedWrite, ApiProviderUnavailable, ApiImportPreview, ApiReadingServers, ApiReadingSync, ApiRecommendation, ApiDiscovery, ApiRefreshRequest, ApiRestore, ApiSchedule, ApiScheduledJob, ApiSession, ApiStats, ApiTelemetryPreview, ApiAniListLink, ApiAniListStatus, ApiTraktConnect, ApiTraktStatus, ApiNewWebhook, ApiNotifierTest, ApiRelation, ApiWebhook, ApiFranchise,
+    ApiFranchiseDetail, ApiFranchiseRename, ApiGoal, ApiGoals, ApiNewGoal, ApiStreak, ApiLeaderboardEntry, ApiRankChoice,
+    ApiRankEntry, ApiRankPair, ApiRankResult, ApiSuggestion, apply_status, media_type_of, parse_item_fields,
+};
+use crate::core::availability::{self, MediaManager};
+use crate::core::badge;
+use crate::core::archive::{Archive, ImportMode, ARCHIVE_VERSION};
+use crate::core::import::{parse_import, plan_merge, preview_merge, preview_replace, MergePlan};
+use crate::core::calendar::{self, CalendarEvent, EventTime};
+use crate::core::federation::Federation;
+use crate::core::focus::{self, FocusPin};
+use crate::core::franchise;
+use crate::core::goals;
+use crate::core::jobs::{JobKind, JobStatus};
+use crate::core::recommend;
+use crate::core::refresh::{self, ItemChanges};
+use crate::core::matcher::{self, MatchOutcome};
+use crate::core::models::{MediaItem, MediaItemType, ScoreScale, TitleLanguage};
+use crate::core::outbox::{self, QueuedOp, QueuedWrite};
+use crate::core::query;
+use crate::core::ranking::{self, Rating};
+use crate::core::reading_sync::{self, ReadingServer};
+use crate::core::notify::{self, Notice, NoticeEvent, NotifierConfig};
+use crate::core::schedule;
+use crate::core::scheduler::{self, JobResult};
+use crate::core::scrobble;
+use crate::core::suggest::{self, SuggestCache};
+use crate::core::settings::{self, Settings, ViewPreset};
+use crate::core::telemetry;
+use crate::core::validation;
+use crate::core::trakt::TraktAuth;
+use crate::core::write_through::{self, AniListAuth};
+use crate::core::webhooks::{self, WebhookEvent, WebhookPayload};
+use crate::core::search::{
+    normalize_isbn, Browse, MediaDetails, MediaSearchType, NextAiring, Relation, Release, Season, SearchError,
+    SearchProvider, SearchResult, UnavailableProvider,
+};
+use crate::core::storage::StorageError;
+use crate::infra::backup::{self, BackupInfo};
+use crate::infra::posters::{self, PosterSize};
+use crate::infra::s3::S3Target;
+use crate::infra::dashboard;
+use crate::infra::http;
+use crate::infra::telemetry as infra_telemetry;
+use crate::infra::tls::{self, TlsConfig};
+use crate::infra::notifiers;
+use crate::infra::webhooks as infra_webhooks;
+use crate::infra::database::{Database, Outbox};
 use crate::infra::anilist::AniListClient;
 use crate::infra::tmdb::TmdbClient;
 use crate::infra::openlibrary::OpenLibraryClient;
+use crate::infra::google_books::GoogleBooksClient;
+use crate::infra::audible::AudibleClient;
 use crate::infra::mangadex::MangaDexClient;
+use crate::infra::anime_lists::{self, AnimeListsClient};
+use crate::infra::trakt::{self, DeviceCode, Poll, TraktClient, TraktConfig};
+use crate::infra::kavita::KavitaClient;
+use crate::infra::komga::KomgaClient;
+use crate::infra::arr::ArrClient;
+
+mod background;
+pub mod base_path;
+pub mod bind;
+mod error;
+mod federation;
+mod graphql;
+mod profiles;
+pub mod rate_limit;
+mod request_log;
+
+use background::Scheduler;
+use bind::BindAddress;
+use error::ApiError;
+use request_log::log_error;
+
+use profiles::Profiles;
+use rate_limit::{Limiter, RateLimitConfig};
 
 // ── App state ────────────────────────────────────────────────
 
 pub struct WebState {
     pub db: Database,
+    /// Item writes waiting for Turso; None for local databases.
+    pub outbox: Option<Outbox>,
+}
+
+/// Handlers that only read take the read guard and run concurrently; any
+/// write, and any read-modify-write, takes the write guard.
+type SharedState = Arc<RwLock<WebState>>;
+type Searchers = Arc<Vec<Box<dyn SearchProvider + Send + Sync>>>;
+type ReadingServers = Arc<Vec<Box<dyn ReadingServer>>>;
+type MediaManagers = Arc<Vec<Box<dyn MediaManager>>>;
+
+/// Combined state passed to handlers via axum State extractor.
+#[derive(Clone)]
+struct AppState {
+    db_state: SharedState,
+    searchers: Searchers,
+    unavailable: Arc<Vec<UnavailableProvider>>,
+    backup_dir: Arc<PathBuf>,
+    /// Where new backups are copied to, if anywhere.
+    backup_target: Arc<Option<S3Target>>,
+    /// Local poster copies, when `local_posters` is on.
+    poster_dir: Arc<PathBuf>,
+    telemetry_endpoint: Arc<Option<String>>,
+    trakt: Arc<Option<TraktConfig>>,
+    reading_servers: ReadingServers,
+    media_managers: MediaManagers,
+    /// Background jobs of this library.
+    scheduler: Scheduler,
+    /// Recent typeahead answers, shared by every profile.
+    suggestions: Arc<Mutex<SuggestCache>>,
+}
+
+/// Search providers that could be enabled, plus the ones that could not.
+pub struct Providers {
+    pub searchers: Vec<Box<dyn SearchProvider + Send + Sync>>,
+    pub unavailable: Vec<UnavailableProvider>,
+    /// Kavita and Komga, when configured.
+    pub reading_servers: Vec<Box<dyn ReadingServer>>,
+    /// Sonarr and Radarr, when configured.
+    pub media_managers: Vec<Box<dyn MediaManager>>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// A library in `dir` with no providers or integrations set up.
+    async fn for_tests(dir: &std::path::Path) -> Self {
+        let db = Database::local(&dir.join("kars.db").to_string_lossy()).await.unwrap();
+        let db_state = Arc::new(RwLock::new(WebState { db, outbox: None }));
+        AppState {
+            scheduler: Scheduler::new(db_state.clone()),
+            db_state,
+            searchers: Arc::default(),
+            unavailable: Arc::default(),
+            backup_dir: Arc::new(dir.join("backups")),
+            backup_target: Arc::new(None),
+            poster_dir: Arc::new(dir.join("posters")),
+            telemetry_endpoint: Arc::new(None),
+            trakt: Arc::new(None),
+            reading_servers: Arc::default(),
+            media_managers: Arc::default(),
+            suggestions: Arc::new(Mutex::new(SuggestCache::new())),
+        }
+    }
+}
+
+// ── Server bootstrap ─────────────────────────────────────────
+
+/// Build search providers. Must be called **outside** an async context because
+/// reqwest::blocking::Client spawns its own Tokio runtime internally.
+pub fn build_searchers() -> Providers {
+    let mut searchers: Vec<Box<dyn SearchProvider + Send + Sync>> = vec![
+        Box::new(AniListClient::new()),
+        Box::new(MangaDexClient::new()),
+        Box::new(OpenLibraryClient::new()),
+        Box::new(GoogleBooksClient::new()),
+        Box::new(AudibleClient::new()),
+    ];
+    let mut unavailable = Vec::new();
+    if let Some(tmdb) = TmdbClient::from_env() {
+        searchers.push(Box::new(tmdb));
+    } else {
+        eprintln!("Note: TMDB_API_KEY not set — movie/series search disabled.");
+        unavailable.push(TmdbClient::missing_key());
+    }
+    let mut reading_servers: Vec<Box<dyn ReadingServer>> = Vec::new();
+    if let Some(kavita) = KavitaClient::from_env() {
+        reading_servers.push(Box::new(kavita));
+    }
+    if let Some(komga) = KomgaClient::from_env() {
+        reading_servers.push(Box::new(komga));
+    }
+    let media_managers: Vec<Box<dyn MediaManager>> = [ArrClient::sonarr_from_env(), ArrClient::radarr_from_env()]
+        .into_iter()
+        .flatten()
+        .map(|client| Box::new(client) as Box<dyn MediaManager>)
+        .collect();
+    Providers { searchers, unavailable, reading_servers, media_managers }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn start_server(
+    db: Database,
+    binds: Vec<BindAddress>,
+    providers: Providers,
+    backup_dir: PathBuf,
+    federation: Option<Federation>,
+    tls: Option<TlsConfig>,
+    rate_limit: Option<RateLimitConfig>,
+    base_path: String,
+) {
+    if let Err(e) = db.fail_interrupted_jobs().await {
+        eprintln!("Failed to reset interrupted jobs: {e}");
+    }
+
+    let outbox = if db.is_remote() {
+        let path = std::env::var("TURSO_OUTBOX_PATH").unwrap_or_else(|_| "data/outbox.db".into());
+        match Outbox::open(&path).await {
+            Ok(outbox) => Some(outbox),
+            Err(e) => {
+                eprintln!("Failed to open the outbox at {path}, writes fail while Turso is down: {e}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let backup_target = match S3Target::from_env() {
+        Some(Ok(target)) => {
+            println!("Backups: uploading to {}", target.location(""));
+            Some(target)
+        }
+        Some(Err(e)) => {
+            eprintln!("Backups stay local: {e}");
+            None
+        }
+        None => None,
+    };
+
+    let db_state = Arc::new(RwLock::new(WebState { db, outbox }));
+    let app_state = AppState {
+        scheduler: Scheduler::new(db_state.clone()),
+        db_state,
+        searchers: Arc::new(providers.searchers),
+        unavailable: Arc::new(providers.unavailable),
+        backup_dir: Arc::new(backup_dir),
+        backup_target: Arc::new(backup_target),
+        poster_dir: Arc::new(std::env::var("POSTER_DIR").unwrap_or_else(|_| "data/posters".into()).into()),
+        telemetry_endpoint: Arc::new(infra_telemetry::endpoint_from_env()),
+        trakt: Arc::new(TraktConfig::from_env()),
+        reading_servers: Arc::new(providers.reading_servers),
+        media_managers: Arc::new(providers.media_managers),
+        suggestions: Arc::new(Mutex::new(SuggestCache::new())),
+    };
+
+    spawn_background(&app_state).await;
+
+    let profiles = Profiles::open(app_state.clone(), profiles::dir_from_env(), &base_path).await;
+    let api = library_router(app_state.clone(), &base_path);
+    let api = match federation {
+        Some(federation) => {
+            println!("Federation: publishing completions as {}", federation.handle());
+            api.merge(federation::router(app_state, federation))
+        }
+        None => api,
+    };
+    let app = api.merge(profiles::router(profiles.clone()));
+    // Selecting a profile rewrites the path, so it has to run before routing.
+    let app = Router::new()
+        .fallback_service(app)
+        .layer(axum::middleware::from_fn_with_state(profiles, profiles::select));
+
+    // Covers every profile; CORS runs first so a 429 still carries its
+    // headers.
+    let app = match rate_limit {
+        Some(config) => {
+            println!(
+                "Rate limit: {} requests a minute per client, bursts of {}",
+                config.limit.per_minute, config.limit.burst
+            );
+            if config.key_header.is_none() && binds.iter().any(|b| matches!(b, BindAddress::Unix(_))) {
+                eprintln!("Note: requests through a Unix socket have no client IP and share one rate limit; set RATE_LIMIT_KEY_HEADER.");
+            }
+            app.layer(axum::middleware::from_fn_with_state(Limiter::new(config), rate_limit::limit))
+        }
+        None => app,
+    };
+    // Add CORS for development (Next.js on :3000 → Rust on :3001)
+    let app = app.layer(tower_http::cors::CorsLayer::permissive());
+    // Behind a prefix everything moves below it; the bare root points there.
+    let app = if base_path.is_empty() {
+        app
+    } else {
+        let home = format!("{base_path}/");
+        let index_base = base_path.clone();
+        Router::new()
+            .nest(&base_path, app)
+            // `nest` matches "/kars" but not "/kars/".
+            .route(&home, get(move || static_handler(axum::http::Uri::from_static("/"), index_base.clone())))
+            .route("/", get(move || async move { Redirect::temporary(&home) }))
+    };
+
+    // Outermost, so the log has the final status and the full path.
+    let log = request_log::enabled_from_env();
+    let app = app.layer(axum::middleware::from_fn(move |request, next| request_log::tag(log, request, next)));
+
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    let url = format!("{}{base_path}", binds[0].local_url(scheme));
+    let api_url = format!("{url}/api");
+    println!("╔══════════════════════════════════════════╗");
+    println!("║      KARS — Media Archive System         ║");
+    println!("║                                          ║");
+    println!("║  Web UI:  {url:<31}║");
+    println!("║  API:     {api_url:<31}║");
+    println!("╚══════════════════════════════════════════╝");
+    if binds.len() > 1 {
+        let all: Vec<String> = binds.iter().map(ToString::to_string).collect();
+        println!("Listening on {}", all.join(", "));
+    }
+
+    match tls {
+        Some(tls) => {
+            // main refuses Unix sockets together with TLS.
+            let addrs: Vec<std::net::SocketAddr> = binds
+                .into_iter()
+                .filter_map(|bind| match bind {
+                    BindAddress::Tcp(addr) => Some(addr),
+                    BindAddress::Unix(_) => None,
+                })
+                .collect();
+            if let Some(redirect_port) = tls.redirect_port {
+                let redirects: Vec<String> = tls::redirect_addrs(&addrs, redirect_port)
+                    .into_iter()
+                    .map(|addr| BindAddress::Tcp(addr).local_url("http"))
+                    .collect();
+                println!("Redirecting {} to HTTPS", redirects.join(", "));
+            }
+            tls::serve(app, addrs, tls).await
+        }
+        None => bind::serve(app, binds).await,
+    }
+}
+
+/// Everything served for one library: the REST API, GraphQL, the API docs
+/// and the embedded frontend.  `base_path` is the URL prefix the router
+/// ends up under.
+fn library_router(app_state: AppState, base_path: &str) -> Router {
+    let static_base = base_path.to_string();
+    Router::new()
+        .route("/api/items", get(list_items).post(create_item))
+        .route("/api/items/from-external", post(create_item_from_external))
+        .route(
+            "/api/items/{id}",
+            get(get_item).put(update_item).delete(delete_item),
+        )
+        .route("/api/posters/{id}/{size}", get(get_poster))
+        .route("/api/items/{id}/rewatch", post(rewatch_item))
+        .route("/api/items/{id}/progress/increment", post(increment_progress))
+        .route("/api/items/{id}/hidden", put(set_hidden))
+        .route("/api/items/{id}/merge", post(merge_item))
+        .route("/api/items/{id}/link", post(link_item))
+        .route("/api/items/{id}/match", post(match_item))
+        .route(
+            "/api/items/{id}/episodes",
+            get(list_episodes).post(mark_episodes),
+        )
+        .route("/api/items/{id}/episodes/{episode}", put(mark_episode))
+        .route("/api/items/{id}/history", get(item_history))
+        .route("/api/items/{id}/progress-history", get(item_progress_history))
+        .route("/api/items/{id}/related", get(related_items))
+        .route("/api/scrobble", post(scrobble))
+        .route("/api/resolve", get(resolve_ref))
+        .route("/api/external-refs/sync", post(sync_external_refs))
+        .route("/api/jobs", get(list_jobs))
+        .route("/api/jobs/refresh", post(start_refresh))
+        .route("/api/jobs/match", post(start_match))
+        .route("/api/jobs/{id}", get(get_job))
+        .route("/api/jobs/{id}/apply", post(apply_job))
+        .route("/api/jobs/{id}/rollback", post(rollback_job))
+        .route("/api/import", post(import_items))
+        .route("/api/export", get(export_archive))
+        .route("/api/export/sqlite", get(export_sqlite))
+        .route("/api/backup", post(trigger_backup))
+        .route("/api/backups", get(list_backups))
+        .route("/api/activity", get(activity_feed))
+        .route("/api/activity/recent", get(recently_updated))
+        .route("/api/search", get(search_items))
+        .route("/api/explore", get(explore_items))
+        .route("/api/explore/suggest", get(suggest_titles))
+        .route("/api/explore/isbn/{isbn}", get(explore_isbn))
+        .route("/api/explore/{source}/{external_id}", get(explore_preview))
+        .route("/api/explore/details", post(explore_details))
+        .route("/api/providers", get(list_providers))
+        .route("/api/settings", get(get_settings).put(update_settings))
+        .route("/api/view-presets", get(list_view_presets))
+        .route(
+            "/api/view-presets/{name}",
+            put(save_view_preset).delete(delete_view_preset),
+        )
+        .route("/api/telemetry/preview", get(preview_telemetry))
+        .route("/api/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/api/webhooks/{id}", delete(delete_webhook))
+        .route("/api/notifiers/test", post(test_notifiers))
+        .route("/api/anilist", get(anilist_status).put(link_anilist).delete(unlink_anilist))
+        .route("/api/trakt", get(trakt_status).delete(disconnect_trakt))
+        .route("/api/trakt/connect", post(connect_trakt))
+        .route("/api/trakt/import", post(import_trakt))
+        .route("/api/reading-servers", get(list_reading_servers))
+        .route("/api/reading-servers/sync", post(sync_reading_servers))
+        .route("/api/outbox", get(list_outbox))
+        .route("/api/outbox/replay", post(replay_outbox_now))
+        .route("/api/outbox/{id}", delete(discard_queued_write))
+        .route("/api/diagnostics", get(list_diagnostics).delete(clear_diagnostics))
+        .route("/api/admin/jobs", get(list_scheduled_jobs))
+        .route("/api/dashboard", get(get_dashboard))
+        .route("/api/stats", get(get_stats))
+        .route("/api/badge.svg", get(stats_badge))
+        .route("/api/stats/extended", get(extended_stats))
+        .route("/api/currently-into", get(currently_into))
+        .route("/api/ready-to-watch", get(ready_to_watch))
+        .route("/api/recommendations", get(recommendations))
+        .route("/api/recommendations/discover", get(discover))
+        .route("/api/stats/focus", get(focus_stats))
+        .route("/api/franchises", get(list_franchises))
+        .route(
+            "/api/franchises/{name}",
+            get(get_franchise).put(rename_franchise).delete(delete_franchise),
+        )
+        .route("/api/focus", get(list_focus))
+        .route("/api/focus/{id}", put(pin_item).delete(unpin_item))
+        .route("/api/rank/next-pair", get(next_rank_pair))
+        .route("/api/rank/choose", post(choose_ranked))
+        .route("/api/rank/leaderboard", get(rank_leaderboard))
+        .route("/api/goals", get(list_goals).post(set_goal))
+        .route("/api/goals/{id}", delete(delete_goal))
+        .route("/api/schedule", get(list_schedule))
+        .route("/api/calendar.ics", get(calendar_feed))
+        .route("/api/notifications", get(list_notifications))
+        .route("/api/notifications/{id}/read", post(mark_notification_read))
+        .with_state(app_state.clone())
+        .merge(graphql::router(app_state.clone(), base_path))
+        .merge(
+            SwaggerUi::new("/api/docs")
+                .url("/api/openapi.json", ApiDoc::openapi())
+                .config(utoipa_swagger_ui::Config::from(format!("{base_path}/api/openapi.json"))),
+        )
+        .fallback(move |uri| static_handler(uri, static_base.clone()))
+        .layer(axum::middleware::from_fn(error::json_errors))
+}
+
+/// Registers the jobs that work on one library's database with its
+/// scheduler: session reminders, automatic backups, webhook delivery,
+/// chapter and download checks, Trakt sync, AniList write-through,
+/// reading progress sync and outbox replay, plus telemetry where it is
+/// set up.  Jobs for services that are not configured are left out.
+async fn spawn_background(app_state: &AppState) -> Vec<tokio::task::AbortHandle> {
+    let jobs = &app_state.scheduler;
+    let db = &app_state.db_state;
+    let mut tasks = vec![
+        jobs.register(scheduler::REMINDERS, {
+            let db = db.clone();
+            move || send_reminders(db.clone())
+        }),
+        jobs.register(scheduler::BACKUPS, {
+            let (db, dir, target) = (db.clone(), app_state.backup_dir.clone(), app_state.backup_target.clone());
+            move || take_backup(db.clone(), dir.clone(), target.clone())
+        }),
+        jobs.register(scheduler::WEBHOOKS, {
+            let (db, sending) = (db.clone(), Arc::new(std::sync::Mutex::new(HashSet::new())));
+            move || deliver_webhooks(db.clone(), sending.clone())
+        }),
+        jobs.register(scheduler::NEW_CHAPTERS, {
+            let (db, searchers) = (db.clone(), app_state.searchers.clone());
+            move || check_new_chapters(db.clone(), searchers.clone())
+        }),
+        jobs.register(scheduler::NOTIFICATIONS, {
+            let (db, cursor) = (db.clone(), Arc::new(std::sync::Mutex::new(None)));
+            move || notify_completed(db.clone(), cursor.clone())
+        }),
+        jobs.register(scheduler::AIRED_EPISODES, {
+            let (db, searchers, upcoming) = (db.clone(), app_state.searchers.clone(), Arc::default());
+            move || notify_aired(db.clone(), searchers.clone(), Arc::clone(&upcoming))
+        }),
+        jobs.register(scheduler::WRITE_THROUGH, {
+            let db = db.clone();
+            move || send_write_through(db.clone())
+        }),
+    ];
+    if !app_state.media_managers.is_empty() {
+        let (db, managers) = (db.clone(), app_state.media_managers.clone());
+        tasks.push(jobs.register(scheduler::DOWNLOADS, move || check_downloads(db.clone(), managers.clone())));
+    }
+    if let Some(config) = app_state.trakt.as_ref().clone() {
+        let (db, config) = (db.clone(), Arc::new(config));
+        tasks.push(jobs.register(scheduler::TRAKT_SYNC, move || sync_trakt(db.clone(), config.clone())));
+    }
+    if !app_state.reading_servers.is_empty() {
+        let (db, servers) = (db.clone(), app_state.reading_servers.clone());
+        let last_run = Arc::new(std::sync::Mutex::new(None));
+        tasks.push(jobs.register(scheduler::READING_SYNC, move || {
+            sync_reading(db.clone(), servers.clone(), last_run.clone())
+        }));
+    }
+    if db.read().await.outbox.is_some() {
+        let db = db.clone();
+        tasks.push(jobs.register(scheduler::OUTBOX_REPLAY, move || replay_queued_writes(db.clone())));
+    }
+    if let Some(endpoint) = app_state.telemetry_endpoint.as_ref().clone() {
+        let db = db.clone();
+        tasks.push(jobs.register(scheduler::TELEMETRY, move || send_telemetry(db.clone(), endpoint.clone())));
+    }
+    tasks
+}
+
+// ── OpenAPI ──────────────────────────────────────────────────
+
+/// The spec served at `/api/openapi.json` and browsable at `/api/docs`.
+/// Every handler below carries a `#[utoipa::path]`; a new route needs
+/// one too and its function listed here.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "kars", description = "Media archive API"),
+    paths(
+        list_items, create_item, create_item_from_external, get_item, update_item, delete_item, get_poster, rewatch_item, increment_progress, set_hidden, merge_item,
+        link_item, match_item, scrobble,
+        list_episodes, mark_episodes, mark_episode, item_history, item_progress_history, related_items,
+        activity_feed,
+        recently_updated, search_items, get_dashboard, get_stats, stats_badge, extended_stats, currently_into, ready_to_watch, recommendations, discover, focus_stats,
+        list_focus, pin_item, unpin_item, next_rank_pair, choose_ranked, rank_leaderboard, list_goals, set_goal, delete_goal, list_franchises, get_franchise, rename_franchise,
+        delete_franchise, explore_items, suggest_titles, explore_preview, explore_isbn,
+        explore_details, resolve_ref, sync_external_refs, list_schedule, calendar_feed, list_notifications,
+        mark_notification_read, list_jobs, get_job, start_refresh, start_match, apply_job, rollback_job,
+        import_items, export_archive, export_sqlite, trigger_backup, list_backups,
+        list_providers, get_settings, update_settings, list_view_presets, save_view_preset,
+        delete_view_preset, preview_telemetry, list_webhooks, create_webhook, delete_webhook, test_notifiers,
+        trakt_status, connect_trakt, disconnect_trakt, import_trakt,
+        anilist_status, link_anilist, unlink_anilist, list_reading_servers, sync_reading_servers,
+        list_outbox, replay_outbox_now, discard_queued_write,
+        list_diagnostics, clear_diagnostics, list_scheduled_jobs, profiles::list_profiles, profiles::create_profile,
+        profiles::delete_profile, profiles::compare_scores,
+    ),
+    components(schemas(
+        ApiRestore, ApiImportPreview, WebhookPayload, ApiProviderError, ApiProviderUnavailable, ApiRollbackConflict,
+    )),
+)]
+struct ApiDoc;
+
+// ── Reminders ────────────────────────────────────────────────
+
+/// Turns sessions that are about to start into notifications.  Each
+/// session is only announced once.
+async fn send_reminders(db_state: SharedState) -> JobResult {
+    let st = db_state.write().await;
+    let settings = st.db.load_settings().await.map_err(|e| e.to_string())?;
+    let items = st.db.load_all().await.map_err(|e| e.to_string())?;
+    let lead = chrono::Duration::minutes(settings.reminder_lead_minutes as i64);
+    let reminders = schedule::session_reminders(&items, chrono::Utc::now(), lead);
+    st.db.add_notifications(&reminders).await.map_err(|e| e.to_string())?;
+    Ok(None)
+}
+
+// ── New chapters ─────────────────────────────────────────────
+
+/// Looks up the newest chapter of every readable being read, at each
+/// provider the item has a ref for, and stores it as the item's
+/// `latest_available`.  A provider that fails is logged and skipped.  The
+/// database is not locked while providers are asked.
+async fn check_new_chapters(db_state: SharedState, searchers: Searchers) -> JobResult {
+    let items = db_state.read().await.db.load_all().await.map_err(|e| e.to_string())?;
+    let reading: Vec<&MediaItem> = items.iter().filter(|i| schedule::follows_chapters(i)).collect();
+    if reading.is_empty() {
+        return Ok(None);
+    }
+
+    let mut latest = Vec::new();
+    for idx in 0..searchers.len() {
+        let prefix = format!("{}:", searchers[idx].source());
+        let refs: Vec<(Uuid, String)> = reading
+            .iter()
+            .flat_map(|i| {
+                i.external_refs
+                    .iter()
+                    .filter_map(|r| r.strip_prefix(&prefix))
+                    .map(|id| (i.id, id.to_string()))
+            })
+            .collect();
+        if refs.is_empty() {
+            continue;
+        }
+        let ids: Vec<String> = refs.iter().map(|(_, id)| id.clone()).collect();
+        let provider = Arc::clone(&searchers);
+        match tokio::task::spawn_blocking(move || provider[idx].latest_chapters(&ids)).await {
+            Ok(Ok(found)) => {
+                for chapter in found {
+                    for (item_id, _) in refs.iter().filter(|(_, id)| *id == chapter.ref_id) {
+                        latest.push((*item_id, chapter.chapter));
+                    }
+                }
+            }
+            Ok(Err(e)) => log_error!("Chapter check failed ({}): {e}", searchers[idx].name()),
+            Err(e) => log_error!("Chapter check failed: {e}"),
+        }
+    }
+
+    // Only changes are written; an item known at several providers
+    // keeps the highest chapter.
+    latest.sort_by_key(|&(id, chapter)| (id, std::cmp::Reverse(chapter)));
+    latest.dedup_by_key(|(id, _)| *id);
+    latest.retain(|(id, chapter)| {
+        items.iter().any(|i| i.id == *id && i.latest_available != Some(*chapter))
+    });
+    db_state.write().await.db.set_latest_chapters(&latest).await.map_err(|e| e.to_string())?;
+    Ok((!latest.is_empty()).then(|| format!("Chapter check: new chapters of {} item(s)", latest.len())))
+}
+
+// ── Downloads ────────────────────────────────────────────────
+
+/// Asks Sonarr and Radarr what they have on disk and stores it as the
+/// movies' and series' `downloaded` flag.  When one of them cannot be
+/// asked the flags stay as they are, rather than being cleared for
+/// everything it manages.  The database is not locked meanwhile.
+async fn check_downloads(db_state: SharedState, managers: MediaManagers) -> JobResult {
+    let mut downloads = Vec::new();
+    for idx in 0..managers.len() {
+        let manager = Arc::clone(&managers);
+        match tokio::task::spawn_blocking(move || manager[idx].downloads()).await {
+            Ok(Ok(found)) => downloads.extend(found),
+            Ok(Err(e)) => return Err(format!("{} failed: {e}", managers[idx].name())),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    let st = db_state.write().await;
+    let items = st.db.load_all().await.map_err(|e| e.to_string())?;
+    let downloaded = availability::downloaded_items(&items, &downloads);
+    let changes: Vec<(Uuid, bool)> = items
+        .iter()
+        .filter(|i| i.downloaded != downloaded.contains(&i.id))
+        .map(|i| (i.id, !i.downloaded))
+        .collect();
+    st.db.set_downloaded(&changes).await.map_err(|e| e.to_string())?;
+    Ok((!changes.is_empty()).then(|| format!("Download check: {} item(s) changed", changes.len())))
+}
+
+// ── Trakt sync ───────────────────────────────────────────────
+
+/// Pulls the connected Trakt account's history once `trakt_sync_hours`
+/// have passed and merges it like `POST /api/trakt/import`.  A sync that
+/// changes nothing leaves no import job behind.
+async fn sync_trakt(db_state: SharedState, config: Arc<TraktConfig>) -> JobResult {
+    let (auth, settings) = {
+        let st = db_state.read().await;
+        match (st.db.load_trakt_auth().await, st.db.load_settings().await) {
+            (Ok(Some(a)), Ok(s)) => (a, s),
+            (Ok(None), _) => return Ok(None),
+            (Err(e), _) | (_, Err(e)) => return Err(e.to_string()),
+        }
+    };
+    if !auth.sync_due(settings.trakt_sync_hours, chrono::Utc::now()) {
+        return Ok(None);
+    }
+
+    let items = fetch_trakt_items(&db_state, &config, auth).await?;
+    let st = db_state.write().await;
+    let stored = st.db.load_all().await.map_err(|e| e.to_string())?;
+    let plan = plan_merge(stored, trakt_archive(items)).without_unchanged();
+    let summary = if plan.writes.is_empty() {
+        None
+    } else {
+        let job = st.db.apply_merge(&plan).await.map_err(|e| e.to_string())?;
+        Some(format!("Trakt sync: {} item(s) updated (job {})", plan.writes.len(), job.id))
+    };
+    st.db.set_trakt_synced(chrono::Utc::now()).await.map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+// ── Reading progress sync ────────────────────────────────────
+
+/// Pulls reading progress from Kavita and Komga every
+/// `reading_sync_minutes`, starting at launch, like
+/// `POST /api/reading-servers/sync`.  `last_run` is when it last did.
+async fn sync_reading(
+    db_state: SharedState,
+    servers: ReadingServers,
+    last_run: Arc<std::sync::Mutex<Option<std::time::Instant>>>,
+) -> JobResult {
+    let minutes = db_state.read().await.db.load_settings().await.map_err(|e| e.to_string())?.reading_sync_minutes;
+    let every = std::time::Duration::from_secs(u64::from(minutes) * 60);
+    {
+        let mut last_run = last_run.lock().unwrap();
+        if minutes == 0 || last_run.is_some_and(|t| t.elapsed() < every) {
+            return Ok(None);
+        }
+        *last_run = Some(std::time::Instant::now());
+    }
+
+    let report = sync_reading_progress(&db_state, &servers).await.map_err(|e| e.to_string())?;
+    for failure in &report.errors {
+        log_error!("Reading sync: {} failed: {}", failure.provider, failure.error);
+    }
+    Ok(report
+        .job
+        .map(|job| format!("Reading sync: {} item(s) updated (job {})", job.items.len(), job.id)))
+}
+
+/// Replays queued item writes once Turso answers again.  Whether there is
+/// anything to replay is checked under the read lock, so the usual run
+/// with an empty outbox doesn't hold up requests.
+async fn replay_queued_writes(db_state: SharedState) -> JobResult {
+    if !replay_due(&*db_state.read().await).await.map_err(|e| e.to_string())? {
+        return Ok(None);
+    }
+    let st = db_state.write().await;
+    let report = replay_outbox(&st, false).await.map_err(|e| e.to_string())?;
+    for write in &report.conflicts {
+        let conflict = write.conflict.as_deref().unwrap_or_default();
+        log_error!("Outbox: {} of {} held back: {conflict}", write.op, write.item_id);
+    }
+    Ok((report.applied > 0).then(|| format!("Outbox: {} queued write(s) replayed", report.applied)))
+}
+
+/// Whether writes are queued and Turso answers.
+async fn replay_due(st: &WebState) -> Result<bool, StorageError> {
+    let Some(outbox) = &st.outbox else { return Ok(false) };
+    Ok(!outbox.list().await?.is_empty() && st.db.reachable().await)
+}
+
+/// Replays the queued writes in order, unless Turso is still down.  A
+/// write that would overwrite a change made meanwhile (see
+/// `outbox::conflict`) is held back with the reason, and so is every
+/// later write to the same item; `force` replays them regardless.
+async fn replay_outbox(st: &WebState, force: bool) -> Result<ApiOutboxReplay, StorageError> {
+    let Some(outbox) = &st.outbox else {
+        return Ok(ApiOutboxReplay::default());
+    };
+    let writes = outbox.list().await?;
+    if writes.is_empty() || !st.db.reachable().await {
+        return Ok(ApiOutboxReplay { pending: writes.len(), ..Default::default() });
+    }
+
+    let scale = st.db.load_settings().await?.score_scale;
+    let mut replayed = HashSet::new();
+    let mut held = HashSet::new();
+    let mut report = ApiOutboxReplay::default();
+    for mut write in writes {
+        let conflict = if held.contains(&write.item_id) && !force {
+            Some("Waits for an earlier write to the same item".to_string())
+        } else {
+            replay_write(&st.db, &write, scale, !force, replayed.contains(&write.item_id)).await?
+        };
+        match conflict {
+            None => {
+                outbox.remove(write.id).await?;
+                replayed.insert(write.item_id);
+                report.applied += 1;
+            }
+            Some(conflict) => {
+                outbox.set_conflict(write.id, Some(&conflict)).await?;
+                held.insert(write.item_id);
+                write.conflict = Some(conflict);
+                report.conflicts.push(ApiQueuedWrite::from(&write));
+                report.pending += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Stores one queued write the way its request would have, checked
+/// against the library as it is now.  Returns why it was held back.
+async fn replay_write(
+    db: &Database,
+    write: &QueuedWrite,
+    scale: ScoreScale,
+    check: bool,
+    replayed_item: bool,
+) -> Result<Option<String>, StorageError> {
+    let existing = db.get_item(write.item_id).await?;
+    let Some(payload) = write.payload.clone() else {
+        if check && let Some(conflict) = outbox::conflict(write, existing.as_ref(), replayed_item) {
+            return Ok(Some(conflict));
+        }
+        db.delete_item(write.item_id).await?;
+        return Ok(None);
+    };
+
+    if let Err(errors) = validation::validate_item(&payload, scale) {
+        return Ok(Some(ApiProblem::invalid(errors).detail));
+    }
+    let mut item = match payload.out_of_scale(scale, existing.as_ref()).and_then(ApiMediaItem::into_media_item) {
+        Ok(item) => item,
+        Err(e) => return Ok(Some(e)),
+    };
+    let current = match write.op {
+        QueuedOp::Create { .. } => find_existing(db, &item).await?,
+        _ => existing,
+    };
+    if check && let Some(conflict) = outbox::conflict(write, current.as_ref(), replayed_item) {
+        return Ok(Some(conflict));
+    }
+    if let Some(current) = &current {
+        match write.op {
+            QueuedOp::Create { upsert: true } => item.id = current.id,
+            QueuedOp::Update => item.follow_updated_unit(current),
+            _ => {}
+        }
+    }
+    db.upsert_item(&mut item).await?;
+    Ok(None)
+}
+
+/// Reads every server (without the database locked) and moves the
+/// matching readables forward in one import job, which can be rolled
+/// back.  A server that fails is reported and the others still apply.
+async fn sync_reading_progress(db_state: &SharedState, servers: &ReadingServers) -> Result<ApiReadingSync, StorageError> {
+    let mut fetched = Vec::new();
+    let mut errors = Vec::new();
+    for idx in 0..servers.len() {
+        let provider = servers[idx].name().to_string();
+        let all = servers.clone();
+        match tokio::task::spawn_blocking(move || all[idx].progress()).await {
+            Ok(Ok(series)) => fetched.extend(series),
+            Ok(Err(e)) => errors.push(ApiProviderError { provider, error: e.to_string() }),
+            Err(e) => errors.push(ApiProviderError { provider, error: e.to_string() }),
+        }
+    }
+
+    let st = db_state.write().await;
+    let mut stored = st.db.load_all().await?;
+    let mut writes: Vec<(Option<MediaItem>, MediaItem)> = Vec::new();
+    let mut unmatched = Vec::new();
+    for series in &fetched {
+        let Some(id) = reading_sync::find_readable(&stored, series).map(|item| item.id) else {
+            unmatched.push(series.title.clone());
+            continue;
+        };
+        let Some(item) = stored.iter_mut().find(|item| item.id == id) else { continue };
+        let before = item.clone();
+        if !reading_sync::apply(item, series) {
+            continue;
+        }
+        match writes.iter_mut().find(|(_, w)| w.id == id) {
+            Some(write) => write.1 = item.clone(),
+            None => writes.push((Some(before), item.clone())),
+        }
+    }
+    unmatched.sort();
+    unmatched.dedup();
+
+    let job = if writes.is_empty() {
+        None
+    } else {
+        let plan = MergePlan { writes, activity: Default::default(), reasons: HashMap::new() };
+        Some(ApiJob::from(&st.db.apply_merge(&plan).await?))
+    };
+    Ok(ApiReadingSync { job, unmatched, errors })
+}
+
+// ── AniList write-through ────────────────────────────────────
+
+/// Most queued updates sent per run, to stay under AniList's rate limit.
+const WRITE_THROUGH_BATCH: usize = 20;
+
+/// Audit log entries read at a time when queueing.
+const WRITE_THROUGH_LOG_BATCH: u32 = 100;
+
+/// Queues AniList items whose progress, score or status changed since the
+/// last run, then sends the queue to the linked account.  Changes are
+/// only queued while `anilist_write_through` is on; the queue is kept
+/// while it is off.
+async fn send_write_through(db_state: SharedState) -> JobResult {
+    let (auth, queue) = {
+        let st = db_state.write().await;
+        let (mut auth, settings) = match (st.db.load_anilist_auth().await, st.db.load_settings().await) {
+            (Ok(Some(a)), Ok(s)) => (a, s),
+            (Ok(None), _) => return Ok(None),
+            (Err(e), _) | (_, Err(e)) => return Err(e.to_string()),
+        };
+        queue_write_through(&st.db, &mut auth, settings.anilist_write_through)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !settings.anilist_write_through {
+            return Ok(None);
+        }
+        let mut queue = Vec::new();
+        let updates = st.db.anilist_queue().await.map_err(|e| e.to_string())?;
+        for update in updates.into_iter().take(WRITE_THROUGH_BATCH) {
+            // Items deleted or no longer from AniList are dropped.
+            let entry = match st.db.get_item(update.item_id).await {
+                Ok(item) => item.as_ref().and_then(write_through::list_entry),
+                Err(e) => {
+                    log_error!("Write-through check failed: {e}");
+                    continue;
+                }
+            };
+            match entry {
+                Some(entry) => queue.push((update, entry)),
+                None => {
+                    if let Err(e) = st.db.dequeue_anilist_update(update.item_id).await {
+                        log_error!("Write-through check failed: {e}");
+                    }
+                }
+            }
+        }
+        (auth, queue)
+    };
+    if queue.is_empty() {
+        return Ok(None);
+    }
+
+    let token = auth.token.clone();
+    let entries: Vec<_> = queue.iter().map(|(_, entry)| entry.clone()).collect();
+    let results = tokio::task::spawn_blocking(move || {
+        let client = AniListClient::with_token(&token);
+        entries.iter().map(|e| client.save_entry(e).map_err(|e| e.to_string())).collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let st = db_state.write().await;
+    let mut sent = 0;
+    for ((update, _), result) in queue.iter().zip(results) {
+        let stored = match result {
+            Ok(()) => {
+                sent += 1;
+                st.db.dequeue_anilist_update(update.item_id).await
+            }
+            Err(e) if update.attempts + 1 >= write_through::MAX_ATTEMPTS => {
+                log_error!("AniList update of {} dropped: {e}", update.item_id);
+                st.db.dequeue_anilist_update(update.item_id).await
+            }
+            Err(e) => st.db.fail_anilist_update(update.item_id, &e).await,
+        };
+        if let Err(e) = stored {
+            log_error!("Write-through failed: {e}");
+        }
+    }
+    Ok((sent > 0).then(|| format!("Write-through: {sent} update(s) sent to AniList")))
+}
+
+/// Reads the audit log after the account's cursor and queues the AniList
+/// items it changed (only when `enabled`), moving the cursor past it.
+async fn queue_write_through(db: &Database, auth: &mut AniListAuth, enabled: bool) -> Result<(), StorageError> {
+    loop {
+        let entries = db.history_since(auth.history_cursor, WRITE_THROUGH_LOG_BATCH).await?;
+        let Some(last) = entries.last() else { return Ok(()) };
+        let mut ids: Vec<Uuid> = Vec::new();
+        if enabled {
+            for entry in entries.iter().filter(|e| write_through::is_synced(e.kind)) {
+                if !ids.contains(&entry.item_id) {
+                    ids.push(entry.item_id);
+                }
+            }
+        }
+        auth.history_cursor = last.id;
+        db.queue_anilist_updates(&ids, auth.history_cursor).await?;
+        if entries.len() < WRITE_THROUGH_LOG_BATCH as usize {
+            return Ok(());
+        }
+    }
+}
+
+// ── Backups ──────────────────────────────────────────────────
+
+/// Takes a backup when the newest one is older than the configured
+/// interval, then drops the oldest beyond the configured count.  The
+/// notifiers hear of backups that fail.
+async fn take_backup(db_state: SharedState, dir: Arc<PathBuf>, target: Arc<Option<S3Target>>) -> JobResult {
+    let result = write_backup(&db_state, &dir, &target).await;
+    if let Err(e) = &result {
+        notify(&db_state, Notice::backup_failed(e)).await;
+    }
+    result
+}
+
+async fn write_backup(db_state: &SharedState, dir: &std::path::Path, target: &Option<S3Target>) -> JobResult {
+    let st = db_state.write().await;
+    let settings = st.db.load_settings().await.map_err(|e| e.to_string())?;
+    if settings.backup_interval_hours == 0 {
+        return Ok(None);
+    }
+
+    let interval = chrono::Duration::hours(settings.backup_interval_hours as i64);
+    let latest = backup::list_backups(dir).map_err(|e| e.to_string())?.first().map(|b| b.created_at);
+    if latest.is_some_and(|at| chrono::Utc::now() - at < interval) {
+        return Ok(None);
+    }
+
+    let info = backup::create_backup(&st.db, dir).await.map_err(|e| e.to_string())?;
+    drop(st);
+    let uploaded = match upload_backup(target, dir, &info).await {
+        Some(Ok(location)) => format!(", uploaded to {location}"),
+        Some(Err(e)) => {
+            let error = format!("Uploading backup {} failed: {e}", info.name);
+            log_error!("{error}");
+            notify(db_state, Notice::backup_failed(&error)).await;
+            String::new()
+        }
+        None => String::new(),
+    };
+    if let Err(e) = backup::prune_backups(dir, settings.backup_keep as usize) {
+        log_error!("Pruning backups failed: {e}");
+    }
+    Ok(Some(format!("Backup {} written{uploaded}", info.name)))
+}
+
+/// Copies a backup just written to the configured bucket.  None when
+/// there is none.  Remote copies are never pruned; a lifecycle rule on
+/// the bucket can expire them.
+async fn upload_backup(target: &Option<S3Target>, dir: &std::path::Path, info: &BackupInfo) -> Option<Result<String, String>> {
+    let target = target.clone()?;
+    let path = dir.join(&info.name);
+    let result = tokio::task::spawn_blocking(move || target.upload(&path))
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+    Some(result)
+}
+
+// ── Telemetry ────────────────────────────────────────────────
+
+/// Sends the anonymous report while `telemetry_enabled` is on.  The job
+/// first runs a day after startup.
+async fn send_telemetry(db_state: SharedState, endpoint: String) -> JobResult {
+    let usage = db_state.read().await.db.usage().await.map_err(|e| e.to_string())?;
+    if !usage.settings.telemetry_enabled {
+        return Ok(None);
+    }
+
+    let payload = telemetry::build_payload(&usage);
+    tokio::task::spawn_blocking(move || infra_telemetry::send(&endpoint, &payload))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(None)
+}
+
+// ── Webhooks ─────────────────────────────────────────────────
+
+/// Most audit log entries turned into events per run.
+const WEBHOOK_BATCH: u32 = 100;
+
+/// Turns new audit log entries into webhook deliveries.  Each webhook
+/// keeps its own place in the log, stored with it, so changes made while
+/// the server was down are still sent.  Every webhook's batch is sent on
+/// its own task: a receiver that is down and being retried holds up only
+/// itself, and is skipped by later runs until its batch is done.
+/// `sending` holds the ids of those webhooks.
+async fn deliver_webhooks(db_state: SharedState, sending: Arc<std::sync::Mutex<HashSet<i64>>>) -> JobResult {
+    let st = db_state.read().await;
+    let hooks = st.db.list_webhooks().await.map_err(|e| e.to_string())?;
+    let mut items: HashMap<Uuid, Option<ApiMediaItem>> = HashMap::new();
+    for hook in hooks {
+        if sending.lock().unwrap().contains(&hook.id) {
+            continue;
+        }
+        let entries = st.db.history_since(hook.delivered_through, WEBHOOK_BATCH).await.map_err(|e| e.to_string())?;
+        let Some(last) = entries.last().map(|entry| entry.id) else { continue };
+
+        let mut deliveries = Vec::new();
+        for entry in &entries {
+            let events = webhooks::events_for(entry);
+            if events.is_empty() {
+                continue;
+            }
+            let item = match items.get(&entry.item_id) {
+                Some(item) => item.clone(),
+                None => {
+                    let item = match st.db.get_item(entry.item_id).await {
+                        Ok(i) => i.as_ref().map(ApiMediaItem::from),
+                        Err(e) => {
+                            log_error!("Webhook check failed: {e}");
+                            None
+                        }
+                    };
+                    items.insert(entry.item_id, item.clone());
+                    item
+                }
+            };
+            for event in events {
+                let payload = WebhookPayload::new(event, entry, item.clone());
+                match serde_json::to_vec(&payload) {
+                    Ok(body) => deliveries.push((event, format!("{}-{}", entry.id, event.as_str()), body)),
+                    Err(e) => log_error!("Webhook payload failed: {e}"),
+                }
+            }
+        }
+
+        sending.lock().unwrap().insert(hook.id);
+        let (db_state, sending) = (db_state.clone(), sending.clone());
+        tokio::spawn(async move {
+            let id = hook.id;
+            let sent = tokio::task::spawn_blocking(move || {
+                for (event, delivery, body) in &deliveries {
+                    if let Err(e) = infra_webhooks::deliver(&hook, *event, delivery, body) {
+                        log_error!("Webhook {} failed: {e}", hook.id);
+                    }
+                }
+            })
+            .await;
+            // Failed deliveries are not retried past `deliver`'s own
+            // attempts, so the batch counts as done either way.
+            match sent {
+                Ok(()) => {
+                    let st = db_state.write().await;
+                    if let Err(e) = st.db.set_webhook_delivered(id, last).await {
+                        log_error!("Webhook {id} progress not saved: {e}");
+                    }
+                }
+                Err(e) => log_error!("Webhook {id} task failed: {e}"),
+            }
+            sending.lock().unwrap().remove(&id);
+        });
+    }
+    Ok(None)
+}
+
+// ── Notifications ────────────────────────────────────────────
+
+/// Sends `notices` through every configured notifier that wants their
+/// event and returns how many were sent.  Failures are logged; the other
+/// notifiers still get theirs.
+async fn send_notices(notifiers: Vec<NotifierConfig>, notices: Vec<Notice>) -> usize {
+    if notifiers.is_empty() || notices.is_empty() {
+        return 0;
+    }
+    tokio::task::spawn_blocking(move || {
+        let mut sent = 0;
+        for config in &notifiers {
+            let notifier = notifiers::build(&config.channel);
+            for notice in notices.iter().filter(|n| config.wants(n.event)) {
+                match notifier.send(notice) {
+                    Ok(()) => sent += 1,
+                    Err(e) => log_error!("Notifier {} failed: {e}", notifier.name()),
+                }
+            }
+        }
+        sent
+    })
+    .await
+    .unwrap_or_else(|e| {
+        log_error!("Sending notifications failed: {e}");
+        0
+    })
+}
+
+/// Sends a notice to the configured notifiers, from a job that has no
+/// settings at hand.  Unreadable settings are logged.
+async fn notify(db_state: &SharedState, notice: Notice) {
+    match db_state.read().await.db.load_settings().await {
+        Ok(settings) => {
+            send_notices(settings.notifiers, vec![notice]).await;
+        }
+        Err(e) => log_error!("Sending notifications failed: {e}"),
+    }
+}
+
+/// Tells the notifiers about items completed by hand since the last run,
+/// from the audit log like `deliver_webhooks`.
+async fn notify_completed(db_state: SharedState, cursor: Arc<std::sync::Mutex<Option<i64>>>) -> JobResult {
+    let (notifiers, notices) = {
+        let st = db_state.read().await;
+        let Some(after) = *cursor.lock().unwrap() else {
+            let latest = st.db.latest_history_id().await.map_err(|e| e.to_string())?;
+            *cursor.lock().unwrap() = Some(latest);
+            return Ok(None);
+        };
+        let entries = st.db.history_since(after, WEBHOOK_BATCH).await.map_err(|e| e.to_string())?;
+        let Some(last) = entries.last() else { return Ok(None) };
+        *cursor.lock().unwrap() = Some(last.id);
+        let notices: Vec<Notice> = entries
+            .iter()
+            .filter(|entry| webhooks::events_for(entry).contains(&WebhookEvent::Completed))
+            .map(|entry| Notice::completed(&entry.title))
+            .collect();
+        (st.db.load_settings().await.map_err(|e| e.to_string())?.notifiers, notices)
+    };
+    let sent = send_notices(notifiers, notices).await;
+    Ok((sent > 0).then(|| format!("Notifications: {sent} sent")))
+}
+
+/// Tells the notifiers about episodes of followed series that aired since
+/// the last run.  `upcoming` holds the next episodes then looked up; the
+/// providers are only asked while a notifier wants these events.
+async fn notify_aired(
+    db_state: SharedState,
+    searchers: Searchers,
+    upcoming: Arc<std::sync::Mutex<HashMap<Uuid, (String, NextAiring)>>>,
+) -> JobResult {
+    let (settings, items) = {
+        let st = db_state.read().await;
+        (
+            st.db.load_settings().await.map_err(|e| e.to_string())?,
+            st.db.load_all().await.map_err(|e| e.to_string())?,
+        )
+    };
+    if !settings.notifiers.iter().any(|n| n.wants(NoticeEvent::EpisodeAired)) {
+        upcoming.lock().unwrap().clear();
+        return Ok(None);
+    }
+    let lookup = next_airing(&searchers, &items).await;
+    let notices = notify::aired_episodes(&mut upcoming.lock().unwrap(), &lookup, chrono::Utc::now());
+    let sent = send_notices(settings.notifiers, notices).await;
+    Ok((sent > 0).then(|| format!("Notifications: {sent} sent")))
+}
+
+/// The scale item scores are shown and entered in (see
+/// `Settings::score_scale`).  Unreadable settings fall back to 0-10.
+async fn score_scale(db: &Database) -> ScoreScale {
+    db.load_settings().await.map(|s| s.score_scale).unwrap_or_default()
+}
+
+/// The language search results are titled in (see
+/// `Settings::title_language`).  Unreadable settings fall back to English.
+async fn title_language(db: &Database) -> TitleLanguage {
+    db.load_settings().await.map(|s| s.title_language).unwrap_or_default()
+}
+
+/// Whether adult titles are left out (see `Settings::hide_adult`).
+/// Unreadable settings show everything.
+async fn hide_adult(db: &Database) -> bool {
+    db.load_settings().await.is_ok_and(|s| s.hide_adult)
+}
+
+// ── GET /api/items ───────────────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListQuery {
+    /// Also list hidden items.
+    #[serde(default)]
+    include_hidden: bool,
+    /// Comma-separated item ids (at most 500): only these items, in this
+    /// order, hidden and adult ones included.  Unknown ids are left out.
+    ids: Option<String>,
+    /// Comma-separated fields to return, e.g. `title,poster_url,status`;
+    /// `id` is always included.  All fields when left out.
+    fields: Option<String>,
+}
+
+/// Most ids `GET /api/items?ids=` takes at once.
+const MAX_BATCH_IDS: usize = 500;
+
+/// Every item, most recently updated first (by title among ties).  Hidden
+/// items are left out unless asked for, and with `hide_adult` on, so are
+/// items rated erotica or pornographic.  With `ids`, just those items,
+/// fetched in one query; with `fields`, only those fields of each.
+#[utoipa::path(
+    get,
+    path = "/api/items",
+    tag = "items",
+    params(ListQuery),
+    responses(
+        (status = 200, body = Vec<ApiMediaItem>),
+        (status = 400, description = "An invalid id, too many, or an unknown field", body = ApiError),
+    )
+)]
+async fn list_items(State(state): State<AppState>, Query(params): Query<ListQuery>) -> Response {
+    let fields = match params.fields.as_deref().map(parse_item_fields).transpose() {
+        Ok(fields) => fields,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    let items = match &params.ids {
+        Some(ids) => {
+            let mut wanted: Vec<Uuid> = Vec::new();
+            for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                match Uuid::parse_str(id) {
+                    Ok(id) if !wanted.contains(&id) => wanted.push(id),
+                    Ok(_) => {}
+                    Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid id '{id}'")).into_response(),
+                }
+            }
+            if wanted.len() > MAX_BATCH_IDS {
+                let message = format!("At most {MAX_BATCH_IDS} ids can be fetched at once");
+                return ApiError::new(StatusCode::BAD_REQUEST, message).into_response();
+            }
+            st.db.get_items(&wanted).await
+        }
+        None => {
+            let hide_adult = hide_adult(&st.db).await;
+            st.db.load_all().await.map(|mut items| {
+                items.retain(|i| (params.include_hidden || !i.hidden) && !(hide_adult && i.is_adult()));
+                // None (never stored with a timestamp) sorts last.
+                items.sort_by_key(|i| std::cmp::Reverse(i.updated_at));
+                items
+            })
+        }
+    };
+    let items = match items {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let api = items.iter().map(|i| ApiMediaItem::from(i).in_scale(scale));
+    match fields {
+        Some(fields) => Json(api.map(|i| i.only(&fields)).collect::<Vec<_>>()).into_response(),
+        None => Json(api.collect::<Vec<_>>()).into_response(),
+    }
+}
+
+// ── POST /api/items?upsert=... ───────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct CreateQuery {
+    /// Overwrite the item already in the library instead of answering 409.
+    #[serde(default)]
+    upsert: bool,
+}
+
+/// Adds an item.  An item from the same provider entry, or of the same
+/// kind under the same title, is answered with 409 and the stored item,
+/// so the client can offer to merge into it or to overwrite it with
+/// `upsert=true`.
+#[utoipa::path(
+    post,
+    path = "/api/items",
+    tag = "items",
+    params(CreateQuery),
+    request_body = ApiMediaItem,
+    responses(
+        (status = 200, description = "Overwrote the item already in the library (`upsert=true`)", body = ApiMediaItem),
+        (status = 201, body = ApiMediaItem),
+        (status = 202, description = "Turso is unreachable; the item is queued", body = ApiQueuedWrite),
+        (status = 400, description = "Invalid item", body = ApiProblem, content_type = "application/problem+json"),
+        (status = 409, description = "Already in the library", body = ApiError),
+    )
+)]
+async fn create_item(
+    State(state): State<AppState>,
+    Query(params): Query<CreateQuery>,
+    Json(mut payload): Json<ApiMediaItem>,
+) -> Response {
+    let scale = score_scale(&state.db_state.read().await.db).await;
+    if payload.status.is_empty() {
+        let settings = state.db_state.read().await.db.load_settings().await.unwrap_or_default();
+        payload.status = settings.default_status(&payload.media_type).to_string();
+    }
+    if let Err(errors) = validation::validate_item(&payload, scale) {
+        return invalid(errors);
+    }
+    let queued = payload.clone();
+    let mut item = match payload.out_of_scale(scale, None).and_then(ApiMediaItem::into_media_item) {
+        Ok(i) => i,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    add_details(&state, &mut item).await;
+    store_new_item(&state, item, queued, params.upsert, scale).await
+}
+
+/// Stores an item about to be added, with its relations and a local
+/// poster copy.  `queued` is the item as sent, queued for later when
+/// Turso is unreachable.
+async fn store_new_item(
+    state: &AppState,
+    mut item: MediaItem,
+    mut queued: ApiMediaItem,
+    upsert: bool,
+    scale: ScoreScale,
+) -> Response {
+    let relations = fetch_relations(state, &item).await;
+
+    let st = state.db_state.write().await;
+
+    let existing = match find_existing(&st.db, &item).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            queued.id = item.id.to_string();
+            let op = QueuedOp::Create { upsert };
+            let write = QueuedWrite::new(item.id, op, Some(queued), chrono::Utc::now());
+            return match queue_offline(&st, write).await {
+                Some(response) => response,
+                None => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            };
+        }
+    };
+    let status = match existing {
+        // Hand the stored item back so the client can choose.
+        Some(existing) if !upsert => {
+            let msg = format!("{} is already in the library", existing.title);
+            let details = ApiMediaItem::from(&existing).in_scale(scale);
+            return ApiError::new(StatusCode::CONFLICT, msg).with_details(details).into_response();
+        }
+        Some(existing) => {
+            item.id = existing.id;
+            StatusCode::OK
+        }
+        None => StatusCode::CREATED,
+    };
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => {
+            if let Some(relations) = relations
+                && let Err(e) = st.db.replace_relations(item.id, &relations).await
+            {
+                log_error!("Storing relations failed: {e}");
+            }
+            keep_poster(state, &st.db, &item).await;
+            let api = ApiMediaItem::from(&item).in_scale(scale);
+            (status, Json(api)).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/from-external ────────────────────────────
+
+/// Adds a provider entry by its id: the server looks up the entry and its
+/// details and builds the item itself, so clients need not copy explore
+/// results field by field.  Duplicates are handled as by `POST /api/items`.
+#[utoipa::path(
+    post,
+    path = "/api/items/from-external",
+    tag = "items",
+    params(CreateQuery),
+    request_body = ApiFromExternal,
+    responses(
+        (status = 200, description = "Overwrote the item already in the library (`upsert=true`)", body = ApiMediaItem),
+        (status = 201, body = ApiMediaItem),
+        (status = 202, description = "Turso is unreachable; the item is queued", body = ApiQueuedWrite),
+        (status = 400, description = "An unknown type or one the provider has no entries of; an invalid status is answered with an ApiProblem", body = ApiError),
+        (status = 404, description = "Unknown provider or entry", body = ApiError),
+        (status = 409, description = "Already in the library", body = ApiError),
+        (status = 502, description = "The provider failed", body = ApiError),
+        (status = 504, description = "The provider did not answer in time", body = ApiError),
+    )
+)]
+async fn create_item_from_external(
+    State(state): State<AppState>,
+    Query(params): Query<CreateQuery>,
+    Json(payload): Json<ApiFromExternal>,
+) -> Response {
+    let (result, details) =
+        match fetch_provider_entry(&state, &payload.source, &payload.external_id, payload.media_type.as_deref()).await {
+            Ok(entry) => entry,
+            Err(e) => return e.into_response(),
+        };
+    let (scale, language, settings) = {
+        let st = state.db_state.read().await;
+        let settings = st.db.load_settings().await.unwrap_or_default();
+        (score_scale(&st.db).await, title_language(&st.db).await, settings)
+    };
+
+    let mut item = result.in_language(language).into_media_item();
+    details.apply(&mut item);
+    let status = payload.status.unwrap_or_else(|| settings.default_status(media_type_of(&item)).to_string());
+    let mut queued = ApiMediaItem::from(&item).in_scale(scale);
+    queued.status = status;
+    if let Err(errors) = validation::validate_item(&queued, scale) {
+        return invalid(errors);
+    }
+    apply_status(&mut item.media_type, &queued.status);
+    store_new_item(&state, item, queued, params.upsert, scale).await
+}
+
+/// Queues an item write that failed because Turso cannot be reached and
+/// answers 202.  None when the failure has another cause, or the
+/// database is local, and is to be reported as it is.
+async fn queue_offline(st: &WebState, write: QueuedWrite) -> Option<Response> {
+    let outbox = st.outbox.as_ref()?;
+    if st.db.reachable().await {
+        return None;
+    }
+    Some(match outbox.push(write).await {
+        Ok(write) => (StatusCode::ACCEPTED, Json(ApiQueuedWrite::from(&write))).into_response(),
+        Err(e) => {
+            let msg = format!("Turso is unreachable and the write could not be queued: {e}");
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
+        }
+    })
+}
+
+/// A 400 with an RFC 7807 body listing the invalid fields.
+fn invalid(errors: Vec<validation::FieldError>) -> Response {
+    let headers = [(axum::http::header::CONTENT_TYPE, "application/problem+json")];
+    (StatusCode::BAD_REQUEST, headers, Json(ApiProblem::invalid(errors))).into_response()
+}
+
+/// The stored item a new one would duplicate: the one from the same
+/// provider entry, otherwise one of the same kind with the same title.
+async fn find_existing(db: &Database, item: &MediaItem) -> Result<Option<MediaItem>, StorageError> {
+    if let (Some(source), Some(external_id)) = (&item.source, &item.external_id)
+        && let Some(existing) = db.find_by_external_id(source, external_id).await?
+    {
+        return Ok(Some(existing));
+    }
+    let items = db.load_all().await?;
+    Ok(matcher::find_same_title(&items, item).cloned())
+}
+
+/// Fills in the provider's detail metadata (overview, genres, runtime,
+/// seasons) of an item about to be added.  A failed lookup is logged and
+/// the item is added as sent.
+async fn add_details(state: &AppState, item: &mut MediaItem) {
+    if let Some(details) = fetch_details(state, item).await {
+        details.apply(item);
+    }
+}
+
+/// Looks up the provider's detail metadata of an item.  None when the
+/// item has no provider entry, the provider has no details or the lookup
+/// failed (logged).
+async fn fetch_details(state: &AppState, item: &MediaItem) -> Option<MediaDetails> {
+    let external_id = item.external_id.clone()?;
+    let idx = state
+        .searchers
+        .iter()
+        .position(|s| item.source.as_deref() == Some(s.source()))?;
+    let search_type = refresh::search_type_for(item);
+    let searchers = Arc::clone(&state.searchers);
+    match tokio::task::spawn_blocking(move || searchers[idx].details(&external_id, search_type)).await {
+        Ok(Ok(details)) => details,
+        Ok(Err(e)) => {
+            log_error!("Details lookup failed ({}): {e}", state.searchers[idx].name());
+            None
+        }
+        Err(e) => {
+            log_error!("Details lookup failed: {e}");
+            None
+        }
+    }
+}
+
+/// Looks up the entries related to an item at its provider.  None when
+/// the item has no provider entry or the lookup failed (logged), so that
+/// stored relations are kept.
+async fn fetch_relations(state: &AppState, item: &MediaItem) -> Option<Vec<Relation>> {
+    let external_id = item.external_id.clone()?;
+    let idx = state
+        .searchers
+        .iter()
+        .position(|s| item.source.as_deref() == Some(s.source()))?;
+    let searchers = Arc::clone(&state.searchers);
+    match tokio::task::spawn_blocking(move || searchers[idx].relations(&external_id)).await {
+        Ok(Ok(relations)) => Some(relations),
+        Ok(Err(e)) => {
+            log_error!("Relations lookup failed ({}): {e}", state.searchers[idx].name());
+            None
+        }
+        Err(e) => {
+            log_error!("Relations lookup failed: {e}");
+            None
+        }
+    }
+}
+
+// ── GET /api/items/:id ───────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/items/{id}",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn get_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    match st.db.get_item(uuid).await {
+        Ok(Some(item)) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Ok(None) => ApiError::not_found("Item").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── PUT /api/items/:id ───────────────────────────────────────
+
+#[utoipa::path(
+    put,
+    path = "/api/items/{id}",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    request_body = ApiMediaItem,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 202, description = "Turso is unreachable; the update is queued", body = ApiQueuedWrite),
+        (status = 400, description = "Invalid item", body = ApiProblem, content_type = "application/problem+json"),
+    )
+)]
+async fn update_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(mut payload): Json<ApiMediaItem>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    // Ensure the ID in the path matches the body
+    payload.id = uuid.to_string();
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    if let Err(errors) = validation::validate_item(&payload, scale) {
+        return invalid(errors);
+    }
+    let existing = match st.db.get_item(uuid).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            let write = QueuedWrite::new(uuid, QueuedOp::Update, Some(payload), chrono::Utc::now());
+            return match queue_offline(&st, write).await {
+                Some(response) => response,
+                None => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            };
+        }
+    };
+    let mut item = match payload
+        .out_of_scale(scale, existing.as_ref())
+        .and_then(ApiMediaItem::into_media_item)
+    {
+        Ok(i) => i,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    if let Some(existing) = &existing {
+        item.follow_updated_unit(existing);
+    }
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => {
+            if existing.is_some_and(|e| e.poster_url != item.poster_url) {
+                if let Err(e) = posters::remove(&state.poster_dir, item.id) {
+                    log_error!("Removing the old poster of {} failed: {e}", item.id);
+                }
+                keep_poster(&state, &st.db, &item).await;
+            }
+            let api = ApiMediaItem::from(&item).in_scale(scale);
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── DELETE /api/items/:id ────────────────────────────────────
+
+#[utoipa::path(
+    delete,
+    path = "/api/items/{id}",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 202, description = "Turso is unreachable; the delete is queued", body = ApiQueuedWrite),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn delete_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    match st.db.delete_item(uuid).await {
+        Ok(true) => {
+            if let Err(e) = posters::remove(&state.poster_dir, uuid) {
+                log_error!("Removing the poster of {uuid} failed: {e}");
+            }
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(false) => ApiError::not_found("Item").into_response(),
+        Err(e) => {
+            let write = QueuedWrite::new(uuid, QueuedOp::Delete, None, chrono::Utc::now());
+            match queue_offline(&st, write).await {
+                Some(response) => response,
+                None => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }
+    }
+}
+
+// ── GET /api/posters/:id/:size ───────────────────────────────
+
+/// An item's poster from the local copy, in `small` (160 px wide),
+/// `medium` (342 px) or `original` size.  With `local_posters` on, a
+/// poster that was not stored yet is downloaded first.
+#[utoipa::path(
+    get,
+    path = "/api/posters/{id}/{size}",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+        ("size" = String, Path, description = "`small`, `medium` or `original`"),
+    ),
+    responses(
+        (status = 200, description = "The poster; thumbnails are JPEG", content_type = "image/jpeg", body = Vec<u8>),
+        (status = 400, description = "Invalid id or size", body = ApiError),
+        (status = 404, description = "No such item, or its poster is not stored", body = ApiError),
+        (status = 502, description = "The poster could not be downloaded", body = ApiError),
+    )
+)]
+async fn get_poster(State(state): State<AppState>, Path((id, size)): Path<(String, String)>) -> Response {
+    let Ok(uuid) = Uuid::parse_str(&id) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response();
+    };
+    let Some(size) = PosterSize::parse(&size) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Size must be small, medium or original").into_response();
+    };
+
+    if posters::read(&state.poster_dir, uuid, size).is_none() {
+        let (item, settings) = {
+            let st = state.db_state.read().await;
+            (st.db.get_item(uuid).await, st.db.load_settings().await)
+        };
+        let url = match (item, settings) {
+            (Ok(None), _) => return ApiError::not_found("Item").into_response(),
+            (Ok(Some(item)), Ok(settings)) if settings.local_posters => item.poster_url,
+            (Ok(Some(_)), Ok(_)) => None,
+            (Err(e), _) | (_, Err(e)) => {
+                return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+        };
+        let Some(url) = url else {
+            return ApiError::not_found("Poster").into_response();
+        };
+        let dir = Arc::clone(&state.poster_dir);
+        let stored = tokio::task::spawn_blocking(move || posters::store(&dir, uuid, &url))
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+        if let Err(e) = stored {
+            return ApiError::new(StatusCode::BAD_GATEWAY, e).into_response();
+        }
+    }
+
+    match posters::read(&state.poster_dir, uuid, size) {
+        Some((data, content_type)) => (
+            StatusCode::OK,
+            [
+                ("content-type", content_type),
+                ("cache-control", "public, max-age=86400"),
+            ],
+            data,
+        )
+            .into_response(),
+        None => ApiError::not_found("Poster").into_response(),
+    }
+}
+
+/// Downloads an item's poster in the background when `local_posters` is
+/// on.  Failures are logged; the poster is tried again when it is first
+/// requested.
+async fn keep_poster(state: &AppState, db: &Database, item: &MediaItem) {
+    let Some(url) = item.poster_url.clone() else { return };
+    if !db.load_settings().await.is_ok_and(|s| s.local_posters) {
+        return;
+    }
+    let (dir, id) = (Arc::clone(&state.poster_dir), item.id);
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = posters::store(&dir, id, &url) {
+            log_error!("Storing the poster of {id} failed: {e}");
+        }
+    });
+}
+
+// ── POST /api/items/:id/rewatch ──────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/rewatch",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+        (status = 409, description = "Item is not completed", body = ApiError),
+    )
+)]
+async fn rewatch_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let mut item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    if !item.start_rewatch() {
+        return ApiError::new(StatusCode::CONFLICT, "Only completed items can be rewatched").into_response();
+    }
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/:id/progress/increment?by=... ────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct IncrementQuery {
+    /// Episodes / chapters to add; defaults to 1.
+    by: Option<u32>,
+    /// Add volumes instead of chapters (readables only).
+    volumes: Option<bool>,
+}
+
+/// Adds to an item's progress.  A planned item becomes watching/reading;
+/// reaching the total completes it.  Adding volumes makes them the
+/// progress shown for the item.
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/progress/increment",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+        IncrementQuery,
+    ),
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+        (status = 409, description = "The item is a movie or already completed, or not a readable when adding volumes", body = ApiError),
+    )
+)]
+async fn increment_progress(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<IncrementQuery>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+    let by = params.by.unwrap_or(1);
+    if by == 0 {
+        return ApiError::new(StatusCode::BAD_REQUEST, "by must be at least 1").into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let mut item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    if params.volumes.unwrap_or(false) {
+        if !item.advance_volumes(by) {
+            return ApiError::new(StatusCode::CONFLICT, "Only unfinished readables have volumes to add to").into_response();
+        }
+    } else {
+        if !item.advance(by) {
+            let msg = "Only unfinished series and readables have progress to add to";
+            return ApiError::new(StatusCode::CONFLICT, msg).into_response();
+        }
+        item.prefer_volumes = false;
+    }
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── PUT /api/items/:id/hidden ────────────────────────────────
+
+/// Hides an item from lists and searches, or shows it again.  It still
+/// counts in stats.
+#[utoipa::path(
+    put,
+    path = "/api/items/{id}/hidden",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    request_body = ApiHiddenRequest,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn set_hidden(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiHiddenRequest>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let mut item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    item.hidden = payload.hidden;
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/:id/merge ────────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/merge",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    request_body = ApiMergeRequest,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn merge_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiMergeRequest>,
+) -> Response {
+    let (keep_id, dup_id) = match (Uuid::parse_str(&id), Uuid::parse_str(&payload.duplicate_id)) {
+        (Ok(k), Ok(d)) => (k, d),
+        _ => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+    if keep_id == dup_id {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Cannot merge an item into itself").into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let (mut keep, duplicate) = match (st.db.get_item(keep_id).await, st.db.get_item(dup_id).await) {
+        (Ok(Some(k)), Ok(Some(d))) => (k, d),
+        (Ok(_), Ok(_)) => return ApiError::not_found("Item").into_response(),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    keep.merge(duplicate.clone());
+    match st.db.merge_items(&mut keep, &duplicate).await {
+        Ok(()) => Json(ApiMediaItem::from(&keep).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/:id/link ─────────────────────────────────
+
+/// Attaches an explore result to an item that was added by hand instead
+/// of creating a second entry for it.
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/link",
+    tag = "items",
+    params(("id" = String, Path, description = "Item id (UUID)")),
+    request_body = ApiExploreResult,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 400, description = "Invalid id or a match of another kind", body = ApiError),
+        (status = 404, description = "No such item", body = ApiError),
+        (status = 409, description = "Another item already has this match", body = ApiError),
+    )
+)]
+async fn link_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiExploreResult>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    link_and_store(&st.db, item, &payload).await
+}
+
+/// Links `item` to `result` and stores it, unless the item is already
+/// linked elsewhere or another item has this match.
+async fn link_and_store(db: &Database, mut item: MediaItem, result: &ApiExploreResult) -> Response {
+    let scale = score_scale(db).await;
+    let previous = (item.source.clone(), item.external_id.clone());
+    if let Err(e) = result.link_to(&mut item) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+    if let (Some(source), Some(ext)) = previous
+        && (item.source.as_deref(), item.external_id.as_deref()) != (Some(source.as_str()), Some(ext.as_str()))
+    {
+        let msg = format!("Item is already linked to {source} {ext}");
+        return ApiError::new(StatusCode::CONFLICT, msg).into_response();
+    }
+
+    let ext = item.external_id.as_deref().unwrap_or_default();
+    match db.find_by_external_id(&result.source, ext).await {
+        Ok(Some(other)) if other.id != item.id => {
+            let msg = format!("{} is already linked to this entry", other.title);
+            let details = ApiMediaItem::from(&other).in_scale(scale);
+            return ApiError::new(StatusCode::CONFLICT, msg).with_details(details).into_response();
+        }
+        Ok(_) => {}
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+
+    match db.upsert_item(&mut item).await {
+        Ok(()) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/:id/match ────────────────────────────────
+
+/// Searches the providers for a manually added item's title.  Lists the
+/// candidates, or with `pick` links the item to one of them like
+/// `/api/items/:id/link` does.
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/match",
+    tag = "items",
+    params(("id" = String, Path, description = "Item id (UUID)")),
+    request_body = Option<ApiMatchRequest>,
+    responses(
+        (status = 200, description = "Without `pick`: the candidates (ApiMatchCandidates). With `pick`: the linked item (ApiMediaItem).", body = ApiMatchCandidates),
+        (status = 400, description = "Invalid id, unknown type or no candidate with the picked id", body = ApiError),
+        (status = 404, description = "No such item", body = ApiError),
+        (status = 409, description = "Item is already linked, or another item already has this match", body = ApiError),
+        (status = 502, description = "Every provider failed", body = ApiError),
+    )
+)]
+async fn match_item(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    payload: Option<Json<ApiMatchRequest>>,
+) -> Response {
+    let Json(payload) = payload.unwrap_or_default();
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let item = {
+        let st = state.db_state.read().await;
+        match st.db.get_item(uuid).await {
+            Ok(Some(item)) => item,
+            Ok(None) => return ApiError::not_found("Item").into_response(),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    };
+    if let (Some(source), Some(ext)) = (&item.source, &item.external_id) {
+        let msg = format!("Item is already linked to {source} {ext}");
+        return ApiError::new(StatusCode::CONFLICT, msg).into_response();
+    }
+    let search_type = match payload.media_type.as_deref() {
+        None => matcher::search_type_for_unmatched(&item),
+        Some(name) => match MediaSearchType::parse(name) {
+            Some(search_type) => search_type,
+            None => return ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown type '{name}'")).into_response(),
+        },
+    };
+
+    let results = match search_chain(&state, &item.title, search_type).await {
+        Ok(results) => results,
+        Err(e) => return ApiError::new(StatusCode::BAD_GATEWAY, e).into_response(),
+    };
+    let language = title_language(&state.db_state.read().await.db).await;
+    let candidates: Vec<ApiExploreResult> =
+        results.iter().map(|r| ApiExploreResult::from_search_result(r, language)).collect();
+
+    let Some(pick) = payload.pick else {
+        let suggested = match matcher::match_title(&item.title, &results) {
+            MatchOutcome::Exact(idx) => Some(idx),
+            MatchOutcome::Ambiguous | MatchOutcome::NoResults => None,
+        };
+        let body = ApiMatchCandidates {
+            media_type: search_type.as_str().to_string(),
+            candidates,
+            suggested,
+        };
+        return Json(body).into_response();
+    };
+    let Some(candidate) = candidates.iter().find(|c| c.external_id.as_deref() == Some(pick.as_str())) else {
+        let msg = format!("No candidate with external_id '{pick}'");
+        return ApiError::new(StatusCode::BAD_REQUEST, msg).into_response();
+    };
+
+    let st = state.db_state.write().await;
+    // The item may have changed during the search.
+    let item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    link_and_store(&st.db, item, candidate).await
+}
+
+/// Searches the providers for `search_type` in the configured fallback
+/// order until one returns something.  Fails when no provider serves the
+/// type or every provider that was asked failed.
+async fn search_chain(
+    state: &AppState,
+    title: &str,
+    search_type: MediaSearchType,
+) -> Result<Vec<SearchResult>, String> {
+    let chain = {
+        let st = state.db_state.read().await;
+        st.db.load_settings().await.map_err(|e| e.to_string())?.chain_for(search_type.as_str()).to_vec()
+    };
+    let names: Vec<&str> = state.searchers.iter().map(|s| s.name()).collect();
+
+    let (mut answered, mut last_error) = (false, None);
+    for idx in settings::chain_order(&names, &chain) {
+        if !state.searchers[idx].supported_types().contains(&search_type) {
+            continue;
+        }
+        let searchers = Arc::clone(&state.searchers);
+        let q = title.to_string();
+        let task = tokio::task::spawn_blocking(move || searchers[idx].search(&q, search_type, 1));
+        let error = match tokio::time::timeout(EXPLORE_PROVIDER_TIMEOUT, task).await {
+            Ok(Ok(Ok(results))) if results.is_empty() => {
+                answered = true;
+                continue;
+            }
+            Ok(Ok(Ok(results))) => return Ok(results),
+            Ok(Ok(Err(e))) => {
+                if let SearchError::Payload { provider, message, snippet } = &e {
+                    let st = state.db_state.write().await;
+                    if let Err(e) = st.db.record_diagnostic(provider, message, snippet).await {
+                        log_error!("Failed to record provider diagnostic: {e}");
+                    }
+                }
+                e.to_string()
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("No answer within {} s", EXPLORE_PROVIDER_TIMEOUT.as_secs()),
+        };
+        log_error!("Search provider {} error: {error}", state.searchers[idx].name());
+        last_error = Some(format!("{}: {error}", state.searchers[idx].name()));
+    }
+    match last_error {
+        Some(error) if !answered => Err(error),
+        None if !answered => Err(format!("No provider for {} is configured", search_type.as_str())),
+        _ => Ok(Vec::new()),
+    }
+}
+
+// ── GET /api/items/:id/episodes ──────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/items/{id}/episodes",
+    tag = "episodes",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = Vec<ApiEpisode>),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn list_episodes(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.read().await;
+    match st.db.get_item(uuid).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+    match st.db.list_episodes(uuid).await {
+        Ok(episodes) => {
+            let api: Vec<ApiEpisode> = episodes.iter().map(ApiEpisode::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/items/:id/episodes ─────────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/items/{id}/episodes",
+    tag = "episodes",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    request_body = ApiEpisodeUpdate,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn mark_episodes(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ApiEpisodeUpdate>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+    set_episodes(&state, uuid, &payload.episodes, payload.watched).await
+}
+
+// ── PUT /api/items/:id/episodes/:episode ─────────────────────
+
+#[utoipa::path(
+    put,
+    path = "/api/items/{id}/episodes/{episode}",
+    tag = "episodes",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+        ("episode" = u32, Path, description = "Episode number"),
+    ),
+    request_body = ApiEpisodeMark,
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn mark_episode(
+    State(state): State<AppState>,
+    Path((id, episode)): Path<(String, u32)>,
+    Json(payload): Json<ApiEpisodeMark>,
+) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+    set_episodes(&state, uuid, &[episode], payload.watched).await
+}
+
+/// Records episode watch state and derives the series' progress counter
+/// from the number of watched episodes, counting the progress made
+/// before episodes were marked one by one.
+async fn set_episodes(state: &AppState, id: Uuid, episodes: &[u32], watched: bool) -> Response {
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let mut item = match st.db.get_item(id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let (current, total) = match &item.media_type {
+        MediaItemType::Series(p, _) => (p.current, p.total),
+        _ => {
+            return ApiError::new(StatusCode::BAD_REQUEST, "Episode tracking is only available for series").into_response();
+        }
+    };
+    if let Some(&bad) = episodes
+        .iter()
+        .find(|&&n| n == 0 || total.is_some_and(|t| n > t))
+    {
+        return ApiError::new(StatusCode::BAD_REQUEST, format!("Episode {bad} is out of range")).into_response();
+    }
+
+    let watched_count = match st.db.set_episodes_watched(id, current, episodes, watched).await {
+        Ok(c) => c,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if let MediaItemType::Series(p, _) = &mut item.media_type {
+        p.current = watched_count;
+    }
+
+    match st.db.upsert_item(&mut item).await {
+        Ok(()) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/scrobble ───────────────────────────────────────
+
+/// Receives plays from a media server: a Plex webhook (multipart, with a
+/// `payload` field), a Jellyfin webhook (JSON) or a Trakt-style scrobble
+/// body.  A finished play of a movie or episode is matched to a library
+/// item and advances it.
+#[utoipa::path(
+    post,
+    path = "/api/scrobble",
+    tag = "items",
+    request_body(content = String, description = "Plex or Jellyfin webhook, or `{\"movie\"}` / `{\"show\", \"episode\"}` with `progress`", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The matched item, advanced unless it already was this far", body = ApiMediaItem),
+        (status = 204, description = "Not a finished play; ignored"),
+        (status = 400, description = "Unreadable body", body = ApiError),
+        (status = 404, description = "No library item matches the play", body = ApiError),
+    )
+)]
+async fn scrobble(State(state): State<AppState>, request: axum::extract::Request) -> Response {
+    let body = match scrobble_body(request).await {
+        Ok(body) => body,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let play = match scrobble::parse(&body) {
+        Ok(Some(play)) => play,
+        Ok(None) => return StatusCode::NO_CONTENT.into_response(),
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let scale = score_scale(&st.db).await;
+    let items = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let Some(mut item) = scrobble::find_item(&items, &play).cloned() else {
+        let msg = format!("No library item matches '{}'", play.title);
+        return ApiError::new(StatusCode::NOT_FOUND, msg).into_response();
+    };
+    if scrobble::apply(&mut item, &play)
+        && let Err(e) = st.db.upsert_item(&mut item).await
+    {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    Json(ApiMediaItem::from(&item).in_scale(scale)).into_response()
+}
+
+/// The JSON of a scrobble request; Plex sends it as the `payload` field of
+/// a multipart form.
+async fn scrobble_body(request: axum::extract::Request) -> Result<serde_json::Value, String> {
+    use axum::extract::{FromRequest, Multipart};
+
+    let multipart = request
+        .headers()
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("multipart/form-data"));
+    if !multipart {
+        let bytes = axum::body::Bytes::from_request(request, &()).await.map_err(|e| e.to_string())?;
+        return serde_json::from_slice(&bytes).map_err(|e| e.to_string());
+    }
+
+    let mut form = Multipart::from_request(request, &()).await.map_err(|e| e.to_string())?;
+    while let Some(field) = form.next_field().await.map_err(|e| e.to_string())? {
+        if field.name() == Some("payload") {
+            let text = field.text().await.map_err(|e| e.to_string())?;
+            return serde_json::from_str(&text).map_err(|e| e.to_string());
+        }
+    }
+    Err("Missing 'payload' field".into())
+}
+
+// ── GET /api/items/:id/related ───────────────────────────────
+
+/// Sequels, prequels, adaptations and other entries related to an item,
+/// as its provider reported them when it was added or last refreshed,
+/// each with the library item it matches, if any.
+#[utoipa::path(
+    get,
+    path = "/api/items/{id}/related",
+    tag = "items",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = Vec<ApiRelation>),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn related_items(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.read().await;
+    match st.db.get_item(uuid).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+    let relations = match st.db.list_relations(uuid).await {
+        Ok(r) => r,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut related = Vec::with_capacity(relations.len());
+    for relation in &relations {
+        match st.db.find_by_provider_entry(&relation.source, &relation.external_id).await {
+            Ok(item) => related.push(ApiRelation::new(relation, item.as_ref())),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    Json(related).into_response()
+}
+
+// ── GET /api/items/:id/history ───────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/items/{id}/history",
+    tag = "activity",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = Vec<ApiHistoryEntry>),
+    )
+)]
+async fn item_history(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.read().await;
+    match st.db.item_history(uuid).await {
+        Ok(entries) => {
+            let api: Vec<ApiHistoryEntry> = entries.iter().map(ApiHistoryEntry::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/items/:id/progress-history ──────────────────────
+
+/// Every progress update of an item with its time, oldest first, e.g. to
+/// chart the pace it was watched or read at.
+#[utoipa::path(
+    get,
+    path = "/api/items/{id}/progress-history",
+    tag = "activity",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, body = Vec<ApiProgressPoint>),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn item_progress_history(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.read().await;
+    match st.db.get_item(uuid).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+    match st.db.item_progress_history(uuid).await {
+        Ok(events) => {
+            let api: Vec<ApiProgressPoint> = events.iter().map(ApiProgressPoint::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/activity?limit=... ──────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LimitQuery {
+    /// How many entries to return at most.
+    limit: Option<u32>,
+}
+
+const DEFAULT_ACTIVITY_LIMIT: u32 = 50;
+const MAX_ACTIVITY_LIMIT: u32 = 500;
+
+#[utoipa::path(
+    get,
+    path = "/api/activity",
+    tag = "activity",
+    params(LimitQuery),
+    responses(
+        (status = 200, body = Vec<ApiHistoryEntry>),
+    )
+)]
+async fn activity_feed(
+    State(state): State<AppState>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).min(MAX_ACTIVITY_LIMIT);
+
+    let st = state.db_state.read().await;
+    match st.db.recent_activity(limit).await {
+        Ok(entries) => {
+            let api: Vec<ApiHistoryEntry> = entries.iter().map(ApiHistoryEntry::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/activity/recent?limit=... ───────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/activity/recent",
+    tag = "activity",
+    params(LimitQuery),
+    responses(
+        (status = 200, body = Vec<ApiMediaItem>),
+    )
+)]
+async fn recently_updated(
+    State(state): State<AppState>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).min(MAX_ACTIVITY_LIMIT);
+
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    match st.db.recently_updated(limit).await {
+        Ok(items) => {
+            let api: Vec<ApiMediaItem> = items.iter().map(|i| ApiMediaItem::from(i).in_scale(scale)).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/search?q=... ────────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SearchQuery {
+    /// Search such as `status:watching tag:fantasy score>=8 frieren`
+    /// (see the README); plain words match titles.
+    q: Option<String>,
+    /// Also list hidden items.
+    #[serde(default)]
+    include_hidden: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    tag = "items",
+    params(SearchQuery),
+    responses(
+        (status = 200, body = Vec<ApiMediaItem>),
+        (status = 400, description = "Invalid search", body = ApiError),
+    )
+)]
+async fn search_items(
+    State(state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Response {
+    let query = params.q.unwrap_or_default();
+    if query.is_empty() {
+        return Json(Vec::<ApiMediaItem>::new()).into_response();
+    }
+
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    let sql = match query::parse(&query).and_then(|q| q.to_sql(scale)) {
+        Ok(sql) => sql,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let hide_adult = hide_adult(&st.db).await;
+    match st.db.query_items(&sql).await {
+        Ok(items) => {
+            let api: Vec<ApiMediaItem> = items
+                .iter()
+                .filter(|i| (params.include_hidden || !i.hidden) && !(hide_adult && i.is_adult()))
+                .map(|i| ApiMediaItem::from(i).in_scale(scale))
+                .collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/stats ───────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/stats",
+    tag = "stats",
+    responses(
+        (status = 200, body = ApiStats),
+    )
+)]
+async fn get_stats(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.stats().await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/badge.svg?metric=... ────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct BadgeQuery {
+    /// One of `total`, `completed` (the default), `watching`, `planned`,
+    /// `on_hold`, `dropped`, `movies`, `series`, `anime`, `readable` or
+    /// `rewatches`.
+    metric: Option<String>,
+    /// Text on the left; defaults to `kars`.
+    label: Option<String>,
+    /// A shields.io color name or a hex color; defaults to `blue`.
+    color: Option<String>,
+}
+
+/// A shields.io-style SVG badge with a library number, e.g. "kars | 412
+/// completed", to embed in a README or blog.  Hidden items count.
+#[utoipa::path(
+    get,
+    path = "/api/badge.svg",
+    tag = "stats",
+    params(BadgeQuery),
+    responses(
+        (status = 200, description = "SVG badge", content_type = "image/svg+xml"),
+        (status = 400, description = "Unknown metric or color", body = ApiError),
+    )
+)]
+async fn stats_badge(State(state): State<AppState>, Query(params): Query<BadgeQuery>) -> Response {
+    let metric = params.metric.as_deref().unwrap_or("completed");
+    let color = params.color.as_deref().unwrap_or(badge::DEFAULT_COLOR);
+    let Some(fill) = badge::color(color) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown color '{color}'")).into_response();
+    };
+
+    let stats = match state.db_state.read().await.db.stats().await {
+        Ok(stats) => stats,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let Some((count, words)) = badge::metric(&stats, metric) else {
+        let message = format!("Unknown metric '{metric}'; expected one of {}", badge::METRICS.join(", "));
+        return ApiError::new(StatusCode::BAD_REQUEST, message).into_response();
+    };
+
+    let label = params.label.as_deref().unwrap_or("kars");
+    (
+        [
+            ("content-type", "image/svg+xml; charset=utf-8"),
+            // Image proxies such as GitHub's cache by these headers.
+            ("cache-control", "max-age=300"),
+        ],
+        badge::render(label, &format!("{count} {words}"), &fill),
+    )
+        .into_response()
+}
+
+// ── GET /api/dashboard ───────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/dashboard",
+    tag = "stats",
+    responses(
+        (status = 200, body = ApiDashboard),
+    )
+)]
+async fn get_dashboard(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let now = chrono::Utc::now();
+    let items = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let midnight = schedule::end_of_today(now);
+    let airing_today = next_airing(&state.searchers, &items)
+        .await
+        .iter()
+        .filter(|(_, next)| next.airing_at < midnight)
+        .count();
+    match dashboard::load(&st.db, now, Some(airing_today)).await {
+        Ok(d) => Json(d).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/stats/extended ──────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/stats/extended",
+    tag = "stats",
+    responses(
+        (status = 200, body = ApiExtendedStats),
+    )
+)]
+async fn extended_stats(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.extended_stats().await {
+        Ok(stats) => Json(stats).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/currently-into ──────────────────────────────────
+
+/// How many items the "currently into" widget shows.
+const CURRENTLY_INTO_LIMIT: usize = 3;
+
+#[utoipa::path(
+    get,
+    path = "/api/currently-into",
+    tag = "stats",
+    responses(
+        (status = 200, body = Vec<ApiEngagement>),
+    )
+)]
+async fn currently_into(State(state): State<AppState>) -> Response {
+    let now = chrono::Utc::now();
+    let since = now - chrono::Duration::days(ACTIVITY_WINDOW_DAYS);
+
+    let st = state.db_state.read().await;
+    let events = match st.db.progress_events_since(since).await {
+        Ok(e) => e,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let items = match st.db.load_all().await {
+        Ok(i) => i,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let ranked = activity::currently_into(&items, &events, now, CURRENTLY_INTO_LIMIT);
+    let api: Vec<ApiEngagement> = ranked.iter().map(ApiEngagement::from).collect();
+    Json(api).into_response()
+}
+
+// ── GET /api/ready-to-watch ──────────────────────────────────
+
+/// Planned movies and series that Sonarr or Radarr has downloaded, by
+/// title.  Empty unless `SONARR_URL` or `RADARR_URL` is set.
+#[utoipa::path(
+    get,
+    path = "/api/ready-to-watch",
+    tag = "items",
+    responses(
+        (status = 200, body = Vec<ApiMediaItem>),
+    )
+)]
+async fn ready_to_watch(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    match st.db.load_all().await {
+        Ok(items) => {
+            let api: Vec<ApiMediaItem> = items
+                .iter()
+                .filter(|i| availability::ready_to_watch(i))
+                .map(|i| ApiMediaItem::from(i).in_scale(scale))
+                .collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/recommendations?limit=... ───────────────────────
+
+const DEFAULT_RECOMMENDATIONS: u32 = 10;
+const MAX_RECOMMENDATIONS: u32 = 50;
+
+/// Planned items worth starting next, each with the signals behind it.
+#[utoipa::path(
+    get,
+    path = "/api/recommendations",
+    tag = "stats",
+    params(LimitQuery),
+    responses((status = 200, body = Vec<ApiRecommendation>))
+)]
+async fn recommendations(
+    State(state): State<AppState>,
+    Query(params): Query<LimitQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_RECOMMENDATIONS).min(MAX_RECOMMENDATIONS);
+
+    let st = state.db_state.read().await;
+    match st.db.load_all().await {
+        Ok(items) => {
+            let ranked = recommend::recommend(&items, limit as usize);
+            let api: Vec<ApiRecommendation> = ranked.iter().map(ApiRecommendation::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/recommendations/discover?limit=... ──────────────
+
+/// How many of the best liked items are asked about.
+const DISCOVERY_SEEDS: usize = 5;
+
+/// Titles outside the library that providers suggest for the best liked
+/// items (AniList recommendations, TMDB similar titles).  A provider that
+/// fails is logged and skipped.
+#[utoipa::path(
+    get,
+    path = "/api/recommendations/discover",
+    tag = "stats",
+    params(LimitQuery),
+    responses((status = 200, body = Vec<ApiDiscovery>))
+)]
+async fn discover(State(state): State<AppState>, Query(params): Query<LimitQuery>) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_RECOMMENDATIONS).min(MAX_RECOMMENDATIONS);
+
+    let (items, hide_adult, language) = {
+        let st = state.db_state.read().await;
+        match st.db.load_all().await {
+            Ok(items) => (items, hide_adult(&st.db).await, title_language(&st.db).await),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    };
+
+    let seeds = recommend::discovery_seeds(&items, DISCOVERY_SEEDS);
+    let lookups: Vec<(usize, usize, String, MediaSearchType)> = seeds
+        .iter()
+        .enumerate()
+        .filter_map(|(seed, item)| {
+            let idx = state
+                .searchers
+                .iter()
+                .position(|s| item.source.as_deref() == Some(s.source()))?;
+            Some((seed, idx, item.external_id.clone()?, refresh::search_type_for(item)))
+        })
+        .collect();
+
+    let searchers = Arc::clone(&state.searchers);
+    let found = tokio::task::spawn_blocking(move || {
+        lookups
+            .into_iter()
+            .filter_map(|(seed, idx, external_id, search_type)| {
+                match searchers[idx].recommendations(&external_id, search_type) {
+                    Ok(mut results) => {
+                        results.retain(|r| !(hide_adult && r.is_adult()));
+                        Some((seed, results))
+                    }
+                    Err(e) => {
+                        log_error!("Recommendations lookup failed ({}): {e}", searchers[idx].name());
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let found = match found {
+        Ok(found) => found,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let suggestions = found.into_iter().map(|(seed, results)| (seeds[seed], results)).collect();
+    let ranked = recommend::rank_discoveries(&items, suggestions, limit as usize);
+    let api: Vec<ApiDiscovery> = ranked.iter().map(|d| ApiDiscovery::new(d, language)).collect();
+    Json(api).into_response()
+}
+
+// ── GET /api/focus ───────────────────────────────────────────
+
+/// Pinned items with the stored item each pin refers to.
+async fn load_focus(db: &Database) -> Result<Vec<(FocusPin, MediaItem)>, StorageError> {
+    let mut entries = Vec::new();
+    for pin in db.list_focus().await? {
+        if let Some(item) = db.get_item(pin.item_id).await? {
+            entries.push((pin, item));
+        }
+    }
+    Ok(entries)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/focus",
+    tag = "focus",
+    responses(
+        (status = 200, body = Vec<ApiFocusEntry>),
+    )
+)]
+async fn list_focus(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match load_focus(&st.db).await {
+        Ok(entries) => {
+            let api: Vec<ApiFocusEntry> =
+                entries.iter().map(|(pin, item)| ApiFocusEntry::new(pin, item)).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── PUT /api/focus/:id ───────────────────────────────────────
+
+#[utoipa::path(
+    put,
+    path = "/api/focus/{id}",
+    tag = "focus",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 200, description = "Pinned, or already pinned", body = ApiFocusEntry),
+        (status = 404, description = "No such item", body = ApiError),
+        (status = 409, description = "The focus list is full", body = ApiError),
+    )
+)]
+async fn pin_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let item = match st.db.get_item(uuid).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return ApiError::not_found("Item").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let (pins, settings) = match (st.db.list_focus().await, st.db.load_settings().await) {
+        (Ok(p), Ok(s)) => (p, s),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    if let Some(pin) = pins.iter().find(|p| p.item_id == uuid) {
+        return Json(ApiFocusEntry::new(pin, &item)).into_response();
+    }
+    if pins.len() >= settings.focus_limit as usize {
+        let message = format!(
+            "The focus list is full ({} items); finish or unpin one first",
+            settings.focus_limit
+        );
+        return ApiError::new(StatusCode::CONFLICT, message).into_response();
+    }
+
+    let pin = FocusPin { item_id: uuid, pinned_at: chrono::Utc::now() };
+    match st.db.pin_item(&pin).await {
+        Ok(()) => Json(ApiFocusEntry::new(&pin, &item)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── DELETE /api/focus/:id ────────────────────────────────────
+
+#[utoipa::path(
+    delete,
+    path = "/api/focus/{id}",
+    tag = "focus",
+    params(
+        ("id" = String, Path, description = "Item id (UUID)"),
+    ),
+    responses(
+        (status = 204, description = "Unpinned"),
+        (status = 404, description = "The item is not pinned", body = ApiError),
+    )
+)]
+async fn unpin_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    let uuid = match Uuid::parse_str(&id) {
+        Ok(u) => u,
+        Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    match st.db.unpin_item(uuid).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Pin").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/rank/next-pair ──────────────────────────────────
+
+/// Two finished or favorite items to pick the better of: one of the
+/// least compared, and one rated close to it.  Hidden items are left out,
+/// and with `hide_adult` on, so are adult titles.
+#[utoipa::path(
+    get,
+    path = "/api/rank/next-pair",
+    tag = "ranking",
+    responses(
+        (status = 200, body = ApiRankPair),
+        (status = 409, description = "Fewer than two items can be ranked", body = ApiError),
+    )
+)]
+async fn next_rank_pair(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let (mut items, ratings) = match (st.db.load_all().await, st.db.list_ratings().await) {
+        (Ok(i), Ok(r)) => (i, r),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let scale = score_scale(&st.db).await;
+    if hide_adult(&st.db).await {
+        items.retain(|i| !i.is_adult());
+    }
+
+    let ratings: HashMap<Uuid, Rating> = ratings.into_iter().map(|r| (r.item_id, r)).collect();
+    match ranking::next_pair(&items, &ratings, http::random_unit) {
+        Some([(left, left_rating), (right, right_rating)]) => Json(ApiRankPair {
+            left: ApiRankEntry::new(&left_rating, left, scale),
+            right: ApiRankEntry::new(&right_rating, right, scale),
+        })
+        .into_response(),
+        None => ApiError::new(
+            StatusCode::CONFLICT,
+            "Ranking needs at least two completed or favorite items",
+        )
+        .into_response(),
+    }
+}
+
+// ── POST /api/rank/choose ────────────────────────────────────
+
+/// Records which of two items is the better one and updates both
+/// ratings.  Any two items can be compared, not only the pair offered.
+#[utoipa::path(
+    post,
+    path = "/api/rank/choose",
+    tag = "ranking",
+    request_body = ApiRankChoice,
+    responses(
+        (status = 200, body = ApiRankResult),
+        (status = 400, description = "Invalid ids, or the same item twice", body = ApiError),
+        (status = 404, description = "No such item", body = ApiError),
+    )
+)]
+async fn choose_ranked(State(state): State<AppState>, Json(choice): Json<ApiRankChoice>) -> Response {
+    let (winner_id, loser_id) = match (Uuid::parse_str(&choice.winner_id), Uuid::parse_str(&choice.loser_id)) {
+        (Ok(w), Ok(l)) => (w, l),
+        _ => return ApiError::new(StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+    };
+    if winner_id == loser_id {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Pick between two different items").into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let (winner, loser) = match (st.db.get_item(winner_id).await, st.db.get_item(loser_id).await) {
+        (Ok(Some(w)), Ok(Some(l))) => (w, l),
+        (Ok(_), Ok(_)) => return ApiError::not_found("Item").into_response(),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let scale = score_scale(&st.db).await;
+    match st.db.record_pick(winner_id, loser_id).await {
+        Ok((winner_rating, loser_rating)) => Json(ApiRankResult {
+            winner: ApiRankEntry::new(&winner_rating, &winner, scale),
+            loser: ApiRankEntry::new(&loser_rating, &loser, scale),
+        })
+        .into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/rank/leaderboard?limit=... ──────────────────────
+
+const DEFAULT_LEADERBOARD: u32 = 50;
+const MAX_LEADERBOARD: u32 = 500;
+
+/// Compared items, best rated first.  Items without a comparison are not
+/// listed.  Hidden items are left out, and with `hide_adult` on, so are
+/// adult titles.
+#[utoipa::path(
+    get,
+    path = "/api/rank/leaderboard",
+    tag = "ranking",
+    params(LimitQuery),
+    responses((status = 200, body = Vec<ApiLeaderboardEntry>))
+)]
+async fn rank_leaderboard(State(state): State<AppState>, Query(params): Query<LimitQuery>) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_LEADERBOARD).min(MAX_LEADERBOARD);
+
+    let st = state.db_state.read().await;
+    let (items, ratings) = match (st.db.load_all().await, st.db.list_ratings().await) {
+        (Ok(i), Ok(r)) => (i, r),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let scale = score_scale(&st.db).await;
+    let hide_adult = hide_adult(&st.db).await;
+
+    let items: HashMap<Uuid, &MediaItem> = items.iter().map(|i| (i.id, i)).collect();
+    let api: Vec<ApiLeaderboardEntry> = ratings
+        .iter()
+        .filter_map(|r| items.get(&r.item_id).map(|item| (r, *item)))
+        .filter(|(_, item)| !item.hidden)
+        .filter(|(_, item)| !(hide_adult && item.is_adult()))
+        .take(limit as usize)
+        .enumerate()
+        .map(|(i, (rating, item))| ApiLeaderboardEntry { rank: i + 1, entry: ApiRankEntry::new(rating, item, scale) })
+        .collect();
+    Json(api).into_response()
+}
+
+// ── GET /api/goals ───────────────────────────────────────────
+
+/// Every yearly goal with the items finished towards it, and the streak
+/// of days with activity.  Only changes made by hand count; imports and
+/// other jobs do not.  Days and years are in the server's time zone.
+#[utoipa::path(
+    get,
+    path = "/api/goals",
+    tag = "goals",
+    responses(
+        (status = 200, body = ApiGoals),
+    )
+)]
+async fn list_goals(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let (list, activity) = match (st.db.list_goals().await, goal_activity(&st.db).await) {
+        (Ok(l), Ok(a)) => (l, a),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+    let (finished, days) = activity;
+    let today = chrono::Local::now().date_naive();
+    Json(ApiGoals {
+        goals: list
+            .iter()
+            .map(|g| ApiGoal::new(g, &goals::progress(g, &finished, today)))
+            .collect(),
+        streak: ApiStreak::from(&goals::streak(&days, today)),
+    })
+    .into_response()
+}
+
+/// What goals and streaks are computed from, in local days: each item
+/// finished with its media type, and every day with activity.  An item
+/// finished twice in a year (a reread) counts once; deleted items no
+/// longer count.
+async fn goal_activity(
+    db: &Database,
+) -> Result<(Vec<(chrono::NaiveDate, &'static str)>, Vec<chrono::NaiveDate>), StorageError> {
+    let items = db.load_all().await?;
+    let completions = db.completion_times().await?;
+    let activity = db.activity_times().await?;
+
+    let day = |t: &chrono::DateTime<chrono::Utc>| t.with_timezone(&chrono::Local).date_naive();
+    let media_types: HashMap<Uuid, &'static str> = items.iter().map(|i| (i.id, media_type_of(i))).collect();
+    let mut seen = HashSet::new();
+    let finished = completions
+        .iter()
+        .filter_map(|(id, at)| Some((*id, day(at), *media_types.get(id)?)))
+        .filter(|(id, d, _)| seen.insert((*id, chrono::Datelike::year(d))))
+        .map(|(_, d, media_type)| (d, media_type))
+        .collect();
+    Ok((finished, activity.iter().map(day).collect()))
+}
+
+// ── POST /api/goals ──────────────────────────────────────────
+
+/// Sets the target for a year and media type, replacing an earlier one.
+#[utoipa::path(
+    post,
+    path = "/api/goals",
+    tag = "goals",
+    request_body = ApiNewGoal,
+    responses(
+        (status = 200, body = ApiGoal),
+        (status = 400, description = "Invalid year, media type or target", body = ApiError),
+    )
+)]
+async fn set_goal(State(state): State<AppState>, Json(body): Json<ApiNewGoal>) -> Response {
+    let media_type = body.media_type.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    if let Err(e) = goals::validate(body.year, media_type, body.target) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let goal = match st.db.set_goal(body.year, media_type, body.target).await {
+        Ok(g) => g,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match goal_activity(&st.db).await {
+        Ok((finished, _)) => {
+            let progress = goals::progress(&goal, &finished, chrono::Local::now().date_naive());
+            Json(ApiGoal::new(&goal, &progress)).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── DELETE /api/goals/:id ────────────────────────────────────
+
+#[utoipa::path(
+    delete,
+    path = "/api/goals/{id}",
+    tag = "goals",
+    params(("id" = i64, Path, description = "Goal id")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such goal", body = ApiError),
+    )
+)]
+async fn delete_goal(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.delete_goal(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Goal").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/franchises ──────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/franchises",
+    tag = "franchises",
+    responses(
+        (status = 200, body = Vec<ApiFranchise>),
+    )
+)]
+async fn list_franchises(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.load_all().await {
+        Ok(items) => {
+            let api: Vec<ApiFranchise> =
+                franchise::summarize(&items).iter().map(ApiFranchise::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/franchises/:name ────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/franchises/{name}",
+    tag = "franchises",
+    params(
+        ("name" = String, Path, description = "Franchise name"),
+    ),
+    responses(
+        (status = 200, body = ApiFranchiseDetail),
+        (status = 404, description = "No item belongs to this franchise", body = ApiError),
+    )
+)]
+async fn get_franchise(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    let items = match st.db.franchise_items(&name).await {
+        Ok(items) if items.is_empty() => return ApiError::not_found("Franchise").into_response(),
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let entries: Vec<&MediaItem> = items.iter().collect();
+    Json(ApiFranchiseDetail {
+        franchise: ApiFranchise::from(&franchise::summary(&name, &entries)),
+        entries: items.iter().map(|i| ApiMediaItem::from(i).in_scale(scale)).collect(),
+    })
+    .into_response()
+}
+
+// ── PUT /api/franchises/:name ────────────────────────────────
+
+/// Renames a franchise; renaming onto an existing one merges the two.
+#[utoipa::path(
+    put,
+    path = "/api/franchises/{name}",
+    tag = "franchises",
+    params(
+        ("name" = String, Path, description = "Franchise name"),
+    ),
+    request_body = ApiFranchiseRename,
+    responses(
+        (status = 200, body = ApiFranchise),
+        (status = 400, description = "Invalid name", body = ApiError),
+        (status = 404, description = "No item belongs to this franchise", body = ApiError),
+    )
+)]
+async fn rename_franchise(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(body): Json<ApiFranchiseRename>,
+) -> Response {
+    let new_name = match franchise::normalize_name(&body.name) {
+        Ok(Some(n)) => n,
+        Ok(None) => return ApiError::new(StatusCode::BAD_REQUEST, "Franchise name is empty").into_response(),
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    match st.db.rename_franchise(&name, Some(&new_name)).await {
+        Ok(0) => return ApiError::not_found("Franchise").into_response(),
+        Ok(_) => {}
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+    match st.db.franchise_items(&new_name).await {
+        Ok(items) => {
+            let entries: Vec<&MediaItem> = items.iter().collect();
+            Json(ApiFranchise::from(&franchise::summary(&new_name, &entries))).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── DELETE /api/franchises/:name ─────────────────────────────
+
+/// Dissolves a franchise; its entries stay in the library.
+#[utoipa::path(
+    delete,
+    path = "/api/franchises/{name}",
+    tag = "franchises",
+    params(
+        ("name" = String, Path, description = "Franchise name"),
+    ),
+    responses(
+        (status = 204, description = "Dissolved"),
+        (status = 404, description = "No item belongs to this franchise", body = ApiError),
+    )
+)]
+async fn delete_franchise(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.rename_franchise(&name, None).await {
+        Ok(0) => ApiError::not_found("Franchise").into_response(),
+        Ok(_) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/stats/focus ─────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/stats/focus",
+    tag = "stats",
+    responses(
+        (status = 200, body = ApiFocusStats),
+    )
+)]
+async fn focus_stats(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let (entries, settings) = match (load_focus(&st.db).await, st.db.load_settings().await) {
+        (Ok(e), Ok(s)) => (e, s),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    let items: Vec<&MediaItem> = entries.iter().map(|(_, item)| item).collect();
+    let summary = focus::summarize(&items);
+    Json(ApiFocusStats::new(&summary, settings.focus_limit)).into_response()
+}
+
+// ── GET /api/explore?q=...&type=...&mode=...&source=...&page=... ──
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ExploreQuery {
+    /// At least two characters; only used by `mode=search`.
+    q: Option<String>,
+    /// `anime` (default), `movie`, `series`, `manga`, `book`, `light_novel`
+    /// or `audiobook`.
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+    /// `search` (default), `trending`, or `season` for the titles that
+    /// started in `season` of `year`.
+    mode: Option<String>,
+    year: Option<i32>,
+    /// `winter`, `spring`, `summer` or `fall`.
+    season: Option<String>,
+    /// Ask only this provider (`anilist`, `tmdb`, ...) instead of walking
+    /// the fallback chain, or merging every provider's results when the
+    /// type has no chain.
+    source: Option<String>,
+    /// 1-based; 10 search results or 20 chart entries per page.
+    page: Option<u32>,
+}
+
+/// How long each provider may take to answer an explore request.
+const EXPLORE_PROVIDER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Header listing the providers that failed or timed out, as a JSON array
+/// of `ApiProviderError`; absent when none did.
+const PROVIDER_ERRORS_HEADER: &str = "x-kars-provider-errors";
+
+#[utoipa::path(
+    get,
+    path = "/api/explore",
+    tag = "explore",
+    params(ExploreQuery),
+    responses(
+        (status = 200, body = Vec<ApiExploreResult>, headers(
+            ("X-Kars-Provider-Errors" = String, description = "JSON array of ApiProviderError, when a provider failed or timed out"),
+        )),
+        (status = 400, description = "Unknown mode or provider, a season without year and season, or page 0", body = ApiError),
+        (status = 503, description = "No provider for the type is configured", body = ApiError),
+    )
+)]
+async fn explore_items(
+    State(state): State<AppState>,
+    Query(params): Query<ExploreQuery>,
+) -> Response {
+    let browse = match params.mode.as_deref() {
+        None | Some("search") => None,
+        Some("trending") => Some(Browse::Trending),
+        Some("season") => {
+            let season = params.season.as_deref().and_then(Season::parse);
+            let (Some(year), Some(season)) = (params.year, season) else {
+                let msg = "mode=season needs a year and a season (winter, spring, summer or fall)";
+                return ApiError::new(StatusCode::BAD_REQUEST, msg).into_response();
+            };
+            Some(Browse::Season { year, season })
+        }
+        Some(other) => {
+            return ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown explore mode: {other}")).into_response();
+        }
+    };
+
+    let page = params.page.unwrap_or(1);
+    if page == 0 {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Pages start at 1").into_response();
+    }
+
+    let query = params.q.unwrap_or_default();
+    if browse.is_none() && query.len() < 2 {
+        return Json(Vec::<ApiExploreResult>::new()).into_response();
+    }
+
+    let search_type = params
+        .media_type
+        .as_deref()
+        .and_then(MediaSearchType::parse)
+        .unwrap_or(MediaSearchType::Anime);
+
+    let served = state
+        .searchers
+        .iter()
+        .any(|s| s.supported_types().contains(&search_type));
+    if !served {
+        let providers: Vec<ApiProvider> = state
+            .unavailable
+            .iter()
+            .filter(|p| p.supported_types.contains(&search_type))
+            .map(ApiProvider::from)
+            .collect();
+        let msg = format!("No provider for {} is configured", search_type.as_str());
+        let details = ApiProviderUnavailable { media_type: search_type.as_str(), providers };
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, msg)
+            .with_code("provider_not_configured")
+            .with_details(details)
+            .into_response();
+    }
+
+    // With a chain configured the first provider with results answers;
+    // without one every provider's results are merged.
+    let (order, chained): (Vec<usize>, bool) = match params.source.as_deref() {
+        Some(source) => {
+            let Some(idx) = state.searchers.iter().position(|s| s.source() == source) else {
+                return ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown provider: {source}")).into_response();
+            };
+            let searcher = &state.searchers[idx];
+            if !searcher.supported_types().contains(&search_type) {
+                let msg = format!("{} has no {} entries", searcher.name(), search_type.as_str());
+                return ApiError::new(StatusCode::BAD_REQUEST, msg).into_response();
+            }
+            (vec![idx], true)
+        }
+        None => {
+            let chain = {
+                let st = state.db_state.read().await;
+                match st.db.load_settings().await {
+                    Ok(s) => s.chain_for(search_type.as_str()).to_vec(),
+                    Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+                }
+            };
+            let names: Vec<&str> = state.searchers.iter().map(|s| s.name()).collect();
+            (settings::chain_order(&names, &chain), !chain.is_empty())
+        }
+    };
+
+    // Each lookup runs on its own blocking thread so reqwest::blocking
+    // doesn't panic inside the async runtime.
+    let start = |idx: usize| {
+        let searchers = Arc::clone(&state.searchers);
+        let q = query.clone();
+        let task = tokio::task::spawn_blocking(move || match browse {
+            Some(browse) => searchers[idx].browse(browse, search_type, page),
+            None => searchers[idx].search(&q, search_type, page),
+        });
+        tokio::time::timeout(EXPLORE_PROVIDER_TIMEOUT, task)
+    };
+    // Merged results need every provider, so they are all asked at once.
+    // A chain only asks the next provider once the one before it failed,
+    // timed out or came up empty, so providers further down are not
+    // spent on every request.
+    let lookups: Vec<_> = order
+        .into_iter()
+        .filter(|&idx| state.searchers[idx].supported_types().contains(&search_type))
+        .map(|idx| (idx, (!chained).then(|| start(idx))))
+        .collect();
+
+    let (hide_adult, language) = {
+        let st = state.db_state.read().await;
+        (hide_adult(&st.db).await, title_language(&st.db).await)
+    };
+    let mut items = Vec::new();
+    let mut failures = Vec::new();
+    let mut mismatches = Vec::new();
+    // Walk the fallback chain until a provider returns something.  Without
+    // a chain every lookup is waited for.
+    for (idx, lookup) in lookups {
+        let name = state.searchers[idx].name();
+        let error = match lookup.unwrap_or_else(|| start(idx)).await {
+            Ok(Ok(Ok(results))) if results.is_empty() => continue,
+            Ok(Ok(Ok(results))) => {
+                items.extend(
+                    results
+                        .iter()
+                        .filter(|r| !(hide_adult && r.is_adult()))
+                        .map(|r| ApiExploreResult::from_search_result(r, language)),
+                );
+                if chained {
+                    break;
+                }
+                continue;
+            }
+            Ok(Ok(Err(e))) => {
+                let message = e.to_string();
+                if let SearchError::Payload { provider, message, snippet } = e {
+                    mismatches.push((provider, message, snippet));
+                }
+                message
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("No answer within {} s", EXPLORE_PROVIDER_TIMEOUT.as_secs()),
+        };
+        log_error!("Search provider {name} error: {error}");
+        failures.push(ApiProviderError { provider: name.to_string(), error });
+    }
+
+    if !mismatches.is_empty() {
+        let st = state.db_state.write().await;
+        for (provider, message, snippet) in &mismatches {
+            if let Err(e) = st.db.record_diagnostic(provider, message, snippet).await {
+                log_error!("Failed to record provider diagnostic: {e}");
+            }
+        }
+    }
+
+    let mut response = Json(items).into_response();
+    if !failures.is_empty() {
+        let header = serde_json::to_string(&failures)
+            .ok()
+            .and_then(|json| HeaderValue::from_str(&json.replace(|c: char| !c.is_ascii(), "?")).ok());
+        if let Some(header) = header {
+            response.headers_mut().insert(PROVIDER_ERRORS_HEADER, header);
+        }
+    }
+    response
+}
+
+// ── GET /api/explore/suggest?q=...&type=... ──────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SuggestQuery {
+    /// What has been typed so far; at least two characters.
+    q: Option<String>,
+    /// `anime` (default), `movie`, `series`, `manga`, `book`, `light_novel`
+    /// or `audiobook`.
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+}
+
+/// How long a suggestion request waits for the providers of the chain;
+/// whatever answered by then is used.
+const SUGGEST_BUDGET: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Typeahead for the add dialog: a few titles with their year and a small
+/// poster.  Every provider of the fallback chain is asked at once and the
+/// first in chain order that answered within a short budget wins.  Answers
+/// are cached for ten minutes per chain, so repeated keystrokes are
+/// answered at once; a failing provider gives no suggestions rather than
+/// an error.
+/// Empty for less than two characters or a type no provider serves.
+#[utoipa::path(
+    get,
+    path = "/api/explore/suggest",
+    tag = "explore",
+    params(SuggestQuery),
+    responses(
+        (status = 200, body = Vec<ApiSuggestion>),
+    )
+)]
+async fn suggest_titles(State(state): State<AppState>, Query(params): Query<SuggestQuery>) -> Response {
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let search_type = params
+        .media_type
+        .as_deref()
+        .and_then(MediaSearchType::parse)
+        .unwrap_or(MediaSearchType::Anime);
+    if query.chars().count() < 2 {
+        return Json(Vec::<ApiSuggestion>::new()).into_response();
+    }
+
+    let settings = match state.db_state.read().await.db.load_settings().await {
+        Ok(settings) => settings,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let chain = settings.chain_for(search_type.as_str()).to_vec();
+
+    let cached = state
+        .suggestions
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(search_type, &chain, &query, std::time::Instant::now());
+    let results = match cached {
+        Some(results) => results,
+        None => {
+            let names: Vec<&str> = state.searchers.iter().map(|s| s.name()).collect();
+            let lookups: Vec<_> = settings::chain_order(&names, &chain)
+                .into_iter()
+                .filter(|&idx| state.searchers[idx].supported_types().contains(&search_type))
+                .map(|idx| {
+                    let searchers = Arc::clone(&state.searchers);
+                    let q = query.clone();
+                    (idx, tokio::task::spawn_blocking(move || searchers[idx].search(&q, search_type, 1)))
+                })
+                .collect();
+
+            // Lookups that already finished still count once the budget
+            // is spent; the ones still running are left to finish.
+            let deadline = tokio::time::Instant::now() + SUGGEST_BUDGET;
+            let mut answered = None;
+            let mut timed_out = false;
+            for (idx, lookup) in lookups {
+                let error = match tokio::time::timeout_at(deadline, lookup).await {
+                    Ok(Ok(Ok(mut results))) => {
+                        results.truncate(suggest::SUGGEST_LIMIT);
+                        let found = !results.is_empty();
+                        answered = Some(results);
+                        if found {
+                            break;
+                        }
+                        continue;
+                    }
+                    Ok(Ok(Err(e))) => e.to_string(),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(_) => {
+                        timed_out = true;
+                        continue;
+                    }
+                };
+                log_error!("Search provider {} error: {error}", state.searchers[idx].name());
+            }
+            let Some(results) = answered else {
+                return Json(Vec::<ApiSuggestion>::new()).into_response();
+            };
+            // Only answers are cached, so a provider that failed is asked
+            // again on the next keystroke, and a later provider's answer
+            // is not kept while an earlier one was merely slow.
+            if !timed_out {
+                state.suggestions.lock().unwrap_or_else(|e| e.into_inner()).insert(
+                    search_type,
+                    &chain,
+                    &query,
+                    results.clone(),
+                    std::time::Instant::now(),
+                );
+            }
+            results
+        }
+    };
+
+    let suggestions: Vec<ApiSuggestion> = results
+        .iter()
+        .filter(|r| !(settings.hide_adult && r.is_adult()))
+        .map(|r| ApiSuggestion::from_search_result(r, settings.title_language))
+        .collect();
+    let mut response = Json(suggestions).into_response();
+    response.headers_mut().insert(axum::http::header::CACHE_CONTROL, HeaderValue::from_static("private, max-age=600"));
+    response
+}
+
+// ── POST /api/explore/details ────────────────────────────────
+
+/// Most entries one details request may ask for.
+const MAX_DETAIL_BATCH: usize = 25;
+
+/// Full provider details for several explore results in one call.
+/// Entries are grouped per provider and type so providers with a batch
+/// query answer each group with a single request.
+#[utoipa::path(
+    post,
+    path = "/api/explore/details",
+    tag = "explore",
+    request_body = ApiDetailsRequest,
+    responses(
+        (status = 200, body = Vec<ApiExploreDetail>),
+        (status = 400, description = "Too many or invalid entries", body = ApiError),
+    )
+)]
+async fn explore_details(
+    State(state): State<AppState>,
+    Json(payload): Json<ApiDetailsRequest>,
+) -> Response {
+    if payload.items.len() > MAX_DETAIL_BATCH {
+        let msg = format!("At most {MAX_DETAIL_BATCH} items per request");
+        return ApiError::new(StatusCode::BAD_REQUEST, msg).into_response();
+    }
+
+    // (provider index, type, id) per entry; None when no provider serves it.
+    let mut wanted = Vec::with_capacity(payload.items.len());
+    for req in &payload.items {
+        if req.external_id.is_empty() {
+            return ApiError::new(StatusCode::BAD_REQUEST, "Empty external_id").into_response();
+        }
+        let Some(search_type) = MediaSearchType::parse(&req.media_type) else {
+            return ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown type '{}'", req.media_type)).into_response();
+        };
+        let provider = state.searchers.iter().position(|s| {
+            s.source() == req.source && s.supported_types().contains(&search_type)
+        });
+        wanted.push(provider.map(|idx| (idx, search_type, req.external_id.clone())));
+    }
+
+    let language = title_language(&state.db_state.read().await.db).await;
+    let searchers = Arc::clone(&state.searchers);
+    let lookups = wanted.clone();
+    let fetched = tokio::task::spawn_blocking(move || {
+        let mut groups: Vec<(usize, MediaSearchType, Vec<String>)> = Vec::new();
+        for (idx, search_type, id) in lookups.into_iter().flatten() {
+            match groups.iter_mut().find(|g| g.0 == idx && g.1 == search_type) {
+                Some(group) => group.2.push(id),
+                None => groups.push((idx, search_type, vec![id])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(idx, search_type, ids)| {
+                let results = searchers[idx]
+                    .fetch_many(&ids, search_type)
+                    .map(|rs| rs.iter().map(|r| ApiExploreResult::from_search_result(r, language)).collect::<Vec<_>>())
+                    .map_err(|e| e.to_string());
+                (idx, search_type, results)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    let groups = match fetched {
+        Ok(g) => g,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let details: Vec<ApiExploreDetail> = payload
+        .items
+        .into_iter()
+        .zip(wanted)
+        .map(|(req, lookup)| {
+            let mut detail = ApiExploreDetail {
+                source: req.source,
+                external_id: req.external_id,
+                result: None,
+                error: None,
+            };
+            let Some((idx, search_type, id)) = lookup else {
+                detail.error = Some("No configured provider for this source and type".into());
+                return detail;
+            };
+            let group = groups.iter().find(|g| g.0 == idx && g.1 == search_type);
+            match group.map(|g| &g.2) {
+                Some(Ok(results)) => {
+                    detail.result = results
+                        .iter()
+                        .find(|r| r.external_id.as_ref() == Some(&id))
+                        .cloned();
+                }
+                Some(Err(e)) => detail.error = Some(e.clone()),
+                None => {}
+            }
+            detail
+        })
+        .collect();
+    Json(details).into_response()
+}
+
+// ── GET /api/explore/{source}/{external_id}?type=... ─────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct PreviewQuery {
+    /// `anime`, `movie`, `series`, `manga`, `book`, `light_novel` or
+    /// `audiobook`;
+    /// defaults to the provider's first type (anime for AniList, movie for
+    /// TMDB).
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+}
+
+/// Looks up one provider entry with its details.  Details are optional:
+/// a provider without a detail lookup, or one that fails, still gives the
+/// entry.
+async fn fetch_provider_entry(
+    state: &AppState,
+    source: &str,
+    external_id: &str,
+    media_type: Option<&str>,
+) -> Result<(SearchResult, MediaDetails), ApiError> {
+    let Some(idx) = state.searchers.iter().position(|s| s.source() == source) else {
+        return Err(ApiError::new(StatusCode::NOT_FOUND, format!("Unknown provider: {source}")));
+    };
+    let search_type = match media_type {
+        Some(t) => MediaSearchType::parse(t)
+            .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, format!("Unknown type '{t}'")))?,
+        None => state.searchers[idx].supported_types()[0],
+    };
+    if !state.searchers[idx].supported_types().contains(&search_type) {
+        let msg = format!("{} has no {} entries", state.searchers[idx].name(), search_type.as_str());
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, msg));
+    }
+
+    let searchers = Arc::clone(&state.searchers);
+    let id = external_id.to_string();
+    let task = tokio::task::spawn_blocking(move || {
+        let Some(result) = searchers[idx].fetch(&id, search_type)? else {
+            return Ok(None);
+        };
+        let details = searchers[idx].details(&id, search_type).unwrap_or_else(|e| {
+            log_error!("Search provider {} error: {e}", searchers[idx].name());
+            None
+        });
+        Ok::<_, SearchError>(Some((result, details.unwrap_or_default())))
+    });
+    match tokio::time::timeout(EXPLORE_PROVIDER_TIMEOUT, task).await {
+        Ok(Ok(Ok(Some(entry)))) => Ok(entry),
+        Ok(Ok(Ok(None))) => Err(ApiError::not_found("Entry")),
+        Ok(Ok(Err(e))) => Err(ApiError::new(StatusCode::BAD_GATEWAY, e.to_string())),
+        Ok(Err(e)) => Err(ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(_) => {
+            let msg = format!("No answer within {} s", EXPLORE_PROVIDER_TIMEOUT.as_secs());
+            Err(ApiError::new(StatusCode::GATEWAY_TIMEOUT, msg))
+        }
+    }
+}
+
+/// Everything a provider knows about one entry (synopsis, genres, length,
+/// staff), for a preview before adding it, with the library item when it
+/// is already there.
+#[utoipa::path(
+    get,
+    path = "/api/explore/{source}/{external_id}",
+    tag = "explore",
+    params(
+        ("source" = String, Path, description = "Provider, e.g. `anilist` or `tmdb`"),
+        ("external_id" = String, Path, description = "The provider's id of the entry"),
+        PreviewQuery,
+    ),
+    responses(
+        (status = 200, body = ApiExplorePreview),
+        (status = 400, description = "Unknown type, or one the provider has no entries of", body = ApiError),
+        (status = 404, description = "Unknown provider or entry", body = ApiError),
+        (status = 502, description = "The provider failed", body = ApiError),
+        (status = 504, description = "The provider did not answer in time", body = ApiError),
+    )
+)]
+async fn explore_preview(
+    State(state): State<AppState>,
+    Path((source, external_id)): Path<(String, String)>,
+    Query(params): Query<PreviewQuery>,
+) -> Response {
+    let (result, details) = match fetch_provider_entry(&state, &source, &external_id, params.media_type.as_deref()).await {
+        Ok(entry) => entry,
+        Err(e) => return e.into_response(),
+    };
+    let st = state.db_state.read().await;
+    let item = match st.db.find_by_provider_entry(&source, &external_id).await {
+        Ok(item) => item,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let scale = score_scale(&st.db).await;
+    let item = item.map(|i| ApiMediaItem::from(&i).in_scale(scale));
+    Json(ApiExplorePreview::new(&result, details, title_language(&st.db).await, item)).into_response()
+}
+
+// ── GET /api/explore/isbn/{isbn} ─────────────────────────────
+
+/// The book with an ISBN, for adding a physical book by its barcode.  The
+/// providers of the book chain (Open Library, then Google Books, unless
+/// `provider_chains` says otherwise) are asked in turn and the first that
+/// knows the ISBN answers, with its details and the library item when the
+/// book is already there.  Add it with `POST /api/items/from-external`.
+#[utoipa::path(
+    get,
+    path = "/api/explore/isbn/{isbn}",
+    tag = "explore",
+    params(("isbn" = String, Path, description = "ISBN-10 or ISBN-13, with or without hyphens")),
+    responses(
+        (status = 200, body = ApiExplorePreview),
+        (status = 400, description = "Not a valid ISBN", body = ApiError),
+        (status = 404, description = "No provider knows the ISBN", body = ApiError),
+        (status = 502, description = "Every provider failed; `details` lists them as ApiProviderError", body = ApiError),
+    )
+)]
+async fn explore_isbn(State(state): State<AppState>, Path(isbn): Path<String>) -> Response {
+    let Some(isbn) = normalize_isbn(&isbn) else {
+        return ApiError::new(StatusCode::BAD_REQUEST, format!("Not a valid ISBN-10 or ISBN-13: {isbn}")).into_response();
+    };
+    let (chain, language) = {
+        let st = state.db_state.read().await;
+        let chain = match st.db.load_settings().await {
+            Ok(s) => s.chain_for(MediaSearchType::Book.as_str()).to_vec(),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+        (chain, title_language(&st.db).await)
+    };
+
+    let names: Vec<&str> = state.searchers.iter().map(|s| s.name()).collect();
+    let mut found = None;
+    let mut failures = Vec::new();
+    let mut asked = 0;
+    for idx in settings::chain_order(&names, &chain) {
+        if !state.searchers[idx].supported_types().contains(&MediaSearchType::Book) {
+            continue;
+        }
+        asked += 1;
+        let searchers = Arc::clone(&state.searchers);
+        let wanted = isbn.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let Some(result) = searchers[idx].lookup_isbn(&wanted)? else {
+                return Ok(None);
+            };
+            let id = result.external_id.clone().unwrap_or_default();
+            let details = searchers[idx].details(&id, MediaSearchType::Book).unwrap_or_else(|e| {
+                log_error!("Search provider {} error: {e}", searchers[idx].name());
+                None
+            });
+            Ok::<_, SearchError>(Some((result, details.unwrap_or_default())))
+        });
+        let error = match tokio::time::timeout(EXPLORE_PROVIDER_TIMEOUT, task).await {
+            Ok(Ok(Ok(Some(entry)))) => {
+                found = Some(entry);
+                break;
+            }
+            Ok(Ok(Ok(None))) => continue,
+            Ok(Ok(Err(e))) => e.to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!("No answer within {} s", EXPLORE_PROVIDER_TIMEOUT.as_secs()),
+        };
+        let name = state.searchers[idx].name();
+        log_error!("Search provider {name} error: {error}");
+        failures.push(ApiProviderError { provider: name.to_string(), error });
+    }
+
+    let Some((result, details)) = found else {
+        if asked > 0 && failures.len() == asked {
+            let msg = format!("No book provider could look up ISBN {isbn}");
+            return ApiError::new(StatusCode::BAD_GATEWAY, msg).with_details(failures).into_response();
+        }
+        return ApiError::new(StatusCode::NOT_FOUND, format!("No book with ISBN {isbn} found")).into_response();
+    };
+
+    let st = state.db_state.read().await;
+    let item = match result.external_id.as_deref() {
+        Some(id) => st.db.find_by_provider_entry(result.source, id).await,
+        None => Ok(None),
+    };
+    let item = match item {
+        Ok(Some(item)) => Ok(Some(item)),
+        Ok(None) => st.db.resolve_external_ref("isbn", &isbn).await,
+        Err(e) => Err(e),
+    };
+    let item = match item {
+        Ok(item) => item,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let scale = score_scale(&st.db).await;
+    let item = item.map(|i| ApiMediaItem::from(&i).in_scale(scale));
+    Json(ApiExplorePreview::new(&result, details, language, item)).into_response()
+}
+
+// ── GET /api/resolve?ref=mal:anime:21 ───────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ResolveQuery {
+    /// `source:id`, e.g. `mal:anime:21`.
+    #[serde(rename = "ref")]
+    reference: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/resolve",
+    tag = "items",
+    params(ResolveQuery),
+    responses(
+        (status = 200, body = ApiMediaItem),
+        (status = 404, description = "No item carries the ref", body = ApiError),
+    )
+)]
+async fn resolve_ref(State(state): State<AppState>, Query(params): Query<ResolveQuery>) -> Response {
+    let Some((source, ref_id)) = params.reference.split_once(':') else {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Expected ref=source:id").into_response();
+    };
+
+    let st = state.db_state.read().await;
+    let scale = score_scale(&st.db).await;
+    match st.db.resolve_external_ref(source, ref_id).await {
+        Ok(Some(item)) => Json(ApiMediaItem::from(&item).in_scale(scale)).into_response(),
+        Ok(None) => ApiError::not_found("Item").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/external-refs/sync ─────────────────────────────
+
+#[derive(Serialize, ToSchema)]
+struct SyncSummary {
+    updated: usize,
+}
+
+/// Pulls the anime-lists id mapping and adds the ids of every other
+/// service to the items it recognizes.
+#[utoipa::path(
+    post,
+    path = "/api/external-refs/sync",
+    tag = "items",
+    responses(
+        (status = 200, body = SyncSummary),
+        (status = 502, description = "Mapping source unreachable", body = ApiError),
+    )
+)]
+async fn sync_external_refs(State(state): State<AppState>) -> Response {
+    let mappings = match tokio::task::spawn_blocking(|| AnimeListsClient::new().fetch()).await {
+        Ok(Ok(m)) => m,
+        Ok(Err(e)) => return ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let mut items = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let changed = anime_lists::expand_refs(&mut items, &mappings);
+    for &idx in &changed {
+        if let Err(e) = st.db.upsert_item(&mut items[idx]).await {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    }
+    Json(SyncSummary { updated: changed.len() }).into_response()
+}
+
+// ── GET /api/schedule?days=... ───────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ScheduleQuery {
+    /// Look-ahead window, 14 by default.
+    days: Option<u32>,
+}
+
+const DEFAULT_SCHEDULE_DAYS: u32 = 14;
+const MAX_SCHEDULE_DAYS: u32 = 366;
+
+#[utoipa::path(
+    get,
+    path = "/api/schedule",
+    tag = "schedule",
+    params(ScheduleQuery),
+    responses(
+        (status = 200, body = ApiSchedule),
+    )
+)]
+async fn list_schedule(
+    State(state): State<AppState>,
+    Query(params): Query<ScheduleQuery>,
+) -> Response {
+    let days = params.days.unwrap_or(DEFAULT_SCHEDULE_DAYS).min(MAX_SCHEDULE_DAYS);
+    let now = chrono::Utc::now();
+
+    let items = match state.db_state.read().await.db.load_all().await {
+        Ok(i) => i,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let window = chrono::Duration::days(days as i64);
+    let sessions = schedule::upcoming_sessions(&items, now, window)
+        .into_iter()
+        .filter_map(|item| ApiSession::new(item, now))
+        .collect();
+    let airing = next_airing(&state.searchers, &items)
+        .await
+        .into_iter()
+        .filter(|(_, next)| next.airing_at <= now + window)
+        .map(|(item, next)| ApiAiring::new(item, &next, now))
+        .collect();
+    Json(ApiSchedule { sessions, airing }).into_response()
+}
+
+/// Groups items by the index of the provider they came from; items of
+/// unknown providers are left out.
+fn by_provider<'a>(
+    searchers: &Searchers,
+    items: impl Iterator<Item = &'a MediaItem>,
+) -> Vec<(usize, Vec<&'a MediaItem>)> {
+    let mut groups: Vec<(usize, Vec<&MediaItem>)> = Vec::new();
+    for item in items {
+        let Some(idx) = searchers
+            .iter()
+            .position(|s| item.source.as_deref() == Some(s.source()))
+        else {
+            continue;
+        };
+        match groups.iter_mut().find(|(i, _)| *i == idx) {
+            Some((_, group)) => group.push(item),
+            None => groups.push((idx, vec![item])),
+        }
+    }
+    groups
+}
+
+/// Looks up the next episode of every series that `follows_airing`, with
+/// the provider the item came from, soonest first.  Providers that fail
+/// are logged and skipped so the rest of the schedule still shows.
+async fn next_airing<'a>(searchers: &Searchers, items: &'a [MediaItem]) -> Vec<(&'a MediaItem, NextAiring)> {
+    let by_provider = by_provider(searchers, items.iter().filter(|i| schedule::follows_airing(i)));
+    let mut airing = Vec::new();
+    for (idx, group) in by_provider {
+        let ids: Vec<String> = group.iter().filter_map(|i| i.external_id.clone()).collect();
+        let provider = Arc::clone(searchers);
+        match tokio::task::spawn_blocking(move || provider[idx].next_airing(&ids)).await {
+            Ok(Ok(found)) => {
+                for next in found {
+                    for item in group.iter().filter(|i| i.external_id.as_ref() == Some(&next.external_id)) {
+                        airing.push((*item, next.clone()));
+                    }
+                }
+            }
+            Ok(Err(e)) => log_error!("Airing lookup failed ({}): {e}", searchers[idx].name()),
+            Err(e) => log_error!("Airing lookup failed: {e}"),
+        }
+    }
+    airing.sort_by_key(|(_, next)| next.airing_at);
+    airing
+}
+
+/// Looks up the release dates of movies that `awaits_release`, like
+/// `next_airing`.
+async fn release_dates<'a>(searchers: &Searchers, items: &'a [MediaItem]) -> Vec<(&'a MediaItem, Release)> {
+    let mut releases = Vec::new();
+    for (idx, group) in by_provider(searchers, items.iter().filter(|i| schedule::awaits_release(i))) {
+        let ids: Vec<String> = group.iter().filter_map(|i| i.external_id.clone()).collect();
+        let provider = Arc::clone(searchers);
+        match tokio::task::spawn_blocking(move || provider[idx].release_dates(&ids)).await {
+            Ok(Ok(found)) => {
+                for release in found {
+                    for item in group.iter().filter(|i| i.external_id.as_ref() == Some(&release.external_id)) {
+                        releases.push((*item, release.clone()));
+                    }
+                }
+            }
+            Ok(Err(e)) => log_error!("Release lookup failed ({}): {e}", searchers[idx].name()),
+            Err(e) => log_error!("Release lookup failed: {e}"),
+        }
+    }
+    releases
+}
+
+// ── GET /api/calendar.ics ────────────────────────────────────
+
+/// How long an airing episode is shown in the calendar.
+const EPISODE_MINUTES: u32 = 30;
+
+/// Subscribable calendar of upcoming episodes of watched and planned
+/// series and of release dates of planned movies.
+#[utoipa::path(
+    get,
+    path = "/api/calendar.ics",
+    tag = "schedule",
+    responses(
+        (status = 200, description = "iCalendar feed", content_type = "text/calendar"),
+    )
+)]
+async fn calendar_feed(State(state): State<AppState>) -> Response {
+    let now = chrono::Utc::now();
+    let items = match state.db_state.read().await.db.load_all().await {
+        Ok(i) => i,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let mut events: Vec<CalendarEvent> = next_airing(&state.searchers, &items)
+        .await
+        .into_iter()
+        .filter(|(_, next)| next.airing_at >= now)
+        .map(|(item, next)| CalendarEvent {
+            uid: format!("airing-{}-{}@kars", item.id, next.episode),
+            summary: format!("{} — episode {}", item.title, next.episode),
+            time: EventTime::At { start: next.airing_at, minutes: EPISODE_MINUTES },
+        })
+        .collect();
+    events.extend(
+        release_dates(&state.searchers, &items)
+            .await
+            .into_iter()
+            .filter(|(_, release)| release.date >= now.date_naive())
+            .map(|(item, release)| CalendarEvent {
+                uid: format!("release-{}@kars", item.id),
+                summary: format!("{} — release", item.title),
+                time: EventTime::Day(release.date),
+            }),
+    );
+
+    (
+        [("content-type", "text/calendar; charset=utf-8")],
+        calendar::to_ics("kars", &events, now),
+    )
+        .into_response()
+}
+
+// ── GET /api/notifications?unread=&limit= ────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct NotificationQuery {
+    /// Only unread notifications.
+    #[serde(default)]
+    unread: bool,
+    limit: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/notifications",
+    tag = "schedule",
+    params(NotificationQuery),
+    responses(
+        (status = 200, body = Vec<ApiNotification>),
+    )
+)]
+async fn list_notifications(
+    State(state): State<AppState>,
+    Query(params): Query<NotificationQuery>,
+) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).min(MAX_ACTIVITY_LIMIT);
+
+    let st = state.db_state.read().await;
+    match st.db.list_notifications(limit, params.unread).await {
+        Ok(list) => {
+            let api: Vec<ApiNotification> = list.iter().map(ApiNotification::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/notifications/:id/read ─────────────────────────
+
+#[utoipa::path(
+    post,
+    path = "/api/notifications/{id}/read",
+    tag = "schedule",
+    params(
+        ("id" = i64, Path, description = "Notification id"),
+    ),
+    responses(
+        (status = 204, description = "Marked as read"),
+        (status = 404, description = "No such notification", body = ApiError),
+    )
+)]
+async fn mark_notification_read(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.mark_notification_read(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Notification").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/jobs?limit=... ──────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs",
+    tag = "jobs",
+    params(LimitQuery),
+    responses(
+        (status = 200, body = Vec<ApiJob>),
+    )
+)]
+async fn list_jobs(State(state): State<AppState>, Query(params): Query<LimitQuery>) -> Response {
+    let limit = params.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT).min(MAX_ACTIVITY_LIMIT);
+
+    let st = state.db_state.read().await;
+    match st.db.list_jobs(limit).await {
+        Ok(jobs) => {
+            let api: Vec<ApiJob> = jobs.iter().map(ApiJob::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/jobs/:id ────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}",
+    tag = "jobs",
+    params(
+        ("id" = i64, Path, description = "Job id"),
+    ),
+    responses(
+        (status = 200, body = ApiJob),
+        (status = 404, description = "No such job", body = ApiError),
+    )
+)]
+async fn get_job(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.get_job(id).await {
+        Ok(Some(job)) => Json(ApiJob::from(&job)).into_response(),
+        Ok(None) => ApiError::not_found("Job").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/jobs/refresh ───────────────────────────────────
+
+/// Starts a metadata refresh in the background.  Poll the returned job
+/// for its result; a dry run ends in `preview` status.
+#[utoipa::path(
+    post,
+    path = "/api/jobs/refresh",
+    tag = "jobs",
+    request_body = Option<ApiRefreshRequest>,
+    responses(
+        (status = 202, body = ApiJob),
+        (status = 400, description = "Invalid item id", body = ApiError),
+    )
+)]
+async fn start_refresh(
+    State(state): State<AppState>,
+    payload: Option<Json<ApiRefreshRequest>>,
+) -> Response {
+    let Json(payload) = payload.unwrap_or_default();
+    let mut item_ids = Vec::with_capacity(payload.item_ids.len());
+    for id in &payload.item_ids {
+        match Uuid::parse_str(id) {
+            Ok(id) => item_ids.push(id),
+            Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid item id '{id}'")).into_response(),
+        }
+    }
+
+    let job = {
+        let st = state.db_state.write().await;
+        match st.db.create_job(JobKind::MetadataRefresh, payload.dry_run).await {
+            Ok(job) => job,
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    };
+    tokio::spawn(run_refresh(state, job.id, payload.dry_run, item_ids));
+    (StatusCode::ACCEPTED, Json(ApiJob::from(&job))).into_response()
+}
+
+async fn run_refresh(state: AppState, job_id: i64, dry_run: bool, item_ids: Vec<Uuid>) {
+    let loaded = state.db_state.read().await.db.load_all().await;
+    let items = match loaded {
+        Ok(items) => items,
+        Err(e) => {
+            let st = state.db_state.write().await;
+            if let Err(e) = st.db.finish_job(job_id, JobStatus::Failed, &[], Some(&e.to_string())).await {
+                log_error!("Failed to record job {job_id}: {e}");
+            }
+            return;
+        }
+    };
+
+    let mut report = Vec::new();
+    for item in items {
+        if !item_ids.is_empty() && !item_ids.contains(&item.id) {
+            continue;
+        }
+        let Some(external_id) = item.external_id.clone() else { continue };
+        let Some(idx) = state
+            .searchers
+            .iter()
+            .position(|s| item.source.as_deref() == Some(s.source()))
+        else {
+            continue;
+        };
+
+        let searchers = Arc::clone(&state.searchers);
+        let search_type = refresh::search_type_for(&item);
+        let fetched =
+            tokio::task::spawn_blocking(move || searchers[idx].fetch(&external_id, search_type)).await;
+
+        let mut entry = ItemChanges {
+            item_id: item.id,
+            title: item.title.clone(),
+            created: false,
+            changes: Vec::new(),
+            error: None,
+        };
+        match fetched {
+            Ok(Ok(Some(fresh))) => entry.changes = refresh::diff(&item, &fresh),
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => {
+                entry.error = Some(e.to_string());
+                if let SearchError::Payload { provider, message, snippet } = &e {
+                    let st = state.db_state.write().await;
+                    if let Err(e) = st.db.record_diagnostic(provider, message, snippet).await {
+                        log_error!("Failed to record provider diagnostic: {e}");
+                    }
+                }
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        if entry.error.is_none()
+            && item.overview.is_none()
+            && let Some(details) = fetch_details(&state, &item).await
+        {
+            entry.changes.extend(refresh::overview_change(&item, details));
+        }
+        if !dry_run
+            && entry.error.is_none()
+            && let Some(relations) = fetch_relations(&state, &item).await
+        {
+            let st = state.db_state.write().await;
+            if let Err(e) = st.db.replace_relations(item.id, &relations).await {
+                log_error!("Storing relations failed: {e}");
+            }
+        }
+        if entry.changes.is_empty() && entry.error.is_none() {
+            continue;
+        }
+
+        if !dry_run {
+            let st = state.db_state.write().await;
+            if let Err(e) = apply_changes(&st.db, &entry).await {
+                entry.error = Some(e.to_string());
+            }
+        }
+        report.push(entry);
+    }
+
+    let status = if dry_run { JobStatus::Preview } else { JobStatus::Completed };
+    let st = state.db_state.write().await;
+    if let Err(e) = st.db.finish_job(job_id, status, &report, None).await {
+        log_error!("Failed to record job {job_id}: {e}");
+    }
+}
+
+// ── POST /api/jobs/match ─────────────────────────────────────
+
+/// Starts looking for provider entries of the items added by hand.  Items
+/// whose title matches exactly one result are linked to it (see
+/// `/api/items/:id/match`); the others are listed with the reason.  A dry
+/// run ends in `preview` status.
+#[utoipa::path(
+    post,
+    path = "/api/jobs/match",
+    tag = "jobs",
+    request_body = Option<ApiRefreshRequest>,
+    responses(
+        (status = 202, body = ApiJob),
+        (status = 400, description = "Invalid item id", body = ApiError),
+    )
+)]
+async fn start_match(
+    State(state): State<AppState>,
+    payload: Option<Json<ApiRefreshRequest>>,
+) -> Response {
+    let Json(payload) = payload.unwrap_or_default();
+    let mut item_ids = Vec::with_capacity(payload.item_ids.len());
+    for id in &payload.item_ids {
+        match Uuid::parse_str(id) {
+            Ok(id) => item_ids.push(id),
+            Err(_) => return ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid item id '{id}'")).into_response(),
+        }
+    }
+
+    let job = {
+        let st = state.db_state.write().await;
+        match st.db.create_job(JobKind::Match, payload.dry_run).await {
+            Ok(job) => job,
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    };
+    tokio::spawn(run_match(state, job.id, payload.dry_run, item_ids));
+    (StatusCode::ACCEPTED, Json(ApiJob::from(&job))).into_response()
+}
+
+async fn run_match(state: AppState, job_id: i64, dry_run: bool, item_ids: Vec<Uuid>) {
+    let loaded = state.db_state.read().await.db.load_all().await;
+    let items = match loaded {
+        Ok(items) => items,
+        Err(e) => {
+            let st = state.db_state.write().await;
+            if let Err(e) = st.db.finish_job(job_id, JobStatus::Failed, &[], Some(&e.to_string())).await {
+                log_error!("Failed to record job {job_id}: {e}");
+            }
+            return;
+        }
+    };
+
+    // Entries taken by this job, so two items are not linked to the same one.
+    let mut claimed: HashSet<(&'static str, String)> = HashSet::new();
+    let mut report = Vec::new();
+    for item in items {
+        if (!item_ids.is_empty() && !item_ids.contains(&item.id))
+            || (item.source.is_some() && item.external_id.is_some())
+        {
+            continue;
+        }
+
+        let mut entry = ItemChanges {
+            item_id: item.id,
+            title: item.title.clone(),
+            created: false,
+            changes: Vec::new(),
+            error: None,
+        };
+        let search_type = matcher::search_type_for_unmatched(&item);
+        match search_chain(&state, &item.title, search_type).await {
+            Ok(results) => match matcher::match_title(&item.title, &results) {
+                MatchOutcome::Exact(idx) => {
+                    let result = &results[idx];
+                    let ext = result.external_id.clone().unwrap_or_default();
+                    let taken = {
+                        let st = state.db_state.read().await;
+                        st.db.find_by_external_id(result.source, &ext).await
+                    };
+                    match taken {
+                        Ok(Some(other)) => {
+                            entry.error = Some(format!("Matches '{}', which is already in the library", other.title));
+                        }
+                        Ok(None) if !claimed.insert((result.source, ext.clone())) => {
+                            entry.error = Some("Matches the same entry as another item".into());
+                        }
+                        Ok(None) => {
+                            entry.changes.push(refresh::FieldChange::Link {
+                                source: result.source.to_string(),
+                                external_id: ext,
+                            });
+                            entry.changes.extend(refresh::diff(&item, result));
+                        }
+                        Err(e) => entry.error = Some(e.to_string()),
+                    }
+                }
+                MatchOutcome::Ambiguous => {
+                    entry.error = Some(format!(
+                        "{} candidates, none with exactly this title; pick one via /api/items/{}/match",
+                        results.len(),
+                        item.id
+                    ));
+                }
+                MatchOutcome::NoResults => entry.error = Some("No match found".into()),
+            },
+            Err(e) => entry.error = Some(e),
+        }
+
+        if !dry_run && entry.error.is_none() {
+            let st = state.db_state.write().await;
+            if let Err(e) = apply_changes(&st.db, &entry).await {
+                entry.error = Some(e.to_string());
+            }
+        }
+        report.push(entry);
+    }
+
+    let status = if dry_run { JobStatus::Preview } else { JobStatus::Completed };
+    let st = state.db_state.write().await;
+    if let Err(e) = st.db.finish_job(job_id, status, &report, None).await {
+        log_error!("Failed to record job {job_id}: {e}");
+    }
+}
+
+/// Applies a refresh result to the current version of the item, so edits
+/// made while the job ran are kept.
+async fn apply_changes(db: &Database, entry: &ItemChanges) -> Result<(), StorageError> {
+    if entry.changes.is_empty() {
+        return Ok(());
+    }
+    let Some(mut item) = db.get_item(entry.item_id).await? else {
+        return Ok(());
+    };
+    for change in &entry.changes {
+        change.apply(&mut item);
+    }
+    db.upsert_item(&mut item).await
+}
+
+// ── POST /api/jobs/:id/apply ─────────────────────────────────
+
+/// Applies the changes found by a dry run.
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/apply",
+    tag = "jobs",
+    params(
+        ("id" = i64, Path, description = "Job id"),
+    ),
+    responses(
+        (status = 200, body = ApiJob),
+        (status = 404, description = "No such job", body = ApiError),
+        (status = 409, description = "Job is not a refresh preview", body = ApiError),
+    )
+)]
+async fn apply_job(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.write().await;
+    let mut job = match st.db.get_job(id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return ApiError::not_found("Job").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if job.status != JobStatus::Preview {
+        let msg = format!("Job is {}, not a preview", job.status.as_str());
+        return ApiError::new(StatusCode::CONFLICT, msg).into_response();
+    }
+
+    for entry in &mut job.items {
+        if let Err(e) = apply_changes(&st.db, entry).await {
+            entry.error = Some(e.to_string());
+        }
+    }
+    if let Err(e) = st.db.finish_job(id, JobStatus::Completed, &job.items, None).await {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    match st.db.get_job(id).await {
+        Ok(Some(job)) => Json(ApiJob::from(&job)).into_response(),
+        Ok(None) => ApiError::not_found("Job").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/jobs/:id/rollback?force= ───────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RollbackQuery {
+    /// Also roll back items changed or deleted since the import, throwing
+    /// those changes away.
+    #[serde(default)]
+    force: bool,
+}
+
+/// Reverses an import: removes the items it created and restores the
+/// ones it merged into.  Items edited or deleted since the import are not
+/// overwritten unless `force` is set: the answer is then a 409 listing
+/// them (code `changed_since_job`, details `ApiRollbackConflict`s).
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/rollback",
+    tag = "jobs",
+    params(
+        ("id" = i64, Path, description = "Job id"),
+        RollbackQuery,
+    ),
+    responses(
+        (status = 200, body = ApiJob),
+        (status = 404, description = "No such job", body = ApiError),
+        (status = 409, description = "Job is not a completed import, or items changed since", body = ApiError),
+    )
+)]
+async fn rollback_job(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Query(params): Query<RollbackQuery>,
+) -> Response {
+    let st = state.db_state.write().await;
+    let job = match st.db.get_job(id).await {
+        Ok(Some(job)) => job,
+        Ok(None) => return ApiError::not_found("Job").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if job.kind != JobKind::Import || job.status != JobStatus::Completed {
+        let msg = format!("Cannot roll back a {} {} job", job.status.as_str(), job.kind.as_str());
+        return ApiError::new(StatusCode::CONFLICT, msg).into_response();
+    }
+    if let (false, Some(finished_at)) = (params.force, job.finished_at) {
+        let conflicts = match st.db.rollback_conflicts(id, finished_at).await {
+            Ok(conflicts) => conflicts,
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+        if !conflicts.is_empty() {
+            let msg = format!("{} items changed since the import; roll back with force=true to discard that", conflicts.len());
+            let details: Vec<ApiRollbackConflict> = conflicts.iter().map(ApiRollbackConflict::from).collect();
+            return ApiError::new(StatusCode::CONFLICT, msg)
+                .with_code("changed_since_job")
+                .with_details(details)
+                .into_response();
+        }
+    }
+
+    if let Err(e) = st.db.rollback_job(id).await {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    match st.db.get_job(id).await {
+        Ok(Some(job)) => Json(ApiJob::from(&job)).into_response(),
+        Ok(None) => ApiError::not_found("Job").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/import?mode=merge|replace&dry_run= ─────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ImportQuery {
+    #[serde(default)]
+    mode: ImportMode,
+    /// Only report what the import would do (ApiImportPreview).
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Imports an archive (as written by `GET /api/export`) or a plain list
+/// of items.  `merge` runs as an import job that can be rolled back;
+/// `replace` backs the database up and then restores the archive as is.
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    tag = "archive",
+    params(ImportQuery),
+    request_body = Archive,
+    responses(
+        (status = 200, description = "`merge`: the import job (ApiJob). `replace`: what was restored (ApiRestore). `dry_run`: what either would do (ApiImportPreview).", body = ApiJob),
+        (status = 400, description = "Invalid archive or items", body = ApiError),
+    )
+)]
+async fn import_items(
+    State(state): State<AppState>,
+    Query(params): Query<ImportQuery>,
+    Json(body): Json<serde_json::Value>,
+) -> Response {
+    let archive = match parse_import(body) {
+        Ok(a) => a,
+        Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    if params.dry_run {
+        return preview_import(&state, params.mode, archive).await;
+    }
+    match params.mode {
+        ImportMode::Merge => merge_import(&state, archive).await,
+        ImportMode::Replace => replace_import(&state, archive).await,
+    }
+}
+
+/// Answers a dry run: how the archive would be merged or restored,
+/// item by item.  Only reads.
+async fn preview_import(state: &AppState, mode: ImportMode, archive: Archive) -> Response {
+    if mode == ImportMode::Replace
+        && let Some(settings) = &archive.settings
+        && let Err(e) = settings.validate(&known_providers(state))
+    {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+    let st = state.db_state.read().await;
+    let stored = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let preview = match mode {
+        ImportMode::Merge => preview_merge(&plan_merge(stored, archive)),
+        ImportMode::Replace => preview_replace(&stored, &archive),
+    };
+    Json(preview).into_response()
+}
+
+/// Adds the archive's items as one import job.  Items that duplicate an
+/// existing one (same id, provider entry, external ref or title) are
+/// merged into it instead.  Episode marks follow their item, progress and
+/// audit rows are only taken over for new items, and settings are kept.
+async fn merge_import(state: &AppState, archive: Archive) -> Response {
+    let st = state.db_state.write().await;
+    let stored = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let plan = plan_merge(stored, archive);
+    match st.db.apply_merge(&plan).await {
+        Ok(job) => Json(ApiJob::from(&job)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Wipes the library and restores the archive exactly, after taking a
+/// backup that the previous state can be recovered from.
+async fn replace_import(state: &AppState, archive: Archive) -> Response {
+    if let Some(settings) = &archive.settings
+        && let Err(e) = settings.validate(&known_providers(state))
+    {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let backup = match backup::create_backup(&st.db, &state.backup_dir).await {
+        Ok(info) => info,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if let Err(e) = st.db.replace_all(&archive).await {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    Json(ApiRestore {
+        items: archive.items.len(),
+        episodes: archive.activity.episodes.len(),
+        progress_events: archive.activity.progress.len(),
+        history_entries: archive.activity.history.len(),
+        backup: ApiBackup::from(&backup),
+    })
+    .into_response()
+}
+
+// ── GET /api/export ──────────────────────────────────────────
+
+/// Downloads the whole library as a JSON archive that `POST /api/import`
+/// reads back.
+#[utoipa::path(
+    get,
+    path = "/api/export",
+    tag = "archive",
+    responses(
+        (status = 200, body = Archive),
+    )
+)]
+async fn export_archive(State(state): State<AppState>) -> Response {
+    let archive = {
+        let st = state.db_state.read().await;
+        match st.db.export_archive().await {
+            Ok(a) => a,
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    };
+    let filename = format!("kars-{}.json", archive.exported_at.format("%Y%m%d-%H%M%S"));
+    (
+        [("content-disposition", format!("attachment; filename=\"{filename}\""))],
+        Json(archive),
+    )
+        .into_response()
+}
+
+// ── GET /api/export/sqlite ───────────────────────────────────
+
+/// Downloads a snapshot of the whole database as a SQLite file.
+#[utoipa::path(
+    get,
+    path = "/api/export/sqlite",
+    tag = "archive",
+    responses(
+        (status = 200, description = "SQLite database file", content_type = "application/vnd.sqlite3", body = Vec<u8>),
+    )
+)]
+async fn export_sqlite(State(state): State<AppState>) -> Response {
+    let dir = match tempfile::tempdir() {
+        Ok(d) => d,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let path = dir.path().join("kars.db");
+
+    {
+        let st = state.db_state.write().await;
+        if let Err(e) = st.db.snapshot_to(&path).await {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    }
+
+    let bytes = match std::fs::read(&path) {
+        Ok(b) => b,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let filename = format!("kars-{}.db", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    (
+        StatusCode::OK,
+        [
+            ("content-type", "application/vnd.sqlite3".to_string()),
+            ("content-disposition", format!("attachment; filename=\"{filename}\"")),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+// ── POST /api/backup ─────────────────────────────────────────
+
+impl From<&BackupInfo> for ApiBackup {
+    fn from(b: &BackupInfo) -> Self {
+        ApiBackup {
+            name: b.name.clone(),
+            size_bytes: b.size,
+            created_at: b.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            uploaded_to: None,
+            upload_error: None,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/backup",
+    tag = "backups",
+    responses(
+        (status = 201, body = ApiBackup),
+    )
+)]
+async fn trigger_backup(State(state): State<AppState>) -> Response {
+    let st = state.db_state.write().await;
+    let settings = match st.db.load_settings().await {
+        Ok(s) => s,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let info = match backup::create_backup(&st.db, &state.backup_dir).await {
+        Ok(info) => info,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    drop(st);
+    let mut api = ApiBackup::from(&info);
+    match upload_backup(&state.backup_target, &state.backup_dir, &info).await {
+        Some(Ok(location)) => api.uploaded_to = Some(location),
+        Some(Err(e)) => api.upload_error = Some(e),
+        None => {}
+    }
+    if let Err(e) = backup::prune_backups(&state.backup_dir, settings.backup_keep as usize) {
+        log_error!("Pruning backups failed: {e}");
+    }
+    (StatusCode::CREATED, Json(api)).into_response()
+}
+
+// ── GET /api/backups ─────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/backups",
+    tag = "backups",
+    responses(
+        (status = 200, body = Vec<ApiBackup>),
+    )
+)]
+async fn list_backups(State(state): State<AppState>) -> Response {
+    match backup::list_backups(&state.backup_dir) {
+        Ok(list) => {
+            let api: Vec<ApiBackup> = list.iter().map(ApiBackup::from).collect();
+            Json(api).into_response()
+        }
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── GET /api/providers ───────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/providers",
+    tag = "explore",
+    responses(
+        (status = 200, body = Vec<ApiProvider>),
+    )
+)]
+async fn list_providers(State(state): State<AppState>) -> Response {
+    let mut api: Vec<ApiProvider> = state
+        .searchers
+        .iter()
+        .map(|s| ApiProvider {
+            name: s.name().to_string(),
+            media_types: s.supported_types().iter().map(|t| t.as_str()).collect(),
+            available: true,
+            reason: None,
+        })
+        .collect();
+    api.extend(state.unavailable.iter().map(ApiProvider::from));
+    Json(api).into_response()
+}
+
+// ── GET /api/settings ────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/settings",
+    tag = "settings",
+    responses(
+        (status = 200, body = Settings),
+    )
+)]
+async fn get_settings(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.load_settings().await {
+        Ok(settings) => Json(settings.redacted()).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── PUT /api/settings ────────────────────────────────────────
+
+#[utoipa::path(
+    put,
+    path = "/api/settings",
+    tag = "settings",
+    request_body = Settings,
+    responses(
+        (status = 200, body = Settings),
+        (status = 400, description = "Invalid settings", body = ApiError),
+    )
+)]
+async fn update_settings(
+    State(state): State<AppState>,
+    Json(mut payload): Json<Settings>,
+) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.load_settings().await {
+        Ok(stored) => payload.keep_secrets(&stored),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+    if let Err(e) = payload.validate(&known_providers(&state)) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+
+    match st.db.save_settings(&payload).await {
+        Ok(()) => Json(payload.redacted()).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Every provider name with its search types, enabled or not.
+fn known_providers(state: &AppState) -> Vec<(&str, &[MediaSearchType])> {
+    state
+        .searchers
+        .iter()
+        .map(|s| (s.name(), s.supported_types()))
+        .chain(state.unavailable.iter().map(|p| (p.name, p.supported_types)))
+        .collect()
 }
 
-type SharedState = Arc<Mutex<WebState>>;
-type Searchers = Arc<Vec<Box<dyn SearchProvider + Send + Sync>>>;
+// ── GET /api/view-presets ────────────────────────────────────
 
-/// Combined state passed to handlers via axum State extractor.
-#[derive(Clone)]
-struct AppState {
-    db_state: SharedState,
-    searchers: Searchers,
+#[utoipa::path(
+    get,
+    path = "/api/view-presets",
+    tag = "settings",
+    responses(
+        (status = 200, body = BTreeMap<String, ViewPreset>),
+    )
+)]
+async fn list_view_presets(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.load_settings().await {
+        Ok(settings) => Json(settings.view_presets).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
 }
 
-// ── Server bootstrap ─────────────────────────────────────────
+// ── PUT /api/view-presets/:name ──────────────────────────────
 
-/// Build search providers. Must be called **outside** an async context because
-/// reqwest::blocking::Client spawns its own Tokio runtime internally.
-pub fn build_searchers() -> Vec<Box<dyn SearchProvider + Send + Sync>> {
-    let mut searchers: Vec<Box<dyn SearchProvider + Send + Sync>> = vec![
-        Box::new(AniListClient::new()),
-        Box::new(MangaDexClient::new()),
-        Box::new(OpenLibraryClient::new()),
-    ];
-    if let Some(tmdb) = TmdbClient::from_env() {
-        searchers.push(Box::new(tmdb));
-    } else {
-        eprintln!("Note: TMDB_API_KEY not set — movie/series search disabled.");
+#[utoipa::path(
+    put,
+    path = "/api/view-presets/{name}",
+    tag = "settings",
+    params(
+        ("name" = String, Path, description = "Preset name"),
+    ),
+    request_body = ViewPreset,
+    responses(
+        (status = 200, body = ViewPreset),
+        (status = 400, description = "Invalid preset name", body = ApiError),
+    )
+)]
+async fn save_view_preset(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(preset): Json<ViewPreset>,
+) -> Response {
+    if let Err(e) = settings::validate_preset_name(&name) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
     }
-    searchers
-}
 
-pub async fn start_server(
-    db: Database,
-    port: u16,
-    searchers: Vec<Box<dyn SearchProvider + Send + Sync>>,
-) {
-    let app_state = AppState {
-        db_state: Arc::new(Mutex::new(WebState { db })),
-        searchers: Arc::new(searchers),
+    let st = state.db_state.write().await;
+    let mut settings = match st.db.load_settings().await {
+        Ok(s) => s,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
+    settings.view_presets.insert(name, preset.clone());
+    match st.db.save_settings(&settings).await {
+        Ok(()) => Json(preset).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-    let api = Router::new()
-        .route("/api/items", get(list_items).post(create_item))
-        .route(
-            "/api/items/{id}",
-            get(get_item).put(update_item).delete(delete_item),
-        )
-        .route("/api/search", get(search_items))
-        .route("/api/explore", get(explore_items))
-        .route("/api/stats", get(get_stats))
-        .with_state(app_state);
-
-    // Add CORS for development (Next.js on :3000 → Rust on :3001)
-    let app = api
-        .fallback(static_handler)
-        .layer(tower_http::cors::CorsLayer::permissive());
+// ── DELETE /api/view-presets/:name ───────────────────────────
 
-    let addr = format!("0.0.0.0:{port}");
-    let listener = tokio::net::TcpListener::bind(&addr)
-        .await
-        .expect("Failed to bind address");
+#[utoipa::path(
+    delete,
+    path = "/api/view-presets/{name}",
+    tag = "settings",
+    params(
+        ("name" = String, Path, description = "Preset name"),
+    ),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such preset", body = ApiError),
+    )
+)]
+async fn delete_view_preset(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    let st = state.db_state.write().await;
+    let mut settings = match st.db.load_settings().await {
+        Ok(s) => s,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if settings.view_presets.remove(&name).is_none() {
+        return ApiError::not_found("View preset").into_response();
+    }
+    match st.db.save_settings(&settings).await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-    println!("╔══════════════════════════════════════════╗");
-    println!("║      KARS — Media Archive System         ║");
-    println!("║                                          ║");
-    println!("║  Web UI:  http://localhost:{port:<5}         ║");
-    println!("║  API:     http://localhost:{port:<5}/api     ║");
-    println!("╚══════════════════════════════════════════╝");
+// ── GET /api/telemetry/preview ───────────────────────────────
 
-    axum::serve(listener, app).await.unwrap();
+#[utoipa::path(
+    get,
+    path = "/api/telemetry/preview",
+    tag = "settings",
+    responses(
+        (status = 200, body = ApiTelemetryPreview),
+    )
+)]
+async fn preview_telemetry(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.usage().await {
+        Ok(usage) => Json(ApiTelemetryPreview {
+            enabled: usage.settings.telemetry_enabled,
+            endpoint: state.telemetry_endpoint.as_ref().clone(),
+            payload: telemetry::build_payload(&usage),
+        })
+        .into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
 }
 
-// ── GET /api/items ───────────────────────────────────────────
+// ── GET /api/webhooks ────────────────────────────────────────
 
-async fn list_items(State(state): State<AppState>) -> Response {
-    let st = state.db_state.lock().await;
-    match st.db.load_all().await {
-        Ok(items) => {
-            let api: Vec<ApiMediaItem> = items.iter().map(ApiMediaItem::from).collect();
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = Vec<ApiWebhook>),
+    )
+)]
+async fn list_webhooks(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.list_webhooks().await {
+        Ok(list) => {
+            let api: Vec<ApiWebhook> = list.iter().map(ApiWebhook::from).collect();
             Json(api).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-// ── POST /api/items ──────────────────────────────────────────
+// ── POST /api/webhooks ───────────────────────────────────────
 
-async fn create_item(
-    State(state): State<AppState>,
-    Json(payload): Json<ApiMediaItem>,
-) -> Response {
-    let item = match payload.into_media_item() {
-        Ok(i) => i,
-        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
-    };
+/// Registers a receiver for `item.created`, `item.completed` and
+/// `item.scored` events.
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    request_body = ApiNewWebhook,
+    responses(
+        (status = 201, body = ApiWebhook),
+        (status = 400, description = "Invalid URL", body = ApiError),
+    )
+)]
+async fn create_webhook(State(state): State<AppState>, Json(body): Json<ApiNewWebhook>) -> Response {
+    let url = body.url.trim();
+    if let Err(e) = webhooks::validate_url(url) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+    let secret = body
+        .secret
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
 
-    let st = state.db_state.lock().await;
-    match st.db.upsert_item(&item).await {
-        Ok(()) => {
-            let api = ApiMediaItem::from(&item);
-            (StatusCode::CREATED, Json(api)).into_response()
-        }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    let st = state.db_state.write().await;
+    match st.db.add_webhook(url, &secret).await {
+        Ok(hook) => (StatusCode::CREATED, Json(ApiWebhook::from(&hook))).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-// ── GET /api/items/:id ───────────────────────────────────────
+// ── DELETE /api/webhooks/:id ─────────────────────────────────
 
-async fn get_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
-    let uuid = match Uuid::parse_str(&id) {
-        Ok(u) => u,
-        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
-    };
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = i64, Path, description = "Webhook id"),
+    ),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "No such webhook", body = ApiError),
+    )
+)]
+async fn delete_webhook(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.delete_webhook(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Webhook").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-    let st = state.db_state.lock().await;
-    match st.db.get_item(uuid).await {
-        Ok(Some(item)) => Json(ApiMediaItem::from(&item)).into_response(),
-        Ok(None) => StatusCode::NOT_FOUND.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+// ── POST /api/notifiers/test ─────────────────────────────────
+
+/// Sends a test notice through every configured notifier, whatever
+/// events it wants, and reports how each went.
+#[utoipa::path(
+    post,
+    path = "/api/notifiers/test",
+    tag = "notifiers",
+    responses(
+        (status = 200, body = Vec<ApiNotifierTest>),
+    )
+)]
+async fn test_notifiers(State(state): State<AppState>) -> Response {
+    let settings = match state.db_state.read().await.db.load_settings().await {
+        Ok(s) => s,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let results = tokio::task::spawn_blocking(move || {
+        settings
+            .notifiers
+            .iter()
+            .map(|config| {
+                let notifier = notifiers::build(&config.channel);
+                let result = notifier.send(&Notice::test());
+                ApiNotifierTest { kind: notifier.name().to_string(), sent: result.is_ok(), error: result.err() }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await;
+    match results {
+        Ok(results) => Json(results).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-// ── PUT /api/items/:id ───────────────────────────────────────
+// ── GET /api/trakt ───────────────────────────────────────────
 
-async fn update_item(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-    Json(mut payload): Json<ApiMediaItem>,
-) -> Response {
-    let uuid = match Uuid::parse_str(&id) {
-        Ok(u) => u,
-        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+#[utoipa::path(
+    get,
+    path = "/api/trakt",
+    tag = "trakt",
+    responses(
+        (status = 200, body = ApiTraktStatus),
+    )
+)]
+async fn trakt_status(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let (auth, settings) = match (st.db.load_trakt_auth().await, st.db.load_settings().await) {
+        (Ok(a), Ok(s)) => (a, s),
+        (Err(e), _) | (_, Err(e)) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     };
+    Json(ApiTraktStatus::new(state.trakt.is_some(), auth.as_ref(), settings.trakt_sync_hours)).into_response()
+}
 
-    // Ensure the ID in the path matches the body
-    payload.id = uuid.to_string();
+// ── POST /api/trakt/connect ──────────────────────────────────
 
-    let item = match payload.into_media_item() {
-        Ok(i) => i,
-        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+/// Starts connecting a Trakt account.  The server waits in the background
+/// for the returned code to be entered and then stores the account,
+/// replacing any connected before.
+#[utoipa::path(
+    post,
+    path = "/api/trakt/connect",
+    tag = "trakt",
+    responses(
+        (status = 200, body = ApiTraktConnect),
+        (status = 502, description = "Trakt could not be reached", body = ApiError),
+        (status = 503, description = "TRAKT_CLIENT_ID or TRAKT_CLIENT_SECRET is not set", body = ApiError),
+    )
+)]
+async fn connect_trakt(State(state): State<AppState>) -> Response {
+    let Some(config) = state.trakt.as_ref().clone() else {
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, TRAKT_NOT_CONFIGURED).into_response();
     };
+    let client_config = config.clone();
+    let code = match tokio::task::spawn_blocking(move || TraktClient::new(&client_config).device_code()).await {
+        Ok(Ok(code)) => code,
+        Ok(Err(e)) => return ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let response = ApiTraktConnect {
+        user_code: code.user_code.clone(),
+        verification_url: code.verification_url.clone(),
+        expires_in: code.expires_in,
+    };
+    tokio::spawn(await_trakt_token(state.db_state.clone(), config, code));
+    Json(response).into_response()
+}
 
-    let st = state.db_state.lock().await;
-    match st.db.upsert_item(&item).await {
-        Ok(()) => {
-            let api = ApiMediaItem::from(&item);
-            Json(api).into_response()
+const TRAKT_NOT_CONFIGURED: &str = "Trakt is not configured; set TRAKT_CLIENT_ID and TRAKT_CLIENT_SECRET";
+
+/// Polls Trakt until the device code is entered, denied or expires.
+async fn await_trakt_token(db_state: SharedState, config: TraktConfig, code: DeviceCode) {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(code.expires_in);
+    let mut interval = std::time::Duration::from_secs(code.interval.max(1));
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(interval).await;
+        let (config, device_code) = (config.clone(), code.device_code.clone());
+        let token = match tokio::task::spawn_blocking(move || TraktClient::new(&config).poll_token(&device_code)).await {
+            Ok(Ok(Poll::Pending)) => continue,
+            Ok(Ok(Poll::SlowDown)) => {
+                interval += std::time::Duration::from_secs(1);
+                continue;
+            }
+            Ok(Ok(Poll::Approved(token))) => token,
+            Ok(Ok(Poll::Failed(reason))) => {
+                log_error!("Connecting Trakt failed: {reason}");
+                return;
+            }
+            Ok(Err(e)) => {
+                log_error!("Connecting Trakt: {e}");
+                continue;
+            }
+            Err(e) => {
+                log_error!("Connecting Trakt failed: {e}");
+                return;
+            }
+        };
+        let auth = TraktAuth {
+            expires_at: token.expires_at(),
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            connected_at: chrono::Utc::now(),
+            last_synced_at: None,
+        };
+        match db_state.write().await.db.save_trakt_auth(&auth).await {
+            Ok(()) => println!("Trakt account connected"),
+            Err(e) => log_error!("Storing the Trakt account failed: {e}"),
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        return;
     }
+    log_error!("Connecting Trakt failed: the code expired");
 }
 
-// ── DELETE /api/items/:id ────────────────────────────────────
+// ── DELETE /api/trakt ────────────────────────────────────────
 
-async fn delete_item(State(state): State<AppState>, Path(id): Path<String>) -> Response {
-    let uuid = match Uuid::parse_str(&id) {
-        Ok(u) => u,
-        Err(_) => return (StatusCode::BAD_REQUEST, "Invalid UUID").into_response(),
+/// Forgets the connected account.  Items imported from it stay.
+#[utoipa::path(
+    delete,
+    path = "/api/trakt",
+    tag = "trakt",
+    responses(
+        (status = 204, description = "Disconnected"),
+        (status = 404, description = "No account is connected", body = ApiError),
+    )
+)]
+async fn disconnect_trakt(State(state): State<AppState>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.delete_trakt_auth().await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Trakt connection").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── POST /api/trakt/import ───────────────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct TraktImportQuery {
+    /// Only report what the import would do (ApiImportPreview).
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Merges the connected account's watched movies and shows into the
+/// library as an import job, which can be rolled back.
+#[utoipa::path(
+    post,
+    path = "/api/trakt/import",
+    tag = "trakt",
+    params(TraktImportQuery),
+    responses(
+        (status = 200, description = "The import job, or with `dry_run` what it would do (ApiImportPreview)", body = ApiJob),
+        (status = 409, description = "No account is connected", body = ApiError),
+        (status = 502, description = "Trakt could not be read", body = ApiError),
+        (status = 503, description = "TRAKT_CLIENT_ID or TRAKT_CLIENT_SECRET is not set", body = ApiError),
+    )
+)]
+async fn import_trakt(State(state): State<AppState>, Query(params): Query<TraktImportQuery>) -> Response {
+    let Some(config) = state.trakt.as_ref() else {
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, TRAKT_NOT_CONFIGURED).into_response();
+    };
+    let auth = match state.db_state.read().await.db.load_trakt_auth().await {
+        Ok(Some(auth)) => auth,
+        Ok(None) => return ApiError::new(StatusCode::CONFLICT, "No Trakt account is connected").into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let items = match fetch_trakt_items(&state.db_state, config, auth).await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::BAD_GATEWAY, e).into_response(),
     };
 
-    let st = state.db_state.lock().await;
-    match st.db.delete_item(uuid).await {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
-        Ok(false) => StatusCode::NOT_FOUND.into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    if params.dry_run {
+        let stored = match state.db_state.read().await.db.load_all().await {
+            Ok(items) => items,
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+        return Json(preview_merge(&plan_merge(stored, trakt_archive(items)))).into_response();
+    }
+
+    let st = state.db_state.write().await;
+    let stored = match st.db.load_all().await {
+        Ok(items) => items,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let plan = plan_merge(stored, trakt_archive(items)).without_unchanged();
+    let job = match st.db.apply_merge(&plan).await {
+        Ok(job) => job,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if let Err(e) = st.db.set_trakt_synced(chrono::Utc::now()).await {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
     }
+    Json(ApiJob::from(&job)).into_response()
 }
 
-// ── GET /api/search?q=... ────────────────────────────────────
+/// Reads the account's history as items, refreshing its token first when
+/// it is about to expire.  The database is not locked while Trakt is
+/// asked.
+async fn fetch_trakt_items(db_state: &SharedState, config: &TraktConfig, mut auth: TraktAuth) -> Result<Vec<MediaItem>, String> {
+    if auth.needs_refresh(chrono::Utc::now()) {
+        let (client_config, refresh_token) = (config.clone(), auth.refresh_token.clone());
+        let token = tokio::task::spawn_blocking(move || TraktClient::new(&client_config).refresh(&refresh_token))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+        auth.expires_at = token.expires_at();
+        auth.access_token = token.access_token;
+        auth.refresh_token = token.refresh_token;
+        db_state.write().await.db.save_trakt_auth(&auth).await.map_err(|e| e.to_string())?;
+    }
+    let config = config.clone();
+    let history = tokio::task::spawn_blocking(move || TraktClient::new(&config).history(&auth.access_token))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(trakt::to_items(history))
+}
 
-#[derive(Deserialize)]
-struct SearchQuery {
-    q: Option<String>,
+/// Trakt items as an archive without activity or settings.
+fn trakt_archive(items: Vec<MediaItem>) -> Archive {
+    Archive {
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Utc::now(),
+        items,
+        activity: Default::default(),
+        settings: None,
+    }
 }
 
-async fn search_items(
-    State(state): State<AppState>,
-    Query(params): Query<SearchQuery>,
-) -> Response {
-    let query = params.q.unwrap_or_default();
-    if query.is_empty() {
-        return Json(Vec::<ApiMediaItem>::new()).into_response();
+// ── GET /api/reading-servers ─────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/reading-servers",
+    tag = "reading-servers",
+    responses(
+        (status = 200, body = ApiReadingServers),
+    )
+)]
+async fn list_reading_servers(State(state): State<AppState>) -> Response {
+    let settings = match state.db_state.read().await.db.load_settings().await {
+        Ok(settings) => settings,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    Json(ApiReadingServers {
+        servers: state.reading_servers.iter().map(|s| s.name().to_string()).collect(),
+        sync_minutes: settings.reading_sync_minutes,
+    })
+    .into_response()
+}
+
+// ── POST /api/reading-servers/sync ───────────────────────────
+
+/// Pulls reading progress from Kavita and Komga now and carries it over
+/// to the matching readables.
+#[utoipa::path(
+    post,
+    path = "/api/reading-servers/sync",
+    tag = "reading-servers",
+    responses(
+        (status = 200, body = ApiReadingSync),
+        (status = 503, description = "Neither Kavita nor Komga is configured", body = ApiError),
+    )
+)]
+async fn sync_reading_servers(State(state): State<AppState>) -> Response {
+    if state.reading_servers.is_empty() {
+        let msg = "Set KAVITA_URL and KAVITA_API_KEY or KOMGA_URL and KOMGA_API_KEY to sync reading progress";
+        return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, msg).into_response();
+    }
+    match sync_reading_progress(&state.db_state, &state.reading_servers).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
+}
 
-    let st = state.db_state.lock().await;
-    match st.db.search_items(&query).await {
-        Ok(items) => {
-            let api: Vec<ApiMediaItem> = items.iter().map(ApiMediaItem::from).collect();
-            Json(api).into_response()
+// ── GET /api/anilist ─────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/anilist",
+    tag = "anilist",
+    responses(
+        (status = 200, body = ApiAniListStatus),
+    )
+)]
+async fn anilist_status(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    anilist_status_response(&st.db).await
+}
+
+async fn anilist_status_response(db: &Database) -> Response {
+    let (auth, settings, queue) = match (db.load_anilist_auth().await, db.load_settings().await, db.anilist_queue().await) {
+        (Ok(a), Ok(s), Ok(q)) => (a, s, q),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    Json(ApiAniListStatus::new(auth.as_ref(), settings.anilist_write_through, &queue)).into_response()
+}
+
+// ── PUT /api/anilist ─────────────────────────────────────────
+
+/// Links the AniList account the token belongs to, replacing any linked
+/// before.  Only changes made from now on are sent.
+#[utoipa::path(
+    put,
+    path = "/api/anilist",
+    tag = "anilist",
+    request_body = ApiAniListLink,
+    responses(
+        (status = 200, body = ApiAniListStatus),
+        (status = 400, description = "AniList did not accept the token", body = ApiError),
+        (status = 502, description = "AniList could not be reached", body = ApiError),
+    )
+)]
+async fn link_anilist(State(state): State<AppState>, Json(body): Json<ApiAniListLink>) -> Response {
+    let token = body.token.trim().to_string();
+    if token.is_empty() {
+        return ApiError::new(StatusCode::BAD_REQUEST, "Token must not be empty").into_response();
     }
+    let client_token = token.clone();
+    let viewer = match tokio::task::spawn_blocking(move || AniListClient::with_token(&client_token).viewer()).await {
+        Ok(Ok(viewer)) => viewer,
+        Ok(Err(SearchError::Api(e))) => return ApiError::new(StatusCode::BAD_REQUEST, e).into_response(),
+        Ok(Err(e)) => return ApiError::new(StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let st = state.db_state.write().await;
+    let history_cursor = match st.db.latest_history_id().await {
+        Ok(id) => id,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let auth = AniListAuth {
+        token,
+        user_id: viewer.id,
+        user_name: viewer.name,
+        connected_at: chrono::Utc::now(),
+        history_cursor,
+    };
+    if let Err(e) = st.db.save_anilist_auth(&auth).await {
+        return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    anilist_status_response(&st.db).await
 }
 
-// ── GET /api/stats ───────────────────────────────────────────
+// ── DELETE /api/anilist ──────────────────────────────────────
 
-async fn get_stats(State(state): State<AppState>) -> Response {
-    let st = state.db_state.lock().await;
-    match st.db.load_all().await {
-        Ok(items) => {
-            let api_items: Vec<ApiMediaItem> = items.iter().map(ApiMediaItem::from).collect();
-            let stats = ApiStats::from_items(&api_items);
-            Json(stats).into_response()
-        }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+/// Unlinks the account; updates not sent yet are dropped.
+#[utoipa::path(
+    delete,
+    path = "/api/anilist",
+    tag = "anilist",
+    responses(
+        (status = 204, description = "Unlinked"),
+        (status = 404, description = "No account is linked", body = ApiError),
+    )
+)]
+async fn unlink_anilist(State(state): State<AppState>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.delete_anilist_auth().await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("AniList link").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
-// ── GET /api/explore?q=...&type=anime|movie|manga|book ───────
+// ── GET /api/outbox ──────────────────────────────────────────
 
-#[derive(Deserialize)]
-struct ExploreQuery {
-    q: Option<String>,
-    #[serde(rename = "type")]
-    media_type: Option<String>,
+/// Item writes queued while Turso was unreachable, oldest first.
+#[utoipa::path(
+    get,
+    path = "/api/outbox",
+    tag = "outbox",
+    responses(
+        (status = 200, body = Vec<ApiQueuedWrite>),
+    )
+)]
+async fn list_outbox(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    let Some(outbox) = &st.outbox else {
+        return Json(Vec::<ApiQueuedWrite>::new()).into_response();
+    };
+    match outbox.list().await {
+        Ok(writes) => Json(writes.iter().map(ApiQueuedWrite::from).collect::<Vec<_>>()).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
 }
 
-async fn explore_items(
-    State(state): State<AppState>,
-    Query(params): Query<ExploreQuery>,
-) -> Response {
-    let query = params.q.unwrap_or_default();
-    if query.len() < 2 {
-        return Json(Vec::<ApiExploreResult>::new()).into_response();
+// ── POST /api/outbox/replay?force= ───────────────────────────
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ReplayQuery {
+    /// Also replay writes held back as conflicts, overwriting the changes
+    /// made meanwhile.
+    #[serde(default)]
+    force: bool,
+}
+
+/// Replays the queued writes now instead of waiting for the next retry.
+#[utoipa::path(
+    post,
+    path = "/api/outbox/replay",
+    tag = "outbox",
+    params(ReplayQuery),
+    responses(
+        (status = 200, body = ApiOutboxReplay),
+        (status = 503, description = "Turso is still unreachable", body = ApiError),
+    )
+)]
+async fn replay_outbox_now(State(state): State<AppState>, Query(params): Query<ReplayQuery>) -> Response {
+    {
+        let st = state.db_state.read().await;
+        let Some(outbox) = &st.outbox else {
+            return Json(ApiOutboxReplay::default()).into_response();
+        };
+        if !st.db.reachable().await {
+            return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Turso is still unreachable").into_response();
+        }
+        match outbox.list().await {
+            Ok(writes) if writes.is_empty() => return Json(ApiOutboxReplay::default()).into_response(),
+            Ok(_) => {}
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    let st = state.db_state.write().await;
+    match replay_outbox(&st, params.force).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
+}
+
+// ── DELETE /api/outbox/:id ───────────────────────────────────
 
-    let search_type = match params.media_type.as_deref() {
-        Some("anime") => MediaSearchType::Anime,
-        Some("movie") => MediaSearchType::Movie,
-        Some("series") => MediaSearchType::Series,
-        Some("manga") => MediaSearchType::Manga,
-        Some("book") => MediaSearchType::Book,
-        Some("light_novel") => MediaSearchType::LightNovel,
-        _ => MediaSearchType::Anime, // default
+/// Drops a queued write without replaying it.
+#[utoipa::path(
+    delete,
+    path = "/api/outbox/{id}",
+    tag = "outbox",
+    params(
+        ("id" = i64, Path, description = "Queued write id"),
+    ),
+    responses(
+        (status = 204, description = "Discarded"),
+        (status = 404, description = "No such queued write", body = ApiError),
+    )
+)]
+async fn discard_queued_write(State(state): State<AppState>, Path(id): Path<i64>) -> Response {
+    let st = state.db_state.write().await;
+    let Some(outbox) = &st.outbox else {
+        return ApiError::not_found("Queued write").into_response();
     };
+    match outbox.remove(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => ApiError::not_found("Queued write").into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
 
-    // Run blocking search providers on a dedicated thread so
-    // reqwest::blocking doesn't panic inside the async runtime.
-    let searchers = Arc::clone(&state.searchers);
-    let q = query.clone();
-    let result = tokio::task::spawn_blocking(move || {
-        let mut all_results = Vec::new();
-        for searcher in searchers.iter() {
-            if searcher.supported_types().contains(&search_type) {
-                match searcher.search(&q, search_type) {
-                    Ok(results) => {
-                        all_results.extend(
-                            results.iter().map(ApiExploreResult::from_search_result)
-                        );
-                    }
-                    Err(e) => {
-                        eprintln!("Search provider {} error: {e}", searcher.name());
-                    }
-                }
-            }
+// ── GET /api/diagnostics ─────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/api/diagnostics",
+    tag = "diagnostics",
+    responses(
+        (status = 200, body = Vec<ApiDiagnostic>),
+    )
+)]
+async fn list_diagnostics(State(state): State<AppState>) -> Response {
+    let st = state.db_state.read().await;
+    match st.db.list_diagnostics().await {
+        Ok(list) => {
+            let api: Vec<ApiDiagnostic> = list.iter().map(ApiDiagnostic::from).collect();
+            Json(api).into_response()
         }
-        all_results
-    })
-    .await;
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+// ── DELETE /api/diagnostics ──────────────────────────────────
 
-    match result {
-        Ok(items) => Json(items).into_response(),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+#[utoipa::path(
+    delete,
+    path = "/api/diagnostics",
+    tag = "diagnostics",
+    responses(
+        (status = 204, description = "Cleared"),
+    )
+)]
+async fn clear_diagnostics(State(state): State<AppState>) -> Response {
+    let st = state.db_state.write().await;
+    match st.db.clear_diagnostics().await {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
     }
 }
 
+// ── GET /api/admin/jobs ──────────────────────────────────────
+
+/// The background jobs running for this library: their interval, how
+/// the last run went and when the next one is due.
+#[utoipa::path(
+    get,
+    path = "/api/admin/jobs",
+    tag = "admin",
+    responses(
+        (status = 200, body = Vec<ApiScheduledJob>),
+    )
+)]
+async fn list_scheduled_jobs(State(state): State<AppState>) -> Json<Vec<ApiScheduledJob>> {
+    Json(state.scheduler.statuses().iter().map(ApiScheduledJob::from).collect())
+}
+
 // ── Static file serving ──────────────────────────────────────
 
 #[cfg(feature = "embed-frontend")]
@@ -298,38 +5770,43 @@ mod embedded {
     pub struct Assets;
 }
 
-async fn static_handler(uri: axum::http::Uri) -> Response {
+/// Serves the embedded frontend.  `base_path` is the `BASE_PATH` prefix
+/// the router is nested under (already stripped from `uri`).
+async fn static_handler(uri: axum::http::Uri, base_path: String) -> Response {
+    // Unknown API routes are errors, not pages of the app.
+    if uri.path().starts_with("/api/") {
+        return ApiError::new(StatusCode::NOT_FOUND, format!("No route for {}", uri.path())).into_response();
+    }
+
     #[cfg(feature = "embed-frontend")]
     {
         let path = uri.path().trim_start_matches('/');
         let path = if path.is_empty() { "index.html" } else { path };
 
-        if let Some(content) = embedded::Assets::get(path) {
-            let mime = guess_mime(path);
-            return (
-                StatusCode::OK,
-                [("content-type", mime)],
-                content.data.to_vec(),
-            )
-                .into_response();
-        }
-
-        // SPA fallback — serve index.html for unmatched routes
-        if let Some(content) = embedded::Assets::get("index.html") {
-            return (
-                StatusCode::OK,
-                [("content-type", "text/html; charset=utf-8")],
-                content.data.to_vec(),
-            )
-                .into_response();
-        }
+        let (path, content) = match embedded::Assets::get(path) {
+            Some(content) => (path, content),
+            // SPA fallback — serve index.html for unmatched routes
+            None => match embedded::Assets::get("index.html") {
+                Some(content) => ("index.html", content),
+                None => return (StatusCode::NOT_FOUND, "Not found").into_response(),
+            },
+        };
 
-        (StatusCode::NOT_FOUND, "Not found").into_response()
+        let mime = guess_mime(path);
+        let html = mime.starts_with("text/html");
+        let rewrite = html || mime.starts_with("text/css") || mime.starts_with("application/javascript");
+        let body = match std::str::from_utf8(&content.data) {
+            Ok(text) if rewrite && !base_path.is_empty() => {
+                base_path::rewrite_asset(text, &base_path, html).into_bytes()
+            }
+            _ => content.data.to_vec(),
+        };
+        (StatusCode::OK, [("content-type", mime)], body).into_response()
     }
 
     #[cfg(not(feature = "embed-frontend"))]
     {
-        let _ = uri;
+        let _ = (uri, base_path);
         axum::response::Html(
             r#"<!DOCTYPE html>
 <html><head><meta charset="utf-8"><title>KARS</title></head>
@@ -368,3 +5845,263 @@ fn guess_mime(path: &str) -> &'static str {
         _ => "application/octet-stream",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::{Progress, ReadStatus, ReadableKind, WatchStatus};
+    use axum::body::{to_bytes, Body};
+    use tower::ServiceExt;
+
+    /// Sends a JSON request to the library's router.
+    async fn send(state: &AppState, method: &str, uri: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let request = axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = library_router(state.clone(), "").oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+    }
+
+    async fn add_series(state: &AppState, current: u32) -> Uuid {
+        let mut item = MediaItem::new(
+            "Frieren".into(),
+            MediaItemType::Series(Progress { current, total: Some(28) }, WatchStatus::Watching),
+        );
+        state.db_state.read().await.db.upsert_item(&mut item).await.unwrap();
+        item.id
+    }
+
+    #[tokio::test]
+    async fn test_marking_episodes_keeps_earlier_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let id = add_series(&state, 8).await;
+
+        let (status, item) = send(&state, "PUT", &format!("/api/items/{id}/episodes/5"), serde_json::json!({"watched": true})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(item["progress"], 8);
+        let (_, item) = send(&state, "PUT", &format!("/api/items/{id}/episodes/10"), serde_json::json!({"watched": true})).await;
+        assert_eq!(item["progress"], 9);
+
+        let (_, item) = send(&state, "POST", &format!("/api/items/{id}/progress/increment"), serde_json::json!({})).await;
+        assert_eq!(item["progress"], 10);
+        let (_, item) = send(&state, "PUT", &format!("/api/items/{id}/episodes/3"), serde_json::json!({"watched": false})).await;
+        assert_eq!(item["progress"], 9);
+    }
+
+    #[tokio::test]
+    async fn test_settings_never_show_notifier_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let (_, mut settings) = send(&state, "GET", "/api/settings", serde_json::Value::Null).await;
+        settings["notifiers"] = serde_json::json!([
+            {"kind": "telegram", "bot_token": "123:abc", "chat_id": "42"},
+            {"kind": "email", "host": "smtp.example.org", "username": "me", "password": "hunter2",
+             "from": "kars@example.org", "to": "me@example.org"},
+            {"kind": "webhook", "url": "https://hooks.example.org/services/T0/B0/xoxb"},
+        ]);
+        let (status, saved) = send(&state, "PUT", "/api/settings", settings).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Saving what was shown keeps the secrets, and nothing shows them.
+        let (status, _) = send(&state, "PUT", "/api/settings", saved).await;
+        assert_eq!(status, StatusCode::OK);
+        for uri in ["/api/settings", "/api/export"] {
+            let (_, body) = send(&state, "GET", uri, serde_json::Value::Null).await;
+            let body = body.to_string();
+            assert!(body.contains(notify::REDACTED), "{uri}");
+            assert!(!body.contains("123:abc") && !body.contains("hunter2") && !body.contains("xoxb"), "{uri}");
+        }
+        let stored = state.db_state.read().await.db.load_settings().await.unwrap();
+        assert!(matches!(&stored.notifiers[0].channel, notify::Channel::Telegram { bot_token, .. } if bot_token == "123:abc"));
+        assert!(matches!(&stored.notifiers[2].channel, notify::Channel::Webhook { url } if url.ends_with("/B0/xoxb")));
+    }
+
+    /// A manga provider answering every search with one title.
+    /// Answers every search with one title, after `delay`.
+    struct OneTitle {
+        name: &'static str,
+        source: &'static str,
+        delay: std::time::Duration,
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl OneTitle {
+        fn new(name: &'static str, source: &'static str) -> Self {
+            OneTitle { name, source, delay: std::time::Duration::ZERO, calls: Arc::default() }
+        }
+
+        fn slow(self, delay: std::time::Duration) -> Self {
+            OneTitle { delay, ..self }
+        }
+    }
+
+    impl SearchProvider for OneTitle {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn source(&self) -> &'static str {
+            self.source
+        }
+
+        fn supported_types(&self) -> &[MediaSearchType] {
+            &[MediaSearchType::Manga]
+        }
+
+        fn search(&self, _query: &str, _media_type: MediaSearchType, _page: u32) -> Result<Vec<SearchResult>, SearchError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(self.delay);
+            Ok(vec![SearchResult {
+                title: format!("Berserk ({})", self.name),
+                media_type: MediaItemType::Readable(ReadableKind::Manga, Progress { current: 0, total: None }, ReadStatus::PlanToRead),
+                global_score: None,
+                poster_url: None,
+                external_refs: Vec::new(),
+                external_id: Some("1".into()),
+                source: self.source,
+                format_label: "Manga".into(),
+                alt_titles: Vec::new(),
+                creator: None,
+                content_rating: None,
+                titles: Default::default(),
+                year: None,
+            }])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_explore_merges_providers_unless_a_chain_is_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::for_tests(dir.path()).await;
+        let anilist = OneTitle::new("AniList", "anilist");
+        let anilist_calls = Arc::clone(&anilist.calls);
+        state.searchers = Arc::new(vec![Box::new(anilist), Box::new(OneTitle::new("MangaDex", "mangadex"))]);
+        let titles = |body: serde_json::Value| -> Vec<String> {
+            body.as_array().unwrap().iter().map(|r| r["title"].as_str().unwrap().to_string()).collect()
+        };
+
+        let (status, body) = send(&state, "GET", "/api/explore?q=berserk&type=manga", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(titles(body), ["Berserk (AniList)", "Berserk (MangaDex)"]);
+
+        let chains = serde_json::json!({"manga": ["MangaDex", "AniList"]});
+        let settings = Settings { provider_chains: serde_json::from_value(chains).unwrap(), ..Settings::default() };
+        state.db_state.read().await.db.save_settings(&settings).await.unwrap();
+        let (_, body) = send(&state, "GET", "/api/explore?q=berserk&type=manga", serde_json::Value::Null).await;
+        assert_eq!(titles(body), ["Berserk (MangaDex)"]);
+        // MangaDex answered, so AniList was not asked a second time.
+        assert_eq!(anilist_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_answers_from_whoever_is_quick() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = AppState::for_tests(dir.path()).await;
+        state.searchers = Arc::new(vec![
+            Box::new(OneTitle::new("AniList", "anilist").slow(std::time::Duration::from_secs(2))),
+            Box::new(OneTitle::new("MangaDex", "mangadex")),
+        ]);
+        let chains = serde_json::json!({"manga": ["AniList", "MangaDex"]});
+        let settings = Settings { provider_chains: serde_json::from_value(chains).unwrap(), ..Settings::default() };
+        state.db_state.read().await.db.save_settings(&settings).await.unwrap();
+
+        let started = std::time::Instant::now();
+        let (status, body) = send(&state, "GET", "/api/explore/suggest?q=berserk&type=manga", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        assert_eq!(body[0]["title"], "Berserk (MangaDex)");
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_are_sent_independently_and_remember_their_place() {
+        let received = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let receiver = Router::new()
+            .route("/quick", axum::routing::post({
+                let received = received.clone();
+                move |headers: axum::http::HeaderMap| async move {
+                    let event = headers.get(webhooks::EVENT_HEADER).unwrap().to_str().unwrap().to_string();
+                    received.lock().unwrap().push(event);
+                }
+            }))
+            .route("/slow", axum::routing::post(|| tokio::time::sleep(std::time::Duration::from_secs(1))));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let slow = state.db_state.read().await.db.add_webhook(&format!("{base}/slow"), "s").await.unwrap();
+        let quick = state.db_state.read().await.db.add_webhook(&format!("{base}/quick"), "s").await.unwrap();
+        add_series(&state, 1).await;
+        let latest = state.db_state.read().await.db.latest_history_id().await.unwrap();
+
+        let sending = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        deliver_webhooks(state.db_state.clone(), sending.clone()).await.unwrap();
+        let delivered = |id: i64| {
+            let state = state.clone();
+            async move {
+                let hooks = state.db_state.read().await.db.list_webhooks().await.unwrap();
+                hooks.iter().find(|h| h.id == id).unwrap().delivered_through
+            }
+        };
+        for _ in 0..50 {
+            if delivered(quick.id).await == latest {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        // The quick receiver has its event while the slow one still waits.
+        assert_eq!(*received.lock().unwrap(), ["item.created"]);
+        assert_eq!(delivered(quick.id).await, latest);
+        assert!(sending.lock().unwrap().contains(&slow.id));
+
+        // A second run sends the quick one nothing new.
+        deliver_webhooks(state.db_state.clone(), sending.clone()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // The slow one catches up on its own.
+        for _ in 0..100 {
+            if sending.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(delivered(slow.id).await, latest);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_refuses_to_overwrite_later_changes_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let id = add_series(&state, 3).await;
+        let job = {
+            let st = state.db_state.read().await;
+            let stored = st.db.get_item(id).await.unwrap().unwrap();
+            let merged = MediaItem { title: "Frieren (TV)".into(), ..stored.clone() };
+            let plan = MergePlan { writes: vec![(Some(stored), merged)], activity: Default::default(), reasons: HashMap::new() };
+            st.db.apply_merge(&plan).await.unwrap()
+        };
+        let (status, _) = send(&state, "DELETE", &format!("/api/items/{id}"), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let uri = format!("/api/jobs/{}/rollback", job.id);
+        let (status, body) = send(&state, "POST", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "changed_since_job");
+        assert_eq!(body["details"][0]["title"], "Frieren");
+        assert!(body["details"][0].get("changed_at").is_none());
+
+        let (status, body) = send(&state, "POST", &format!("{uri}?force=true"), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "rolled_back");
+        let restored = state.db_state.read().await.db.get_item(id).await.unwrap().unwrap();
+        assert_eq!(restored.title, "Frieren");
+    }
+}
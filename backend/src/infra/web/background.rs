@@ -0,0 +1,87 @@
+//! Runs the jobs of `core::scheduler` for one library, each in its own
+//! task, and keeps their status for `GET /api/admin/jobs`.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::task::AbortHandle;
+
+use super::SharedState;
+use crate::core::scheduler::{self, JobResult, JobSpec, JobStatus};
+
+/// Longest a job sleeps before looking at its interval again, so changed
+/// settings apply without a restart.
+const RECHECK: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub(super) struct Scheduler {
+    db_state: SharedState,
+    jobs: Arc<Mutex<Vec<JobStatus>>>,
+}
+
+impl Scheduler {
+    pub(super) fn new(db_state: SharedState) -> Self {
+        Scheduler { db_state, jobs: Arc::default() }
+    }
+
+    /// Starts calling `run` every interval of `spec`.  Runs of one job
+    /// never overlap; a run that takes longer than the interval delays
+    /// the next.  Work done is logged, and so are failures.
+    pub(super) fn register<F, Fut>(&self, spec: JobSpec, mut run: F) -> AbortHandle
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        self.jobs.lock().unwrap().push(JobStatus::new(spec, Utc::now()));
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let wait = scheduler.until_due(&spec).await;
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait.min(RECHECK)).await;
+                    continue;
+                }
+                scheduler.update(&spec, |status| status.start(Utc::now()));
+                let result = run().await;
+                match &result {
+                    Ok(Some(summary)) => println!("{summary}"),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Job {} failed: {e}", spec.name),
+                }
+                scheduler.update(&spec, |status| status.finish(result, Utc::now()));
+            }
+        })
+        .abort_handle()
+    }
+
+    /// Every registered job, in the order of `scheduler::JOBS`.
+    pub(super) fn statuses(&self) -> Vec<JobStatus> {
+        let mut jobs = self.jobs.lock().unwrap().clone();
+        jobs.sort_by_key(|job| scheduler::JOBS.iter().position(|spec| spec.name == job.spec.name));
+        jobs
+    }
+
+    /// How long until `spec` is due, with its interval brought up to date
+    /// from the settings.  Unreadable settings keep the interval as is.
+    async fn until_due(&self, spec: &JobSpec) -> Duration {
+        let overrides = self.db_state.read().await.db.load_settings().await.map(|s| s.job_interval_seconds);
+        let now = Utc::now();
+        self.update(spec, |status| {
+            if let Ok(overrides) = &overrides {
+                status.set_interval(scheduler::interval(spec, overrides));
+            }
+            if status.due(now) { Duration::ZERO } else { (status.next_run - now).to_std().unwrap_or(RECHECK) }
+        })
+    }
+
+    fn update<T>(&self, spec: &JobSpec, change: impl FnOnce(&mut JobStatus) -> T) -> T {
+        let mut jobs = self.jobs.lock().unwrap();
+        let status = jobs
+            .iter_mut()
+            .find(|status| status.spec.name == spec.name)
+            .expect("registered jobs stay registered");
+        change(status)
+    }
+}
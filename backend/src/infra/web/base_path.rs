@@ -0,0 +1,112 @@
+//! Serving kars below a path prefix (`BASE_PATH`), e.g. behind a reverse
+//! proxy at `https://home.example/kars/`.
+
+/// `BASE_PATH` as the router wants it: empty for the root, otherwise a
+/// leading slash and no trailing one (`"/kars"`).
+pub fn normalize(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    for segment in trimmed.split('/') {
+        let valid = !segment.is_empty()
+            && segment != "."
+            && segment != ".."
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~'));
+        if !valid {
+            return Err(format!("BASE_PATH is not a plain URL path: {raw}"));
+        }
+    }
+    Ok(format!("/{trimmed}"))
+}
+
+/// Makes an exported frontend file work below `base`.  The static export
+/// refers to its assets with root-relative URLs (`/_next/...`, and in
+/// HTML `src="/..."` and `href="/..."`), which are prefixed here.  HTML
+/// also gets `window.__KARS_BASE_PATH__`, which the frontend puts in
+/// front of its API calls.
+#[cfg(any(feature = "embed-frontend", test))]
+pub fn rewrite_asset(body: &str, base: &str, html: bool) -> String {
+    let mut out = prefix_after(body, base, "/_next/", &['"', '\'', '`', '(']);
+    if html {
+        for attribute in ["src=\"/", "href=\"/"] {
+            out = prefix_attribute(&out, base, attribute);
+        }
+        let script = format!("<script>window.__KARS_BASE_PATH__=\"{base}\"</script>");
+        out = match out.find("<head>") {
+            Some(i) => format!("{}{script}{}", &out[..i + 6], &out[i + 6..]),
+            None => script + &out,
+        };
+    }
+    out
+}
+
+/// Prefixes every `needle` that directly follows one of `after`.
+#[cfg(any(feature = "embed-frontend", test))]
+fn prefix_after(body: &str, base: &str, needle: &str, after: &[char]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(i) = rest.find(needle) {
+        out.push_str(&rest[..i]);
+        if rest[..i].ends_with(after) {
+            out.push_str(base);
+        }
+        out.push_str(needle);
+        rest = &rest[i + needle.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Prefixes the root-relative values of `attribute` (`src="/`), leaving
+/// protocol-relative (`//host`) and already prefixed ones alone.
+#[cfg(any(feature = "embed-frontend", test))]
+fn prefix_attribute(body: &str, base: &str, attribute: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(i) = rest.find(attribute) {
+        let value = &rest[i + attribute.len() - 1..];
+        out.push_str(&rest[..i + attribute.len() - 1]);
+        if !value.starts_with("//") && !value.starts_with(&format!("{base}/")) {
+            out.push_str(base);
+        }
+        rest = value;
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(""), Ok(String::new()));
+        assert_eq!(normalize("/"), Ok(String::new()));
+        assert_eq!(normalize("kars/"), Ok("/kars".into()));
+        assert_eq!(normalize(" /apps/kars "), Ok("/apps/kars".into()));
+        assert!(normalize("/a//b").is_err());
+        assert!(normalize("/../etc").is_err());
+        assert!(normalize("/ka rs").is_err());
+    }
+
+    #[test]
+    fn test_rewrite_asset_prefixes_root_urls() {
+        let html = r#"<html><head><link href="/_next/static/a.css"/></head><body><img src="/placeholder.svg"><a href="//cdn.example/x">x</a><script src="/_next/static/b.js"></script></body></html>"#;
+        let out = rewrite_asset(html, "/kars", true);
+        assert!(out.starts_with(r#"<html><head><script>window.__KARS_BASE_PATH__="/kars"</script><link href="/kars/_next/static/a.css"/>"#));
+        assert!(out.contains(r#"src="/kars/placeholder.svg""#));
+        assert!(out.contains(r#"href="//cdn.example/x""#));
+        assert!(out.contains(r#"src="/kars/_next/static/b.js""#));
+        assert!(!out.contains("/kars/kars/"));
+
+        let js = r#"r.p="/_next/";fetch(`/_next/x`);a="foo/_next/"; url(/_next/static/media/f.woff2)"#;
+        assert_eq!(
+            rewrite_asset(js, "/kars", false),
+            r#"r.p="/kars/_next/";fetch(`/kars/_next/x`);a="foo/_next/"; url(/kars/_next/static/media/f.woff2)"#
+        );
+    }
+}
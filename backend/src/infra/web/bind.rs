@@ -0,0 +1,178 @@
+//! Where the server listens, from `BIND_ADDRESS`: one or more IP
+//! addresses (with or without a port) and Unix socket paths, e.g.
+//! `127.0.0.1,100.101.102.103:8080,unix:/run/kars/kars.sock`.  Without it
+//! the server listens on every interface at `PORT`.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+
+use axum::Router;
+use tokio::task::JoinSet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindAddress {
+    Tcp(SocketAddr),
+    /// A Unix socket, for a reverse proxy on the same host.  Requests
+    /// through it have no client IP.
+    Unix(PathBuf),
+}
+
+impl fmt::Display for BindAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindAddress::Tcp(addr) => write!(f, "{addr}"),
+            BindAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl BindAddress {
+    /// Parses a comma-separated list; addresses without a port get `port`.
+    pub fn parse_list(value: &str, port: u16) -> Result<Vec<Self>, String> {
+        let binds = value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| Self::parse(entry, port))
+            .collect::<Result<Vec<_>, _>>()?;
+        if binds.is_empty() {
+            return Err("BIND_ADDRESS has no addresses".into());
+        }
+        Ok(binds)
+    }
+
+    fn parse(entry: &str, port: u16) -> Result<Self, String> {
+        if let Some(path) = entry.strip_prefix("unix:") {
+            if !cfg!(unix) {
+                return Err(format!("Unix sockets are not available on this platform: {entry}"));
+            }
+            if path.is_empty() {
+                return Err("A unix: entry in BIND_ADDRESS needs a path".into());
+            }
+            return Ok(BindAddress::Unix(path.into()));
+        }
+        if let Ok(addr) = entry.parse::<SocketAddr>() {
+            return Ok(BindAddress::Tcp(addr));
+        }
+        let ip = entry.strip_prefix('[').and_then(|e| e.strip_suffix(']')).unwrap_or(entry);
+        ip.parse::<IpAddr>()
+            .map(|ip| BindAddress::Tcp(SocketAddr::new(ip, port)))
+            .map_err(|_| format!("Not an IP address, IP:port or unix:path in BIND_ADDRESS: {entry}"))
+    }
+
+    /// The addresses from `BIND_ADDRESS`, or every interface at `port`.
+    pub fn from_env(port: u16) -> Result<Vec<Self>, String> {
+        match std::env::var("BIND_ADDRESS").ok().filter(|v| !v.trim().is_empty()) {
+            Some(value) => Self::parse_list(&value, port),
+            None => Ok(vec![BindAddress::Tcp(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port))]),
+        }
+    }
+
+    /// How to reach the server from this host: `http://localhost:3001`,
+    /// or the socket for a Unix one.
+    pub fn local_url(&self, scheme: &str) -> String {
+        match self {
+            BindAddress::Tcp(addr) if addr.ip().is_unspecified() || addr.ip().is_loopback() => {
+                format!("{scheme}://localhost:{}", addr.port())
+            }
+            BindAddress::Tcp(addr) => format!("{scheme}://{addr}"),
+            BindAddress::Unix(_) => self.to_string(),
+        }
+    }
+}
+
+/// Removes a socket left behind by an earlier run, which blocks the bind.
+/// Anything else at the path is kept and reported, since it is more
+/// likely a mistyped `BIND_ADDRESS` than a stale socket.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path).map_err(|e| e.to_string()),
+        Ok(_) => Err("the path exists and is not a socket".into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Serves `app` over plain HTTP on every address until the process ends.
+pub async fn serve(app: Router, binds: Vec<BindAddress>) {
+    let mut servers = JoinSet::new();
+    for bind in binds {
+        let app = app.clone();
+        match bind {
+            BindAddress::Tcp(addr) => {
+                let listener = tokio::net::TcpListener::bind(addr)
+                    .await
+                    .unwrap_or_else(|e| panic!("Failed to bind {addr}: {e}"));
+                servers.spawn(async move {
+                    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await
+                });
+            }
+            #[cfg(unix)]
+            BindAddress::Unix(path) => {
+                if let Err(e) = remove_stale_socket(&path) {
+                    panic!("Failed to bind {}: {e}", path.display());
+                }
+                let listener = tokio::net::UnixListener::bind(&path)
+                    .unwrap_or_else(|e| panic!("Failed to bind {}: {e}", path.display()));
+                // No ConnectInfo: socket clients have no address, so they
+                // share one rate limit bucket unless a key header is set.
+                servers.spawn(async move { axum::serve(listener, app.into_make_service()).await });
+            }
+            #[cfg(not(unix))]
+            BindAddress::Unix(path) => panic!("Unix sockets are not available here: {}", path.display()),
+        }
+    }
+    while let Some(result) = servers.join_next().await {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Server stopped: {e}"),
+            Err(e) => eprintln!("Server task failed: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_fills_in_the_port() {
+        let binds = BindAddress::parse_list("127.0.0.1, 100.64.0.7:8080,[::1],unix:/run/kars.sock", 3001).unwrap();
+        assert_eq!(
+            binds,
+            [
+                BindAddress::Tcp("127.0.0.1:3001".parse().unwrap()),
+                BindAddress::Tcp("100.64.0.7:8080".parse().unwrap()),
+                BindAddress::Tcp("[::1]:3001".parse().unwrap()),
+                BindAddress::Unix("/run/kars.sock".into()),
+            ]
+        );
+        assert_eq!(binds[1].local_url("http"), "http://100.64.0.7:8080");
+        assert_eq!(binds[2].local_url("https"), "https://localhost:3001");
+        assert_eq!(binds[3].to_string(), "unix:/run/kars.sock");
+
+        assert!(BindAddress::parse_list("localhost", 3001).is_err());
+        assert!(BindAddress::parse_list("unix:", 3001).is_err());
+        assert!(BindAddress::parse_list(" , ", 3001).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_stale_sockets_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("kars.sock");
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        assert!(remove_stale_socket(&socket).is_ok());
+        assert!(!socket.exists());
+        assert!(remove_stale_socket(&socket).is_ok());
+
+        let config = dir.path().join("kars.env");
+        std::fs::write(&config, "PORT=3001").unwrap();
+        assert!(remove_stale_socket(&config).is_err());
+        assert!(config.exists());
+    }
+}
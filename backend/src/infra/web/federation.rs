@@ -0,0 +1,76 @@
+//! Opt-in, read-only ActivityPub: an actor with a public outbox of
+//! completed items, discoverable through WebFinger.  Only mounted when
+//! `FEDERATION_URL` is set.  Nothing is delivered to followers; other
+//! servers read the outbox.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+use super::AppState;
+use crate::core::federation::{Federation, ACTIVITY_JSON};
+
+/// How many completions the outbox lists.
+const OUTBOX_LIMIT: u32 = 50;
+
+#[derive(Clone)]
+struct FederationState {
+    app: AppState,
+    federation: Arc<Federation>,
+}
+
+pub(super) fn router(app: AppState, federation: Federation) -> Router {
+    Router::new()
+        .route("/.well-known/webfinger", get(webfinger))
+        .route("/ap/actor", get(actor))
+        .route("/ap/outbox", get(outbox))
+        .route("/ap/inbox", get(inbox).post(inbox))
+        .with_state(FederationState { app, federation: Arc::new(federation) })
+}
+
+fn activity_json(body: Value) -> Response {
+    ([(header::CONTENT_TYPE, ACTIVITY_JSON)], Json(body)).into_response()
+}
+
+#[derive(Deserialize)]
+struct WebFingerQuery {
+    resource: Option<String>,
+}
+
+async fn webfinger(
+    State(state): State<FederationState>,
+    Query(params): Query<WebFingerQuery>,
+) -> Response {
+    let Some(resource) = params.resource else {
+        return (StatusCode::BAD_REQUEST, "Missing resource").into_response();
+    };
+    match state.federation.webfinger(&resource) {
+        Some(jrd) => ([(header::CONTENT_TYPE, "application/jrd+json")], Json(jrd)).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn actor(State(state): State<FederationState>) -> Response {
+    activity_json(state.federation.actor())
+}
+
+async fn outbox(State(state): State<FederationState>) -> Response {
+    let st = state.app.db_state.read().await;
+    match st.db.recent_completions(OUTBOX_LIMIT).await {
+        Ok(entries) => activity_json(state.federation.outbox(&entries)),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// The actor needs an inbox to be valid, but follows and replies are not
+/// accepted.
+async fn inbox() -> Response {
+    (StatusCode::NOT_IMPLEMENTED, "This actor does not accept activities").into_response()
+}
@@ -0,0 +1,207 @@
+//! `/api/graphql`: the REST operations as one GraphQL schema, so a client
+//! can fetch an item together with its history and episodes in a single
+//! request.  Every resolver calls the REST handler it mirrors, which keeps
+//! validation and side effects identical between the two APIs.
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    ComplexObject, Context, EmptySubscription, Error, ErrorExtensions, Object, Result, Schema, ID,
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+    routing::get,
+    Json, Router,
+};
+use serde::de::DeserializeOwned;
+
+use super::{AppState, CreateQuery, ExploreQuery, ListQuery, SearchQuery};
+use crate::core::api_types::{
+    ApiEpisode, ApiEpisodeUpdate, ApiExploreResult, ApiHistoryEntry, ApiMediaItem, ApiMergeRequest,
+    ApiProgressPoint, ApiStats,
+};
+
+type KarsSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// The GraphQL endpoint (POST) and a GraphiQL explorer (GET).
+/// `base_path` is the `BASE_PATH` prefix GraphiQL has to post to.
+pub(super) fn router(state: AppState, base_path: &str) -> Router {
+    let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(state)
+        .finish();
+    let endpoint = format!("{base_path}/api/graphql");
+    Router::new()
+        .route("/api/graphql", get(move || graphiql(endpoint.clone())).post(execute))
+        .with_state(schema)
+}
+
+async fn execute(
+    State(schema): State<KarsSchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request).await)
+}
+
+async fn graphiql(endpoint: String) -> Html<String> {
+    Html(GraphiQLSource::build().endpoint(&endpoint).finish())
+}
+
+/// Turns a REST response into a resolver result: the JSON body on
+/// success, `None` on 404 and an error carrying the status and the
+/// `ApiError` code otherwise.
+async fn rest<T: DeserializeOwned>(response: impl IntoResponse) -> Result<Option<T>> {
+    let response = response.into_response();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+        let message = error["message"].as_str().map_or_else(|| status.to_string(), str::to_string);
+        let code = error["code"].as_str().unwrap_or_default().to_string();
+        return Err(Error::new(message).extend_with(|_, e| {
+            e.set("status", status.as_u16());
+            e.set("code", code.as_str());
+        }));
+    }
+    // Queued in the outbox: there is no item to hand back yet.
+    if status == StatusCode::ACCEPTED {
+        return Err(Error::new("Turso is unreachable; the write is queued").extend_with(|_, e| {
+            e.set("status", status.as_u16());
+            e.set("code", "queued");
+        }));
+    }
+    if status == StatusCode::NO_CONTENT {
+        return Ok(Some(serde_json::from_str("null")?));
+    }
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+/// Like `rest`, but a missing item is an error.
+async fn rest_found<T: DeserializeOwned>(response: impl IntoResponse) -> Result<T> {
+    rest(response).await?.ok_or_else(|| Error::new("Not found"))
+}
+
+fn app(ctx: &Context<'_>) -> AppState {
+    ctx.data_unchecked::<AppState>().clone()
+}
+
+pub(super) struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Every item, or the items matching `search` (e.g.
+    /// `status:watching score>=8 frieren`).  Hidden items only with
+    /// `includeHidden`.  `ids` fetches just those items, in that order.
+    async fn items(
+        &self,
+        ctx: &Context<'_>,
+        search: Option<String>,
+        #[graphql(default)] include_hidden: bool,
+        ids: Option<Vec<ID>>,
+    ) -> Result<Vec<ApiMediaItem>> {
+        let state = State(app(ctx));
+        match search {
+            Some(q) => {
+                let params = SearchQuery { q: Some(q), include_hidden };
+                rest_found(super::search_items(state, Query(params)).await).await
+            }
+            None => {
+                let ids = ids.map(|ids| ids.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(","));
+                rest_found(super::list_items(state, Query(ListQuery { include_hidden, ids, fields: None })).await).await
+            }
+        }
+    }
+
+    async fn item(&self, ctx: &Context<'_>, id: ID) -> Result<Option<ApiMediaItem>> {
+        rest(super::get_item(State(app(ctx)), Path(id.0)).await).await
+    }
+
+    async fn stats(&self, ctx: &Context<'_>) -> Result<ApiStats> {
+        rest_found(super::get_stats(State(app(ctx))).await).await
+    }
+
+    /// Provider search and charts, as `GET /api/explore`.
+    #[allow(clippy::too_many_arguments)]
+    async fn explore(
+        &self,
+        ctx: &Context<'_>,
+        query: Option<String>,
+        #[graphql(name = "type")] media_type: Option<String>,
+        mode: Option<String>,
+        year: Option<i32>,
+        season: Option<String>,
+        source: Option<String>,
+        page: Option<u32>,
+    ) -> Result<Vec<ApiExploreResult>> {
+        let params = ExploreQuery { q: query, media_type, mode, year, season, source, page };
+        rest_found(super::explore_items(State(app(ctx)), Query(params)).await).await
+    }
+}
+
+#[ComplexObject]
+impl ApiMediaItem {
+    /// Audit log of the item, newest first.
+    async fn history(&self, ctx: &Context<'_>) -> Result<Vec<ApiHistoryEntry>> {
+        rest_found(super::item_history(State(app(ctx)), Path(self.id.clone())).await).await
+    }
+
+    /// Progress updates with their times, oldest first.
+    async fn progress_history(&self, ctx: &Context<'_>) -> Result<Vec<ApiProgressPoint>> {
+        rest_found(super::item_progress_history(State(app(ctx)), Path(self.id.clone())).await).await
+    }
+
+    /// Per-episode watch state; empty for anything but series.
+    async fn episodes(&self, ctx: &Context<'_>) -> Result<Vec<ApiEpisode>> {
+        rest_found(super::list_episodes(State(app(ctx)), Path(self.id.clone())).await).await
+    }
+}
+
+pub(super) struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Fails with status 409 when the item is already in the library,
+    /// unless `upsert` overwrites it.
+    async fn create_item(
+        &self,
+        ctx: &Context<'_>,
+        item: ApiMediaItem,
+        #[graphql(default)] upsert: bool,
+    ) -> Result<ApiMediaItem> {
+        rest_found(super::create_item(State(app(ctx)), Query(CreateQuery { upsert }), Json(item)).await).await
+    }
+
+    async fn update_item(&self, ctx: &Context<'_>, id: ID, item: ApiMediaItem) -> Result<ApiMediaItem> {
+        rest_found(super::update_item(State(app(ctx)), Path(id.0), Json(item)).await).await
+    }
+
+    /// False when there was no such item.
+    async fn delete_item(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let deleted: Option<()> = rest(super::delete_item(State(app(ctx)), Path(id.0)).await).await?;
+        Ok(deleted.is_some())
+    }
+
+    async fn rewatch_item(&self, ctx: &Context<'_>, id: ID) -> Result<ApiMediaItem> {
+        rest_found(super::rewatch_item(State(app(ctx)), Path(id.0)).await).await
+    }
+
+    /// Folds `duplicate_id` into `id` and deletes the duplicate.
+    async fn merge_items(&self, ctx: &Context<'_>, id: ID, duplicate_id: ID) -> Result<ApiMediaItem> {
+        let body = ApiMergeRequest { duplicate_id: duplicate_id.0 };
+        rest_found(super::merge_item(State(app(ctx)), Path(id.0), Json(body)).await).await
+    }
+
+    async fn mark_episodes(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        episodes: Vec<u32>,
+        #[graphql(default = true)] watched: bool,
+    ) -> Result<ApiMediaItem> {
+        let body = ApiEpisodeUpdate { episodes, watched };
+        rest_found(super::mark_episodes(State(app(ctx)), Path(id.0), Json(body)).await).await
+    }
+}
@@ -0,0 +1,431 @@
+//! Named profiles ("me", "partner"): separate libraries in one instance.
+//! Each profile is its own SQLite file in `PROFILES_DIR`, served with the
+//! full API and frontend below `/p/{name}/`, or at the usual paths when
+//! requests carry an `X-Kars-Profile` header.  The instance's own database
+//! is the `default` profile.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
+use tower::ServiceExt;
+
+use super::{library_router, spawn_background, ApiError, AppState, Scheduler, WebState};
+use crate::core::api_types::{on_scale, ApiNewProfile, ApiProfile, ApiScoreComparison, ApiSharedScore};
+use crate::core::models::MediaItem;
+use crate::core::settings::Settings;
+use crate::core::profiles::{self, DEFAULT_PROFILE};
+use crate::infra::database::Database;
+
+/// Header selecting the profile of a request made at the usual paths.
+pub const PROFILE_HEADER: &str = "x-kars-profile";
+
+/// Where profile databases live, from `PROFILES_DIR`.
+pub(super) fn dir_from_env() -> PathBuf {
+    std::env::var("PROFILES_DIR")
+        .unwrap_or_else(|_| "data/profiles".into())
+        .into()
+}
+
+struct Profile {
+    router: Router,
+    state: AppState,
+    tasks: Vec<AbortHandle>,
+}
+
+#[derive(Clone)]
+pub(super) struct Profiles {
+    /// The default profile; the others share its providers.
+    default: AppState,
+    dir: Arc<PathBuf>,
+    base_path: Arc<String>,
+    open: Arc<RwLock<BTreeMap<String, Profile>>>,
+}
+
+impl Profiles {
+    /// Opens every profile database found in `dir`.
+    pub(super) async fn open(default: AppState, dir: PathBuf, base_path: &str) -> Self {
+        let profiles = Profiles {
+            default,
+            dir: Arc::new(dir),
+            base_path: Arc::new(base_path.to_string()),
+            open: Arc::default(),
+        };
+        let names: Vec<String> = match std::fs::read_dir(profiles.dir.as_ref()) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok()?.file_name().to_str()?.strip_suffix(".db").map(str::to_string))
+                .filter(|name| profiles::validate_name(name).is_ok() && name != DEFAULT_PROFILE)
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        for name in names {
+            match profiles.load(&name).await {
+                Ok(profile) => {
+                    println!("Profile {name}: {}/p/{name}/", profiles.base_path);
+                    profiles.open.write().await.insert(name, profile);
+                }
+                Err(e) => eprintln!("Failed to open profile {name}: {e}"),
+            }
+        }
+        profiles
+    }
+
+    /// Every profile's state by name, the default first.
+    async fn states(&self) -> Vec<(String, AppState)> {
+        let mut states = vec![(DEFAULT_PROFILE.to_string(), self.default.clone())];
+        states.extend(
+            self.open
+                .read()
+                .await
+                .iter()
+                .map(|(name, profile)| (name.clone(), profile.state.clone())),
+        );
+        states
+    }
+
+    fn db_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.db"))
+    }
+
+    /// Opens (creating if needed) a profile's database and starts serving it.
+    async fn load(&self, name: &str) -> Result<Profile, String> {
+        let db = Database::local(&self.db_path(name).to_string_lossy())
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = db.fail_interrupted_jobs().await {
+            eprintln!("Failed to reset interrupted jobs of profile {name}: {e}");
+        }
+        let db_state = Arc::new(RwLock::new(WebState { db, outbox: None }));
+        let state = AppState {
+            scheduler: Scheduler::new(db_state.clone()),
+            db_state,
+            searchers: self.default.searchers.clone(),
+            unavailable: self.default.unavailable.clone(),
+            backup_dir: Arc::new(self.default.backup_dir.join("profiles").join(name)),
+            poster_dir: Arc::new(self.default.poster_dir.join("profiles").join(name)),
+            backup_target: Arc::new(self.default.backup_target.as_ref().as_ref().map(|t| t.for_profile(name))),
+            // Usage reports only ever describe the default profile.
+            telemetry_endpoint: Arc::new(None),
+            trakt: self.default.trakt.clone(),
+            // Kavita and Komga are the default profile's accounts; syncing
+            // them into another library would copy one person's reading.
+            reading_servers: Arc::default(),
+            media_managers: self.default.media_managers.clone(),
+            suggestions: self.default.suggestions.clone(),
+        };
+        Ok(Profile {
+            router: library_router(state.clone(), &format!("{}/p/{name}", self.base_path)),
+            tasks: spawn_background(&state).await,
+            state,
+        })
+    }
+}
+
+/// Hands requests for a profile other than the default to that profile's
+/// router: `/p/{name}/...` with the prefix removed, or any other path
+/// with the profile header.  Profile management stays on the default.
+/// Wraps the whole router, so a rewritten path is routed afresh.
+pub(super) async fn select(State(profiles): State<Profiles>, mut req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    let name = if let Some(after) = path.strip_prefix("/p/") {
+        let (name, rest) = after.split_at(after.find('/').unwrap_or(after.len()));
+        let rest = if rest.is_empty() { "/" } else { rest };
+        let uri = match req.uri().query() {
+            Some(query) => format!("{rest}?{query}"),
+            None => rest.to_string(),
+        };
+        let name = name.to_string();
+        match uri.parse::<Uri>() {
+            Ok(uri) => *req.uri_mut() = uri,
+            Err(e) => return ApiError::new(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        }
+        name
+    } else if path.starts_with("/api/profiles") {
+        return next.run(req).await;
+    } else {
+        match req.headers().get(PROFILE_HEADER).map(|v| v.to_str()) {
+            None => return next.run(req).await,
+            Some(Ok(name)) => name.to_string(),
+            Some(Err(e)) => return ApiError::new(StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        }
+    };
+
+    if name == DEFAULT_PROFILE {
+        return next.run(req).await;
+    }
+    let router = profiles.open.read().await.get(&name).map(|p| p.router.clone());
+    match router {
+        Some(router) => match router.oneshot(req).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        },
+        None => ApiError::new(StatusCode::NOT_FOUND, format!("No profile named {name}")).into_response(),
+    }
+}
+
+/// `/api/profiles`, served by the default profile only.
+pub(super) fn router(profiles: Profiles) -> Router {
+    Router::new()
+        .route("/api/profiles", get(list_profiles).post(create_profile))
+        .route("/api/profiles/{name}", delete(delete_profile))
+        .route("/api/profiles/{name}/scores", get(compare_scores))
+        .with_state(profiles)
+        .layer(axum::middleware::from_fn(super::error::json_errors))
+}
+
+async fn profile_summary(name: &str, state: &AppState) -> Result<ApiProfile, String> {
+    let st = state.db_state.read().await;
+    let stats = st.db.stats().await.map_err(|e| e.to_string())?;
+    Ok(ApiProfile { name: name.to_string(), stats })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::reading_sync::{ReadingServer, ServerSeries};
+    use crate::core::scheduler;
+    use crate::core::search::SearchError;
+
+    struct Kavita;
+
+    impl ReadingServer for Kavita {
+        fn name(&self) -> &'static str {
+            "Kavita"
+        }
+
+        fn progress(&self) -> Result<Vec<ServerSeries>, SearchError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_the_default_profile_syncs_reading_servers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut default = AppState::for_tests(dir.path()).await;
+        default.reading_servers = Arc::new(vec![Box::new(Kavita)]);
+        let syncs = |state: &AppState| {
+            state.scheduler.statuses().iter().any(|job| job.spec.name == scheduler::READING_SYNC.name)
+        };
+
+        let default_tasks = spawn_background(&default).await;
+        assert!(syncs(&default));
+
+        let profiles = Profiles::open(default, dir.path().join("profiles"), "").await;
+        let partner = profiles.load("partner").await.unwrap();
+        assert!(partner.state.reading_servers.is_empty());
+        assert!(!syncs(&partner.state));
+
+        for task in default_tasks.iter().chain(&partner.tasks) {
+            task.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scores_are_only_compared_between_sharing_profiles() {
+        use crate::core::models::{MediaItemType, WatchStatus};
+
+        let dir = tempfile::tempdir().unwrap();
+        let profiles = Profiles::open(AppState::for_tests(dir.path()).await, dir.path().join("profiles"), "").await;
+        for (name, score, share) in [("default", 90, true), ("partner", 70, true), ("kid", 10, false)] {
+            let state = if name == DEFAULT_PROFILE {
+                profiles.default.clone()
+            } else {
+                let profile = profiles.load(name).await.unwrap();
+                let state = profile.state.clone();
+                profiles.open.write().await.insert(name.to_string(), profile);
+                state
+            };
+            let st = state.db_state.read().await;
+            let mut item = MediaItem::new("Heat".into(), MediaItemType::Movie(WatchStatus::Completed));
+            item.score = Some(score);
+            st.db.upsert_item(&mut item).await.unwrap();
+            st.db.save_settings(&Settings { share_scores: share, ..Settings::default() }).await.unwrap();
+        }
+        let compare = |name: &str| compare_scores(State(profiles.clone()), Path(name.to_string()));
+
+        let response = compare("default").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["compared_with"], serde_json::json!(["partner"]));
+        assert_eq!(body["instance_mean"], 7.0);
+        assert_eq!(body["titles"][0]["average"], 7.0);
+        assert_eq!(body["titles"][0]["difference"], 2.0);
+        assert_eq!(body["titles"][0]["raters"], 1);
+
+        assert_eq!(compare("kid").await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(compare("nobody").await.status(), StatusCode::NOT_FOUND);
+        for profile in profiles.open.read().await.values() {
+            for task in &profile.tasks {
+                task.abort();
+            }
+        }
+    }
+}
+
+// ── GET /api/profiles ────────────────────────────────────────
+
+/// Every profile with its totals, the default first.
+#[utoipa::path(
+    get,
+    path = "/api/profiles",
+    tag = "profiles",
+    responses((status = 200, body = Vec<ApiProfile>))
+)]
+pub(super) async fn list_profiles(State(profiles): State<Profiles>) -> Response {
+    let states = profiles.states().await;
+    let mut list = Vec::with_capacity(states.len());
+    for (name, state) in &states {
+        match profile_summary(name, state).await {
+            Ok(profile) => list.push(profile),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+        }
+    }
+    Json(list).into_response()
+}
+
+// ── POST /api/profiles ───────────────────────────────────────
+
+/// Creates an empty profile, served right away below `/p/{name}/`.
+#[utoipa::path(
+    post,
+    path = "/api/profiles",
+    tag = "profiles",
+    request_body = ApiNewProfile,
+    responses(
+        (status = 201, body = ApiProfile),
+        (status = 400, description = "Invalid name", body = ApiError),
+        (status = 409, description = "A profile with that name exists", body = ApiError),
+    )
+)]
+pub(super) async fn create_profile(State(profiles): State<Profiles>, Json(body): Json<ApiNewProfile>) -> Response {
+    let name = body.name.trim();
+    if let Err(e) = profiles::validate_name(name) {
+        return ApiError::new(StatusCode::BAD_REQUEST, e).into_response();
+    }
+    let mut open = profiles.open.write().await;
+    if name == DEFAULT_PROFILE || open.contains_key(name) {
+        return ApiError::new(StatusCode::CONFLICT, format!("Profile {name} already exists")).into_response();
+    }
+
+    let profile = match profiles.load(name).await {
+        Ok(profile) => profile,
+        Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    };
+    let state = profile.state.clone();
+    open.insert(name.to_string(), profile);
+    drop(open);
+
+    match profile_summary(name, &state).await {
+        Ok(profile) => (StatusCode::CREATED, Json(profile)).into_response(),
+        Err(e) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+    }
+}
+
+// ── DELETE /api/profiles/:name ───────────────────────────────
+
+/// Deletes a profile and its database.  Its backups are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/profiles/{name}",
+    tag = "profiles",
+    params(("name" = String, Path, description = "Profile name")),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 400, description = "The default profile cannot be deleted", body = ApiError),
+        (status = 404, description = "No such profile", body = ApiError),
+    )
+)]
+pub(super) async fn delete_profile(State(profiles): State<Profiles>, Path(name): Path<String>) -> Response {
+    if name == DEFAULT_PROFILE {
+        return ApiError::new(StatusCode::BAD_REQUEST, "The default profile cannot be deleted").into_response();
+    }
+    let Some(profile) = profiles.open.write().await.remove(&name) else {
+        return ApiError::not_found("Profile").into_response();
+    };
+    for task in &profile.tasks {
+        task.abort();
+    }
+    drop(profile);
+
+    let path = profiles.db_path(&name);
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = path.clone().into_os_string();
+        file.push(suffix);
+        match std::fs::remove_file(&file) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+// ── GET /api/profiles/:name/scores ───────────────────────────
+
+/// How a profile's scores compare with those of the other profiles, per
+/// title they share.  Both sides opt in with `share_scores`; profiles
+/// without it are left out of the averages.
+#[utoipa::path(
+    get,
+    path = "/api/profiles/{name}/scores",
+    tag = "profiles",
+    params(("name" = String, Path, description = "Profile name")),
+    responses(
+        (status = 200, body = ApiScoreComparison),
+        (status = 403, description = "The profile does not share its scores", body = ApiError),
+        (status = 404, description = "No such profile", body = ApiError),
+    )
+)]
+pub(super) async fn compare_scores(State(profiles): State<Profiles>, Path(name): Path<String>) -> Response {
+    let mut libraries: Vec<(String, Settings, Vec<MediaItem>)> = Vec::new();
+    for (profile, state) in profiles.states().await {
+        let st = state.db_state.read().await;
+        let loaded = match st.db.load_settings().await {
+            Ok(settings) => st.db.load_all().await.map(|items| (settings, items)),
+            Err(e) => Err(e),
+        };
+        match loaded {
+            Ok((settings, items)) => libraries.push((profile, settings, items)),
+            Err(e) => return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+
+    let Some(idx) = libraries.iter().position(|(profile, _, _)| *profile == name) else {
+        return ApiError::not_found("Profile").into_response();
+    };
+    let (_, settings, mine) = libraries.remove(idx);
+    if !settings.share_scores {
+        return ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("Profile {name} does not share its scores; turn on share_scores to compare them"),
+        )
+        .into_response();
+    }
+    libraries.retain(|(_, settings, _)| settings.share_scores);
+
+    let scale = settings.score_scale;
+    let mean = |items: &[MediaItem]| profiles::mean_score(items).map(|m| on_scale(m, scale));
+    let others: Vec<Vec<MediaItem>> = libraries.iter().map(|(_, _, items)| items.clone()).collect();
+    let pooled: Vec<MediaItem> = others.concat();
+    Json(ApiScoreComparison {
+        compared_with: libraries.into_iter().map(|(profile, _, _)| profile).collect(),
+        mean_score: mean(&mine),
+        instance_mean: mean(&pooled),
+        titles: profiles::shared_scores(&mine, &others)
+            .iter()
+            .map(|shared| ApiSharedScore::new(shared, scale))
+            .collect(),
+    })
+    .into_response()
+}
@@ -0,0 +1,126 @@
+//! Rate limiting of the API for public or shared deployments, configured
+//! with `RATE_LIMIT_PER_MINUTE` and friends.  Clients are told where they
+//! stand in `X-RateLimit-*` headers and get a 429 with `Retry-After` once
+//! their bucket is empty (see `core::rate_limit`).
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::ApiError;
+use crate::core::rate_limit::{Decision, RateLimit, RateLimiter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    pub limit: RateLimit,
+    /// Header naming the client instead of its IP, from
+    /// `RATE_LIMIT_KEY_HEADER`.
+    pub key_header: Option<HeaderName>,
+}
+
+impl RateLimitConfig {
+    /// None when `RATE_LIMIT_PER_MINUTE` is unset or 0.  A burst or key
+    /// header that cannot be used is an error rather than a silent
+    /// fallback to no limit.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+        let number = |name: &str, value: String| {
+            value.trim().parse::<u32>().map_err(|_| format!("{name} is not a number: {value}"))
+        };
+        let per_minute = match var("RATE_LIMIT_PER_MINUTE") {
+            Some(value) => number("RATE_LIMIT_PER_MINUTE", value)?,
+            None => 0,
+        };
+        let burst = var("RATE_LIMIT_BURST").map(|v| number("RATE_LIMIT_BURST", v)).transpose()?;
+        let key_header = var("RATE_LIMIT_KEY_HEADER")
+            .map(|name| {
+                HeaderName::try_from(name.trim()).map_err(|_| format!("RATE_LIMIT_KEY_HEADER is not a header name: {name}"))
+            })
+            .transpose()?;
+        if per_minute == 0 {
+            return match (burst, key_header) {
+                (None, None) => Ok(None),
+                _ => Err("RATE_LIMIT_BURST and RATE_LIMIT_KEY_HEADER need RATE_LIMIT_PER_MINUTE".into()),
+            };
+        }
+        let burst = burst.unwrap_or(per_minute);
+        if burst == 0 {
+            return Err("RATE_LIMIT_BURST must be at least 1".into());
+        }
+        Ok(Some(RateLimitConfig { limit: RateLimit { per_minute, burst }, key_header }))
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct Limiter {
+    limiter: Arc<Mutex<RateLimiter>>,
+    key_header: Option<HeaderName>,
+}
+
+impl Limiter {
+    pub(super) fn new(config: RateLimitConfig) -> Self {
+        Limiter { limiter: Arc::new(Mutex::new(RateLimiter::new(config.limit))), key_header: config.key_header }
+    }
+
+    /// The key header's value (the first entry of a list such as
+    /// `X-Forwarded-For`), or the peer's IP.
+    fn client(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> String {
+        let from_header = self
+            .key_header
+            .as_ref()
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        match (from_header, peer) {
+            (Some(key), _) => key.to_string(),
+            (None, Some(peer)) => peer.ip().to_string(),
+            (None, None) => "unknown".into(),
+        }
+    }
+}
+
+/// Only the API and federation are limited; the frontend's files are not.
+fn is_limited(path: &str) -> bool {
+    path.split('/').any(|segment| segment == "api" || segment == "ap")
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &Decision) {
+    let values = [
+        ("x-ratelimit-limit", u64::from(decision.limit)),
+        ("x-ratelimit-remaining", u64::from(decision.remaining)),
+        ("x-ratelimit-reset", decision.reset_secs),
+    ];
+    for (name, value) in values {
+        headers.insert(name, HeaderValue::from(value));
+    }
+}
+
+pub(super) async fn limit(State(limiter): State<Limiter>, request: Request, next: Next) -> Response {
+    if !is_limited(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let client = limiter.client(request.headers(), peer);
+    let decision = limiter.limiter.lock().unwrap_or_else(|e| e.into_inner()).check(&client, Instant::now());
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        let seconds = decision.retry_after_secs;
+        let mut response = ApiError::new(StatusCode::TOO_MANY_REQUESTS, format!("Too many requests; retry in {seconds} s"))
+            .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        response
+    };
+    set_headers(response.headers_mut(), &decision);
+    response
+}
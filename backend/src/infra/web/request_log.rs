@@ -0,0 +1,124 @@
+//! Request ids and the access log.  Every response carries an
+//! `X-Request-Id`, taken from the request when a proxy or the frontend
+//! sent a usable one and generated otherwise, and every request is logged
+//! with it, so a bug report quoting the id leads to the matching log line.
+//! Handlers log their errors with `log_error!`, which starts the line with
+//! the same id.
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest request id taken over from a request.
+const MAX_ID_LEN: usize = 64;
+
+tokio::task_local! {
+    /// Id of the request the current task is serving.
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being served, or None outside a request (background
+/// jobs, and tasks a handler spawned).
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(String::clone).ok()
+}
+
+/// `eprintln!` that starts the line with the id of the request being
+/// served, when there is one.
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        match $crate::infra::web::request_log::current() {
+            Some(id) => eprintln!("[{id}] {}", format_args!($($arg)*)),
+            None => eprintln!($($arg)*),
+        }
+    };
+}
+pub(crate) use log_error;
+
+/// Whether the access log is written, from `ACCESS_LOG` (on unless `off`,
+/// `false` or `0`).
+pub fn enabled_from_env() -> bool {
+    std::env::var("ACCESS_LOG")
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "off" | "false" | "0"))
+        .unwrap_or(true)
+}
+
+/// The id a client sent, if it is short and plain enough to log as is.
+fn client_id(request: &Request) -> Option<String> {
+    let id = request.headers().get(&REQUEST_ID_HEADER)?.to_str().ok()?;
+    let plain = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    (!id.is_empty() && id.len() <= MAX_ID_LEN && id.chars().all(plain)).then(|| id.to_string())
+}
+
+/// Tags the request with its id and echoes the id in the response.  With
+/// `log` set, prints one line per request: time, id, client IP, method,
+/// path (without the query, which may hold a token), status and latency.
+pub async fn tag(log: bool, mut request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let id = client_id(&request).unwrap_or_else(|| Uuid::new_v4().to_string());
+    let value = HeaderValue::from_str(&id).expect("request ids are plain ASCII");
+    request.headers_mut().insert(REQUEST_ID_HEADER, value.clone());
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or_else(|| "-".to_string(), |info| info.0.ip().to_string());
+
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(request)).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    if log {
+        println!(
+            "{} {id} {ip} {method} {path} {} {}ms",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            response.status().as_u16(),
+            started.elapsed().as_millis(),
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_request_ids_are_echoed_or_generated() {
+        let app = Router::new()
+            .route("/api/stats", get(|| async { current().unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(|request, next| tag(false, request, next)));
+        let get = |id: Option<&str>| {
+            let request = Request::get("/api/stats");
+            let request = match id {
+                Some(id) => request.header(&REQUEST_ID_HEADER, id),
+                None => request,
+            };
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let id = |response: &Response| response.headers()[&REQUEST_ID_HEADER].to_str().unwrap().to_string();
+
+        let response = get(Some("web-7f3a.2")).await.unwrap();
+        assert_eq!(id(&response), "web-7f3a.2");
+        // Handlers see the id they are logging under.
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, "web-7f3a.2");
+        assert_eq!(current(), None);
+
+        let generated = id(&get(None).await.unwrap());
+        assert!(Uuid::parse_str(&generated).is_ok());
+        let replaced = id(&get(Some("two words")).await.unwrap());
+        assert!(Uuid::parse_str(&replaced).is_ok());
+        assert_ne!(generated, replaced);
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::core::webhooks::{self, Webhook, WebhookEvent, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER};
+
+/// Waits before the second and third attempt; the first is immediate.
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_secs(2), Duration::from_secs(10)];
+
+/// POSTs a signed event body, retrying on network errors and on 5xx and
+/// 429 answers.  Other 4xx answers are final.  Blocking; run it off the
+/// async runtime.
+pub fn deliver(hook: &Webhook, event: WebhookEvent, delivery: &str, body: &[u8]) -> Result<(), String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let signature = webhooks::sign(&hook.secret, body);
+
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        let result = client
+            .post(&hook.url)
+            .header("content-type", "application/json")
+            .header(EVENT_HEADER, event.as_str())
+            .header(DELIVERY_HEADER, delivery)
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.to_vec())
+            .send();
+        let error = match result {
+            Ok(resp) if resp.status().is_success() => return Ok(()),
+            Ok(resp) => {
+                let status = resp.status();
+                let error = format!("{} answered {status}", hook.url);
+                if status.is_client_error() && status.as_u16() != 429 {
+                    return Err(error);
+                }
+                error
+            }
+            Err(e) => e.to_string(),
+        };
+        match delays.next() {
+            Some(delay) => std::thread::sleep(*delay),
+            None => return Err(error),
+        }
+    }
+}
@@ -1,30 +1,380 @@
 mod core;
 mod infra;
 
+use std::path::{Path, PathBuf};
+
+use clap::builder::PossibleValuesParser;
+use clap::{Parser, Subcommand};
 use infra::database::{Database, SqlStorage};
 use infra::terminal::TerminalInput;
 use infra::anilist::AniListClient;
 use infra::tmdb::TmdbClient;
 use infra::openlibrary::OpenLibraryClient;
+use infra::google_books::GoogleBooksClient;
+use infra::audible::AudibleClient;
 use infra::mangadex::MangaDexClient;
-use crate::core::search::SearchProvider;
+use infra::tls::TlsConfig;
+use infra::web::bind::BindAddress;
+use infra::web::rate_limit::RateLimitConfig;
+use crate::core::api_types::STATUSES;
+use crate::core::archive::{Archive, ARCHIVE_VERSION};
+use crate::core::{exchange, import};
+use crate::core::app::{OutputFormat, ProgressChange};
+use crate::core::federation::Federation;
+use crate::core::search::{MediaSearchType, SearchProvider};
+
+/// Personal media archive.  Without a command the web server is started.
+#[derive(Parser)]
+#[command(name = "kars", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Same as `kars interactive`; kept for older scripts.
+    #[arg(long, hide = true, conflicts_with = "web")]
+    cli: bool,
+    /// Same as `kars serve`; kept for older scripts.
+    #[arg(long, hide = true)]
+    web: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the REST API and web UI (the default).
+    Serve,
+    /// Classic menu-driven terminal UI.
+    Interactive,
+    /// Add a title, matched against the providers for its type.
+    Add {
+        title: String,
+        #[arg(long = "type", default_value = "anime", value_parser = parse_search_type)]
+        search_type: MediaSearchType,
+        /// Defaults to the type's status in `default_statuses`, or
+        /// plan_to_watch (plan_to_read for readables).
+        #[arg(long, value_parser = PossibleValuesParser::new(STATUSES.iter().copied()))]
+        status: Option<String>,
+    },
+    /// Set or move the progress of an item: `12`, `+1` or `-1`.
+    Progress {
+        title: String,
+        #[arg(allow_hyphen_values = true)]
+        change: ProgressChange,
+        /// Change the volumes of a readable instead of its chapters.
+        #[arg(long)]
+        volumes: bool,
+    },
+    /// Add one episode / chapter (or `count`) to an item's progress.
+    Next {
+        title: String,
+        #[arg(default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+        /// Add volumes of a readable instead of chapters.
+        #[arg(long)]
+        volumes: bool,
+    },
+    /// Mark an item as completed.
+    Complete { title: String },
+    /// Keep an item for stats but leave it out of lists and searches.
+    Hide { title: String },
+    /// Show a hidden item in lists and searches again.
+    Unhide { title: String },
+    /// List the archive.
+    List {
+        #[arg(long, value_parser = PossibleValuesParser::new(STATUSES.iter().copied()))]
+        status: Option<String>,
+        /// Also list hidden items.
+        #[arg(long)]
+        include_hidden: bool,
+        /// `text`, `json` (the API's format) or `tsv`.
+        #[arg(long, default_value = "text")]
+        format: OutputFormat,
+        /// Same as `--format json`.
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+    /// List the items matching a search, e.g.
+    /// `kars query "status:watching tag:fantasy score>=8 frieren"`.
+    Query {
+        #[arg(allow_hyphen_values = true)]
+        search: String,
+        /// Also list hidden items.
+        #[arg(long)]
+        include_hidden: bool,
+        /// `text`, `json` (the API's format) or `tsv`.
+        #[arg(long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Show one item.
+    Detail {
+        title: String,
+        /// `text`, `json` (the API's format) or `tsv`.
+        #[arg(long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Show, set or remove custom fields of an item.
+    Field {
+        title: String,
+        /// Field to show or set; without it every field is listed.
+        name: Option<String>,
+        /// New value of the field.
+        value: Option<String>,
+        /// Remove the field.
+        #[arg(long, requires = "name", conflicts_with = "value")]
+        unset: bool,
+    },
+    /// Library totals, as `GET /api/stats`.
+    Stats {
+        /// `text`, `json` or `tsv`.
+        #[arg(long, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Import a file as an import job, like `POST /api/import`: a JSON
+    /// archive or item list, CSV or a MyAnimeList export.  `kars import
+    /// txt <file>` adds a plain-text watchlist, one title per line.
+    Import {
+        /// The file, or its format (`json`, `csv`, `mal` or `txt`) and then
+        /// the file.  Without a format the extension decides.
+        #[arg(value_name = "[FORMAT] FILE")]
+        format_or_file: String,
+        file: Option<PathBuf>,
+        /// Type of the titles of a txt watchlist.
+        #[arg(long = "type", default_value = "anime", value_parser = parse_search_type)]
+        search_type: MediaSearchType,
+        /// Status of the titles of a txt watchlist; defaults as for `add`.
+        #[arg(long, value_parser = PossibleValuesParser::new(STATUSES.iter().copied()))]
+        status: Option<String>,
+        /// Only list what would be created, updated or skipped.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write the library to a file.
+    Export {
+        /// `json` (the archive of `GET /api/export`), `csv` or `mal`
+        /// (MyAnimeList XML, items with a MyAnimeList id only).
+        #[arg(long, default_value = "json", value_parser = ["json", "csv", "mal"])]
+        format: String,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Live terminal dashboard.
+    Top {
+        /// Seconds between refreshes.
+        #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
+}
+
+fn parse_search_type(s: &str) -> Result<MediaSearchType, String> {
+    MediaSearchType::parse(s)
+        .ok_or_else(|| "expected anime, manga, light_novel, movie, series, book or audiobook".into())
+}
 
 fn main() {
     // Load .env (silently ignore if missing — production uses real env vars)
     let _ = dotenvy::dotenv();
 
-    let args: Vec<String> = std::env::args().collect();
-    let cli_mode = args.iter().any(|a| a == "--cli");
+    let cli = Cli::parse();
+    let command = match cli.command {
+        None if cli.cli => Command::Interactive,
+        // `--web` and no command at all both serve.
+        None => Command::Serve,
+        Some(command) => command,
+    };
 
-    if cli_mode {
-        run_cli();
-    } else {
-        run_web();
+    let result = match command {
+        Command::Serve => {
+            run_web();
+            Ok(())
+        }
+        Command::Interactive => {
+            build_app().run();
+            Ok(())
+        }
+        Command::Add { title, search_type, status } => {
+            let mut app = build_app();
+            let status = status.unwrap_or_else(|| app.default_status(search_type));
+            app.add_title(&title, search_type, &status)
+        }
+        Command::Progress { title, change, volumes } => build_app().change_progress(&title, change, volumes),
+        Command::Next { title, count, volumes } => {
+            build_app().change_progress(&title, ProgressChange::By(count as i64), volumes)
+        }
+        Command::Complete { title } => build_app().complete_title(&title),
+        Command::Hide { title } => build_app().set_hidden(&title, true),
+        Command::Unhide { title } => build_app().set_hidden(&title, false),
+        Command::List { status, include_hidden, format, json } => {
+            let format = if json { OutputFormat::Json } else { format };
+            build_app().print_list(status.as_deref(), include_hidden, format)
+        }
+        Command::Query { search, include_hidden, format } => {
+            build_app().print_query(&search, include_hidden, format)
+        }
+        Command::Detail { title, format } => build_app().print_item(&title, format),
+        Command::Field { title, name, value, unset } => match (name, value) {
+            (Some(name), Some(value)) => build_app().set_custom_field(&title, &name, Some(&value)),
+            (Some(name), None) if unset => build_app().set_custom_field(&title, &name, None),
+            (name, _) => build_app().print_custom_fields(&title, name.as_deref()),
+        },
+        Command::Stats { format } => build_app().print_stats(format),
+        Command::Import { format_or_file, file, search_type, status, dry_run } => {
+            let (format, file) = match file {
+                Some(file) => (format_or_file, file),
+                None => {
+                    let file = PathBuf::from(format_or_file);
+                    (format_for(&file), file)
+                }
+            };
+            match format.as_str() {
+                "txt" if dry_run => Err("--dry-run is not available for txt watchlists".into()),
+                "txt" => run_import(&file, search_type, status),
+                "json" | "csv" | "mal" => run_import_file(&file, &format, dry_run),
+                _ => Err(format!("Unknown import format '{format}'; expected json, csv, mal or txt.")),
+            }
+        }
+        Command::Export { format, out } => run_export(&format, &out),
+        Command::Top { interval } => {
+            run_top(interval);
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// `kars import txt <file> [--type anime] [--status plan_to_watch]`
+///
+/// Imports a plain-text watchlist, one title per line.  Blank lines and
+/// lines starting with `#` are ignored.
+fn run_import(path: &Path, search_type: MediaSearchType, status: Option<String>) -> Result<(), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let titles: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect();
+
+    let mut app = build_app();
+    let status = status.unwrap_or_else(|| app.default_status(search_type));
+    app.import_titles(&titles, search_type, &status);
+    Ok(())
+}
+
+/// Format of an import file by its extension.
+fn format_for(path: &Path) -> String {
+    match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("xml") => "mal".into(),
+        Some(ext) => ext.into(),
+        None => String::new(),
     }
 }
 
-/// Classic terminal UI — kept as emergency / power-user access.
-fn run_cli() {
+/// `kars import [json|csv|mal] <file> [--dry-run]`
+///
+/// Merges the file's items into the library as one import job that can
+/// be rolled back, the way `POST /api/import` does.
+fn run_import_file(path: &Path, format: &str, dry_run: bool) -> Result<(), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create async runtime");
+    rt.block_on(async {
+        let db = open_database().await;
+        let archive = match format {
+            "json" => {
+                let body = serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {e}"))?;
+                import::parse_import(body)?
+            }
+            _ => {
+                let items = if format == "csv" {
+                    let scale = db.load_settings().await.map_err(|e| e.to_string())?.score_scale;
+                    exchange::read_csv(&content, scale)?
+                } else {
+                    exchange::read_mal(&content)?
+                };
+                Archive {
+                    version: ARCHIVE_VERSION,
+                    exported_at: chrono::Utc::now(),
+                    items,
+                    activity: Default::default(),
+                    settings: None,
+                }
+            }
+        };
+
+        let stored = db.load_all().await.map_err(|e| e.to_string())?;
+        let plan = import::plan_merge(stored, archive);
+        if dry_run {
+            let preview = import::preview_merge(&plan);
+            for item in &preview.items {
+                let fields = if item.fields.is_empty() { String::new() } else { format!(" ({})", item.fields.join(", ")) };
+                println!("{:<7} {} — {}{fields}", item.action, item.title, item.reason);
+            }
+            println!(
+                "\nDry run: {} to create, {} to update, {} to skip.",
+                preview.created, preview.updated, preview.skipped
+            );
+            return Ok(());
+        }
+        let job = db.apply_merge(&plan).await.map_err(|e| e.to_string())?;
+        if let Some(error) = &job.error {
+            return Err(format!("Import job {} failed: {error}", job.id));
+        }
+        let created = job.items.iter().filter(|i| i.created).count();
+        println!(
+            "Import job {}: {created} created, {} updated.  Undo it with POST /api/jobs/{}/rollback.",
+            job.id,
+            job.items.len() - created,
+            job.id
+        );
+        Ok(())
+    })
+}
+
+/// `kars export [--format json|csv|mal] --out <file>`
+fn run_export(format: &str, out: &Path) -> Result<(), String> {
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create async runtime");
+    let content = rt.block_on(async {
+        let db = open_database().await;
+        match format {
+            "json" => {
+                let archive = db.export_archive().await.map_err(|e| e.to_string())?;
+                serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())
+            }
+            "csv" => {
+                let scale = db.load_settings().await.map_err(|e| e.to_string())?.score_scale;
+                let items = db.load_all().await.map_err(|e| e.to_string())?;
+                Ok(exchange::write_csv(&items, scale))
+            }
+            _ => {
+                let items = db.load_all().await.map_err(|e| e.to_string())?;
+                let (xml, skipped) = exchange::write_mal(&items);
+                if skipped > 0 {
+                    eprintln!("Left out {skipped} items without a MyAnimeList id.");
+                }
+                Ok(xml)
+            }
+        }
+    })?;
+    std::fs::write(out, content).map_err(|e| format!("Failed to write {}: {e}", out.display()))?;
+    println!("Exported to {}", out.display());
+    Ok(())
+}
+
+/// `kars top [--interval <seconds>]`
+///
+/// Shows the dashboard (upcoming sessions, continue watching, unread
+/// notifications and jobs) in the terminal, refreshed every few seconds.
+fn run_top(interval: u64) {
+    let rt = tokio::runtime::Runtime::new().expect("Failed to create async runtime");
+    rt.block_on(async {
+        let db = open_database().await;
+        infra::top::run(db, std::time::Duration::from_secs(interval)).await;
+    });
+}
+
+fn build_app() -> core::app::App<SqlStorage, TerminalInput> {
     let db_mode = std::env::var("DATABASE_MODE").unwrap_or_else(|_| "local".into());
 
     let storage: SqlStorage = match db_mode.as_str() {
@@ -48,6 +398,8 @@ fn run_cli() {
         Box::new(AniListClient::new()),
         Box::new(MangaDexClient::new()),
         Box::new(OpenLibraryClient::new()),
+        Box::new(GoogleBooksClient::new()),
+        Box::new(AudibleClient::new()),
     ];
 
     if let Some(tmdb) = TmdbClient::from_env() {
@@ -56,15 +408,13 @@ fn run_cli() {
         eprintln!("Note: TMDB_API_KEY not set — movie/series search disabled.");
     }
 
-    let mut app = match core::app::App::new(storage, input, searchers) {
+    match core::app::App::new(storage, input, searchers) {
         Ok(app) => app,
         Err(e) => {
             eprintln!("Failed to initialize: {e}");
             std::process::exit(1);
         }
-    };
-
-    app.run();
+    }
 }
 
 /// Web server mode — default.  Serves the REST API (and embedded frontend
@@ -73,37 +423,86 @@ fn run_web() {
     // Build search providers BEFORE entering the async runtime.
     // reqwest::blocking::Client creates its own mini-runtime;
     // constructing/dropping it inside block_on causes a panic.
-    let searchers = infra::web::build_searchers();
+    let providers = infra::web::build_searchers();
 
     let rt = tokio::runtime::Runtime::new().expect("Failed to create async runtime");
 
     rt.block_on(async {
-        let db_mode = std::env::var("DATABASE_MODE").unwrap_or_else(|_| "local".into());
-
-        let db = match db_mode.as_str() {
-            "turso" => {
-                let url = std::env::var("TURSO_DATABASE_URL")
-                    .expect("TURSO_DATABASE_URL must be set when DATABASE_MODE=turso");
-                let token = std::env::var("TURSO_AUTH_TOKEN")
-                    .expect("TURSO_AUTH_TOKEN must be set when DATABASE_MODE=turso");
-                Database::turso(&url, &token)
-                    .await
-                    .expect("Failed to connect to Turso")
-            }
-            _ => {
-                let path = std::env::var("DATABASE_PATH")
-                    .unwrap_or_else(|_| "data/kars.db".into());
-                Database::local(&path)
-                    .await
-                    .expect("Failed to open local database")
-            }
-        };
+        let db = open_database().await;
 
         let port: u16 = std::env::var("PORT")
             .ok()
             .and_then(|p| p.parse().ok())
             .unwrap_or(3001);
 
-        infra::web::start_server(db, port, searchers).await;
+        let backup_dir = std::env::var("BACKUP_DIR")
+            .unwrap_or_else(|_| "data/backups".into());
+
+        let federation = std::env::var("FEDERATION_URL").ok().map(|url| {
+            let user = std::env::var("FEDERATION_USER").unwrap_or_else(|_| "kars".into());
+            Federation::new(&url, &user)
+        });
+
+        let tls = match TlsConfig::from_env() {
+            Ok(tls) => tls,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        let binds = match BindAddress::from_env(port) {
+            Ok(binds) if tls.is_some() && binds.iter().any(|b| matches!(b, BindAddress::Unix(_))) => {
+                eprintln!("HTTPS is only served on IP addresses; remove the unix: entries from BIND_ADDRESS");
+                std::process::exit(1);
+            }
+            Ok(binds) => binds,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        let base_path = match infra::web::base_path::normalize(&std::env::var("BASE_PATH").unwrap_or_default()) {
+            Ok(base_path) => base_path,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        let rate_limit = match RateLimitConfig::from_env() {
+            Ok(rate_limit) => rate_limit,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        };
+
+        infra::web::start_server(db, binds, providers, backup_dir.into(), federation, tls, rate_limit, base_path).await;
     });
 }
+
+/// Opens the database configured by `DATABASE_MODE` and friends.
+async fn open_database() -> Database {
+    let db_mode = std::env::var("DATABASE_MODE").unwrap_or_else(|_| "local".into());
+
+    match db_mode.as_str() {
+        "turso" => {
+            let url = std::env::var("TURSO_DATABASE_URL")
+                .expect("TURSO_DATABASE_URL must be set when DATABASE_MODE=turso");
+            let token = std::env::var("TURSO_AUTH_TOKEN")
+                .expect("TURSO_AUTH_TOKEN must be set when DATABASE_MODE=turso");
+            Database::turso(&url, &token)
+                .await
+                .expect("Failed to connect to Turso")
+        }
+        _ => {
+            let path = std::env::var("DATABASE_PATH")
+                .unwrap_or_else(|_| "data/kars.db".into());
+            Database::local(&path)
+                .await
+                .expect("Failed to open local database")
+        }
+    }
+}
@@ -2,6 +2,7 @@
 
 import * as React from "react"
 import useSWR from "swr"
+import { apiUrl } from "@/lib/utils"
 
 import { AppSidebar } from "@/components/app-sidebar"
 import { SiteHeader } from "@/components/site-header"
@@ -12,7 +13,7 @@ import { AddMediaDialog } from "@/components/add-media-dialog"
 import { SidebarInset, SidebarProvider } from "@/components/ui/sidebar"
 import type { MediaItem } from "@/lib/types"
 
-const fetcher = (url: string) => fetch(url).then((r) => r.json())
+const fetcher = (url: string) => fetch(apiUrl(url)).then((r) => r.json())
 
 export default function Page() {
   const [activeView, setActiveView] = React.useState("dashboard")
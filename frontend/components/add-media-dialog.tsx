@@ -2,6 +2,7 @@
 
 import * as React from "react"
 import { mutate } from "swr"
+import { apiUrl } from "@/lib/utils"
 import { PlusIcon } from "lucide-react"
 
 import { Button } from "@/components/ui/button"
@@ -23,11 +24,11 @@ import {
   SelectTrigger,
   SelectValue,
 } from "@/components/ui/select"
-import type { MediaType, Status } from "@/lib/types"
+import type { ApiError, MediaItem, MediaType, Status } from "@/lib/types"
 import { mediaTypeLabel, statusLabel } from "@/lib/types"
 
 const mediaTypes: MediaType[] = [
-  "anime", "movie", "series", "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel",
+  "anime", "movie", "series", "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel", "audiobook",
 ]
 
 const statuses: Status[] = [
@@ -75,11 +76,19 @@ export function AddMediaDialog({ open, onOpenChange }: AddMediaDialogProps) {
         favorite: false,
       }
 
-      const res = await fetch("/api/items", {
-        method: "POST",
-        headers: { "Content-Type": "application/json" },
-        body: JSON.stringify(payload),
-      })
+      const post = (upsert: boolean) =>
+        fetch(apiUrl(upsert ? "/api/items?upsert=true" : "/api/items"), {
+          method: "POST",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify(payload),
+        })
+      let res = await post(false)
+      if (res.status === 409) {
+        // Already in the library: only overwrite it when asked to.
+        const { details: existing }: ApiError<MediaItem> = await res.json()
+        if (!window.confirm(`"${existing.title}" is already in your library. Replace it?`)) return
+        res = await post(true)
+      }
       if (res.ok) {
         mutate("/api/items")
         mutate("/api/stats")
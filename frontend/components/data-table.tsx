@@ -29,8 +29,10 @@ import {
   ClapperboardIcon,
   MonitorPlayIcon,
   BookOpenIcon,
+  BookmarkIcon,
+  XIcon,
 } from "lucide-react"
-import { mutate } from "swr"
+import useSWR, { mutate } from "swr"
 
 import { useIsMobile } from "@/hooks/use-mobile"
 import { Badge } from "@/components/ui/badge"
@@ -84,9 +86,9 @@ import {
   AlertDialogTitle,
   AlertDialogTrigger,
 } from "@/components/ui/alert-dialog"
-import type { MediaItem, Status, MediaType } from "@/lib/types"
-import { statusLabel, mediaTypeLabel } from "@/lib/types"
-import { cn } from "@/lib/utils"
+import type { ApiError, MediaItem, Status, MediaType, ViewPreset } from "@/lib/types"
+import { formatMinutes, isReadable, statusLabel, mediaTypeLabel } from "@/lib/types"
+import { apiUrl, cn } from "@/lib/utils"
 
 // --- Helpers ---
 
@@ -181,6 +183,14 @@ const columns: ColumnDef<MediaItem>[] = [
       return value.includes(row.getValue(id))
     },
   },
+  {
+    // Search for creator:"Name" to list everything by the same creator.
+    accessorKey: "creator",
+    header: "Creator",
+    cell: ({ row }) => (
+      <span className="text-xs text-muted-foreground">{row.original.creator ?? "—"}</span>
+    ),
+  },
   {
     accessorKey: "status",
     header: "Status",
@@ -201,10 +211,20 @@ const columns: ColumnDef<MediaItem>[] = [
     header: "Progress",
     cell: ({ row }) => {
       const item = row.original
-      const total = item.total_episodes
+      const volumes = item.prefer_volumes && item.volume_progress != null
+      const current = volumes ? item.volume_progress : item.progress
+      const total = volumes ? item.total_volumes : item.total_episodes
+      if (item.media_type === "audiobook") {
+        return (
+          <span className="text-xs tabular-nums text-muted-foreground">
+            {formatMinutes(item.progress)}{total ? ` / ${formatMinutes(total)}` : ""}
+          </span>
+        )
+      }
       return (
         <span className="text-xs tabular-nums text-muted-foreground">
-          {item.progress}{total ? ` / ${total}` : ""}
+          {volumes ? "Vol. " : ""}
+          {current}{total ? ` / ${total}` : ""}
         </span>
       )
     },
@@ -253,7 +273,7 @@ function RowActions({ item }: { item: MediaItem }) {
   async function handleDelete() {
     setDeleting(true)
     try {
-      const res = await fetch(`/api/items/${item.id}`, { method: "DELETE" })
+      const res = await fetch(apiUrl(`/api/items/${item.id}`), { method: "DELETE" })
       if (res.ok) {
         mutate("/api/items")
         mutate("/api/stats")
@@ -329,6 +349,7 @@ const allMediaTypes: MediaType[] = [
   "book",
   "light_novel",
   "web_novel",
+  "audiobook",
 ]
 
 function MediaItemEditor({
@@ -370,7 +391,7 @@ function MediaItemEditor({
   async function handleSave() {
     setSaving(true)
     try {
-      const res = await fetch(`/api/items/${item.id}`, {
+      const res = await fetch(apiUrl(`/api/items/${item.id}`), {
         method: "PUT",
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({
@@ -605,6 +626,18 @@ function MediaItemEditor({
 
 // --- Main DataTable ---
 
+const presetFetcher = (url: string) => fetch(apiUrl(url)).then((r) => r.json())
+
+const searchFetcher = async (url: string): Promise<MediaItem[]> => {
+  const res = await fetch(apiUrl(url))
+  if (!res.ok) throw new Error(((await res.json()) as ApiError).message)
+  return res.json()
+}
+
+// Filters like `status:watching` or `score>=8` are evaluated by the server;
+// plain words are matched against titles locally.
+const SEARCH_FILTER = /(^|\s)-?(status:|tag:|type:|creator:|score[:<>=])/
+
 export function DataTable({ data }: { data: MediaItem[] }) {
   const [sorting, setSorting] = React.useState<SortingState>([])
   const [columnFilters, setColumnFilters] = React.useState<ColumnFiltersState>(
@@ -616,23 +649,33 @@ export function DataTable({ data }: { data: MediaItem[] }) {
   const [statusFilter, setStatusFilter] = React.useState<string>("all")
   const [typeFilter, setTypeFilter] = React.useState<string>("all")
 
-  // Apply status and type filters
+  const serverSearch = SEARCH_FILTER.test(globalFilter)
+  const { data: searchMatches, error: searchError } = useSWR<MediaItem[]>(
+    serverSearch ? `/api/search?q=${encodeURIComponent(globalFilter)}` : null,
+    searchFetcher
+  )
+
+  // Apply the search, status and type filters
   const filteredData = React.useMemo(() => {
     let result = data
+    if (serverSearch) {
+      const ids = new Set((searchMatches ?? []).map((item) => item.id))
+      result = result.filter((item) => ids.has(item.id))
+    }
     if (statusFilter !== "all") {
       result = result.filter((item) => item.status === statusFilter)
     }
     if (typeFilter !== "all") {
       if (typeFilter === "readable") {
         result = result.filter((item) =>
-          ["manga", "manhwa", "webtoon", "book", "light_novel", "web_novel"].includes(item.media_type)
+          isReadable(item.media_type)
         )
       } else {
         result = result.filter((item) => item.media_type === typeFilter)
       }
     }
     return result
-  }, [data, statusFilter, typeFilter])
+  }, [data, serverSearch, searchMatches, statusFilter, typeFilter])
 
   const table = useReactTable({
     data: filteredData,
@@ -652,6 +695,7 @@ export function DataTable({ data }: { data: MediaItem[] }) {
     getPaginationRowModel: getPaginationRowModel(),
     getSortedRowModel: getSortedRowModel(),
     globalFilterFn: (row, _columnId, filterValue) => {
+      if (SEARCH_FILTER.test(filterValue)) return true
       return row.original.title
         .toLowerCase()
         .includes(filterValue.toLowerCase())
@@ -661,14 +705,64 @@ export function DataTable({ data }: { data: MediaItem[] }) {
     },
   })
 
+  // Saved views live on the server so they follow the user across devices.
+  const { data: presets = {} } = useSWR<Record<string, ViewPreset>>(
+    "/api/view-presets",
+    presetFetcher
+  )
+
+  function applyPreset(preset: ViewPreset) {
+    const visibility: VisibilityState = {}
+    if (preset.visible_fields.length > 0) {
+      for (const column of table.getAllColumns()) {
+        if (column.getCanHide()) {
+          visibility[column.id] = preset.visible_fields.includes(column.id)
+        }
+      }
+    }
+    setColumnVisibility(visibility)
+    setSorting(preset.sort ? [{ id: preset.sort.field, desc: preset.sort.descending }] : [])
+    setStatusFilter(preset.filters.status ?? "all")
+    setTypeFilter(preset.filters.type ?? "all")
+  }
+
+  async function saveCurrentView() {
+    const name = window.prompt("Save current view as:")?.trim()
+    if (!name) return
+    const filters: Record<string, string> = {}
+    if (statusFilter !== "all") filters.status = statusFilter
+    if (typeFilter !== "all") filters.type = typeFilter
+    const preset: ViewPreset = {
+      visible_fields: table.getVisibleLeafColumns().map((c) => c.id),
+      sort: sorting[0] ? { field: sorting[0].id, descending: sorting[0].desc } : null,
+      filters,
+      group_by: null,
+    }
+    const res = await fetch(apiUrl(`/api/view-presets/${encodeURIComponent(name)}`), {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(preset),
+    })
+    if (res.ok) mutate("/api/view-presets")
+  }
+
+  async function deletePreset(name: string) {
+    const res = await fetch(apiUrl(`/api/view-presets/${encodeURIComponent(name)}`), {
+      method: "DELETE",
+    })
+    if (res.ok) mutate("/api/view-presets")
+  }
+
   return (
     <div className="flex flex-col gap-4 px-4 lg:px-6">
       {/* Toolbar */}
       <div className="flex flex-wrap items-center gap-2">
         <Input
-          placeholder="Search library..."
+          placeholder="Search library... (status:watching score>=8)"
           value={globalFilter}
           onChange={(e) => setGlobalFilter(e.target.value)}
+          title={serverSearch && searchError ? searchError.message : undefined}
+          aria-invalid={serverSearch && !!searchError}
           className="h-8 w-full max-w-xs"
         />
 
@@ -703,10 +797,43 @@ export function DataTable({ data }: { data: MediaItem[] }) {
           </SelectContent>
         </Select>
 
-        {/* Column visibility */}
+        {/* Saved views */}
         <DropdownMenu>
           <DropdownMenuTrigger asChild>
             <Button variant="outline" size="sm" className="ml-auto h-8">
+              <BookmarkIcon />
+              <span className="hidden lg:inline">Views</span>
+              <ChevronDownIcon />
+            </Button>
+          </DropdownMenuTrigger>
+          <DropdownMenuContent align="end" className="w-52">
+            {Object.entries(presets).map(([name, preset]) => (
+              <DropdownMenuItem key={name} onSelect={() => applyPreset(preset)}>
+                <span className="flex-1 truncate">{name}</span>
+                <button
+                  type="button"
+                  className="text-muted-foreground hover:text-foreground"
+                  onClick={(e) => {
+                    e.stopPropagation()
+                    deletePreset(name)
+                  }}
+                >
+                  <XIcon className="size-3.5" />
+                  <span className="sr-only">Delete view {name}</span>
+                </button>
+              </DropdownMenuItem>
+            ))}
+            {Object.keys(presets).length > 0 && <DropdownMenuSeparator />}
+            <DropdownMenuItem onSelect={saveCurrentView}>
+              Save current view…
+            </DropdownMenuItem>
+          </DropdownMenuContent>
+        </DropdownMenu>
+
+        {/* Column visibility */}
+        <DropdownMenu>
+          <DropdownMenuTrigger asChild>
+            <Button variant="outline" size="sm" className="h-8">
               <ColumnsIcon />
               <span className="hidden lg:inline">Columns</span>
               <ChevronDownIcon />
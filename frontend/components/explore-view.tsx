@@ -12,6 +12,7 @@ import {
   Loader2,
   Check,
   MonitorPlayIcon,
+  KeyRound,
 } from "lucide-react"
 
 import { Input } from "@/components/ui/input"
@@ -24,11 +25,21 @@ import {
   SelectTrigger,
   SelectValue,
 } from "@/components/ui/select"
-import type { ExploreResult, ExploreSearchType, MediaItem } from "@/lib/types"
-import { mediaTypeLabel } from "@/lib/types"
-import { cn } from "@/lib/utils"
+import type {
+  ApiError,
+  ExploreResult,
+  ExploreSearchType,
+  MediaItem,
+  ProviderUnavailable,
+} from "@/lib/types"
+import { formatMinutes, mediaTypeLabel } from "@/lib/types"
+import { apiUrl, cn } from "@/lib/utils"
 
-const fetcher = (url: string) => fetch(url).then((r) => r.json())
+const fetcher = (url: string) => fetch(apiUrl(url)).then((r) => r.json())
+
+function isUnavailable(data: unknown): data is ApiError<ProviderUnavailable> {
+  return (data as ApiError | undefined)?.code === "provider_not_configured"
+}
 
 const searchTypes: { label: string; value: ExploreSearchType; icon: React.ReactNode }[] = [
   { label: "Anime", value: "anime", icon: <Sparkles className="h-3.5 w-3.5" /> },
@@ -50,9 +61,13 @@ export function ExploreView() {
       ? `/api/explore?q=${encodeURIComponent(submittedQuery)}&type=${searchType}`
       : null
 
-  const { data: results, isLoading } = useSWR<ExploreResult[]>(searchKey, fetcher, {
-    revalidateOnFocus: false,
-  })
+  const { data, isLoading } = useSWR<ExploreResult[] | ApiError<ProviderUnavailable>>(
+    searchKey,
+    fetcher,
+    { revalidateOnFocus: false },
+  )
+  const unavailable = isUnavailable(data) ? data.details : null
+  const results = isUnavailable(data) ? undefined : data
 
   function handleSearch(e: React.FormEvent) {
     e.preventDefault()
@@ -69,7 +84,7 @@ export function ExploreView() {
     setAddingIds((prev) => new Set(prev).add(key))
 
     const defaultStatus = [
-      "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel",
+      "manga", "manhwa", "webtoon", "book", "light_novel", "web_novel", "audiobook",
     ].includes(result.media_type)
       ? "plan_to_read"
       : "plan_to_watch"
@@ -86,17 +101,44 @@ export function ExploreView() {
       poster_url: result.poster_url,
       source: result.source,
       external_id: result.external_id,
+      alt_titles: result.alt_titles,
+      external_refs: result.external_refs,
+      creator: result.creator,
+      content_rating: result.content_rating,
       tags: [],
       favorite: false,
     }
 
     try {
-      const res = await fetch("/api/items", {
+      const res = await fetch(apiUrl("/api/items"), {
         method: "POST",
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify(payload),
       })
-      if (res.ok) {
+      if (res.status === 409) {
+        // Already in the library: offer to refresh the existing entry instead.
+        const { details: existing }: ApiError<MediaItem> = await res.json()
+        if (window.confirm(`"${existing.title}" is already in your library. Update it with these details?`)) {
+          const update = await fetch(apiUrl(`/api/items/${existing.id}`), {
+            method: "PUT",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify({
+              ...existing,
+              title: result.title,
+              global_score: result.global_score,
+              total_episodes: result.total_episodes ?? existing.total_episodes,
+              poster_url: result.poster_url ?? existing.poster_url,
+              alt_titles: result.alt_titles,
+              external_refs: result.external_refs,
+              creator: existing.creator ?? result.creator,
+            }),
+          })
+          if (update.ok) {
+            setAddedIds((prev) => new Set(prev).add(key))
+            mutate("/api/items")
+          }
+        }
+      } else if (res.ok) {
         setAddedIds((prev) => new Set(prev).add(key))
         mutate("/api/items")
         mutate("/api/stats")
@@ -156,7 +198,24 @@ export function ExploreView() {
         </div>
       )}
 
-      {!isLoading && !results && !submittedQuery && (
+      {!isLoading && unavailable && (
+        <div className="flex flex-col items-center justify-center py-16 text-center">
+          <KeyRound className="mb-3 h-10 w-10 text-muted-foreground/40" />
+          <p className="text-sm font-medium">
+            {unavailable.providers.map((p) => p.name).join(", ") || "No provider"} is not configured
+          </p>
+          {unavailable.providers.map((p) => (
+            <p key={p.name} className="mt-1 text-xs text-muted-foreground">
+              {p.reason}
+            </p>
+          ))}
+          <p className="mt-1 text-xs text-muted-foreground">
+            Add an API key in settings to enable {unavailable.media_type} search.
+          </p>
+        </div>
+      )}
+
+      {!isLoading && !results && !unavailable && !submittedQuery && (
         <div className="flex flex-col items-center justify-center py-16 text-center">
           <Sparkles className="mb-3 h-10 w-10 text-muted-foreground/40" />
           <p className="text-sm font-medium">Discover new media</p>
@@ -241,7 +300,11 @@ export function ExploreView() {
                     {result.total_episodes && (
                       <>
                         <span>·</span>
-                        <span>{result.total_episodes} ep</span>
+                        <span>
+                          {result.media_type === "audiobook"
+                            ? formatMinutes(result.total_episodes)
+                            : `${result.total_episodes} ep`}
+                        </span>
                       </>
                     )}
                   </div>
@@ -1,6 +1,7 @@
 "use client"
 
 import useSWR from "swr"
+import { apiUrl } from "@/lib/utils"
 import {
   BookOpenIcon,
   ClapperboardIcon,
@@ -21,7 +22,7 @@ import {
 } from "@/components/ui/card"
 import type { Stats } from "@/lib/types"
 
-const fetcher = (url: string) => fetch(url).then((r) => r.json())
+const fetcher = (url: string) => fetch(apiUrl(url)).then((r) => r.json())
 
 export function SectionCards() {
   const { data: stats } = useSWR<Stats>("/api/stats", fetcher, {
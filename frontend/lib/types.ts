@@ -8,6 +8,8 @@ export type MediaType =
   | "book"
   | "light_novel"
   | "web_novel"
+  /** Progress and total are minutes listened and the running time. */
+  | "audiobook"
 
 /** Broad category used for sidebar filters */
 export type MediaFilter = "all" | "movie" | "series" | "anime" | "readable"
@@ -35,6 +37,70 @@ export interface MediaItem {
   external_id: string | null
   tags: string[]
   favorite: boolean
+  repeat_count: number
+  notes?: string | null
+  notes_spoiler: boolean
+  /** Other known titles (romaji, native, English, synonyms). */
+  alt_titles: string[]
+  /** Cross-source ids such as "anilist:21" or "tmdb:tv:37854". */
+  external_refs: string[]
+  /** RFC 3339 start of a planned watch / reading session. */
+  scheduled_for?: string | null
+  overview?: string | null
+  genres?: string[]
+  /** Length of a movie, or of an episode of a series. */
+  runtime_minutes?: number | null
+  season_count?: number | null
+  /** Name of the franchise the item belongs to. */
+  franchise?: string | null
+  /** User-defined fields, e.g. `{ "owned on Blu-ray": "yes" }`. */
+  custom_fields?: Record<string, string>
+  /** Volumes read, for readables that track volumes next to chapters. */
+  volume_progress?: number | null
+  total_volumes?: number | null
+  /** Progress is shown in volumes; follows whichever one an update changes. */
+  prefer_volumes?: boolean
+  /** Author of a readable or studio of an anime. */
+  creator?: string | null
+  /** As the provider rates the title. */
+  content_rating?: ContentRating | null
+  /** Left out of lists and searches unless include_hidden=true is passed. */
+  hidden?: boolean
+  /** Newest chapter out in English, for manga from MangaDex; set by the server. */
+  latest_available?: number | null
+  /** Chapters out that have not been read yet, while reading. */
+  chapters_behind?: number | null
+  /** Planned, and downloaded by Sonarr or Radarr; set by the server. */
+  ready_to_watch?: boolean
+  /** RFC 3339; set by the server. */
+  created_at?: string
+  /** RFC 3339; set by the server. */
+  updated_at?: string
+}
+
+export interface Franchise {
+  name: string
+  items: number
+  completed: number
+  /** Mean completion of the entries, 0-1. */
+  progress: number
+  average_score?: number
+}
+
+export interface FranchiseDetail extends Franchise {
+  entries: MediaItem[]
+}
+
+/** An entry related to a library item, from GET /api/items/:id/related. */
+export interface Relation {
+  /** e.g. "sequel", "prequel", "adaptation". */
+  kind: string
+  source: string
+  external_id: string
+  title: string
+  format?: string
+  /** The matching library item; null when it is not in the library. */
+  item: MediaItem | null
 }
 
 export interface ExploreResult {
@@ -46,9 +112,224 @@ export interface ExploreResult {
   source: string
   total_episodes: number | null
   format_label: string
+  alt_titles: string[]
+  external_refs: string[]
+  creator?: string | null
+  content_rating?: ContentRating | null
+}
+
+export type ContentRating = "safe" | "suggestive" | "erotica" | "pornographic"
+
+/** Body of POST /api/items/from-external. */
+export interface FromExternal {
+  source: string
+  external_id: string
+  /** Explore search type; defaults to the provider's first type. */
+  type?: ExploreSearchType
+  /** Defaults to the type's status in `default_statuses`. */
+  status?: string
+}
+
+/** GET /api/explore/:source/:external_id */
+export interface ExplorePreview {
+  result: ExploreResult
+  overview: string | null
+  genres: string[]
+  runtime_minutes: number | null
+  season_count: number | null
+  episode_count: number | null
+  staff: Credit[]
+  /** The entry as it is in the library; null when it is not. */
+  item: MediaItem | null
+}
+
+export interface Credit {
+  name: string
+  role: string
 }
 
-export type ExploreSearchType = "anime" | "movie" | "series" | "manga" | "book" | "light_novel"
+/** One entry of GET /api/explore/suggest. */
+export interface Suggestion {
+  title: string
+  media_type: string
+  year: number | null
+  /** A small version of the poster. */
+  poster_url: string | null
+  source: string
+  external_id: string | null
+}
+
+/** One entry of the POST /api/explore/details response. */
+export interface ExploreDetail {
+  source: string
+  external_id: string
+  /** null when the provider does not know the id. */
+  result: ExploreResult | null
+  error?: string
+}
+
+export interface ProviderStatus {
+  name: string
+  media_types: ExploreSearchType[]
+  available: boolean
+  reason: string | null
+}
+
+/** `details` of the `provider_not_configured` 503 of /api/explore. */
+export interface ProviderUnavailable {
+  media_type: ExploreSearchType
+  providers: ProviderStatus[]
+}
+
+/** One entry of the X-Kars-Provider-Errors header of /api/explore. */
+export interface ProviderError {
+  provider: string
+  error: string
+}
+
+/** Body of every failed API request. */
+export interface ApiError<D = unknown> {
+  /** Machine-readable, e.g. `not_found` or `provider_not_configured`. */
+  code: string
+  message: string
+  /** The conflicting item of a 409, for instance; null when there is nothing more. */
+  details: D
+}
+
+/** RFC 7807 body of a 400 for an invalid item. */
+export interface Problem extends ApiError<FieldError[]> {
+  type: string
+  title: string
+  status: number
+  detail: string
+  code: "invalid_request"
+}
+
+export interface FieldError {
+  field: string
+  message: string
+}
+
+export interface Settings {
+  /** Provider names per search type, tried in order until one returns results. */
+  provider_chains: Partial<Record<ExploreSearchType, string[]>>
+  /** How long before a scheduled session its reminder is sent. */
+  reminder_lead_minutes: number
+  /** Hours between automatic backups; 0 turns them off. */
+  backup_interval_hours: number
+  /** Number of backups kept. */
+  backup_keep: number
+  view_presets: Record<string, ViewPreset>
+  /** How many items can be pinned to the focus list. */
+  focus_limit: number
+  /** Opt-in daily anonymous usage report. */
+  telemetry_enabled: boolean
+  /** Scale of item scores in the API. */
+  score_scale: ScoreScale
+  /** Language anime and manga search results are titled in. */
+  title_language: TitleLanguage
+  /** Hours between Trakt history pulls; 0 only imports when asked. */
+  trakt_sync_hours: number
+  /** Send progress, score and status changes of AniList items to the linked account. */
+  anilist_write_through: boolean
+  /** Minutes between reading progress pulls from Kavita and Komga; 0 only syncs when asked. */
+  reading_sync_minutes: number
+  /** Keep copies of posters, served from /api/posters/:id/:size. */
+  local_posters: boolean
+  /** Leave titles rated erotica or pornographic out of explore and library lists. */
+  hide_adult: boolean
+  /** Interval overrides of background jobs by name, in seconds (at least 5). */
+  job_interval_seconds: Record<string, number>
+  /** Where notices of library events are pushed. */
+  notifiers: NotifierConfig[]
+  /** Status of items added without one, by media type; unlisted types are planned. */
+  default_statuses: Record<string, string>
+  /** Color scheme hint for the UI. */
+  theme: Theme
+  /** Take part in the score comparison between profiles. */
+  share_scores: boolean
+}
+
+export type NoticeEvent = "item_completed" | "episode_aired" | "backup_failed"
+
+/** One entry of the notifiers setting; no events means all of them. */
+export type NotifierConfig = { events?: NoticeEvent[] } & (
+  | { kind: "webhook"; url: string }
+  | { kind: "ntfy"; server?: string; topic: string; token?: string | null }
+  | {
+      kind: "email"
+      host: string
+      port?: number | null
+      security?: "starttls" | "tls" | "none"
+      username?: string | null
+      password?: string | null
+      from: string
+      to: string
+    }
+  | { kind: "telegram"; bot_token: string; chat_id: string }
+)
+
+/** From POST /api/notifiers/test. */
+export interface NotifierTest {
+  kind: NotifierConfig["kind"]
+  sent: boolean
+  error?: string
+}
+
+export type ScoreScale = "ten" | "hundred" | "five_stars"
+
+export type TitleLanguage = "english" | "romaji" | "native"
+
+export type Theme = "system" | "light" | "dark"
+
+/** A saved library list configuration, stored server-side. */
+export interface ViewPreset {
+  /** Column ids to show; empty shows the default columns. */
+  visible_fields: string[]
+  sort: { field: string; descending: boolean } | null
+  /** Filter values by field, e.g. { status: "watching", type: "anime" }. */
+  filters: Record<string, string>
+  group_by: string | null
+}
+
+export interface Backup {
+  name: string
+  size_bytes: number
+  created_at: string
+  uploaded_to?: string
+  upload_error?: string
+}
+
+/** Response of POST /api/import?dry_run=true; nothing was written. */
+export interface ImportPreview {
+  created: number
+  updated: number
+  skipped: number
+  /** Only a replace import deletes items. */
+  deleted: number
+  items: ImportPreviewItem[]
+}
+
+export interface ImportPreviewItem {
+  item_id: string
+  title: string
+  action: "create" | "update" | "skip" | "delete"
+  reason: string
+  /** Fields an update changes. */
+  fields?: string[]
+}
+
+/** Response of POST /api/import?mode=replace. */
+export interface Restore {
+  items: number
+  episodes: number
+  progress_events: number
+  history_entries: number
+  /** Snapshot taken just before the library was replaced. */
+  backup: Backup
+}
+
+export type ExploreSearchType = "anime" | "movie" | "series" | "manga" | "book" | "light_novel" | "audiobook"
 
 export interface Stats {
   total: number
@@ -61,11 +342,350 @@ export interface Stats {
   series: number
   anime: number
   readable: number
+  rewatching: number
+  total_rewatches: number
+  /** Manga being read with new chapters out, and how many chapters in all. */
+  behind_on_chapters?: number
+  chapters_behind?: number
+}
+
+export interface Episode {
+  episode: number
+  watched: boolean
+  watched_at?: string
+}
+
+export interface HistoryEntry {
+  id: number
+  item_id: string
+  title: string
+  event: "added" | "status_changed" | "progress_updated" | "score_set" | "deleted" | "merged" | "pass_completed"
+  /** For `pass_completed`, the score the finished pass ended with. */
+  old_value?: string
+  /** For `pass_completed`, when the pass was completed. */
+  new_value?: string
+  recorded_at: string
+  /** The import or other job that made the change. */
+  job_id?: number
+}
+
+/** One progress update of an item, from `/api/items/:id/progress-history`. */
+export interface ProgressPoint {
+  progress: number
+  /** Change from the previous value; negative when progress went back. */
+  delta: number
+  recorded_at: string
+}
+
+export interface ScheduledSession {
+  item: MediaItem
+  scheduled_for: string
+  starts_in_minutes: number
+}
+
+export interface Airing {
+  item: MediaItem
+  episode: number
+  airing_at: string
+  airs_in_minutes: number
+  /** Aired episodes not watched yet. */
+  behind_by: number
+}
+
+/** GET /api/schedule */
+export interface Schedule {
+  sessions: ScheduledSession[]
+  airing: Airing[]
+}
+
+export interface Notification {
+  id: number
+  item_id?: string
+  kind: "session_reminder"
+  message: string
+  created_at: string
+  read: boolean
+}
+
+export type FieldChange =
+  | { field: "total"; old: number | null; new: number }
+  | { field: "global_score"; old: number | null; new: number }
+  | { field: "poster_url"; old: string | null; new: string }
+  | { field: "creator"; old: string | null; new: string }
+  | { field: "content_rating"; old: ContentRating | null; new: ContentRating }
+  | { field: "overview"; old: string | null; new: string }
+  | { field: "link"; source: string; external_id: string }
+
+export interface ItemChanges {
+  item_id: string
+  title: string
+  /** Set when an import added the item rather than merging into it. */
+  created?: boolean
+  changes: FieldChange[]
+  error?: string
+}
+
+/** POST /api/items/:id/match without `pick` */
+export interface MatchCandidates {
+  media_type: string
+  candidates: ExploreResult[]
+  /** Index of the one candidate with exactly the item's title. */
+  suggested: number | null
+}
+
+export interface Job {
+  id: number
+  kind: "metadata_refresh" | "import" | "match"
+  /** A finished dry run stays in "preview" until it is applied. */
+  status: "running" | "preview" | "completed" | "failed" | "rolled_back"
+  dry_run: boolean
+  created_at: string
+  finished_at?: string
+  error?: string
+  items: ItemChanges[]
+}
+
+/** A background job of the scheduler, from /api/admin/jobs. */
+export interface ScheduledJob {
+  name: string
+  description: string
+  interval_seconds: number
+  running: boolean
+  /** Runs and failed runs since startup. */
+  runs: number
+  failures: number
+  last_started_at?: string
+  last_finished_at?: string
+  /** "idle" when the last run had nothing to do. */
+  last_status?: "ok" | "idle" | "failed"
+  last_message?: string
+  next_run_at: string
+}
+
+export interface Engagement {
+  item: MediaItem
+  velocity: number
+  last_activity: string
+}
+
+/** GET /api/telemetry/preview */
+export interface TelemetryPreview {
+  enabled: boolean
+  endpoint: string | null
+  payload: {
+    schema: number
+    version: string
+    /** Count ranges by type, e.g. "10-49". */
+    items: Record<string, string>
+    features: Record<string, boolean>
+  }
+}
+
+export interface Webhook {
+  id: number
+  url: string
+  /** Key of the X-Kars-Signature HMAC. */
+  secret: string
+  created_at: string
+}
+
+/** From GET /api/trakt. */
+export interface TraktStatus {
+  /** Whether TRAKT_CLIENT_ID and TRAKT_CLIENT_SECRET are set. */
+  configured: boolean
+  connected: boolean
+  connected_at: string | null
+  last_synced_at: string | null
+  sync_hours: number
+}
+
+/** From POST /api/trakt/connect: the code to enter at verification_url. */
+export interface TraktConnect {
+  user_code: string
+  verification_url: string
+  expires_in: number
+}
+
+/** From GET /api/anilist. */
+export interface AniListStatus {
+  linked: boolean
+  user_name: string | null
+  connected_at: string | null
+  /** The anilist_write_through setting. */
+  write_through: boolean
+  /** Updates not sent yet, oldest first. */
+  queue: QueuedUpdate[]
+}
+
+export interface QueuedUpdate {
+  item_id: string
+  queued_at: string
+  /** Failed tries so far; dropped after a few. */
+  attempts: number
+  last_error: string | null
+}
+
+/** From GET /api/reading-servers. */
+export interface ReadingServers {
+  /** Kavita and Komga, when configured. */
+  servers: string[]
+  /** The reading_sync_minutes setting. */
+  sync_minutes: number
+}
+
+/** From POST /api/reading-servers/sync. */
+export interface ReadingSync {
+  /** The import job holding the changes; null when nothing changed. */
+  job: Job | null
+  /** Series that match no readable, or several. */
+  unmatched: string[]
+  /** Servers that could not be read. */
+  errors: ProviderError[]
+}
+
+/** An item write queued while Turso is unreachable; the body of a 202. */
+export interface QueuedWrite {
+  id: number
+  item_id: string
+  op: "create" | "upsert" | "update" | "delete"
+  /** Null for deletes. */
+  title: string | null
+  queued_at: string
+  /** Why the last replay held the write back. */
+  conflict: string | null
+}
+
+/** From POST /api/outbox/replay. */
+export interface OutboxReplay {
+  applied: number
+  /** Held back; they stay queued. */
+  conflicts: QueuedWrite[]
+  /** Still queued, conflicts included. */
+  pending: number
+}
+
+/** Everything the dashboard shows, from GET /api/dashboard. */
+export interface Dashboard {
+  generated_at: string
+  stats: Stats
+  upcoming: ScheduledSession[]
+  continue_watching: Engagement[]
+  notifications: Notification[]
+  jobs: Job[]
+  /** Followed series with an episode out before midnight; null when not looked up. */
+  airing_today: number | null
+  /** One-line overview, also printed when the interactive CLI starts. */
+  summary: string
+}
+
+export interface ExtendedStats {
+  /** Scored items per whole point; index 10 counts only perfect scores. */
+  score_histogram: number[]
+  scored: number
+  mean_score: number | null
+  median_score: number | null
+  /** Most used first. */
+  tags: { tag: string; count: number }[]
+  readable_kinds: Partial<Record<MediaType, number>>
+  /** Counts finished rewatches in full. */
+  episodes_watched: number
+  chapters_read: number
+  movies_watched: number
+  time_spent: TimeSpent
+  time_by_media_type: Partial<Record<MediaType, TimeSpent>>
+  /** By year, e.g. "2025"; only progress logged in that year. */
+  time_by_year: Record<string, TimeSpent>
+  /** Watched movies and series without a runtime, missing from the hours. */
+  without_runtime: number
+}
+
+export interface TimeSpent {
+  hours_watched: number
+  pages_read: number
+  hours_listened: number
+}
+
+export interface FocusEntry {
+  item: MediaItem
+  pinned_at: string
+  /** Share of the item that is done, 0-1. */
+  completion: number
+}
+
+export interface RankEntry {
+  item: MediaItem
+  /** Elo rating, 1500 before the first comparison. */
+  rating: number
+  comparisons: number
+}
+
+export interface RankPair {
+  left: RankEntry
+  right: RankEntry
+}
+
+export interface LeaderboardEntry extends RankEntry {
+  /** 1 for the best rated item. */
+  rank: number
+}
+
+export interface Goal {
+  id: number
+  year: number
+  /** What counts, e.g. "book"; missing for every completion. */
+  media_type?: MediaType
+  target: number
+  completed: number
+  /** At least as far as an even pace through the year would be today. */
+  on_track: boolean
+}
+
+export interface Goals {
+  goals: Goal[]
+  streak: {
+    current_days: number
+    longest_days: number
+    /** YYYY-MM-DD of the latest activity. */
+    last_active?: string
+  }
+}
+
+export interface FocusStats {
+  pinned: number
+  limit: number
+  /** Finished pins that can make room for the next item. */
+  completed: number
+  /** Mean completion of the pinned items, 0-1. */
+  progress: number
+}
+
+/** Why an item was recommended. */
+export type Signal =
+  | { signal: "shared_tags"; tags: string[] }
+  | { signal: "because_you_rated"; item_id: string; title: string; score: number | null }
+  | { signal: "acclaimed"; global_score: number }
+
+export interface Recommendation {
+  item: MediaItem
+  score: number
+  signals: Signal[]
+}
+
+export interface Discovery {
+  result: ExploreResult
+  score: number
+  signals: Signal[]
 }
 
 /** Check if a media type falls under the "readable" group */
 export function isReadable(type: MediaType): boolean {
-  return ["manga", "manhwa", "webtoon", "book", "light_novel", "web_novel"].includes(type)
+  return ["manga", "manhwa", "webtoon", "book", "light_novel", "web_novel", "audiobook"].includes(type)
+}
+
+/** Minutes as "12h 05m", or "42m" under an hour; audiobook progress */
+export function formatMinutes(minutes: number): string {
+  if (minutes < 60) return `${minutes}m`
+  return `${Math.floor(minutes / 60)}h ${String(minutes % 60).padStart(2, "0")}m`
 }
 
 /** Check if a status is a "reading" variant */
@@ -85,6 +705,7 @@ export function mediaTypeLabel(type: MediaType | string): string {
     case "book": return "Book"
     case "light_novel": return "Light Novel"
     case "web_novel": return "Web Novel"
+    case "audiobook": return "Audiobook"
     default: return type
   }
 }
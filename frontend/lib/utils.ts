@@ -5,10 +5,25 @@ export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
 }
 
-const API_BASE = ''
+declare global {
+  interface Window {
+    /** `BASE_PATH` of the server, injected into the embedded index.html. */
+    __KARS_BASE_PATH__?: string
+  }
+}
+
+/** `path` (e.g. `/api/items`) below the prefix kars is served under. */
+export function apiUrl(path: string): string {
+  const base = typeof window === 'undefined' ? '' : window.__KARS_BASE_PATH__ ?? ''
+  return `${base}${path}`
+}
 
 export async function apiFetch<T>(path: string, init?: RequestInit): Promise<T> {
-  const res = await fetch(`${API_BASE}${path}`, init)
-  if (!res.ok) throw new Error(`API error: ${res.status}`)
+  const res = await fetch(apiUrl(path), init)
+  if (!res.ok) {
+    // The id finds the request in the server's access log.
+    const id = res.headers.get('X-Request-Id')
+    throw new Error(`API error: ${res.status}${id ? ` (request ${id})` : ''}`)
+  }
   return res.json()
 }